@@ -0,0 +1,663 @@
+use crate::assigned_numbers::{
+    class_of_device_to_string, get_appearance_name, get_company_name, get_uuid128_name,
+    get_uuid16_name, get_uuid32_name,
+};
+use crate::bytes_to_string;
+use crate::HostStack;
+use crate::ParseNode;
+use crate::ParseNodeA;
+use crate::ParseNodeOpt;
+
+use crate::ParseBitsNode;
+use crate::ParseBytesNode;
+
+mod decoders;
+
+/// Decodes the payload of manufacturer specific data or service data. Gets the
+/// bytes following the company identifier or UUID and their start byte, and
+/// returns the JSON of the decoded fields, or an empty string when the payload
+/// is not recognised.
+pub type AdPayloadDecoder = fn(&[u8], u16) -> String;
+
+/// What an [`AdPayloadDecoder`] is registered for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AdDecoderKey {
+    CompanyId(u16),
+    ServiceUuid16(u16),
+    ServiceUuid32(u32),
+    ServiceUuid128(u128),
+}
+
+#[derive(Debug, Default)]
+pub struct AdArg {
+    decoders: Vec<(AdDecoderKey, AdPayloadDecoder)>,
+}
+
+impl AdArg {
+    pub(crate) fn register(&mut self, key: AdDecoderKey, decoder: AdPayloadDecoder) {
+        self.decoders.push((key, decoder));
+    }
+
+    /// The latest decoder registered for `key`, falling back to the built-in ones.
+    fn find(&self, key: AdDecoderKey) -> Option<AdDecoder> {
+        self.decoders
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, (k, _))| *k == key)
+            .map(|(index, (_, decode))| AdDecoder {
+                id: AdDecoderId::Registered(index),
+                decode: *decode,
+            })
+            .or_else(|| AdDecoder::builtin(key))
+    }
+}
+
+/// Where a decoder found for a payload comes from: the built-in one for a
+/// key, or the registration with that index.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AdDecoderId {
+    Builtin(AdDecoderKey),
+    Registered(usize),
+}
+
+/// A decoder found for a payload. Decoders are told apart by their id, never
+/// by comparing function pointers.
+#[derive(Debug, Clone, Copy)]
+struct AdDecoder {
+    id: AdDecoderId,
+    decode: AdPayloadDecoder,
+}
+
+impl PartialEq for AdDecoder {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl AdDecoder {
+    fn builtin(key: AdDecoderKey) -> Option<Self> {
+        decoders::builtin_decoder(key).map(|decode| AdDecoder {
+            id: AdDecoderId::Builtin(key),
+            decode,
+        })
+    }
+}
+
+fn find_decoder(args: Option<&mut HostStack>, key: AdDecoderKey) -> Option<AdDecoder> {
+    match args {
+        Some(args) => args.ad_arg.find(key),
+        None => AdDecoder::builtin(key),
+    }
+}
+
+fn decoded_as_json(decoder: &Option<AdDecoder>, data: &[u8], start_byte: u16) -> Option<String> {
+    decoder
+        .map(|decoder| (decoder.decode)(data, start_byte))
+        .filter(|decoded| !decoded.is_empty())
+}
+
+/// A run of AD structures, as found in advertising data, scan response data
+/// and the Extended Inquiry Response.
+#[derive(Debug, PartialEq)]
+pub struct AdvertisingData {
+    structures: Vec<AdStructure>,
+}
+
+impl ParseNode for AdvertisingData {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Self {
+        let mut args = args;
+        let mut structures = Vec::new();
+        let mut data = data;
+        // a zero length ends the significant part, the rest is padding
+        while let Some(structure) = AdStructure::new(data, args.as_deref_mut()) {
+            data = &data[structure.len()..];
+            structures.push(structure);
+        }
+        AdvertisingData { structures }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut offset = start_byte;
+        self.structures
+            .iter()
+            .enumerate()
+            .map(|(i, structure)| {
+                let json = format!(
+                    r#""AD Structure[{}]": {{{}}}"#,
+                    i,
+                    structure.as_json(offset)
+                );
+                offset += structure.len() as u16;
+                json
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct AdStructure {
+    length: u8,
+    ad_type: u8,
+    data: AdData,
+}
+
+impl AdStructure {
+    fn len(&self) -> usize {
+        self.length as usize + 1
+    }
+}
+
+impl ParseNodeOpt for AdStructure {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 2 || data[0] == 0 || data.len() < data[0] as usize + 1 {
+            None
+        } else {
+            let length = data[0];
+            let ad_type = data[1];
+            Some(AdStructure {
+                length,
+                ad_type,
+                data: AdData::new(&data[2..length as usize + 1], args, ad_type),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let length_s = ParseBytesNode::new(start_byte, 1).format("Length", self.length, "", "");
+        let ad_type_s = ParseBytesNode::new(start_byte + 1, 1).format(
+            "AD Type",
+            self.ad_type,
+            get_ad_type_name(self.ad_type),
+            "",
+        );
+        let data_s = self.data.as_json(start_byte + 2);
+        if data_s.is_empty() {
+            format!("{}, {}", length_s, ad_type_s)
+        } else {
+            format!("{}, {}, {}", length_s, ad_type_s, data_s)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum AdData {
+    Undefined(Vec<u8>),
+    Flags(u8),
+    // the UUIDs, and the bytes left over that do not make a whole one
+    Uuid16List(Vec<u16>, Vec<u8>),
+    Uuid32List(Vec<u32>, Vec<u8>),
+    Uuid128List(Vec<u128>, Vec<u8>),
+    LocalName(Vec<u8>),
+    TxPowerLevel(i8),
+    ClassOfDevice(u32),
+    ServiceData16(u16, Vec<u8>, Option<AdDecoder>),
+    ServiceData32(u32, Vec<u8>, Option<AdDecoder>),
+    ServiceData128(u128, Vec<u8>, Option<AdDecoder>),
+    Appearance(u16),
+    LeRole(u8),
+    Uri(u8, Vec<u8>),
+    ManufacturerSpecificData(u16, Vec<u8>, Option<AdDecoder>),
+}
+
+impl ParseNodeA<u8> for AdData {
+    fn new(data: &[u8], args: Option<&mut HostStack>, ad_type: u8) -> Self {
+        match (ad_type, data.len()) {
+            (0x01, 1..) => AdData::Flags(data[0]),
+            (0x02 | 0x03 | 0x14, _) => {
+                let uuids = data.chunks_exact(2);
+                let remainder = uuids.remainder().to_vec();
+                AdData::Uuid16List(
+                    uuids
+                        .map(|uuid| u16::from_le_bytes(uuid.try_into().unwrap()))
+                        .collect(),
+                    remainder,
+                )
+            }
+            (0x04 | 0x05 | 0x1f, _) => {
+                let uuids = data.chunks_exact(4);
+                let remainder = uuids.remainder().to_vec();
+                AdData::Uuid32List(
+                    uuids
+                        .map(|uuid| u32::from_le_bytes(uuid.try_into().unwrap()))
+                        .collect(),
+                    remainder,
+                )
+            }
+            (0x06 | 0x07 | 0x15, _) => {
+                let uuids = data.chunks_exact(16);
+                let remainder = uuids.remainder().to_vec();
+                AdData::Uuid128List(
+                    uuids
+                        .map(|uuid| u128::from_le_bytes(uuid.try_into().unwrap()))
+                        .collect(),
+                    remainder,
+                )
+            }
+            (0x08 | 0x09, _) => AdData::LocalName(data.to_vec()),
+            (0x0a, 1..) => AdData::TxPowerLevel(data[0] as i8),
+            (0x0d, 3..) => AdData::ClassOfDevice(
+                data[0] as u32 | (data[1] as u32) << 8 | (data[2] as u32) << 16,
+            ),
+            (0x16, 2..) => {
+                let uuid = u16::from_le_bytes([data[0], data[1]]);
+                AdData::ServiceData16(
+                    uuid,
+                    data[2..].to_vec(),
+                    find_decoder(args, AdDecoderKey::ServiceUuid16(uuid)),
+                )
+            }
+            (0x19, 2..) => AdData::Appearance(u16::from_le_bytes([data[0], data[1]])),
+            (0x1c, 1..) => AdData::LeRole(data[0]),
+            (0x20, 4..) => {
+                let uuid = u32::from_le_bytes(data[0..4].try_into().unwrap());
+                AdData::ServiceData32(
+                    uuid,
+                    data[4..].to_vec(),
+                    find_decoder(args, AdDecoderKey::ServiceUuid32(uuid)),
+                )
+            }
+            (0x21, 16..) => {
+                let uuid = u128::from_le_bytes(data[0..16].try_into().unwrap());
+                AdData::ServiceData128(
+                    uuid,
+                    data[16..].to_vec(),
+                    find_decoder(args, AdDecoderKey::ServiceUuid128(uuid)),
+                )
+            }
+            (0x24, 1..) => AdData::Uri(data[0], data[1..].to_vec()),
+            (0xff, 2..) => {
+                let company_id = u16::from_le_bytes([data[0], data[1]]);
+                AdData::ManufacturerSpecificData(
+                    company_id,
+                    data[2..].to_vec(),
+                    find_decoder(args, AdDecoderKey::CompanyId(company_id)),
+                )
+            }
+            _ => AdData::Undefined(data.to_vec()),
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        match self {
+            AdData::Undefined(data) => {
+                ParseBytesNode::new(start_byte, data.len() as u16).format("Data", &data[..], "", "")
+            }
+            AdData::Flags(flags) => {
+                let bits = [
+                    "LE Limited Discoverable Mode",
+                    "LE General Discoverable Mode",
+                    "BR/EDR Not Supported",
+                    "Simultaneous LE and BR/EDR to Same Device Capable (Controller)",
+                    "Previously Used",
+                ];
+                let bits_s = bits
+                    .iter()
+                    .enumerate()
+                    .map(|(bit, name)| {
+                        ParseBitsNode::new(start_byte, 1, bit as u8, 1).format(
+                            name,
+                            (flags >> bit) & 0x01,
+                            "",
+                            "",
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                format!(r#""Flags": {{{}}}"#, bits_s)
+            }
+            AdData::Uuid16List(uuids, remainder) => uuids
+                .iter()
+                .enumerate()
+                .map(|(i, uuid)| {
+                    ParseBytesNode::new(start_byte + i as u16 * 2, 2).format(
+                        &format!("UUID[{}]", i),
+                        uuid,
+                        get_uuid16_name(*uuid),
+                        "",
+                    )
+                })
+                .chain(remainder_as_json(
+                    remainder,
+                    start_byte + uuids.len() as u16 * 2,
+                ))
+                .collect::<Vec<String>>()
+                .join(", "),
+            AdData::Uuid32List(uuids, remainder) => uuids
+                .iter()
+                .enumerate()
+                .map(|(i, uuid)| {
+                    ParseBytesNode::new(start_byte + i as u16 * 4, 4).format(
+                        &format!("UUID[{}]", i),
+                        uuid,
+                        get_uuid32_name(*uuid),
+                        "",
+                    )
+                })
+                .chain(remainder_as_json(
+                    remainder,
+                    start_byte + uuids.len() as u16 * 4,
+                ))
+                .collect::<Vec<String>>()
+                .join(", "),
+            AdData::Uuid128List(uuids, remainder) => uuids
+                .iter()
+                .enumerate()
+                .map(|(i, uuid)| {
+                    ParseBytesNode::new(start_byte + i as u16 * 16, 16).format(
+                        &format!("UUID[{}]", i),
+                        uuid128_to_string(*uuid),
+                        get_uuid128_name(*uuid),
+                        "",
+                    )
+                })
+                .chain(remainder_as_json(
+                    remainder,
+                    start_byte + uuids.len() as u16 * 16,
+                ))
+                .collect::<Vec<String>>()
+                .join(", "),
+            AdData::LocalName(name) => ParseBytesNode::new(start_byte, name.len() as u16).format(
+                "Local Name",
+                bytes_to_string(name).as_str(),
+                "",
+                "",
+            ),
+            AdData::TxPowerLevel(tx_power) => ParseBytesNode::new(start_byte, 1).format(
+                "TX Power Level",
+                *tx_power as u8,
+                &format!("{} dBm", tx_power),
+                "",
+            ),
+            AdData::ClassOfDevice(class_of_device) => ParseBytesNode::new(start_byte, 3).format(
+                "Class of Device",
+                class_of_device,
+                &class_of_device_to_string(*class_of_device),
+                "",
+            ),
+            AdData::ServiceData16(uuid, data, decoder) => {
+                let uuid_s = ParseBytesNode::new(start_byte, 2).format(
+                    "UUID",
+                    uuid,
+                    get_uuid16_name(*uuid),
+                    "",
+                );
+                let data_s = ParseBytesNode::new(start_byte + 2, data.len() as u16).format(
+                    "Service Data",
+                    &data[..],
+                    "",
+                    "",
+                );
+                match decoded_as_json(decoder, data, start_byte + 2) {
+                    Some(decoded_s) => format!("{}, {}, {}", uuid_s, data_s, decoded_s),
+                    None => format!("{}, {}", uuid_s, data_s),
+                }
+            }
+            AdData::ServiceData32(uuid, data, decoder) => {
+                let uuid_s = ParseBytesNode::new(start_byte, 4).format(
+                    "UUID",
+                    uuid,
+                    get_uuid32_name(*uuid),
+                    "",
+                );
+                let data_s = ParseBytesNode::new(start_byte + 4, data.len() as u16).format(
+                    "Service Data",
+                    &data[..],
+                    "",
+                    "",
+                );
+                match decoded_as_json(decoder, data, start_byte + 4) {
+                    Some(decoded_s) => format!("{}, {}, {}", uuid_s, data_s, decoded_s),
+                    None => format!("{}, {}", uuid_s, data_s),
+                }
+            }
+            AdData::ServiceData128(uuid, data, decoder) => {
+                let uuid_s = ParseBytesNode::new(start_byte, 16).format(
+                    "UUID",
+                    uuid128_to_string(*uuid),
+                    get_uuid128_name(*uuid),
+                    "",
+                );
+                let data_s = ParseBytesNode::new(start_byte + 16, data.len() as u16).format(
+                    "Service Data",
+                    &data[..],
+                    "",
+                    "",
+                );
+                match decoded_as_json(decoder, data, start_byte + 16) {
+                    Some(decoded_s) => format!("{}, {}, {}", uuid_s, data_s, decoded_s),
+                    None => format!("{}, {}", uuid_s, data_s),
+                }
+            }
+            AdData::Appearance(appearance) => ParseBytesNode::new(start_byte, 2).format(
+                "Appearance",
+                appearance,
+                get_appearance_name(*appearance),
+                "",
+            ),
+            AdData::LeRole(role) => {
+                let role_name_s = match role {
+                    0x00 => "Only Peripheral Role supported",
+                    0x01 => "Only Central Role supported",
+                    0x02 => "Peripheral and Central Role supported, Peripheral Role preferred",
+                    0x03 => "Peripheral and Central Role supported, Central Role preferred",
+                    _ => "Reserved for future use",
+                };
+                ParseBytesNode::new(start_byte, 1).format("LE Role", role, role_name_s, "")
+            }
+            AdData::Uri(scheme, raw_uri) => {
+                let scheme_s = ParseBytesNode::new(start_byte, 1).format(
+                    "URI Scheme",
+                    scheme,
+                    get_uri_scheme_name(*scheme),
+                    "",
+                );
+                let uri = bytes_to_string(raw_uri);
+                let uri_s = ParseBytesNode::new(start_byte + 1, raw_uri.len() as u16).format(
+                    "URI",
+                    uri.as_str(),
+                    &format!("{}{}", get_uri_scheme_name(*scheme), uri),
+                    "",
+                );
+                format!("{}, {}", scheme_s, uri_s)
+            }
+            AdData::ManufacturerSpecificData(company_id, data, decoder) => {
+                let company_id_s = ParseBytesNode::new(start_byte, 2).format(
+                    "Company Identifier",
+                    company_id,
+                    get_company_name(*company_id),
+                    "",
+                );
+                let data_s = ParseBytesNode::new(start_byte + 2, data.len() as u16).format(
+                    "Data",
+                    &data[..],
+                    "",
+                    "",
+                );
+                match decoded_as_json(decoder, data, start_byte + 2) {
+                    Some(decoded_s) => format!("{}, {}, {}", company_id_s, data_s, decoded_s),
+                    None => format!("{}, {}", company_id_s, data_s),
+                }
+            }
+        }
+    }
+}
+
+/// The bytes at the end of a UUID list too short to hold another UUID.
+fn remainder_as_json(remainder: &[u8], start_byte: u16) -> Option<String> {
+    (!remainder.is_empty()).then(|| {
+        ParseBytesNode::new(start_byte, remainder.len() as u16).format(
+            "Data",
+            remainder,
+            "",
+            "Not a whole UUID",
+        )
+    })
+}
+
+/// Formats a 128-bit UUID read little endian from the air in its usual
+/// big endian text form.
+pub fn uuid128_to_string(uuid: u128) -> String {
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        uuid >> 96,
+        (uuid >> 80) & 0xffff,
+        (uuid >> 64) & 0xffff,
+        (uuid >> 48) & 0xffff,
+        uuid & 0xffff_ffff_ffff
+    )
+}
+
+fn get_ad_type_name(ad_type: u8) -> &'static str {
+    match ad_type {
+        0x01 => "Flags",
+        0x02 => "Incomplete List of 16-bit Service Class UUIDs",
+        0x03 => "Complete List of 16-bit Service Class UUIDs",
+        0x04 => "Incomplete List of 32-bit Service Class UUIDs",
+        0x05 => "Complete List of 32-bit Service Class UUIDs",
+        0x06 => "Incomplete List of 128-bit Service Class UUIDs",
+        0x07 => "Complete List of 128-bit Service Class UUIDs",
+        0x08 => "Shortened Local Name",
+        0x09 => "Complete Local Name",
+        0x0a => "Tx Power Level",
+        0x0d => "Class of Device",
+        0x0e => "Simple Pairing Hash C-192",
+        0x0f => "Simple Pairing Randomizer R-192",
+        0x10 => "Device ID",
+        0x11 => "Security Manager Out of Band Flags",
+        0x12 => "Peripheral Connection Interval Range",
+        0x14 => "List of 16-bit Service Solicitation UUIDs",
+        0x15 => "List of 128-bit Service Solicitation UUIDs",
+        0x16 => "Service Data - 16-bit UUID",
+        0x17 => "Public Target Address",
+        0x18 => "Random Target Address",
+        0x19 => "Appearance",
+        0x1a => "Advertising Interval",
+        0x1b => "LE Bluetooth Device Address",
+        0x1c => "LE Role",
+        0x1d => "Simple Pairing Hash C-256",
+        0x1e => "Simple Pairing Randomizer R-256",
+        0x1f => "List of 32-bit Service Solicitation UUIDs",
+        0x20 => "Service Data - 32-bit UUID",
+        0x21 => "Service Data - 128-bit UUID",
+        0x22 => "LE Secure Connections Confirmation Value",
+        0x23 => "LE Secure Connections Random Value",
+        0x24 => "URI",
+        0x25 => "Indoor Positioning",
+        0x26 => "Transport Discovery Data",
+        0x27 => "LE Supported Features",
+        0x28 => "Channel Map Update Indication",
+        0x29 => "PB-ADV",
+        0x2a => "Mesh Message",
+        0x2b => "Mesh Beacon",
+        0x2c => "BIGInfo",
+        0x2d => "Broadcast_Code",
+        0x2e => "Resolvable Set Identifier",
+        0x2f => "Advertising Interval - long",
+        0x30 => "Broadcast_Name",
+        0x31 => "Encrypted Advertising Data",
+        0x3d => "3D Information Data",
+        0xff => "Manufacturer Specific Data",
+        _ => "Unknown",
+    }
+}
+
+fn get_uri_scheme_name(scheme: u8) -> &'static str {
+    match scheme {
+        0x01 => "",
+        0x02 => "aaa:",
+        0x03 => "aaas:",
+        0x04 => "about:",
+        0x05 => "acap:",
+        0x06 => "acct:",
+        0x07 => "cap:",
+        0x08 => "cid:",
+        0x09 => "coap:",
+        0x0a => "coaps:",
+        0x0b => "crid:",
+        0x0c => "data:",
+        0x0d => "dav:",
+        0x0e => "dict:",
+        0x0f => "dns:",
+        0x10 => "file:",
+        0x11 => "ftp:",
+        0x12 => "geo:",
+        0x13 => "go:",
+        0x14 => "gopher:",
+        0x15 => "h323:",
+        0x16 => "http:",
+        0x17 => "https:",
+        0x18 => "iax:",
+        0x19 => "icap:",
+        0x1a => "im:",
+        0x1b => "imap:",
+        0x1c => "info:",
+        0x1d => "ipp:",
+        0x1e => "ipps:",
+        0x1f => "iris:",
+        0x2e => "mailto:",
+        0x3a => "rtsp:",
+        0x45 => "tel:",
+        0x4f => "urn:",
+        0x50 => "ws:",
+        0x51 => "wss:",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{hci, str_to_array, AdDecoderKey, HostStack};
+
+    // LE Advertising Report carrying `data` from 66:55:44:33:22:11
+    fn advertising_report(data: &str) -> String {
+        let data = str_to_array(data);
+        let mut packet = vec![0x04, 0x3e, data.len() as u8 + 12, 0x02, 0x01, 0x00, 0x00];
+        packet.extend([0x11, 0x22, 0x33, 0x44, 0x55, 0x66, data.len() as u8]);
+        packet.extend(data);
+        packet.push(0xc5);
+        hci::parse(&packet, &mut HostStack::new())
+    }
+
+    #[test]
+    fn local_name_spans_the_raw_bytes() {
+        // a"b\ takes 4 bytes on air but 6 characters once escaped
+        let json = advertising_report("05 09 61 22 62 5c 02 01 06");
+        assert!(json.contains(r#""Local Name": ["a\"b\\", "", "B(16, 4)", ""]"#));
+        assert!(json.contains(r#""Length": [0x2, "", "B(20, 1)", ""]"#));
+    }
+
+    #[test]
+    fn uuid_list_remainder_is_flagged() {
+        let json = advertising_report("06 03 0d 18 0f 18 0e");
+        assert!(json.contains(r#""UUID[1]": [0x180f, "Battery", "B(18, 2)", ""]"#));
+        assert!(json.contains(r#""Data": ["0e", "", "B(20, 1)", "Not a whole UUID"]"#));
+    }
+
+    #[test]
+    fn whole_uuid128_list_has_no_remainder() {
+        let json = advertising_report("11 07 fb 34 9b 5f 80 00 00 80 00 10 00 00 0d 18 00 00");
+        assert!(json.contains(r#""UUID[0]": ["0000180d-0000-1000-8000-00805f9b34fb""#));
+        assert!(!json.contains("Not a whole UUID"));
+    }
+
+    fn custom_apple_decoder(data: &[u8], start_byte: u16) -> String {
+        format!(
+            r#""Custom": [{}, "", "B({}, 1)", ""]"#,
+            data.len(),
+            start_byte
+        )
+    }
+
+    #[test]
+    fn registered_decoder_overrides_the_builtin_one() {
+        let packet =
+            str_to_array("04 3e 14 02 01 00 00 11 22 33 44 55 66 08 07 ff 4c 00 10 02 0b 00 c5");
+        let builtin = hci::parse(&packet, &mut HostStack::new());
+        assert!(builtin.contains(r#""Continuity[0]""#));
+
+        let mut args = HostStack::new();
+        args.register_ad_decoder(AdDecoderKey::CompanyId(0x004c), custom_apple_decoder);
+        let custom = hci::parse(&packet, &mut args);
+        assert!(custom.contains(r#""Custom": [4, "", "B(18, 1)", ""]"#));
+        assert!(!custom.contains("Continuity"));
+    }
+}
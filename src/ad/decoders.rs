@@ -0,0 +1,371 @@
+use crate::assigned_numbers::class_of_device_to_string;
+use crate::bytes_to_string;
+use crate::hci::BdAddr;
+use crate::ParseBytesNode;
+
+use super::uuid128_to_string;
+use super::AdDecoderKey;
+use super::AdPayloadDecoder;
+
+pub(super) fn builtin_decoder(key: AdDecoderKey) -> Option<AdPayloadDecoder> {
+    match key {
+        AdDecoderKey::CompanyId(0x004c) => Some(apple_continuity_as_json),
+        AdDecoderKey::CompanyId(0x0006) => Some(microsoft_swift_pair_as_json),
+        AdDecoderKey::ServiceUuid16(0xfeaa) => Some(eddystone_as_json),
+        AdDecoderKey::ServiceUuid16(0xfe2c) => Some(google_fast_pair_as_json),
+        _ => None,
+    }
+}
+
+/// Apple manufacturer data is a run of Continuity messages, each a type and
+/// length header followed by the message. Only iBeacon is decoded further.
+fn apple_continuity_as_json(data: &[u8], start_byte: u16) -> String {
+    let mut messages = Vec::new();
+    let mut offset = 0;
+    while offset + 2 <= data.len() {
+        let msg_type = data[offset];
+        let msg_len = data[offset + 1] as usize;
+        let start = start_byte + offset as u16;
+        let end = (offset + 2 + msg_len).min(data.len());
+        let msg_data = &data[offset + 2..end];
+        let type_s = ParseBytesNode::new(start, 1).format(
+            "Type",
+            msg_type,
+            get_continuity_type_name(msg_type),
+            "",
+        );
+        let len_s = ParseBytesNode::new(start + 1, 1).format(
+            "Length",
+            msg_len as u8,
+            "",
+            if msg_data.len() < msg_len {
+                "Truncated"
+            } else {
+                ""
+            },
+        );
+        let msg_s = match (msg_type, msg_data.len()) {
+            (0x02, 21..) => ibeacon_as_json(msg_data, start + 2),
+            _ => ParseBytesNode::new(start + 2, msg_data.len() as u16)
+                .format("Data", msg_data, "", ""),
+        };
+        messages.push(format!(
+            r#""Continuity[{}]": {{{}, {}, {}}}"#,
+            messages.len(),
+            type_s,
+            len_s,
+            msg_s
+        ));
+        offset = end;
+    }
+    messages.join(", ")
+}
+
+fn ibeacon_as_json(data: &[u8], start_byte: u16) -> String {
+    let uuid = u128::from_be_bytes(data[0..16].try_into().unwrap());
+    let major = u16::from_be_bytes([data[16], data[17]]);
+    let minor = u16::from_be_bytes([data[18], data[19]]);
+    let measured_power = data[20] as i8;
+    format!(
+        "{}, {}, {}, {}",
+        ParseBytesNode::new(start_byte, 16).format(
+            "Proximity UUID",
+            uuid128_to_string(uuid),
+            "",
+            ""
+        ),
+        ParseBytesNode::new(start_byte + 16, 2).format("Major", major, &major.to_string(), ""),
+        ParseBytesNode::new(start_byte + 18, 2).format("Minor", minor, &minor.to_string(), ""),
+        ParseBytesNode::new(start_byte + 20, 1).format(
+            "Measured Power",
+            measured_power as u8,
+            &format!("{} dBm", measured_power),
+            ""
+        ),
+    )
+}
+
+/// Swift Pair advertisements carry the Microsoft beacon ID 0x03.
+fn microsoft_swift_pair_as_json(data: &[u8], start_byte: u16) -> String {
+    if data.len() < 3 || data[0] != 0x03 {
+        return String::new();
+    }
+    let scenario = data[1];
+    let mut fields = vec![
+        ParseBytesNode::new(start_byte, 1).format("Microsoft Beacon ID", data[0], "Swift Pair", ""),
+        ParseBytesNode::new(start_byte + 1, 1).format(
+            "Microsoft Beacon Sub Scenario",
+            scenario,
+            match scenario {
+                0x00 => "Pairing over LE only",
+                0x01 => "Pairing over LE and BR/EDR",
+                0x02 => "Pairing over BR/EDR only",
+                _ => "Unknown",
+            },
+            "",
+        ),
+        ParseBytesNode::new(start_byte + 2, 1).format("Reserved RSSI Byte", data[2], "", ""),
+    ];
+    let mut offset = 3;
+    if matches!(scenario, 0x01 | 0x02) && data.len() >= offset + 6 {
+        fields.push(ParseBytesNode::new(start_byte + offset as u16, 6).format(
+            "BD_ADDR",
+            BdAddr::new(&data[offset..]),
+            "",
+            "",
+        ));
+        offset += 6;
+    }
+    if scenario == 0x02 && data.len() >= offset + 3 {
+        let class_of_device =
+            data[offset] as u32 | (data[offset + 1] as u32) << 8 | (data[offset + 2] as u32) << 16;
+        fields.push(ParseBytesNode::new(start_byte + offset as u16, 3).format(
+            "Class of Device",
+            class_of_device,
+            &class_of_device_to_string(class_of_device),
+            "",
+        ));
+        offset += 3;
+    }
+    if data.len() > offset {
+        fields.push(
+            ParseBytesNode::new(start_byte + offset as u16, (data.len() - offset) as u16).format(
+                "Display Name",
+                bytes_to_string(&data[offset..]),
+                "",
+                "",
+            ),
+        );
+    }
+    format!(r#""Swift Pair": {{{}}}"#, fields.join(", "))
+}
+
+fn eddystone_as_json(data: &[u8], start_byte: u16) -> String {
+    if data.is_empty() {
+        return String::new();
+    }
+    let frame_type = data[0];
+    let frame_type_s = ParseBytesNode::new(start_byte, 1).format(
+        "Frame Type",
+        frame_type,
+        match frame_type {
+            0x00 => "UID",
+            0x10 => "URL",
+            0x20 => "TLM",
+            0x30 => "EID",
+            _ => "Unknown",
+        },
+        "",
+    );
+    let tx_power_s = |tx_power: u8| {
+        ParseBytesNode::new(start_byte + 1, 1).format(
+            "Ranging Data",
+            tx_power,
+            &format!("{} dBm at 0 m", tx_power as i8),
+            "",
+        )
+    };
+    let frame_s = match (frame_type, data.len()) {
+        (0x00, 18..) => format!(
+            "{}, {}, {}",
+            tx_power_s(data[1]),
+            ParseBytesNode::new(start_byte + 2, 10).format("Namespace", &data[2..12], "", ""),
+            ParseBytesNode::new(start_byte + 12, 6).format("Instance", &data[12..18], "", ""),
+        ),
+        (0x10, 3..) => {
+            let scheme = data[2];
+            let scheme_name = match scheme {
+                0x00 => "http://www.",
+                0x01 => "https://www.",
+                0x02 => "http://",
+                0x03 => "https://",
+                _ => "",
+            };
+            let url = format!("{}{}", scheme_name, eddystone_url_expand(&data[3..]));
+            format!(
+                "{}, {}, {}",
+                tx_power_s(data[1]),
+                ParseBytesNode::new(start_byte + 2, 1).format(
+                    "URL Scheme",
+                    scheme,
+                    scheme_name,
+                    if scheme_name.is_empty() {
+                        "Invalid"
+                    } else {
+                        ""
+                    },
+                ),
+                ParseBytesNode::new(start_byte + 3, (data.len() - 3) as u16).format(
+                    "Encoded URL",
+                    &data[3..],
+                    &url,
+                    "",
+                ),
+            )
+        }
+        (0x20, 2..) if data[1] != 0x00 => {
+            ParseBytesNode::new(start_byte + 1, 1).format("Version", data[1], "Encrypted TLM", "")
+        }
+        (0x20, 14..) => {
+            let battery_voltage = u16::from_be_bytes([data[2], data[3]]);
+            let temperature = i16::from_be_bytes([data[4], data[5]]);
+            let adv_count = u32::from_be_bytes(data[6..10].try_into().unwrap());
+            let sec_count = u32::from_be_bytes(data[10..14].try_into().unwrap());
+            format!(
+                "{}, {}, {}, {}, {}",
+                ParseBytesNode::new(start_byte + 1, 1).format(
+                    "Version",
+                    data[1],
+                    "Unencrypted TLM",
+                    ""
+                ),
+                ParseBytesNode::new(start_byte + 2, 2).format(
+                    "Battery Voltage",
+                    battery_voltage,
+                    &format!("{} mV", battery_voltage),
+                    "",
+                ),
+                ParseBytesNode::new(start_byte + 4, 2).format(
+                    "Beacon Temperature",
+                    temperature as u16,
+                    &match temperature as u16 {
+                        0x8000 => String::from("Not supported"),
+                        _ => format!("{:.2} C", temperature as f32 / 256.0),
+                    },
+                    "",
+                ),
+                ParseBytesNode::new(start_byte + 6, 4).format(
+                    "Advertising PDU Count",
+                    adv_count,
+                    &adv_count.to_string(),
+                    "",
+                ),
+                ParseBytesNode::new(start_byte + 10, 4).format(
+                    "Time Since Power-on",
+                    sec_count,
+                    &format!("{:.1} s", sec_count as f64 / 10.0),
+                    "",
+                ),
+            )
+        }
+        (0x30, 10..) => format!(
+            "{}, {}",
+            tx_power_s(data[1]),
+            ParseBytesNode::new(start_byte + 2, 8).format(
+                "Ephemeral Identifier",
+                &data[2..10],
+                "",
+                ""
+            ),
+        ),
+        _ => ParseBytesNode::new(start_byte + 1, (data.len() - 1) as u16).format(
+            "Frame Data",
+            &data[1..],
+            "",
+            if matches!(frame_type, 0x00 | 0x10 | 0x20 | 0x30) {
+                "Truncated"
+            } else {
+                ""
+            },
+        ),
+    };
+    format!(r#""Eddystone": {{{}, {}}}"#, frame_type_s, frame_s)
+}
+
+fn eddystone_url_expand(data: &[u8]) -> String {
+    data.iter()
+        .map(|byte| match byte {
+            0x00 => String::from(".com/"),
+            0x01 => String::from(".org/"),
+            0x02 => String::from(".edu/"),
+            0x03 => String::from(".net/"),
+            0x04 => String::from(".info/"),
+            0x05 => String::from(".biz/"),
+            0x06 => String::from(".gov/"),
+            0x07 => String::from(".com"),
+            0x08 => String::from(".org"),
+            0x09 => String::from(".edu"),
+            0x0a => String::from(".net"),
+            0x0b => String::from(".info"),
+            0x0c => String::from(".biz"),
+            0x0d => String::from(".gov"),
+            _ => bytes_to_string(&[*byte]),
+        })
+        .collect()
+}
+
+/// Fast Pair service data is either the 24-bit model ID while discoverable,
+/// or a flags byte followed by length/type prefixed fields while not.
+fn google_fast_pair_as_json(data: &[u8], start_byte: u16) -> String {
+    if data.len() == 3 {
+        let model_id = (data[0] as u32) << 16 | (data[1] as u32) << 8 | data[2] as u32;
+        return format!(
+            r#""Fast Pair": {{{}}}"#,
+            ParseBytesNode::new(start_byte, 3).format("Model ID", model_id, "Discoverable", "")
+        );
+    }
+    if data.is_empty() {
+        return String::new();
+    }
+    let mut fields =
+        vec![ParseBytesNode::new(start_byte, 1).format("Flags", data[0], "Not discoverable", "")];
+    let mut offset = 1;
+    while offset < data.len() {
+        let field_len = (data[offset] >> 4) as usize;
+        let field_type = data[offset] & 0x0f;
+        let end = (offset + 1 + field_len).min(data.len());
+        let start = start_byte + offset as u16;
+        let field_name = match field_type {
+            0x00 => "Account Key Filter (show UI)",
+            0x01 => "Salt",
+            0x02 => "Account Key Filter (hide UI)",
+            0x03 => "Battery Values (show UI)",
+            0x04 => "Battery Values (hide UI)",
+            _ => "Unknown",
+        };
+        fields.push(format!(
+            r#""Field[{}]": {{{}, {}}}"#,
+            fields.len() - 1,
+            ParseBytesNode::new(start, 1).format(
+                "Length and Type",
+                data[offset],
+                field_name,
+                if end - offset - 1 < field_len {
+                    "Truncated"
+                } else {
+                    ""
+                },
+            ),
+            ParseBytesNode::new(start + 1, (end - offset - 1) as u16).format(
+                "Value",
+                &data[offset + 1..end],
+                "",
+                "",
+            ),
+        ));
+        offset = end;
+    }
+    format!(r#""Fast Pair": {{{}}}"#, fields.join(", "))
+}
+
+fn get_continuity_type_name(msg_type: u8) -> &'static str {
+    match msg_type {
+        0x01 => "Hidden",
+        0x02 => "iBeacon",
+        0x03 => "AirPrint",
+        0x05 => "AirDrop",
+        0x06 => "HomeKit",
+        0x07 => "Proximity Pairing",
+        0x08 => "Hey Siri",
+        0x09 => "AirPlay Target",
+        0x0a => "AirPlay Source",
+        0x0b => "Magic Switch",
+        0x0c => "Handoff",
+        0x0d => "Tethering Target Presence",
+        0x0e => "Tethering Source Presence",
+        0x0f => "Nearby Action",
+        0x10 => "Nearby Info",
+        0x12 => "Find My",
+        _ => "Unknown",
+    }
+}
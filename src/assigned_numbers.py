@@ -0,0 +1,118 @@
+#!/usr/bin/env python3
+"""Regenerates the lookup tables of assigned_numbers.rs from the Bluetooth
+SIG's machine readable assigned numbers.
+
+    git clone https://bitbucket.org/bluetooth-SIG/public.git
+    python3 src/assigned_numbers.py public/assigned_numbers
+
+Every table of assigned_numbers.rs listed in TABLES below is replaced, with
+the comment naming its YAML files, by all the entries of those files sorted
+by value. The first file listing a value names it. Needs PyYAML.
+"""
+
+import pathlib
+import sys
+
+import yaml
+
+RUST_FILE = pathlib.Path(__file__).with_name("assigned_numbers.rs")
+
+
+def company_identifiers(root):
+    data = yaml.safe_load((root / "company_identifiers/company_identifiers.yaml").read_text())
+    return [(entry["value"], entry["name"]) for entry in data["company_identifiers"]]
+
+
+def uuids(root, files):
+    entries = []
+    for file in files:
+        data = yaml.safe_load((root / file).read_text())
+        entries += [(entry["uuid"], entry["name"]) for entry in data["uuids"]]
+    return entries
+
+
+def appearance_values(root):
+    data = yaml.safe_load((root / "core/appearance_values.yaml").read_text())
+    entries = []
+    for category in data["appearance_values"]:
+        value = category["category"] << 6
+        name = category["name"]
+        # the category value itself is the generic device of the category
+        entries.append((value, name if value == 0 else "Generic " + name))
+        for subcategory in category.get("subcategory", []):
+            entries.append((value | subcategory["value"], subcategory["name"]))
+    return entries
+
+
+UUID16_FILES = [
+    "uuids/protocol_identifiers.yaml",
+    "uuids/service_class.yaml",
+    "uuids/service_uuids.yaml",
+    "uuids/declarations.yaml",
+    "uuids/characteristic_uuids.yaml",
+    "uuids/descriptors.yaml",
+    "uuids/member_uuids.yaml",
+    "uuids/sdo_uuids.yaml",
+]
+
+# table name, YAML files and how to read them
+TABLES = [
+    ("COMPANY_IDENTIFIERS", ["company_identifiers/company_identifiers.yaml"], company_identifiers),
+    ("UUID16_NAMES", UUID16_FILES, lambda root: uuids(root, UUID16_FILES)),
+    ("APPEARANCE_NAMES", ["core/appearance_values.yaml"], appearance_values),
+]
+
+
+def source_comment(files):
+    """Names the YAML files on as many `//` lines as 80 columns need."""
+    text = files[0] if len(files) == 1 else ", ".join(files[:-1]) + " and " + files[-1]
+    lines, line = [], "//"
+    for word in text.split(" "):
+        if len(line) + 1 + len(word) > 80:
+            lines.append(line)
+            line = "//"
+        line += " " + word
+    return lines + [line]
+
+
+def rust_string(name):
+    name = " ".join(str(name).split())
+    return '"' + name.replace("\\", "\\\\").replace('"', '\\"') + '"'
+
+
+def table(name, files, entries):
+    names = {}
+    for value, entry_name in entries:
+        names.setdefault(value, entry_name)
+    lines = source_comment(files)
+    lines.append("const {}: &[(u16, &str)] = &[".format(name))
+    for value in sorted(names):
+        lines.append("    ({:#06x}, {}),".format(value, rust_string(names[value])))
+    lines.append("];")
+    return lines
+
+
+def replace_table(source, name, lines):
+    """Swaps the table `name`, with the comment lines above it, for `lines`."""
+    start = source.index("\nconst {}:".format(name)) + 1
+    while True:
+        previous = source.rfind("\n", 0, start - 1) + 1
+        if not source[previous:start].startswith("//"):
+            break
+        start = previous
+    end = source.index("\n];\n", start) + len("\n];")
+    return source[:start] + "\n".join(lines) + source[end:]
+
+
+def main():
+    if len(sys.argv) != 2:
+        sys.exit("usage: {} <path to assigned_numbers>".format(sys.argv[0]))
+    root = pathlib.Path(sys.argv[1])
+    source = RUST_FILE.read_text()
+    for name, files, read in TABLES:
+        source = replace_table(source, name, table(name, files, read(root)))
+    RUST_FILE.write_text(source)
+
+
+if __name__ == "__main__":
+    main()
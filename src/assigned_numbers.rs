@@ -0,0 +1,960 @@
+//! Lookup tables for the Bluetooth SIG assigned numbers that show up as
+//! aliases in the output. The tables are kept sorted by value so they can be
+//! binary searched; values missing from them give an empty alias.
+//!
+//! The entries come from the SIG's machine readable assigned numbers,
+//! https://bitbucket.org/bluetooth-SIG/public, under `assigned_numbers/`.
+//! Each table names the YAML files it comes from and is rewritten from them
+//! by `assigned_numbers.py` next to this file:
+//!
+//! ```text
+//! python3 src/assigned_numbers.py <checkout>/assigned_numbers
+//! ```
+//!
+//! Until it is next run the tables hold a hand-picked subset of the files.
+
+pub(crate) fn get_company_name(company_id: u16) -> &'static str {
+    lookup(COMPANY_IDENTIFIERS, company_id)
+}
+
+/// Names 16-bit UUIDs: protocols, service classes, GATT services, attribute
+/// types, descriptors and characteristics share one namespace.
+pub(crate) fn get_uuid16_name(uuid: u16) -> &'static str {
+    lookup(UUID16_NAMES, uuid)
+}
+
+pub(crate) fn get_uuid32_name(uuid: u32) -> &'static str {
+    u16::try_from(uuid).map(get_uuid16_name).unwrap_or("")
+}
+
+/// Names 128-bit UUIDs built on the Bluetooth Base UUID
+/// 00000000-0000-1000-8000-00805F9B34FB.
+pub(crate) fn get_uuid128_name(uuid: u128) -> &'static str {
+    const BASE_UUID: u128 = 0x0000_0000_0000_1000_8000_0080_5f9b_34fb;
+    if uuid & ((1 << 96) - 1) == BASE_UUID {
+        get_uuid32_name((uuid >> 96) as u32)
+    } else {
+        ""
+    }
+}
+
+/// Names an appearance value by its sub-category, falling back to the
+/// category in bits 6 to 15.
+pub(crate) fn get_appearance_name(appearance: u16) -> &'static str {
+    match lookup(APPEARANCE_NAMES, appearance) {
+        "" => get_appearance_category_name(appearance >> 6),
+        name => name,
+    }
+}
+
+/// Version numbers shared by LMP_VersNr and the Link Layer VersNr.
+pub(crate) fn get_version_name(version: u8) -> &'static str {
+    match version {
+        0x00 => "Bluetooth Core Specification 1.0b",
+        0x01 => "Bluetooth Core Specification 1.1",
+        0x02 => "Bluetooth Core Specification 1.2",
+        0x03 => "Bluetooth Core Specification 2.0 + EDR",
+        0x04 => "Bluetooth Core Specification 2.1 + EDR",
+        0x05 => "Bluetooth Core Specification 3.0 + HS",
+        0x06 => "Bluetooth Core Specification 4.0",
+        0x07 => "Bluetooth Core Specification 4.1",
+        0x08 => "Bluetooth Core Specification 4.2",
+        0x09 => "Bluetooth Core Specification 5.0",
+        0x0a => "Bluetooth Core Specification 5.1",
+        0x0b => "Bluetooth Core Specification 5.2",
+        0x0c => "Bluetooth Core Specification 5.3",
+        0x0d => "Bluetooth Core Specification 5.4",
+        0x0e => "Bluetooth Core Specification 6.0",
+        0x0f => "Bluetooth Core Specification 6.1",
+        _ => "Reserved for future use",
+    }
+}
+
+/// Describes a Class of Device as "Major: Minor (Service, ...)".
+pub(crate) fn class_of_device_to_string(class_of_device: u32) -> String {
+    let major = ((class_of_device >> 8) & 0x1f) as u8;
+    let minor = ((class_of_device >> 2) & 0x3f) as u8;
+    let services = [
+        (13, "Limited Discoverable Mode"),
+        (14, "LE audio"),
+        (16, "Positioning"),
+        (17, "Networking"),
+        (18, "Rendering"),
+        (19, "Capturing"),
+        (20, "Object Transfer"),
+        (21, "Audio"),
+        (22, "Telephony"),
+        (23, "Information"),
+    ]
+    .iter()
+    .filter(|(bit, _)| class_of_device & (1 << bit) != 0)
+    .map(|(_, name)| *name)
+    .collect::<Vec<&str>>();
+    let device_s = format!(
+        "{}: {}",
+        get_major_device_class_name(major),
+        get_minor_device_class_name(major, minor)
+    );
+    if services.is_empty() {
+        device_s
+    } else {
+        format!("{} ({})", device_s, services.join(", "))
+    }
+}
+
+fn lookup<T: Ord + Copy>(table: &[(T, &'static str)], key: T) -> &'static str {
+    table
+        .binary_search_by_key(&key, |(k, _)| *k)
+        .map(|i| table[i].1)
+        .unwrap_or("")
+}
+
+fn get_major_device_class_name(major: u8) -> &'static str {
+    match major {
+        0x00 => "Miscellaneous",
+        0x01 => "Computer",
+        0x02 => "Phone",
+        0x03 => "LAN/Network Access point",
+        0x04 => "Audio/Video",
+        0x05 => "Peripheral",
+        0x06 => "Imaging",
+        0x07 => "Wearable",
+        0x08 => "Toy",
+        0x09 => "Health",
+        0x1f => "Uncategorized",
+        _ => "Reserved",
+    }
+}
+
+fn get_minor_device_class_name(major: u8, minor: u8) -> String {
+    let name = match (major, minor) {
+        (0x01, 0x00) => "Uncategorized",
+        (0x01, 0x01) => "Desktop workstation",
+        (0x01, 0x02) => "Server-class computer",
+        (0x01, 0x03) => "Laptop",
+        (0x01, 0x04) => "Handheld PC/PDA (clamshell)",
+        (0x01, 0x05) => "Palm-size PC/PDA",
+        (0x01, 0x06) => "Wearable computer (watch size)",
+        (0x01, 0x07) => "Tablet",
+        (0x02, 0x00) => "Uncategorized",
+        (0x02, 0x01) => "Cellular",
+        (0x02, 0x02) => "Cordless",
+        (0x02, 0x03) => "Smartphone",
+        (0x02, 0x04) => "Wired modem or voice gateway",
+        (0x02, 0x05) => "Common ISDN access",
+        (0x03, _) => match minor >> 3 {
+            0x00 => "Fully available",
+            0x01 => "1% to 17% utilized",
+            0x02 => "17% to 33% utilized",
+            0x03 => "33% to 50% utilized",
+            0x04 => "50% to 67% utilized",
+            0x05 => "67% to 83% utilized",
+            0x06 => "83% to 99% utilized",
+            _ => "No service available",
+        },
+        (0x04, 0x00) => "Uncategorized",
+        (0x04, 0x01) => "Wearable Headset Device",
+        (0x04, 0x02) => "Hands-free Device",
+        (0x04, 0x04) => "Microphone",
+        (0x04, 0x05) => "Loudspeaker",
+        (0x04, 0x06) => "Headphones",
+        (0x04, 0x07) => "Portable Audio",
+        (0x04, 0x08) => "Car audio",
+        (0x04, 0x09) => "Set-top box",
+        (0x04, 0x0a) => "HiFi Audio Device",
+        (0x04, 0x0b) => "VCR",
+        (0x04, 0x0c) => "Video Camera",
+        (0x04, 0x0d) => "Camcorder",
+        (0x04, 0x0e) => "Video Monitor",
+        (0x04, 0x0f) => "Video Display and Loudspeaker",
+        (0x04, 0x10) => "Video Conferencing",
+        (0x04, 0x12) => "Gaming/Toy",
+        (0x05, _) => {
+            let kind = match minor >> 4 {
+                0x00 => "",
+                0x01 => "Keyboard",
+                0x02 => "Pointing device",
+                _ => "Combo keyboard/pointing device",
+            };
+            let device = match minor & 0x0f {
+                0x00 => "",
+                0x01 => "Joystick",
+                0x02 => "Gamepad",
+                0x03 => "Remote control",
+                0x04 => "Sensing device",
+                0x05 => "Digitizer tablet",
+                0x06 => "Card Reader",
+                0x07 => "Digital Pen",
+                0x08 => "Handheld scanner",
+                0x09 => "Handheld gestural input device",
+                _ => "Reserved",
+            };
+            return match (kind, device) {
+                ("", "") => String::from("Uncategorized"),
+                (kind, "") => kind.to_string(),
+                ("", device) => device.to_string(),
+                (kind, device) => format!("{} {}", kind, device),
+            };
+        }
+        (0x06, _) => {
+            let kinds = [
+                (2, "Display"),
+                (3, "Camera"),
+                (4, "Scanner"),
+                (5, "Printer"),
+            ]
+            .iter()
+            .filter(|(bit, _)| minor & (1 << bit) != 0)
+            .map(|(_, name)| *name)
+            .collect::<Vec<&str>>();
+            return match kinds.is_empty() {
+                true => String::from("Uncategorized"),
+                false => kinds.join("/"),
+            };
+        }
+        (0x07, 0x01) => "Wristwatch",
+        (0x07, 0x02) => "Pager",
+        (0x07, 0x03) => "Jacket",
+        (0x07, 0x04) => "Helmet",
+        (0x07, 0x05) => "Glasses",
+        (0x07, 0x06) => "Pin",
+        (0x08, 0x01) => "Robot",
+        (0x08, 0x02) => "Vehicle",
+        (0x08, 0x03) => "Doll/Action figure",
+        (0x08, 0x04) => "Controller",
+        (0x08, 0x05) => "Game",
+        (0x09, 0x00) => "Undefined",
+        (0x09, 0x01) => "Blood Pressure Monitor",
+        (0x09, 0x02) => "Thermometer",
+        (0x09, 0x03) => "Weighing Scale",
+        (0x09, 0x04) => "Glucose Meter",
+        (0x09, 0x05) => "Pulse Oximeter",
+        (0x09, 0x06) => "Heart/Pulse Rate Monitor",
+        (0x09, 0x07) => "Health Data Display",
+        (0x09, 0x08) => "Step Counter",
+        (0x09, 0x09) => "Body Composition Analyzer",
+        (0x09, 0x0a) => "Peak Flow Monitor",
+        (0x09, 0x0b) => "Medication Monitor",
+        (0x09, 0x0c) => "Knee Prosthesis",
+        (0x09, 0x0d) => "Ankle Prosthesis",
+        (0x09, 0x0e) => "Generic Health Manager",
+        (0x09, 0x0f) => "Personal Mobility Device",
+        (0x00 | 0x1f, _) => "Uncategorized",
+        _ => "Reserved",
+    };
+    name.to_string()
+}
+
+fn get_appearance_category_name(category: u16) -> &'static str {
+    match category {
+        0x000 => "Unknown",
+        0x001 => "Phone",
+        0x002 => "Computer",
+        0x003 => "Watch",
+        0x004 => "Clock",
+        0x005 => "Display",
+        0x006 => "Remote Control",
+        0x007 => "Eye-glasses",
+        0x008 => "Tag",
+        0x009 => "Keyring",
+        0x00a => "Media Player",
+        0x00b => "Barcode Scanner",
+        0x00c => "Thermometer",
+        0x00d => "Heart Rate Sensor",
+        0x00e => "Blood Pressure",
+        0x00f => "Human Interface Device",
+        0x010 => "Glucose Meter",
+        0x011 => "Running Walking Sensor",
+        0x012 => "Cycling",
+        0x015 => "Control Device",
+        0x016 => "Network Device",
+        0x017 => "Sensor",
+        0x018 => "Light Fixtures",
+        0x019 => "Fan",
+        0x01a => "HVAC",
+        0x01b => "Air Conditioning",
+        0x01c => "Humidifier",
+        0x01d => "Heating",
+        0x01e => "Access Control",
+        0x01f => "Motorized Device",
+        0x020 => "Power Device",
+        0x021 => "Light Source",
+        0x022 => "Window Covering",
+        0x023 => "Audio Sink",
+        0x024 => "Audio Source",
+        0x025 => "Motorized Vehicle",
+        0x026 => "Domestic Appliance",
+        0x027 => "Wearable Audio Device",
+        0x028 => "Aircraft",
+        0x029 => "AV Equipment",
+        0x02a => "Display Equipment",
+        0x02b => "Hearing aid",
+        0x02c => "Gaming",
+        0x02d => "Signage",
+        0x031 => "Pulse Oximeter",
+        0x032 => "Weight Scale",
+        0x033 => "Personal Mobility Device",
+        0x034 => "Continuous Glucose Monitor",
+        0x035 => "Insulin Pump",
+        0x036 => "Medication Delivery",
+        0x037 => "Spirometer",
+        0x051 => "Outdoor Sports Activity",
+        _ => "Reserved for future use",
+    }
+}
+
+// company_identifiers/company_identifiers.yaml
+const COMPANY_IDENTIFIERS: &[(u16, &str)] = &[
+    (0x0000, "Ericsson AB"),
+    (0x0001, "Nokia Mobile Phones"),
+    (0x0002, "Intel Corp."),
+    (0x0003, "IBM Corp."),
+    (0x0004, "Toshiba Corp."),
+    (0x0005, "3Com"),
+    (0x0006, "Microsoft"),
+    (0x0007, "Lucent"),
+    (0x0008, "Motorola"),
+    (0x0009, "Infineon Technologies AG"),
+    (0x000a, "Qualcomm Technologies International, Ltd. (QTIL)"),
+    (0x000b, "Silicon Wave"),
+    (0x000c, "Digianswer A/S"),
+    (0x000d, "Texas Instruments Inc."),
+    (0x000e, "Parthus Technologies Inc."),
+    (0x000f, "Broadcom Corporation"),
+    (0x0010, "Mitel Semiconductor"),
+    (0x0011, "Widcomm, Inc."),
+    (0x0012, "Zeevo, Inc."),
+    (0x0013, "Atmel Corporation"),
+    (0x0014, "Mitsubishi Electric Corporation"),
+    (0x0015, "RTX A/S"),
+    (0x0016, "KC Technology Inc."),
+    (0x0017, "Newlogic"),
+    (0x0018, "Transilica, Inc."),
+    (0x0019, "Rohde & Schwarz GmbH & Co. KG"),
+    (0x001a, "TTPCom Limited"),
+    (0x001b, "Signia Technologies, Inc."),
+    (0x001c, "Conexant Systems Inc."),
+    (0x001d, "Qualcomm"),
+    (0x001e, "Inventel"),
+    (0x001f, "AVM Berlin"),
+    (0x0020, "BandSpeed, Inc."),
+    (0x0021, "Mansella Ltd"),
+    (0x0022, "NEC Corporation"),
+    (0x0023, "WavePlus Technology Co., Ltd."),
+    (0x0024, "Alcatel"),
+    (0x0025, "NXP B.V."),
+    (0x0026, "C Technologies"),
+    (0x0027, "Open Interface"),
+    (0x0028, "R F Micro Devices"),
+    (0x0029, "Hitachi Ltd"),
+    (0x002a, "Symbol Technologies, Inc."),
+    (0x002b, "Tenovis"),
+    (0x002c, "Macronix International Co. Ltd."),
+    (0x002d, "GCT Semiconductor"),
+    (0x002e, "Norwood Systems"),
+    (0x002f, "MewTel Technology Inc."),
+    (0x0030, "ST Microelectronics"),
+    (0x0031, "Synopsys, Inc."),
+    (0x0032, "Red-M (Communications) Ltd"),
+    (0x0033, "Commil Ltd"),
+    (0x0034, "Computer Access Technology Corporation (CATC)"),
+    (0x0035, "Eclipse (HQ Espana) S.L."),
+    (0x0036, "Renesas Electronics Corporation"),
+    (0x0037, "Mobilian Corporation"),
+    (0x0038, "Syntronix Corporation"),
+    (0x0039, "Integrated System Solution Corp."),
+    (0x003a, "Panasonic Holdings Corporation"),
+    (0x003b, "Gennum Corporation"),
+    (0x003c, "BlackBerry Limited"),
+    (0x003d, "IPextreme, Inc."),
+    (0x003e, "Systems and Chips, Inc"),
+    (0x003f, "Bluetooth SIG, Inc"),
+    (0x0040, "Seiko Epson Corporation"),
+    (0x0041, "Integrated Silicon Solution Taiwan, Inc."),
+    (0x0042, "CONWISE Technology Corporation Ltd"),
+    (0x0043, "PARROT AUTOMOTIVE SAS"),
+    (0x0044, "Socket Mobile"),
+    (0x0045, "Atheros Communications, Inc."),
+    (0x0046, "MediaTek, Inc."),
+    (0x0047, "Bluegiga"),
+    (0x0048, "Marvell Technology Group Ltd."),
+    (0x0049, "3DSP Corporation"),
+    (0x004a, "Accel Semiconductor Ltd."),
+    (0x004b, "Continental Automotive Systems"),
+    (0x004c, "Apple, Inc."),
+    (0x004d, "Staccato Communications, Inc."),
+    (0x004e, "Avago Technologies"),
+    (0x004f, "APT Ltd."),
+    (0x0050, "SiRF Technology, Inc."),
+    (0x0051, "Tzero Technologies, Inc."),
+    (0x0052, "J&M Corporation"),
+    (0x0053, "Free2move AB"),
+    (0x0054, "3DiJoy Corporation"),
+    (0x0055, "Plantronics, Inc."),
+    (0x0056, "Sony Ericsson Mobile Communications"),
+    (0x0057, "Harman International Industries, Inc."),
+    (0x0058, "Vizio, Inc."),
+    (0x0059, "Nordic Semiconductor ASA"),
+    (0x005a, "EM Microelectronic-Marin SA"),
+    (0x005b, "Ralink Technology Corporation"),
+    (0x005c, "Belkin International, Inc."),
+    (0x005d, "Realtek Semiconductor Corporation"),
+    (0x005e, "Stonestreet One, LLC"),
+    (0x005f, "Wicentric, Inc."),
+    (0x0060, "RivieraWaves S.A.S"),
+    (0x0061, "RDA Microelectronics"),
+    (0x0062, "Gibson Guitars"),
+    (0x0063, "MiCommand Inc."),
+    (0x0064, "Band XI International, LLC"),
+    (0x0065, "HP, Inc."),
+    (0x0066, "9Solutions Oy"),
+    (0x0067, "GN Audio A/S"),
+    (0x0068, "General Motors"),
+    (0x0069, "A&D Engineering, Inc."),
+    (0x006a, "MindTree Ltd."),
+    (0x006b, "Polar Electro OY"),
+    (0x006c, "Beautiful Enterprise Co., Ltd."),
+    (0x006d, "BriarTek, Inc"),
+    (0x006e, "Summit Data Communications, Inc."),
+    (0x006f, "Sound ID"),
+    (0x0070, "Monster, LLC"),
+    (0x0071, "connectBlue AB"),
+    (0x0072, "ShangHai Super Smart Electronics Co. Ltd."),
+    (0x0073, "Group Sense Ltd."),
+    (0x0074, "Zomm, LLC"),
+    (0x0075, "Samsung Electronics Co. Ltd."),
+    (0x0076, "Creative Technology Ltd."),
+    (0x0077, "Laird Connectivity LLC"),
+    (0x0078, "Nike, Inc."),
+    (0x0079, "lesswire AG"),
+    (0x007a, "MStar Semiconductor, Inc."),
+    (0x007b, "Hanlynn Technologies"),
+    (0x007c, "A & R Cambridge"),
+    (0x007d, "Seers Technology Co., Ltd."),
+    (0x007e, "Sports Tracking Technologies Ltd."),
+    (0x007f, "Autonet Mobile"),
+    (0x0080, "DeLorme Publishing Company, Inc."),
+    (0x0081, "WuXi Vimicro"),
+    (0x0082, "DSEA A/S"),
+    (0x0083, "TimeKeeping Systems, Inc."),
+    (0x0084, "Ludus Helsinki Ltd."),
+    (0x0085, "BlueRadios, Inc."),
+    (0x0086, "Equinux AG"),
+    (0x0087, "Garmin International, Inc."),
+    (0x0088, "Ecotest"),
+    (0x0089, "GN Hearing A/S"),
+    (0x008a, "Jawbone"),
+    (0x008b, "Topcon Positioning Systems, LLC"),
+    (0x008c, "Gimbal Inc."),
+    (0x008d, "Zscan Software"),
+    (0x008e, "Quintic Corp"),
+    (0x008f, "Telit Wireless Solutions GmbH"),
+    (0x0090, "Funai Electric Co., Ltd."),
+    (0x0091, "Advanced PANMOBIL systems GmbH & Co. KG"),
+    (0x0092, "ThinkOptics, Inc."),
+    (0x0093, "Universal Electronics, Inc."),
+    (0x0094, "Airoha Technology Corp."),
+    (0x0095, "NEC Lighting, Ltd."),
+    (0x0096, "ODM Technology, Inc."),
+    (0x0097, "ConnecteDevice Ltd."),
+    (0x0098, "zero1.tv GmbH"),
+    (0x0099, "i.Tech Dynamic Global Distribution Ltd."),
+    (0x009a, "Alpwise"),
+    (0x009b, "Jiangsu Toppower Automotive Electronics Co., Ltd."),
+    (0x009c, "Colorfy, Inc."),
+    (0x009d, "Geoforce Inc."),
+    (0x009e, "Bose Corporation"),
+    (0x009f, "Suunto Oy"),
+    (0x00a0, "Kensington Computer Products Group"),
+    (0x00a1, "SR-Medizinelektronik"),
+    (0x00a2, "Vertu Corporation Limited"),
+    (0x00a3, "Meta Watch Ltd."),
+    (0x00a4, "LINAK A/S"),
+    (0x00a5, "OTL Dynamics LLC"),
+    (0x00a6, "Panda Ocean Inc."),
+    (0x00a7, "Visteon Corporation"),
+    (0x00a8, "ARP Devices Limited"),
+    (0x00a9, "MARELLI EUROPE S.P.A."),
+    (0x00aa, "CAEN RFID srl"),
+    (0x00ab, "Ingenieur-Systemgruppe Zahn GmbH"),
+    (0x00ac, "Green Throttle Games"),
+    (0x00ad, "Peter Systemtechnik GmbH"),
+    (0x00ae, "Omegawave Oy"),
+    (0x00af, "Cinetix"),
+    (0x00b0, "Passif Semiconductor Corp"),
+    (0x00b1, "Saris Cycling Group, Inc"),
+    (0x00b2, "Bekey A/S"),
+    (0x00b3, "Clarinox Technologies Pty. Ltd."),
+    (0x00b4, "BDE Technology Co., Ltd."),
+    (0x00b5, "Swirl Networks"),
+    (0x00b6, "Meso international"),
+    (0x00b7, "TreLab Ltd"),
+    (0x00b8, "Qualcomm Innovation Center, Inc. (QuIC)"),
+    (0x00b9, "Johnson Controls, Inc."),
+    (0x00ba, "Starkey Hearing Technologies"),
+    (0x00bb, "S-Power Electronics Limited"),
+    (0x00bc, "Ace Sensor Inc"),
+    (0x00bd, "Aplix Corporation"),
+    (0x00be, "AAMP of America"),
+    (0x00bf, "Stalmart Technology Limited"),
+    (0x00c0, "AMICCOM Electronics Corporation"),
+    (0x00c1, "Shenzhen Excelsecu Data Technology Co.,Ltd"),
+    (0x00c2, "Geneq Inc."),
+    (0x00c3, "adidas AG"),
+    (0x00c4, "LG Electronics"),
+    (0x00c5, "Onset Computer Corporation"),
+    (0x00c6, "Selfly BV"),
+    (0x00c7, "Quuppa Oy."),
+    (0x00c8, "GeLo Inc"),
+    (0x00c9, "Evluma"),
+    (0x00ca, "MC10"),
+    (0x00cb, "Binauric SE"),
+    (0x00cc, "Beats Electronics"),
+    (0x00cd, "Microchip Technology Inc."),
+    (0x00ce, "Eve Systems GmbH"),
+    (0x00cf, "ARCHOS SA"),
+    (0x00d0, "Dexcom, Inc."),
+    (0x00d1, "Polar Electro Europe B.V."),
+    (0x00d2, "Dialog Semiconductor B.V."),
+    (0x00d3, "Taixingbang Technology (HK) Co,. LTD."),
+    (0x00d4, "Kawantech"),
+    (0x00d5, "Austco Communication Systems"),
+    (0x00d6, "Timex Group USA, Inc."),
+    (0x00d7, "Qualcomm Technologies, Inc."),
+    (0x00d8, "Qualcomm Connected Experiences, Inc."),
+    (0x00d9, "Voyetra Turtle Beach"),
+    (0x00da, "txtr GmbH"),
+    (0x00db, "Snuza (Pty) Ltd"),
+    (0x00dc, "Procter & Gamble"),
+    (0x00dd, "Hosiden Corporation"),
+    (0x00de, "Muzik LLC"),
+    (0x00df, "Misfit Wearables Corp"),
+    (0x00e0, "Google"),
+    (0x0118, "Radius Networks, Inc."),
+    (0x012d, "Sony Corporation"),
+    (0x0131, "Cypress Semiconductor"),
+    (0x0157, "Anhui Huami Information Technology Co., Ltd."),
+    (0x0171, "Amazon.com Services LLC"),
+    (0x01a9, "Canon Inc."),
+    (0x01ab, "Meta Platforms, Inc."),
+    (0x01da, "Logitech International SA"),
+    (0x022b, "Tesla, Inc."),
+    (0x027d, "HUAWEI Technologies Co., Ltd."),
+    (0x02e5, "Espressif Systems (Shanghai) Co., Ltd."),
+    (0x02ff, "Silicon Laboratories"),
+    (0x038f, "Xiaomi Inc."),
+    (0x0499, "Ruuvi Innovations Ltd."),
+    (0x05a7, "Sonos Inc"),
+    (0x0822, "Adafruit Industries"),
+    (0x0969, "Woan Technology (Shenzhen) Co., Ltd."),
+    (0xffff, "Reserved for testing"),
+];
+
+// uuids/protocol_identifiers.yaml, uuids/service_class.yaml,
+// uuids/service_uuids.yaml, uuids/declarations.yaml,
+// uuids/characteristic_uuids.yaml, uuids/descriptors.yaml,
+// uuids/member_uuids.yaml and uuids/sdo_uuids.yaml
+const UUID16_NAMES: &[(u16, &str)] = &[
+    (0x0001, "SDP"),
+    (0x0003, "RFCOMM"),
+    (0x0005, "TCS-BIN"),
+    (0x0007, "ATT"),
+    (0x0008, "OBEX"),
+    (0x000f, "BNEP"),
+    (0x0011, "HIDP"),
+    (0x0017, "AVCTP"),
+    (0x0019, "AVDTP"),
+    (0x001b, "AVCTP Browsing"),
+    (0x001e, "MCAP Control Channel"),
+    (0x001f, "MCAP Data Channel"),
+    (0x0100, "L2CAP"),
+    (0x1000, "Service Discovery Server"),
+    (0x1001, "Browse Group Descriptor"),
+    (0x1002, "Public Browse Root"),
+    (0x1101, "Serial Port"),
+    (0x1102, "LAN Access Using PPP"),
+    (0x1103, "Dialup Networking"),
+    (0x1104, "IrMC Sync"),
+    (0x1105, "OBEX Object Push"),
+    (0x1106, "OBEX File Transfer"),
+    (0x1107, "IrMC Sync Command"),
+    (0x1108, "Headset"),
+    (0x1109, "Cordless Telephony"),
+    (0x110a, "Audio Source"),
+    (0x110b, "Audio Sink"),
+    (0x110c, "A/V Remote Control Target"),
+    (0x110d, "Advanced Audio Distribution"),
+    (0x110e, "A/V Remote Control"),
+    (0x110f, "A/V Remote Control Controller"),
+    (0x1110, "Intercom"),
+    (0x1111, "Fax"),
+    (0x1112, "Headset - Audio Gateway"),
+    (0x1113, "WAP"),
+    (0x1114, "WAP Client"),
+    (0x1115, "PANU"),
+    (0x1116, "NAP"),
+    (0x1117, "GN"),
+    (0x1118, "Direct Printing"),
+    (0x1119, "Reference Printing"),
+    (0x111a, "Basic Imaging Profile"),
+    (0x111b, "Imaging Responder"),
+    (0x111c, "Imaging Automatic Archive"),
+    (0x111d, "Imaging Referenced Objects"),
+    (0x111e, "Handsfree"),
+    (0x111f, "Handsfree Audio Gateway"),
+    (0x1120, "Direct Printing Reference Objects Service"),
+    (0x1121, "Reflected UI"),
+    (0x1122, "Basic Printing"),
+    (0x1123, "Printing Status"),
+    (0x1124, "Human Interface Device Service"),
+    (0x1125, "Hardcopy Cable Replacement"),
+    (0x1126, "HCR Print"),
+    (0x1127, "HCR Scan"),
+    (0x1128, "Common ISDN Access"),
+    (0x112d, "SIM Access"),
+    (0x112e, "Phonebook Access - PCE"),
+    (0x112f, "Phonebook Access - PSE"),
+    (0x1130, "Phonebook Access"),
+    (0x1131, "Headset - HS"),
+    (0x1132, "Message Access Server"),
+    (0x1133, "Message Notification Server"),
+    (0x1134, "Message Access Profile"),
+    (0x1135, "GNSS"),
+    (0x1136, "GNSS Server"),
+    (0x1137, "3D Display"),
+    (0x1138, "3D Glasses"),
+    (0x1139, "3D Synchronization"),
+    (0x113a, "MPS Profile"),
+    (0x113b, "MPS SC"),
+    (0x113c, "CTN Access Service"),
+    (0x113d, "CTN Notification Service"),
+    (0x113e, "CTN Profile"),
+    (0x1200, "PnP Information"),
+    (0x1201, "Generic Networking"),
+    (0x1202, "Generic File Transfer"),
+    (0x1203, "Generic Audio"),
+    (0x1204, "Generic Telephony"),
+    (0x1205, "UPNP Service"),
+    (0x1206, "UPNP IP Service"),
+    (0x1300, "ESDP UPNP IP PAN"),
+    (0x1301, "ESDP UPNP IP LAP"),
+    (0x1302, "ESDP UPNP L2CAP"),
+    (0x1303, "Video Source"),
+    (0x1304, "Video Sink"),
+    (0x1305, "Video Distribution"),
+    (0x1400, "HDP"),
+    (0x1401, "HDP Source"),
+    (0x1402, "HDP Sink"),
+    (0x1800, "Generic Access"),
+    (0x1801, "Generic Attribute"),
+    (0x1802, "Immediate Alert"),
+    (0x1803, "Link Loss"),
+    (0x1804, "Tx Power"),
+    (0x1805, "Current Time"),
+    (0x1806, "Reference Time Update"),
+    (0x1807, "Next DST Change"),
+    (0x1808, "Glucose"),
+    (0x1809, "Health Thermometer"),
+    (0x180a, "Device Information"),
+    (0x180d, "Heart Rate"),
+    (0x180e, "Phone Alert Status"),
+    (0x180f, "Battery"),
+    (0x1810, "Blood Pressure"),
+    (0x1811, "Alert Notification"),
+    (0x1812, "Human Interface Device"),
+    (0x1813, "Scan Parameters"),
+    (0x1814, "Running Speed and Cadence"),
+    (0x1815, "Automation IO"),
+    (0x1816, "Cycling Speed and Cadence"),
+    (0x1818, "Cycling Power"),
+    (0x1819, "Location and Navigation"),
+    (0x181a, "Environmental Sensing"),
+    (0x181b, "Body Composition"),
+    (0x181c, "User Data"),
+    (0x181d, "Weight Scale"),
+    (0x181e, "Bond Management"),
+    (0x181f, "Continuous Glucose Monitoring"),
+    (0x1820, "Internet Protocol Support"),
+    (0x1821, "Indoor Positioning"),
+    (0x1822, "Pulse Oximeter"),
+    (0x1823, "HTTP Proxy"),
+    (0x1824, "Transport Discovery"),
+    (0x1825, "Object Transfer"),
+    (0x1826, "Fitness Machine"),
+    (0x1827, "Mesh Provisioning"),
+    (0x1828, "Mesh Proxy"),
+    (0x1829, "Reconnection Configuration"),
+    (0x183a, "Insulin Delivery"),
+    (0x183b, "Binary Sensor"),
+    (0x183c, "Emergency Configuration"),
+    (0x183e, "Physical Activity Monitor"),
+    (0x1843, "Audio Input Control"),
+    (0x1844, "Volume Control"),
+    (0x1845, "Volume Offset Control"),
+    (0x1846, "Coordinated Set Identification"),
+    (0x1847, "Device Time"),
+    (0x1848, "Media Control"),
+    (0x1849, "Generic Media Control"),
+    (0x184a, "Constant Tone Extension"),
+    (0x184b, "Telephone Bearer"),
+    (0x184c, "Generic Telephone Bearer"),
+    (0x184d, "Microphone Control"),
+    (0x184e, "Audio Stream Control"),
+    (0x184f, "Broadcast Audio Scan"),
+    (0x1850, "Published Audio Capabilities"),
+    (0x1851, "Basic Audio Announcement"),
+    (0x1852, "Broadcast Audio Announcement"),
+    (0x1853, "Common Audio"),
+    (0x1854, "Hearing Access"),
+    (0x1855, "Telephony and Media Audio"),
+    (0x1856, "Public Broadcast Announcement"),
+    (0x1857, "Electronic Shelf Label"),
+    (0x1858, "Gaming Audio"),
+    (0x1859, "Mesh Proxy Solicitation"),
+    (0x2800, "Primary Service"),
+    (0x2801, "Secondary Service"),
+    (0x2802, "Include"),
+    (0x2803, "Characteristic"),
+    (0x2900, "Characteristic Extended Properties"),
+    (0x2901, "Characteristic User Description"),
+    (0x2902, "Client Characteristic Configuration"),
+    (0x2903, "Server Characteristic Configuration"),
+    (0x2904, "Characteristic Presentation Format"),
+    (0x2905, "Characteristic Aggregate Format"),
+    (0x2906, "Valid Range"),
+    (0x2907, "External Report Reference"),
+    (0x2908, "Report Reference"),
+    (0x2909, "Number of Digitals"),
+    (0x290a, "Value Trigger Setting"),
+    (0x290b, "Environmental Sensing Configuration"),
+    (0x290c, "Environmental Sensing Measurement"),
+    (0x290d, "Environmental Sensing Trigger Setting"),
+    (0x290e, "Time Trigger Setting"),
+    (0x290f, "Complete BR-EDR Transport Block Data"),
+    (0x2a00, "Device Name"),
+    (0x2a01, "Appearance"),
+    (0x2a02, "Peripheral Privacy Flag"),
+    (0x2a03, "Reconnection Address"),
+    (0x2a04, "Peripheral Preferred Connection Parameters"),
+    (0x2a05, "Service Changed"),
+    (0x2a06, "Alert Level"),
+    (0x2a07, "Tx Power Level"),
+    (0x2a08, "Date Time"),
+    (0x2a09, "Day of Week"),
+    (0x2a0a, "Day Date Time"),
+    (0x2a0c, "Exact Time 256"),
+    (0x2a0d, "DST Offset"),
+    (0x2a0e, "Time Zone"),
+    (0x2a0f, "Local Time Information"),
+    (0x2a11, "Time with DST"),
+    (0x2a12, "Time Accuracy"),
+    (0x2a13, "Time Source"),
+    (0x2a14, "Reference Time Information"),
+    (0x2a16, "Time Update Control Point"),
+    (0x2a17, "Time Update State"),
+    (0x2a18, "Glucose Measurement"),
+    (0x2a19, "Battery Level"),
+    (0x2a1c, "Temperature Measurement"),
+    (0x2a1d, "Temperature Type"),
+    (0x2a1e, "Intermediate Temperature"),
+    (0x2a21, "Measurement Interval"),
+    (0x2a22, "Boot Keyboard Input Report"),
+    (0x2a23, "System ID"),
+    (0x2a24, "Model Number String"),
+    (0x2a25, "Serial Number String"),
+    (0x2a26, "Firmware Revision String"),
+    (0x2a27, "Hardware Revision String"),
+    (0x2a28, "Software Revision String"),
+    (0x2a29, "Manufacturer Name String"),
+    (
+        0x2a2a,
+        "IEEE 11073-20601 Regulatory Certification Data List",
+    ),
+    (0x2a2b, "Current Time"),
+    (0x2a2c, "Magnetic Declination"),
+    (0x2a31, "Scan Refresh"),
+    (0x2a32, "Boot Keyboard Output Report"),
+    (0x2a33, "Boot Mouse Input Report"),
+    (0x2a34, "Glucose Measurement Context"),
+    (0x2a35, "Blood Pressure Measurement"),
+    (0x2a36, "Intermediate Cuff Pressure"),
+    (0x2a37, "Heart Rate Measurement"),
+    (0x2a38, "Body Sensor Location"),
+    (0x2a39, "Heart Rate Control Point"),
+    (0x2a3f, "Alert Status"),
+    (0x2a40, "Ringer Control Point"),
+    (0x2a41, "Ringer Setting"),
+    (0x2a42, "Alert Category ID Bit Mask"),
+    (0x2a43, "Alert Category ID"),
+    (0x2a44, "Alert Notification Control Point"),
+    (0x2a45, "Unread Alert Status"),
+    (0x2a46, "New Alert"),
+    (0x2a47, "Supported New Alert Category"),
+    (0x2a48, "Supported Unread Alert Category"),
+    (0x2a49, "Blood Pressure Feature"),
+    (0x2a4a, "HID Information"),
+    (0x2a4b, "Report Map"),
+    (0x2a4c, "HID Control Point"),
+    (0x2a4d, "Report"),
+    (0x2a4e, "Protocol Mode"),
+    (0x2a4f, "Scan Interval Window"),
+    (0x2a50, "PnP ID"),
+    (0x2a51, "Glucose Feature"),
+    (0x2a52, "Record Access Control Point"),
+    (0x2a53, "RSC Measurement"),
+    (0x2a54, "RSC Feature"),
+    (0x2a55, "SC Control Point"),
+    (0x2a5b, "CSC Measurement"),
+    (0x2a5c, "CSC Feature"),
+    (0x2a5d, "Sensor Location"),
+    (0x2a63, "Cycling Power Measurement"),
+    (0x2a64, "Cycling Power Vector"),
+    (0x2a65, "Cycling Power Feature"),
+    (0x2a66, "Cycling Power Control Point"),
+    (0x2a67, "Location and Speed"),
+    (0x2a68, "Navigation"),
+    (0x2a6d, "Pressure"),
+    (0x2a6e, "Temperature"),
+    (0x2a6f, "Humidity"),
+    (0x2a9d, "Weight Measurement"),
+    (0x2a9e, "Weight Scale Feature"),
+    (0x2aa6, "Central Address Resolution"),
+    (0x2ac9, "Resolvable Private Address Only"),
+    (0x2b29, "Client Supported Features"),
+    (0x2b2a, "Database Hash"),
+    (0x2b3a, "Server Supported Features"),
+    (0xfd6f, "Exposure Notification"),
+    (0xfe2c, "Google Fast Pair"),
+    (0xfeaa, "Google Eddystone"),
+];
+
+// core/appearance_values.yaml
+const APPEARANCE_NAMES: &[(u16, &str)] = &[
+    (0x0000, "Unknown"),
+    (0x0040, "Generic Phone"),
+    (0x0080, "Generic Computer"),
+    (0x0081, "Desktop Workstation"),
+    (0x0082, "Server-class Computer"),
+    (0x0083, "Laptop"),
+    (0x0084, "Handheld PC/PDA (clamshell)"),
+    (0x0085, "Palm-size PC/PDA"),
+    (0x0086, "Wearable computer (watch size)"),
+    (0x0087, "Tablet"),
+    (0x0088, "Docking Station"),
+    (0x0089, "All in One"),
+    (0x008a, "Blade Server"),
+    (0x008b, "Convertible"),
+    (0x008c, "Detachable"),
+    (0x008d, "IoT Gateway"),
+    (0x008e, "Mini PC"),
+    (0x008f, "Stick PC"),
+    (0x00c0, "Generic Watch"),
+    (0x00c1, "Sports Watch"),
+    (0x00c2, "Smartwatch"),
+    (0x0100, "Generic Clock"),
+    (0x0140, "Generic Display"),
+    (0x0180, "Generic Remote Control"),
+    (0x01c0, "Generic Eye-glasses"),
+    (0x0200, "Generic Tag"),
+    (0x0240, "Generic Keyring"),
+    (0x0280, "Generic Media Player"),
+    (0x02c0, "Generic Barcode Scanner"),
+    (0x0300, "Generic Thermometer"),
+    (0x0301, "Ear Thermometer"),
+    (0x0340, "Generic Heart Rate Sensor"),
+    (0x0341, "Heart Rate Belt"),
+    (0x0380, "Generic Blood Pressure"),
+    (0x0381, "Arm Blood Pressure"),
+    (0x0382, "Wrist Blood Pressure"),
+    (0x03c0, "Generic Human Interface Device"),
+    (0x03c1, "Keyboard"),
+    (0x03c2, "Mouse"),
+    (0x03c3, "Joystick"),
+    (0x03c4, "Gamepad"),
+    (0x03c5, "Digitizer Tablet"),
+    (0x03c6, "Card Reader"),
+    (0x03c7, "Digital Pen"),
+    (0x03c8, "Barcode Scanner"),
+    (0x03c9, "Touchpad"),
+    (0x03ca, "Presentation Remote"),
+    (0x0400, "Generic Glucose Meter"),
+    (0x0440, "Generic Running Walking Sensor"),
+    (0x0441, "In-Shoe Running Walking Sensor"),
+    (0x0442, "On-Shoe Running Walking Sensor"),
+    (0x0443, "On-Hip Running Walking Sensor"),
+    (0x0480, "Generic Cycling"),
+    (0x0481, "Cycling Computer"),
+    (0x0482, "Speed Sensor"),
+    (0x0483, "Cadence Sensor"),
+    (0x0484, "Power Sensor"),
+    (0x0485, "Speed and Cadence Sensor"),
+    (0x0840, "Generic Audio Sink"),
+    (0x0841, "Standalone Speaker"),
+    (0x0842, "Soundbar"),
+    (0x0843, "Bookshelf Speaker"),
+    (0x0844, "Standmounted Speaker"),
+    (0x0845, "Speakerphone"),
+    (0x0880, "Generic Audio Source"),
+    (0x0881, "Microphone"),
+    (0x0882, "Alarm"),
+    (0x0883, "Bell"),
+    (0x0884, "Horn"),
+    (0x0885, "Broadcasting Device"),
+    (0x0886, "Service Desk"),
+    (0x0887, "Kiosk"),
+    (0x0888, "Broadcasting Room"),
+    (0x0889, "Auditorium"),
+    (0x0940, "Generic Wearable Audio Device"),
+    (0x0941, "Earbud"),
+    (0x0942, "Headset"),
+    (0x0943, "Headphones"),
+    (0x0944, "Neck Band"),
+    (0x0a40, "Generic Hearing aid"),
+    (0x0a41, "In-ear hearing aid"),
+    (0x0a42, "Behind-ear hearing aid"),
+    (0x0a43, "Cochlear Implant"),
+    (0x0a80, "Generic Gaming"),
+    (0x0a81, "Home Video Game Console"),
+    (0x0a82, "Portable handheld console"),
+    (0x0c40, "Generic Pulse Oximeter"),
+    (0x0c41, "Fingertip Pulse Oximeter"),
+    (0x0c42, "Wrist Worn Pulse Oximeter"),
+    (0x0c80, "Generic Weight Scale"),
+    (0x1440, "Generic Outdoor Sports Activity"),
+    (0x1441, "Location Display"),
+    (0x1442, "Location and Navigation Display"),
+    (0x1443, "Location Pod"),
+    (0x1444, "Location and Navigation Pod"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_sorted<T: Ord + Copy + std::fmt::Debug>(table: &[(T, &str)]) {
+        for pair in table.windows(2) {
+            assert!(pair[0].0 < pair[1].0, "{:?} is out of order", pair[1]);
+        }
+    }
+
+    #[test]
+    fn tables_are_sorted() {
+        assert_sorted(COMPANY_IDENTIFIERS);
+        assert_sorted(UUID16_NAMES);
+        assert_sorted(APPEARANCE_NAMES);
+    }
+
+    #[test]
+    fn lookup_names_known_values() {
+        assert_eq!(get_company_name(0x004c), "Apple, Inc.");
+        assert_eq!(get_uuid16_name(0x180d), "Heart Rate");
+        assert_eq!(get_uuid32_name(0x0001_180d), "");
+        assert_eq!(
+            get_uuid128_name(0x0000_180d_0000_1000_8000_0080_5f9b_34fb),
+            "Heart Rate"
+        );
+        assert_eq!(get_appearance_name(0x0040), "Generic Phone");
+        // sub-categories missing from the table fall back to the category
+        assert_eq!(get_appearance_name(0x0041), "Phone");
+    }
+}
@@ -0,0 +1,798 @@
+use crate::HostStack;
+use crate::ParseNodeOpt;
+use crate::SummaryKeys;
+
+use crate::ParseBytesNode;
+
+use crate::ad::uuid128_to_string;
+use crate::assigned_numbers::{get_uuid128_name, get_uuid16_name};
+
+mod gatt;
+use gatt::GattDatabase;
+mod values;
+
+#[derive(Default, Debug)]
+pub struct ATTArg {
+    databases: Vec<GattDatabase>,
+    // state of the connections that have ended, kept for the summary
+    closed: Vec<GattDatabase>,
+}
+
+impl ATTArg {
+    /// Returns the GATT database of `acl_handle`, creating it on first use.
+    fn database(&mut self, acl_handle: u16) -> &mut GattDatabase {
+        let index = match self
+            .databases
+            .iter()
+            .position(|database| database.acl_handle == acl_handle)
+        {
+            Some(index) => index,
+            None => {
+                self.databases.push(GattDatabase::new(acl_handle));
+                self.databases.len() - 1
+            }
+        };
+        &mut self.databases[index]
+    }
+
+    /// Closes the GATT database of `acl_handle`, the next connection given
+    /// the handle starts discovery afresh.
+    pub(crate) fn disconnect(&mut self, acl_handle: u16) {
+        let (ended, open): (Vec<GattDatabase>, Vec<GattDatabase>) =
+            std::mem::take(&mut self.databases)
+                .into_iter()
+                .partition(|database| database.acl_handle == acl_handle);
+        self.databases = open;
+        self.closed.extend(ended);
+    }
+
+    pub(crate) fn gatt_summary(&self) -> String {
+        let mut keys = SummaryKeys::default();
+        self.closed
+            .iter()
+            .chain(self.databases.iter())
+            .map(|database| database.summary(&mut keys))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+/// An ATT PDU, carried on the LE fixed channel 0x0004, on a BR/EDR channel
+/// opened on PSM ATT, or as the SDU of an EATT bearer.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq)]
+pub struct ATT {
+    opcode: u8,
+    pdu: AttPdu,
+    context: AttContext,
+}
+
+impl ParseNodeOpt for ATT {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.is_empty() {
+            return None;
+        }
+        let opcode = data[0];
+        let pdu = AttPdu::new(&data[1..], opcode);
+        let context = args
+            .map(|args| {
+                let acl_handle = args.hci_arg.acl_handle();
+                AttContext::new(args.att_arg.database(acl_handle), opcode, &pdu)
+            })
+            .unwrap_or_default();
+        Some(ATT {
+            opcode,
+            pdu,
+            context,
+        })
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let opcode_s = ParseBytesNode::new(start_byte, 1).format(
+            "Opcode",
+            self.opcode,
+            get_att_opcode_name(self.opcode),
+            "",
+        );
+        let pdu_s = self.pdu.as_json(self.opcode, start_byte + 1, &self.context);
+        match pdu_s.is_empty() {
+            true => format!(r#""ATT": {{{}}}"#, opcode_s),
+            false => format!(r#""ATT": {{{}, {}}}"#, opcode_s, pdu_s),
+        }
+    }
+}
+
+/// Names and types for the attribute handles a PDU refers to, looked up in
+/// the GATT database of its connection when the PDU was parsed.
+#[derive(Debug, Default, PartialEq)]
+struct AttContext {
+    labels: Vec<(u16, String, Option<AttUuid>)>,
+    // the handles and attribute type of the request a response answers
+    request_handles: Vec<u16>,
+    request_type: Option<AttUuid>,
+}
+
+impl AttContext {
+    fn new(database: &mut GattDatabase, opcode: u8, pdu: &AttPdu) -> Self {
+        let request = database.update(opcode, pdu);
+        let request_handles = request
+            .as_ref()
+            .map(|(_, request)| request.handles())
+            .unwrap_or_default();
+        let request_type = match request {
+            Some((_, AttPdu::HandleRange { uuid, .. })) => uuid,
+            _ => None,
+        };
+        let labels = pdu
+            .handles()
+            .into_iter()
+            .chain(request_handles.iter().copied())
+            .map(|handle| (handle, database.label(handle), database.uuid(handle)))
+            .collect();
+        AttContext {
+            labels,
+            request_handles,
+            request_type,
+        }
+    }
+
+    fn label(&self, handle: u16) -> &str {
+        self.labels
+            .iter()
+            .find(|(known, _, _)| *known == handle)
+            .map(|(_, label, _)| label.as_str())
+            .unwrap_or_default()
+    }
+
+    fn uuid(&self, handle: u16) -> Option<AttUuid> {
+        self.labels
+            .iter()
+            .find(|(known, _, _)| *known == handle)
+            .and_then(|(_, _, uuid)| *uuid)
+    }
+
+    /// The label of the `index`th handle of the request a response answers.
+    fn request_label(&self, index: usize) -> &str {
+        self.request_handles
+            .get(index)
+            .map(|handle| self.label(*handle))
+            .unwrap_or_default()
+    }
+
+    fn request_uuid(&self, index: usize) -> Option<AttUuid> {
+        self.request_handles
+            .get(index)
+            .and_then(|handle| self.uuid(*handle))
+    }
+}
+
+/// Appends the decoded form of a value to its fields, when its type is known
+/// and has a decoder.
+fn push_decoded_value(fields: &mut Vec<String>, uuid: Option<AttUuid>, value: &[u8], start: u16) {
+    let decoded_s = uuid
+        .map(|uuid| values::value_as_json(uuid, value, start))
+        .unwrap_or_default();
+    if !decoded_s.is_empty() {
+        fields.push(decoded_s);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum AttPdu {
+    // PDUs without parameters, and anything too short to decode
+    Empty,
+    // 0x01
+    ErrorRsp {
+        request_opcode: u8,
+        handle: u16,
+        error_code: u8,
+    },
+    // 0x02, 0x03
+    Mtu(u16),
+    // 0x04, 0x06, 0x08, 0x10
+    HandleRange {
+        start: u16,
+        end: u16,
+        uuid: Option<AttUuid>,
+        value: Vec<u8>,
+    },
+    // 0x05
+    FindInfoRsp {
+        format: u8,
+        list: Vec<(u16, AttUuid)>,
+    },
+    // 0x07
+    HandlesInfoList(Vec<(u16, u16)>),
+    // 0x09, 0x11
+    AttributeDataList {
+        length: u8,
+        list: Vec<AttributeData>,
+    },
+    // 0x0a, 0x0c, 0x12, 0x16, 0x17, 0x1b, 0x1d, 0x52, 0xd2
+    HandleValue {
+        handle: u16,
+        offset: Option<u16>,
+        value: Vec<u8>,
+        signature: Option<Vec<u8>>,
+    },
+    // 0x0b, 0x0d, 0x0f
+    Value(Vec<u8>),
+    // 0x0e, 0x20
+    Handles(Vec<u16>),
+    // 0x18
+    Flags(u8),
+    // 0x21
+    LengthValueList(Vec<Vec<u8>>),
+    // 0x23
+    HandleLengthValueList(Vec<(u16, Vec<u8>)>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct AttributeData {
+    handle: u16,
+    end_group_handle: Option<u16>,
+    value: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum AttUuid {
+    Uuid16(u16),
+    Uuid128(u128),
+}
+
+impl AttUuid {
+    fn new(data: &[u8]) -> Option<Self> {
+        match data.len() {
+            2 => Some(AttUuid::Uuid16(u16::from_le_bytes([data[0], data[1]]))),
+            16 => Some(AttUuid::Uuid128(u128::from_le_bytes(
+                data.try_into().unwrap(),
+            ))),
+            _ => None,
+        }
+    }
+
+    /// The 16-bit form of the UUID, if it has one.
+    fn as_uuid16(&self) -> Option<u16> {
+        const BASE_UUID: u128 = 0x0000_0000_0000_1000_8000_0080_5f9b_34fb;
+        match self {
+            AttUuid::Uuid16(uuid) => Some(*uuid),
+            AttUuid::Uuid128(uuid) if uuid & ((1 << 96) - 1) == BASE_UUID => {
+                u16::try_from(uuid >> 96).ok()
+            }
+            AttUuid::Uuid128(_) => None,
+        }
+    }
+
+    fn len(&self) -> u16 {
+        match self {
+            AttUuid::Uuid16(_) => 2,
+            AttUuid::Uuid128(_) => 16,
+        }
+    }
+
+    fn format(&self, key: &str, start_byte: u16) -> String {
+        match self {
+            AttUuid::Uuid16(uuid) => {
+                ParseBytesNode::new(start_byte, 2).format(key, uuid, get_uuid16_name(*uuid), "")
+            }
+            AttUuid::Uuid128(uuid) => ParseBytesNode::new(start_byte, 16).format(
+                key,
+                uuid128_to_string(*uuid),
+                get_uuid128_name(*uuid),
+                "",
+            ),
+        }
+    }
+}
+
+fn handle_at(data: &[u8], index: usize) -> u16 {
+    u16::from_le_bytes([data[index], data[index + 1]])
+}
+
+impl AttPdu {
+    /// The attribute handles the PDU names, not counting handle ranges.
+    fn handles(&self) -> Vec<u16> {
+        match self {
+            AttPdu::ErrorRsp { handle, .. } | AttPdu::HandleValue { handle, .. } => vec![*handle],
+            AttPdu::FindInfoRsp { list, .. } => list.iter().map(|(handle, _)| *handle).collect(),
+            AttPdu::HandlesInfoList(list) => list.iter().map(|(found, _)| *found).collect(),
+            AttPdu::AttributeDataList { list, .. } => list.iter().map(|item| item.handle).collect(),
+            AttPdu::Handles(handles) => handles.clone(),
+            AttPdu::HandleLengthValueList(list) => list.iter().map(|(handle, _)| *handle).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn new(data: &[u8], opcode: u8) -> Self {
+        let pdu = match opcode {
+            0x01 if data.len() >= 4 => Some(AttPdu::ErrorRsp {
+                request_opcode: data[0],
+                handle: handle_at(data, 1),
+                error_code: data[3],
+            }),
+            0x02 | 0x03 if data.len() >= 2 => Some(AttPdu::Mtu(handle_at(data, 0))),
+            0x04 | 0x06 | 0x08 | 0x10 if data.len() >= 4 => {
+                let (uuid, value) = match opcode {
+                    0x04 => (None, &data[4..]),
+                    0x06 => (
+                        AttUuid::new(&data[4..data.len().min(6)]),
+                        data.get(6..).unwrap_or_default(),
+                    ),
+                    _ => (AttUuid::new(&data[4..]), &data[data.len()..]),
+                };
+                Some(AttPdu::HandleRange {
+                    start: handle_at(data, 0),
+                    end: handle_at(data, 2),
+                    uuid,
+                    value: value.to_vec(),
+                })
+            }
+            0x05 if !data.is_empty() => {
+                let uuid_len = if data[0] == 0x02 { 16 } else { 2 };
+                Some(AttPdu::FindInfoRsp {
+                    format: data[0],
+                    list: data[1..]
+                        .chunks_exact(2 + uuid_len)
+                        .filter_map(|info| {
+                            AttUuid::new(&info[2..]).map(|uuid| (handle_at(info, 0), uuid))
+                        })
+                        .collect(),
+                })
+            }
+            0x07 => Some(AttPdu::HandlesInfoList(
+                data.chunks_exact(4)
+                    .map(|info| (handle_at(info, 0), handle_at(info, 2)))
+                    .collect(),
+            )),
+            0x09 | 0x11 if !data.is_empty() => {
+                let length = data[0] as usize;
+                let header_len = if opcode == 0x11 { 4 } else { 2 };
+                let list = match length >= header_len {
+                    true => data[1..]
+                        .chunks_exact(length)
+                        .map(|item| AttributeData {
+                            handle: handle_at(item, 0),
+                            end_group_handle: (opcode == 0x11).then(|| handle_at(item, 2)),
+                            value: item[header_len..].to_vec(),
+                        })
+                        .collect(),
+                    false => Vec::new(),
+                };
+                Some(AttPdu::AttributeDataList {
+                    length: data[0],
+                    list,
+                })
+            }
+            0x0a | 0x12 | 0x1b | 0x1d | 0x52 if data.len() >= 2 => Some(AttPdu::HandleValue {
+                handle: handle_at(data, 0),
+                offset: None,
+                value: data[2..].to_vec(),
+                signature: None,
+            }),
+            0x0c | 0x16 | 0x17 if data.len() >= 4 => Some(AttPdu::HandleValue {
+                handle: handle_at(data, 0),
+                offset: Some(handle_at(data, 2)),
+                value: data[4..].to_vec(),
+                signature: None,
+            }),
+            0xd2 if data.len() >= 14 => Some(AttPdu::HandleValue {
+                handle: handle_at(data, 0),
+                offset: None,
+                value: data[2..data.len() - 12].to_vec(),
+                signature: Some(data[data.len() - 12..].to_vec()),
+            }),
+            0x0b | 0x0d | 0x0f => Some(AttPdu::Value(data.to_vec())),
+            0x0e | 0x20 => Some(AttPdu::Handles(
+                data.chunks_exact(2)
+                    .map(|handle| handle_at(handle, 0))
+                    .collect(),
+            )),
+            0x18 if !data.is_empty() => Some(AttPdu::Flags(data[0])),
+            0x21 => {
+                let mut list = Vec::new();
+                let mut rest = data;
+                while rest.len() >= 2 {
+                    let length = (handle_at(rest, 0) as usize).min(rest.len() - 2);
+                    list.push(rest[2..2 + length].to_vec());
+                    rest = &rest[2 + length..];
+                }
+                Some(AttPdu::LengthValueList(list))
+            }
+            0x23 => {
+                let mut list = Vec::new();
+                let mut rest = data;
+                while rest.len() >= 4 {
+                    let length = (handle_at(rest, 2) as usize).min(rest.len() - 4);
+                    list.push((handle_at(rest, 0), rest[4..4 + length].to_vec()));
+                    rest = &rest[4 + length..];
+                }
+                Some(AttPdu::HandleLengthValueList(list))
+            }
+            _ => None,
+        };
+        pdu.unwrap_or(AttPdu::Empty)
+    }
+
+    fn as_json(&self, opcode: u8, start_byte: u16, context: &AttContext) -> String {
+        match self {
+            AttPdu::Empty => String::new(),
+            AttPdu::ErrorRsp {
+                request_opcode,
+                handle,
+                error_code,
+            } => {
+                let request_opcode_s = ParseBytesNode::new(start_byte, 1).format(
+                    "Request Opcode In Error",
+                    request_opcode,
+                    get_att_opcode_name(*request_opcode),
+                    "",
+                );
+                let handle_s = ParseBytesNode::new(start_byte + 1, 2).format(
+                    "Attribute Handle In Error",
+                    handle,
+                    context.label(*handle),
+                    "",
+                );
+                let error_code_s = ParseBytesNode::new(start_byte + 3, 1).format(
+                    "Error Code",
+                    error_code,
+                    get_att_error_name(*error_code),
+                    "",
+                );
+                format!("{}, {}, {}", request_opcode_s, handle_s, error_code_s)
+            }
+            AttPdu::Mtu(mtu) => {
+                let name = match opcode {
+                    0x02 => "Client Rx MTU",
+                    _ => "Server Rx MTU",
+                };
+                ParseBytesNode::new(start_byte, 2).format(name, mtu, &mtu.to_string(), "")
+            }
+            AttPdu::HandleRange {
+                start,
+                end,
+                uuid,
+                value,
+            } => {
+                let mut fields = vec![
+                    ParseBytesNode::new(start_byte, 2).format("Starting Handle", start, "", ""),
+                    ParseBytesNode::new(start_byte + 2, 2).format("Ending Handle", end, "", ""),
+                ];
+                let mut offset = start_byte + 4;
+                if let Some(uuid) = uuid {
+                    let name = match opcode {
+                        0x10 => "Attribute Group Type",
+                        _ => "Attribute Type",
+                    };
+                    fields.push(uuid.format(name, offset));
+                    offset += uuid.len();
+                }
+                if opcode == 0x06 {
+                    fields.push(ParseBytesNode::new(offset, value.len() as u16).format(
+                        "Attribute Value",
+                        &value[..],
+                        "",
+                        "",
+                    ));
+                }
+                fields.join(", ")
+            }
+            AttPdu::FindInfoRsp { format, list } => {
+                let format_name_s = match format {
+                    0x01 => "Handles and 16-bit Bluetooth UUIDs",
+                    0x02 => "Handles and 128-bit UUIDs",
+                    _ => "Reserved for future use",
+                };
+                let mut fields = vec![ParseBytesNode::new(start_byte, 1).format(
+                    "Format",
+                    format,
+                    format_name_s,
+                    "",
+                )];
+                let mut offset = start_byte + 1;
+                for (i, (handle, uuid)) in list.iter().enumerate() {
+                    let handle_s = ParseBytesNode::new(offset, 2).format(
+                        "Handle",
+                        handle,
+                        context.label(*handle),
+                        "",
+                    );
+                    let uuid_s = uuid.format("UUID", offset + 2);
+                    fields.push(format!(
+                        r#""Information Data[{}]": {{{}, {}}}"#,
+                        i, handle_s, uuid_s
+                    ));
+                    offset += 2 + uuid.len();
+                }
+                fields.join(", ")
+            }
+            AttPdu::HandlesInfoList(list) => list
+                .iter()
+                .enumerate()
+                .map(|(i, (found, end))| {
+                    let offset = start_byte + 4 * i as u16;
+                    let found_s = ParseBytesNode::new(offset, 2).format(
+                        "Found Attribute Handle",
+                        found,
+                        context.label(*found),
+                        "",
+                    );
+                    let end_s =
+                        ParseBytesNode::new(offset + 2, 2).format("Group End Handle", end, "", "");
+                    format!(
+                        r#""Handles Information[{}]": {{{}, {}}}"#,
+                        i, found_s, end_s
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(", "),
+            AttPdu::AttributeDataList { length, list } => {
+                let mut fields =
+                    vec![ParseBytesNode::new(start_byte, 1).format("Length", length, "", "")];
+                let mut offset = start_byte + 1;
+                for (i, item) in list.iter().enumerate() {
+                    let mut item_fields = vec![ParseBytesNode::new(offset, 2).format(
+                        "Attribute Handle",
+                        item.handle,
+                        context.label(item.handle),
+                        "",
+                    )];
+                    let mut value_offset = offset + 2;
+                    if let Some(end_group_handle) = item.end_group_handle {
+                        item_fields.push(ParseBytesNode::new(value_offset, 2).format(
+                            "End Group Handle",
+                            end_group_handle,
+                            "",
+                            "",
+                        ));
+                        value_offset += 2;
+                    }
+                    item_fields.push(
+                        ParseBytesNode::new(value_offset, item.value.len() as u16).format(
+                            "Attribute Value",
+                            &item.value[..],
+                            "",
+                            "",
+                        ),
+                    );
+                    // reading a characteristic by its type rather than a declaration
+                    let request_type = context
+                        .request_type
+                        .filter(|uuid| !matches!(uuid, AttUuid::Uuid16(0x2800..=0x2803)));
+                    if opcode == 0x09 {
+                        push_decoded_value(
+                            &mut item_fields,
+                            request_type,
+                            &item.value,
+                            value_offset,
+                        );
+                    }
+                    fields.push(format!(
+                        r#""Attribute Data[{}]": {{{}}}"#,
+                        i,
+                        item_fields.join(", ")
+                    ));
+                    offset += *length as u16;
+                }
+                fields.join(", ")
+            }
+            AttPdu::HandleValue {
+                handle,
+                offset,
+                value,
+                signature,
+            } => {
+                let mut fields = vec![ParseBytesNode::new(start_byte, 2).format(
+                    "Attribute Handle",
+                    handle,
+                    context.label(*handle),
+                    "",
+                )];
+                let mut value_offset = start_byte + 2;
+                if let Some(offset) = offset {
+                    fields.push(ParseBytesNode::new(value_offset, 2).format(
+                        "Value Offset",
+                        offset,
+                        "",
+                        "",
+                    ));
+                    value_offset += 2;
+                }
+                // a Read Request carries nothing but the handle
+                if opcode != 0x0a && opcode != 0x0c {
+                    let name = match opcode {
+                        0x16 | 0x17 => "Part Attribute Value",
+                        _ => "Attribute Value",
+                    };
+                    fields.push(
+                        ParseBytesNode::new(value_offset, value.len() as u16).format(
+                            name,
+                            &value[..],
+                            "",
+                            "",
+                        ),
+                    );
+                    // prepared writes only carry part of the value
+                    if !matches!(opcode, 0x16 | 0x17) {
+                        push_decoded_value(&mut fields, context.uuid(*handle), value, value_offset);
+                    }
+                    value_offset += value.len() as u16;
+                }
+                if let Some(signature) = signature {
+                    fields.push(ParseBytesNode::new(value_offset, 12).format(
+                        "Authentication Signature",
+                        &signature[..],
+                        "",
+                        "",
+                    ));
+                }
+                fields.join(", ")
+            }
+            AttPdu::Value(value) => {
+                let name = match opcode {
+                    0x0d => "Part Attribute Value",
+                    0x0f => "Set Of Values",
+                    _ => "Attribute Value",
+                };
+                let mut fields = vec![ParseBytesNode::new(start_byte, value.len() as u16).format(
+                    name,
+                    &value[..],
+                    context.request_label(0),
+                    "",
+                )];
+                if opcode == 0x0b {
+                    push_decoded_value(&mut fields, context.request_uuid(0), value, start_byte);
+                }
+                fields.join(", ")
+            }
+            AttPdu::Handles(handles) => handles
+                .iter()
+                .enumerate()
+                .map(|(i, handle)| {
+                    ParseBytesNode::new(start_byte + 2 * i as u16, 2).format(
+                        &format!("Attribute Handle[{}]", i),
+                        handle,
+                        context.label(*handle),
+                        "",
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(", "),
+            AttPdu::Flags(flags) => {
+                let flags_name_s = match flags {
+                    0x00 => "Cancel all prepared writes",
+                    0x01 => "Immediately write all pending prepared values",
+                    _ => "Reserved for future use",
+                };
+                ParseBytesNode::new(start_byte, 1).format("Flags", flags, flags_name_s, "")
+            }
+            AttPdu::LengthValueList(list) => {
+                let mut offset = start_byte;
+                list.iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        let length_s = ParseBytesNode::new(offset, 2).format(
+                            "Value Length",
+                            value.len() as u16,
+                            "",
+                            "",
+                        );
+                        let value_s = ParseBytesNode::new(offset + 2, value.len() as u16).format(
+                            "Attribute Value",
+                            &value[..],
+                            context.request_label(i),
+                            "",
+                        );
+                        let mut fields = vec![length_s, value_s];
+                        push_decoded_value(&mut fields, context.request_uuid(i), value, offset + 2);
+                        offset += 2 + value.len() as u16;
+                        format!(r#""Length Value Tuple[{}]": {{{}}}"#, i, fields.join(", "))
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            }
+            AttPdu::HandleLengthValueList(list) => {
+                let mut offset = start_byte;
+                list.iter()
+                    .enumerate()
+                    .map(|(i, (handle, value))| {
+                        let handle_s = ParseBytesNode::new(offset, 2).format(
+                            "Attribute Handle",
+                            handle,
+                            context.label(*handle),
+                            "",
+                        );
+                        let length_s = ParseBytesNode::new(offset + 2, 2).format(
+                            "Value Length",
+                            value.len() as u16,
+                            "",
+                            "",
+                        );
+                        let value_s = ParseBytesNode::new(offset + 4, value.len() as u16).format(
+                            "Attribute Value",
+                            &value[..],
+                            "",
+                            "",
+                        );
+                        let mut fields = vec![handle_s, length_s, value_s];
+                        push_decoded_value(&mut fields, context.uuid(*handle), value, offset + 4);
+                        offset += 4 + value.len() as u16;
+                        format!(
+                            r#""Handle Length Value Tuple[{}]": {{{}}}"#,
+                            i,
+                            fields.join(", ")
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            }
+        }
+    }
+}
+
+fn get_att_opcode_name(opcode: u8) -> &'static str {
+    match opcode {
+        0x01 => "ATT_ERROR_RSP",
+        0x02 => "ATT_EXCHANGE_MTU_REQ",
+        0x03 => "ATT_EXCHANGE_MTU_RSP",
+        0x04 => "ATT_FIND_INFORMATION_REQ",
+        0x05 => "ATT_FIND_INFORMATION_RSP",
+        0x06 => "ATT_FIND_BY_TYPE_VALUE_REQ",
+        0x07 => "ATT_FIND_BY_TYPE_VALUE_RSP",
+        0x08 => "ATT_READ_BY_TYPE_REQ",
+        0x09 => "ATT_READ_BY_TYPE_RSP",
+        0x0a => "ATT_READ_REQ",
+        0x0b => "ATT_READ_RSP",
+        0x0c => "ATT_READ_BLOB_REQ",
+        0x0d => "ATT_READ_BLOB_RSP",
+        0x0e => "ATT_READ_MULTIPLE_REQ",
+        0x0f => "ATT_READ_MULTIPLE_RSP",
+        0x10 => "ATT_READ_BY_GROUP_TYPE_REQ",
+        0x11 => "ATT_READ_BY_GROUP_TYPE_RSP",
+        0x12 => "ATT_WRITE_REQ",
+        0x13 => "ATT_WRITE_RSP",
+        0x16 => "ATT_PREPARE_WRITE_REQ",
+        0x17 => "ATT_PREPARE_WRITE_RSP",
+        0x18 => "ATT_EXECUTE_WRITE_REQ",
+        0x19 => "ATT_EXECUTE_WRITE_RSP",
+        0x1b => "ATT_HANDLE_VALUE_NTF",
+        0x1d => "ATT_HANDLE_VALUE_IND",
+        0x1e => "ATT_HANDLE_VALUE_CFM",
+        0x20 => "ATT_READ_MULTIPLE_VARIABLE_REQ",
+        0x21 => "ATT_READ_MULTIPLE_VARIABLE_RSP",
+        0x23 => "ATT_MULTIPLE_HANDLE_VALUE_NTF",
+        0x52 => "ATT_WRITE_CMD",
+        0xd2 => "ATT_SIGNED_WRITE_CMD",
+        _ => "Unknown",
+    }
+}
+
+fn get_att_error_name(error_code: u8) -> &'static str {
+    match error_code {
+        0x01 => "Invalid Handle",
+        0x02 => "Read Not Permitted",
+        0x03 => "Write Not Permitted",
+        0x04 => "Invalid PDU",
+        0x05 => "Insufficient Authentication",
+        0x06 => "Request Not Supported",
+        0x07 => "Invalid Offset",
+        0x08 => "Insufficient Authorization",
+        0x09 => "Prepare Queue Full",
+        0x0a => "Attribute Not Found",
+        0x0b => "Attribute Not Long",
+        0x0c => "Encryption Key Size Too Short",
+        0x0d => "Invalid Attribute Value Length",
+        0x0e => "Unlikely Error",
+        0x0f => "Insufficient Encryption",
+        0x10 => "Unsupported Group Type",
+        0x11 => "Insufficient Resources",
+        0x12 => "Database Out Of Sync",
+        0x13 => "Value Not Allowed",
+        0x80..=0x9f => "Application Error",
+        0xfc => "Write Request Rejected",
+        0xfd => "Client Characteristic Configuration Descriptor Improperly Configured",
+        0xfe => "Procedure Already in Progress",
+        0xff => "Out of Range",
+        0xe0..=0xfb => "Common Profile and Service Error",
+        _ => "Reserved for future use",
+    }
+}
@@ -0,0 +1,410 @@
+use super::{AttPdu, AttUuid};
+
+use crate::ad::uuid128_to_string;
+use crate::assigned_numbers::{get_uuid128_name, get_uuid16_name};
+use crate::SummaryKeys;
+
+const PRIMARY_SERVICE: AttUuid = AttUuid::Uuid16(0x2800);
+const SECONDARY_SERVICE: AttUuid = AttUuid::Uuid16(0x2801);
+const INCLUDE: AttUuid = AttUuid::Uuid16(0x2802);
+const CHARACTERISTIC: AttUuid = AttUuid::Uuid16(0x2803);
+
+/// The remote GATT database of one connection, as far as the discovery
+/// procedures seen so far have revealed it.
+#[derive(Debug, Default)]
+pub(super) struct GattDatabase {
+    pub(super) acl_handle: u16,
+    services: Vec<GattService>,
+    includes: Vec<GattInclude>,
+    characteristics: Vec<GattCharacteristic>,
+    descriptors: Vec<GattDescriptor>,
+    // requests waiting for their response. Each client has one outstanding
+    // per bearer, and both ends of a connection can be a client, so a
+    // response is matched to the request of its own opcode.
+    pending: Vec<(u8, AttPdu)>,
+}
+
+// more outstanding requests than this means responses were missed
+const MAX_PENDING: usize = 8;
+
+#[derive(Debug)]
+struct GattService {
+    start: u16,
+    end: u16,
+    uuid: AttUuid,
+    primary: bool,
+}
+
+#[derive(Debug)]
+struct GattInclude {
+    handle: u16,
+    start: u16,
+    end: u16,
+    uuid: Option<AttUuid>,
+}
+
+#[derive(Debug)]
+struct GattCharacteristic {
+    handle: u16,
+    properties: u8,
+    value_handle: u16,
+    uuid: AttUuid,
+}
+
+#[derive(Debug)]
+struct GattDescriptor {
+    handle: u16,
+    uuid: AttUuid,
+}
+
+impl GattDatabase {
+    pub(super) fn new(acl_handle: u16) -> Self {
+        GattDatabase {
+            acl_handle,
+            ..Default::default()
+        }
+    }
+
+    /// Follows the request/response flow and records what discovery
+    /// responses reveal. Returns the request a response answers.
+    pub(super) fn update(&mut self, opcode: u8, pdu: &AttPdu) -> Option<(u8, AttPdu)> {
+        match opcode {
+            0x04 | 0x06 | 0x08 | 0x0a | 0x0c | 0x0e | 0x10 | 0x12 | 0x16 | 0x18 | 0x20 => {
+                // a client sends a request only once the previous one is answered
+                self.pending.retain(|(request, _)| *request != opcode);
+                if self.pending.len() == MAX_PENDING {
+                    self.pending.remove(0);
+                }
+                self.pending.push((opcode, pdu.clone()));
+                return None;
+            }
+            0x01 | 0x05 | 0x07 | 0x09 | 0x0b | 0x0d | 0x0f | 0x11 | 0x13 | 0x17 | 0x19 | 0x21 => {}
+            _ => return None,
+        }
+        let request_opcode = match pdu {
+            AttPdu::ErrorRsp { request_opcode, .. } => *request_opcode,
+            _ => opcode - 1,
+        };
+        let pending = self
+            .pending
+            .iter()
+            .position(|(request, _)| *request == request_opcode)
+            .map(|index| self.pending.remove(index));
+        let request_type = match &pending {
+            Some((_, AttPdu::HandleRange { uuid, .. })) => *uuid,
+            _ => None,
+        };
+        let is_service_discovery =
+            request_type == Some(PRIMARY_SERVICE) || request_type == Some(SECONDARY_SERVICE);
+        match pdu {
+            AttPdu::AttributeDataList { list, .. } if opcode == 0x11 && is_service_discovery => {
+                for item in list.iter() {
+                    if let (Some(end), Some(uuid)) =
+                        (item.end_group_handle, AttUuid::new(&item.value))
+                    {
+                        self.add_service(GattService {
+                            start: item.handle,
+                            end,
+                            uuid,
+                            primary: request_type == Some(PRIMARY_SERVICE),
+                        });
+                    }
+                }
+            }
+            // discovering primary services by UUID
+            AttPdu::HandlesInfoList(list) if request_type == Some(PRIMARY_SERVICE) => {
+                let uuid = match &pending {
+                    Some((_, AttPdu::HandleRange { value, .. })) => AttUuid::new(value),
+                    _ => None,
+                };
+                if let Some(uuid) = uuid {
+                    for (start, end) in list.iter() {
+                        self.add_service(GattService {
+                            start: *start,
+                            end: *end,
+                            uuid,
+                            primary: true,
+                        });
+                    }
+                }
+            }
+            AttPdu::AttributeDataList { list, .. } if request_type == Some(INCLUDE) => {
+                for item in list.iter().filter(|item| item.value.len() >= 4) {
+                    self.includes
+                        .retain(|include| include.handle != item.handle);
+                    self.includes.push(GattInclude {
+                        handle: item.handle,
+                        start: u16::from_le_bytes([item.value[0], item.value[1]]),
+                        end: u16::from_le_bytes([item.value[2], item.value[3]]),
+                        uuid: AttUuid::new(&item.value[4..]),
+                    });
+                }
+            }
+            AttPdu::AttributeDataList { list, .. } if request_type == Some(CHARACTERISTIC) => {
+                for item in list.iter().filter(|item| item.value.len() >= 3) {
+                    if let Some(uuid) = AttUuid::new(&item.value[3..]) {
+                        self.characteristics
+                            .retain(|characteristic| characteristic.handle != item.handle);
+                        self.characteristics.push(GattCharacteristic {
+                            handle: item.handle,
+                            properties: item.value[0],
+                            value_handle: u16::from_le_bytes([item.value[1], item.value[2]]),
+                            uuid,
+                        });
+                    }
+                }
+                self.characteristics
+                    .sort_by_key(|characteristic| characteristic.handle);
+            }
+            AttPdu::FindInfoRsp { list, .. } => {
+                for (handle, uuid) in list.iter() {
+                    let is_declaration = matches!(uuid, AttUuid::Uuid16(0x2800..=0x2803));
+                    let is_value = self
+                        .characteristics
+                        .iter()
+                        .any(|characteristic| characteristic.value_handle == *handle);
+                    if !is_declaration && !is_value {
+                        self.descriptors
+                            .retain(|descriptor| descriptor.handle != *handle);
+                        self.descriptors.push(GattDescriptor {
+                            handle: *handle,
+                            uuid: *uuid,
+                        });
+                    }
+                }
+                self.descriptors.sort_by_key(|descriptor| descriptor.handle);
+            }
+            _ => {}
+        }
+        pending
+    }
+
+    fn add_service(&mut self, service: GattService) {
+        self.services.retain(|known| known.start != service.start);
+        self.services.push(service);
+        self.services.sort_by_key(|service| service.start);
+    }
+
+    fn service(&self, handle: u16) -> Option<&GattService> {
+        self.services
+            .iter()
+            .find(|service| service.start <= handle && handle <= service.end)
+    }
+
+    /// The characteristic whose value or descriptors `handle` belongs to.
+    fn characteristic(&self, handle: u16) -> Option<&GattCharacteristic> {
+        let service = self.service(handle);
+        self.characteristics.iter().rev().find(|characteristic| {
+            let in_service = match service {
+                Some(service) => service.start <= characteristic.handle,
+                None => true,
+            };
+            characteristic.value_handle <= handle && in_service
+        })
+    }
+
+    /// The type of the attribute at `handle`, for characteristic values and
+    /// descriptors.
+    pub(super) fn uuid(&self, handle: u16) -> Option<AttUuid> {
+        self.characteristics
+            .iter()
+            .find(|characteristic| characteristic.value_handle == handle)
+            .map(|characteristic| characteristic.uuid)
+            .or_else(|| {
+                self.descriptors
+                    .iter()
+                    .find(|descriptor| descriptor.handle == handle)
+                    .map(|descriptor| descriptor.uuid)
+            })
+    }
+
+    /// Names an attribute handle as "Service → Characteristic", adding the
+    /// descriptor when the handle is one. Empty when nothing is known of it.
+    pub(super) fn label(&self, handle: u16) -> String {
+        let mut names = Vec::new();
+        if let Some(service) = self.service(handle) {
+            names.push(get_uuid_name(&service.uuid));
+        }
+        let descriptor = self
+            .descriptors
+            .iter()
+            .find(|descriptor| descriptor.handle == handle);
+        if let Some(characteristic) = self.characteristic(handle) {
+            if characteristic.value_handle == handle || descriptor.is_some() {
+                names.push(get_uuid_name(&characteristic.uuid));
+            }
+        }
+        if let Some(descriptor) = descriptor {
+            names.push(get_uuid_name(&descriptor.uuid));
+        }
+        names.join(" → ")
+    }
+
+    pub(super) fn summary(&self, keys: &mut SummaryKeys) -> String {
+        let services_s = self
+            .services
+            .iter()
+            .enumerate()
+            .map(|(i, service)| {
+                format!(r#""Service[{}]": {{{}}}"#, i, self.service_summary(service))
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        let key = keys.unique(format!("Connection {:#x}", self.acl_handle));
+        format!(r#""{}": {{{}}}"#, key, services_s)
+    }
+
+    fn service_summary(&self, service: &GattService) -> String {
+        let mut fields = vec![
+            format!(r#""UUID": "{}""#, get_uuid_name(&service.uuid)),
+            format!(
+                r#""Type": "{}""#,
+                match service.primary {
+                    true => "Primary",
+                    false => "Secondary",
+                }
+            ),
+            format!(
+                r#""Handles": "{:#06x}-{:#06x}""#,
+                service.start, service.end
+            ),
+        ];
+        let in_service = |handle: u16| service.start <= handle && handle <= service.end;
+        for (i, include) in self
+            .includes
+            .iter()
+            .filter(|include| in_service(include.handle))
+            .enumerate()
+        {
+            let uuid_s = include
+                .uuid
+                .or_else(|| {
+                    self.services
+                        .iter()
+                        .find(|service| service.start == include.start)
+                        .map(|service| service.uuid)
+                })
+                .map(|uuid| get_uuid_name(&uuid))
+                .unwrap_or_default();
+            fields.push(format!(
+                r#""Included Service[{}]": {{"UUID": "{}", "Handles": "{:#06x}-{:#06x}"}}"#,
+                i, uuid_s, include.start, include.end
+            ));
+        }
+        for (i, characteristic) in self
+            .characteristics
+            .iter()
+            .filter(|characteristic| in_service(characteristic.handle))
+            .enumerate()
+        {
+            let descriptors_s = self
+                .descriptors
+                .iter()
+                .filter(|descriptor| {
+                    in_service(descriptor.handle)
+                        && self
+                            .characteristic(descriptor.handle)
+                            .map(|owner| owner.handle)
+                            == Some(characteristic.handle)
+                })
+                .enumerate()
+                .map(|(j, descriptor)| {
+                    format!(
+                        r#", "Descriptor[{}]": {{"UUID": "{}", "Handle": "{:#06x}"}}"#,
+                        j,
+                        get_uuid_name(&descriptor.uuid),
+                        descriptor.handle
+                    )
+                })
+                .collect::<String>();
+            fields.push(format!(
+                r#""Characteristic[{}]": {{"UUID": "{}", "Properties": "{}", "Value Handle": "{:#06x}"{}}}"#,
+                i,
+                get_uuid_name(&characteristic.uuid),
+                get_properties_name(characteristic.properties),
+                characteristic.value_handle,
+                descriptors_s
+            ));
+        }
+        fields.join(", ")
+    }
+}
+
+/// The assigned name of a UUID, or the UUID itself when it has none.
+fn get_uuid_name(uuid: &AttUuid) -> String {
+    match uuid {
+        AttUuid::Uuid16(uuid) => match get_uuid16_name(*uuid) {
+            "" => format!("{:#06x}", uuid),
+            name => name.to_string(),
+        },
+        AttUuid::Uuid128(uuid) => match get_uuid128_name(*uuid) {
+            "" => uuid128_to_string(*uuid),
+            name => name.to_string(),
+        },
+    }
+}
+
+fn get_properties_name(properties: u8) -> String {
+    const NAMES: [&str; 8] = [
+        "Broadcast",
+        "Read",
+        "Write Without Response",
+        "Write",
+        "Notify",
+        "Indicate",
+        "Authenticated Signed Writes",
+        "Extended Properties",
+    ];
+    NAMES
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| properties & (1 << bit) != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{hci, str_to_array, HostStack};
+
+    fn gatt_summary(packets: &[&str]) -> String {
+        let mut args = HostStack::new();
+        for packet in packets {
+            hci::parse(&str_to_array(packet), &mut args);
+        }
+        args.gatt_summary()
+    }
+
+    // both ends act as clients: one discovers services while the other
+    // discovers characteristics
+    const SERVICE_REQ: &str = "02 40 20 0b 00 07 00 04 00 10 01 00 ff ff 00 28";
+    const CHARACTERISTIC_REQ: &str = "02 40 20 0b 00 07 00 04 00 08 01 00 ff ff 03 28";
+    const SERVICE_RSP: &str = "02 40 20 0c 00 08 00 04 00 11 06 01 00 05 00 0d 18";
+    const CHARACTERISTIC_RSP: &str = "02 40 20 0d 00 09 00 04 00 09 07 02 00 10 03 00 37 2a";
+
+    #[test]
+    fn responses_match_their_own_request() {
+        let summary = gatt_summary(&[
+            SERVICE_REQ,
+            CHARACTERISTIC_REQ,
+            SERVICE_RSP,
+            CHARACTERISTIC_RSP,
+        ]);
+        assert!(summary.contains(r#""Service[0]": {"UUID": "Heart Rate", "Type": "Primary""#));
+        assert!(summary.contains(r#""Characteristic[0]": {"UUID": "Heart Rate Measurement""#));
+    }
+
+    #[test]
+    fn error_response_only_ends_its_request() {
+        let summary = gatt_summary(&[
+            SERVICE_REQ,
+            CHARACTERISTIC_REQ,
+            // Attribute Not Found for the Read By Type Request
+            "02 40 20 09 00 05 00 04 00 01 08 01 00 0a",
+            SERVICE_RSP,
+        ]);
+        assert!(summary.contains(r#""Service[0]": {"UUID": "Heart Rate", "Type": "Primary""#));
+        assert!(!summary.contains("Characteristic[0]"));
+    }
+}
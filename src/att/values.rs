@@ -0,0 +1,500 @@
+use crate::assigned_numbers::{get_appearance_name, get_company_name};
+use crate::bytes_to_string;
+use crate::hid::report_descriptor_as_json;
+use crate::ParseBitsNode;
+use crate::ParseBytesNode;
+
+use super::AttUuid;
+
+/// Decodes the value of a well-known characteristic or descriptor into
+/// `"Name": {fields}`. Empty when the UUID is not one we know or the value
+/// is too short for it.
+pub(super) fn value_as_json(uuid: AttUuid, value: &[u8], start_byte: u16) -> String {
+    let uuid = match uuid.as_uuid16() {
+        Some(uuid) => uuid,
+        None => return String::new(),
+    };
+    let (name, fields) = match uuid {
+        0x2a00 => ("Device Name", device_name_as_json(value, start_byte)),
+        0x2a01 => ("Appearance", appearance_as_json(value, start_byte)),
+        0x2a05 => (
+            "Service Changed",
+            service_changed_as_json(value, start_byte),
+        ),
+        0x2a19 => ("Battery Level", battery_level_as_json(value, start_byte)),
+        0x2a1c => (
+            "Temperature Measurement",
+            temperature_measurement_as_json(value, start_byte),
+        ),
+        0x2a2b => ("Current Time", current_time_as_json(value, start_byte)),
+        0x2a35 => (
+            "Blood Pressure Measurement",
+            blood_pressure_measurement_as_json(value, start_byte),
+        ),
+        0x2a37 => (
+            "Heart Rate Measurement",
+            heart_rate_measurement_as_json(value, start_byte),
+        ),
+        0x2a4b => ("Report Map", report_descriptor_as_json(value, start_byte)),
+        0x2a50 => ("PnP ID", pnp_id_as_json(value, start_byte)),
+        0x2a5b => (
+            "CSC Measurement",
+            csc_measurement_as_json(value, start_byte),
+        ),
+        0x2902 => (
+            "Client Characteristic Configuration",
+            cccd_as_json(value, start_byte),
+        ),
+        _ => return String::new(),
+    };
+    match fields.is_empty() {
+        true => String::new(),
+        false => format!(r#""{}": {{{}}}"#, name, fields),
+    }
+}
+
+/// Reads the fields a flags byte announces, one after the other, keeping
+/// track of the offset and stopping quietly when the value runs out.
+struct FieldReader<'a> {
+    value: &'a [u8],
+    offset: usize,
+    start_byte: u16,
+    fields: Vec<String>,
+}
+
+impl<'a> FieldReader<'a> {
+    fn new(value: &'a [u8], start_byte: u16) -> Self {
+        FieldReader {
+            value,
+            offset: 0,
+            start_byte,
+            fields: Vec::new(),
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Option<(u16, &'a [u8])> {
+        let bytes = self.value.get(self.offset..self.offset + len)?;
+        let start = self.start_byte + self.offset as u16;
+        self.offset += len;
+        Some((start, bytes))
+    }
+
+    fn u8(&mut self, key: &str, alias: impl Fn(u8) -> String) -> Option<u8> {
+        let (start, bytes) = self.take(1)?;
+        self.fields
+            .push(ParseBytesNode::new(start, 1).format(key, bytes[0], &alias(bytes[0]), ""));
+        Some(bytes[0])
+    }
+
+    fn u16(&mut self, key: &str, alias: impl Fn(u16) -> String) -> Option<u16> {
+        let (start, bytes) = self.take(2)?;
+        let value = u16::from_le_bytes([bytes[0], bytes[1]]);
+        self.fields
+            .push(ParseBytesNode::new(start, 2).format(key, value, &alias(value), ""));
+        Some(value)
+    }
+
+    fn u32(&mut self, key: &str, alias: impl Fn(u32) -> String) -> Option<u32> {
+        let (start, bytes) = self.take(4)?;
+        let value = u32::from_le_bytes(bytes.try_into().unwrap());
+        self.fields
+            .push(ParseBytesNode::new(start, 4).format(key, value, &alias(value), ""));
+        Some(value)
+    }
+
+    /// Splits the field just read into its named bit fields.
+    fn bits(&mut self, key: &str, len: u16, names: &[(u8, u8, &str)]) {
+        let start = self.start_byte + (self.offset - len as usize) as u16;
+        let value = self.value[self.offset - len as usize..self.offset]
+            .iter()
+            .rev()
+            .fold(0u32, |value, byte| (value << 8) | *byte as u32);
+        for (bit, bits_len, name) in names.iter() {
+            let field = (value >> bit) & ((1 << bits_len) - 1);
+            self.fields
+                .push(ParseBitsNode::new(start, len, *bit, *bits_len).format(
+                    &format!("{} {}", key, name),
+                    field,
+                    "",
+                    "",
+                ));
+        }
+    }
+
+    /// The 7 byte Date Time characteristic, as embedded in measurements.
+    fn date_time(&mut self, key: &str) -> Option<()> {
+        let (start, bytes) = self.take(7)?;
+        let date_time_s = format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            u16::from_le_bytes([bytes[0], bytes[1]]),
+            bytes[2],
+            bytes[3],
+            bytes[4],
+            bytes[5],
+            bytes[6]
+        );
+        self.fields
+            .push(ParseBytesNode::new(start, 7).format(key, bytes, &date_time_s, ""));
+        Some(())
+    }
+
+    fn finish(self) -> String {
+        self.fields.join(", ")
+    }
+}
+
+/// IEEE 11073-20601 16-bit SFLOAT: 4-bit exponent, 12-bit mantissa.
+fn sfloat_to_string(value: u16) -> String {
+    match value {
+        0x07ff => "NaN".to_string(),
+        0x0800 => "NRes".to_string(),
+        0x07fe => "+INFINITY".to_string(),
+        0x0802 => "-INFINITY".to_string(),
+        _ => {
+            let mantissa = ((value << 4) as i16 >> 4) as i32;
+            let exponent = (value as i16 >> 12) as i32;
+            scaled_to_string(mantissa, exponent)
+        }
+    }
+}
+
+/// IEEE 11073-20601 32-bit FLOAT: 8-bit exponent, 24-bit mantissa.
+fn float_to_string(value: u32) -> String {
+    match value {
+        0x007f_ffff => "NaN".to_string(),
+        0x0080_0000 => "NRes".to_string(),
+        0x007f_fffe => "+INFINITY".to_string(),
+        0x0080_0002 => "-INFINITY".to_string(),
+        _ => {
+            let mantissa = (value << 8) as i32 >> 8;
+            let exponent = value as i32 >> 24;
+            scaled_to_string(mantissa, exponent)
+        }
+    }
+}
+
+/// mantissa * 10^exponent with as many decimals as the exponent gives, so
+/// that the binary rounding of the product doesn't show.
+fn scaled_to_string(mantissa: i32, exponent: i32) -> String {
+    let value = mantissa as f64 * 10f64.powi(exponent);
+    match exponent < 0 {
+        true => format!("{:.*}", -exponent as usize, value),
+        false => format!("{}", value),
+    }
+}
+
+fn device_name_as_json(value: &[u8], start_byte: u16) -> String {
+    ParseBytesNode::new(start_byte, value.len() as u16).format(
+        "Name",
+        bytes_to_string(value),
+        "",
+        "",
+    )
+}
+
+fn appearance_as_json(value: &[u8], start_byte: u16) -> String {
+    let mut reader = FieldReader::new(value, start_byte);
+    reader.u16("Appearance", |appearance| {
+        get_appearance_name(appearance).to_string()
+    });
+    reader.finish()
+}
+
+fn service_changed_as_json(value: &[u8], start_byte: u16) -> String {
+    let mut reader = FieldReader::new(value, start_byte);
+    reader.u16("Start of Affected Attribute Handle Range", |_| {
+        String::new()
+    });
+    reader.u16("End of Affected Attribute Handle Range", |_| String::new());
+    reader.finish()
+}
+
+fn battery_level_as_json(value: &[u8], start_byte: u16) -> String {
+    let mut reader = FieldReader::new(value, start_byte);
+    reader.u8("Battery Level", |level| format!("{} %", level));
+    reader.finish()
+}
+
+fn temperature_measurement_as_json(value: &[u8], start_byte: u16) -> String {
+    let mut reader = FieldReader::new(value, start_byte);
+    let Some(flags) = reader.u8("Flags", |_| String::new()) else {
+        return String::new();
+    };
+    reader.bits(
+        "Flags",
+        1,
+        &[
+            (0, 1, "Temperature Units"),
+            (1, 1, "Time Stamp Present"),
+            (2, 1, "Temperature Type Present"),
+        ],
+    );
+    let unit = match flags & 0x01 {
+        0 => "Celsius",
+        _ => "Fahrenheit",
+    };
+    reader.u32("Temperature Measurement Value", |temperature| {
+        format!("{} {}", float_to_string(temperature), unit)
+    });
+    if flags & 0x02 != 0 {
+        reader.date_time("Time Stamp");
+    }
+    if flags & 0x04 != 0 {
+        reader.u8("Temperature Type", |temperature_type| {
+            match temperature_type {
+                0x01 => "Armpit",
+                0x02 => "Body (general)",
+                0x03 => "Ear (usually earlobe)",
+                0x04 => "Finger",
+                0x05 => "Gastro-intestinal Tract",
+                0x06 => "Mouth",
+                0x07 => "Rectum",
+                0x08 => "Toe",
+                0x09 => "Tympanum (ear drum)",
+                _ => "Reserved for future use",
+            }
+            .to_string()
+        });
+    }
+    reader.finish()
+}
+
+fn current_time_as_json(value: &[u8], start_byte: u16) -> String {
+    let mut reader = FieldReader::new(value, start_byte);
+    reader.date_time("Date Time");
+    reader.u8("Day of Week", |day| {
+        match day {
+            0 => "Unknown",
+            1 => "Monday",
+            2 => "Tuesday",
+            3 => "Wednesday",
+            4 => "Thursday",
+            5 => "Friday",
+            6 => "Saturday",
+            7 => "Sunday",
+            _ => "Reserved for future use",
+        }
+        .to_string()
+    });
+    reader.u8("Fractions256", |fractions| format!("{}/256 s", fractions));
+    reader.u8("Adjust Reason", |reason| {
+        [
+            "Manual time update",
+            "External reference time update",
+            "Change of time zone",
+            "Change of DST",
+        ]
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| reason & (1 << bit) != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<&str>>()
+        .join(", ")
+    });
+    reader.finish()
+}
+
+fn blood_pressure_measurement_as_json(value: &[u8], start_byte: u16) -> String {
+    let mut reader = FieldReader::new(value, start_byte);
+    let Some(flags) = reader.u8("Flags", |_| String::new()) else {
+        return String::new();
+    };
+    reader.bits(
+        "Flags",
+        1,
+        &[
+            (0, 1, "Blood Pressure Units"),
+            (1, 1, "Time Stamp Present"),
+            (2, 1, "Pulse Rate Present"),
+            (3, 1, "User ID Present"),
+            (4, 1, "Measurement Status Present"),
+        ],
+    );
+    let unit = match flags & 0x01 {
+        0 => "mmHg",
+        _ => "kPa",
+    };
+    for key in ["Systolic", "Diastolic", "Mean Arterial Pressure"] {
+        reader.u16(key, |pressure| {
+            format!("{} {}", sfloat_to_string(pressure), unit)
+        });
+    }
+    if flags & 0x02 != 0 {
+        reader.date_time("Time Stamp");
+    }
+    if flags & 0x04 != 0 {
+        reader.u16("Pulse Rate", |rate| {
+            format!("{} beats per minute", sfloat_to_string(rate))
+        });
+    }
+    if flags & 0x08 != 0 {
+        reader.u8("User ID", |user| match user {
+            0xff => "Unknown User".to_string(),
+            _ => String::new(),
+        });
+    }
+    if flags & 0x10 != 0
+        && reader
+            .u16("Measurement Status", |_| String::new())
+            .is_some()
+    {
+        reader.bits(
+            "Measurement Status",
+            2,
+            &[
+                (0, 1, "Body Movement Detection"),
+                (1, 1, "Cuff Fit Detection"),
+                (2, 1, "Irregular Pulse Detection"),
+                (3, 2, "Pulse Rate Range Detection"),
+                (5, 1, "Measurement Position Detection"),
+            ],
+        );
+    }
+    reader.finish()
+}
+
+fn heart_rate_measurement_as_json(value: &[u8], start_byte: u16) -> String {
+    let mut reader = FieldReader::new(value, start_byte);
+    let Some(flags) = reader.u8("Flags", |_| String::new()) else {
+        return String::new();
+    };
+    reader.bits(
+        "Flags",
+        1,
+        &[
+            (0, 1, "Heart Rate Value Format"),
+            (1, 2, "Sensor Contact Status"),
+            (3, 1, "Energy Expended Status"),
+            (4, 1, "RR-Interval"),
+        ],
+    );
+    let bpm = |rate: u32| format!("{} bpm", rate);
+    if flags & 0x01 == 0 {
+        reader.u8("Heart Rate Measurement Value", |rate| bpm(rate as u32));
+    } else {
+        reader.u16("Heart Rate Measurement Value", |rate| bpm(rate as u32));
+    }
+    if flags & 0x08 != 0 {
+        reader.u16("Energy Expended", |energy| format!("{} kJ", energy));
+    }
+    if flags & 0x10 != 0 {
+        let mut i = 0;
+        while reader
+            .u16(&format!("RR-Interval[{}]", i), |interval| {
+                format!("{:.3} s", interval as f64 / 1024.0)
+            })
+            .is_some()
+        {
+            i += 1;
+        }
+    }
+    reader.finish()
+}
+
+fn pnp_id_as_json(value: &[u8], start_byte: u16) -> String {
+    let mut reader = FieldReader::new(value, start_byte);
+    let source = reader.u8("Vendor ID Source", |source| {
+        match source {
+            0x01 => "Bluetooth SIG assigned Company Identifier",
+            0x02 => "USB Implementer's Forum assigned Vendor ID",
+            _ => "Reserved for future use",
+        }
+        .to_string()
+    });
+    reader.u16("Vendor ID", |vendor| match source {
+        Some(0x01) => get_company_name(vendor).to_string(),
+        _ => String::new(),
+    });
+    reader.u16("Product ID", |_| String::new());
+    reader.u16("Product Version", |version| {
+        format!(
+            "{}.{}.{}",
+            version >> 8,
+            (version >> 4) & 0x0f,
+            version & 0x0f
+        )
+    });
+    reader.finish()
+}
+
+fn csc_measurement_as_json(value: &[u8], start_byte: u16) -> String {
+    let mut reader = FieldReader::new(value, start_byte);
+    let Some(flags) = reader.u8("Flags", |_| String::new()) else {
+        return String::new();
+    };
+    reader.bits(
+        "Flags",
+        1,
+        &[
+            (0, 1, "Wheel Revolution Data Present"),
+            (1, 1, "Crank Revolution Data Present"),
+        ],
+    );
+    let event_time = |time: u16| format!("{:.3} s", time as f64 / 1024.0);
+    if flags & 0x01 != 0 {
+        reader.u32("Cumulative Wheel Revolutions", |revolutions| {
+            revolutions.to_string()
+        });
+        reader.u16("Last Wheel Event Time", event_time);
+    }
+    if flags & 0x02 != 0 {
+        reader.u16("Cumulative Crank Revolutions", |revolutions| {
+            revolutions.to_string()
+        });
+        reader.u16("Last Crank Event Time", event_time);
+    }
+    reader.finish()
+}
+
+fn cccd_as_json(value: &[u8], start_byte: u16) -> String {
+    let mut reader = FieldReader::new(value, start_byte);
+    if reader.u16("Properties", |_| String::new()).is_some() {
+        reader.bits(
+            "Properties",
+            2,
+            &[(0, 1, "Notification"), (1, 1, "Indication")],
+        );
+    }
+    reader.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{float_to_string, sfloat_to_string, value_as_json};
+    use crate::att::AttUuid;
+    use crate::str_to_array;
+
+    #[test]
+    fn sfloat_keeps_the_decimals_of_its_exponent() {
+        // mantissa 373, exponent -1
+        assert_eq!(sfloat_to_string(0xf175), "37.3");
+        // mantissa -5, exponent -2
+        assert_eq!(sfloat_to_string(0xeffb), "-0.05");
+        // mantissa 12, exponent 2
+        assert_eq!(sfloat_to_string(0x200c), "1200");
+        assert_eq!(sfloat_to_string(0x07ff), "NaN");
+    }
+
+    #[test]
+    fn float_keeps_the_decimals_of_its_exponent() {
+        // mantissa 3730, exponent -2
+        assert_eq!(float_to_string(0xfe00_0e92), "37.30");
+        // mantissa -1, exponent -3
+        assert_eq!(float_to_string(0xfdff_ffff), "-0.001");
+        assert_eq!(float_to_string(0x0080_0002), "-INFINITY");
+    }
+
+    #[test]
+    fn temperature_measurement_is_decoded() {
+        // Celsius with a temperature type, 37.3 as mantissa 373, exponent -1
+        let value = str_to_array("04 75 01 00 ff 06");
+        let json = value_as_json(AttUuid::Uuid16(0x2a1c), &value, 10);
+        assert!(
+            json.starts_with(r#""Temperature Measurement": {"Flags": [0x4, "", "B(10, 1)", ""]"#)
+        );
+        assert!(json.contains(
+            r#""Temperature Measurement Value": [0xff000175, "37.3 Celsius", "B(11, 4)", ""]"#
+        ));
+        assert!(json.contains(r#""Temperature Type": [0x6, "Mouth", "B(15, 1)", ""]"#));
+    }
+}
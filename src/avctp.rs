@@ -0,0 +1,268 @@
+use crate::assigned_numbers::get_uuid16_name;
+use crate::avrcp::AVRCP;
+use crate::HostStack;
+use crate::ParseNodeA;
+use crate::ParseNodeOptA;
+
+use crate::ParseBitsNode;
+use crate::ParseBytesNode;
+
+const SINGLE_PACKET: u8 = 0x00;
+const START_PACKET: u8 = 0x01;
+const END_PACKET: u8 = 0x03;
+
+const AV_REMOTE_CONTROL: u16 = 0x110e;
+
+#[derive(Default, Debug)]
+pub struct AVCTPArg {
+    fragments: Vec<Fragment>,
+}
+
+/// A message split into start, continue and end packets, collected until
+/// the end packet is in.
+#[derive(Debug)]
+struct Fragment {
+    acl_handle: u16,
+    channel: u16,
+    label: u8,
+    response: bool,
+    pid: u16,
+    data: Vec<u8>,
+}
+
+impl AVCTPArg {
+    /// Drops the messages of `acl_handle` still being reassembled.
+    pub(crate) fn disconnect(&mut self, acl_handle: u16) {
+        self.fragments
+            .retain(|fragment| fragment.acl_handle != acl_handle);
+    }
+
+    /// Collects a start, continue or end packet. Returns the PID and the
+    /// whole message once the end packet is in.
+    fn reassemble(
+        &mut self,
+        (acl_handle, channel): (u16, u16),
+        avctp: &AVCTP,
+        payload: &[u8],
+    ) -> Option<(u16, Vec<u8>)> {
+        let same_message = |fragment: &Fragment| {
+            fragment.acl_handle == acl_handle
+                && fragment.channel == channel
+                && fragment.label == avctp.label
+                && fragment.response == avctp.response
+        };
+        match avctp.packet_type {
+            START_PACKET => {
+                self.fragments.retain(|fragment| !same_message(fragment));
+                self.fragments.push(Fragment {
+                    acl_handle,
+                    channel,
+                    label: avctp.label,
+                    response: avctp.response,
+                    pid: avctp.pid.unwrap_or_default(),
+                    data: payload.to_vec(),
+                });
+                None
+            }
+            END_PACKET => {
+                let index = self.fragments.iter().position(same_message)?;
+                let mut fragment = self.fragments.remove(index);
+                fragment.data.extend_from_slice(payload);
+                Some((fragment.pid, fragment.data))
+            }
+            _ => {
+                let fragment = self.fragments.iter_mut().find(|f| same_message(f))?;
+                fragment.data.extend_from_slice(payload);
+                None
+            }
+        }
+    }
+}
+
+/// An AVCTP packet on the control or the browsing channel, with the
+/// message it completes.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq)]
+pub(crate) struct AVCTP {
+    label: u8,
+    packet_type: u8,
+    response: bool,
+    ipid: bool,
+    packets: Option<u8>,
+    pid: Option<u16>,
+    payload: Vec<u8>,
+    message: Option<AvctpMessage>,
+}
+
+#[derive(Debug, PartialEq)]
+enum AvctpMessage {
+    Avrcp(AVRCP),
+    // a message of a profile that is not decoded, or the IPID reply
+    Other(Vec<u8>),
+}
+
+impl AVCTP {
+    fn header_len(&self) -> u16 {
+        match self.packet_type {
+            SINGLE_PACKET => 3,
+            START_PACKET => 4,
+            _ => 1,
+        }
+    }
+}
+
+impl ParseNodeOptA<bool> for AVCTP {
+    /// `browsing` tells the browsing channel from the control channel.
+    fn new(data: &[u8], args: Option<&mut HostStack>, browsing: bool) -> Option<Self> {
+        let header = *data.first()?;
+        let packet_type = (header >> 2) & 0x03;
+        let mut avctp = AVCTP {
+            label: header >> 4,
+            packet_type,
+            response: header & 0x02 != 0,
+            ipid: header & 0x01 != 0,
+            packets: None,
+            pid: None,
+            payload: Vec::new(),
+            message: None,
+        };
+        match packet_type {
+            SINGLE_PACKET => avctp.pid = Some(u16::from_be_bytes([*data.get(1)?, *data.get(2)?])),
+            START_PACKET => {
+                avctp.packets = Some(*data.get(1)?);
+                avctp.pid = Some(u16::from_be_bytes([*data.get(2)?, *data.get(3)?]));
+            }
+            _ => {}
+        }
+        let payload = &data[(avctp.header_len() as usize).min(data.len())..];
+        avctp.payload = payload.to_vec();
+
+        let mut args = args;
+        let complete = match (packet_type, args.as_deref_mut()) {
+            (SINGLE_PACKET, _) => Some((avctp.pid.unwrap_or_default(), payload.to_vec())),
+            (_, Some(args)) => {
+                let key = (args.hci_arg.acl_handle(), args.l2cap_arg.payload_channel());
+                args.avctp_arg.reassemble(key, &avctp, payload)
+            }
+            (_, None) => None,
+        };
+        avctp.message = complete.map(|(pid, message)| match pid {
+            AV_REMOTE_CONTROL if !avctp.ipid => {
+                AvctpMessage::Avrcp(AVRCP::new(&message, args, (browsing, avctp.response)))
+            }
+            _ => AvctpMessage::Other(message),
+        });
+        Some(avctp)
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut fields = vec![
+            ParseBitsNode::new(start_byte, 1, 4, 4).format("Transaction Label", self.label, "", ""),
+            ParseBitsNode::new(start_byte, 1, 2, 2).format(
+                "Packet Type",
+                self.packet_type,
+                match self.packet_type {
+                    SINGLE_PACKET => "Single Packet",
+                    START_PACKET => "Start Packet",
+                    END_PACKET => "End Packet",
+                    _ => "Continue Packet",
+                },
+                "",
+            ),
+            ParseBitsNode::new(start_byte, 1, 1, 1).format(
+                "C/R",
+                self.response as u8,
+                match self.response {
+                    true => "Response",
+                    false => "Command",
+                },
+                "",
+            ),
+            ParseBitsNode::new(start_byte, 1, 0, 1).format(
+                "IPID",
+                self.ipid as u8,
+                match self.ipid {
+                    true => "Invalid Profile Identifier",
+                    false => "",
+                },
+                "",
+            ),
+        ];
+        let mut offset = start_byte + 1;
+        if let Some(packets) = self.packets {
+            fields.push(ParseBytesNode::new(offset, 1).format(
+                "Number Of AVCTP Packets",
+                packets,
+                "",
+                "",
+            ));
+            offset += 1;
+        }
+        if let Some(pid) = self.pid {
+            fields.push(ParseBytesNode::new(offset, 2).format(
+                "PID",
+                pid,
+                get_uuid16_name(pid),
+                "",
+            ));
+            offset += 2;
+        }
+        let message_s = match &self.message {
+            // the reassembled message is placed as if it started here
+            Some(AvctpMessage::Avrcp(avrcp)) => avrcp.as_json(offset),
+            Some(AvctpMessage::Other(message)) if !message.is_empty() => ParseBytesNode::new(
+                offset,
+                message.len() as u16,
+            )
+            .format("Message", &message[..], "", ""),
+            _ => String::new(),
+        };
+        match self.packet_type {
+            SINGLE_PACKET => {
+                if !message_s.is_empty() {
+                    fields.push(message_s);
+                }
+            }
+            _ => {
+                if !self.payload.is_empty() {
+                    fields.push(
+                        ParseBytesNode::new(offset, self.payload.len() as u16).format(
+                            "Message Fragment",
+                            &self.payload[..],
+                            "",
+                            "",
+                        ),
+                    );
+                }
+                if self.message.is_some() {
+                    fields.push(format!(r#""Reassembled Message": {{{}}}"#, message_s));
+                }
+            }
+        }
+        format!(r#""AVCTP": {{{}}}"#, fields.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::parse_on_channel;
+
+    // AVCTP control channel 0x0040 <-> 0x0041, then a GetCapabilities
+    // command split over a start and an end packet
+    const PACKETS: [&str; 2] = [
+        "02 40 20 0e 00 0a 00 41 00 14 02 11 0e 01 48 00 00 19 58",
+        "02 40 20 0a 00 06 00 41 00 1c 10 00 00 01 03",
+    ];
+
+    #[test]
+    fn fragmented_message_is_reassembled() {
+        let (json, _) = parse_on_channel(0x0017, &PACKETS);
+        assert!(json[0].contains(r#""Number Of AVCTP Packets": [0x2, "", "B(10, 1)", ""]"#));
+        assert!(
+            json[0].contains(r#""Message Fragment": ["01 48 00 00 19 58", "", "B(13, 6)", ""]}"#)
+        );
+        assert!(json[1].contains(r#""Message Fragment": ["10 00 00 01 03", "", "B(10, 5)", ""], "Reassembled Message": {"AV/C": {"Command Type": [0x1, "STATUS", "#));
+        assert!(json[1].contains(r#""PDU ID": [0x10, "GetCapabilities", "B(16, 1)", ""]"#));
+        assert!(json[1].contains(r#""Capability ID": [0x3, "Events Supported", "B(20, 1)", ""]"#));
+    }
+}
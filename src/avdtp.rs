@@ -0,0 +1,1008 @@
+use crate::HostStack;
+use crate::ParseNodeOpt;
+use crate::SummaryKeys;
+
+use crate::ParseBitsNode;
+use crate::ParseBytesNode;
+
+mod capabilities;
+use capabilities::{get_media_type_name, get_service_category_name, Capability};
+mod media;
+use media::{AvdtpMedia, MediaStats};
+
+const SINGLE_PACKET: u8 = 0x00;
+const START_PACKET: u8 = 0x01;
+const END_PACKET: u8 = 0x03;
+
+const COMMAND: u8 = 0x00;
+const GENERAL_REJECT: u8 = 0x01;
+const RESPONSE_ACCEPT: u8 = 0x02;
+
+const DISCOVER: u8 = 0x01;
+const GET_CAPABILITIES: u8 = 0x02;
+const SET_CONFIGURATION: u8 = 0x03;
+const GET_CONFIGURATION: u8 = 0x04;
+const RECONFIGURE: u8 = 0x05;
+const OPEN: u8 = 0x06;
+const START: u8 = 0x07;
+const CLOSE: u8 = 0x08;
+const SUSPEND: u8 = 0x09;
+const ABORT: u8 = 0x0a;
+const SECURITY_CONTROL: u8 = 0x0b;
+const GET_ALL_CAPABILITIES: u8 = 0x0c;
+const DELAY_REPORT: u8 = 0x0d;
+
+#[derive(Default, Debug)]
+pub struct AVDTPArg {
+    connections: Vec<AvdtpConnection>,
+    // state of the connections that have ended, kept for the summary
+    closed: Vec<AvdtpConnection>,
+}
+
+impl AVDTPArg {
+    /// Returns the AVDTP state of `acl_handle`, creating it on first use.
+    fn connection(&mut self, acl_handle: u16) -> &mut AvdtpConnection {
+        let index = match self
+            .connections
+            .iter()
+            .position(|connection| connection.acl_handle == acl_handle)
+        {
+            Some(index) => index,
+            None => {
+                self.connections.push(AvdtpConnection {
+                    acl_handle,
+                    ..Default::default()
+                });
+                self.connections.len() - 1
+            }
+        };
+        &mut self.connections[index]
+    }
+
+    /// Stops tracking the channel `cid` of `acl_handle` once it is released.
+    pub(crate) fn release_channel(&mut self, acl_handle: u16, cid: u16) {
+        if let Some(connection) = self
+            .connections
+            .iter_mut()
+            .find(|connection| connection.acl_handle == acl_handle)
+        {
+            if connection.signaling_channel == Some(cid) {
+                connection.signaling_channel = None;
+            }
+            connection
+                .media_channels
+                .retain(|channel| channel.cid != cid);
+        }
+    }
+
+    /// Closes the AVDTP session of `acl_handle`, with its channels and
+    /// stream end points.
+    pub(crate) fn disconnect(&mut self, acl_handle: u16) {
+        let (ended, open): (Vec<AvdtpConnection>, Vec<AvdtpConnection>) =
+            std::mem::take(&mut self.connections)
+                .into_iter()
+                .partition(|connection| connection.acl_handle == acl_handle);
+        self.connections = open;
+        self.closed.extend(ended);
+    }
+
+    pub(crate) fn summary(&self) -> String {
+        let mut keys = SummaryKeys::default();
+        self.closed
+            .iter()
+            .chain(self.connections.iter())
+            .map(|connection| connection.summary(&mut keys))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+/// The signaling channel, media channels and stream end points of the
+/// AVDTP session on one connection.
+#[derive(Debug, Default)]
+struct AvdtpConnection {
+    acl_handle: u16,
+    // the first AVDTP channel still open is the signaling channel, the
+    // ones opened after it transport media
+    signaling_channel: Option<u16>,
+    media_channels: Vec<MediaChannel>,
+    // ACP SEID of the stream whose Open was accepted and which has not
+    // got its media channel yet
+    opening: Option<u8>,
+    fragments: Vec<Fragment>,
+    // commands waiting for their response, with their transaction label
+    commands: Vec<(u8, u8, AvdtpMessage)>,
+    seps: Vec<Sep>,
+}
+
+/// A media transport channel, the stream it was opened for and what it
+/// carried so far.
+#[derive(Debug)]
+struct MediaChannel {
+    cid: u16,
+    seid: Option<u8>,
+    stats: MediaStats,
+}
+
+/// A signal split into a start, continue and end packets.
+#[derive(Debug)]
+struct Fragment {
+    label: u8,
+    message_type: u8,
+    signal_id: u8,
+    data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SepState {
+    Idle,
+    Configured,
+    Open,
+    Streaming,
+}
+
+/// A stream end point, as Discover and the signals naming its SEID tell.
+#[derive(Debug, Clone)]
+struct Sep {
+    seid: u8,
+    media_type: Option<u8>,
+    tsep: Option<u8>,
+    in_use: bool,
+    capabilities: Vec<Capability>,
+    configuration: Vec<Capability>,
+    int_seid: Option<u8>,
+    state: SepState,
+    delay: Option<u16>,
+}
+
+impl Sep {
+    fn summary(&self) -> String {
+        let mut fields = Vec::new();
+        if let Some(media_type) = self.media_type {
+            fields.push(format!(
+                r#""Media Type": "{}""#,
+                get_media_type_name(media_type)
+            ));
+        }
+        if let Some(tsep) = self.tsep {
+            fields.push(format!(r#""TSEP": "{}""#, get_tsep_name(tsep)));
+        }
+        fields.push(format!(r#""In Use": {}"#, self.in_use));
+        fields.push(format!(r#""State": "{:?}""#, self.state));
+        if !self.capabilities.is_empty() {
+            fields.push(format!(
+                r#""Capabilities": [{}]"#,
+                describe_capabilities(&self.capabilities)
+            ));
+        }
+        if !self.configuration.is_empty() {
+            fields.push(format!(
+                r#""Configuration": [{}]"#,
+                describe_capabilities(&self.configuration)
+            ));
+        }
+        if let Some(int_seid) = self.int_seid {
+            fields.push(format!(r#""INT SEID": {}"#, int_seid));
+        }
+        if let Some(delay) = self.delay {
+            fields.push(format!(r#""Delay": "{}.{} ms""#, delay / 10, delay % 10));
+        }
+        format!(r#""SEID {}": {{{}}}"#, self.seid, fields.join(", "))
+    }
+
+    /// The configured codec, or the first one it is capable of.
+    fn codec_description(&self) -> Option<String> {
+        self.configuration
+            .iter()
+            .chain(self.capabilities.iter())
+            .find_map(|capability| capability.codec_description())
+    }
+
+    /// The configured codec type and sampling frequency.
+    fn codec(&self) -> Option<(u8, Option<u32>)> {
+        self.configuration
+            .iter()
+            .find_map(|capability| capability.codec())
+    }
+}
+
+fn describe_capabilities(capabilities: &[Capability]) -> String {
+    capabilities
+        .iter()
+        .map(|capability| {
+            format!(
+                r#""{}""#,
+                capability
+                    .codec_description()
+                    .unwrap_or(get_service_category_name(capability.category).to_string())
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+impl AvdtpConnection {
+    fn sep(&mut self, seid: u8) -> &mut Sep {
+        let index = match self.seps.iter().position(|sep| sep.seid == seid) {
+            Some(index) => index,
+            None => {
+                self.seps.push(Sep {
+                    seid,
+                    media_type: None,
+                    tsep: None,
+                    in_use: false,
+                    capabilities: Vec::new(),
+                    configuration: Vec::new(),
+                    int_seid: None,
+                    state: SepState::Idle,
+                    delay: None,
+                });
+                self.seps.len() - 1
+            }
+        };
+        &mut self.seps[index]
+    }
+
+    /// Collects the packets of a fragmented signal. Returns the signal
+    /// identifier and the reassembled signal once the end packet is in.
+    fn reassemble(&mut self, signal: &AvdtpSignal, payload: &[u8]) -> Option<(u8, Vec<u8>)> {
+        let same_signal = |fragment: &Fragment| {
+            fragment.label == signal.label && fragment.message_type == signal.message_type
+        };
+        match signal.packet_type {
+            START_PACKET => {
+                self.fragments.retain(|fragment| !same_signal(fragment));
+                self.fragments.push(Fragment {
+                    label: signal.label,
+                    message_type: signal.message_type,
+                    signal_id: signal.signal_id.unwrap_or_default(),
+                    data: payload.to_vec(),
+                });
+                None
+            }
+            END_PACKET => {
+                let index = self.fragments.iter().position(same_signal)?;
+                let mut fragment = self.fragments.remove(index);
+                fragment.data.extend_from_slice(payload);
+                Some((fragment.signal_id, fragment.data))
+            }
+            _ => {
+                let fragment = self.fragments.iter_mut().find(|f| same_signal(f))?;
+                fragment.data.extend_from_slice(payload);
+                None
+            }
+        }
+    }
+
+    /// Follows a complete command or response on the stream end points.
+    fn update(&mut self, label: u8, message_type: u8, signal_id: u8, message: &AvdtpMessage) {
+        if message_type == COMMAND {
+            if let AvdtpMessage::DelayReport { acp_seid, delay } = message {
+                self.sep(*acp_seid).delay = Some(*delay);
+            }
+            self.commands.retain(|(known, ..)| *known != label);
+            self.commands.push((label, signal_id, message.clone()));
+            return;
+        }
+        let index = self
+            .commands
+            .iter()
+            .position(|(known, known_id, _)| *known == label && *known_id == signal_id);
+        let Some(index) = index else {
+            return;
+        };
+        let (_, _, command) = self.commands.remove(index);
+        if message_type != RESPONSE_ACCEPT {
+            return;
+        }
+        match (signal_id, &command, message) {
+            (DISCOVER, _, AvdtpMessage::Seps(seps)) => {
+                for info in seps.iter() {
+                    let sep = self.sep(info[0] >> 2);
+                    sep.in_use = info[0] & 0x02 != 0;
+                    sep.media_type = Some(info[1] >> 4);
+                    sep.tsep = Some((info[1] >> 3) & 0x01);
+                }
+            }
+            (
+                GET_CAPABILITIES | GET_ALL_CAPABILITIES,
+                AvdtpMessage::Seid(seid),
+                AvdtpMessage::Capabilities(capabilities),
+            ) => self.sep(*seid).capabilities = capabilities.clone(),
+            (
+                GET_CONFIGURATION,
+                AvdtpMessage::Seid(seid),
+                AvdtpMessage::Capabilities(capabilities),
+            ) => self.sep(*seid).configuration = capabilities.clone(),
+            (
+                SET_CONFIGURATION | RECONFIGURE,
+                AvdtpMessage::Configuration {
+                    acp_seid,
+                    int_seid,
+                    capabilities,
+                },
+                _,
+            ) => {
+                let sep = self.sep(*acp_seid);
+                match int_seid {
+                    // Reconfigure only carries the capabilities it changes
+                    None => {
+                        for capability in capabilities.iter() {
+                            sep.configuration
+                                .retain(|known| known.category != capability.category);
+                            sep.configuration.push(capability.clone());
+                        }
+                    }
+                    Some(int_seid) => {
+                        sep.configuration = capabilities.clone();
+                        sep.int_seid = Some(*int_seid);
+                        sep.state = SepState::Configured;
+                        sep.in_use = true;
+                    }
+                }
+            }
+            (OPEN, AvdtpMessage::Seid(seid), _) => {
+                self.sep(*seid).state = SepState::Open;
+                self.opening = Some(*seid);
+            }
+            (START | SUSPEND, AvdtpMessage::Seids(seids), _) => {
+                for seid in seids.iter() {
+                    self.sep(*seid).state = match signal_id {
+                        START => SepState::Streaming,
+                        _ => SepState::Open,
+                    };
+                }
+            }
+            (CLOSE | ABORT, AvdtpMessage::Seid(seid), _) => {
+                let sep = self.sep(*seid);
+                sep.state = SepState::Idle;
+                sep.in_use = false;
+                self.media_channels
+                    .retain(|channel| channel.seid != Some(*seid));
+            }
+            _ => {}
+        }
+    }
+
+    fn summary(&self, keys: &mut SummaryKeys) -> String {
+        let mut fields = Vec::new();
+        if let Some(channel) = self.signaling_channel {
+            fields.push(format!(r#""Signaling Channel": "{:#06x}""#, channel));
+        }
+        if !self.media_channels.is_empty() {
+            let channels_s = self
+                .media_channels
+                .iter()
+                .map(|channel| {
+                    let seid_s = match channel.seid {
+                        Some(seid) => seid.to_string(),
+                        None => "null".to_string(),
+                    };
+                    format!(
+                        r#""{:#06x}": {{"SEID": {}, {}}}"#,
+                        channel.cid,
+                        seid_s,
+                        channel.stats.summary()
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            fields.push(format!(r#""Media Channels": {{{}}}"#, channels_s));
+        }
+        fields.extend(self.seps.iter().map(|sep| sep.summary()));
+        let key = keys.unique(format!("Connection {:#x}", self.acl_handle));
+        format!(r#""{}": {{{}}}"#, key, fields.join(", "))
+    }
+}
+
+/// What an AVDTP channel carries: signals on the first channel of a
+/// connection, media on the ones opened for streams.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq)]
+pub(crate) enum AVDTP {
+    Signaling(AvdtpSignal),
+    Media(AvdtpMedia),
+}
+
+impl ParseNodeOpt for AVDTP {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        let mut args = args;
+        if let Some(args) = args.as_deref_mut() {
+            let acl_handle = args.hci_arg.acl_handle();
+            let channel = args.l2cap_arg.payload_channel();
+            let signaling_channel = args
+                .l2cap_arg
+                .open_channels(acl_handle, 0x0019)
+                .next()
+                .unwrap_or(channel);
+            let connection = args.avdtp_arg.connection(acl_handle);
+            connection.signaling_channel = Some(signaling_channel);
+            if signaling_channel != channel {
+                let index = match connection
+                    .media_channels
+                    .iter()
+                    .position(|known| known.cid == channel)
+                {
+                    Some(index) => index,
+                    None => {
+                        connection.media_channels.push(MediaChannel {
+                            cid: channel,
+                            seid: connection.opening.take(),
+                            stats: MediaStats::default(),
+                        });
+                        connection.media_channels.len() - 1
+                    }
+                };
+                let seid = connection.media_channels[index].seid;
+                let (stream, codec) = match seid {
+                    Some(seid) => {
+                        let sep = connection.sep(seid);
+                        (Some((seid, sep.codec_description())), sep.codec())
+                    }
+                    None => (None, None),
+                };
+                let media = AvdtpMedia::new(data, stream, codec.map(|(codec_type, _)| codec_type));
+                connection.media_channels[index]
+                    .stats
+                    .update(&media, codec.and_then(|(_, frequency)| frequency));
+                return Some(AVDTP::Media(media));
+            }
+        }
+        AvdtpSignal::new(data, args).map(AVDTP::Signaling)
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        match self {
+            AVDTP::Signaling(signal) => format!(r#""AVDTP": {{{}}}"#, signal.as_json(start_byte)),
+            AVDTP::Media(media) => format!(r#""AVDTP Media": {{{}}}"#, media.as_json(start_byte)),
+        }
+    }
+}
+
+/// One AVDTP signaling packet, with the signal it completes.
+#[derive(Debug, PartialEq)]
+pub(crate) struct AvdtpSignal {
+    label: u8,
+    packet_type: u8,
+    message_type: u8,
+    signal_packets: Option<u8>,
+    signal_id: Option<u8>,
+    payload: Vec<u8>,
+    // the signal identifier and message, for single packets and the end
+    // packet of a fragmented signal
+    message: Option<(u8, AvdtpMessage)>,
+}
+
+impl AvdtpSignal {
+    fn header_len(&self) -> u16 {
+        match self.packet_type {
+            SINGLE_PACKET => 2,
+            START_PACKET => 3,
+            _ => 1,
+        }
+    }
+
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        let header = *data.first()?;
+        let packet_type = (header >> 2) & 0x03;
+        let mut signal = AvdtpSignal {
+            label: header >> 4,
+            packet_type,
+            message_type: header & 0x03,
+            signal_packets: None,
+            signal_id: None,
+            payload: Vec::new(),
+            message: None,
+        };
+        match packet_type {
+            SINGLE_PACKET => signal.signal_id = Some(*data.get(1)? & 0x3f),
+            START_PACKET => {
+                signal.signal_packets = Some(*data.get(1)?);
+                signal.signal_id = Some(*data.get(2)? & 0x3f);
+            }
+            _ => {}
+        }
+        let payload = &data[(signal.header_len() as usize).min(data.len())..];
+        signal.payload = payload.to_vec();
+        let complete = match (packet_type, args) {
+            (SINGLE_PACKET, args) => {
+                Some((signal.signal_id.unwrap_or_default(), payload.to_vec(), args))
+            }
+            (_, Some(args)) => {
+                let connection = args.avdtp_arg.connection(args.hci_arg.acl_handle());
+                connection
+                    .reassemble(&signal, payload)
+                    .map(|(signal_id, data)| (signal_id, data, Some(args)))
+            }
+            (_, None) => None,
+        };
+        if let Some((signal_id, data, args)) = complete {
+            let message = AvdtpMessage::new(&data, signal_id, signal.message_type);
+            if let Some(args) = args {
+                let connection = args.avdtp_arg.connection(args.hci_arg.acl_handle());
+                connection.update(signal.label, signal.message_type, signal_id, &message);
+            }
+            signal.message = Some((signal_id, message));
+        }
+        Some(signal)
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut fields = vec![
+            ParseBitsNode::new(start_byte, 1, 4, 4).format("Transaction Label", self.label, "", ""),
+            ParseBitsNode::new(start_byte, 1, 2, 2).format(
+                "Packet Type",
+                self.packet_type,
+                match self.packet_type {
+                    SINGLE_PACKET => "Single Packet",
+                    START_PACKET => "Start Packet",
+                    END_PACKET => "End Packet",
+                    _ => "Continue Packet",
+                },
+                "",
+            ),
+            ParseBitsNode::new(start_byte, 1, 0, 2).format(
+                "Message Type",
+                self.message_type,
+                match self.message_type {
+                    COMMAND => "Command",
+                    GENERAL_REJECT => "General Reject",
+                    RESPONSE_ACCEPT => "Response Accept",
+                    _ => "Response Reject",
+                },
+                "",
+            ),
+        ];
+        let mut offset = start_byte + 1;
+        if let Some(signal_packets) = self.signal_packets {
+            fields.push(ParseBytesNode::new(offset, 1).format(
+                "Number Of Signal Packets",
+                signal_packets,
+                "",
+                "",
+            ));
+            offset += 1;
+        }
+        if let Some(signal_id) = self.signal_id {
+            fields.push(ParseBitsNode::new(offset, 1, 0, 6).format(
+                "Signal Identifier",
+                signal_id,
+                get_signal_id_name(signal_id),
+                "",
+            ));
+            offset += 1;
+        }
+        match &self.message {
+            Some((signal_id, message)) if self.packet_type != SINGLE_PACKET => {
+                if !self.payload.is_empty() {
+                    fields.push(
+                        ParseBytesNode::new(offset, self.payload.len() as u16).format(
+                            "Signal Fragment",
+                            &self.payload[..],
+                            "",
+                            "",
+                        ),
+                    );
+                }
+                // the reassembled signal is placed as if it started here
+                let message_s = message.as_json(offset);
+                fields.push(format!(
+                    r#""Reassembled {}": {{{}}}"#,
+                    get_signal_id_name(*signal_id),
+                    message_s
+                ));
+            }
+            Some((_, message)) => {
+                let message_s = message.as_json(offset);
+                if !message_s.is_empty() {
+                    fields.push(message_s);
+                }
+            }
+            None if !self.payload.is_empty() => fields.push(
+                ParseBytesNode::new(offset, self.payload.len() as u16).format(
+                    "Signal Fragment",
+                    &self.payload[..],
+                    "",
+                    "",
+                ),
+            ),
+            None => {}
+        }
+        fields.join(", ")
+    }
+}
+
+/// The parameters of a complete signal.
+#[derive(Debug, Clone, PartialEq)]
+enum AvdtpMessage {
+    Empty,
+    // Discover response, the two bytes of each SEP
+    Seps(Vec<[u8; 2]>),
+    Seid(u8),
+    Seids(Vec<u8>),
+    // Set Configuration names the INT SEID, Reconfigure does not
+    Configuration {
+        acp_seid: u8,
+        int_seid: Option<u8>,
+        capabilities: Vec<Capability>,
+    },
+    Capabilities(Vec<Capability>),
+    DelayReport {
+        acp_seid: u8,
+        delay: u16,
+    },
+    SecurityControl {
+        acp_seid: Option<u8>,
+        data: Vec<u8>,
+    },
+    // the service category or the first ACP SEID the error is about
+    Reject {
+        category: Option<u8>,
+        acp_seid: Option<u8>,
+        error_code: u8,
+    },
+    Unknown(Vec<u8>),
+}
+
+impl AvdtpMessage {
+    fn new(data: &[u8], signal_id: u8, message_type: u8) -> Self {
+        let seid = |index: usize| data.get(index).map(|seid| seid >> 2);
+        match (message_type, signal_id) {
+            (GENERAL_REJECT, _) => AvdtpMessage::Empty,
+            (COMMAND, DISCOVER) => AvdtpMessage::Empty,
+            (
+                COMMAND,
+                GET_CAPABILITIES | GET_CONFIGURATION | OPEN | CLOSE | ABORT | GET_ALL_CAPABILITIES,
+            ) if !data.is_empty() => AvdtpMessage::Seid(data[0] >> 2),
+            (COMMAND, START | SUSPEND) => {
+                AvdtpMessage::Seids(data.iter().map(|seid| seid >> 2).collect())
+            }
+            (COMMAND, SET_CONFIGURATION) if data.len() >= 2 => AvdtpMessage::Configuration {
+                acp_seid: data[0] >> 2,
+                int_seid: seid(1),
+                capabilities: Capability::parse_all(&data[2..]),
+            },
+            (COMMAND, RECONFIGURE) if !data.is_empty() => AvdtpMessage::Configuration {
+                acp_seid: data[0] >> 2,
+                int_seid: None,
+                capabilities: Capability::parse_all(&data[1..]),
+            },
+            (COMMAND, SECURITY_CONTROL) if !data.is_empty() => AvdtpMessage::SecurityControl {
+                acp_seid: seid(0),
+                data: data[1..].to_vec(),
+            },
+            (COMMAND, DELAY_REPORT) if data.len() >= 3 => AvdtpMessage::DelayReport {
+                acp_seid: data[0] >> 2,
+                delay: u16::from_be_bytes([data[1], data[2]]),
+            },
+            (RESPONSE_ACCEPT, DISCOVER) => {
+                AvdtpMessage::Seps(data.chunks_exact(2).map(|sep| [sep[0], sep[1]]).collect())
+            }
+            (RESPONSE_ACCEPT, GET_CAPABILITIES | GET_CONFIGURATION | GET_ALL_CAPABILITIES) => {
+                AvdtpMessage::Capabilities(Capability::parse_all(data))
+            }
+            (RESPONSE_ACCEPT, SECURITY_CONTROL) => AvdtpMessage::SecurityControl {
+                acp_seid: None,
+                data: data.to_vec(),
+            },
+            (RESPONSE_ACCEPT, _) if data.is_empty() => AvdtpMessage::Empty,
+            // the response reject of these names what the error is about
+            (0x03, SET_CONFIGURATION | RECONFIGURE) if data.len() >= 2 => AvdtpMessage::Reject {
+                category: Some(data[0]),
+                acp_seid: None,
+                error_code: data[1],
+            },
+            (0x03, START | SUSPEND) if data.len() >= 2 => AvdtpMessage::Reject {
+                category: None,
+                acp_seid: seid(0),
+                error_code: data[1],
+            },
+            (0x03, _) if !data.is_empty() => AvdtpMessage::Reject {
+                category: None,
+                acp_seid: None,
+                error_code: data[0],
+            },
+            _ if data.is_empty() => AvdtpMessage::Empty,
+            _ => AvdtpMessage::Unknown(data.to_vec()),
+        }
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let seid_node = |offset: u16, key: &str, seid: u8| {
+            ParseBitsNode::new(offset, 1, 2, 6).format(key, seid, "", "")
+        };
+        let capabilities_json = |capabilities: &[Capability], mut offset: u16| {
+            capabilities
+                .iter()
+                .enumerate()
+                .map(|(i, capability)| {
+                    let capability_s = capability.as_json(offset);
+                    offset += capability.len();
+                    format!(r#""Service Capability[{}]": {{{}}}"#, i, capability_s)
+                })
+                .collect::<Vec<String>>()
+        };
+        let fields = match self {
+            AvdtpMessage::Empty => Vec::new(),
+            AvdtpMessage::Seps(seps) => seps
+                .iter()
+                .enumerate()
+                .map(|(i, sep)| {
+                    let offset = start_byte + 2 * i as u16;
+                    let fields = [
+                        seid_node(offset, "ACP SEID", sep[0] >> 2),
+                        ParseBitsNode::new(offset, 1, 1, 1).format(
+                            "In Use",
+                            (sep[0] >> 1) & 0x01,
+                            "",
+                            "",
+                        ),
+                        ParseBitsNode::new(offset + 1, 1, 4, 4).format(
+                            "Media Type",
+                            sep[1] >> 4,
+                            get_media_type_name(sep[1] >> 4),
+                            "",
+                        ),
+                        ParseBitsNode::new(offset + 1, 1, 3, 1).format(
+                            "TSEP",
+                            (sep[1] >> 3) & 0x01,
+                            get_tsep_name((sep[1] >> 3) & 0x01),
+                            "",
+                        ),
+                    ];
+                    format!(r#""SEP[{}]": {{{}}}"#, i, fields.join(", "))
+                })
+                .collect(),
+            AvdtpMessage::Seid(seid) => vec![seid_node(start_byte, "ACP SEID", *seid)],
+            AvdtpMessage::Seids(seids) => seids
+                .iter()
+                .enumerate()
+                .map(|(i, seid)| {
+                    seid_node(start_byte + i as u16, &format!("ACP SEID[{}]", i), *seid)
+                })
+                .collect(),
+            AvdtpMessage::Configuration {
+                acp_seid,
+                int_seid,
+                capabilities,
+            } => {
+                let mut fields = vec![seid_node(start_byte, "ACP SEID", *acp_seid)];
+                if let Some(int_seid) = int_seid {
+                    fields.push(seid_node(start_byte + 1, "INT SEID", *int_seid));
+                }
+                fields.extend(capabilities_json(
+                    capabilities,
+                    start_byte + 1 + int_seid.is_some() as u16,
+                ));
+                fields
+            }
+            AvdtpMessage::Capabilities(capabilities) => capabilities_json(capabilities, start_byte),
+            AvdtpMessage::DelayReport { acp_seid, delay } => vec![
+                seid_node(start_byte, "ACP SEID", *acp_seid),
+                ParseBytesNode::new(start_byte + 1, 2).format(
+                    "Delay",
+                    *delay,
+                    &format!("{}.{} ms", delay / 10, delay % 10),
+                    "",
+                ),
+            ],
+            AvdtpMessage::SecurityControl { acp_seid, data } => {
+                let mut fields = Vec::new();
+                let mut offset = start_byte;
+                if let Some(acp_seid) = acp_seid {
+                    fields.push(seid_node(offset, "ACP SEID", *acp_seid));
+                    offset += 1;
+                }
+                if !data.is_empty() {
+                    fields.push(ParseBytesNode::new(offset, data.len() as u16).format(
+                        "Content Protection Security Data",
+                        &data[..],
+                        "",
+                        "",
+                    ));
+                }
+                fields
+            }
+            AvdtpMessage::Reject {
+                category,
+                acp_seid,
+                error_code,
+            } => {
+                let mut fields = Vec::new();
+                let mut offset = start_byte;
+                if let Some(category) = category {
+                    fields.push(ParseBytesNode::new(offset, 1).format(
+                        "Service Category",
+                        *category,
+                        get_service_category_name(*category),
+                        "",
+                    ));
+                    offset += 1;
+                }
+                if let Some(acp_seid) = acp_seid {
+                    fields.push(seid_node(offset, "ACP SEID", *acp_seid));
+                    offset += 1;
+                }
+                fields.push(ParseBytesNode::new(offset, 1).format(
+                    "Error Code",
+                    *error_code,
+                    get_error_code_name(*error_code),
+                    "",
+                ));
+                fields
+            }
+            AvdtpMessage::Unknown(data) => vec![ParseBytesNode::new(start_byte, data.len() as u16)
+                .format("Parameters", &data[..], "", "")],
+        };
+        fields.join(", ")
+    }
+}
+
+fn get_tsep_name(tsep: u8) -> &'static str {
+    match tsep {
+        0 => "Source",
+        _ => "Sink",
+    }
+}
+
+fn get_signal_id_name(signal_id: u8) -> &'static str {
+    match signal_id {
+        DISCOVER => "Discover",
+        GET_CAPABILITIES => "Get Capabilities",
+        SET_CONFIGURATION => "Set Configuration",
+        GET_CONFIGURATION => "Get Configuration",
+        RECONFIGURE => "Reconfigure",
+        OPEN => "Open",
+        START => "Start",
+        CLOSE => "Close",
+        SUSPEND => "Suspend",
+        ABORT => "Abort",
+        SECURITY_CONTROL => "Security Control",
+        GET_ALL_CAPABILITIES => "Get All Capabilities",
+        DELAY_REPORT => "Delay Report",
+        _ => "Reserved",
+    }
+}
+
+fn get_error_code_name(error_code: u8) -> &'static str {
+    match error_code {
+        0x01 => "BAD_HEADER_FORMAT",
+        0x11 => "BAD_LENGTH",
+        0x12 => "BAD_ACP_SEID",
+        0x13 => "SEP_IN_USE",
+        0x14 => "SEP_NOT_IN_USE",
+        0x17 => "BAD_SERV_CATEGORY",
+        0x18 => "BAD_PAYLOAD_FORMAT",
+        0x19 => "NOT_SUPPORTED_COMMAND",
+        0x1a => "INVALID_CAPABILITIES",
+        0x22 => "BAD_RECOVERY_TYPE",
+        0x23 => "BAD_MEDIA_TRANSPORT_FORMAT",
+        0x25 => "BAD_RECOVERY_FORMAT",
+        0x26 => "BAD_ROHC_FORMAT",
+        0x27 => "BAD_CP_FORMAT",
+        0x28 => "BAD_MULTIPLEXING_FORMAT",
+        0x29 => "UNSUPPORTED_CONFIGURATION",
+        0x31 => "BAD_STATE",
+        0xc1 => "INVALID_CODEC_TYPE",
+        0xc2 => "NOT_SUPPORTED_CODEC_TYPE",
+        0xc3 => "INVALID_SAMPLING_FREQUENCY",
+        0xc4 => "NOT_SUPPORTED_SAMPLING_FREQUENCY",
+        0xc5 => "INVALID_CHANNEL_MODE",
+        0xc6 => "NOT_SUPPORTED_CHANNEL_MODE",
+        0xc7 => "INVALID_SUBBANDS",
+        0xc8 => "NOT_SUPPORTED_SUBBANDS",
+        0xc9 => "INVALID_ALLOCATION_METHOD",
+        0xca => "NOT_SUPPORTED_ALLOCATION_METHOD",
+        0xcb => "INVALID_MINIMUM_BITPOOL_VALUE",
+        0xcc => "NOT_SUPPORTED_MINIMUM_BITPOOL_VALUE",
+        0xcd => "INVALID_MAXIMUM_BITPOOL_VALUE",
+        0xce => "NOT_SUPPORTED_MAXIMUM_BITPOOL_VALUE",
+        0xcf => "INVALID_LAYER",
+        0xd0 => "NOT_SUPPORTED_LAYER",
+        0xd1 => "NOT_SUPPORTED_CRC",
+        0xd2 => "NOT_SUPPORTED_MPF",
+        0xd3 => "NOT_SUPPORTED_VBR",
+        0xd4 => "INVALID_BIT_RATE",
+        0xd5 => "NOT_SUPPORTED_BIT_RATE",
+        0xd6 => "INVALID_OBJECT_TYPE",
+        0xd7 => "NOT_SUPPORTED_OBJECT_TYPE",
+        0xd8 => "INVALID_CHANNELS",
+        0xd9 => "NOT_SUPPORTED_CHANNELS",
+        0xda => "INVALID_VERSION",
+        0xdb => "NOT_SUPPORTED_VERSION",
+        0xdc => "NOT_SUPPORTED_MAXIMUM_SUL",
+        0xdd => "INVALID_BLOCK_LENGTH",
+        0xe0 => "INVALID_CP_TYPE",
+        0xe1 => "INVALID_CP_FORMAT",
+        0xe2 => "INVALID_CODEC_PARAMETER",
+        0xe3 => "NOT_SUPPORTED_CODEC_PARAMETER",
+        _ => "Reserved",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{l2cap_connect, parse_packets};
+    use crate::HostStack;
+
+    // AVDTP on 0x0040 <-> 0x0041 with a Discover response split over a
+    // start, continue and end packet. The channel is then released, the
+    // next one opened, 0x0044 <-> 0x0045, signals again and 0x0046 <->
+    // 0x0047 after it carries media.
+    fn parse() -> (Vec<String>, HostStack) {
+        let first = l2cap_connect(0x0040, 1, 0x0019, (0x0040, 0x0041));
+        let second = l2cap_connect(0x0040, 3, 0x0019, (0x0044, 0x0045));
+        let media = l2cap_connect(0x0040, 4, 0x0019, (0x0046, 0x0047));
+        parse_packets(&[
+            &first[0],
+            &first[1],
+            "02 40 20 06 00 02 00 41 00 10 01",
+            "02 40 20 09 00 05 00 40 00 16 03 01 04 08",
+            "02 40 20 07 00 03 00 40 00 1a 08 00",
+            "02 40 20 07 00 03 00 40 00 1e 0c 18",
+            "02 40 20 0c 00 08 00 01 00 06 02 04 00 41 00 40 00",
+            "02 40 20 0c 00 08 00 01 00 07 02 04 00 41 00 40 00",
+            &second[0],
+            &second[1],
+            "02 40 20 06 00 02 00 45 00 20 01",
+            &media[0],
+            &media[1],
+            "02 40 20 14 00 10 00 47 00 80 60 00 01 00 00 00 00 00 00 00 01 01 9c bd 10",
+        ])
+    }
+
+    #[test]
+    fn fragmented_signal_is_reassembled() {
+        let (json, _) = parse();
+        assert!(json[3].contains(r#""Signal Fragment": ["04 08", "", "B(12, 2)", ""]"#));
+        assert!(!json[4].contains("Reassembled"));
+        assert!(json[5].contains(r#""Reassembled Discover": {"SEP[0]": {"ACP SEID": [0x1, "#));
+        assert!(json[5].contains(r#""SEP[2]": {"ACP SEID": [0x3, "#));
+        assert!(json[5].contains(r#""Media Type": [0x1, "Video", "#));
+    }
+
+    #[test]
+    fn signaling_follows_the_first_open_channel() {
+        let (json, args) = parse();
+        assert!(json[10].contains(r#""AVDTP": {"Transaction Label": [0x2, "#));
+        assert!(json[13].contains(r#""AVDTP Media": {"RTP Header""#));
+        let summary = args.avdtp_summary();
+        assert!(summary.contains(r#""Signaling Channel": "0x0044""#));
+        assert!(summary.contains(r#""Media Channels": {"0x0046": {"SEID": null, "Packets": 1, "#));
+    }
+
+    // two A2DP links picking the same CIDs the other way around, 0x0040 is
+    // signaling on ACL handle 0x0040 and media on 0x0041
+    #[test]
+    fn channels_of_each_link_are_told_apart() {
+        let signaling = l2cap_connect(0x0040, 1, 0x0019, (0x0040, 0x0041));
+        let media = l2cap_connect(0x0040, 2, 0x0019, (0x0042, 0x0043));
+        let other_signaling = l2cap_connect(0x0041, 1, 0x0019, (0x0042, 0x0043));
+        let other_media = l2cap_connect(0x0041, 2, 0x0019, (0x0040, 0x0041));
+        let (json, args) = parse_packets(&[
+            &signaling[0],
+            &signaling[1],
+            &media[0],
+            &media[1],
+            &other_signaling[0],
+            &other_signaling[1],
+            &other_media[0],
+            &other_media[1],
+            "02 41 20 14 00 10 00 41 00 80 60 00 01 00 00 00 00 00 00 00 01 01 9c bd 10",
+            "02 41 20 06 00 02 00 43 00 10 01",
+            "02 40 20 06 00 02 00 41 00 20 01",
+            "02 40 20 14 00 10 00 43 00 80 60 00 01 00 00 00 00 00 00 00 01 01 9c bd 10",
+        ]);
+        assert!(json[8].contains(r#""AVDTP Media": {"RTP Header""#));
+        assert!(json[9].contains(r#""AVDTP": {"Transaction Label": [0x1, "#));
+        assert!(json[10].contains(r#""AVDTP": {"Transaction Label": [0x2, "#));
+        assert!(json[11].contains(r#""AVDTP Media": {"RTP Header""#));
+        let summary = args.avdtp_summary();
+        assert!(summary.contains(
+            r#""Connection 0x40": {"Signaling Channel": "0x0040", "Media Channels": {"0x0042": "#
+        ));
+        assert!(summary.contains(
+            r#""Connection 0x41": {"Signaling Channel": "0x0042", "Media Channels": {"0x0040": "#
+        ));
+    }
+}
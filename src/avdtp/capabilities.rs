@@ -0,0 +1,486 @@
+use crate::assigned_numbers::get_company_name;
+
+use crate::ParseBitsNode;
+use crate::ParseBytesNode;
+
+pub(super) const MEDIA_CODEC: u8 = 0x07;
+
+pub(super) const SBC: u8 = 0x00;
+pub(super) const AAC: u8 = 0x02;
+const VENDOR: u8 = 0xff;
+
+/// One service capability: category, LOSC and what the category carries.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Capability {
+    pub(super) category: u8,
+    pub(super) info: Vec<u8>,
+    error: &'static str,
+}
+
+impl Capability {
+    /// Reads capabilities up to the end of the signal or the first one that
+    /// does not fit.
+    pub(super) fn parse_all(data: &[u8]) -> Vec<Capability> {
+        let mut capabilities = Vec::new();
+        let mut offset = 0;
+        while offset + 2 <= data.len() {
+            let losc = data[offset + 1] as usize;
+            let end = offset + 2 + losc;
+            capabilities.push(Capability {
+                category: data[offset],
+                info: data[offset + 2..end.min(data.len())].to_vec(),
+                error: match end > data.len() {
+                    true => "LOSC exceeds the signal",
+                    false => "",
+                },
+            });
+            offset = end;
+        }
+        capabilities
+    }
+
+    pub(super) fn len(&self) -> u16 {
+        2 + self.info.len() as u16
+    }
+
+    /// Reads like "SBC, 44.1 kHz, Joint Stereo, Bitpool 2-53" for a media
+    /// codec capability.
+    pub(super) fn codec_description(&self) -> Option<String> {
+        if self.category != MEDIA_CODEC || self.info.len() < 2 {
+            return None;
+        }
+        let info = &self.info[2..];
+        let description = match (self.info[1], info.len()) {
+            (SBC, 4..) => format!(
+                "SBC, {}, {}, Bitpool {}-{}",
+                get_bits_name(info[0] as u32 >> 4, &SBC_FREQUENCIES),
+                get_bits_name(info[0] as u32 & 0x0f, &SBC_CHANNEL_MODES),
+                info[2],
+                info[3]
+            ),
+            (AAC, 6..) => format!(
+                "AAC, {}, {}, {} Channels, {} bps{}",
+                get_bits_name(info[0] as u32, &AAC_OBJECT_TYPES),
+                get_bits_name(
+                    (info[1] as u32) << 4 | (info[2] as u32) >> 4,
+                    &AAC_FREQUENCIES
+                ),
+                get_bits_name(info[2] as u32 & 0x0f, &AAC_CHANNELS),
+                aac_bit_rate(info),
+                match info[3] & 0x80 {
+                    0 => "",
+                    _ => ", VBR",
+                }
+            ),
+            (VENDOR, 6..) => {
+                let (vendor, codec) = vendor_codec(info);
+                let specific = &info[6..];
+                match get_vendor_codec_name(vendor, codec) {
+                    "" => format!(
+                        "{} codec {:#06x}",
+                        match get_company_name(vendor as u16) {
+                            "" => format!("Vendor {:#010x}", vendor),
+                            name => name.to_string(),
+                        },
+                        codec
+                    ),
+                    name @ ("aptX" | "aptX HD") if !specific.is_empty() => format!(
+                        "{}, {}, {}",
+                        name,
+                        get_bits_name(specific[0] as u32 >> 4, &SBC_FREQUENCIES),
+                        get_bits_name(specific[0] as u32 & 0x0f, &APTX_CHANNEL_MODES)
+                    ),
+                    name @ "LDAC" if specific.len() >= 2 => format!(
+                        "{}, {}, {}",
+                        name,
+                        get_bits_name(specific[0] as u32 & 0x3f, &LDAC_FREQUENCIES),
+                        get_bits_name(specific[1] as u32 & 0x07, &LDAC_CHANNEL_MODES)
+                    ),
+                    name => name.to_string(),
+                }
+            }
+            (codec, _) => get_codec_type_name(codec).to_string(),
+        };
+        Some(description)
+    }
+
+    /// The codec type of a media codec capability, with its sampling
+    /// frequency in Hz when a single one is set, as in a configuration.
+    pub(super) fn codec(&self) -> Option<(u8, Option<u32>)> {
+        if self.category != MEDIA_CODEC || self.info.len() < 2 {
+            return None;
+        }
+        let info = &self.info[2..];
+        let sampling_frequency = match (self.info[1], info.len()) {
+            (SBC, 1..) => single_bit(info[0] as u32 >> 4, &SBC_SAMPLING_FREQUENCIES),
+            (AAC, 3..) => single_bit(
+                (info[1] as u32) << 4 | (info[2] as u32) >> 4,
+                &AAC_SAMPLING_FREQUENCIES,
+            ),
+            _ => None,
+        };
+        Some((self.info[1], sampling_frequency))
+    }
+
+    pub(super) fn as_json(&self, start_byte: u16) -> String {
+        let mut fields = vec![
+            ParseBytesNode::new(start_byte, 1).format(
+                "Service Category",
+                self.category,
+                get_service_category_name(self.category),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 1, 1).format(
+                "LOSC",
+                self.info.len() as u8,
+                "",
+                self.error,
+            ),
+        ];
+        let start = start_byte + 2;
+        let info = &self.info[..];
+        match (self.category, info.len()) {
+            (0x03, 3..) => {
+                fields.push(ParseBytesNode::new(start, 1).format(
+                    "Recovery Type",
+                    info[0],
+                    match info[0] {
+                        0x01 => "RFC2733",
+                        _ => "Forbidden",
+                    },
+                    "",
+                ));
+                fields.push(ParseBytesNode::new(start + 1, 1).format(
+                    "Maximum Recovery Window Size",
+                    info[1],
+                    "",
+                    "",
+                ));
+                fields.push(ParseBytesNode::new(start + 2, 1).format(
+                    "Maximum Number of Media Packets",
+                    info[2],
+                    "",
+                    "",
+                ));
+            }
+            (0x04, 2..) => {
+                let cp_type = u16::from_le_bytes([info[0], info[1]]);
+                fields.push(ParseBytesNode::new(start, 2).format(
+                    "Content Protection Type",
+                    cp_type,
+                    match cp_type {
+                        0x0001 => "DTCP",
+                        0x0002 => "SCMS-T",
+                        _ => "",
+                    },
+                    "",
+                ));
+                if info.len() > 2 {
+                    fields.push(
+                        ParseBytesNode::new(start + 2, info.len() as u16 - 2).format(
+                            "Content Protection Type Specific Value",
+                            &info[2..],
+                            "",
+                            "",
+                        ),
+                    );
+                }
+            }
+            (MEDIA_CODEC, 2..) => fields.extend(media_codec_as_json(info, start)),
+            (_, 0) => {}
+            _ => fields.push(ParseBytesNode::new(start, info.len() as u16).format(
+                "Service Capabilities Information",
+                info,
+                "",
+                "",
+            )),
+        }
+        fields.join(", ")
+    }
+}
+
+fn vendor_codec(info: &[u8]) -> (u32, u16) {
+    (
+        u32::from_le_bytes([info[0], info[1], info[2], info[3]]),
+        u16::from_le_bytes([info[4], info[5]]),
+    )
+}
+
+fn aac_bit_rate(info: &[u8]) -> u32 {
+    (info[3] as u32 & 0x7f) << 16 | (info[4] as u32) << 8 | info[5] as u32
+}
+
+/// The media type, codec type and the codec specific information elements.
+fn media_codec_as_json(info: &[u8], start: u16) -> Vec<String> {
+    let mut fields = vec![
+        ParseBitsNode::new(start, 1, 4, 4).format(
+            "Media Type",
+            info[0] >> 4,
+            get_media_type_name(info[0] >> 4),
+            "",
+        ),
+        ParseBytesNode::new(start + 1, 1).format(
+            "Media Codec Type",
+            info[1],
+            get_codec_type_name(info[1]),
+            "",
+        ),
+    ];
+    let start = start + 2;
+    let specific = &info[2..];
+    match (info[1], specific.len()) {
+        (SBC, 4..) => fields.extend([
+            ParseBitsNode::new(start, 1, 4, 4).format(
+                "Sampling Frequency",
+                specific[0] >> 4,
+                &get_bits_name(specific[0] as u32 >> 4, &SBC_FREQUENCIES),
+                "",
+            ),
+            ParseBitsNode::new(start, 1, 0, 4).format(
+                "Channel Mode",
+                specific[0] & 0x0f,
+                &get_bits_name(specific[0] as u32 & 0x0f, &SBC_CHANNEL_MODES),
+                "",
+            ),
+            ParseBitsNode::new(start + 1, 1, 4, 4).format(
+                "Block Length",
+                specific[1] >> 4,
+                &get_bits_name(specific[1] as u32 >> 4, &SBC_BLOCK_LENGTHS),
+                "",
+            ),
+            ParseBitsNode::new(start + 1, 1, 2, 2).format(
+                "Subbands",
+                (specific[1] >> 2) & 0x03,
+                &get_bits_name((specific[1] as u32 >> 2) & 0x03, &SBC_SUBBANDS),
+                "",
+            ),
+            ParseBitsNode::new(start + 1, 1, 0, 2).format(
+                "Allocation Method",
+                specific[1] & 0x03,
+                &get_bits_name(specific[1] as u32 & 0x03, &SBC_ALLOCATION_METHODS),
+                "",
+            ),
+            ParseBytesNode::new(start + 2, 1).format("Minimum Bitpool Value", specific[2], "", ""),
+            ParseBytesNode::new(start + 3, 1).format("Maximum Bitpool Value", specific[3], "", ""),
+        ]),
+        (AAC, 6..) => {
+            let frequencies = (specific[1] as u16) << 4 | (specific[2] as u16) >> 4;
+            fields.extend([
+                ParseBytesNode::new(start, 1).format(
+                    "Object Type",
+                    specific[0],
+                    &get_bits_name(specific[0] as u32, &AAC_OBJECT_TYPES),
+                    "",
+                ),
+                // MSB first fields crossing a byte boundary get a node per byte
+                ParseBytesNode::new(start + 1, 1).format(
+                    "Sampling Frequency (1/2)",
+                    frequencies,
+                    &get_bits_name(frequencies as u32, &AAC_FREQUENCIES),
+                    "",
+                ),
+                ParseBitsNode::new(start + 2, 1, 4, 4).format(
+                    "Sampling Frequency (2/2)",
+                    frequencies,
+                    &get_bits_name(frequencies as u32, &AAC_FREQUENCIES),
+                    "",
+                ),
+                ParseBitsNode::new(start + 2, 1, 0, 4).format(
+                    "Channels",
+                    specific[2] & 0x0f,
+                    &get_bits_name(specific[2] as u32 & 0x0f, &AAC_CHANNELS),
+                    "",
+                ),
+                ParseBitsNode::new(start + 3, 1, 7, 1).format("VBR", specific[3] >> 7, "", ""),
+                ParseBitsNode::new(start + 3, 1, 0, 7).format(
+                    "Bit Rate (1/2)",
+                    aac_bit_rate(specific),
+                    &format!("{} bps", aac_bit_rate(specific)),
+                    "",
+                ),
+                ParseBytesNode::new(start + 4, 2).format(
+                    "Bit Rate (2/2)",
+                    aac_bit_rate(specific),
+                    &format!("{} bps", aac_bit_rate(specific)),
+                    "",
+                ),
+            ]);
+        }
+        (VENDOR, 6..) => {
+            let (vendor, codec) = vendor_codec(specific);
+            fields.push(ParseBytesNode::new(start, 4).format(
+                "Vendor ID",
+                vendor,
+                get_company_name(vendor as u16),
+                "",
+            ));
+            fields.push(ParseBytesNode::new(start + 4, 2).format(
+                "Vendor Specific Codec ID",
+                codec,
+                get_vendor_codec_name(vendor, codec),
+                "",
+            ));
+            let start = start + 6;
+            let values = &specific[6..];
+            match (get_vendor_codec_name(vendor, codec), values.len()) {
+                ("aptX" | "aptX HD", 1..) => fields.extend([
+                    ParseBitsNode::new(start, 1, 4, 4).format(
+                        "Sampling Frequency",
+                        values[0] >> 4,
+                        &get_bits_name(values[0] as u32 >> 4, &SBC_FREQUENCIES),
+                        "",
+                    ),
+                    ParseBitsNode::new(start, 1, 0, 4).format(
+                        "Channel Mode",
+                        values[0] & 0x0f,
+                        &get_bits_name(values[0] as u32 & 0x0f, &APTX_CHANNEL_MODES),
+                        "",
+                    ),
+                ]),
+                ("LDAC", 2..) => fields.extend([
+                    ParseBitsNode::new(start, 1, 0, 6).format(
+                        "Sampling Frequency",
+                        values[0] & 0x3f,
+                        &get_bits_name(values[0] as u32 & 0x3f, &LDAC_FREQUENCIES),
+                        "",
+                    ),
+                    ParseBitsNode::new(start + 1, 1, 0, 3).format(
+                        "Channel Mode",
+                        values[1] & 0x07,
+                        &get_bits_name(values[1] as u32 & 0x07, &LDAC_CHANNEL_MODES),
+                        "",
+                    ),
+                ]),
+                (_, 0) => {}
+                _ => fields.push(ParseBytesNode::new(start, values.len() as u16).format(
+                    "Vendor Specific Value",
+                    values,
+                    "",
+                    "",
+                )),
+            }
+        }
+        (_, 0) => {}
+        _ => fields.push(ParseBytesNode::new(start, specific.len() as u16).format(
+            "Codec Specific Information Elements",
+            specific,
+            "",
+            "",
+        )),
+    }
+    fields
+}
+
+const SBC_FREQUENCIES: [&str; 4] = ["16 kHz", "32 kHz", "44.1 kHz", "48 kHz"];
+const SBC_CHANNEL_MODES: [&str; 4] = ["Mono", "Dual Channel", "Stereo", "Joint Stereo"];
+const SBC_BLOCK_LENGTHS: [&str; 4] = ["4", "8", "12", "16"];
+const SBC_SUBBANDS: [&str; 2] = ["4", "8"];
+const SBC_ALLOCATION_METHODS: [&str; 2] = ["SNR", "Loudness"];
+const AAC_OBJECT_TYPES: [&str; 8] = [
+    "MPEG-2 AAC LC",
+    "MPEG-4 AAC LC",
+    "MPEG-4 AAC LTP",
+    "MPEG-4 AAC scalable",
+    "MPEG-4 HE-AAC",
+    "MPEG-4 HE-AACv2",
+    "MPEG-4 HE-AAC-ELDv2",
+    "",
+];
+const AAC_FREQUENCIES: [&str; 12] = [
+    "8 kHz",
+    "11.025 kHz",
+    "12 kHz",
+    "16 kHz",
+    "22.05 kHz",
+    "24 kHz",
+    "32 kHz",
+    "44.1 kHz",
+    "48 kHz",
+    "64 kHz",
+    "88.2 kHz",
+    "96 kHz",
+];
+const SBC_SAMPLING_FREQUENCIES: [u32; 4] = [16000, 32000, 44100, 48000];
+const AAC_SAMPLING_FREQUENCIES: [u32; 12] = [
+    8000, 11025, 12000, 16000, 22050, 24000, 32000, 44100, 48000, 64000, 88200, 96000,
+];
+const AAC_CHANNELS: [&str; 4] = ["1", "2", "5.1", "7.1"];
+const APTX_CHANNEL_MODES: [&str; 4] = ["", "", "Stereo", "Mono"];
+const LDAC_FREQUENCIES: [&str; 6] = [
+    "44.1 kHz",
+    "48 kHz",
+    "88.2 kHz",
+    "96 kHz",
+    "176.4 kHz",
+    "192 kHz",
+];
+const LDAC_CHANNEL_MODES: [&str; 3] = ["Mono", "Dual Channel", "Stereo"];
+
+/// Names the bits set in a field, `names` listing them from the most
+/// significant bit of the field down.
+fn get_bits_name(value: u32, names: &[&str]) -> String {
+    names
+        .iter()
+        .enumerate()
+        .filter(|(i, name)| !name.is_empty() && value & (1 << (names.len() - 1 - i)) != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+/// The value of the only bit set in a field, `values` listing them from
+/// the most significant bit of the field down.
+fn single_bit(value: u32, values: &[u32]) -> Option<u32> {
+    match value.count_ones() {
+        1 => values
+            .len()
+            .checked_sub(1 + value.trailing_zeros() as usize)
+            .map(|i| values[i]),
+        _ => None,
+    }
+}
+
+pub(super) fn get_service_category_name(category: u8) -> &'static str {
+    match category {
+        0x01 => "Media Transport",
+        0x02 => "Reporting",
+        0x03 => "Recovery",
+        0x04 => "Content Protection",
+        0x05 => "Header Compression",
+        0x06 => "Multiplexing",
+        0x07 => "Media Codec",
+        0x08 => "Delay Reporting",
+        _ => "Reserved",
+    }
+}
+
+pub(super) fn get_media_type_name(media_type: u8) -> &'static str {
+    match media_type {
+        0x00 => "Audio",
+        0x01 => "Video",
+        0x02 => "Multimedia",
+        _ => "Reserved",
+    }
+}
+
+fn get_codec_type_name(codec: u8) -> &'static str {
+    match codec {
+        0x00 => "SBC",
+        0x01 => "MPEG-1,2 Audio",
+        0x02 => "MPEG-2,4 AAC",
+        0x04 => "MPEG-D USAC",
+        0x08 => "ATRAC family",
+        0xff => "Non-A2DP",
+        _ => "Reserved",
+    }
+}
+
+fn get_vendor_codec_name(vendor: u32, codec: u16) -> &'static str {
+    match (vendor, codec) {
+        (0x0000004f, 0x0001) => "aptX",
+        (0x000000d7, 0x0024) => "aptX HD",
+        (0x000000d7, 0x00ad) => "aptX Adaptive",
+        (0x0000012d, 0x00aa) => "LDAC",
+        _ => "",
+    }
+}
@@ -0,0 +1,1017 @@
+use crate::ParseBitsNode;
+use crate::ParseBytesNode;
+
+use super::capabilities::{AAC, SBC};
+
+const SBC_SYNCWORD: u8 = 0x9c;
+const SBC_SAMPLING_FREQUENCIES: [u32; 4] = [16000, 32000, 44100, 48000];
+const AAC_SAMPLING_FREQUENCIES: [u32; 13] = [
+    96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000, 7350,
+];
+
+/// A packet on a media transport channel: the RTP header and the codec
+/// payload the stream was configured for.
+#[derive(Debug, PartialEq)]
+pub(crate) struct AvdtpMedia {
+    // ACP SEID of the stream and its codec
+    stream: Option<(u8, Option<String>)>,
+    rtp: Option<RtpHeader>,
+    payload: MediaPayload,
+    data: Vec<u8>,
+}
+
+impl AvdtpMedia {
+    pub(super) fn new(
+        data: &[u8],
+        stream: Option<(u8, Option<String>)>,
+        codec_type: Option<u8>,
+    ) -> Self {
+        let rtp = RtpHeader::new(data);
+        let payload = match &rtp {
+            Some(rtp) => {
+                let payload = &data[rtp.len() as usize..data.len() - rtp.padding_len as usize];
+                match codec_type {
+                    Some(SBC) => MediaPayload::Sbc(SbcPayload::new(payload)),
+                    Some(AAC) => MediaPayload::Aac(AacFrame::new(payload)),
+                    _ => MediaPayload::Other(payload.len() as u16),
+                }
+            }
+            None => MediaPayload::Other(0),
+        };
+        AvdtpMedia {
+            stream,
+            rtp,
+            payload,
+            data: data.to_vec(),
+        }
+    }
+
+    pub(super) fn as_json(&self, start_byte: u16) -> String {
+        let alias = match &self.stream {
+            Some((seid, Some(codec))) => format!("Stream of SEID {}, {}", seid, codec),
+            Some((seid, None)) => format!("Stream of SEID {}", seid),
+            None => String::new(),
+        };
+        let Some(rtp) = &self.rtp else {
+            return ParseBytesNode::new(start_byte, self.data.len() as u16).format(
+                "Media Packet",
+                &self.data[..],
+                &alias,
+                "Not an RTP packet",
+            );
+        };
+        let mut fields = vec![format!(
+            r#""RTP Header": {{{}}}"#,
+            rtp.as_json(start_byte, &alias)
+        )];
+        let offset = start_byte + rtp.len();
+        let end = self.data.len() - rtp.padding_len as usize;
+        let payload = &self.data[rtp.len() as usize..end];
+        fields.push(match &self.payload {
+            MediaPayload::Sbc(sbc) => format!(r#""SBC Payload": {{{}}}"#, sbc.as_json(offset)),
+            MediaPayload::Aac(aac) => format!(r#""AAC Payload": {{{}}}"#, aac.as_json(offset)),
+            MediaPayload::Other(len) => {
+                ParseBytesNode::new(offset, *len).format("Media Payload", payload, "", "")
+            }
+        });
+        if rtp.padding_len > 0 {
+            fields.push(
+                ParseBytesNode::new(start_byte + end as u16, rtp.padding_len as u16).format(
+                    "Padding",
+                    &self.data[end..],
+                    "",
+                    "",
+                ),
+            );
+        }
+        fields.join(", ")
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct RtpHeader {
+    padding: bool,
+    extension: bool,
+    csrc_count: u8,
+    marker: bool,
+    payload_type: u8,
+    sequence_number: u16,
+    timestamp: u32,
+    ssrc: u32,
+    csrcs: Vec<u32>,
+    // profile specific identifier and the extension words
+    header_extension: Option<(u16, Vec<u8>)>,
+    padding_len: u8,
+}
+
+impl RtpHeader {
+    fn new(data: &[u8]) -> Option<Self> {
+        if data.len() < 12 || data[0] >> 6 != 2 {
+            return None;
+        }
+        let csrc_count = data[0] & 0x0f;
+        let mut offset = 12 + 4 * csrc_count as usize;
+        let csrcs = data
+            .get(12..offset)?
+            .chunks_exact(4)
+            .map(|csrc| u32::from_be_bytes([csrc[0], csrc[1], csrc[2], csrc[3]]))
+            .collect();
+        let extension = data[0] & 0x10 != 0;
+        let header_extension = match extension {
+            true => {
+                let header = data.get(offset..offset + 4)?;
+                let words = u16::from_be_bytes([header[2], header[3]]) as usize;
+                let extension_data = data.get(offset + 4..offset + 4 + 4 * words)?;
+                offset += 4 + 4 * words;
+                Some((
+                    u16::from_be_bytes([header[0], header[1]]),
+                    extension_data.to_vec(),
+                ))
+            }
+            false => None,
+        };
+        let padding = data[0] & 0x20 != 0;
+        let padding_len = match padding {
+            true => *data.last()?,
+            false => 0,
+        };
+        if offset + padding_len as usize > data.len() {
+            return None;
+        }
+        Some(RtpHeader {
+            padding,
+            extension,
+            csrc_count,
+            marker: data[1] & 0x80 != 0,
+            payload_type: data[1] & 0x7f,
+            sequence_number: u16::from_be_bytes([data[2], data[3]]),
+            timestamp: u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+            ssrc: u32::from_be_bytes([data[8], data[9], data[10], data[11]]),
+            csrcs,
+            header_extension,
+            padding_len,
+        })
+    }
+
+    fn len(&self) -> u16 {
+        let extension_len = self
+            .header_extension
+            .as_ref()
+            .map_or(0, |(_, data)| 4 + data.len());
+        (12 + 4 * self.csrcs.len() + extension_len) as u16
+    }
+
+    fn as_json(&self, start_byte: u16, stream: &str) -> String {
+        let mut fields = vec![
+            ParseBitsNode::new(start_byte, 1, 6, 2).format("Version", 2u8, "", ""),
+            ParseBitsNode::new(start_byte, 1, 5, 1).format("Padding", self.padding as u8, "", ""),
+            ParseBitsNode::new(start_byte, 1, 4, 1).format(
+                "Extension",
+                self.extension as u8,
+                "",
+                "",
+            ),
+            ParseBitsNode::new(start_byte, 1, 0, 4).format("CSRC Count", self.csrc_count, "", ""),
+            ParseBitsNode::new(start_byte + 1, 1, 7, 1).format("Marker", self.marker as u8, "", ""),
+            ParseBitsNode::new(start_byte + 1, 1, 0, 7).format(
+                "Payload Type",
+                self.payload_type,
+                stream,
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 2, 2).format(
+                "Sequence Number",
+                self.sequence_number,
+                "",
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 4, 4).format("Timestamp", self.timestamp, "", ""),
+            ParseBytesNode::new(start_byte + 8, 4).format("SSRC", self.ssrc, "", ""),
+        ];
+        for (i, csrc) in self.csrcs.iter().enumerate() {
+            fields.push(
+                ParseBytesNode::new(start_byte + 12 + 4 * i as u16, 4).format(
+                    &format!("CSRC[{}]", i),
+                    *csrc,
+                    "",
+                    "",
+                ),
+            );
+        }
+        if let Some((profile, data)) = &self.header_extension {
+            let offset = start_byte + 12 + 4 * self.csrcs.len() as u16;
+            fields.push(ParseBytesNode::new(offset, 2).format(
+                "Extension Profile",
+                *profile,
+                "",
+                "",
+            ));
+            fields.push(ParseBytesNode::new(offset + 2, 2).format(
+                "Extension Length",
+                data.len() as u16 / 4,
+                "",
+                "",
+            ));
+            if !data.is_empty() {
+                fields.push(ParseBytesNode::new(offset + 4, data.len() as u16).format(
+                    "Extension Data",
+                    &data[..],
+                    "",
+                    "",
+                ));
+            }
+        }
+        fields.join(", ")
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum MediaPayload {
+    Sbc(SbcPayload),
+    Aac(AacFrame),
+    // length of a payload of a codec that is not decoded
+    Other(u16),
+}
+
+/// The A2DP media payload header of an SBC stream and the frames after it.
+#[derive(Debug, PartialEq)]
+struct SbcPayload {
+    header: Option<u8>,
+    frames: Vec<SbcFrame>,
+    // offset and bytes of what is not a complete frame
+    rest: Option<(u16, Vec<u8>)>,
+}
+
+impl SbcPayload {
+    fn new(data: &[u8]) -> Self {
+        let Some(header) = data.first().copied() else {
+            return SbcPayload {
+                header: None,
+                frames: Vec::new(),
+                rest: None,
+            };
+        };
+        let mut frames = Vec::new();
+        let mut offset = 1;
+        match header & 0xc0 {
+            // the starting packet of a fragmented frame holds its header,
+            // the later ones carry on with its data
+            0x80 => {}
+            fragmented => {
+                let count = match fragmented {
+                    0xc0 => 1,
+                    _ => header & 0x0f,
+                };
+                while frames.len() < count as usize {
+                    let Some(frame) = SbcFrame::new(&data[offset..], fragmented == 0xc0) else {
+                        break;
+                    };
+                    offset += frame.data.len();
+                    frames.push(frame);
+                }
+            }
+        }
+        let rest = (offset < data.len()).then(|| (offset as u16, data[offset..].to_vec()));
+        SbcPayload {
+            header: Some(header),
+            frames,
+            rest,
+        }
+    }
+
+    fn frame_count(&self) -> u8 {
+        self.header.unwrap_or_default() & 0x0f
+    }
+
+    /// The frames this packet starts: a fragmented frame counts once, in
+    /// its starting packet.
+    fn starting_frames(&self) -> u32 {
+        match self.header.unwrap_or_default() & 0xc0 {
+            0xc0 => 1,
+            0x80 => 0,
+            _ => self.frame_count() as u32,
+        }
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let Some(header) = self.header else {
+            return String::new();
+        };
+        let mut fields = vec![
+            ParseBitsNode::new(start_byte, 1, 7, 1).format("Fragmented", header >> 7, "", ""),
+            ParseBitsNode::new(start_byte, 1, 6, 1).format(
+                "Starting Packet",
+                (header >> 6) & 0x01,
+                "",
+                "",
+            ),
+            ParseBitsNode::new(start_byte, 1, 5, 1).format(
+                "Last Packet",
+                (header >> 5) & 0x01,
+                "",
+                "",
+            ),
+            ParseBitsNode::new(start_byte, 1, 0, 4).format(
+                "Number Of Frames",
+                self.frame_count(),
+                match header & 0x80 {
+                    0 => "",
+                    _ => "Fragments left, this one included",
+                },
+                "",
+            ),
+        ];
+        let mut offset = start_byte + 1;
+        for (i, frame) in self.frames.iter().enumerate() {
+            fields.push(format!(
+                r#""SBC Frame[{}]": {{{}}}"#,
+                i,
+                frame.as_json(offset)
+            ));
+            offset += frame.data.len() as u16;
+        }
+        if let Some((offset, rest)) = &self.rest {
+            let (key, error) = match header & 0x80 {
+                0 => ("Frame Data", "Not a complete SBC frame"),
+                _ => ("Frame Fragment", ""),
+            };
+            fields.push(
+                ParseBytesNode::new(start_byte + offset, rest.len() as u16).format(
+                    key,
+                    &rest[..],
+                    "",
+                    error,
+                ),
+            );
+        }
+        fields.join(", ")
+    }
+}
+
+/// An SBC frame, its header and the length it works out to.
+#[derive(Debug, PartialEq)]
+struct SbcFrame {
+    sampling_frequency: u8,
+    blocks: u8,
+    channel_mode: u8,
+    allocation_method: u8,
+    subbands: u8,
+    bitpool: u8,
+    crc: u8,
+    data: Vec<u8>,
+}
+
+impl SbcFrame {
+    /// Reads the frame at the start of `data`, if a whole one is there or
+    /// it is the first fragment of one.
+    fn new(data: &[u8], fragment: bool) -> Option<Self> {
+        if data.len() < 4 || data[0] != SBC_SYNCWORD {
+            return None;
+        }
+        let mut frame = SbcFrame {
+            sampling_frequency: data[1] >> 6,
+            blocks: (data[1] >> 4) & 0x03,
+            channel_mode: (data[1] >> 2) & 0x03,
+            allocation_method: (data[1] >> 1) & 0x01,
+            subbands: data[1] & 0x01,
+            bitpool: data[2],
+            crc: data[3],
+            data: Vec::new(),
+        };
+        frame.data = match fragment {
+            true => data[..frame.frame_length().min(data.len())].to_vec(),
+            false => data.get(..frame.frame_length())?.to_vec(),
+        };
+        Some(frame)
+    }
+
+    fn block_count(&self) -> usize {
+        4 * (self.blocks as usize + 1)
+    }
+
+    fn subband_count(&self) -> usize {
+        4 * (self.subbands as usize + 1)
+    }
+
+    fn frame_length(&self) -> usize {
+        let channels = match self.channel_mode {
+            0 => 1,
+            _ => 2,
+        };
+        let bitpool = self.bitpool as usize;
+        let bits = match self.channel_mode {
+            0 | 1 => self.block_count() * channels * bitpool,
+            2 => self.block_count() * bitpool,
+            _ => self.subband_count() + self.block_count() * bitpool,
+        };
+        4 + 4 * self.subband_count() * channels / 8 + bits.div_ceil(8)
+    }
+
+    fn sampling_frequency(&self) -> u32 {
+        SBC_SAMPLING_FREQUENCIES[self.sampling_frequency as usize]
+    }
+
+    fn bit_rate(&self) -> u32 {
+        let samples = (self.subband_count() * self.block_count()) as u32;
+        8 * self.frame_length() as u32 * self.sampling_frequency() / samples
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        [
+            ParseBytesNode::new(start_byte, 1).format("Syncword", SBC_SYNCWORD, "", ""),
+            ParseBitsNode::new(start_byte + 1, 1, 6, 2).format(
+                "Sampling Frequency",
+                self.sampling_frequency,
+                &format!("{} Hz", self.sampling_frequency()),
+                "",
+            ),
+            ParseBitsNode::new(start_byte + 1, 1, 4, 2).format(
+                "Blocks",
+                self.blocks,
+                &self.block_count().to_string(),
+                "",
+            ),
+            ParseBitsNode::new(start_byte + 1, 1, 2, 2).format(
+                "Channel Mode",
+                self.channel_mode,
+                ["Mono", "Dual Channel", "Stereo", "Joint Stereo"][self.channel_mode as usize],
+                "",
+            ),
+            ParseBitsNode::new(start_byte + 1, 1, 1, 1).format(
+                "Allocation Method",
+                self.allocation_method,
+                ["Loudness", "SNR"][self.allocation_method as usize],
+                "",
+            ),
+            ParseBitsNode::new(start_byte + 1, 1, 0, 1).format(
+                "Subbands",
+                self.subbands,
+                &self.subband_count().to_string(),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 2, 1).format("Bitpool", self.bitpool, "", ""),
+            ParseBytesNode::new(start_byte + 3, 1).format("CRC", self.crc, "", ""),
+            ParseBytesNode::new(start_byte + 4, self.data.len() as u16 - 4).format(
+                "Frame Data",
+                &self.data[4..],
+                &format!(
+                    "Frame of {} bytes, {} bps",
+                    self.frame_length(),
+                    self.bit_rate()
+                ),
+                "",
+            ),
+        ]
+        .join(", ")
+    }
+}
+
+/// A field read by [`BitReader`], at its bit position in the payload.
+#[derive(Debug, PartialEq)]
+struct BitField {
+    key: &'static str,
+    value: u32,
+    position: usize,
+    len: u8,
+    alias: String,
+}
+
+/// Reads the most significant bits first, keeping the fields it was asked
+/// to name.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    fields: Vec<BitField>,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BitReader {
+            data,
+            position: 0,
+            fields: Vec::new(),
+        }
+    }
+
+    fn read(&mut self, len: u8) -> Option<u32> {
+        if self.position + len as usize > 8 * self.data.len() {
+            return None;
+        }
+        let mut value = 0;
+        for _ in 0..len {
+            let bit = self.data[self.position / 8] >> (7 - self.position % 8) & 0x01;
+            value = value << 1 | bit as u32;
+            self.position += 1;
+        }
+        Some(value)
+    }
+
+    fn field(&mut self, key: &'static str, len: u8) -> Option<u32> {
+        self.field_with(key, len, |_| String::new())
+    }
+
+    fn field_with(
+        &mut self,
+        key: &'static str,
+        len: u8,
+        alias: impl Fn(u32) -> String,
+    ) -> Option<u32> {
+        let position = self.position;
+        let value = self.read(len)?;
+        self.fields.push(BitField {
+            key,
+            value,
+            position,
+            len,
+            alias: alias(value),
+        });
+        Some(value)
+    }
+}
+
+/// The AAC framing of a packet: an ADTS header, or the LATM AudioMuxElement
+/// A2DP carries with the StreamMuxConfig in band.
+#[derive(Debug, PartialEq)]
+struct AacFrame {
+    adts: bool,
+    fields: Vec<BitField>,
+    // bit position of the access unit and its length in bytes
+    payload: Option<(usize, usize)>,
+    len: usize,
+    error: &'static str,
+}
+
+impl AacFrame {
+    fn new(data: &[u8]) -> Self {
+        let adts = data.len() >= 2 && data[0] == 0xff && data[1] & 0xf6 == 0xf0;
+        let mut reader = BitReader::new(data);
+        let result = match adts {
+            true => read_adts_header(&mut reader),
+            false => read_audio_mux_element(&mut reader),
+        };
+        let (payload, error) = match result {
+            Ok(len) if reader.position + 8 * len <= 8 * data.len() => {
+                (Some((reader.position, len)), "")
+            }
+            Ok(_) => (None, "Payload exceeds the packet"),
+            Err(error) => (None, error),
+        };
+        AacFrame {
+            adts,
+            fields: reader.fields,
+            payload,
+            len: data.len(),
+            error,
+        }
+    }
+
+    /// The sampling frequency the in band configuration names.
+    fn sampling_frequency(&self) -> Option<u32> {
+        self.fields
+            .iter()
+            .find(|field| field.key == "Sampling Frequency Index")
+            .and_then(|field| AAC_SAMPLING_FREQUENCIES.get(field.value as usize).copied())
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut fields = vec![format!(
+            r#""Framing": "{}""#,
+            match self.adts {
+                true => "ADTS",
+                false => "LATM",
+            }
+        )];
+        // the fields are MSB first, one crossing a byte boundary is given a
+        // node per byte so that each keeps LSB relative bit offsets, unless
+        // it is made of whole bytes
+        for field in self.fields.iter() {
+            let end = field.position + field.len as usize;
+            if field.position.is_multiple_of(8) && end.is_multiple_of(8) {
+                fields.push(
+                    ParseBytesNode::new(
+                        start_byte + (field.position / 8) as u16,
+                        field.len as u16 / 8,
+                    )
+                    .format(field.key, field.value, &field.alias, ""),
+                );
+                continue;
+            }
+            let pieces = (end - 1) / 8 - field.position / 8 + 1;
+            for piece in 0..pieces {
+                let byte = field.position / 8 + piece;
+                let first = field.position.max(8 * byte);
+                let last = end.min(8 * byte + 8);
+                let key = match pieces {
+                    1 => field.key.to_string(),
+                    _ => format!("{} ({}/{})", field.key, piece + 1, pieces),
+                };
+                fields.push(
+                    ParseBitsNode::new(
+                        start_byte + byte as u16,
+                        1,
+                        (8 * byte + 8 - last) as u8,
+                        (last - first) as u8,
+                    )
+                    .format(&key, field.value, &field.alias, ""),
+                );
+            }
+        }
+        match self.payload {
+            Some((position, len)) => {
+                let start = position / 8;
+                let bytes = (position % 8 + 8 * len).div_ceil(8);
+                fields.push(
+                    ParseBytesNode::new(start_byte + start as u16, bytes as u16).format(
+                        "Access Unit",
+                        len as u16,
+                        &format!("{} bytes", len),
+                        "",
+                    ),
+                );
+            }
+            None if !self.error.is_empty() => {
+                fields.push(ParseBytesNode::new(start_byte, self.len as u16).format(
+                    "AAC Data",
+                    self.len as u16,
+                    "",
+                    self.error,
+                ))
+            }
+            None => {}
+        }
+        fields.join(", ")
+    }
+}
+
+fn get_sampling_frequency_alias(index: u32) -> String {
+    match AAC_SAMPLING_FREQUENCIES.get(index as usize) {
+        Some(frequency) => format!("{} Hz", frequency),
+        None => "Escape value".to_string(),
+    }
+}
+
+/// Reads an ADTS header and returns the length of the raw data after it.
+fn read_adts_header(reader: &mut BitReader) -> Result<usize, &'static str> {
+    let truncated = "ADTS header exceeds the packet";
+    let mut header = || -> Option<(u32, u32)> {
+        reader.field("Syncword", 12)?;
+        reader.field("ID", 1)?;
+        reader.field("Layer", 2)?;
+        let protection_absent = reader.field("Protection Absent", 1)?;
+        reader.field_with("Profile", 2, |profile| {
+            ["Main", "LC", "SSR", "LTP"][profile as usize].to_string()
+        })?;
+        reader.field_with("Sampling Frequency Index", 4, get_sampling_frequency_alias)?;
+        reader.field("Private Bit", 1)?;
+        reader.field("Channel Configuration", 3)?;
+        reader.field("Original/Copy", 1)?;
+        reader.field("Home", 1)?;
+        reader.field("Copyright Identification Bit", 1)?;
+        reader.field("Copyright Identification Start", 1)?;
+        let frame_length = reader.field("Frame Length", 13)?;
+        reader.field("Buffer Fullness", 11)?;
+        reader.field("Number Of Raw Data Blocks", 2)?;
+        if protection_absent == 0 {
+            reader.field("CRC", 16)?;
+        }
+        Some((frame_length, protection_absent))
+    };
+    let (frame_length, protection_absent) = header().ok_or(truncated)?;
+    let header_len = match protection_absent {
+        0 => 9,
+        _ => 7,
+    };
+    (frame_length as usize)
+        .checked_sub(header_len)
+        .ok_or("Frame length shorter than the header")
+}
+
+/// Reads an AudioMuxElement with muxConfigPresent set and returns the
+/// length of its one payload.
+fn read_audio_mux_element(reader: &mut BitReader) -> Result<usize, &'static str> {
+    let truncated = "AudioMuxElement exceeds the packet";
+    let use_same_stream_mux = reader.field("Use Same Stream Mux", 1).ok_or(truncated)?;
+    if use_same_stream_mux == 0 {
+        read_stream_mux_config(reader)?;
+    }
+    let position = reader.position;
+    let mut len = 0;
+    loop {
+        let tmp = reader.read(8).ok_or(truncated)?;
+        len += tmp as usize;
+        if tmp != 0xff {
+            break;
+        }
+    }
+    reader.fields.push(BitField {
+        key: "Payload Length",
+        value: len as u32,
+        position,
+        len: (reader.position - position).min(0xf8) as u8,
+        alias: String::new(),
+    });
+    Ok(len)
+}
+
+/// Reads a variable length LatmValue, `bytesForValue` plus one bytes.
+fn read_latm_value(reader: &mut BitReader, key: &'static str) -> Option<u32> {
+    let bytes = reader.read(2)? as u8 + 1;
+    reader.field(key, 8 * bytes)
+}
+
+fn read_stream_mux_config(reader: &mut BitReader) -> Result<(), &'static str> {
+    let truncated = "StreamMuxConfig exceeds the packet";
+    let audio_mux_version = reader.field("Audio Mux Version", 1).ok_or(truncated)?;
+    if audio_mux_version == 1 {
+        if reader.field("Audio Mux Version A", 1).ok_or(truncated)? != 0 {
+            return Err("Audio Mux Version A is not supported");
+        }
+        read_latm_value(reader, "Tara Buffer Fullness").ok_or(truncated)?;
+    }
+    reader
+        .field("All Streams Same Time Framing", 1)
+        .ok_or(truncated)?;
+    reader.field("Num Sub Frames", 6).ok_or(truncated)?;
+    let programs = reader.field("Num Program", 4).ok_or(truncated)?;
+    let layers = reader.field("Num Layer", 3).ok_or(truncated)?;
+    if programs != 0 || layers != 0 {
+        return Err("Multiple programs or layers are not supported");
+    }
+    match audio_mux_version {
+        0 => read_audio_specific_config(reader)?,
+        _ => {
+            let len = read_latm_value(reader, "Audio Specific Config Length").ok_or(truncated)?;
+            let end = reader.position + len as usize;
+            read_audio_specific_config(reader)?;
+            reader.position = reader.position.max(end);
+        }
+    }
+    let frame_length_type = reader.field("Frame Length Type", 3).ok_or(truncated)?;
+    if frame_length_type != 0 {
+        return Err("Frame length types other than 0 are not supported");
+    }
+    reader.field("LATM Buffer Fullness", 8).ok_or(truncated)?;
+    if reader.field("Other Data Present", 1).ok_or(truncated)? == 1 {
+        match audio_mux_version {
+            0 => loop {
+                let escape = reader.read(1).ok_or(truncated)?;
+                reader.field("Other Data Length", 8).ok_or(truncated)?;
+                if escape == 0 {
+                    break;
+                }
+            },
+            _ => {
+                read_latm_value(reader, "Other Data Length").ok_or(truncated)?;
+            }
+        }
+    }
+    if reader.field("CRC Check Present", 1).ok_or(truncated)? == 1 {
+        reader.field("CRC Checksum", 8).ok_or(truncated)?;
+    }
+    Ok(())
+}
+
+fn read_audio_specific_config(reader: &mut BitReader) -> Result<(), &'static str> {
+    let truncated = "AudioSpecificConfig exceeds the packet";
+    let object_type = |reader: &mut BitReader| -> Option<u32> {
+        match reader.field_with("Audio Object Type", 5, get_audio_object_type_name)? {
+            31 => reader
+                .field("Audio Object Type Ext", 6)
+                .map(|object_type| 32 + object_type),
+            object_type => Some(object_type),
+        }
+    };
+    let sampling_frequency = |reader: &mut BitReader| -> Option<()> {
+        if reader.field_with("Sampling Frequency Index", 4, get_sampling_frequency_alias)? == 0x0f {
+            reader.field("Sampling Frequency", 24)?;
+        }
+        Some(())
+    };
+    let mut audio_object_type = object_type(reader).ok_or(truncated)?;
+    sampling_frequency(reader).ok_or(truncated)?;
+    let channel_configuration = reader.field("Channel Configuration", 4).ok_or(truncated)?;
+    // SBR and PS name the core object type after the extension frequency
+    if audio_object_type == 5 || audio_object_type == 29 {
+        reader
+            .field_with(
+                "Extension Sampling Frequency Index",
+                4,
+                get_sampling_frequency_alias,
+            )
+            .ok_or(truncated)?;
+        audio_object_type = object_type(reader).ok_or(truncated)?;
+    }
+    match audio_object_type {
+        1..=4 | 6 | 7 | 17 | 19..=23 => {
+            if channel_configuration == 0 {
+                return Err("Program config elements are not supported");
+            }
+            reader.field("Frame Length Flag", 1).ok_or(truncated)?;
+            if reader.field("Depends On Core Coder", 1).ok_or(truncated)? == 1 {
+                reader.field("Core Coder Delay", 14).ok_or(truncated)?;
+            }
+            reader.field("Extension Flag", 1).ok_or(truncated)?;
+            Ok(())
+        }
+        _ => Err("Audio object type is not supported"),
+    }
+}
+
+fn get_audio_object_type_name(object_type: u32) -> String {
+    match object_type {
+        1 => "AAC Main",
+        2 => "AAC LC",
+        3 => "AAC SSR",
+        4 => "AAC LTP",
+        5 => "SBR",
+        6 => "AAC Scalable",
+        17 => "ER AAC LC",
+        23 => "ER AAC LD",
+        29 => "PS",
+        31 => "Escape value",
+        39 => "ER AAC ELD",
+        _ => "",
+    }
+    .to_string()
+}
+
+/// What a media channel carried: packets, losses, bit rate per second of
+/// media time and the SBC bitpool over time.
+#[derive(Debug, Default)]
+pub(super) struct MediaStats {
+    packets: u32,
+    frames: u32,
+    payload_bytes: u64,
+    sequence_number: Option<u16>,
+    lost_packets: u32,
+    // the sequence number a gap ended at and how many packets it missed
+    gaps: Vec<(u16, u16)>,
+    out_of_order: u32,
+    first_timestamp: Option<u32>,
+    // media payload bits in each second of RTP time since the first packet
+    bits_per_second: Vec<u64>,
+    bitpool: Option<u8>,
+    // sequence number where the bitpool changed, from and to
+    bitpool_changes: Vec<(u16, u8, u8)>,
+}
+
+impl MediaStats {
+    /// Counts a packet, `sampling_frequency` being the RTP clock rate the
+    /// stream was configured with.
+    pub(super) fn update(&mut self, media: &AvdtpMedia, sampling_frequency: Option<u32>) {
+        let Some(rtp) = &media.rtp else {
+            return;
+        };
+        self.packets += 1;
+        let payload_len = media.data.len() - rtp.len() as usize - rtp.padding_len as usize;
+        self.payload_bytes += payload_len as u64;
+
+        let sequence_number = rtp.sequence_number;
+        match self.sequence_number {
+            Some(last) => match sequence_number.wrapping_sub(last) {
+                1 => self.sequence_number = Some(sequence_number),
+                // late or repeated packets do not move the expected number
+                0 | 0x8000.. => self.out_of_order += 1,
+                diff => {
+                    self.lost_packets += diff as u32 - 1;
+                    self.gaps.push((sequence_number, diff - 1));
+                    self.sequence_number = Some(sequence_number);
+                }
+            },
+            None => self.sequence_number = Some(sequence_number),
+        }
+
+        let mut sampling_frequency = sampling_frequency;
+        match &media.payload {
+            MediaPayload::Sbc(sbc) => {
+                self.frames += sbc.starting_frames();
+                if let Some(frame) = sbc.frames.first() {
+                    sampling_frequency = sampling_frequency.or(Some(frame.sampling_frequency()));
+                    match self.bitpool {
+                        Some(bitpool) if bitpool != frame.bitpool => {
+                            self.bitpool_changes
+                                .push((sequence_number, bitpool, frame.bitpool));
+                        }
+                        _ => {}
+                    }
+                    self.bitpool = Some(frame.bitpool);
+                }
+            }
+            MediaPayload::Aac(aac) => {
+                self.frames += aac.payload.is_some() as u32;
+                sampling_frequency = sampling_frequency.or(aac.sampling_frequency());
+            }
+            MediaPayload::Other(_) => {}
+        }
+
+        let first_timestamp = *self.first_timestamp.get_or_insert(rtp.timestamp);
+        if let Some(sampling_frequency) = sampling_frequency.filter(|frequency| *frequency > 0) {
+            let second =
+                (rtp.timestamp.wrapping_sub(first_timestamp) / sampling_frequency) as usize;
+            // a timestamp far off the stream is not worth a day of empty seconds
+            if second < self.bits_per_second.len() + 60 {
+                if second >= self.bits_per_second.len() {
+                    self.bits_per_second.resize(second + 1, 0);
+                }
+                self.bits_per_second[second] += 8 * payload_len as u64;
+            }
+        }
+    }
+
+    pub(super) fn summary(&self) -> String {
+        let mut fields = vec![
+            format!(r#""Packets": {}"#, self.packets),
+            format!(r#""Frames": {}"#, self.frames),
+            format!(r#""Payload Bytes": {}"#, self.payload_bytes),
+            format!(r#""Lost Packets": {}"#, self.lost_packets),
+        ];
+        if !self.gaps.is_empty() {
+            let gaps_s = self
+                .gaps
+                .iter()
+                .map(|(sequence_number, missing)| {
+                    format!(r#""{} missing before {:#06x}""#, missing, sequence_number)
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            fields.push(format!(r#""Sequence Gaps": [{}]"#, gaps_s));
+        }
+        if self.out_of_order > 0 {
+            fields.push(format!(r#""Out Of Order": {}"#, self.out_of_order));
+        }
+        if !self.bits_per_second.is_empty() {
+            let bitrate_s = self
+                .bits_per_second
+                .iter()
+                .map(|bits| bits.to_string())
+                .collect::<Vec<String>>()
+                .join(", ");
+            fields.push(format!(r#""Bitrate Per Second": [{}]"#, bitrate_s));
+        }
+        if let Some(bitpool) = self.bitpool {
+            fields.push(format!(r#""Bitpool": {}"#, bitpool));
+        }
+        if !self.bitpool_changes.is_empty() {
+            let changes_s = self
+                .bitpool_changes
+                .iter()
+                .map(|(sequence_number, from, to)| {
+                    format!(r#""{} -> {} at {:#06x}""#, from, to, sequence_number)
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            fields.push(format!(r#""Bitpool Changes": [{}]"#, changes_s));
+        }
+        fields.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AacFrame, SbcPayload, SBC_SYNCWORD};
+    use crate::str_to_array;
+
+    fn sbc_payload(header: u8, frame: [u8; 3], len: usize, frames: usize) -> Vec<u8> {
+        let mut data = vec![header];
+        for _ in 0..frames {
+            let start = data.len();
+            data.extend([SBC_SYNCWORD, frame[0], frame[1], frame[2]]);
+            data.resize(start + len, 0);
+        }
+        data
+    }
+
+    #[test]
+    fn sbc_frame_length() {
+        // 44.1 kHz, 16 blocks, joint stereo, 8 subbands, bitpool 53
+        let payload = SbcPayload::new(&sbc_payload(0x01, [0xbd, 0x35, 0x00], 119, 1));
+        assert_eq!(payload.frames.len(), 1);
+        assert_eq!(payload.frames[0].frame_length(), 119);
+        assert_eq!(payload.rest, None);
+        assert!(payload
+            .as_json(0)
+            .contains(r#""Frame of 119 bytes, 327993 bps", "B(5, 115)""#));
+
+        // 44.1 kHz, 16 blocks, mono, 8 subbands, bitpool 31, the second
+        // frame cut short
+        let mut data = sbc_payload(0x02, [0xb1, 0x1f, 0x00], 70, 2);
+        data.truncate(1 + 70 + 10);
+        let payload = SbcPayload::new(&data);
+        assert_eq!(payload.frames.len(), 1);
+        assert_eq!(payload.frames[0].frame_length(), 70);
+        assert!(payload
+            .as_json(0)
+            .contains(r#""B(71, 10)", "Not a complete SBC frame"]"#));
+    }
+
+    #[test]
+    fn aac_fields_split_at_byte_boundaries() {
+        // ADTS, LC, 44.1 kHz, stereo, a frame of 11 bytes
+        let json = AacFrame::new(&str_to_array("ff f1 50 80 01 7f fc 01 02 03 04")).as_json(0);
+        assert!(json.contains(r#""Syncword (1/2)": [0xfff, "", "B(0, 1), b(0, 8)", ""], "Syncword (2/2)": [0xfff, "", "B(1, 1), b(4, 4)", ""]"#));
+        assert!(json
+            .contains(r#""Sampling Frequency Index": [0x4, "44100 Hz", "B(2, 1), b(2, 4)", ""]"#));
+        assert!(json.contains(r#""Frame Length (1/3)": [0xb, "", "B(3, 1), b(0, 2)", ""], "Frame Length (2/3)": [0xb, "", "B(4, 1), b(0, 8)", ""], "Frame Length (3/3)": [0xb, "", "B(5, 1), b(5, 3)", ""]"#));
+        assert!(json.contains(r#""Access Unit": [0x4, "4 bytes", "B(7, 4)", ""]"#));
+    }
+}
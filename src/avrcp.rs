@@ -0,0 +1,1238 @@
+use crate::bytes_to_string;
+use crate::HostStack;
+use crate::ParseNodeA;
+
+use crate::ParseBitsNode;
+use crate::ParseBytesNode;
+
+mod browsing;
+use browsing::BrowsingPdu;
+
+const VENDOR_DEPENDENT: u8 = 0x00;
+const UNIT_INFO: u8 = 0x30;
+const SUBUNIT_INFO: u8 = 0x31;
+const PASS_THROUGH: u8 = 0x7c;
+
+const BLUETOOTH_SIG: u32 = 0x001958;
+const REJECTED: u8 = 0x0a;
+
+const SINGLE_PACKET: u8 = 0x00;
+const START_PACKET: u8 = 0x01;
+const END_PACKET: u8 = 0x03;
+
+const GET_CAPABILITIES: u8 = 0x10;
+const GET_ELEMENT_ATTRIBUTES: u8 = 0x20;
+const GET_PLAY_STATUS: u8 = 0x30;
+const REGISTER_NOTIFICATION: u8 = 0x31;
+const REQUEST_CONTINUING_RESPONSE: u8 = 0x40;
+const ABORT_CONTINUING_RESPONSE: u8 = 0x41;
+const SET_ABSOLUTE_VOLUME: u8 = 0x50;
+
+#[derive(Default, Debug)]
+pub struct AVRCPArg {
+    // parameters of the continuing responses being collected, by
+    // connection and PDU ID
+    fragments: Vec<(u16, u8, Vec<u8>)>,
+}
+
+impl AVRCPArg {
+    /// Drops the continuing responses of `acl_handle` being collected.
+    pub(crate) fn disconnect(&mut self, acl_handle: u16) {
+        self.fragments
+            .retain(|(handle, _, _)| *handle != acl_handle);
+    }
+
+    /// Collects the parameters of a fragmented vendor dependent PDU.
+    /// Returns them all once the end packet is in.
+    fn reassemble(
+        &mut self,
+        acl_handle: u16,
+        pdu_id: u8,
+        packet_type: u8,
+        parameters: &[u8],
+    ) -> Option<Vec<u8>> {
+        let position = self
+            .fragments
+            .iter()
+            .position(|(handle, id, _)| *handle == acl_handle && *id == pdu_id);
+        match (packet_type, position) {
+            (START_PACKET, position) => {
+                if let Some(index) = position {
+                    self.fragments.remove(index);
+                }
+                self.fragments
+                    .push((acl_handle, pdu_id, parameters.to_vec()));
+                None
+            }
+            (END_PACKET, Some(index)) => {
+                let (_, _, mut data) = self.fragments.remove(index);
+                data.extend_from_slice(parameters);
+                Some(data)
+            }
+            (_, Some(index)) => {
+                self.fragments[index].2.extend_from_slice(parameters);
+                None
+            }
+            (_, None) => None,
+        }
+    }
+}
+
+/// An AVRCP message: an AV/C frame on the control channel, or a browsing
+/// PDU.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq)]
+pub(crate) enum AVRCP {
+    Control(AvcFrame),
+    Browsing(BrowsingPdu),
+}
+
+impl ParseNodeA<(bool, bool)> for AVRCP {
+    fn new(data: &[u8], args: Option<&mut HostStack>, (browsing, response): (bool, bool)) -> Self {
+        match browsing {
+            true => AVRCP::Browsing(BrowsingPdu::new(data, response)),
+            false => AVRCP::Control(AvcFrame::new(data, args)),
+        }
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        match self {
+            AVRCP::Control(frame) => format!(r#""AV/C": {{{}}}"#, frame.as_json(start_byte)),
+            AVRCP::Browsing(pdu) => {
+                format!(r#""AVRCP Browsing": {{{}}}"#, pdu.as_json(start_byte))
+            }
+        }
+    }
+}
+
+/// Reads big endian fields one after the other.
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data, offset: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.offset..self.offset + len)?;
+        self.offset += len;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|bytes| bytes[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u24(&mut self) -> Option<u32> {
+        self.bytes(3)
+            .map(|bytes| u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes(4)
+            .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.bytes(8).map(|bytes| {
+            let mut value = [0; 8];
+            value.copy_from_slice(bytes);
+            u64::from_be_bytes(value)
+        })
+    }
+
+    /// A name or value prefixed by its 2 byte length.
+    fn text(&mut self) -> Option<Vec<u8>> {
+        let len = self.u16()?;
+        self.bytes(len as usize).map(|text| text.to_vec())
+    }
+}
+
+/// A media attribute and its value, as GetElementAttributes and the
+/// browsing PDUs return them.
+#[derive(Debug, PartialEq)]
+struct AttributeValue {
+    id: u32,
+    character_set: u16,
+    value: Vec<u8>,
+}
+
+impl AttributeValue {
+    fn parse(reader: &mut Reader) -> Option<Self> {
+        Some(AttributeValue {
+            id: reader.u32()?,
+            character_set: reader.u16()?,
+            value: reader.text()?,
+        })
+    }
+
+    fn len(&self) -> u16 {
+        8 + self.value.len() as u16
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        [
+            ParseBytesNode::new(start_byte, 4).format(
+                "Attribute ID",
+                self.id,
+                get_media_attribute_name(self.id),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 4, 2).format(
+                "Character Set ID",
+                self.character_set,
+                get_character_set_name(self.character_set),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 6, 2).format(
+                "Attribute Value Length",
+                self.value.len() as u16,
+                "",
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 8, self.value.len() as u16).format(
+                "Attribute Value",
+                bytes_to_string(&self.value),
+                "",
+                "",
+            ),
+        ]
+        .join(", ")
+    }
+}
+
+/// Renders attributes one after the other, numbered from zero.
+fn attributes_as_json(attributes: &[AttributeValue], start_byte: u16) -> Vec<String> {
+    let mut offset = start_byte;
+    attributes
+        .iter()
+        .enumerate()
+        .map(|(i, attribute)| {
+            let attribute_s = attribute.as_json(offset);
+            offset += attribute.len();
+            format!(r#""Attribute[{}]": {{{}}}"#, i, attribute_s)
+        })
+        .collect()
+}
+
+/// Renders a list of attribute IDs, numbered from zero.
+fn attribute_ids_as_json(attribute_ids: &[u32], start_byte: u16) -> Vec<String> {
+    attribute_ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| {
+            ParseBytesNode::new(start_byte + 4 * i as u16, 4).format(
+                &format!("Attribute ID[{}]", i),
+                *id,
+                get_media_attribute_name(*id),
+                "",
+            )
+        })
+        .collect()
+}
+
+/// An AV/C command or response frame.
+#[derive(Debug, PartialEq)]
+pub(crate) struct AvcFrame {
+    ctype: u8,
+    subunit_type: u8,
+    subunit_id: u8,
+    opcode: u8,
+    operation: AvcOperation,
+}
+
+#[derive(Debug, PartialEq)]
+enum AvcOperation {
+    UnitInfo(Vec<u8>),
+    SubunitInfo(Vec<u8>),
+    PassThrough {
+        state: u8,
+        operation_id: u8,
+        data: Vec<u8>,
+    },
+    VendorDependent {
+        company_id: u32,
+        pdu: Option<AvrcpPdu>,
+        data: Vec<u8>,
+    },
+    Unknown(Vec<u8>),
+    // the frame ends before its opcode
+    Truncated,
+}
+
+impl AvcFrame {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Self {
+        let mut frame = AvcFrame {
+            ctype: data.first().map_or(0, |ctype| ctype & 0x0f),
+            subunit_type: data.get(1).map_or(0, |subunit| subunit >> 3),
+            subunit_id: data.get(1).map_or(0, |subunit| subunit & 0x07),
+            opcode: 0,
+            operation: AvcOperation::Truncated,
+        };
+        let Some(opcode) = data.get(2).copied() else {
+            return frame;
+        };
+        frame.opcode = opcode;
+        let operands = &data[3..];
+        frame.operation = match opcode {
+            UNIT_INFO => AvcOperation::UnitInfo(operands.to_vec()),
+            SUBUNIT_INFO => AvcOperation::SubunitInfo(operands.to_vec()),
+            PASS_THROUGH if operands.len() >= 2 => AvcOperation::PassThrough {
+                state: operands[0] >> 7,
+                operation_id: operands[0] & 0x7f,
+                data: operands[2..].to_vec(),
+            },
+            VENDOR_DEPENDENT if operands.len() >= 3 => {
+                let company_id = u32::from_be_bytes([0, operands[0], operands[1], operands[2]]);
+                let pdu = match company_id {
+                    BLUETOOTH_SIG => AvrcpPdu::new(&operands[3..], args, frame.ctype),
+                    _ => None,
+                };
+                AvcOperation::VendorDependent {
+                    company_id,
+                    pdu,
+                    data: operands[3..].to_vec(),
+                }
+            }
+            _ => AvcOperation::Unknown(operands.to_vec()),
+        };
+        frame
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut fields = vec![ParseBitsNode::new(start_byte, 1, 0, 4).format(
+            match self.ctype {
+                0x00..=0x07 => "Command Type",
+                _ => "Response",
+            },
+            self.ctype,
+            get_ctype_name(self.ctype),
+            "",
+        )];
+        if self.operation == AvcOperation::Truncated {
+            return fields.join(", ");
+        }
+        fields.extend([
+            ParseBitsNode::new(start_byte + 1, 1, 3, 5).format(
+                "Subunit Type",
+                self.subunit_type,
+                get_subunit_type_name(self.subunit_type),
+                "",
+            ),
+            ParseBitsNode::new(start_byte + 1, 1, 0, 3).format(
+                "Subunit ID",
+                self.subunit_id,
+                "",
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 2, 1).format(
+                "Opcode",
+                self.opcode,
+                get_opcode_name(self.opcode),
+                "",
+            ),
+        ]);
+        let offset = start_byte + 3;
+        match &self.operation {
+            AvcOperation::UnitInfo(operands) if operands.len() >= 5 => fields.extend([
+                ParseBytesNode::new(offset, 1).format("Constant", operands[0], "", ""),
+                ParseBitsNode::new(offset + 1, 1, 3, 5).format(
+                    "Unit Type",
+                    operands[1] >> 3,
+                    get_subunit_type_name(operands[1] >> 3),
+                    "",
+                ),
+                ParseBitsNode::new(offset + 1, 1, 0, 3).format("Unit", operands[1] & 0x07, "", ""),
+                company_id_node(offset + 2, &operands[2..5]),
+            ]),
+            AvcOperation::SubunitInfo(operands) if !operands.is_empty() => {
+                fields.extend([
+                    ParseBitsNode::new(offset, 1, 4, 3).format(
+                        "Page",
+                        (operands[0] >> 4) & 0x07,
+                        "",
+                        "",
+                    ),
+                    ParseBitsNode::new(offset, 1, 0, 3).format(
+                        "Extension Code",
+                        operands[0] & 0x07,
+                        "",
+                        "",
+                    ),
+                ]);
+                for (i, entry) in operands[1..].iter().enumerate() {
+                    let entry_offset = offset + 1 + i as u16;
+                    let alias = match entry {
+                        0xff => "Unused".to_string(),
+                        _ => format!(
+                            "{}, Max Subunit ID {}",
+                            get_subunit_type_name(entry >> 3),
+                            entry & 0x07
+                        ),
+                    };
+                    fields.push(ParseBytesNode::new(entry_offset, 1).format(
+                        &format!("Page Data[{}]", i),
+                        *entry,
+                        &alias,
+                        "",
+                    ));
+                }
+            }
+            AvcOperation::PassThrough {
+                state,
+                operation_id,
+                data,
+            } => {
+                fields.extend([
+                    ParseBitsNode::new(offset, 1, 7, 1).format(
+                        "State Flag",
+                        *state,
+                        match state {
+                            0 => "Pushed",
+                            _ => "Released",
+                        },
+                        "",
+                    ),
+                    ParseBitsNode::new(offset, 1, 0, 7).format(
+                        "Operation ID",
+                        *operation_id,
+                        get_operation_id_name(*operation_id),
+                        "",
+                    ),
+                    ParseBytesNode::new(offset + 1, 1).format(
+                        "Operation Data Field Length",
+                        data.len() as u8,
+                        "",
+                        "",
+                    ),
+                ]);
+                match (operation_id, data.len()) {
+                    (0x7e, 5..) => fields.extend([
+                        company_id_node(offset + 2, &data[..3]),
+                        ParseBytesNode::new(offset + 5, 2).format(
+                            "Vendor Unique Operation ID",
+                            u16::from_be_bytes([data[3], data[4]]),
+                            match u16::from_be_bytes([data[3], data[4]]) {
+                                0x0000 => "Next Group",
+                                0x0001 => "Previous Group",
+                                _ => "",
+                            },
+                            "",
+                        ),
+                    ]),
+                    (_, 0) => {}
+                    _ => fields.push(ParseBytesNode::new(offset + 2, data.len() as u16).format(
+                        "Operation Data",
+                        &data[..],
+                        "",
+                        "",
+                    )),
+                }
+            }
+            AvcOperation::VendorDependent {
+                company_id,
+                pdu,
+                data,
+            } => {
+                fields.push(company_id_node(offset, &company_id.to_be_bytes()[1..]));
+                match pdu {
+                    Some(pdu) => fields.push(pdu.as_json(offset + 3)),
+                    None if !data.is_empty() => {
+                        fields.push(ParseBytesNode::new(offset + 3, data.len() as u16).format(
+                            "Vendor Dependent Data",
+                            &data[..],
+                            "",
+                            "",
+                        ))
+                    }
+                    None => {}
+                }
+            }
+            AvcOperation::UnitInfo(operands)
+            | AvcOperation::SubunitInfo(operands)
+            | AvcOperation::Unknown(operands)
+                if !operands.is_empty() =>
+            {
+                fields.push(ParseBytesNode::new(offset, operands.len() as u16).format(
+                    "Operands",
+                    &operands[..],
+                    "",
+                    "",
+                ))
+            }
+            _ => {}
+        }
+        fields.join(", ")
+    }
+}
+
+fn company_id_node(start_byte: u16, company_id: &[u8]) -> String {
+    let company_id = u32::from_be_bytes([0, company_id[0], company_id[1], company_id[2]]);
+    ParseBytesNode::new(start_byte, 3).format(
+        "Company ID",
+        company_id,
+        match company_id {
+            BLUETOOTH_SIG => "Bluetooth SIG",
+            _ => "",
+        },
+        "",
+    )
+}
+
+/// A Bluetooth SIG vendor dependent PDU, its parameters decoded when the
+/// whole of them is known.
+#[derive(Debug, PartialEq)]
+struct AvrcpPdu {
+    pdu_id: u8,
+    packet_type: u8,
+    parameter_length: u16,
+    parameters: Vec<u8>,
+    decoded: Option<PduParameters>,
+}
+
+#[derive(Debug, PartialEq)]
+enum PduParameters {
+    Empty,
+    // GetCapabilities command
+    CapabilityId(u8),
+    // GetCapabilities response, company IDs or event IDs
+    Capabilities {
+        capability_id: u8,
+        capabilities: Vec<u32>,
+    },
+    RegisterNotification {
+        event_id: u8,
+        playback_interval: u32,
+    },
+    // interim or changed response of RegisterNotification
+    Notification {
+        event_id: u8,
+        data: Vec<u8>,
+    },
+    GetElementAttributes {
+        identifier: u64,
+        attribute_ids: Vec<u32>,
+    },
+    Attributes(Vec<AttributeValue>),
+    AbsoluteVolume(u8),
+    PlayStatus {
+        song_length: u32,
+        song_position: u32,
+        play_status: u8,
+    },
+    // RequestContinuingResponse and AbortContinuingResponse
+    ContinuePduId(u8),
+    ErrorCode(u8),
+    Raw(Vec<u8>),
+}
+
+impl AvrcpPdu {
+    fn new(data: &[u8], args: Option<&mut HostStack>, ctype: u8) -> Option<Self> {
+        if data.len() < 4 {
+            return None;
+        }
+        let pdu_id = data[0];
+        let packet_type = data[1] & 0x03;
+        let parameters = &data[4..];
+        let whole = match (packet_type, args) {
+            (SINGLE_PACKET, _) => Some(parameters.to_vec()),
+            (_, Some(args)) => {
+                let acl_handle = args.hci_arg.acl_handle();
+                args.avrcp_arg
+                    .reassemble(acl_handle, pdu_id, packet_type, parameters)
+            }
+            (_, None) => None,
+        };
+        Some(AvrcpPdu {
+            pdu_id,
+            packet_type,
+            parameter_length: u16::from_be_bytes([data[2], data[3]]),
+            parameters: parameters.to_vec(),
+            decoded: whole.map(|whole| PduParameters::new(&whole, pdu_id, ctype)),
+        })
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut fields = vec![
+            ParseBytesNode::new(start_byte, 1).format(
+                "PDU ID",
+                self.pdu_id,
+                get_pdu_id_name(self.pdu_id),
+                "",
+            ),
+            ParseBitsNode::new(start_byte + 1, 1, 0, 2).format(
+                "Packet Type",
+                self.packet_type,
+                match self.packet_type {
+                    SINGLE_PACKET => "Single Packet",
+                    START_PACKET => "Start Packet",
+                    END_PACKET => "End Packet",
+                    _ => "Continue Packet",
+                },
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 2, 2).format(
+                "Parameter Length",
+                self.parameter_length,
+                "",
+                "",
+            ),
+        ];
+        let offset = start_byte + 4;
+        if self.packet_type != SINGLE_PACKET && !self.parameters.is_empty() {
+            fields.push(
+                ParseBytesNode::new(offset, self.parameters.len() as u16).format(
+                    "Parameter Fragment",
+                    &self.parameters[..],
+                    "",
+                    "",
+                ),
+            );
+        }
+        match (&self.decoded, self.packet_type) {
+            (Some(decoded), SINGLE_PACKET) => fields.extend(decoded.as_json(offset)),
+            // the reassembled parameters are placed as if they started here
+            (Some(decoded), _) => fields.push(format!(
+                r#""Reassembled Parameters": {{{}}}"#,
+                decoded.as_json(offset).join(", ")
+            )),
+            (None, _) => {}
+        }
+        fields.join(", ")
+    }
+}
+
+impl PduParameters {
+    fn new(data: &[u8], pdu_id: u8, ctype: u8) -> Self {
+        let command = ctype < 0x08;
+        let mut reader = Reader::new(data);
+        let parameters = match (pdu_id, command) {
+            _ if data.is_empty() => Some(PduParameters::Empty),
+            (_, false) if ctype == REJECTED => Some(PduParameters::ErrorCode(data[0])),
+            (GET_CAPABILITIES, true) => Some(PduParameters::CapabilityId(data[0])),
+            (GET_CAPABILITIES, false) => (|| {
+                let capability_id = reader.u8()?;
+                let count = reader.u8()?;
+                let capabilities = (0..count)
+                    .map(|_| match capability_id {
+                        0x02 => reader.u24(),
+                        _ => reader.u8().map(|event_id| event_id as u32),
+                    })
+                    .collect::<Option<Vec<u32>>>()?;
+                Some(PduParameters::Capabilities {
+                    capability_id,
+                    capabilities,
+                })
+            })(),
+            (REGISTER_NOTIFICATION, true) => (|| {
+                Some(PduParameters::RegisterNotification {
+                    event_id: reader.u8()?,
+                    playback_interval: reader.u32()?,
+                })
+            })(),
+            (REGISTER_NOTIFICATION, false) => Some(PduParameters::Notification {
+                event_id: data[0],
+                data: data[1..].to_vec(),
+            }),
+            (GET_ELEMENT_ATTRIBUTES, true) => (|| {
+                let identifier = reader.u64()?;
+                let count = reader.u8()?;
+                let attribute_ids = (0..count)
+                    .map(|_| reader.u32())
+                    .collect::<Option<Vec<u32>>>()?;
+                Some(PduParameters::GetElementAttributes {
+                    identifier,
+                    attribute_ids,
+                })
+            })(),
+            (GET_ELEMENT_ATTRIBUTES, false) => (|| {
+                let count = reader.u8()?;
+                let attributes = (0..count)
+                    .map(|_| AttributeValue::parse(&mut reader))
+                    .collect::<Option<Vec<AttributeValue>>>()?;
+                Some(PduParameters::Attributes(attributes))
+            })(),
+            (SET_ABSOLUTE_VOLUME, _) => Some(PduParameters::AbsoluteVolume(data[0] & 0x7f)),
+            (GET_PLAY_STATUS, false) => (|| {
+                Some(PduParameters::PlayStatus {
+                    song_length: reader.u32()?,
+                    song_position: reader.u32()?,
+                    play_status: reader.u8()?,
+                })
+            })(),
+            (REQUEST_CONTINUING_RESPONSE | ABORT_CONTINUING_RESPONSE, true) => {
+                Some(PduParameters::ContinuePduId(data[0]))
+            }
+            _ => None,
+        };
+        parameters.unwrap_or_else(|| PduParameters::Raw(data.to_vec()))
+    }
+
+    fn as_json(&self, start_byte: u16) -> Vec<String> {
+        match self {
+            PduParameters::Empty => Vec::new(),
+            PduParameters::CapabilityId(capability_id) => {
+                vec![capability_id_node(start_byte, *capability_id)]
+            }
+            PduParameters::Capabilities {
+                capability_id,
+                capabilities,
+            } => {
+                let mut fields = vec![
+                    capability_id_node(start_byte, *capability_id),
+                    ParseBytesNode::new(start_byte + 1, 1).format(
+                        "Capability Count",
+                        capabilities.len() as u8,
+                        "",
+                        "",
+                    ),
+                ];
+                for (i, capability) in capabilities.iter().enumerate() {
+                    fields.push(match capability_id {
+                        0x02 => ParseBytesNode::new(start_byte + 2 + 3 * i as u16, 3).format(
+                            &format!("Company ID[{}]", i),
+                            *capability,
+                            match *capability {
+                                BLUETOOTH_SIG => "Bluetooth SIG",
+                                _ => "",
+                            },
+                            "",
+                        ),
+                        _ => ParseBytesNode::new(start_byte + 2 + i as u16, 1).format(
+                            &format!("Event ID[{}]", i),
+                            *capability as u8,
+                            get_event_id_name(*capability as u8),
+                            "",
+                        ),
+                    });
+                }
+                fields
+            }
+            PduParameters::RegisterNotification {
+                event_id,
+                playback_interval,
+            } => vec![
+                event_id_node(start_byte, *event_id),
+                ParseBytesNode::new(start_byte + 1, 4).format(
+                    "Playback Interval",
+                    *playback_interval,
+                    &format!("{} s", playback_interval),
+                    "",
+                ),
+            ],
+            PduParameters::Notification { event_id, data } => {
+                let mut fields = vec![event_id_node(start_byte, *event_id)];
+                fields.extend(event_data_as_json(*event_id, data, start_byte + 1));
+                fields
+            }
+            PduParameters::GetElementAttributes {
+                identifier,
+                attribute_ids,
+            } => {
+                let mut fields = vec![
+                    ParseBytesNode::new(start_byte, 8).format(
+                        "Identifier",
+                        *identifier,
+                        match identifier {
+                            0 => "PLAYING",
+                            _ => "",
+                        },
+                        "",
+                    ),
+                    ParseBytesNode::new(start_byte + 8, 1).format(
+                        "Number Of Attributes",
+                        attribute_ids.len() as u8,
+                        match attribute_ids.len() {
+                            0 => "All attributes",
+                            _ => "",
+                        },
+                        "",
+                    ),
+                ];
+                fields.extend(attribute_ids_as_json(attribute_ids, start_byte + 9));
+                fields
+            }
+            PduParameters::Attributes(attributes) => {
+                let mut fields = vec![ParseBytesNode::new(start_byte, 1).format(
+                    "Number Of Attributes",
+                    attributes.len() as u8,
+                    "",
+                    "",
+                )];
+                fields.extend(attributes_as_json(attributes, start_byte + 1));
+                fields
+            }
+            PduParameters::AbsoluteVolume(volume) => {
+                vec![ParseBitsNode::new(start_byte, 1, 0, 7).format(
+                    "Absolute Volume",
+                    *volume,
+                    &format!("{}%", *volume as u32 * 100 / 0x7f),
+                    "",
+                )]
+            }
+            PduParameters::PlayStatus {
+                song_length,
+                song_position,
+                play_status,
+            } => vec![
+                ParseBytesNode::new(start_byte, 4).format(
+                    "Song Length",
+                    *song_length,
+                    &format_milliseconds(*song_length),
+                    "",
+                ),
+                ParseBytesNode::new(start_byte + 4, 4).format(
+                    "Song Position",
+                    *song_position,
+                    &format_milliseconds(*song_position),
+                    "",
+                ),
+                ParseBytesNode::new(start_byte + 8, 1).format(
+                    "Play Status",
+                    *play_status,
+                    get_play_status_name(*play_status),
+                    "",
+                ),
+            ],
+            PduParameters::ContinuePduId(pdu_id) => {
+                vec![ParseBytesNode::new(start_byte, 1).format(
+                    "Continue PDU ID",
+                    *pdu_id,
+                    get_pdu_id_name(*pdu_id),
+                    "",
+                )]
+            }
+            PduParameters::ErrorCode(error_code) => {
+                vec![ParseBytesNode::new(start_byte, 1).format(
+                    "Error Code",
+                    *error_code,
+                    get_status_name(*error_code),
+                    "",
+                )]
+            }
+            PduParameters::Raw(data) => {
+                vec![ParseBytesNode::new(start_byte, data.len() as u16).format(
+                    "Parameters",
+                    &data[..],
+                    "",
+                    "",
+                )]
+            }
+        }
+    }
+}
+
+fn capability_id_node(start_byte: u16, capability_id: u8) -> String {
+    ParseBytesNode::new(start_byte, 1).format(
+        "Capability ID",
+        capability_id,
+        match capability_id {
+            0x02 => "Company ID",
+            0x03 => "Events Supported",
+            _ => "Reserved",
+        },
+        "",
+    )
+}
+
+fn event_id_node(start_byte: u16, event_id: u8) -> String {
+    ParseBytesNode::new(start_byte, 1).format("Event ID", event_id, get_event_id_name(event_id), "")
+}
+
+/// The parameters a notification carries for its event.
+fn event_data_as_json(event_id: u8, data: &[u8], start_byte: u16) -> Vec<String> {
+    let node = |offset: u16, len: u16| ParseBytesNode::new(start_byte + offset, len);
+    match (event_id, data.len()) {
+        (0x01, 1..) => {
+            vec![node(0, 1).format("Play Status", data[0], get_play_status_name(data[0]), "")]
+        }
+        (0x02, 8..) => {
+            let identifier = u64::from_be_bytes([
+                data[0], data[1], data[2], data[3], data[4], data[5], data[6], data[7],
+            ]);
+            vec![node(0, 8).format(
+                "Identifier",
+                identifier,
+                match identifier {
+                    0 => "Track selected",
+                    u64::MAX => "No track selected",
+                    _ => "",
+                },
+                "",
+            )]
+        }
+        (0x05, 4..) => {
+            let position = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+            vec![node(0, 4).format(
+                "Playback Position",
+                position,
+                &format_milliseconds(position),
+                "",
+            )]
+        }
+        (0x06, 1..) => vec![node(0, 1).format(
+            "Battery Status",
+            data[0],
+            match data[0] {
+                0x00 => "NORMAL",
+                0x01 => "WARNING",
+                0x02 => "CRITICAL",
+                0x03 => "EXTERNAL",
+                0x04 => "FULL_CHARGE",
+                _ => "Reserved",
+            },
+            "",
+        )],
+        (0x07, 1..) => vec![node(0, 1).format(
+            "System Status",
+            data[0],
+            match data[0] {
+                0x00 => "POWER_ON",
+                0x01 => "POWER_OFF",
+                0x02 => "UNPLUGGED",
+                _ => "Reserved",
+            },
+            "",
+        )],
+        (0x08, 1..) => {
+            let mut fields = vec![node(0, 1).format("Number Of Attributes", data[0], "", "")];
+            for (i, pair) in data[1..].chunks_exact(2).enumerate() {
+                let offset = 1 + 2 * i as u16;
+                fields.push(node(offset, 1).format(
+                    &format!("Player Application Setting Attribute ID[{}]", i),
+                    pair[0],
+                    get_player_setting_name(pair[0]),
+                    "",
+                ));
+                fields.push(node(offset + 1, 1).format(
+                    &format!("Player Application Setting Value ID[{}]", i),
+                    pair[1],
+                    get_player_setting_value_name(pair[0], pair[1]),
+                    "",
+                ));
+            }
+            fields
+        }
+        (0x0b, 4..) => vec![
+            node(0, 2).format("Player ID", u16::from_be_bytes([data[0], data[1]]), "", ""),
+            node(2, 2).format(
+                "UID Counter",
+                u16::from_be_bytes([data[2], data[3]]),
+                "",
+                "",
+            ),
+        ],
+        (0x0c, 2..) => vec![node(0, 2).format(
+            "UID Counter",
+            u16::from_be_bytes([data[0], data[1]]),
+            "",
+            "",
+        )],
+        (0x0d, 1..) => vec![ParseBitsNode::new(start_byte, 1, 0, 7).format(
+            "Absolute Volume",
+            data[0] & 0x7f,
+            &format!("{}%", (data[0] & 0x7f) as u32 * 100 / 0x7f),
+            "",
+        )],
+        (_, 0) => Vec::new(),
+        _ => vec![node(0, data.len() as u16).format("Event Data", data, "", "")],
+    }
+}
+
+fn format_milliseconds(milliseconds: u32) -> String {
+    match milliseconds {
+        0xffffffff => "Not supported".to_string(),
+        _ => format!(
+            "{}:{:02}.{:03}",
+            milliseconds / 60000,
+            milliseconds / 1000 % 60,
+            milliseconds % 1000
+        ),
+    }
+}
+
+fn get_ctype_name(ctype: u8) -> &'static str {
+    match ctype {
+        0x00 => "CONTROL",
+        0x01 => "STATUS",
+        0x02 => "SPECIFIC INQUIRY",
+        0x03 => "NOTIFY",
+        0x04 => "GENERAL INQUIRY",
+        0x08 => "NOT IMPLEMENTED",
+        0x09 => "ACCEPTED",
+        0x0a => "REJECTED",
+        0x0b => "IN TRANSITION",
+        0x0c => "IMPLEMENTED/STABLE",
+        0x0d => "CHANGED",
+        0x0f => "INTERIM",
+        _ => "Reserved",
+    }
+}
+
+fn get_subunit_type_name(subunit_type: u8) -> &'static str {
+    match subunit_type {
+        0x00 => "Monitor",
+        0x01 => "Audio",
+        0x02 => "Printer",
+        0x03 => "Disc",
+        0x04 => "Tape Recorder/Player",
+        0x05 => "Tuner",
+        0x06 => "CA",
+        0x07 => "Camera",
+        0x09 => "Panel",
+        0x0a => "Bulletin Board",
+        0x0b => "Camera Storage",
+        0x1c => "Vendor Unique",
+        0x1e => "Extended",
+        0x1f => "Unit",
+        _ => "Reserved",
+    }
+}
+
+fn get_opcode_name(opcode: u8) -> &'static str {
+    match opcode {
+        VENDOR_DEPENDENT => "VENDOR DEPENDENT",
+        UNIT_INFO => "UNIT INFO",
+        SUBUNIT_INFO => "SUBUNIT INFO",
+        PASS_THROUGH => "PASS THROUGH",
+        _ => "",
+    }
+}
+
+fn get_operation_id_name(operation_id: u8) -> &'static str {
+    match operation_id {
+        0x00 => "SELECT",
+        0x01 => "UP",
+        0x02 => "DOWN",
+        0x03 => "LEFT",
+        0x04 => "RIGHT",
+        0x05 => "RIGHT-UP",
+        0x06 => "RIGHT-DOWN",
+        0x07 => "LEFT-UP",
+        0x08 => "LEFT-DOWN",
+        0x09 => "ROOT MENU",
+        0x0a => "SETUP MENU",
+        0x0b => "CONTENTS MENU",
+        0x0c => "FAVORITE MENU",
+        0x0d => "EXIT",
+        0x20 => "0",
+        0x21 => "1",
+        0x22 => "2",
+        0x23 => "3",
+        0x24 => "4",
+        0x25 => "5",
+        0x26 => "6",
+        0x27 => "7",
+        0x28 => "8",
+        0x29 => "9",
+        0x2a => "DOT",
+        0x2b => "ENTER",
+        0x2c => "CLEAR",
+        0x30 => "CHANNEL UP",
+        0x31 => "CHANNEL DOWN",
+        0x32 => "PREVIOUS CHANNEL",
+        0x33 => "SOUND SELECT",
+        0x34 => "INPUT SELECT",
+        0x35 => "DISPLAY INFORMATION",
+        0x36 => "HELP",
+        0x37 => "PAGE UP",
+        0x38 => "PAGE DOWN",
+        0x40 => "POWER",
+        0x41 => "VOLUME UP",
+        0x42 => "VOLUME DOWN",
+        0x43 => "MUTE",
+        0x44 => "PLAY",
+        0x45 => "STOP",
+        0x46 => "PAUSE",
+        0x47 => "RECORD",
+        0x48 => "REWIND",
+        0x49 => "FAST FORWARD",
+        0x4a => "EJECT",
+        0x4b => "FORWARD",
+        0x4c => "BACKWARD",
+        0x50 => "ANGLE",
+        0x51 => "SUBPICTURE",
+        0x71 => "F1",
+        0x72 => "F2",
+        0x73 => "F3",
+        0x74 => "F4",
+        0x75 => "F5",
+        0x7e => "VENDOR UNIQUE",
+        _ => "Reserved",
+    }
+}
+
+fn get_pdu_id_name(pdu_id: u8) -> &'static str {
+    match pdu_id {
+        GET_CAPABILITIES => "GetCapabilities",
+        0x11 => "ListPlayerApplicationSettingAttributes",
+        0x12 => "ListPlayerApplicationSettingValues",
+        0x13 => "GetCurrentPlayerApplicationSettingValue",
+        0x14 => "SetPlayerApplicationSettingValue",
+        0x15 => "GetPlayerApplicationSettingAttributeText",
+        0x16 => "GetPlayerApplicationSettingValueText",
+        0x17 => "InformDisplayableCharacterSet",
+        0x18 => "InformBatteryStatusOfCT",
+        GET_ELEMENT_ATTRIBUTES => "GetElementAttributes",
+        GET_PLAY_STATUS => "GetPlayStatus",
+        REGISTER_NOTIFICATION => "RegisterNotification",
+        REQUEST_CONTINUING_RESPONSE => "RequestContinuingResponse",
+        ABORT_CONTINUING_RESPONSE => "AbortContinuingResponse",
+        SET_ABSOLUTE_VOLUME => "SetAbsoluteVolume",
+        0x60 => "SetAddressedPlayer",
+        0x70 => "SetBrowsedPlayer",
+        0x71 => "GetFolderItems",
+        0x72 => "ChangePath",
+        0x73 => "GetItemAttributes",
+        0x74 => "PlayItem",
+        0x75 => "GetTotalNumberOfItems",
+        0x80 => "Search",
+        0x90 => "AddToNowPlaying",
+        0xa0 => "General Reject",
+        _ => "Reserved",
+    }
+}
+
+fn get_event_id_name(event_id: u8) -> &'static str {
+    match event_id {
+        0x01 => "EVENT_PLAYBACK_STATUS_CHANGED",
+        0x02 => "EVENT_TRACK_CHANGED",
+        0x03 => "EVENT_TRACK_REACHED_END",
+        0x04 => "EVENT_TRACK_REACHED_START",
+        0x05 => "EVENT_PLAYBACK_POS_CHANGED",
+        0x06 => "EVENT_BATT_STATUS_CHANGED",
+        0x07 => "EVENT_SYSTEM_STATUS_CHANGED",
+        0x08 => "EVENT_PLAYER_APPLICATION_SETTING_CHANGED",
+        0x09 => "EVENT_NOW_PLAYING_CONTENT_CHANGED",
+        0x0a => "EVENT_AVAILABLE_PLAYERS_CHANGED",
+        0x0b => "EVENT_ADDRESSED_PLAYER_CHANGED",
+        0x0c => "EVENT_UIDS_CHANGED",
+        0x0d => "EVENT_VOLUME_CHANGED",
+        _ => "Reserved",
+    }
+}
+
+fn get_play_status_name(play_status: u8) -> &'static str {
+    match play_status {
+        0x00 => "STOPPED",
+        0x01 => "PLAYING",
+        0x02 => "PAUSED",
+        0x03 => "FWD_SEEK",
+        0x04 => "REV_SEEK",
+        0xff => "ERROR",
+        _ => "Reserved",
+    }
+}
+
+fn get_player_setting_name(attribute_id: u8) -> &'static str {
+    match attribute_id {
+        0x01 => "Equalizer",
+        0x02 => "Repeat Mode",
+        0x03 => "Shuffle",
+        0x04 => "Scan",
+        0x80..=0xff => "Provided by the target",
+        _ => "Reserved",
+    }
+}
+
+fn get_player_setting_value_name(attribute_id: u8, value: u8) -> &'static str {
+    match (attribute_id, value) {
+        (0x01, 0x01) | (0x02..=0x04, 0x01) => "OFF",
+        (0x01, 0x02) => "ON",
+        (0x02, 0x02) => "Single track repeat",
+        (0x02, 0x03) => "All track repeat",
+        (0x02, 0x04) => "Group repeat",
+        (0x03 | 0x04, 0x02) => "All tracks",
+        (0x03 | 0x04, 0x03) => "Group",
+        _ => "",
+    }
+}
+
+fn get_media_attribute_name(attribute_id: u32) -> &'static str {
+    match attribute_id {
+        0x01 => "Title",
+        0x02 => "Artist Name",
+        0x03 => "Album Name",
+        0x04 => "Track Number",
+        0x05 => "Total Number of Tracks",
+        0x06 => "Genre",
+        0x07 => "Playing Time",
+        0x08 => "Default Cover Art",
+        _ => "Reserved",
+    }
+}
+
+fn get_character_set_name(character_set: u16) -> &'static str {
+    match character_set {
+        0x0003 => "US-ASCII",
+        0x0004 => "ISO-8859-1",
+        0x006a => "UTF-8",
+        0x03e8 => "UCS-2",
+        0x03f6 => "UTF-16",
+        _ => "",
+    }
+}
+
+/// The status and error codes responses and rejects carry.
+fn get_status_name(status: u8) -> &'static str {
+    match status {
+        0x00 => "Invalid Command",
+        0x01 => "Invalid Parameter",
+        0x02 => "Parameter Content Error",
+        0x03 => "Internal Error",
+        0x04 => "Operation Completed Without Error",
+        0x05 => "UID Changed",
+        0x07 => "Invalid Direction",
+        0x08 => "Not a Directory",
+        0x09 => "Does Not Exist",
+        0x0a => "Invalid Scope",
+        0x0b => "Range Out of Bounds",
+        0x0c => "Folder Item is not playable",
+        0x0d => "Media in Use",
+        0x0e => "Now Playing List Full",
+        0x0f => "Search Not Supported",
+        0x10 => "Search in Progress",
+        0x11 => "Invalid Player Id",
+        0x12 => "Player Not Browsable",
+        0x13 => "Player Not Addressed",
+        0x14 => "No valid Search Results",
+        0x15 => "No available players",
+        0x16 => "Addressed Player Changed",
+        _ => "Reserved",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::parse_on_channel;
+
+    // AVCTP control channel 0x0040 <-> 0x0041, then a GetElementAttributes
+    // response split in a start packet and, after RequestContinuingResponse,
+    // an end packet
+    const PACKETS: [&str; 3] = [
+        "02 40 20 1d 00 19 00 40 00 22 11 0e 0c 48 00 00 19 58 20 01 00 0c 01 00 00 00 01 00 6a 00 0b 48 65 6c",
+        "02 40 20 12 00 0e 00 41 00 30 11 0e 00 48 00 00 19 58 40 00 00 01 20",
+        "02 40 20 19 00 15 00 40 00 32 11 0e 0c 48 00 00 19 58 20 03 00 08 6c 6f 20 57 6f 72 6c 64",
+    ];
+
+    #[test]
+    fn continuing_response_is_reassembled() {
+        let (json, _) = parse_on_channel(0x0017, &PACKETS);
+        assert!(
+            json[0].contains(r#""Packet Type": [0x1, "Start Packet", "B(19, 1), b(0, 2)", ""]"#)
+        );
+        assert!(!json[0].contains("Reassembled Parameters"));
+        assert!(json[1]
+            .contains(r#""Continue PDU ID": [0x20, "GetElementAttributes", "B(22, 1)", ""]"#));
+        assert!(json[2].contains(r#""Parameter Fragment": ["6c 6f 20 57 6f 72 6c 64", "", "B(22, 8)", ""], "Reassembled Parameters": {"Number Of Attributes": [0x1, "", "B(22, 1)", ""]"#));
+        assert!(json[2].contains(r#""Attribute Value": ["Hello World", "", "B(31, 11)", ""]"#));
+    }
+}
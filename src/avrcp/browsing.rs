@@ -0,0 +1,701 @@
+use crate::bytes_to_string;
+
+use crate::ParseBytesNode;
+
+use super::{
+    attribute_ids_as_json, attributes_as_json, get_character_set_name, get_pdu_id_name,
+    get_play_status_name, get_status_name, AttributeValue, Reader,
+};
+
+const SET_BROWSED_PLAYER: u8 = 0x70;
+const GET_FOLDER_ITEMS: u8 = 0x71;
+const CHANGE_PATH: u8 = 0x72;
+const GET_ITEM_ATTRIBUTES: u8 = 0x73;
+const GET_TOTAL_NUMBER_OF_ITEMS: u8 = 0x75;
+const GENERAL_REJECT: u8 = 0xa0;
+
+const OPERATION_COMPLETED: u8 = 0x04;
+
+/// A PDU on the browsing channel.
+#[derive(Debug, PartialEq)]
+pub(crate) struct BrowsingPdu {
+    pdu_id: u8,
+    parameter_length: u16,
+    parameters: BrowsingParameters,
+}
+
+#[derive(Debug, PartialEq)]
+enum BrowsingParameters {
+    Empty,
+    SetBrowsedPlayer {
+        player_id: u16,
+    },
+    BrowsedPlayer {
+        status: u8,
+        uid_counter: u16,
+        number_of_items: u32,
+        character_set: u16,
+        folder_names: Vec<Vec<u8>>,
+    },
+    GetFolderItems {
+        scope: u8,
+        start_item: u32,
+        end_item: u32,
+        attribute_count: u8,
+        attribute_ids: Vec<u32>,
+    },
+    FolderItems {
+        status: u8,
+        uid_counter: u16,
+        items: Vec<Item>,
+    },
+    ChangePath {
+        uid_counter: u16,
+        direction: u8,
+        folder_uid: u64,
+    },
+    // ChangePath and GetTotalNumberOfItems responses
+    NumberOfItems {
+        status: u8,
+        uid_counter: Option<u16>,
+        number_of_items: u32,
+    },
+    GetItemAttributes {
+        scope: u8,
+        uid: u64,
+        uid_counter: u16,
+        attribute_ids: Vec<u32>,
+    },
+    ItemAttributes {
+        status: u8,
+        attributes: Vec<AttributeValue>,
+    },
+    Scope(u8),
+    // a failed response, or a General Reject
+    Status(u8),
+    Raw(Vec<u8>),
+}
+
+/// An item of a GetFolderItems response.
+#[derive(Debug, PartialEq)]
+enum Item {
+    MediaPlayer {
+        player_id: u16,
+        major_player_type: u8,
+        player_sub_type: u32,
+        play_status: u8,
+        features: Vec<u8>,
+        character_set: u16,
+        name: Vec<u8>,
+    },
+    Folder {
+        uid: u64,
+        folder_type: u8,
+        playable: u8,
+        character_set: u16,
+        name: Vec<u8>,
+    },
+    MediaElement {
+        uid: u64,
+        media_type: u8,
+        character_set: u16,
+        name: Vec<u8>,
+        attributes: Vec<AttributeValue>,
+    },
+    Unknown(u8, Vec<u8>),
+}
+
+impl BrowsingPdu {
+    pub(super) fn new(data: &[u8], response: bool) -> Self {
+        let pdu_id = data.first().copied().unwrap_or_default();
+        let parameter_length = match data.len() {
+            3.. => u16::from_be_bytes([data[1], data[2]]),
+            _ => 0,
+        };
+        let parameters = &data[3.min(data.len())..];
+        BrowsingPdu {
+            pdu_id,
+            parameter_length,
+            parameters: BrowsingParameters::new(parameters, pdu_id, response),
+        }
+    }
+
+    pub(super) fn as_json(&self, start_byte: u16) -> String {
+        let mut fields = vec![
+            ParseBytesNode::new(start_byte, 1).format(
+                "PDU ID",
+                self.pdu_id,
+                get_pdu_id_name(self.pdu_id),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 1, 2).format(
+                "Parameter Length",
+                self.parameter_length,
+                "",
+                "",
+            ),
+        ];
+        fields.extend(self.parameters.as_json(start_byte + 3));
+        fields.join(", ")
+    }
+}
+
+impl BrowsingParameters {
+    fn new(data: &[u8], pdu_id: u8, response: bool) -> Self {
+        let mut reader = Reader::new(data);
+        let parameters = match (pdu_id, response) {
+            _ if data.is_empty() => Some(BrowsingParameters::Empty),
+            (GENERAL_REJECT, _) => Some(BrowsingParameters::Status(data[0])),
+            // a response that failed carries its status alone
+            (_, true) if data[0] != OPERATION_COMPLETED => {
+                Some(BrowsingParameters::Status(data[0]))
+            }
+            (SET_BROWSED_PLAYER, false) => reader
+                .u16()
+                .map(|player_id| BrowsingParameters::SetBrowsedPlayer { player_id }),
+            (SET_BROWSED_PLAYER, true) => (|| {
+                let status = reader.u8()?;
+                let uid_counter = reader.u16()?;
+                let number_of_items = reader.u32()?;
+                let character_set = reader.u16()?;
+                let depth = reader.u8()?;
+                let folder_names = (0..depth)
+                    .map(|_| reader.text())
+                    .collect::<Option<Vec<Vec<u8>>>>()?;
+                Some(BrowsingParameters::BrowsedPlayer {
+                    status,
+                    uid_counter,
+                    number_of_items,
+                    character_set,
+                    folder_names,
+                })
+            })(),
+            (GET_FOLDER_ITEMS, false) => (|| {
+                let scope = reader.u8()?;
+                let start_item = reader.u32()?;
+                let end_item = reader.u32()?;
+                let attribute_count = reader.u8()?;
+                // 0 asks for all attributes and 0xff for none, neither lists any
+                let attribute_ids = match attribute_count {
+                    0x00 | 0xff => Vec::new(),
+                    count => (0..count)
+                        .map(|_| reader.u32())
+                        .collect::<Option<Vec<u32>>>()?,
+                };
+                Some(BrowsingParameters::GetFolderItems {
+                    scope,
+                    start_item,
+                    end_item,
+                    attribute_count,
+                    attribute_ids,
+                })
+            })(),
+            (GET_FOLDER_ITEMS, true) => (|| {
+                let status = reader.u8()?;
+                let uid_counter = reader.u16()?;
+                let count = reader.u16()?;
+                let items = (0..count)
+                    .map(|_| Item::parse(&mut reader))
+                    .collect::<Option<Vec<Item>>>()?;
+                Some(BrowsingParameters::FolderItems {
+                    status,
+                    uid_counter,
+                    items,
+                })
+            })(),
+            (CHANGE_PATH, false) => (|| {
+                Some(BrowsingParameters::ChangePath {
+                    uid_counter: reader.u16()?,
+                    direction: reader.u8()?,
+                    folder_uid: reader.u64()?,
+                })
+            })(),
+            (CHANGE_PATH, true) => (|| {
+                Some(BrowsingParameters::NumberOfItems {
+                    status: reader.u8()?,
+                    uid_counter: None,
+                    number_of_items: reader.u32()?,
+                })
+            })(),
+            (GET_ITEM_ATTRIBUTES, false) => (|| {
+                let scope = reader.u8()?;
+                let uid = reader.u64()?;
+                let uid_counter = reader.u16()?;
+                let count = reader.u8()?;
+                let attribute_ids = (0..count)
+                    .map(|_| reader.u32())
+                    .collect::<Option<Vec<u32>>>()?;
+                Some(BrowsingParameters::GetItemAttributes {
+                    scope,
+                    uid,
+                    uid_counter,
+                    attribute_ids,
+                })
+            })(),
+            (GET_ITEM_ATTRIBUTES, true) => (|| {
+                let status = reader.u8()?;
+                let count = reader.u8()?;
+                let attributes = (0..count)
+                    .map(|_| AttributeValue::parse(&mut reader))
+                    .collect::<Option<Vec<AttributeValue>>>()?;
+                Some(BrowsingParameters::ItemAttributes { status, attributes })
+            })(),
+            (GET_TOTAL_NUMBER_OF_ITEMS, false) => Some(BrowsingParameters::Scope(data[0])),
+            (GET_TOTAL_NUMBER_OF_ITEMS, true) => (|| {
+                Some(BrowsingParameters::NumberOfItems {
+                    status: reader.u8()?,
+                    uid_counter: Some(reader.u16()?),
+                    number_of_items: reader.u32()?,
+                })
+            })(),
+            _ => None,
+        };
+        parameters.unwrap_or_else(|| BrowsingParameters::Raw(data.to_vec()))
+    }
+
+    fn as_json(&self, start_byte: u16) -> Vec<String> {
+        let node = |offset: u16, len: u16| ParseBytesNode::new(start_byte + offset, len);
+        match self {
+            BrowsingParameters::Empty => Vec::new(),
+            BrowsingParameters::SetBrowsedPlayer { player_id } => {
+                vec![node(0, 2).format("Player ID", *player_id, "", "")]
+            }
+            BrowsingParameters::BrowsedPlayer {
+                status,
+                uid_counter,
+                number_of_items,
+                character_set,
+                folder_names,
+            } => {
+                let mut fields = vec![
+                    status_node(start_byte, *status),
+                    node(1, 2).format("UID Counter", *uid_counter, "", ""),
+                    node(3, 4).format("Number Of Items", *number_of_items, "", ""),
+                    node(7, 2).format(
+                        "Character Set ID",
+                        *character_set,
+                        get_character_set_name(*character_set),
+                        "",
+                    ),
+                    node(9, 1).format("Folder Depth", folder_names.len() as u8, "", ""),
+                ];
+                let mut offset = 10;
+                for (i, name) in folder_names.iter().enumerate() {
+                    fields.push(node(offset, 2).format(
+                        &format!("Folder Name Length[{}]", i),
+                        name.len() as u16,
+                        "",
+                        "",
+                    ));
+                    fields.push(node(offset + 2, name.len() as u16).format(
+                        &format!("Folder Name[{}]", i),
+                        bytes_to_string(name),
+                        "",
+                        "",
+                    ));
+                    offset += 2 + name.len() as u16;
+                }
+                fields
+            }
+            BrowsingParameters::GetFolderItems {
+                scope,
+                start_item,
+                end_item,
+                attribute_count,
+                attribute_ids,
+            } => {
+                let mut fields = vec![
+                    scope_node(start_byte, *scope),
+                    node(1, 4).format("Start Item", *start_item, "", ""),
+                    node(5, 4).format("End Item", *end_item, "", ""),
+                    node(9, 1).format(
+                        "Attribute Count",
+                        *attribute_count,
+                        match attribute_count {
+                            0x00 => "All attributes",
+                            0xff => "No attributes",
+                            _ => "",
+                        },
+                        "",
+                    ),
+                ];
+                fields.extend(attribute_ids_as_json(attribute_ids, start_byte + 10));
+                fields
+            }
+            BrowsingParameters::FolderItems {
+                status,
+                uid_counter,
+                items,
+            } => {
+                let mut fields = vec![
+                    status_node(start_byte, *status),
+                    node(1, 2).format("UID Counter", *uid_counter, "", ""),
+                    node(3, 2).format("Number Of Items", items.len() as u16, "", ""),
+                ];
+                let mut offset = start_byte + 5;
+                for (i, item) in items.iter().enumerate() {
+                    fields.push(format!(r#""Item[{}]": {{{}}}"#, i, item.as_json(offset)));
+                    offset += 3 + item.len();
+                }
+                fields
+            }
+            BrowsingParameters::ChangePath {
+                uid_counter,
+                direction,
+                folder_uid,
+            } => vec![
+                node(0, 2).format("UID Counter", *uid_counter, "", ""),
+                node(2, 1).format(
+                    "Direction",
+                    *direction,
+                    match direction {
+                        0x00 => "Folder Up",
+                        0x01 => "Folder Down",
+                        _ => "Reserved",
+                    },
+                    "",
+                ),
+                node(3, 8).format("Folder UID", *folder_uid, "", ""),
+            ],
+            BrowsingParameters::NumberOfItems {
+                status,
+                uid_counter,
+                number_of_items,
+            } => {
+                let mut fields = vec![status_node(start_byte, *status)];
+                let mut offset = 1;
+                if let Some(uid_counter) = uid_counter {
+                    fields.push(node(1, 2).format("UID Counter", *uid_counter, "", ""));
+                    offset += 2;
+                }
+                fields.push(node(offset, 4).format("Number Of Items", *number_of_items, "", ""));
+                fields
+            }
+            BrowsingParameters::GetItemAttributes {
+                scope,
+                uid,
+                uid_counter,
+                attribute_ids,
+            } => {
+                let mut fields = vec![
+                    scope_node(start_byte, *scope),
+                    node(1, 8).format("UID", *uid, "", ""),
+                    node(9, 2).format("UID Counter", *uid_counter, "", ""),
+                    node(11, 1).format("Number Of Attributes", attribute_ids.len() as u8, "", ""),
+                ];
+                fields.extend(attribute_ids_as_json(attribute_ids, start_byte + 12));
+                fields
+            }
+            BrowsingParameters::ItemAttributes { status, attributes } => {
+                let mut fields = vec![
+                    status_node(start_byte, *status),
+                    node(1, 1).format("Number Of Attributes", attributes.len() as u8, "", ""),
+                ];
+                fields.extend(attributes_as_json(attributes, start_byte + 2));
+                fields
+            }
+            BrowsingParameters::Scope(scope) => vec![scope_node(start_byte, *scope)],
+            BrowsingParameters::Status(status) => vec![status_node(start_byte, *status)],
+            BrowsingParameters::Raw(data) => {
+                vec![node(0, data.len() as u16).format("Parameters", &data[..], "", "")]
+            }
+        }
+    }
+}
+
+impl Item {
+    fn parse(reader: &mut Reader) -> Option<Self> {
+        let item_type = reader.u8()?;
+        let len = reader.u16()?;
+        let mut item = Reader::new(reader.bytes(len as usize)?);
+        let parsed = match item_type {
+            0x01 => (|| {
+                Some(Item::MediaPlayer {
+                    player_id: item.u16()?,
+                    major_player_type: item.u8()?,
+                    player_sub_type: item.u32()?,
+                    play_status: item.u8()?,
+                    features: item.bytes(16)?.to_vec(),
+                    character_set: item.u16()?,
+                    name: item.text()?,
+                })
+            })(),
+            0x02 => (|| {
+                Some(Item::Folder {
+                    uid: item.u64()?,
+                    folder_type: item.u8()?,
+                    playable: item.u8()?,
+                    character_set: item.u16()?,
+                    name: item.text()?,
+                })
+            })(),
+            0x03 => (|| {
+                let uid = item.u64()?;
+                let media_type = item.u8()?;
+                let character_set = item.u16()?;
+                let name = item.text()?;
+                let count = item.u8()?;
+                let attributes = (0..count)
+                    .map(|_| AttributeValue::parse(&mut item))
+                    .collect::<Option<Vec<AttributeValue>>>()?;
+                Some(Item::MediaElement {
+                    uid,
+                    media_type,
+                    character_set,
+                    name,
+                    attributes,
+                })
+            })(),
+            _ => None,
+        };
+        Some(parsed.unwrap_or_else(|| Item::Unknown(item_type, item.data.to_vec())))
+    }
+
+    fn item_type(&self) -> u8 {
+        match self {
+            Item::MediaPlayer { .. } => 0x01,
+            Item::Folder { .. } => 0x02,
+            Item::MediaElement { .. } => 0x03,
+            Item::Unknown(item_type, _) => *item_type,
+        }
+    }
+
+    /// The length of the item, not counting its type and length fields.
+    fn len(&self) -> u16 {
+        match self {
+            Item::MediaPlayer { name, .. } => 28 + name.len() as u16,
+            Item::Folder { name, .. } => 14 + name.len() as u16,
+            Item::MediaElement {
+                name, attributes, ..
+            } => {
+                14 + name.len() as u16
+                    + attributes
+                        .iter()
+                        .map(|attribute| attribute.len())
+                        .sum::<u16>()
+            }
+            Item::Unknown(_, data) => data.len() as u16,
+        }
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let node = |offset: u16, len: u16| ParseBytesNode::new(start_byte + offset, len);
+        let mut fields = vec![
+            node(0, 1).format(
+                "Item Type",
+                self.item_type(),
+                match self.item_type() {
+                    0x01 => "Media Player Item",
+                    0x02 => "Folder Item",
+                    0x03 => "Media Element Item",
+                    _ => "Reserved",
+                },
+                "",
+            ),
+            node(1, 2).format("Item Length", self.len(), "", ""),
+        ];
+        let name_nodes = |offset: u16, character_set: u16, name: &[u8]| {
+            [
+                node(offset, 2).format(
+                    "Character Set ID",
+                    character_set,
+                    get_character_set_name(character_set),
+                    "",
+                ),
+                node(offset + 2, 2).format("Name Length", name.len() as u16, "", ""),
+                node(offset + 4, name.len() as u16).format("Name", bytes_to_string(name), "", ""),
+            ]
+        };
+        match self {
+            Item::MediaPlayer {
+                player_id,
+                major_player_type,
+                player_sub_type,
+                play_status,
+                features,
+                character_set,
+                name,
+            } => {
+                fields.extend([
+                    node(3, 2).format("Player ID", *player_id, "", ""),
+                    node(5, 1).format(
+                        "Major Player Type",
+                        *major_player_type,
+                        &get_major_player_type_name(*major_player_type),
+                        "",
+                    ),
+                    node(6, 4).format(
+                        "Player Sub Type",
+                        *player_sub_type,
+                        match player_sub_type & 0x03 {
+                            0x01 => "Audio Book",
+                            0x02 => "Podcast",
+                            0x03 => "Audio Book, Podcast",
+                            _ => "",
+                        },
+                        "",
+                    ),
+                    node(10, 1).format(
+                        "Play Status",
+                        *play_status,
+                        get_play_status_name(*play_status),
+                        "",
+                    ),
+                    node(11, 16).format("Feature Bit Mask", &features[..], "", ""),
+                ]);
+                fields.extend(name_nodes(27, *character_set, name));
+            }
+            Item::Folder {
+                uid,
+                folder_type,
+                playable,
+                character_set,
+                name,
+            } => {
+                fields.extend([
+                    node(3, 8).format("Folder UID", *uid, "", ""),
+                    node(11, 1).format(
+                        "Folder Type",
+                        *folder_type,
+                        match folder_type {
+                            0x00 => "Mixed",
+                            0x01 => "Titles",
+                            0x02 => "Albums",
+                            0x03 => "Artists",
+                            0x04 => "Genres",
+                            0x05 => "Playlists",
+                            0x06 => "Years",
+                            _ => "Reserved",
+                        },
+                        "",
+                    ),
+                    node(12, 1).format(
+                        "Is Playable",
+                        *playable,
+                        match playable {
+                            0x00 => "Not playable",
+                            0x01 => "Playable",
+                            _ => "Reserved",
+                        },
+                        "",
+                    ),
+                ]);
+                fields.extend(name_nodes(13, *character_set, name));
+            }
+            Item::MediaElement {
+                uid,
+                media_type,
+                character_set,
+                name,
+                attributes,
+            } => {
+                fields.extend([
+                    node(3, 8).format("Media Element UID", *uid, "", ""),
+                    node(11, 1).format(
+                        "Media Type",
+                        *media_type,
+                        match media_type {
+                            0x00 => "Audio",
+                            0x01 => "Video",
+                            _ => "Reserved",
+                        },
+                        "",
+                    ),
+                ]);
+                fields.extend(name_nodes(12, *character_set, name));
+                let offset = 16 + name.len() as u16;
+                fields.push(node(offset, 1).format(
+                    "Number Of Attributes",
+                    attributes.len() as u8,
+                    "",
+                    "",
+                ));
+                fields.extend(attributes_as_json(attributes, start_byte + offset + 1));
+            }
+            Item::Unknown(_, data) if !data.is_empty() => {
+                fields.push(node(3, data.len() as u16).format("Item Data", &data[..], "", ""))
+            }
+            Item::Unknown(..) => {}
+        }
+        fields.join(", ")
+    }
+}
+
+fn status_node(start_byte: u16, status: u8) -> String {
+    ParseBytesNode::new(start_byte, 1).format("Status", status, get_status_name(status), "")
+}
+
+fn scope_node(start_byte: u16, scope: u8) -> String {
+    ParseBytesNode::new(start_byte, 1).format(
+        "Scope",
+        scope,
+        match scope {
+            0x00 => "Media Player List",
+            0x01 => "Media Player Virtual Filesystem",
+            0x02 => "Search",
+            0x03 => "Now Playing",
+            _ => "Reserved",
+        },
+        "",
+    )
+}
+
+fn get_major_player_type_name(major_player_type: u8) -> String {
+    ["Audio", "Video", "Broadcasting Audio", "Broadcasting Video"]
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| major_player_type & (1 << i) != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::parse_on_channel;
+
+    // AVCTP browsing channel 0x0040 <-> 0x0041: SetBrowsedPlayer,
+    // GetFolderItems answered with a folder and a media element, ChangePath
+    // and GetItemAttributes, each a command then its response
+    const PACKETS: [&str; 8] = [
+        "02 40 20 0c 00 08 00 41 00 10 11 0e 70 00 02 00 01",
+        "02 40 20 1b 00 17 00 40 00 12 11 0e 70 00 11 04 00 01 00 00 00 02 00 6a 01 00 05 4d 75 73 69 63",
+        "02 40 20 18 00 14 00 41 00 20 11 0e 71 00 0e 01 00 00 00 00 00 00 00 09 01 00 00 00 01",
+        "02 40 20 47 00 43 00 40 00 22 11 0e 71 00 3d 04 00 01 00 02 02 00 13 00 00 00 00 00 00 00 11 01 01 00 6a 00 05 41 6c 62 75 6d 03 00 1f 00 00 00 00 00 00 00 22 00 00 6a 00 04 53 6f 6e 67 01 00 00 00 01 00 6a 00 05 54 69 74 6c 65",
+        "02 40 20 15 00 11 00 41 00 30 11 0e 72 00 0b 00 01 01 00 00 00 00 00 00 00 11",
+        "02 40 20 0f 00 0b 00 40 00 32 11 0e 72 00 05 04 00 00 00 07",
+        "02 40 20 1a 00 16 00 41 00 40 11 0e 73 00 10 03 00 00 00 00 00 00 00 22 00 01 01 00 00 00 01",
+        "02 40 20 19 00 15 00 40 00 42 11 0e 73 00 0f 04 01 00 00 00 01 00 6a 00 05 48 65 6c 6c 6f",
+    ];
+
+    #[test]
+    fn set_browsed_player() {
+        let (json, _) = parse_on_channel(0x001b, &PACKETS);
+        assert!(json[0].contains(r#""AVRCP Browsing": {"PDU ID": [0x70, "SetBrowsedPlayer", "B(12, 1)", ""], "Parameter Length": [0x2, "", "B(13, 2)", ""], "Player ID": [0x1, "", "B(15, 2)", ""]}"#));
+        assert!(json[1].contains(r#""Status": [0x4, "Operation Completed Without Error", "B(15, 1)", ""], "UID Counter": [0x1, "", "B(16, 2)", ""], "Number Of Items": [0x2, "", "B(18, 4)", ""], "Character Set ID": [0x6a, "UTF-8", "B(22, 2)", ""], "Folder Depth": [0x1, "", "B(24, 1)", ""], "Folder Name Length[0]": [0x5, "", "B(25, 2)", ""], "Folder Name[0]": ["Music", "", "B(27, 5)", ""]}"#));
+    }
+
+    #[test]
+    fn folder_items_follow_each_other() {
+        let (json, _) = parse_on_channel(0x001b, &PACKETS);
+        assert!(json[2].contains(r#""Scope": [0x1, "Media Player Virtual Filesystem", "B(15, 1)", ""], "Start Item": [0x0, "", "B(16, 4)", ""], "End Item": [0x9, "", "B(20, 4)", ""], "Attribute Count": [0x1, "", "B(24, 1)", ""], "Attribute ID[0]": [0x1, "Title", "B(25, 4)", ""]"#));
+        assert!(json[3].contains(r#""Number Of Items": [0x2, "", "B(18, 2)", ""], "Item[0]": {"Item Type": [0x2, "Folder Item", "B(20, 1)", ""], "Item Length": [0x13, "", "B(21, 2)", ""], "Folder UID": [0x11, "", "B(23, 8)", ""]"#));
+        assert!(json[3].contains(r#""Name": ["Album", "", "B(37, 5)", ""]}, "Item[1]": {"Item Type": [0x3, "Media Element Item", "B(42, 1)", ""], "Item Length": [0x1f, "", "B(43, 2)", ""], "Media Element UID": [0x22, "", "B(45, 8)", ""]"#));
+        assert!(json[3].contains(r#""Name": ["Song", "", "B(58, 4)", ""], "Number Of Attributes": [0x1, "", "B(62, 1)", ""], "Attribute[0]": {"Attribute ID": [0x1, "Title", "B(63, 4)", ""]"#));
+        assert!(json[3].contains(r#""Attribute Value": ["Title", "", "B(71, 5)", ""]}}"#));
+    }
+
+    #[test]
+    fn change_path() {
+        let (json, _) = parse_on_channel(0x001b, &PACKETS);
+        assert!(json[4].contains(r#""UID Counter": [0x1, "", "B(15, 2)", ""], "Direction": [0x1, "Folder Down", "B(17, 1)", ""], "Folder UID": [0x11, "", "B(18, 8)", ""]}"#));
+        assert!(json[5].contains(r#""Status": [0x4, "Operation Completed Without Error", "B(15, 1)", ""], "Number Of Items": [0x7, "", "B(16, 4)", ""]}"#));
+    }
+
+    #[test]
+    fn get_item_attributes() {
+        let (json, _) = parse_on_channel(0x001b, &PACKETS);
+        assert!(json[6].contains(r#""Scope": [0x3, "Now Playing", "B(15, 1)", ""], "UID": [0x22, "", "B(16, 8)", ""], "UID Counter": [0x1, "", "B(24, 2)", ""], "Number Of Attributes": [0x1, "", "B(26, 1)", ""], "Attribute ID[0]": [0x1, "Title", "B(27, 4)", ""]}"#));
+        assert!(json[7].contains(r#""Number Of Attributes": [0x1, "", "B(16, 1)", ""], "Attribute[0]": {"Attribute ID": [0x1, "Title", "B(17, 4)", ""], "Character Set ID": [0x6a, "UTF-8", "B(21, 2)", ""], "Attribute Value Length": [0x5, "", "B(23, 2)", ""], "Attribute Value": ["Hello", "", "B(25, 5)", ""]}"#));
+    }
+}
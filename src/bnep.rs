@@ -0,0 +1,549 @@
+use crate::assigned_numbers::get_uuid16_name;
+use crate::HostStack;
+use crate::ParseNodeFormat;
+use crate::ParseNodeOpt;
+
+use crate::ParseBitsNode;
+use crate::ParseBytesNode;
+
+mod ethernet;
+use ethernet::{get_ether_type_name, EthernetPayload};
+
+const GENERAL_ETHERNET: u8 = 0x00;
+const CONTROL: u8 = 0x01;
+const COMPRESSED_ETHERNET: u8 = 0x02;
+const COMPRESSED_ETHERNET_SOURCE_ONLY: u8 = 0x03;
+const COMPRESSED_ETHERNET_DEST_ONLY: u8 = 0x04;
+
+const CONTROL_COMMAND_NOT_UNDERSTOOD: u8 = 0x00;
+const SETUP_CONNECTION_REQUEST: u8 = 0x01;
+const SETUP_CONNECTION_RESPONSE: u8 = 0x02;
+const FILTER_NET_TYPE_SET: u8 = 0x03;
+const FILTER_NET_TYPE_RESPONSE: u8 = 0x04;
+const FILTER_MULTI_ADDR_SET: u8 = 0x05;
+const FILTER_MULTI_ADDR_RESPONSE: u8 = 0x06;
+
+const EXTENSION_CONTROL: u8 = 0x00;
+
+/// An IEEE 802 MAC address, in network byte order as BNEP carries it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MacAddr([u8; 6]);
+
+impl MacAddr {
+    fn new(data: &[u8]) -> Option<Self> {
+        Some(MacAddr(data.get(0..6)?.try_into().unwrap()))
+    }
+}
+
+impl std::fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let addr = self.0;
+        write!(
+            f,
+            "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
+            addr[0], addr[1], addr[2], addr[3], addr[4], addr[5]
+        )
+    }
+}
+
+impl ParseNodeFormat for MacAddr {
+    fn node_format(&self) -> String {
+        format!(r#""{}""#, self)
+    }
+}
+
+/// A BNEP packet: the Ethernet header, all of it or what is left after
+/// compression, a control message, the extension headers and the Ethernet
+/// payload. All multi-byte fields of BNEP are big-endian.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq)]
+pub(crate) struct BNEP {
+    packet_type: u8,
+    extension: bool,
+    destination: Option<MacAddr>,
+    source: Option<MacAddr>,
+    protocol: Option<u16>,
+    control: Option<BnepControl>,
+    extensions: Vec<ExtensionHeader>,
+    payload: Option<EthernetPayload>,
+    error: &'static str,
+}
+
+#[derive(Debug, PartialEq)]
+struct ExtensionHeader {
+    extension_type: u8,
+    extension: bool,
+    length: u8,
+    // an extension control header carries a control message, the others
+    // are kept as sent
+    control: Option<BnepControl>,
+    data: Vec<u8>,
+}
+
+impl ParseNodeOpt for BNEP {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        let header = *data.first()?;
+        let mut bnep = BNEP {
+            packet_type: header & 0x7f,
+            extension: header & 0x80 != 0,
+            destination: None,
+            source: None,
+            protocol: None,
+            control: None,
+            extensions: Vec::new(),
+            payload: None,
+            error: "",
+        };
+        let mut offset = 1;
+        let (destination, source) = match bnep.packet_type {
+            GENERAL_ETHERNET => (true, true),
+            COMPRESSED_ETHERNET_SOURCE_ONLY => (false, true),
+            COMPRESSED_ETHERNET_DEST_ONLY => (true, false),
+            COMPRESSED_ETHERNET => (false, false),
+            CONTROL => {
+                let control = BnepControl::new(&data[offset..]);
+                offset += control.len();
+                bnep.control = Some(control);
+                (false, false)
+            }
+            _ => return Some(bnep),
+        };
+        if destination {
+            bnep.destination = MacAddr::new(&data[offset.min(data.len())..]);
+            if bnep.destination.is_none() {
+                bnep.error = "Ethernet header truncated";
+                return Some(bnep);
+            }
+            offset += 6;
+        }
+        if source {
+            bnep.source = MacAddr::new(&data[offset.min(data.len())..]);
+            if bnep.source.is_none() {
+                bnep.error = "Ethernet header truncated";
+                return Some(bnep);
+            }
+            offset += 6;
+        }
+        if bnep.packet_type != CONTROL {
+            let Some([high, low]) = data.get(offset..offset + 2) else {
+                bnep.error = "Ethernet header truncated";
+                return Some(bnep);
+            };
+            bnep.protocol = Some(u16::from_be_bytes([*high, *low]));
+            offset += 2;
+        }
+        let mut extension = bnep.extension;
+        while extension {
+            let Some([extension_header, length]) = data.get(offset..offset + 2) else {
+                bnep.error = "Extension header truncated";
+                return Some(bnep);
+            };
+            let end = offset + 2 + *length as usize;
+            let Some(extension_data) = data.get(offset + 2..end) else {
+                bnep.error = "Extension header truncated";
+                return Some(bnep);
+            };
+            extension = extension_header & 0x80 != 0;
+            let extension_type = extension_header & 0x7f;
+            bnep.extensions.push(ExtensionHeader {
+                extension_type,
+                extension,
+                length: *length,
+                control: match extension_type {
+                    EXTENSION_CONTROL => Some(BnepControl::new(extension_data)),
+                    _ => None,
+                },
+                data: extension_data.to_vec(),
+            });
+            offset = end;
+        }
+        if let Some(protocol) = bnep.protocol {
+            bnep.payload = EthernetPayload::new(protocol, &data[offset..]);
+        }
+        Some(bnep)
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut fields = vec![
+            ParseBitsNode::new(start_byte, 1, 7, 1).format(
+                "Extension Flag",
+                self.extension as u8,
+                "",
+                "",
+            ),
+            ParseBitsNode::new(start_byte, 1, 0, 7).format(
+                "BNEP Type",
+                self.packet_type,
+                get_packet_type_name(self.packet_type),
+                self.error,
+            ),
+        ];
+        let mut offset = start_byte + 1;
+        if let Some(control) = &self.control {
+            fields.push(control.as_json(offset));
+            offset += control.len() as u16;
+        }
+        if let Some(destination) = self.destination {
+            fields.push(ParseBytesNode::new(offset, 6).format(
+                "Destination Address",
+                destination,
+                "",
+                "",
+            ));
+            offset += 6;
+        }
+        if let Some(source) = self.source {
+            fields.push(ParseBytesNode::new(offset, 6).format("Source Address", source, "", ""));
+            offset += 6;
+        }
+        if let Some(protocol) = self.protocol {
+            fields.push(ParseBytesNode::new(offset, 2).format(
+                "Networking Protocol Type",
+                protocol,
+                get_ether_type_name(protocol),
+                "",
+            ));
+            offset += 2;
+        }
+        for (i, extension) in self.extensions.iter().enumerate() {
+            let mut extension_fields = vec![
+                ParseBitsNode::new(offset, 1, 7, 1).format(
+                    "Extension Flag",
+                    extension.extension as u8,
+                    "",
+                    "",
+                ),
+                ParseBitsNode::new(offset, 1, 0, 7).format(
+                    "Extension Type",
+                    extension.extension_type,
+                    match extension.extension_type {
+                        EXTENSION_CONTROL => "Extension Control",
+                        _ => "Reserved",
+                    },
+                    "",
+                ),
+                ParseBytesNode::new(offset + 1, 1).format(
+                    "Extension Length",
+                    extension.length,
+                    "",
+                    "",
+                ),
+            ];
+            match &extension.control {
+                Some(control) => extension_fields.push(control.as_json(offset + 2)),
+                None if !extension.data.is_empty() => extension_fields.push(
+                    ParseBytesNode::new(offset + 2, extension.length as u16).format(
+                        "Extension Data",
+                        &extension.data[..],
+                        "",
+                        "",
+                    ),
+                ),
+                None => {}
+            }
+            fields.push(format!(
+                r#""Extension Header[{}]": {{{}}}"#,
+                i,
+                extension_fields.join(", ")
+            ));
+            offset += 2 + extension.length as u16;
+        }
+        if let Some(payload) = &self.payload {
+            fields.push(payload.as_json(offset));
+        }
+        format!(r#""BNEP": {{{}}}"#, fields.join(", "))
+    }
+}
+
+/// A BNEP control message, sent as a control packet or in an extension
+/// header.
+#[derive(Debug, PartialEq)]
+struct BnepControl {
+    control_type: u8,
+    body: ControlBody,
+    error: &'static str,
+}
+
+#[derive(Debug, PartialEq)]
+enum ControlBody {
+    NotUnderstood(u8),
+    SetupConnectionRequest {
+        uuid_size: u8,
+        destination: Vec<u8>,
+        source: Vec<u8>,
+    },
+    ResponseMessage(u16),
+    FilterNetTypeSet(u16, Vec<(u16, u16)>),
+    FilterMultiAddrSet(u16, Vec<(MacAddr, MacAddr)>),
+    Unknown,
+}
+
+impl BnepControl {
+    fn new(data: &[u8]) -> Self {
+        let control_type = data.first().copied().unwrap_or(0xff);
+        let body = &data[1.min(data.len())..];
+        let be_u16 = |index: usize| {
+            body.get(index..index + 2)
+                .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        };
+        let body = match control_type {
+            CONTROL_COMMAND_NOT_UNDERSTOOD => body.first().map(|t| ControlBody::NotUnderstood(*t)),
+            SETUP_CONNECTION_REQUEST => body.first().and_then(|uuid_size| {
+                let size = *uuid_size as usize;
+                Some(ControlBody::SetupConnectionRequest {
+                    uuid_size: *uuid_size,
+                    destination: body.get(1..1 + size)?.to_vec(),
+                    source: body.get(1 + size..1 + 2 * size)?.to_vec(),
+                })
+            }),
+            SETUP_CONNECTION_RESPONSE | FILTER_NET_TYPE_RESPONSE | FILTER_MULTI_ADDR_RESPONSE => {
+                be_u16(0).map(ControlBody::ResponseMessage)
+            }
+            FILTER_NET_TYPE_SET => be_u16(0).and_then(|list_length| {
+                let list = body.get(2..2 + list_length as usize)?;
+                Some(ControlBody::FilterNetTypeSet(
+                    list_length,
+                    list.chunks_exact(4)
+                        .map(|range| {
+                            (
+                                u16::from_be_bytes([range[0], range[1]]),
+                                u16::from_be_bytes([range[2], range[3]]),
+                            )
+                        })
+                        .collect(),
+                ))
+            }),
+            FILTER_MULTI_ADDR_SET => be_u16(0).and_then(|list_length| {
+                let list = body.get(2..2 + list_length as usize)?;
+                Some(ControlBody::FilterMultiAddrSet(
+                    list_length,
+                    list.chunks_exact(12)
+                        .filter_map(|range| {
+                            Some((MacAddr::new(range)?, MacAddr::new(&range[6..])?))
+                        })
+                        .collect(),
+                ))
+            }),
+            _ => Some(ControlBody::Unknown),
+        };
+        match body {
+            Some(body) => BnepControl {
+                control_type,
+                body,
+                error: "",
+            },
+            None => BnepControl {
+                control_type,
+                body: ControlBody::Unknown,
+                error: "Control message truncated",
+            },
+        }
+    }
+
+    fn len(&self) -> usize {
+        1 + match &self.body {
+            ControlBody::NotUnderstood(_) => 1,
+            ControlBody::SetupConnectionRequest { uuid_size, .. } => 1 + 2 * *uuid_size as usize,
+            ControlBody::ResponseMessage(_) => 2,
+            ControlBody::FilterNetTypeSet(list_length, _)
+            | ControlBody::FilterMultiAddrSet(list_length, _) => 2 + *list_length as usize,
+            ControlBody::Unknown => 0,
+        }
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut fields = vec![ParseBytesNode::new(start_byte, 1).format(
+            "BNEP Control Type",
+            self.control_type,
+            get_control_type_name(self.control_type),
+            self.error,
+        )];
+        let offset = start_byte + 1;
+        match &self.body {
+            ControlBody::NotUnderstood(unknown_type) => {
+                fields.push(ParseBytesNode::new(offset, 1).format(
+                    "Unknown Control Type",
+                    *unknown_type,
+                    get_control_type_name(*unknown_type),
+                    "",
+                ))
+            }
+            ControlBody::SetupConnectionRequest {
+                uuid_size,
+                destination,
+                source,
+            } => {
+                fields.push(ParseBytesNode::new(offset, 1).format("UUID Size", *uuid_size, "", ""));
+                let size = *uuid_size as u16;
+                for (i, (key, uuid)) in [
+                    ("Destination Service UUID", destination),
+                    ("Source Service UUID", source),
+                ]
+                .iter()
+                .enumerate()
+                {
+                    fields.push(
+                        ParseBytesNode::new(offset + 1 + i as u16 * size, size).format(
+                            key,
+                            &uuid[..],
+                            &get_service_uuid_name(uuid),
+                            "",
+                        ),
+                    );
+                }
+            }
+            ControlBody::ResponseMessage(response) => {
+                fields.push(ParseBytesNode::new(offset, 2).format(
+                    "Response Message",
+                    *response,
+                    match self.control_type {
+                        SETUP_CONNECTION_RESPONSE => get_setup_response_name(*response),
+                        _ => get_filter_response_name(*response),
+                    },
+                    "",
+                ))
+            }
+            ControlBody::FilterNetTypeSet(list_length, ranges) => {
+                fields.push(ParseBytesNode::new(offset, 2).format(
+                    "List Length",
+                    *list_length,
+                    "",
+                    "",
+                ));
+                for (i, (start, end)) in ranges.iter().enumerate() {
+                    let range_offset = offset + 2 + 4 * i as u16;
+                    fields.push(ParseBytesNode::new(range_offset, 2).format(
+                        &format!("Network Protocol Type Start[{}]", i),
+                        *start,
+                        get_ether_type_name(*start),
+                        "",
+                    ));
+                    fields.push(ParseBytesNode::new(range_offset + 2, 2).format(
+                        &format!("Network Protocol Type End[{}]", i),
+                        *end,
+                        get_ether_type_name(*end),
+                        "",
+                    ));
+                }
+            }
+            ControlBody::FilterMultiAddrSet(list_length, ranges) => {
+                fields.push(ParseBytesNode::new(offset, 2).format(
+                    "List Length",
+                    *list_length,
+                    "",
+                    "",
+                ));
+                for (i, (start, end)) in ranges.iter().enumerate() {
+                    let range_offset = offset + 2 + 12 * i as u16;
+                    fields.push(ParseBytesNode::new(range_offset, 6).format(
+                        &format!("Multicast Address Start[{}]", i),
+                        *start,
+                        "",
+                        "",
+                    ));
+                    fields.push(ParseBytesNode::new(range_offset + 6, 6).format(
+                        &format!("Multicast Address End[{}]", i),
+                        *end,
+                        "",
+                        "",
+                    ));
+                }
+            }
+            ControlBody::Unknown => {}
+        }
+        fields.join(", ")
+    }
+}
+
+/// Names the PAN service a setup request asks for. 32 and 128-bit UUIDs
+/// are named when they are built on the Base UUID.
+fn get_service_uuid_name(uuid: &[u8]) -> String {
+    const BASE_UUID_TAIL: [u8; 12] = [
+        0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0x80, 0x5f, 0x9b, 0x34, 0xfb,
+    ];
+    let uuid16 = match uuid.len() {
+        2 => Some(u16::from_be_bytes([uuid[0], uuid[1]])),
+        4 | 16 if uuid[0..2] == [0, 0] && (uuid.len() == 4 || uuid[4..] == BASE_UUID_TAIL) => {
+            Some(u16::from_be_bytes([uuid[2], uuid[3]]))
+        }
+        _ => None,
+    };
+    uuid16
+        .map(|uuid| get_uuid16_name(uuid).to_string())
+        .unwrap_or_default()
+}
+
+fn get_packet_type_name(packet_type: u8) -> &'static str {
+    match packet_type {
+        GENERAL_ETHERNET => "BNEP_GENERAL_ETHERNET",
+        CONTROL => "BNEP_CONTROL",
+        COMPRESSED_ETHERNET => "BNEP_COMPRESSED_ETHERNET",
+        COMPRESSED_ETHERNET_SOURCE_ONLY => "BNEP_COMPRESSED_ETHERNET_SOURCE_ONLY",
+        COMPRESSED_ETHERNET_DEST_ONLY => "BNEP_COMPRESSED_ETHERNET_DEST_ONLY",
+        _ => "Reserved",
+    }
+}
+
+fn get_control_type_name(control_type: u8) -> &'static str {
+    match control_type {
+        CONTROL_COMMAND_NOT_UNDERSTOOD => "BNEP_CONTROL_COMMAND_NOT_UNDERSTOOD",
+        SETUP_CONNECTION_REQUEST => "BNEP_SETUP_CONNECTION_REQUEST_MSG",
+        SETUP_CONNECTION_RESPONSE => "BNEP_SETUP_CONNECTION_RESPONSE_MSG",
+        FILTER_NET_TYPE_SET => "BNEP_FILTER_NET_TYPE_SET_MSG",
+        FILTER_NET_TYPE_RESPONSE => "BNEP_FILTER_NET_TYPE_RESPONSE_MSG",
+        FILTER_MULTI_ADDR_SET => "BNEP_FILTER_MULTI_ADDR_SET_MSG",
+        FILTER_MULTI_ADDR_RESPONSE => "BNEP_FILTER_MULTI_ADDR_RESPONSE_MSG",
+        _ => "Reserved",
+    }
+}
+
+fn get_setup_response_name(response: u16) -> &'static str {
+    match response {
+        0x0000 => "Operation Successful",
+        0x0001 => "Operation Failed: Invalid Destination Service UUID",
+        0x0002 => "Operation Failed: Invalid Source Service UUID",
+        0x0003 => "Operation Failed: Invalid Service UUID Size",
+        0x0004 => "Operation Failed: Connection not allowed",
+        _ => "Reserved",
+    }
+}
+
+fn get_filter_response_name(response: u16) -> &'static str {
+    match response {
+        0x0000 => "Operation Successful",
+        0x0001 => "Unsupported Request",
+        0x0002 => "Operation Failed: Invalid Range",
+        0x0003 => "Operation Failed: Too many filters",
+        0x0004 => "Operation Failed: Unable to fulfill request due to security reasons",
+        _ => "Reserved",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::parse_on_channel;
+
+    // BNEP on 0x0040 <-> 0x0041: a compressed Ethernet packet with two
+    // extension headers, one whose extension header is cut short and a
+    // general Ethernet packet too short for its addresses
+    const PACKETS: [&str; 3] = [
+        "02 40 20 12 00 0e 00 41 00 82 12 34 81 02 de ad 00 03 04 00 00 aa bb",
+        "02 40 20 0a 00 06 00 41 00 82 12 34 00 05 04",
+        "02 40 20 09 00 05 00 41 00 00 01 02 03 04",
+    ];
+
+    #[test]
+    fn extension_headers_are_chained() {
+        let (json, _) = parse_on_channel(0x000f, &PACKETS);
+        assert!(json[0].contains(r#""Extension Header[0]": {"Extension Flag": [0x1, "", "B(12, 1), b(7, 1)", ""], "Extension Type": [0x1, "Reserved", "B(12, 1), b(0, 7)", ""], "Extension Length": [0x2, "", "B(13, 1)", ""], "Extension Data": ["de ad", "", "B(14, 2)", ""]}"#));
+        assert!(json[0].contains(r#""Extension Header[1]": {"Extension Flag": [0x0, "", "B(16, 1), b(7, 1)", ""], "Extension Type": [0x0, "Extension Control", "B(16, 1), b(0, 7)", ""], "Extension Length": [0x3, "", "B(17, 1)", ""], "BNEP Control Type": [0x4, "#));
+        assert!(json[0].contains(r#""Ethernet Payload": ["aa bb", "", "B(21, 2)", ""]"#));
+    }
+
+    #[test]
+    fn truncated_headers_are_flagged() {
+        let (json, _) = parse_on_channel(0x000f, &PACKETS);
+        assert!(json[1].contains(r#""BNEP Type": [0x2, "BNEP_COMPRESSED_ETHERNET", "B(9, 1), b(0, 7)", "Extension header truncated"]"#));
+        assert!(json[2].contains(r#""BNEP Type": [0x0, "BNEP_GENERAL_ETHERNET", "B(9, 1), b(0, 7)", "Ethernet header truncated"]"#));
+    }
+}
@@ -0,0 +1,863 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use super::MacAddr;
+
+use crate::ParseBitsNode;
+use crate::ParseBytesNode;
+
+const ETHER_TYPE_IPV4: u16 = 0x0800;
+const ETHER_TYPE_ARP: u16 = 0x0806;
+const ETHER_TYPE_VLAN: u16 = 0x8100;
+const ETHER_TYPE_IPV6: u16 = 0x86dd;
+
+const PROTOCOL_ICMP: u8 = 1;
+const PROTOCOL_TCP: u8 = 6;
+const PROTOCOL_UDP: u8 = 17;
+const PROTOCOL_ICMPV6: u8 = 58;
+
+fn be_u16(data: &[u8], index: usize) -> Option<u16> {
+    data.get(index..index + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn be_u32(data: &[u8], index: usize) -> Option<u32> {
+    data.get(index..index + 4)
+        .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+fn ipv4_addr(data: &[u8], index: usize) -> Option<Ipv4Addr> {
+    let bytes: [u8; 4] = data.get(index..index + 4)?.try_into().unwrap();
+    Some(Ipv4Addr::from(bytes))
+}
+
+fn ipv6_addr(data: &[u8], index: usize) -> Option<Ipv6Addr> {
+    let bytes: [u8; 16] = data.get(index..index + 16)?.try_into().unwrap();
+    Some(Ipv6Addr::from(bytes))
+}
+
+/// The Ethernet frame payload a BNEP packet carries, summarised down to
+/// the headers that tell what the traffic is.
+#[derive(Debug, PartialEq)]
+pub(super) enum EthernetPayload {
+    Vlan(u16, u16, Option<Box<EthernetPayload>>),
+    Ipv4(Box<Ipv4Packet>),
+    Ipv6(Box<Ipv6Packet>),
+    Arp(ArpPacket),
+    // a payload not decoded, or one too short for its headers
+    Other(Vec<u8>, &'static str),
+}
+
+impl EthernetPayload {
+    pub(super) fn new(ether_type: u16, data: &[u8]) -> Option<Self> {
+        if data.is_empty() {
+            return None;
+        }
+        Some(match ether_type {
+            ETHER_TYPE_VLAN => match be_u16(data, 0).zip(be_u16(data, 2)) {
+                Some((tci, inner_type)) => EthernetPayload::Vlan(
+                    tci,
+                    inner_type,
+                    EthernetPayload::new(inner_type, &data[4..]).map(Box::new),
+                ),
+                None => EthernetPayload::Other(data.to_vec(), "Truncated"),
+            },
+            ETHER_TYPE_IPV4 => match Ipv4Packet::new(data) {
+                Some(packet) => EthernetPayload::Ipv4(Box::new(packet)),
+                None => EthernetPayload::Other(data.to_vec(), "Truncated"),
+            },
+            ETHER_TYPE_IPV6 => match Ipv6Packet::new(data) {
+                Some(packet) => EthernetPayload::Ipv6(Box::new(packet)),
+                None => EthernetPayload::Other(data.to_vec(), "Truncated"),
+            },
+            // ARP for other than IPv4 over Ethernet is shown as sent
+            ETHER_TYPE_ARP => match ArpPacket::new(data) {
+                Some(packet) => EthernetPayload::Arp(packet),
+                None => EthernetPayload::Other(data.to_vec(), ""),
+            },
+            _ => EthernetPayload::Other(data.to_vec(), ""),
+        })
+    }
+
+    pub(super) fn as_json(&self, start_byte: u16) -> String {
+        match self {
+            EthernetPayload::Vlan(tci, inner_type, inner) => {
+                let mut fields = vec![
+                    ParseBitsNode::new(start_byte, 2, 13, 3).format(
+                        "Priority Code Point",
+                        tci >> 13,
+                        "",
+                        "",
+                    ),
+                    ParseBitsNode::new(start_byte, 2, 12, 1).format(
+                        "Drop Eligible Indicator",
+                        (tci >> 12) & 0x01,
+                        "",
+                        "",
+                    ),
+                    ParseBitsNode::new(start_byte, 2, 0, 12).format(
+                        "VLAN Identifier",
+                        tci & 0x0fff,
+                        "",
+                        "",
+                    ),
+                    ParseBytesNode::new(start_byte + 2, 2).format(
+                        "EtherType",
+                        *inner_type,
+                        get_ether_type_name(*inner_type),
+                        "",
+                    ),
+                ];
+                if let Some(inner) = inner {
+                    fields.push(inner.as_json(start_byte + 4));
+                }
+                format!(r#""802.1Q": {{{}}}"#, fields.join(", "))
+            }
+            EthernetPayload::Ipv4(packet) => packet.as_json(start_byte),
+            EthernetPayload::Ipv6(packet) => packet.as_json(start_byte),
+            EthernetPayload::Arp(packet) => packet.as_json(start_byte),
+            EthernetPayload::Other(data, error) => ParseBytesNode::new(
+                start_byte,
+                data.len() as u16,
+            )
+            .format("Ethernet Payload", &data[..], "", error),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) struct Ipv4Packet {
+    header_length: u8,
+    total_length: u16,
+    flags_fragment_offset: u16,
+    ttl: u8,
+    protocol: u8,
+    source: Ipv4Addr,
+    destination: Ipv4Addr,
+    // only the first fragment starts with the transport header
+    transport: Option<Transport>,
+}
+
+impl Ipv4Packet {
+    fn new(data: &[u8]) -> Option<Self> {
+        let header_length = (data.first()? & 0x0f) * 4;
+        let protocol = *data.get(9)?;
+        let flags_fragment_offset = be_u16(data, 6)?;
+        let transport = match flags_fragment_offset & 0x1fff {
+            0 => data
+                .get(header_length as usize..)
+                .and_then(|segment| Transport::new(protocol, segment, false)),
+            _ => None,
+        };
+        Some(Ipv4Packet {
+            header_length,
+            total_length: be_u16(data, 2)?,
+            flags_fragment_offset,
+            ttl: *data.get(8)?,
+            protocol,
+            source: ipv4_addr(data, 12)?,
+            destination: ipv4_addr(data, 16)?,
+            transport,
+        })
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let flags = self.flags_fragment_offset;
+        let mut flag_names = Vec::new();
+        if flags & 0x4000 != 0 {
+            flag_names.push("Don't Fragment".to_string());
+        }
+        if flags & 0x2000 != 0 {
+            flag_names.push("More Fragments".to_string());
+        }
+        if flags & 0x1fff != 0 {
+            flag_names.push(format!("Fragment Offset {}", (flags & 0x1fff) * 8));
+        }
+        let mut fields = vec![
+            ParseBitsNode::new(start_byte, 1, 4, 4).format("Version", 4u8, "", ""),
+            ParseBitsNode::new(start_byte, 1, 0, 4).format(
+                "Header Length",
+                self.header_length / 4,
+                &format!("{} bytes", self.header_length),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 2, 2).format(
+                "Total Length",
+                self.total_length,
+                "",
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 6, 2).format(
+                "Flags And Fragment Offset",
+                flags,
+                &flag_names.join(", "),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 8, 1).format("Time To Live", self.ttl, "", ""),
+            ParseBytesNode::new(start_byte + 9, 1).format(
+                "Protocol",
+                self.protocol,
+                get_ip_protocol_name(self.protocol),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 12, 4).format(
+                "Source Address",
+                self.source.to_string(),
+                "",
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 16, 4).format(
+                "Destination Address",
+                self.destination.to_string(),
+                "",
+                "",
+            ),
+        ];
+        if let Some(transport) = &self.transport {
+            fields.push(transport.as_json(start_byte + self.header_length as u16));
+        }
+        format!(r#""IPv4": {{{}}}"#, fields.join(", "))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) struct Ipv6Packet {
+    payload_length: u16,
+    next_header: u8,
+    hop_limit: u8,
+    source: Ipv6Addr,
+    destination: Ipv6Addr,
+    // extension headers are not followed
+    transport: Option<Transport>,
+}
+
+impl Ipv6Packet {
+    fn new(data: &[u8]) -> Option<Self> {
+        let next_header = *data.get(6)?;
+        Some(Ipv6Packet {
+            payload_length: be_u16(data, 4)?,
+            next_header,
+            hop_limit: *data.get(7)?,
+            source: ipv6_addr(data, 8)?,
+            destination: ipv6_addr(data, 24)?,
+            transport: Transport::new(next_header, data.get(40..)?, true),
+        })
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut fields = vec![
+            ParseBitsNode::new(start_byte, 1, 4, 4).format("Version", 6u8, "", ""),
+            ParseBytesNode::new(start_byte + 4, 2).format(
+                "Payload Length",
+                self.payload_length,
+                "",
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 6, 1).format(
+                "Next Header",
+                self.next_header,
+                get_ip_protocol_name(self.next_header),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 7, 1).format("Hop Limit", self.hop_limit, "", ""),
+            ParseBytesNode::new(start_byte + 8, 16).format(
+                "Source Address",
+                self.source.to_string(),
+                "",
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 24, 16).format(
+                "Destination Address",
+                self.destination.to_string(),
+                "",
+                "",
+            ),
+        ];
+        if let Some(transport) = &self.transport {
+            fields.push(transport.as_json(start_byte + 40));
+        }
+        format!(r#""IPv6": {{{}}}"#, fields.join(", "))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Transport {
+    Icmp {
+        version6: bool,
+        icmp_type: u8,
+        code: u8,
+        // identifier and sequence number of echo messages
+        echo: Option<(u16, u16)>,
+        // target address of neighbor solicitations and advertisements
+        target: Option<Ipv6Addr>,
+    },
+    Udp {
+        source_port: u16,
+        destination_port: u16,
+        length: u16,
+        application: Option<Application>,
+    },
+    Tcp {
+        source_port: u16,
+        destination_port: u16,
+        sequence_number: u32,
+        acknowledgment_number: u32,
+        flags: u8,
+    },
+}
+
+impl Transport {
+    fn new(protocol: u8, data: &[u8], version6: bool) -> Option<Self> {
+        match protocol {
+            PROTOCOL_ICMP | PROTOCOL_ICMPV6 => {
+                let icmp_type = *data.first()?;
+                let is_echo = match version6 {
+                    true => matches!(icmp_type, 128 | 129),
+                    false => matches!(icmp_type, 0 | 8),
+                };
+                Some(Transport::Icmp {
+                    version6,
+                    icmp_type,
+                    code: *data.get(1)?,
+                    echo: match is_echo {
+                        true => be_u16(data, 4).zip(be_u16(data, 6)),
+                        false => None,
+                    },
+                    target: match (version6, icmp_type) {
+                        (true, 135 | 136) => ipv6_addr(data, 8),
+                        _ => None,
+                    },
+                })
+            }
+            PROTOCOL_UDP => {
+                let source_port = be_u16(data, 0)?;
+                let destination_port = be_u16(data, 2)?;
+                let payload = data.get(8..).unwrap_or_default();
+                Some(Transport::Udp {
+                    source_port,
+                    destination_port,
+                    length: be_u16(data, 4)?,
+                    application: Application::new(source_port, destination_port, payload),
+                })
+            }
+            PROTOCOL_TCP => Some(Transport::Tcp {
+                source_port: be_u16(data, 0)?,
+                destination_port: be_u16(data, 2)?,
+                sequence_number: be_u32(data, 4)?,
+                acknowledgment_number: be_u32(data, 8)?,
+                flags: *data.get(13)?,
+            }),
+            _ => None,
+        }
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        match self {
+            Transport::Icmp {
+                version6,
+                icmp_type,
+                code,
+                echo,
+                target,
+            } => {
+                let mut fields = vec![
+                    ParseBytesNode::new(start_byte, 1).format(
+                        "Type",
+                        *icmp_type,
+                        match version6 {
+                            true => get_icmpv6_type_name(*icmp_type),
+                            false => get_icmp_type_name(*icmp_type),
+                        },
+                        "",
+                    ),
+                    ParseBytesNode::new(start_byte + 1, 1).format("Code", *code, "", ""),
+                ];
+                if let Some((identifier, sequence_number)) = echo {
+                    fields.push(ParseBytesNode::new(start_byte + 4, 2).format(
+                        "Identifier",
+                        *identifier,
+                        "",
+                        "",
+                    ));
+                    fields.push(ParseBytesNode::new(start_byte + 6, 2).format(
+                        "Sequence Number",
+                        *sequence_number,
+                        "",
+                        "",
+                    ));
+                }
+                if let Some(target) = target {
+                    fields.push(ParseBytesNode::new(start_byte + 8, 16).format(
+                        "Target Address",
+                        target.to_string(),
+                        "",
+                        "",
+                    ));
+                }
+                let key = match version6 {
+                    true => "ICMPv6",
+                    false => "ICMP",
+                };
+                format!(r#""{}": {{{}}}"#, key, fields.join(", "))
+            }
+            Transport::Udp {
+                source_port,
+                destination_port,
+                length,
+                application,
+            } => {
+                let mut fields = vec![
+                    ParseBytesNode::new(start_byte, 2).format(
+                        "Source Port",
+                        *source_port,
+                        get_port_name(*source_port),
+                        "",
+                    ),
+                    ParseBytesNode::new(start_byte + 2, 2).format(
+                        "Destination Port",
+                        *destination_port,
+                        get_port_name(*destination_port),
+                        "",
+                    ),
+                    ParseBytesNode::new(start_byte + 4, 2).format("Length", *length, "", ""),
+                ];
+                if let Some(application) = application {
+                    fields.push(application.as_json(start_byte + 8));
+                }
+                format!(r#""UDP": {{{}}}"#, fields.join(", "))
+            }
+            Transport::Tcp {
+                source_port,
+                destination_port,
+                sequence_number,
+                acknowledgment_number,
+                flags,
+            } => {
+                let flag_names = ["FIN", "SYN", "RST", "PSH", "ACK", "URG", "ECE", "CWR"]
+                    .iter()
+                    .enumerate()
+                    .filter(|(bit, _)| flags & (1 << bit) != 0)
+                    .map(|(_, name)| *name)
+                    .collect::<Vec<&str>>()
+                    .join(", ");
+                let fields = [
+                    ParseBytesNode::new(start_byte, 2).format(
+                        "Source Port",
+                        *source_port,
+                        get_port_name(*source_port),
+                        "",
+                    ),
+                    ParseBytesNode::new(start_byte + 2, 2).format(
+                        "Destination Port",
+                        *destination_port,
+                        get_port_name(*destination_port),
+                        "",
+                    ),
+                    ParseBytesNode::new(start_byte + 4, 4).format(
+                        "Sequence Number",
+                        *sequence_number,
+                        "",
+                        "",
+                    ),
+                    ParseBytesNode::new(start_byte + 8, 4).format(
+                        "Acknowledgment Number",
+                        *acknowledgment_number,
+                        "",
+                        "",
+                    ),
+                    ParseBytesNode::new(start_byte + 13, 1).format(
+                        "Flags",
+                        *flags,
+                        &flag_names,
+                        "",
+                    ),
+                ];
+                format!(r#""TCP": {{{}}}"#, fields.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Application {
+    Dhcp(DhcpMessage),
+    Dhcpv6 {
+        message_type: u8,
+        transaction_id: u32,
+    },
+}
+
+impl Application {
+    fn new(source_port: u16, destination_port: u16, data: &[u8]) -> Option<Self> {
+        match (source_port, destination_port) {
+            (67 | 68, 67 | 68) => DhcpMessage::new(data).map(Application::Dhcp),
+            (546 | 547, 546 | 547) => Some(Application::Dhcpv6 {
+                message_type: *data.first()?,
+                transaction_id: be_u32(data, 0)? & 0x00ff_ffff,
+            }),
+            _ => None,
+        }
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        match self {
+            Application::Dhcp(message) => message.as_json(start_byte),
+            Application::Dhcpv6 {
+                message_type,
+                transaction_id,
+            } => {
+                let fields = [
+                    ParseBytesNode::new(start_byte, 1).format(
+                        "Message Type",
+                        *message_type,
+                        get_dhcpv6_message_type_name(*message_type),
+                        "",
+                    ),
+                    ParseBytesNode::new(start_byte + 1, 3).format(
+                        "Transaction ID",
+                        *transaction_id,
+                        "",
+                        "",
+                    ),
+                ];
+                format!(r#""DHCPv6": {{{}}}"#, fields.join(", "))
+            }
+        }
+    }
+}
+
+/// A DHCP message, with the options that tell how the lease goes.
+#[derive(Debug, PartialEq)]
+struct DhcpMessage {
+    op: u8,
+    transaction_id: u32,
+    client_address: Ipv4Addr,
+    your_address: Ipv4Addr,
+    client_hardware_address: MacAddr,
+    // offset from the start of the message, code and data
+    options: Vec<(u16, u8, Vec<u8>)>,
+}
+
+impl DhcpMessage {
+    fn new(data: &[u8]) -> Option<Self> {
+        const MAGIC_COOKIE: [u8; 4] = [0x63, 0x82, 0x53, 0x63];
+        let mut options = Vec::new();
+        if data.get(236..240) == Some(&MAGIC_COOKIE[..]) {
+            let mut offset = 240;
+            while let Some(code) = data.get(offset) {
+                match code {
+                    0 => offset += 1,
+                    255 => break,
+                    _ => {
+                        let Some(length) = data.get(offset + 1) else {
+                            break;
+                        };
+                        let end = (offset + 2 + *length as usize).min(data.len());
+                        options.push((offset as u16, *code, data[offset + 2..end].to_vec()));
+                        offset = end;
+                    }
+                }
+            }
+        }
+        Some(DhcpMessage {
+            op: *data.first()?,
+            transaction_id: be_u32(data, 4)?,
+            client_address: ipv4_addr(data, 12)?,
+            your_address: ipv4_addr(data, 16)?,
+            client_hardware_address: MacAddr::new(data.get(28..)?)?,
+            options,
+        })
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut fields = vec![
+            ParseBytesNode::new(start_byte, 1).format(
+                "Op",
+                self.op,
+                match self.op {
+                    1 => "BOOTREQUEST",
+                    2 => "BOOTREPLY",
+                    _ => "",
+                },
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 4, 4).format(
+                "Transaction ID",
+                self.transaction_id,
+                "",
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 12, 4).format(
+                "Client IP Address",
+                self.client_address.to_string(),
+                "",
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 16, 4).format(
+                "Your IP Address",
+                self.your_address.to_string(),
+                "",
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 28, 6).format(
+                "Client Hardware Address",
+                self.client_hardware_address,
+                "",
+                "",
+            ),
+        ];
+        for (offset, code, data) in &self.options {
+            let node = ParseBytesNode::new(start_byte + offset, 2 + data.len() as u16);
+            let address = ipv4_addr(data, 0).map(|address| address.to_string());
+            fields.push(match (code, &data[..], address) {
+                (53, [message_type], _) => node.format(
+                    "DHCP Message Type",
+                    *message_type,
+                    get_dhcp_message_type_name(*message_type),
+                    "",
+                ),
+                (50, _, Some(address)) => node.format("Requested IP Address", address, "", ""),
+                (54, _, Some(address)) => node.format("Server Identifier", address, "", ""),
+                (1, _, Some(address)) => node.format("Subnet Mask", address, "", ""),
+                (3, _, Some(address)) => node.format("Router", address, "", ""),
+                (51, _, _) => match be_u32(data, 0) {
+                    Some(lease_time) => node.format(
+                        "IP Address Lease Time",
+                        lease_time,
+                        &format!("{} s", lease_time),
+                        "",
+                    ),
+                    None => node.format("IP Address Lease Time", &data[..], "", "Truncated"),
+                },
+                (12, _, _) => node.format("Host Name", crate::bytes_to_string(data), "", ""),
+                _ => node.format(&format!("Option {}", code), &data[..], "", ""),
+            });
+        }
+        format!(r#""DHCP": {{{}}}"#, fields.join(", "))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) struct ArpPacket {
+    operation: u16,
+    sender_hardware_address: MacAddr,
+    sender_protocol_address: Ipv4Addr,
+    target_hardware_address: MacAddr,
+    target_protocol_address: Ipv4Addr,
+}
+
+impl ArpPacket {
+    /// Only ARP for IPv4 over Ethernet is decoded.
+    fn new(data: &[u8]) -> Option<Self> {
+        if data.get(0..6)? != [0x00, 0x01, 0x08, 0x00, 0x06, 0x04] {
+            return None;
+        }
+        Some(ArpPacket {
+            operation: be_u16(data, 6)?,
+            sender_hardware_address: MacAddr::new(data.get(8..)?)?,
+            sender_protocol_address: ipv4_addr(data, 14)?,
+            target_hardware_address: MacAddr::new(data.get(18..)?)?,
+            target_protocol_address: ipv4_addr(data, 24)?,
+        })
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let fields = [
+            ParseBytesNode::new(start_byte, 2).format("Hardware Type", 1u16, "Ethernet", ""),
+            ParseBytesNode::new(start_byte + 2, 2).format("Protocol Type", 0x0800u16, "IPv4", ""),
+            ParseBytesNode::new(start_byte + 6, 2).format(
+                "Operation",
+                self.operation,
+                match self.operation {
+                    1 => "Request",
+                    2 => "Reply",
+                    _ => "",
+                },
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 8, 6).format(
+                "Sender Hardware Address",
+                self.sender_hardware_address,
+                "",
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 14, 4).format(
+                "Sender Protocol Address",
+                self.sender_protocol_address.to_string(),
+                "",
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 18, 6).format(
+                "Target Hardware Address",
+                self.target_hardware_address,
+                "",
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 24, 4).format(
+                "Target Protocol Address",
+                self.target_protocol_address.to_string(),
+                "",
+                "",
+            ),
+        ];
+        format!(r#""ARP": {{{}}}"#, fields.join(", "))
+    }
+}
+
+pub(super) fn get_ether_type_name(ether_type: u16) -> &'static str {
+    match ether_type {
+        ETHER_TYPE_IPV4 => "IPv4",
+        ETHER_TYPE_ARP => "ARP",
+        ETHER_TYPE_VLAN => "802.1Q VLAN",
+        ETHER_TYPE_IPV6 => "IPv6",
+        0x888e => "EAPOL",
+        0x88cc => "LLDP",
+        0x0000..=0x05dc => "IEEE 802.3 Length",
+        _ => "",
+    }
+}
+
+fn get_ip_protocol_name(protocol: u8) -> &'static str {
+    match protocol {
+        0 => "IPv6 Hop-by-Hop Option",
+        PROTOCOL_ICMP => "ICMP",
+        2 => "IGMP",
+        PROTOCOL_TCP => "TCP",
+        PROTOCOL_UDP => "UDP",
+        43 => "IPv6 Routing Header",
+        44 => "IPv6 Fragment Header",
+        PROTOCOL_ICMPV6 => "ICMPv6",
+        59 => "No Next Header",
+        _ => "",
+    }
+}
+
+fn get_icmp_type_name(icmp_type: u8) -> &'static str {
+    match icmp_type {
+        0 => "Echo Reply",
+        3 => "Destination Unreachable",
+        5 => "Redirect",
+        8 => "Echo Request",
+        11 => "Time Exceeded",
+        _ => "",
+    }
+}
+
+fn get_icmpv6_type_name(icmp_type: u8) -> &'static str {
+    match icmp_type {
+        1 => "Destination Unreachable",
+        2 => "Packet Too Big",
+        3 => "Time Exceeded",
+        128 => "Echo Request",
+        129 => "Echo Reply",
+        133 => "Router Solicitation",
+        134 => "Router Advertisement",
+        135 => "Neighbor Solicitation",
+        136 => "Neighbor Advertisement",
+        143 => "Multicast Listener Report v2",
+        _ => "",
+    }
+}
+
+fn get_port_name(port: u16) -> &'static str {
+    match port {
+        53 => "DNS",
+        67 => "DHCP Server",
+        68 => "DHCP Client",
+        80 => "HTTP",
+        123 => "NTP",
+        443 => "HTTPS",
+        546 => "DHCPv6 Client",
+        547 => "DHCPv6 Server",
+        5353 => "mDNS",
+        _ => "",
+    }
+}
+
+fn get_dhcp_message_type_name(message_type: u8) -> &'static str {
+    match message_type {
+        1 => "DHCPDISCOVER",
+        2 => "DHCPOFFER",
+        3 => "DHCPREQUEST",
+        4 => "DHCPDECLINE",
+        5 => "DHCPACK",
+        6 => "DHCPNAK",
+        7 => "DHCPRELEASE",
+        8 => "DHCPINFORM",
+        _ => "",
+    }
+}
+
+fn get_dhcpv6_message_type_name(message_type: u8) -> &'static str {
+    match message_type {
+        1 => "SOLICIT",
+        2 => "ADVERTISE",
+        3 => "REQUEST",
+        4 => "CONFIRM",
+        5 => "RENEW",
+        6 => "REBIND",
+        7 => "REPLY",
+        8 => "RELEASE",
+        9 => "DECLINE",
+        10 => "RECONFIGURE",
+        11 => "INFORMATION-REQUEST",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::parse_on_channel;
+
+    // compressed Ethernet packets on BNEP 0x0040 <-> 0x0041, the payload
+    // starting at byte 12
+    const ICMP_ECHO: &str = "02 40 20 27 00 23 00 41 00 02 08 00 45 00 00 20 12 34 40 00 40 01 00 00 c0 a8 01 0a c0 a8 01 01 08 00 00 00 01 02 00 07 70 69 6e 67";
+    const VLAN_ICMPV6_ECHO: &str = "02 40 20 3f 00 3b 00 41 00 02 81 00 a0 0c 86 dd 60 00 00 00 00 0c 3a ff fe 80 00 00 00 00 00 00 02 11 22 ff fe 33 44 55 ff 02 00 00 00 00 00 00 00 00 00 00 00 00 00 01 80 00 00 00 0a 0b 00 01 70 69 6e 67";
+    const ARP_REQUEST: &str = "02 40 20 23 00 1f 00 41 00 02 08 06 00 01 08 00 06 04 00 01 00 11 22 33 44 55 c0 a8 01 0a 00 00 00 00 00 00 c0 a8 01 01";
+
+    /// A DHCPDISCOVER from 0.0.0.0:68 to 255.255.255.255:67, its options
+    /// padded apart.
+    fn dhcp_discover() -> String {
+        let ip_udp = "02 40 20 28 01 24 01 41 00 02 08 00 45 00 01 21 12 34 40 00 40 11 00 00 00 00 00 00 ff ff ff ff 00 44 00 43 01 0d 00 00";
+        let bootp = "01 01 06 00 de ad be ef";
+        let options = "63 82 53 63 35 01 01 00 32 04 c0 a8 01 0a 0c 04 68 6f 73 74 37 02 01 03 ff";
+        format!(
+            "{} {}{} 00 11 22 33 44 55{} {}",
+            ip_udp,
+            bootp,
+            " 00".repeat(20),
+            " 00".repeat(202),
+            options
+        )
+    }
+
+    #[test]
+    fn ipv4_icmp_echo() {
+        let (json, _) = parse_on_channel(0x000f, &[ICMP_ECHO]);
+        assert!(json[0].contains(r#""IPv4": {"Version": [0x4, "", "B(12, 1), b(4, 4)", ""], "Header Length": [0x5, "20 bytes", "B(12, 1), b(0, 4)", ""], "Total Length": [0x20, "", "B(14, 2)", ""], "Flags And Fragment Offset": [0x4000, "Don't Fragment", "B(18, 2)", ""], "Time To Live": [0x40, "", "B(20, 1)", ""], "Protocol": [0x1, "ICMP", "B(21, 1)", ""], "Source Address": ["192.168.1.10", "", "B(24, 4)", ""], "Destination Address": ["192.168.1.1", "", "B(28, 4)", ""]"#));
+        assert!(json[0].contains(r#""ICMP": {"Type": [0x8, "Echo Request", "B(32, 1)", ""], "Code": [0x0, "", "B(33, 1)", ""], "Identifier": [0x102, "", "B(36, 2)", ""], "Sequence Number": [0x7, "", "B(38, 2)", ""]}"#));
+    }
+
+    #[test]
+    fn vlan_tag_shifts_the_ipv6_packet() {
+        let (json, _) = parse_on_channel(0x000f, &[VLAN_ICMPV6_ECHO]);
+        assert!(json[0].contains(r#""802.1Q": {"Priority Code Point": [0x5, "", "B(12, 2), b(13, 3)", ""], "Drop Eligible Indicator": [0x0, "", "B(12, 2), b(12, 1)", ""], "VLAN Identifier": [0xc, "", "B(12, 2), b(0, 12)", ""], "EtherType": [0x86dd, "IPv6", "B(14, 2)", ""], "IPv6": {"Version": [0x6, "", "B(16, 1), b(4, 4)", ""]"#));
+        assert!(json[0].contains(r#""Next Header": [0x3a, "ICMPv6", "B(22, 1)", ""], "Hop Limit": [0xff, "", "B(23, 1)", ""], "Source Address": ["fe80::211:22ff:fe33:4455", "", "B(24, 16)", ""], "Destination Address": ["ff02::1", "", "B(40, 16)", ""]"#));
+        assert!(json[0].contains(r#""ICMPv6": {"Type": [0x80, "Echo Request", "B(56, 1)", ""], "Code": [0x0, "", "B(57, 1)", ""], "Identifier": [0xa0b, "", "B(60, 2)", ""], "Sequence Number": [0x1, "", "B(62, 2)", ""]}"#));
+    }
+
+    #[test]
+    fn arp_request() {
+        let (json, _) = parse_on_channel(0x000f, &[ARP_REQUEST]);
+        assert!(json[0].contains(r#""ARP": {"Hardware Type": [0x1, "Ethernet", "B(12, 2)", ""], "Protocol Type": [0x800, "IPv4", "B(14, 2)", ""], "Operation": [0x1, "Request", "B(18, 2)", ""], "Sender Hardware Address": ["00:11:22:33:44:55", "", "B(20, 6)", ""], "Sender Protocol Address": ["192.168.1.10", "", "B(26, 4)", ""], "Target Hardware Address": ["00:00:00:00:00:00", "", "B(30, 6)", ""], "Target Protocol Address": ["192.168.1.1", "", "B(36, 4)", ""]}"#));
+    }
+
+    #[test]
+    fn dhcp_options_follow_the_magic_cookie() {
+        let (json, _) = parse_on_channel(0x000f, &[&dhcp_discover()]);
+        assert!(json[0].contains(r#""UDP": {"Source Port": [0x44, "DHCP Client", "B(32, 2)", ""], "Destination Port": [0x43, "DHCP Server", "B(34, 2)", ""], "Length": [0x10d, "", "B(36, 2)", ""], "DHCP": {"Op": [0x1, "BOOTREQUEST", "B(40, 1)", ""], "Transaction ID": [0xdeadbeef, "", "B(44, 4)", ""]"#));
+        assert!(json[0]
+            .contains(r#""Client Hardware Address": ["00:11:22:33:44:55", "", "B(68, 6)", ""]"#));
+        // the pad option at byte 283 is skipped
+        assert!(json[0].contains(r#""DHCP Message Type": [0x1, "DHCPDISCOVER", "B(280, 3)", ""], "Requested IP Address": ["192.168.1.10", "", "B(284, 6)", ""], "Host Name": ["host", "", "B(290, 6)", ""], "Option 55": ["01 03", "", "B(296, 4)", ""]}"#));
+    }
+}
@@ -1,13 +1,237 @@
+use std::fmt::Debug;
+use std::fmt::Display;
+
+use crate::ad::AdvertisingData;
+use crate::assigned_numbers::{class_of_device_to_string, get_company_name, get_version_name};
 use crate::l2cap::L2CAP;
 use crate::HostStack;
 use crate::ParseNode;
 use crate::ParseNodeA;
+use crate::ParseNodeFormat;
 use crate::ParseNodeOpt;
 use crate::ParseNodeOptA;
 
 use crate::ParseBitsNode;
 use crate::ParseBytesNode;
 
+mod le_meta;
+use le_meta::EvtLeMeta;
+
+#[derive(Default, Debug)]
+pub struct HciArg {
+    pairings: Vec<SspPairing>,
+    // remote device of each open connection, from Connection Complete
+    connections: Vec<(u16, BdAddr)>,
+    // connection handle of the ACL packet being parsed
+    acl_handle: u16,
+}
+
+impl HciArg {
+    /// The connection the ACL data being parsed belongs to, for the layers
+    /// above L2CAP that keep state per connection.
+    pub(crate) fn acl_handle(&self) -> u16 {
+        self.acl_handle
+    }
+
+    /// The remote device of the connection `acl_handle`, when its Connection
+    /// Complete was seen.
+    pub(crate) fn remote_device(&self, acl_handle: u16) -> Option<BdAddr> {
+        self.connections
+            .iter()
+            .find(|(handle, _)| *handle == acl_handle)
+            .map(|(_, bd_addr)| *bd_addr)
+    }
+
+    fn connect(&mut self, acl_handle: u16, bd_addr: BdAddr) {
+        self.connections.retain(|(handle, _)| *handle != acl_handle);
+        self.connections.push((acl_handle, bd_addr));
+    }
+
+    pub(crate) fn disconnect(&mut self, acl_handle: u16) {
+        self.connections.retain(|(handle, _)| *handle != acl_handle);
+    }
+
+    /// Returns the pairing record of `bd_addr`, creating it on first use.
+    fn pairing(&mut self, bd_addr: BdAddr) -> &mut SspPairing {
+        let index = match self.pairings.iter().position(|p| p.bd_addr == bd_addr) {
+            Some(index) => index,
+            None => {
+                self.pairings.push(SspPairing::new(bd_addr));
+                self.pairings.len() - 1
+            }
+        };
+        &mut self.pairings[index]
+    }
+
+    /// Returns the pairing record an IO capability exchange with `bd_addr`
+    /// belongs to. Either side may start the exchange, so a new record is
+    /// only started once the last attempt has completed.
+    fn pairing_exchange(&mut self, bd_addr: BdAddr) -> &mut SspPairing {
+        let pairing = self.pairing(bd_addr);
+        if pairing.completed {
+            *pairing = SspPairing::new(bd_addr);
+        }
+        pairing
+    }
+
+    pub(crate) fn pairing_summary(&self) -> String {
+        self.pairings
+            .iter()
+            .map(|pairing| pairing.summary())
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum AssociationModel {
+    NumericComparison,
+    JustWorks,
+    PasskeyEntry,
+    OutOfBand,
+}
+
+impl AssociationModel {
+    pub(crate) fn get_name(&self) -> &'static str {
+        match self {
+            AssociationModel::NumericComparison => "Numeric Comparison",
+            AssociationModel::JustWorks => "Just Works",
+            AssociationModel::PasskeyEntry => "Passkey Entry",
+            AssociationModel::OutOfBand => "Out of Band",
+        }
+    }
+}
+
+/// What has been seen of one Secure Simple Pairing exchange. Local values
+/// come from commands sent by the host, remote values from events.
+struct SspPairing {
+    bd_addr: BdAddr,
+
+    local_io_capability: Option<u8>,
+    local_oob_data_present: u8,
+    local_auth_requirements: u8,
+    remote_io_capability: Option<u8>,
+    remote_oob_data_present: u8,
+    remote_auth_requirements: u8,
+
+    model: Option<AssociationModel>,
+    numeric_value: Option<u32>,
+    key_type: Option<u8>,
+    status: Option<u8>,
+    // Simple_Pairing_Complete or Link_Key_Notification has been seen
+    completed: bool,
+}
+
+impl SspPairing {
+    fn new(bd_addr: BdAddr) -> Self {
+        SspPairing {
+            bd_addr,
+            local_io_capability: None,
+            local_oob_data_present: 0,
+            local_auth_requirements: 0,
+            remote_io_capability: None,
+            remote_oob_data_present: 0,
+            remote_auth_requirements: 0,
+            model: None,
+            numeric_value: None,
+            key_type: None,
+            status: None,
+            completed: false,
+        }
+    }
+
+    /// Association model picked from the IO capability mapping table of the
+    /// Core spec (Vol 3, Part C, 5.2.2.6).
+    fn io_capability_model(&self) -> AssociationModel {
+        if self.local_oob_data_present != 0 || self.remote_oob_data_present != 0 {
+            return AssociationModel::OutOfBand;
+        }
+        let mitm = (self.local_auth_requirements | self.remote_auth_requirements) & 0x01 != 0;
+        match (self.local_io_capability, self.remote_io_capability) {
+            (Some(local), Some(remote)) if mitm => match (local, remote) {
+                (0x03, _) | (_, 0x03) => AssociationModel::JustWorks,
+                (0x02, _) | (_, 0x02) => AssociationModel::PasskeyEntry,
+                (0x01, 0x01) => AssociationModel::NumericComparison,
+                _ => AssociationModel::JustWorks,
+            },
+            _ => AssociationModel::JustWorks,
+        }
+    }
+
+    fn is_authenticated(&self) -> Option<bool> {
+        match self.key_type? {
+            0x05 | 0x08 => Some(true),
+            0x03 | 0x04 | 0x07 => Some(false),
+            _ => None,
+        }
+    }
+
+    fn summary(&self) -> String {
+        let model_s = self
+            .model
+            .or_else(|| {
+                self.remote_io_capability
+                    .and(self.local_io_capability)
+                    .map(|_| self.io_capability_model())
+            })
+            .map(|model| model.get_name())
+            .unwrap_or("Unknown");
+        let key_type_s = self.key_type.map(get_key_type_name).unwrap_or("Unknown");
+        let authenticated_s = self
+            .is_authenticated()
+            .map(|authenticated| authenticated.to_string())
+            .unwrap_or(r#""Unknown""#.to_string());
+        let status_s = self.status.map(get_error_code_name).unwrap_or("Pending");
+        format!(
+            r#""{}": {{"Association Model": "{}", "Key Type": "{}", "Authenticated": {}, "Status": "{}"}}"#,
+            self.bd_addr, model_s, key_type_s, authenticated_s, status_s
+        )
+    }
+}
+
+impl Debug for SspPairing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SspPairing {{ bd_addr: {}, local_io_capability: {:?}, remote_io_capability: {:?}, model: {:?}, numeric_value: {:?}, key_type: {:?}, status: {:?}}}",
+            self.bd_addr,
+            self.local_io_capability.map(get_io_capability_name),
+            self.remote_io_capability.map(get_io_capability_name),
+            self.model.map(|model| model.get_name()),
+            self.numeric_value,
+            self.key_type.map(get_key_type_name),
+            self.status.map(get_error_code_name),
+        )
+    }
+}
+
+/// A BD_ADDR as carried on the wire, least significant byte first.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BdAddr([u8; 6]);
+
+impl BdAddr {
+    pub(crate) fn new(data: &[u8]) -> Self {
+        BdAddr(data[0..6].try_into().unwrap())
+    }
+}
+
+impl Display for BdAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let addr = self.0;
+        write!(
+            f,
+            "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
+            addr[5], addr[4], addr[3], addr[2], addr[1], addr[0]
+        )
+    }
+}
+
+impl ParseNodeFormat for BdAddr {
+    fn node_format(&self) -> String {
+        format!(r#""{}""#, self)
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum HciPacket {
     Undefined,
@@ -31,7 +255,7 @@ impl ParseNode for HciPacket {
             _ => HciPacket::Undefined,
         }
     }
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         let start_byte = start_byte + 1;
         match self {
             HciPacket::Cmd(pkg) => pkg
@@ -63,7 +287,7 @@ impl ParseNodeOpt for HciCmd {
         } else {
             let opcode = u16::from_le_bytes([data[0], data[1]]);
             let param_len = data[2];
-            let param = HciCmdParam::new(data, args, opcode);
+            let param = HciCmdParam::new(&data[3..], args, opcode);
             Some(HciCmd {
                 opcode,
                 param_len,
@@ -71,9 +295,9 @@ impl ParseNodeOpt for HciCmd {
             })
         }
     }
-    fn as_json(&self, start_byte: u8) -> String {
-        let ocf = opcode_to_ogf(self.opcode);
-        let ogf = opcode_to_ocf(self.opcode);
+    fn as_json(&self, start_byte: u16) -> String {
+        let ocf = opcode_to_ocf(self.opcode);
+        let ogf = opcode_to_ogf(self.opcode);
         let ocf_s = ParseBitsNode::new(start_byte, 2, 0, 10).format(
             "Opcode Command Field (OCF)",
             ocf,
@@ -92,7 +316,11 @@ impl ParseNodeOpt for HciCmd {
             "",
             "",
         );
-        format!(r#""Opcode": {{{}, {}}}, {}"#, ogf_s, ocf_s, param_len_s)
+        let param_s = self.param.as_json(start_byte + 3);
+        format!(
+            r#""Opcode": {{{}, {}}}, {}, {}"#,
+            ogf_s, ocf_s, param_len_s, param_s
+        )
     }
 }
 
@@ -105,7 +333,7 @@ enum HciCmdParam {
     InformationalParameters,
     StatusParameters,
     Testing,
-    LeController,
+    LeController(OgfLeController),
 }
 
 impl HciCmdParam {
@@ -117,7 +345,7 @@ impl HciCmdParam {
             HciCmdParam::InformationalParameters => "Informational Parameters",
             HciCmdParam::StatusParameters => "Status Parameters",
             HciCmdParam::Testing => "Testing",
-            HciCmdParam::LeController => "LE Controller",
+            HciCmdParam::LeController(_) => "LE Controller",
             HciCmdParam::Undefined => "Undefined",
         }
     }
@@ -126,6 +354,7 @@ impl HciCmdParam {
         match self {
             HciCmdParam::LinkControl(cmd) => cmd.get_ocf_name(),
             HciCmdParam::ControllerAndBaseband(cmd) => cmd.get_ocf_name(),
+            HciCmdParam::LeController(cmd) => cmd.get_ocf_name(),
             _ => "",
         }
     }
@@ -143,11 +372,11 @@ impl ParseNodeA<u16> for HciCmdParam {
             4 => HciCmdParam::InformationalParameters,
             5 => HciCmdParam::StatusParameters,
             6 => HciCmdParam::Testing,
-            8 => HciCmdParam::LeController,
+            8 => HciCmdParam::LeController(OgfLeController::new(data, args, opcode)),
             _ => HciCmdParam::Undefined,
         }
     }
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         let body = match self {
             HciCmdParam::LinkControl(cmd) => cmd.as_json(start_byte),
             HciCmdParam::LinkPolicy => "".to_string(),
@@ -155,7 +384,7 @@ impl ParseNodeA<u16> for HciCmdParam {
             HciCmdParam::InformationalParameters => "".to_string(),
             HciCmdParam::StatusParameters => "".to_string(),
             HciCmdParam::Testing => "".to_string(),
-            HciCmdParam::LeController => "".to_string(),
+            HciCmdParam::LeController(cmd) => cmd.as_json(start_byte),
             HciCmdParam::Undefined => "".to_string(),
         };
         format!(r#""HCI": {{{}}}"#, body)
@@ -166,12 +395,40 @@ impl ParseNodeA<u16> for HciCmdParam {
 enum OgfLinkControl {
     Undefined,
     Inquiry(Option<OcfInquiry>),
+    LinkKeyRequestReply(Option<OcfLinkKeyRequestReply>),
+    LinkKeyRequestNegativeReply(Option<OcfBdAddr>),
+    IoCapabilityRequestReply(Option<OcfIoCapabilityRequestReply>),
+    UserConfirmationRequestReply(Option<OcfBdAddr>),
+    UserConfirmationRequestNegativeReply(Option<OcfBdAddr>),
+    UserPasskeyRequestReply(Option<OcfUserPasskeyRequestReply>),
+    UserPasskeyRequestNegativeReply(Option<OcfBdAddr>),
+    RemoteOobDataRequestReply(Option<OcfRemoteOobDataRequestReply>),
+    RemoteOobDataRequestNegativeReply(Option<OcfBdAddr>),
+    IoCapabilityRequestNegativeReply(Option<OcfIoCapabilityRequestNegativeReply>),
 }
 
 impl OgfLinkControl {
     fn get_ocf_name(&self) -> &'static str {
         match self {
             OgfLinkControl::Inquiry(_) => "Inquiry",
+            OgfLinkControl::LinkKeyRequestReply(_) => "Link_Key_Request_Reply",
+            OgfLinkControl::LinkKeyRequestNegativeReply(_) => "Link_Key_Request_Negative_Reply",
+            OgfLinkControl::IoCapabilityRequestReply(_) => "IO_Capability_Request_Reply",
+            OgfLinkControl::UserConfirmationRequestReply(_) => "User_Confirmation_Request_Reply",
+            OgfLinkControl::UserConfirmationRequestNegativeReply(_) => {
+                "User_Confirmation_Request_Negative_Reply"
+            }
+            OgfLinkControl::UserPasskeyRequestReply(_) => "User_Passkey_Request_Reply",
+            OgfLinkControl::UserPasskeyRequestNegativeReply(_) => {
+                "User_Passkey_Request_Negative_Reply"
+            }
+            OgfLinkControl::RemoteOobDataRequestReply(_) => "Remote_OOB_Data_Request_Reply",
+            OgfLinkControl::RemoteOobDataRequestNegativeReply(_) => {
+                "Remote_OOB_Data_Request_Negative_Reply"
+            }
+            OgfLinkControl::IoCapabilityRequestNegativeReply(_) => {
+                "IO_Capability_Request_Negative_Reply"
+            }
             _ => "",
         }
     }
@@ -179,20 +436,60 @@ impl OgfLinkControl {
 
 impl ParseNodeA<u16> for OgfLinkControl {
     fn new(data: &[u8], args: Option<&mut HostStack>, opcode: u16) -> Self {
-        let ocf = opcode_to_ogf(opcode);
+        let ocf = opcode_to_ocf(opcode);
         match ocf {
-            1 => OgfLinkControl::Inquiry(OcfInquiry::new(data, args)),
+            0x0001 => OgfLinkControl::Inquiry(OcfInquiry::new(data, args)),
+            0x000b => OgfLinkControl::LinkKeyRequestReply(OcfLinkKeyRequestReply::new(data, args)),
+            0x000c => OgfLinkControl::LinkKeyRequestNegativeReply(OcfBdAddr::new(data, args)),
+            0x002b => OgfLinkControl::IoCapabilityRequestReply(OcfIoCapabilityRequestReply::new(
+                data, args,
+            )),
+            0x002c => OgfLinkControl::UserConfirmationRequestReply(OcfBdAddr::new(data, args)),
+            0x002d => {
+                OgfLinkControl::UserConfirmationRequestNegativeReply(OcfBdAddr::new(data, args))
+            }
+            0x002e => {
+                OgfLinkControl::UserPasskeyRequestReply(OcfUserPasskeyRequestReply::new(data, args))
+            }
+            0x002f => OgfLinkControl::UserPasskeyRequestNegativeReply(OcfBdAddr::new(data, args)),
+            0x0030 => OgfLinkControl::RemoteOobDataRequestReply(OcfRemoteOobDataRequestReply::new(
+                data, args,
+            )),
+            0x0033 => OgfLinkControl::RemoteOobDataRequestNegativeReply(OcfBdAddr::new(data, args)),
+            0x0034 => OgfLinkControl::IoCapabilityRequestNegativeReply(
+                OcfIoCapabilityRequestNegativeReply::new(data, args),
+            ),
             _ => OgfLinkControl::Undefined,
         }
     }
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         match self {
-            OgfLinkControl::Inquiry(cmd) => cmd
-                .is_some()
-                .then(|| cmd.as_ref().unwrap().as_json(start_byte)),
+            OgfLinkControl::Inquiry(cmd) => cmd.as_ref().map(|cmd| cmd.as_json(start_byte)),
+            OgfLinkControl::LinkKeyRequestReply(cmd) => {
+                cmd.as_ref().map(|cmd| cmd.as_json(start_byte))
+            }
+            OgfLinkControl::IoCapabilityRequestReply(cmd) => {
+                cmd.as_ref().map(|cmd| cmd.as_json(start_byte))
+            }
+            OgfLinkControl::UserPasskeyRequestReply(cmd) => {
+                cmd.as_ref().map(|cmd| cmd.as_json(start_byte))
+            }
+            OgfLinkControl::RemoteOobDataRequestReply(cmd) => {
+                cmd.as_ref().map(|cmd| cmd.as_json(start_byte))
+            }
+            OgfLinkControl::IoCapabilityRequestNegativeReply(cmd) => {
+                cmd.as_ref().map(|cmd| cmd.as_json(start_byte))
+            }
+            OgfLinkControl::LinkKeyRequestNegativeReply(cmd)
+            | OgfLinkControl::UserConfirmationRequestReply(cmd)
+            | OgfLinkControl::UserConfirmationRequestNegativeReply(cmd)
+            | OgfLinkControl::UserPasskeyRequestNegativeReply(cmd)
+            | OgfLinkControl::RemoteOobDataRequestNegativeReply(cmd) => {
+                cmd.as_ref().map(|cmd| cmd.as_json(start_byte))
+            }
             _ => None,
         }
-        .unwrap_or("".to_string())
+        .unwrap_or_default()
     }
 }
 
@@ -218,7 +515,7 @@ impl ParseNodeOpt for OcfInquiry {
             })
         }
     }
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         let lap_s = ParseBytesNode::new(start_byte, 3).format("LAP", self.lap, "", "");
         let inquiry_len_s = ParseBytesNode::new(start_byte + 3, 1).format(
             "Inquiry Length",
@@ -236,16 +533,204 @@ impl ParseNodeOpt for OcfInquiry {
     }
 }
 
+/// Command parameters made of a single BD_ADDR, shared by the negative
+/// replies and the user confirmation reply.
+#[derive(Debug, PartialEq)]
+struct OcfBdAddr {
+    bd_addr: BdAddr,
+}
+
+impl ParseNodeOpt for OcfBdAddr {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 6 {
+            None
+        } else {
+            Some(OcfBdAddr {
+                bd_addr: BdAddr::new(data),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        ParseBytesNode::new(start_byte, 6).format("BD_ADDR", self.bd_addr, "", "")
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct OcfLinkKeyRequestReply {
+    bd_addr: BdAddr,
+    link_key: [u8; 16],
+}
+
+impl ParseNodeOpt for OcfLinkKeyRequestReply {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 22 {
+            None
+        } else {
+            Some(OcfLinkKeyRequestReply {
+                bd_addr: BdAddr::new(data),
+                link_key: data[6..22].try_into().unwrap(),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let bd_addr_s = ParseBytesNode::new(start_byte, 6).format("BD_ADDR", self.bd_addr, "", "");
+        let link_key_s =
+            ParseBytesNode::new(start_byte + 6, 16).format("Link_Key", &self.link_key[..], "", "");
+        format!("{}, {}", bd_addr_s, link_key_s)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct OcfIoCapabilityRequestReply {
+    bd_addr: BdAddr,
+    io_capability: u8,
+    oob_data_present: u8,
+    auth_requirements: u8,
+}
+
+impl ParseNodeOpt for OcfIoCapabilityRequestReply {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 9 {
+            None
+        } else {
+            let bd_addr = BdAddr::new(data);
+            let io_capability = data[6];
+            let oob_data_present = data[7];
+            let auth_requirements = data[8];
+
+            if let Some(args) = args {
+                let pairing = args.hci_arg.pairing_exchange(bd_addr);
+                pairing.local_io_capability = Some(io_capability);
+                pairing.local_oob_data_present = oob_data_present;
+                pairing.local_auth_requirements = auth_requirements;
+            }
+
+            Some(OcfIoCapabilityRequestReply {
+                bd_addr,
+                io_capability,
+                oob_data_present,
+                auth_requirements,
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        io_capability_as_json(
+            start_byte,
+            self.bd_addr,
+            self.io_capability,
+            self.oob_data_present,
+            self.auth_requirements,
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct OcfUserPasskeyRequestReply {
+    bd_addr: BdAddr,
+    numeric_value: u32,
+}
+
+impl ParseNodeOpt for OcfUserPasskeyRequestReply {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 10 {
+            None
+        } else {
+            Some(OcfUserPasskeyRequestReply {
+                bd_addr: BdAddr::new(data),
+                numeric_value: u32::from_le_bytes(data[6..10].try_into().unwrap()),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let bd_addr_s = ParseBytesNode::new(start_byte, 6).format("BD_ADDR", self.bd_addr, "", "");
+        let numeric_value_s = ParseBytesNode::new(start_byte + 6, 4).format(
+            "Numeric_Value",
+            self.numeric_value,
+            &format!("{:06}", self.numeric_value),
+            "",
+        );
+        format!("{}, {}", bd_addr_s, numeric_value_s)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct OcfRemoteOobDataRequestReply {
+    bd_addr: BdAddr,
+    c: [u8; 16],
+    r: [u8; 16],
+}
+
+impl ParseNodeOpt for OcfRemoteOobDataRequestReply {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 38 {
+            None
+        } else {
+            Some(OcfRemoteOobDataRequestReply {
+                bd_addr: BdAddr::new(data),
+                c: data[6..22].try_into().unwrap(),
+                r: data[22..38].try_into().unwrap(),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let bd_addr_s = ParseBytesNode::new(start_byte, 6).format("BD_ADDR", self.bd_addr, "", "");
+        let c_s = ParseBytesNode::new(start_byte + 6, 16).format("C", &self.c[..], "", "");
+        let r_s = ParseBytesNode::new(start_byte + 22, 16).format("R", &self.r[..], "", "");
+        format!("{}, {}, {}", bd_addr_s, c_s, r_s)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct OcfIoCapabilityRequestNegativeReply {
+    bd_addr: BdAddr,
+    reason: u8,
+}
+
+impl ParseNodeOpt for OcfIoCapabilityRequestNegativeReply {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 7 {
+            None
+        } else {
+            let bd_addr = BdAddr::new(data);
+            let reason = data[6];
+
+            if let Some(args) = args {
+                args.hci_arg.pairing(bd_addr).status = Some(reason);
+            }
+
+            Some(OcfIoCapabilityRequestNegativeReply { bd_addr, reason })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let bd_addr_s = ParseBytesNode::new(start_byte, 6).format("BD_ADDR", self.bd_addr, "", "");
+        let reason_s = ParseBytesNode::new(start_byte + 6, 1).format(
+            "Reason",
+            self.reason,
+            get_error_code_name(self.reason),
+            "",
+        );
+        format!("{}, {}", bd_addr_s, reason_s)
+    }
+}
+
 #[derive(Debug, PartialEq)]
 enum OgfControllerAndBaseband {
     Undefined,
     Reset(Option<OcfReset>),
+    WriteSimplePairingMode(Option<OcfWriteSimplePairingMode>),
+    SendKeypressNotification(Option<KeypressNotification>),
+    WriteExtendedInquiryResponse(Option<OcfWriteExtendedInquiryResponse>),
 }
 
 impl OgfControllerAndBaseband {
     fn get_ocf_name(&self) -> &'static str {
         match self {
             OgfControllerAndBaseband::Reset(_) => "Reset",
+            OgfControllerAndBaseband::WriteSimplePairingMode(_) => "Write_Simple_Pairing_Mode",
+            OgfControllerAndBaseband::SendKeypressNotification(_) => "Send_Keypress_Notification",
+            OgfControllerAndBaseband::WriteExtendedInquiryResponse(_) => {
+                "Write_Extended_Inquiry_Response"
+            }
             _ => "",
         }
     }
@@ -253,17 +738,35 @@ impl OgfControllerAndBaseband {
 
 impl ParseNodeA<u16> for OgfControllerAndBaseband {
     fn new(data: &[u8], args: Option<&mut HostStack>, opcode: u16) -> Self {
-        let ocf = opcode_to_ogf(opcode);
+        let ocf = opcode_to_ocf(opcode);
         match ocf {
-            3 => OgfControllerAndBaseband::Reset(OcfReset::new(data, args)),
+            0x0003 => OgfControllerAndBaseband::Reset(OcfReset::new(data, args)),
+            0x0052 => OgfControllerAndBaseband::WriteExtendedInquiryResponse(
+                OcfWriteExtendedInquiryResponse::new(data, args),
+            ),
+            0x0056 => OgfControllerAndBaseband::WriteSimplePairingMode(
+                OcfWriteSimplePairingMode::new(data, args),
+            ),
+            0x0060 => OgfControllerAndBaseband::SendKeypressNotification(
+                KeypressNotification::new(data, args),
+            ),
             _ => OgfControllerAndBaseband::Undefined,
         }
     }
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         match self {
             OgfControllerAndBaseband::Reset(cmd) => cmd
                 .is_some()
                 .then(|| cmd.as_ref().unwrap().as_json(start_byte)),
+            OgfControllerAndBaseband::WriteSimplePairingMode(cmd) => {
+                cmd.as_ref().map(|cmd| cmd.as_json(start_byte))
+            }
+            OgfControllerAndBaseband::SendKeypressNotification(cmd) => {
+                cmd.as_ref().map(|cmd| cmd.as_json(start_byte))
+            }
+            OgfControllerAndBaseband::WriteExtendedInquiryResponse(cmd) => {
+                cmd.as_ref().map(|cmd| cmd.as_json(start_byte))
+            }
             _ => None,
         }
         .unwrap_or("".to_string())
@@ -277,11 +780,235 @@ impl ParseNodeOpt for OcfReset {
     fn new(_data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
         Some(OcfReset {})
     }
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         ParseBytesNode::new(start_byte, 0).format("Reset", "", "", "")
     }
 }
 
+#[derive(Debug, PartialEq)]
+struct OcfWriteSimplePairingMode {
+    simple_pairing_mode: u8,
+}
+
+impl ParseNodeOpt for OcfWriteSimplePairingMode {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.is_empty() {
+            None
+        } else {
+            Some(OcfWriteSimplePairingMode {
+                simple_pairing_mode: data[0],
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let mode_name_s = match self.simple_pairing_mode {
+            0x00 => "Simple Pairing mode disabled",
+            0x01 => "Simple pairing enabled",
+            _ => "Reserved for future use",
+        };
+        ParseBytesNode::new(start_byte, 1).format(
+            "Simple_Pairing_Mode",
+            self.simple_pairing_mode,
+            mode_name_s,
+            "",
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct OcfWriteExtendedInquiryResponse {
+    fec_required: u8,
+    extended_inquiry_response: AdvertisingData,
+}
+
+impl ParseNodeOpt for OcfWriteExtendedInquiryResponse {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.is_empty() {
+            None
+        } else {
+            Some(OcfWriteExtendedInquiryResponse {
+                fec_required: data[0],
+                extended_inquiry_response: AdvertisingData::new(&data[1..], args),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let fec_required_name_s = match self.fec_required {
+            0x00 => "FEC is not required",
+            0x01 => "FEC is required",
+            _ => "Reserved for future use",
+        };
+        let fec_required_s = ParseBytesNode::new(start_byte, 1).format(
+            "FEC_Required",
+            self.fec_required,
+            fec_required_name_s,
+            "",
+        );
+        format!(
+            r#"{}, "Extended_Inquiry_Response": {{{}}}"#,
+            fec_required_s,
+            self.extended_inquiry_response.as_json(start_byte + 1)
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum OgfLeController {
+    Undefined,
+    SetAdvertisingData(Option<OcfLeSetAdvertisingData>),
+    SetScanResponseData(Option<OcfLeSetAdvertisingData>),
+    SetExtendedAdvertisingData(Option<OcfLeSetExtendedAdvertisingData>),
+    SetExtendedScanResponseData(Option<OcfLeSetExtendedAdvertisingData>),
+}
+
+impl OgfLeController {
+    fn get_ocf_name(&self) -> &'static str {
+        match self {
+            OgfLeController::SetAdvertisingData(_) => "LE_Set_Advertising_Data",
+            OgfLeController::SetScanResponseData(_) => "LE_Set_Scan_Response_Data",
+            OgfLeController::SetExtendedAdvertisingData(_) => "LE_Set_Extended_Advertising_Data",
+            OgfLeController::SetExtendedScanResponseData(_) => "LE_Set_Extended_Scan_Response_Data",
+            _ => "",
+        }
+    }
+}
+
+impl ParseNodeA<u16> for OgfLeController {
+    fn new(data: &[u8], args: Option<&mut HostStack>, opcode: u16) -> Self {
+        let ocf = opcode_to_ocf(opcode);
+        match ocf {
+            0x0008 => OgfLeController::SetAdvertisingData(OcfLeSetAdvertisingData::new(data, args)),
+            0x0009 => {
+                OgfLeController::SetScanResponseData(OcfLeSetAdvertisingData::new(data, args))
+            }
+            0x0037 => OgfLeController::SetExtendedAdvertisingData(
+                OcfLeSetExtendedAdvertisingData::new(data, args),
+            ),
+            0x0038 => OgfLeController::SetExtendedScanResponseData(
+                OcfLeSetExtendedAdvertisingData::new(data, args),
+            ),
+            _ => OgfLeController::Undefined,
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        match self {
+            OgfLeController::SetAdvertisingData(cmd)
+            | OgfLeController::SetScanResponseData(cmd) => {
+                cmd.as_ref().map(|cmd| cmd.as_json(start_byte))
+            }
+            OgfLeController::SetExtendedAdvertisingData(cmd)
+            | OgfLeController::SetExtendedScanResponseData(cmd) => {
+                cmd.as_ref().map(|cmd| cmd.as_json(start_byte))
+            }
+            _ => None,
+        }
+        .unwrap_or_default()
+    }
+}
+
+/// Parameters of LE_Set_Advertising_Data and LE_Set_Scan_Response_Data.
+#[derive(Debug, PartialEq)]
+struct OcfLeSetAdvertisingData {
+    data_len: u8,
+    data: AdvertisingData,
+}
+
+impl ParseNodeOpt for OcfLeSetAdvertisingData {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.is_empty() {
+            None
+        } else {
+            let data_len = data[0];
+            let end = (1 + data_len as usize).min(data.len());
+            Some(OcfLeSetAdvertisingData {
+                data_len,
+                data: AdvertisingData::new(&data[1..end], args),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let data_len_s =
+            ParseBytesNode::new(start_byte, 1).format("Data_Length", self.data_len, "", "");
+        format!(
+            r#"{}, "Data": {{{}}}"#,
+            data_len_s,
+            self.data.as_json(start_byte + 1)
+        )
+    }
+}
+
+/// Parameters of LE_Set_Extended_Advertising_Data and
+/// LE_Set_Extended_Scan_Response_Data.
+#[derive(Debug, PartialEq)]
+struct OcfLeSetExtendedAdvertisingData {
+    advertising_handle: u8,
+    operation: u8,
+    fragment_preference: u8,
+    data_len: u8,
+    data: AdvertisingData,
+}
+
+impl ParseNodeOpt for OcfLeSetExtendedAdvertisingData {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 4 {
+            None
+        } else {
+            let data_len = data[3];
+            let end = (4 + data_len as usize).min(data.len());
+            Some(OcfLeSetExtendedAdvertisingData {
+                advertising_handle: data[0],
+                operation: data[1],
+                fragment_preference: data[2],
+                data_len,
+                data: AdvertisingData::new(&data[4..end], args),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let operation_name_s = match self.operation {
+            0x00 => "Intermediate fragment of fragmented extended advertising data",
+            0x01 => "First fragment of fragmented extended advertising data",
+            0x02 => "Last fragment of fragmented extended advertising data",
+            0x03 => "Complete extended advertising data",
+            0x04 => "Unchanged data (just update the Advertising DID)",
+            _ => "Reserved for future use",
+        };
+        let fragment_preference_name_s = match self.fragment_preference {
+            0x00 => "The Controller may fragment all Host advertising data",
+            0x01 => "The Controller should not fragment or should minimize fragmentation of Host advertising data",
+            _ => "Reserved for future use",
+        };
+        let advertising_handle_s = ParseBytesNode::new(start_byte, 1).format(
+            "Advertising_Handle",
+            self.advertising_handle,
+            "",
+            "",
+        );
+        let operation_s = ParseBytesNode::new(start_byte + 1, 1).format(
+            "Operation",
+            self.operation,
+            operation_name_s,
+            "",
+        );
+        let fragment_preference_s = ParseBytesNode::new(start_byte + 2, 1).format(
+            "Fragment_Preference",
+            self.fragment_preference,
+            fragment_preference_name_s,
+            "",
+        );
+        let data_len_s =
+            ParseBytesNode::new(start_byte + 3, 1).format("Data_Length", self.data_len, "", "");
+        format!(
+            r#"{}, {}, {}, {}, "Data": {{{}}}"#,
+            advertising_handle_s,
+            operation_s,
+            fragment_preference_s,
+            data_len_s,
+            self.data.as_json(start_byte + 4)
+        )
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct HciAcl {
     handle: u16,
@@ -297,6 +1024,10 @@ impl ParseNodeOpt for HciAcl {
             None
         } else {
             let handle = u16::from_le_bytes(data[0..2].try_into().unwrap());
+            let mut args = args;
+            if let Some(args) = args.as_deref_mut() {
+                args.hci_arg.acl_handle = handle & 0xfff;
+            }
             Some(HciAcl {
                 handle: handle & 0xfff,
                 pb_flag: ((handle >> 12) & 0x3) as u8,
@@ -307,7 +1038,7 @@ impl ParseNodeOpt for HciAcl {
         }
     }
 
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         let handle_s =
             ParseBitsNode::new(start_byte, 2, 0, 12).format("Handle", self.handle, "", "");
         let pb_flag_s =
@@ -322,9 +1053,9 @@ impl ParseNodeOpt for HciAcl {
         );
         let data_s = self
             .data
-            .is_some()
-            .then(|| self.data.as_ref().unwrap().as_json(start_byte + 4))
-            .unwrap_or("".to_string());
+            .as_ref()
+            .map(|data| data.as_json(start_byte + 4))
+            .unwrap_or_default();
         format!(
             r#""ACL": {{{}, {}, {}, {}, {}}}"#,
             handle_s, pb_flag_s, bc_flag_s, data_len_s, data_s
@@ -353,14 +1084,13 @@ impl ParseNodeOpt for HciEvt {
             })
         }
     }
-    fn as_json(&self, start_byte: u8) -> String {
-        let code_name_s = match self.code {
-            0x0e => "HCI_Command_Complete",
-            _ => "Unknown",
-        };
-
-        let code_s =
-            ParseBytesNode::new(start_byte, 1).format("Event Code", self.code, code_name_s, "");
+    fn as_json(&self, start_byte: u16) -> String {
+        let code_s = ParseBytesNode::new(start_byte, 1).format(
+            "Event Code",
+            self.code,
+            get_evt_name(self.code),
+            "",
+        );
         let len_s = ParseBytesNode::new(start_byte + 1, 1).format(
             "Parameter Total Length",
             self.len,
@@ -369,9 +1099,9 @@ impl ParseNodeOpt for HciEvt {
         );
         let param_s = self
             .param
-            .is_some()
-            .then(|| self.param.as_ref().unwrap().as_json(start_byte + 2))
-            .unwrap_or("".to_string());
+            .as_ref()
+            .map(|param| param.as_json(start_byte + 2))
+            .unwrap_or_default();
         format!(r#""EVT": {{{}, {}, {}}}"#, code_s, len_s, param_s)
     }
 }
@@ -379,22 +1109,93 @@ impl ParseNodeOpt for HciEvt {
 #[derive(Debug, PartialEq)]
 enum HciEvtParam {
     Undefined,
+    ConnectionComplete(Option<EvtConnectionComplete>),
+    DisconnectionComplete(Option<EvtDisconnectionComplete>),
+    ReadRemoteVersionInformationComplete(Option<EvtReadRemoteVersionInformationComplete>),
     CommandComplete(Option<EvtCommandComplete>),
+    LinkKeyRequest(Option<EvtBdAddr>),
+    ExtendedInquiryResult(Option<EvtExtendedInquiryResult>),
+    LinkKeyNotification(Option<EvtLinkKeyNotification>),
+    IoCapabilityRequest(Option<EvtBdAddr>),
+    IoCapabilityResponse(Option<EvtIoCapabilityResponse>),
+    UserConfirmationRequest(Option<EvtUserConfirmationRequest>),
+    UserPasskeyRequest(Option<EvtBdAddr>),
+    RemoteOobDataRequest(Option<EvtBdAddr>),
+    SimplePairingComplete(Option<EvtSimplePairingComplete>),
+    UserPasskeyNotification(Option<EvtUserPasskeyNotification>),
+    KeypressNotification(Option<KeypressNotification>),
+    LeMeta(Option<EvtLeMeta>),
 }
 
 impl ParseNodeOptA<u8> for HciEvtParam {
     fn new(data: &[u8], args: Option<&mut HostStack>, code: u8) -> Option<Self> {
         let ret = match code {
+            0x03 => HciEvtParam::ConnectionComplete(EvtConnectionComplete::new(data, args)),
+            0x05 => HciEvtParam::DisconnectionComplete(EvtDisconnectionComplete::new(data, args)),
+            0x0c => HciEvtParam::ReadRemoteVersionInformationComplete(
+                EvtReadRemoteVersionInformationComplete::new(data, args),
+            ),
             0x0e => HciEvtParam::CommandComplete(EvtCommandComplete::new(data, args)),
+            0x17 => HciEvtParam::LinkKeyRequest(EvtBdAddr::new(data, args, code)),
+            0x18 => HciEvtParam::LinkKeyNotification(EvtLinkKeyNotification::new(data, args)),
+            0x2f => HciEvtParam::ExtendedInquiryResult(EvtExtendedInquiryResult::new(data, args)),
+            0x31 => HciEvtParam::IoCapabilityRequest(EvtBdAddr::new(data, args, code)),
+            0x32 => HciEvtParam::IoCapabilityResponse(EvtIoCapabilityResponse::new(data, args)),
+            0x33 => {
+                HciEvtParam::UserConfirmationRequest(EvtUserConfirmationRequest::new(data, args))
+            }
+            0x34 => HciEvtParam::UserPasskeyRequest(EvtBdAddr::new(data, args, code)),
+            0x35 => HciEvtParam::RemoteOobDataRequest(EvtBdAddr::new(data, args, code)),
+            0x36 => HciEvtParam::SimplePairingComplete(EvtSimplePairingComplete::new(data, args)),
+            0x3b => {
+                HciEvtParam::UserPasskeyNotification(EvtUserPasskeyNotification::new(data, args))
+            }
+            0x3c => HciEvtParam::KeypressNotification(KeypressNotification::new(data, args)),
+            0x3e => HciEvtParam::LeMeta(EvtLeMeta::new(data, args)),
             _ => HciEvtParam::Undefined,
         };
         Some(ret)
     }
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         match self {
+            HciEvtParam::ConnectionComplete(evt) => evt.as_ref().map(|evt| evt.as_json(start_byte)),
+            HciEvtParam::DisconnectionComplete(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
+            HciEvtParam::ReadRemoteVersionInformationComplete(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
             HciEvtParam::CommandComplete(evt) => evt
                 .is_some()
                 .then(|| evt.as_ref().unwrap().as_json(start_byte)),
+            HciEvtParam::LinkKeyRequest(evt)
+            | HciEvtParam::IoCapabilityRequest(evt)
+            | HciEvtParam::UserPasskeyRequest(evt)
+            | HciEvtParam::RemoteOobDataRequest(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
+            HciEvtParam::LinkKeyNotification(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
+            HciEvtParam::ExtendedInquiryResult(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
+            HciEvtParam::IoCapabilityResponse(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
+            HciEvtParam::UserConfirmationRequest(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
+            HciEvtParam::SimplePairingComplete(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
+            HciEvtParam::UserPasskeyNotification(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
+            HciEvtParam::KeypressNotification(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
+            HciEvtParam::LeMeta(evt) => evt.as_ref().map(|evt| evt.as_json(start_byte)),
             _ => None,
         }
         .unwrap_or("".to_string())
@@ -419,7 +1220,7 @@ impl ParseNodeOpt for EvtCommandComplete {
             })
         }
     }
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         let mut data = Vec::from(self.command_opcode.to_le_bytes());
         data.push(3);
         let cmd = HciCmd::new(&data, None);
@@ -434,16 +1235,16 @@ impl ParseNodeOpt for EvtCommandComplete {
         let opcode_ogf_s = ParseBytesNode::new(start_byte + 2, 1).format(
             "Opcode Group Field (OGF)",
             opcode_to_ogf(self.command_opcode),
-            cmd.is_some()
-                .then(|| cmd.as_ref().unwrap().param.get_ogf_name())
+            cmd.as_ref()
+                .map(|cmd| cmd.param.get_ogf_name())
                 .unwrap_or(""),
             "",
         );
         let opcode_ocf_s = ParseBytesNode::new(start_byte + 1, 2).format(
             "Opcode Command Field (OCF)",
             opcode_to_ocf(self.command_opcode),
-            cmd.is_some()
-                .then(|| cmd.as_ref().unwrap().param.get_ocf_name())
+            cmd.as_ref()
+                .map(|cmd| cmd.param.get_ocf_name())
                 .unwrap_or(""),
             "",
         );
@@ -454,6 +1255,588 @@ impl ParseNodeOpt for EvtCommandComplete {
     }
 }
 
+/// Event parameters made of a single BD_ADDR. The event code tells which
+/// step of the Secure Simple Pairing exchange has been reached.
+#[derive(Debug, PartialEq)]
+struct EvtBdAddr {
+    bd_addr: BdAddr,
+}
+
+impl ParseNodeOptA<u8> for EvtBdAddr {
+    fn new(data: &[u8], args: Option<&mut HostStack>, code: u8) -> Option<Self> {
+        if data.len() < 6 {
+            None
+        } else {
+            let bd_addr = BdAddr::new(data);
+
+            if let Some(args) = args {
+                match code {
+                    // a new pairing attempt starts with the IO capability exchange
+                    0x31 => {
+                        args.hci_arg.pairing_exchange(bd_addr);
+                    }
+                    0x34 => {
+                        args.hci_arg.pairing(bd_addr).model = Some(AssociationModel::PasskeyEntry)
+                    }
+                    0x35 => args.hci_arg.pairing(bd_addr).model = Some(AssociationModel::OutOfBand),
+                    _ => (),
+                }
+            }
+
+            Some(EvtBdAddr { bd_addr })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        ParseBytesNode::new(start_byte, 6).format("BD_ADDR", self.bd_addr, "", "")
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct EvtLinkKeyNotification {
+    bd_addr: BdAddr,
+    link_key: [u8; 16],
+    key_type: u8,
+}
+
+impl ParseNodeOpt for EvtLinkKeyNotification {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 23 {
+            None
+        } else {
+            let bd_addr = BdAddr::new(data);
+            let key_type = data[22];
+
+            if let Some(args) = args {
+                let pairing = args.hci_arg.pairing(bd_addr);
+                pairing.key_type = Some(key_type);
+                pairing.completed = true;
+            }
+
+            Some(EvtLinkKeyNotification {
+                bd_addr,
+                link_key: data[6..22].try_into().unwrap(),
+                key_type,
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let bd_addr_s = ParseBytesNode::new(start_byte, 6).format("BD_ADDR", self.bd_addr, "", "");
+        let link_key_s =
+            ParseBytesNode::new(start_byte + 6, 16).format("Link_Key", &self.link_key[..], "", "");
+        let key_type_s = ParseBytesNode::new(start_byte + 22, 1).format(
+            "Key_Type",
+            self.key_type,
+            get_key_type_name(self.key_type),
+            "",
+        );
+        format!("{}, {}, {}", bd_addr_s, link_key_s, key_type_s)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct EvtIoCapabilityResponse {
+    bd_addr: BdAddr,
+    io_capability: u8,
+    oob_data_present: u8,
+    auth_requirements: u8,
+}
+
+impl ParseNodeOpt for EvtIoCapabilityResponse {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 9 {
+            None
+        } else {
+            let bd_addr = BdAddr::new(data);
+            let io_capability = data[6];
+            let oob_data_present = data[7];
+            let auth_requirements = data[8];
+
+            if let Some(args) = args {
+                let pairing = args.hci_arg.pairing_exchange(bd_addr);
+                pairing.remote_io_capability = Some(io_capability);
+                pairing.remote_oob_data_present = oob_data_present;
+                pairing.remote_auth_requirements = auth_requirements;
+            }
+
+            Some(EvtIoCapabilityResponse {
+                bd_addr,
+                io_capability,
+                oob_data_present,
+                auth_requirements,
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        io_capability_as_json(
+            start_byte,
+            self.bd_addr,
+            self.io_capability,
+            self.oob_data_present,
+            self.auth_requirements,
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct EvtUserConfirmationRequest {
+    bd_addr: BdAddr,
+    numeric_value: u32,
+}
+
+impl ParseNodeOpt for EvtUserConfirmationRequest {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 10 {
+            None
+        } else {
+            let bd_addr = BdAddr::new(data);
+            let numeric_value = u32::from_le_bytes(data[6..10].try_into().unwrap());
+
+            if let Some(args) = args {
+                let pairing = args.hci_arg.pairing(bd_addr);
+                // Just Works is signalled with the same event, only the IO
+                // capabilities tell it apart from Numeric Comparison
+                pairing.model = Some(pairing.io_capability_model());
+                pairing.numeric_value = Some(numeric_value);
+            }
+
+            Some(EvtUserConfirmationRequest {
+                bd_addr,
+                numeric_value,
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let bd_addr_s = ParseBytesNode::new(start_byte, 6).format("BD_ADDR", self.bd_addr, "", "");
+        let numeric_value_s = ParseBytesNode::new(start_byte + 6, 4).format(
+            "Numeric_Value",
+            self.numeric_value,
+            &format!("{:06}", self.numeric_value),
+            "",
+        );
+        format!("{}, {}", bd_addr_s, numeric_value_s)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct EvtConnectionComplete {
+    status: u8,
+    connection_handle: u16,
+    bd_addr: BdAddr,
+    link_type: u8,
+    encryption_enabled: u8,
+}
+
+impl ParseNodeOpt for EvtConnectionComplete {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 11 {
+            None
+        } else {
+            let status = data[0];
+            let connection_handle = u16::from_le_bytes([data[1], data[2]]);
+            let bd_addr = BdAddr::new(&data[3..]);
+
+            if let Some(args) = args {
+                if status == 0x00 {
+                    args.hci_arg.connect(connection_handle & 0xfff, bd_addr);
+                }
+            }
+
+            Some(EvtConnectionComplete {
+                status,
+                connection_handle,
+                bd_addr,
+                link_type: data[9],
+                encryption_enabled: data[10],
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let status_s = ParseBytesNode::new(start_byte, 1).format(
+            "Status",
+            self.status,
+            get_error_code_name(self.status),
+            "",
+        );
+        let connection_handle_s = ParseBytesNode::new(start_byte + 1, 2).format(
+            "Connection_Handle",
+            self.connection_handle,
+            "",
+            "",
+        );
+        let bd_addr_s =
+            ParseBytesNode::new(start_byte + 3, 6).format("BD_ADDR", self.bd_addr, "", "");
+        let link_type_name_s = match self.link_type {
+            0x00 => "SCO connection",
+            0x01 => "ACL connection",
+            _ => "Reserved for future use",
+        };
+        let link_type_s = ParseBytesNode::new(start_byte + 9, 1).format(
+            "Link_Type",
+            self.link_type,
+            link_type_name_s,
+            "",
+        );
+        let encryption_enabled_name_s = match self.encryption_enabled {
+            0x00 => "Link level encryption disabled",
+            0x01 => "Link level encryption enabled",
+            _ => "Reserved for future use",
+        };
+        let encryption_enabled_s = ParseBytesNode::new(start_byte + 10, 1).format(
+            "Encryption_Enabled",
+            self.encryption_enabled,
+            encryption_enabled_name_s,
+            "",
+        );
+        format!(
+            "{}, {}, {}, {}, {}",
+            status_s, connection_handle_s, bd_addr_s, link_type_s, encryption_enabled_s
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct EvtDisconnectionComplete {
+    status: u8,
+    connection_handle: u16,
+    reason: u8,
+}
+
+impl ParseNodeOpt for EvtDisconnectionComplete {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 4 {
+            None
+        } else {
+            let status = data[0];
+            let connection_handle = u16::from_le_bytes([data[1], data[2]]);
+
+            // the handle may be given to the next connection, which must
+            // not inherit anything of this one
+            if let Some(args) = args {
+                if status == 0x00 {
+                    args.disconnect(connection_handle & 0xfff);
+                }
+            }
+
+            Some(EvtDisconnectionComplete {
+                status,
+                connection_handle,
+                reason: data[3],
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let status_s = ParseBytesNode::new(start_byte, 1).format(
+            "Status",
+            self.status,
+            get_error_code_name(self.status),
+            "",
+        );
+        let connection_handle_s = ParseBytesNode::new(start_byte + 1, 2).format(
+            "Connection_Handle",
+            self.connection_handle,
+            "",
+            "",
+        );
+        let reason_s = ParseBytesNode::new(start_byte + 3, 1).format(
+            "Reason",
+            self.reason,
+            get_error_code_name(self.reason),
+            "",
+        );
+        format!("{}, {}, {}", status_s, connection_handle_s, reason_s)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct EvtReadRemoteVersionInformationComplete {
+    status: u8,
+    connection_handle: u16,
+    version: u8,
+    company_identifier: u16,
+    subversion: u16,
+}
+
+impl ParseNodeOpt for EvtReadRemoteVersionInformationComplete {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 8 {
+            None
+        } else {
+            Some(EvtReadRemoteVersionInformationComplete {
+                status: data[0],
+                connection_handle: u16::from_le_bytes([data[1], data[2]]),
+                version: data[3],
+                company_identifier: u16::from_le_bytes([data[4], data[5]]),
+                subversion: u16::from_le_bytes([data[6], data[7]]),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let status_s = ParseBytesNode::new(start_byte, 1).format(
+            "Status",
+            self.status,
+            get_error_code_name(self.status),
+            "",
+        );
+        let connection_handle_s = ParseBytesNode::new(start_byte + 1, 2).format(
+            "Connection_Handle",
+            self.connection_handle,
+            "",
+            "",
+        );
+        let version_s = ParseBytesNode::new(start_byte + 3, 1).format(
+            "Version",
+            self.version,
+            get_version_name(self.version),
+            "",
+        );
+        let company_identifier_s = ParseBytesNode::new(start_byte + 4, 2).format(
+            "Company_Identifier",
+            self.company_identifier,
+            get_company_name(self.company_identifier),
+            "",
+        );
+        let subversion_s =
+            ParseBytesNode::new(start_byte + 6, 2).format("Subversion", self.subversion, "", "");
+        format!(
+            "{}, {}, {}, {}, {}",
+            status_s, connection_handle_s, version_s, company_identifier_s, subversion_s
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct EvtSimplePairingComplete {
+    status: u8,
+    bd_addr: BdAddr,
+}
+
+impl ParseNodeOpt for EvtSimplePairingComplete {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 7 {
+            None
+        } else {
+            let status = data[0];
+            let bd_addr = BdAddr::new(&data[1..]);
+
+            if let Some(args) = args {
+                let pairing = args.hci_arg.pairing(bd_addr);
+                pairing.status = Some(status);
+                pairing.completed = true;
+            }
+
+            Some(EvtSimplePairingComplete { status, bd_addr })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let status_s = ParseBytesNode::new(start_byte, 1).format(
+            "Status",
+            self.status,
+            get_error_code_name(self.status),
+            "",
+        );
+        let bd_addr_s =
+            ParseBytesNode::new(start_byte + 1, 6).format("BD_ADDR", self.bd_addr, "", "");
+        format!("{}, {}", status_s, bd_addr_s)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct EvtUserPasskeyNotification {
+    bd_addr: BdAddr,
+    passkey: u32,
+}
+
+impl ParseNodeOpt for EvtUserPasskeyNotification {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 10 {
+            None
+        } else {
+            let bd_addr = BdAddr::new(data);
+            let passkey = u32::from_le_bytes(data[6..10].try_into().unwrap());
+
+            if let Some(args) = args {
+                let pairing = args.hci_arg.pairing(bd_addr);
+                pairing.model = Some(AssociationModel::PasskeyEntry);
+                pairing.numeric_value = Some(passkey);
+            }
+
+            Some(EvtUserPasskeyNotification { bd_addr, passkey })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let bd_addr_s = ParseBytesNode::new(start_byte, 6).format("BD_ADDR", self.bd_addr, "", "");
+        let passkey_s = ParseBytesNode::new(start_byte + 6, 4).format(
+            "Passkey",
+            self.passkey,
+            &format!("{:06}", self.passkey),
+            "",
+        );
+        format!("{}, {}", bd_addr_s, passkey_s)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct EvtExtendedInquiryResult {
+    num_responses: u8,
+    bd_addr: BdAddr,
+    page_scan_repetition_mode: u8,
+    class_of_device: u32,
+    clock_offset: u16,
+    rssi: i8,
+    extended_inquiry_response: AdvertisingData,
+}
+
+impl ParseNodeOpt for EvtExtendedInquiryResult {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 15 {
+            None
+        } else {
+            Some(EvtExtendedInquiryResult {
+                num_responses: data[0],
+                bd_addr: BdAddr::new(&data[1..]),
+                page_scan_repetition_mode: data[7],
+                class_of_device: data[9] as u32 | (data[10] as u32) << 8 | (data[11] as u32) << 16,
+                clock_offset: u16::from_le_bytes([data[12], data[13]]),
+                rssi: data[14] as i8,
+                extended_inquiry_response: AdvertisingData::new(&data[15..], args),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let page_scan_repetition_mode_name_s = match self.page_scan_repetition_mode {
+            0x00 => "R0",
+            0x01 => "R1",
+            0x02 => "R2",
+            _ => "Reserved for future use",
+        };
+        let num_responses_s =
+            ParseBytesNode::new(start_byte, 1).format("Num_Responses", self.num_responses, "", "");
+        let bd_addr_s =
+            ParseBytesNode::new(start_byte + 1, 6).format("BD_ADDR", self.bd_addr, "", "");
+        let page_scan_repetition_mode_s = ParseBytesNode::new(start_byte + 7, 1).format(
+            "Page_Scan_Repetition_Mode",
+            self.page_scan_repetition_mode,
+            page_scan_repetition_mode_name_s,
+            "",
+        );
+        let class_of_device_s = ParseBytesNode::new(start_byte + 9, 3).format(
+            "Class_Of_Device",
+            self.class_of_device,
+            &class_of_device_to_string(self.class_of_device),
+            "",
+        );
+        let clock_offset_s = ParseBytesNode::new(start_byte + 12, 2).format(
+            "Clock_Offset",
+            self.clock_offset,
+            "",
+            "",
+        );
+        let rssi_s = ParseBytesNode::new(start_byte + 14, 1).format(
+            "RSSI",
+            self.rssi as u8,
+            &format!("{} dBm", self.rssi),
+            "",
+        );
+        format!(
+            r#"{}, {}, {}, {}, {}, {}, "Extended_Inquiry_Response": {{{}}}"#,
+            num_responses_s,
+            bd_addr_s,
+            page_scan_repetition_mode_s,
+            class_of_device_s,
+            clock_offset_s,
+            rssi_s,
+            self.extended_inquiry_response.as_json(start_byte + 15)
+        )
+    }
+}
+
+/// Parameters of both the Keypress_Notification event and the
+/// Send_Keypress_Notification command.
+#[derive(Debug, PartialEq)]
+struct KeypressNotification {
+    bd_addr: BdAddr,
+    notification_type: u8,
+}
+
+impl ParseNodeOpt for KeypressNotification {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 7 {
+            None
+        } else {
+            Some(KeypressNotification {
+                bd_addr: BdAddr::new(data),
+                notification_type: data[6],
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let notification_type_name_s = match self.notification_type {
+            0x00 => "Passkey entry started",
+            0x01 => "Passkey digit entered",
+            0x02 => "Passkey digit erased",
+            0x03 => "Passkey cleared",
+            0x04 => "Passkey entry completed",
+            _ => "Reserved for future use",
+        };
+        let bd_addr_s = ParseBytesNode::new(start_byte, 6).format("BD_ADDR", self.bd_addr, "", "");
+        let notification_type_s = ParseBytesNode::new(start_byte + 6, 1).format(
+            "Notification_Type",
+            self.notification_type,
+            notification_type_name_s,
+            "",
+        );
+        format!("{}, {}", bd_addr_s, notification_type_s)
+    }
+}
+
+fn io_capability_as_json(
+    start_byte: u16,
+    bd_addr: BdAddr,
+    io_capability: u8,
+    oob_data_present: u8,
+    auth_requirements: u8,
+) -> String {
+    let oob_data_present_name_s = match oob_data_present {
+        0x00 => "OOB authentication data not present",
+        0x01 => "P-192 OOB authentication data from remote device present",
+        0x02 => "P-256 OOB authentication data from remote device present",
+        0x03 => "P-192 and P-256 OOB authentication data from remote device present",
+        _ => "Reserved for future use",
+    };
+    let auth_requirements_name_s = match auth_requirements {
+        0x00 => "MITM Protection Not Required - No Bonding",
+        0x01 => "MITM Protection Required - No Bonding",
+        0x02 => "MITM Protection Not Required - Dedicated Bonding",
+        0x03 => "MITM Protection Required - Dedicated Bonding",
+        0x04 => "MITM Protection Not Required - General Bonding",
+        0x05 => "MITM Protection Required - General Bonding",
+        _ => "Reserved for future use",
+    };
+    let bd_addr_s = ParseBytesNode::new(start_byte, 6).format("BD_ADDR", bd_addr, "", "");
+    let io_capability_s = ParseBytesNode::new(start_byte + 6, 1).format(
+        "IO_Capability",
+        io_capability,
+        get_io_capability_name(io_capability),
+        "",
+    );
+    let oob_data_present_s = ParseBytesNode::new(start_byte + 7, 1).format(
+        "OOB_Data_Present",
+        oob_data_present,
+        oob_data_present_name_s,
+        "",
+    );
+    let auth_requirements_s = ParseBytesNode::new(start_byte + 8, 1).format(
+        "Authentication_Requirements",
+        auth_requirements,
+        auth_requirements_name_s,
+        "",
+    );
+    format!(
+        "{}, {}, {}, {}",
+        bd_addr_s, io_capability_s, oob_data_present_s, auth_requirements_s
+    )
+}
+
 pub fn parse(data: &[u8], args: &mut HostStack) -> String {
     let ret = HciPacket::new(data, Some(args));
     println!("ret={:?}\n", ret);
@@ -462,11 +1845,110 @@ pub fn parse(data: &[u8], args: &mut HostStack) -> String {
 }
 
 fn opcode_to_ogf(opcode: u16) -> u8 {
-    (opcode & 0x3ff) as u8
+    (opcode >> 10) as u8
 }
 
-fn opcode_to_ocf(opcode: u16) -> u8 {
-    (opcode >> 10) as u8
+fn opcode_to_ocf(opcode: u16) -> u16 {
+    opcode & 0x3ff
+}
+
+fn get_evt_name(code: u8) -> &'static str {
+    match code {
+        0x03 => "HCI_Connection_Complete",
+        0x05 => "HCI_Disconnection_Complete",
+        0x0c => "HCI_Read_Remote_Version_Information_Complete",
+        0x0e => "HCI_Command_Complete",
+        0x17 => "HCI_Link_Key_Request",
+        0x18 => "HCI_Link_Key_Notification",
+        0x2f => "HCI_Extended_Inquiry_Result",
+        0x31 => "HCI_IO_Capability_Request",
+        0x32 => "HCI_IO_Capability_Response",
+        0x33 => "HCI_User_Confirmation_Request",
+        0x34 => "HCI_User_Passkey_Request",
+        0x35 => "HCI_Remote_OOB_Data_Request",
+        0x36 => "HCI_Simple_Pairing_Complete",
+        0x3b => "HCI_User_Passkey_Notification",
+        0x3c => "HCI_Keypress_Notification",
+        0x3e => "HCI_LE_Meta",
+        _ => "Unknown",
+    }
+}
+
+fn get_error_code_name(code: u8) -> &'static str {
+    match code {
+        0x00 => "Success",
+        0x01 => "Unknown HCI Command",
+        0x02 => "Unknown Connection Identifier",
+        0x03 => "Hardware Failure",
+        0x04 => "Page Timeout",
+        0x05 => "Authentication Failure",
+        0x06 => "PIN or Key Missing",
+        0x07 => "Memory Capacity Exceeded",
+        0x08 => "Connection Timeout",
+        0x09 => "Connection Limit Exceeded",
+        0x0a => "Synchronous Connection Limit To A Device Exceeded",
+        0x0b => "Connection Already Exists",
+        0x0c => "Command Disallowed",
+        0x0d => "Connection Rejected due to Limited Resources",
+        0x0e => "Connection Rejected Due To Security Reasons",
+        0x0f => "Connection Rejected due to Unacceptable BD_ADDR",
+        0x10 => "Connection Accept Timeout Exceeded",
+        0x11 => "Unsupported Feature or Parameter Value",
+        0x12 => "Invalid HCI Command Parameters",
+        0x13 => "Remote User Terminated Connection",
+        0x14 => "Remote Device Terminated Connection due to Low Resources",
+        0x15 => "Remote Device Terminated Connection due to Power Off",
+        0x16 => "Connection Terminated By Local Host",
+        0x17 => "Repeated Attempts",
+        0x18 => "Pairing Not Allowed",
+        0x19 => "Unknown LMP PDU",
+        0x1a => "Unsupported Remote Feature",
+        0x1f => "Unspecified Error",
+        0x20 => "Unsupported LMP Parameter Value",
+        0x21 => "Role Change Not Allowed",
+        0x22 => "LMP Response Timeout / LL Response Timeout",
+        0x23 => "LMP Error Transaction Collision / LL Procedure Collision",
+        0x24 => "LMP PDU Not Allowed",
+        0x25 => "Encryption Mode Not Acceptable",
+        0x26 => "Link Key cannot be Changed",
+        0x28 => "Instant Passed",
+        0x29 => "Pairing With Unit Key Not Supported",
+        0x2a => "Different Transaction Collision",
+        0x2f => "Insufficient Security",
+        0x37 => "Simple Pairing Not Supported by Host",
+        0x3a => "Controller Busy",
+        0x3b => "Unacceptable Connection Parameters",
+        0x3c => "Advertising Timeout",
+        0x3d => "Connection Terminated due to MIC Failure",
+        0x3e => "Connection Failed to be Established / Synchronization Timeout",
+        0x43 => "Limit Reached",
+        0x44 => "Operation Cancelled by Host",
+        0x45 => "Packet Too Long",
+        _ => "Reserved for future use",
+    }
+}
+
+fn get_io_capability_name(io_capability: u8) -> &'static str {
+    match io_capability {
+        0x00 => "DisplayOnly",
+        0x01 => "DisplayYesNo",
+        0x02 => "KeyboardOnly",
+        0x03 => "NoInputNoOutput",
+        _ => "Reserved for future use",
+    }
+}
+
+fn get_key_type_name(key_type: u8) -> &'static str {
+    match key_type {
+        0x00 => "Combination Key",
+        0x03 => "Debug Combination Key",
+        0x04 => "Unauthenticated Combination Key generated from P-192",
+        0x05 => "Authenticated Combination Key generated from P-192",
+        0x06 => "Changed Combination Key",
+        0x07 => "Unauthenticated Combination Key generated from P-256",
+        0x08 => "Authenticated Combination Key generated from P-256",
+        _ => "Reserved for future use",
+    }
 }
 
 #[cfg(test)]
@@ -502,4 +1984,81 @@ mod tests {
     //     });
     //     assert_eq!(res, expect);
     // }
+
+    use super::parse;
+    use crate::test_utils::parse_packets;
+    use crate::{str_to_array, HostStack};
+
+    const IO_CAPABILITY_REQUEST: &str = "04 31 06 66 55 44 33 22 11";
+    // DisplayYesNo with MITM protection from both sides
+    const IO_CAPABILITY_REPLY: &str = "01 2b 04 09 66 55 44 33 22 11 01 00 03";
+    const IO_CAPABILITY_RESPONSE: &str = "04 32 09 66 55 44 33 22 11 01 00 03";
+    const USER_CONFIRMATION_REQUEST: &str = "04 33 0a 66 55 44 33 22 11 40 e2 01 00";
+    const SIMPLE_PAIRING_COMPLETE: &str = "04 36 07 00 66 55 44 33 22 11";
+    const LINK_KEY_NOTIFICATION: &str =
+        "04 18 17 66 55 44 33 22 11 00 11 22 33 44 55 66 77 88 99 aa bb cc dd ee ff 05";
+    const NUMERIC_COMPARISON: &str = r#"{"11:22:33:44:55:66": {"Association Model": "Numeric Comparison", "Key Type": "Authenticated Combination Key generated from P-192", "Authenticated": true, "Status": "Success"}}"#;
+
+    #[test]
+    fn pairing_started_by_the_peer_keeps_its_io_capability() {
+        let (_, args) = parse_packets(&[
+            IO_CAPABILITY_RESPONSE,
+            IO_CAPABILITY_REQUEST,
+            IO_CAPABILITY_REPLY,
+            USER_CONFIRMATION_REQUEST,
+            SIMPLE_PAIRING_COMPLETE,
+            LINK_KEY_NOTIFICATION,
+        ]);
+        assert_eq!(args.pairing_summary(), NUMERIC_COMPARISON);
+    }
+
+    #[test]
+    fn pairing_started_by_the_host_is_replaced_once_complete() {
+        let first = [
+            IO_CAPABILITY_REQUEST,
+            IO_CAPABILITY_REPLY,
+            IO_CAPABILITY_RESPONSE,
+            USER_CONFIRMATION_REQUEST,
+            SIMPLE_PAIRING_COMPLETE,
+            LINK_KEY_NOTIFICATION,
+        ];
+        let (_, args) = parse_packets(&first);
+        assert_eq!(args.pairing_summary(), NUMERIC_COMPARISON);
+
+        // the peer comes back as NoInputNoOutput without MITM protection
+        let (_, args) = parse_packets(
+            &[
+                &first[..],
+                &[
+                    IO_CAPABILITY_REQUEST,
+                    IO_CAPABILITY_REPLY,
+                    "04 32 09 66 55 44 33 22 11 03 00 00",
+                ],
+            ]
+            .concat(),
+        );
+        assert_eq!(
+            args.pairing_summary(),
+            r#"{"11:22:33:44:55:66": {"Association Model": "Just Works", "Key Type": "Unknown", "Authenticated": "Unknown", "Status": "Pending"}}"#
+        );
+    }
+
+    #[test]
+    fn disconnection_ends_the_connection_state() {
+        let mut args = HostStack::new();
+        for packet in [
+            "02 40 20 0b 00 07 00 04 00 10 01 00 ff ff 00 28",
+            "02 40 20 0c 00 08 00 04 00 11 06 01 00 05 00 0d 18",
+            "02 40 20 0b 00 07 00 04 00 08 01 00 ff ff 03 28",
+            "04 05 04 00 40 00 13",
+            // the handle is given to a new connection, which never asked
+            "02 40 20 0d 00 09 00 04 00 09 07 02 00 10 03 00 37 2a",
+        ] {
+            parse(&str_to_array(packet), &mut args);
+        }
+        assert_eq!(
+            args.gatt_summary(),
+            r#"{"Connection 0x40": {"Service[0]": {"UUID": "Heart Rate", "Type": "Primary", "Handles": "0x0001-0x0005"}}, "Connection 0x40 #2": {}}"#
+        );
+    }
 }
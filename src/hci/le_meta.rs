@@ -0,0 +1,1657 @@
+use super::get_error_code_name;
+use super::BdAddr;
+
+use crate::ad::AdvertisingData;
+use crate::HostStack;
+use crate::ParseNode;
+use crate::ParseNodeA;
+use crate::ParseNodeOpt;
+use crate::ParseNodeOptA;
+
+use crate::ParseBitsNode;
+use crate::ParseBytesNode;
+
+#[derive(Debug, PartialEq)]
+pub struct EvtLeMeta {
+    subevent_code: u8,
+    param: LeMetaParam,
+}
+
+impl ParseNodeOpt for EvtLeMeta {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.is_empty() {
+            None
+        } else {
+            let subevent_code = data[0];
+            Some(EvtLeMeta {
+                subevent_code,
+                param: LeMetaParam::new(&data[1..], args, subevent_code),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let subevent_code_s = ParseBytesNode::new(start_byte, 1).format(
+            "Subevent_Code",
+            self.subevent_code,
+            get_subevent_name(self.subevent_code),
+            "",
+        );
+        let param_s = self.param.as_json(start_byte + 1);
+        if param_s.is_empty() {
+            subevent_code_s
+        } else {
+            format!("{}, {}", subevent_code_s, param_s)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum LeMetaParam {
+    Undefined,
+    ConnectionComplete(Option<LeConnectionComplete>),
+    AdvertisingReport(Option<LeAdvertisingReport>),
+    ConnectionUpdateComplete(Option<LeConnectionUpdateComplete>),
+    ReadRemoteFeaturesComplete(Option<LeReadRemoteFeaturesComplete>),
+    LongTermKeyRequest(Option<LeLongTermKeyRequest>),
+    DataLengthChange(Option<LeDataLengthChange>),
+    EnhancedConnectionComplete(Option<LeEnhancedConnectionComplete>),
+    PhyUpdateComplete(Option<LePhyUpdateComplete>),
+    ExtendedAdvertisingReport(Option<LeExtendedAdvertisingReport>),
+    PeriodicAdvertisingSyncEstablished(Option<LePeriodicAdvertisingSyncEstablished>),
+    PeriodicAdvertisingReport(Option<LePeriodicAdvertisingReport>),
+    AdvertisingSetTerminated(Option<LeAdvertisingSetTerminated>),
+    CisEstablished(Option<LeCisEstablished>),
+    CisRequest(Option<LeCisRequest>),
+    CreateBigComplete(Option<LeCreateBigComplete>),
+    BigSyncEstablished(Option<LeBigSyncEstablished>),
+}
+
+impl ParseNodeA<u8> for LeMetaParam {
+    fn new(data: &[u8], args: Option<&mut HostStack>, code: u8) -> Self {
+        match code {
+            0x01 => LeMetaParam::ConnectionComplete(LeConnectionComplete::new(data, args)),
+            0x02 => LeMetaParam::AdvertisingReport(LeAdvertisingReport::new(data, args)),
+            0x03 => {
+                LeMetaParam::ConnectionUpdateComplete(LeConnectionUpdateComplete::new(data, args))
+            }
+            0x04 => LeMetaParam::ReadRemoteFeaturesComplete(LeReadRemoteFeaturesComplete::new(
+                data, args,
+            )),
+            0x05 => LeMetaParam::LongTermKeyRequest(LeLongTermKeyRequest::new(data, args)),
+            0x07 => LeMetaParam::DataLengthChange(LeDataLengthChange::new(data, args)),
+            0x0a | 0x29 => LeMetaParam::EnhancedConnectionComplete(
+                LeEnhancedConnectionComplete::new(data, args, code),
+            ),
+            0x0c => LeMetaParam::PhyUpdateComplete(LePhyUpdateComplete::new(data, args)),
+            0x0d => {
+                LeMetaParam::ExtendedAdvertisingReport(LeExtendedAdvertisingReport::new(data, args))
+            }
+            0x0e => LeMetaParam::PeriodicAdvertisingSyncEstablished(
+                LePeriodicAdvertisingSyncEstablished::new(data, args),
+            ),
+            0x0f => {
+                LeMetaParam::PeriodicAdvertisingReport(LePeriodicAdvertisingReport::new(data, args))
+            }
+            0x12 => {
+                LeMetaParam::AdvertisingSetTerminated(LeAdvertisingSetTerminated::new(data, args))
+            }
+            0x19 => LeMetaParam::CisEstablished(LeCisEstablished::new(data, args)),
+            0x1a => LeMetaParam::CisRequest(LeCisRequest::new(data, args)),
+            0x1b => LeMetaParam::CreateBigComplete(LeCreateBigComplete::new(data, args)),
+            0x1d => LeMetaParam::BigSyncEstablished(LeBigSyncEstablished::new(data, args)),
+            _ => LeMetaParam::Undefined,
+        }
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        match self {
+            LeMetaParam::ConnectionComplete(evt) => evt.as_ref().map(|evt| evt.as_json(start_byte)),
+            LeMetaParam::AdvertisingReport(evt) => evt.as_ref().map(|evt| evt.as_json(start_byte)),
+            LeMetaParam::ConnectionUpdateComplete(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
+            LeMetaParam::ReadRemoteFeaturesComplete(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
+            LeMetaParam::LongTermKeyRequest(evt) => evt.as_ref().map(|evt| evt.as_json(start_byte)),
+            LeMetaParam::DataLengthChange(evt) => evt.as_ref().map(|evt| evt.as_json(start_byte)),
+            LeMetaParam::EnhancedConnectionComplete(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
+            LeMetaParam::PhyUpdateComplete(evt) => evt.as_ref().map(|evt| evt.as_json(start_byte)),
+            LeMetaParam::ExtendedAdvertisingReport(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
+            LeMetaParam::PeriodicAdvertisingSyncEstablished(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
+            LeMetaParam::PeriodicAdvertisingReport(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
+            LeMetaParam::AdvertisingSetTerminated(evt) => {
+                evt.as_ref().map(|evt| evt.as_json(start_byte))
+            }
+            LeMetaParam::CisEstablished(evt) => evt.as_ref().map(|evt| evt.as_json(start_byte)),
+            LeMetaParam::CisRequest(evt) => evt.as_ref().map(|evt| evt.as_json(start_byte)),
+            LeMetaParam::CreateBigComplete(evt) => evt.as_ref().map(|evt| evt.as_json(start_byte)),
+            LeMetaParam::BigSyncEstablished(evt) => evt.as_ref().map(|evt| evt.as_json(start_byte)),
+            LeMetaParam::Undefined => None,
+        }
+        .unwrap_or_default()
+    }
+}
+
+// subevent 0x01
+#[derive(Debug, PartialEq)]
+struct LeConnectionComplete {
+    status: u8,
+    connection_handle: u16,
+    role: u8,
+    peer_address_type: u8,
+    peer_address: BdAddr,
+    connection_interval: u16,
+    peripheral_latency: u16,
+    supervision_timeout: u16,
+    central_clock_accuracy: u8,
+}
+
+impl ParseNodeOpt for LeConnectionComplete {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 18 {
+            None
+        } else {
+            let status = data[0];
+            let connection_handle = u16::from_le_bytes([data[1], data[2]]);
+            let peer_address = BdAddr::new(&data[5..]);
+
+            if let Some(args) = args {
+                if status == 0x00 {
+                    args.hci_arg
+                        .connect(connection_handle & 0xfff, peer_address);
+                }
+            }
+
+            Some(LeConnectionComplete {
+                status,
+                connection_handle,
+                role: data[3],
+                peer_address_type: data[4],
+                peer_address,
+                connection_interval: u16::from_le_bytes([data[11], data[12]]),
+                peripheral_latency: u16::from_le_bytes([data[13], data[14]]),
+                supervision_timeout: u16::from_le_bytes([data[15], data[16]]),
+                central_clock_accuracy: data[17],
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let head_s = connection_head_as_json(
+            start_byte,
+            self.status,
+            self.connection_handle,
+            self.role,
+            self.peer_address_type,
+            self.peer_address,
+        );
+        let tail_s = connection_tail_as_json(
+            start_byte + 11,
+            self.connection_interval,
+            self.peripheral_latency,
+            self.supervision_timeout,
+            self.central_clock_accuracy,
+        );
+        format!("{}, {}", head_s, tail_s)
+    }
+}
+
+// subevent 0x0a (v1) and 0x29 (v2)
+#[derive(Debug, PartialEq)]
+struct LeEnhancedConnectionComplete {
+    status: u8,
+    connection_handle: u16,
+    role: u8,
+    peer_address_type: u8,
+    peer_address: BdAddr,
+    local_resolvable_private_address: BdAddr,
+    peer_resolvable_private_address: BdAddr,
+    connection_interval: u16,
+    peripheral_latency: u16,
+    supervision_timeout: u16,
+    central_clock_accuracy: u8,
+    // v2 only
+    advertising_handle: Option<u8>,
+    sync_handle: Option<u16>,
+}
+
+impl ParseNodeOptA<u8> for LeEnhancedConnectionComplete {
+    fn new(data: &[u8], args: Option<&mut HostStack>, code: u8) -> Option<Self> {
+        let is_v2 = code == 0x29;
+        if data.len() < 30 || (is_v2 && data.len() < 33) {
+            None
+        } else {
+            let status = data[0];
+            let connection_handle = u16::from_le_bytes([data[1], data[2]]);
+            let peer_address = BdAddr::new(&data[5..]);
+
+            if let Some(args) = args {
+                if status == 0x00 {
+                    args.hci_arg
+                        .connect(connection_handle & 0xfff, peer_address);
+                }
+            }
+
+            Some(LeEnhancedConnectionComplete {
+                status,
+                connection_handle,
+                role: data[3],
+                peer_address_type: data[4],
+                peer_address,
+                local_resolvable_private_address: BdAddr::new(&data[11..]),
+                peer_resolvable_private_address: BdAddr::new(&data[17..]),
+                connection_interval: u16::from_le_bytes([data[23], data[24]]),
+                peripheral_latency: u16::from_le_bytes([data[25], data[26]]),
+                supervision_timeout: u16::from_le_bytes([data[27], data[28]]),
+                central_clock_accuracy: data[29],
+                advertising_handle: is_v2.then(|| data[30]),
+                sync_handle: is_v2.then(|| u16::from_le_bytes([data[31], data[32]])),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let head_s = connection_head_as_json(
+            start_byte,
+            self.status,
+            self.connection_handle,
+            self.role,
+            self.peer_address_type,
+            self.peer_address,
+        );
+        let local_rpa_s = ParseBytesNode::new(start_byte + 11, 6).format(
+            "Local_Resolvable_Private_Address",
+            self.local_resolvable_private_address,
+            "",
+            "",
+        );
+        let peer_rpa_s = ParseBytesNode::new(start_byte + 17, 6).format(
+            "Peer_Resolvable_Private_Address",
+            self.peer_resolvable_private_address,
+            "",
+            "",
+        );
+        let tail_s = connection_tail_as_json(
+            start_byte + 23,
+            self.connection_interval,
+            self.peripheral_latency,
+            self.supervision_timeout,
+            self.central_clock_accuracy,
+        );
+        let mut json = format!("{}, {}, {}, {}", head_s, local_rpa_s, peer_rpa_s, tail_s);
+        if let (Some(advertising_handle), Some(sync_handle)) =
+            (self.advertising_handle, self.sync_handle)
+        {
+            let advertising_handle_s = ParseBytesNode::new(start_byte + 30, 1).format(
+                "Advertising_Handle",
+                advertising_handle,
+                "",
+                "",
+            );
+            let sync_handle_s =
+                ParseBytesNode::new(start_byte + 31, 2).format("Sync_Handle", sync_handle, "", "");
+            json.push_str(&format!(", {}, {}", advertising_handle_s, sync_handle_s));
+        }
+        json
+    }
+}
+
+// subevent 0x02
+#[derive(Debug, PartialEq)]
+struct LeAdvertisingReport {
+    num_reports: u8,
+    reports: Vec<AdvertisingReport>,
+}
+
+impl ParseNodeOpt for LeAdvertisingReport {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.is_empty() {
+            None
+        } else {
+            let mut args = args;
+            let num_reports = data[0];
+            let mut reports = Vec::new();
+            let mut offset = 1;
+            for _ in 0..num_reports {
+                match AdvertisingReport::new(&data[offset..], args.as_deref_mut()) {
+                    Some(report) => {
+                        offset += report.len();
+                        reports.push(report);
+                    }
+                    None => break,
+                }
+            }
+            Some(LeAdvertisingReport {
+                num_reports,
+                reports,
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut json =
+            ParseBytesNode::new(start_byte, 1).format("Num_Reports", self.num_reports, "", "");
+        let mut offset = start_byte + 1;
+        for (i, report) in self.reports.iter().enumerate() {
+            json.push_str(&format!(
+                r#", "Report[{}]": {{{}}}"#,
+                i,
+                report.as_json(offset)
+            ));
+            offset += report.len() as u16;
+        }
+        json
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct AdvertisingReport {
+    event_type: u8,
+    address_type: u8,
+    address: BdAddr,
+    data: Vec<u8>,
+    advertising_data: AdvertisingData,
+    rssi: i8,
+}
+
+impl AdvertisingReport {
+    fn len(&self) -> usize {
+        10 + self.data.len()
+    }
+}
+
+impl ParseNodeOpt for AdvertisingReport {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 9 || data.len() < 10 + data[8] as usize {
+            None
+        } else {
+            let data_len = data[8] as usize;
+            Some(AdvertisingReport {
+                event_type: data[0],
+                address_type: data[1],
+                address: BdAddr::new(&data[2..]),
+                data: data[9..9 + data_len].to_vec(),
+                advertising_data: AdvertisingData::new(&data[9..9 + data_len], args),
+                rssi: data[9 + data_len] as i8,
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let event_type_name_s = match self.event_type {
+            0x00 => "ADV_IND",
+            0x01 => "ADV_DIRECT_IND",
+            0x02 => "ADV_SCAN_IND",
+            0x03 => "ADV_NONCONN_IND",
+            0x04 => "SCAN_RSP",
+            _ => "Reserved for future use",
+        };
+        let data_len = self.data.len() as u16;
+        let event_type_s = ParseBytesNode::new(start_byte, 1).format(
+            "Event_Type",
+            self.event_type,
+            event_type_name_s,
+            "",
+        );
+        let address_type_s = ParseBytesNode::new(start_byte + 1, 1).format(
+            "Address_Type",
+            self.address_type,
+            get_address_type_name(self.address_type),
+            "",
+        );
+        let address_s =
+            ParseBytesNode::new(start_byte + 2, 6).format("Address", self.address, "", "");
+        let data_len_s =
+            ParseBytesNode::new(start_byte + 8, 1).format("Data_Length", data_len as u8, "", "");
+        let data_s = format!(
+            r#""Data": {{{}}}"#,
+            self.advertising_data.as_json(start_byte + 9)
+        );
+        let rssi_s = ParseBytesNode::new(start_byte + 9 + data_len, 1).format(
+            "RSSI",
+            self.rssi as u8,
+            &get_rssi_name(self.rssi),
+            "",
+        );
+        format!(
+            "{}, {}, {}, {}, {}, {}",
+            event_type_s, address_type_s, address_s, data_len_s, data_s, rssi_s
+        )
+    }
+}
+
+// subevent 0x03
+#[derive(Debug, PartialEq)]
+struct LeConnectionUpdateComplete {
+    status: u8,
+    connection_handle: u16,
+    connection_interval: u16,
+    peripheral_latency: u16,
+    supervision_timeout: u16,
+}
+
+impl ParseNodeOpt for LeConnectionUpdateComplete {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 9 {
+            None
+        } else {
+            Some(LeConnectionUpdateComplete {
+                status: data[0],
+                connection_handle: u16::from_le_bytes([data[1], data[2]]),
+                connection_interval: u16::from_le_bytes([data[3], data[4]]),
+                peripheral_latency: u16::from_le_bytes([data[5], data[6]]),
+                supervision_timeout: u16::from_le_bytes([data[7], data[8]]),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let status_s = status_as_json(start_byte, self.status);
+        let handle_s = handle_as_json(start_byte + 1, self.connection_handle);
+        let interval_s = ParseBytesNode::new(start_byte + 3, 2).format(
+            "Connection_Interval",
+            self.connection_interval,
+            &format!("{:.2} ms", self.connection_interval as f32 * 1.25),
+            "",
+        );
+        let latency_s = ParseBytesNode::new(start_byte + 5, 2).format(
+            "Peripheral_Latency",
+            self.peripheral_latency,
+            "",
+            "",
+        );
+        let timeout_s = ParseBytesNode::new(start_byte + 7, 2).format(
+            "Supervision_Timeout",
+            self.supervision_timeout,
+            &format!("{} ms", self.supervision_timeout as u32 * 10),
+            "",
+        );
+        format!(
+            "{}, {}, {}, {}, {}",
+            status_s, handle_s, interval_s, latency_s, timeout_s
+        )
+    }
+}
+
+// subevent 0x04
+#[derive(Debug, PartialEq)]
+struct LeReadRemoteFeaturesComplete {
+    status: u8,
+    connection_handle: u16,
+    le_features: u64,
+}
+
+impl ParseNodeOpt for LeReadRemoteFeaturesComplete {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 11 {
+            None
+        } else {
+            Some(LeReadRemoteFeaturesComplete {
+                status: data[0],
+                connection_handle: u16::from_le_bytes([data[1], data[2]]),
+                le_features: u64::from_le_bytes(data[3..11].try_into().unwrap()),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let status_s = status_as_json(start_byte, self.status);
+        let handle_s = handle_as_json(start_byte + 1, self.connection_handle);
+        let features_s = ParseBytesNode::new(start_byte + 3, 8).format(
+            "LE_Features",
+            self.le_features,
+            &get_le_features_name(self.le_features),
+            "",
+        );
+        format!("{}, {}, {}", status_s, handle_s, features_s)
+    }
+}
+
+// subevent 0x05
+#[derive(Debug, PartialEq)]
+struct LeLongTermKeyRequest {
+    connection_handle: u16,
+    random_number: u64,
+    encrypted_diversifier: u16,
+}
+
+impl ParseNodeOpt for LeLongTermKeyRequest {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 12 {
+            None
+        } else {
+            Some(LeLongTermKeyRequest {
+                connection_handle: u16::from_le_bytes([data[0], data[1]]),
+                random_number: u64::from_le_bytes(data[2..10].try_into().unwrap()),
+                encrypted_diversifier: u16::from_le_bytes([data[10], data[11]]),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        // both zero means the LTK comes from LE Secure Connections pairing
+        let secure_connections = self.random_number == 0 && self.encrypted_diversifier == 0;
+        let handle_s = handle_as_json(start_byte, self.connection_handle);
+        let random_number_s = ParseBytesNode::new(start_byte + 2, 8).format(
+            "Random_Number",
+            self.random_number,
+            "",
+            "",
+        );
+        let ediv_s = ParseBytesNode::new(start_byte + 10, 2).format(
+            "Encrypted_Diversifier",
+            self.encrypted_diversifier,
+            if secure_connections {
+                "LE Secure Connections"
+            } else {
+                "LE Legacy Pairing"
+            },
+            "",
+        );
+        format!("{}, {}, {}", handle_s, random_number_s, ediv_s)
+    }
+}
+
+// subevent 0x07
+#[derive(Debug, PartialEq)]
+struct LeDataLengthChange {
+    connection_handle: u16,
+    max_tx_octets: u16,
+    max_tx_time: u16,
+    max_rx_octets: u16,
+    max_rx_time: u16,
+}
+
+impl ParseNodeOpt for LeDataLengthChange {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 10 {
+            None
+        } else {
+            Some(LeDataLengthChange {
+                connection_handle: u16::from_le_bytes([data[0], data[1]]),
+                max_tx_octets: u16::from_le_bytes([data[2], data[3]]),
+                max_tx_time: u16::from_le_bytes([data[4], data[5]]),
+                max_rx_octets: u16::from_le_bytes([data[6], data[7]]),
+                max_rx_time: u16::from_le_bytes([data[8], data[9]]),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let handle_s = handle_as_json(start_byte, self.connection_handle);
+        let max_tx_octets_s = ParseBytesNode::new(start_byte + 2, 2).format(
+            "Max_TX_Octets",
+            self.max_tx_octets,
+            &format!("{} bytes", self.max_tx_octets),
+            "",
+        );
+        let max_tx_time_s = ParseBytesNode::new(start_byte + 4, 2).format(
+            "Max_TX_Time",
+            self.max_tx_time,
+            &format!("{} us", self.max_tx_time),
+            "",
+        );
+        let max_rx_octets_s = ParseBytesNode::new(start_byte + 6, 2).format(
+            "Max_RX_Octets",
+            self.max_rx_octets,
+            &format!("{} bytes", self.max_rx_octets),
+            "",
+        );
+        let max_rx_time_s = ParseBytesNode::new(start_byte + 8, 2).format(
+            "Max_RX_Time",
+            self.max_rx_time,
+            &format!("{} us", self.max_rx_time),
+            "",
+        );
+        format!(
+            "{}, {}, {}, {}, {}",
+            handle_s, max_tx_octets_s, max_tx_time_s, max_rx_octets_s, max_rx_time_s
+        )
+    }
+}
+
+// subevent 0x0c
+#[derive(Debug, PartialEq)]
+struct LePhyUpdateComplete {
+    status: u8,
+    connection_handle: u16,
+    tx_phy: u8,
+    rx_phy: u8,
+}
+
+impl ParseNodeOpt for LePhyUpdateComplete {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 5 {
+            None
+        } else {
+            Some(LePhyUpdateComplete {
+                status: data[0],
+                connection_handle: u16::from_le_bytes([data[1], data[2]]),
+                tx_phy: data[3],
+                rx_phy: data[4],
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let status_s = status_as_json(start_byte, self.status);
+        let handle_s = handle_as_json(start_byte + 1, self.connection_handle);
+        let tx_phy_s = ParseBytesNode::new(start_byte + 3, 1).format(
+            "TX_PHY",
+            self.tx_phy,
+            get_phy_name(self.tx_phy),
+            "",
+        );
+        let rx_phy_s = ParseBytesNode::new(start_byte + 4, 1).format(
+            "RX_PHY",
+            self.rx_phy,
+            get_phy_name(self.rx_phy),
+            "",
+        );
+        format!("{}, {}, {}, {}", status_s, handle_s, tx_phy_s, rx_phy_s)
+    }
+}
+
+// subevent 0x0d
+#[derive(Debug, PartialEq)]
+struct LeExtendedAdvertisingReport {
+    num_reports: u8,
+    reports: Vec<ExtendedAdvertisingReport>,
+}
+
+impl ParseNodeOpt for LeExtendedAdvertisingReport {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.is_empty() {
+            None
+        } else {
+            let mut args = args;
+            let num_reports = data[0];
+            let mut reports = Vec::new();
+            let mut offset = 1;
+            for _ in 0..num_reports {
+                match ExtendedAdvertisingReport::new(&data[offset..], args.as_deref_mut()) {
+                    Some(report) => {
+                        offset += report.len();
+                        reports.push(report);
+                    }
+                    None => break,
+                }
+            }
+            Some(LeExtendedAdvertisingReport {
+                num_reports,
+                reports,
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut json =
+            ParseBytesNode::new(start_byte, 1).format("Num_Reports", self.num_reports, "", "");
+        let mut offset = start_byte + 1;
+        for (i, report) in self.reports.iter().enumerate() {
+            json.push_str(&format!(
+                r#", "Report[{}]": {{{}}}"#,
+                i,
+                report.as_json(offset)
+            ));
+            offset += report.len() as u16;
+        }
+        json
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct ExtendedAdvertisingReport {
+    event_type: u16,
+    address_type: u8,
+    address: BdAddr,
+    primary_phy: u8,
+    secondary_phy: u8,
+    advertising_sid: u8,
+    tx_power: i8,
+    rssi: i8,
+    periodic_advertising_interval: u16,
+    direct_address_type: u8,
+    direct_address: BdAddr,
+    data: Vec<u8>,
+    advertising_data: AdvertisingData,
+}
+
+impl ExtendedAdvertisingReport {
+    fn len(&self) -> usize {
+        24 + self.data.len()
+    }
+}
+
+impl ParseNodeOpt for ExtendedAdvertisingReport {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 24 || data.len() < 24 + data[23] as usize {
+            None
+        } else {
+            let data_len = data[23] as usize;
+            Some(ExtendedAdvertisingReport {
+                event_type: u16::from_le_bytes([data[0], data[1]]),
+                address_type: data[2],
+                address: BdAddr::new(&data[3..]),
+                primary_phy: data[9],
+                secondary_phy: data[10],
+                advertising_sid: data[11],
+                tx_power: data[12] as i8,
+                rssi: data[13] as i8,
+                periodic_advertising_interval: u16::from_le_bytes([data[14], data[15]]),
+                direct_address_type: data[16],
+                direct_address: BdAddr::new(&data[17..]),
+                data: data[24..24 + data_len].to_vec(),
+                advertising_data: AdvertisingData::new(&data[24..24 + data_len], args),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let data_len = self.data.len() as u16;
+        let data_status_name_s = match (self.event_type >> 5) & 0x03 {
+            0b00 => "Complete",
+            0b01 => "Incomplete, more data to come",
+            0b10 => "Incomplete, data truncated, no more to come",
+            _ => "Reserved for future use",
+        };
+        let event_type_s = format!(
+            r#""Event_Type": {{{}, {}, {}, {}, {}, {}, {}}}"#,
+            ParseBitsNode::new(start_byte, 2, 0, 1).format(
+                "Connectable",
+                self.event_type & 0x01,
+                "",
+                ""
+            ),
+            ParseBitsNode::new(start_byte, 2, 1, 1).format(
+                "Scannable",
+                (self.event_type >> 1) & 0x01,
+                "",
+                ""
+            ),
+            ParseBitsNode::new(start_byte, 2, 2, 1).format(
+                "Directed",
+                (self.event_type >> 2) & 0x01,
+                "",
+                ""
+            ),
+            ParseBitsNode::new(start_byte, 2, 3, 1).format(
+                "Scan Response",
+                (self.event_type >> 3) & 0x01,
+                "",
+                ""
+            ),
+            ParseBitsNode::new(start_byte, 2, 4, 1).format(
+                "Legacy",
+                (self.event_type >> 4) & 0x01,
+                "",
+                ""
+            ),
+            ParseBitsNode::new(start_byte, 2, 5, 2).format(
+                "Data Status",
+                (self.event_type >> 5) & 0x03,
+                data_status_name_s,
+                ""
+            ),
+            ParseBytesNode::new(start_byte, 2).format("Value", self.event_type, "", ""),
+        );
+        let address_type_s = ParseBytesNode::new(start_byte + 2, 1).format(
+            "Address_Type",
+            self.address_type,
+            get_address_type_name(self.address_type),
+            "",
+        );
+        let address_s =
+            ParseBytesNode::new(start_byte + 3, 6).format("Address", self.address, "", "");
+        let primary_phy_s = ParseBytesNode::new(start_byte + 9, 1).format(
+            "Primary_PHY",
+            self.primary_phy,
+            get_phy_name(self.primary_phy),
+            "",
+        );
+        let secondary_phy_s = ParseBytesNode::new(start_byte + 10, 1).format(
+            "Secondary_PHY",
+            self.secondary_phy,
+            match self.secondary_phy {
+                0x00 => "No packets on the secondary advertising physical channel",
+                phy => get_phy_name(phy),
+            },
+            "",
+        );
+        let sid_s = ParseBytesNode::new(start_byte + 11, 1).format(
+            "Advertising_SID",
+            self.advertising_sid,
+            if self.advertising_sid == 0xff {
+                "No ADI field provided"
+            } else {
+                ""
+            },
+            "",
+        );
+        let tx_power_s = ParseBytesNode::new(start_byte + 12, 1).format(
+            "TX_Power",
+            self.tx_power as u8,
+            &if self.tx_power == 0x7f {
+                "Not available".to_string()
+            } else {
+                format!("{} dBm", self.tx_power)
+            },
+            "",
+        );
+        let rssi_s = ParseBytesNode::new(start_byte + 13, 1).format(
+            "RSSI",
+            self.rssi as u8,
+            &get_rssi_name(self.rssi),
+            "",
+        );
+        let interval_s = ParseBytesNode::new(start_byte + 14, 2).format(
+            "Periodic_Advertising_Interval",
+            self.periodic_advertising_interval,
+            &if self.periodic_advertising_interval == 0 {
+                "No periodic advertising".to_string()
+            } else {
+                format!("{:.2} ms", self.periodic_advertising_interval as f32 * 1.25)
+            },
+            "",
+        );
+        let direct_address_type_s = ParseBytesNode::new(start_byte + 16, 1).format(
+            "Direct_Address_Type",
+            self.direct_address_type,
+            get_address_type_name(self.direct_address_type),
+            "",
+        );
+        let direct_address_s = ParseBytesNode::new(start_byte + 17, 6).format(
+            "Direct_Address",
+            self.direct_address,
+            "",
+            "",
+        );
+        let data_len_s =
+            ParseBytesNode::new(start_byte + 23, 1).format("Data_Length", data_len as u8, "", "");
+        let data_s = format!(
+            r#""Data": {{{}}}"#,
+            self.advertising_data.as_json(start_byte + 24)
+        );
+        format!(
+            "{}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}",
+            event_type_s,
+            address_type_s,
+            address_s,
+            primary_phy_s,
+            secondary_phy_s,
+            sid_s,
+            tx_power_s,
+            rssi_s,
+            interval_s,
+            direct_address_type_s,
+            direct_address_s,
+            data_len_s,
+            data_s
+        )
+    }
+}
+
+// subevent 0x0e
+#[derive(Debug, PartialEq)]
+struct LePeriodicAdvertisingSyncEstablished {
+    status: u8,
+    sync_handle: u16,
+    advertising_sid: u8,
+    advertiser_address_type: u8,
+    advertiser_address: BdAddr,
+    advertiser_phy: u8,
+    periodic_advertising_interval: u16,
+    advertiser_clock_accuracy: u8,
+}
+
+impl ParseNodeOpt for LePeriodicAdvertisingSyncEstablished {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 15 {
+            None
+        } else {
+            Some(LePeriodicAdvertisingSyncEstablished {
+                status: data[0],
+                sync_handle: u16::from_le_bytes([data[1], data[2]]),
+                advertising_sid: data[3],
+                advertiser_address_type: data[4],
+                advertiser_address: BdAddr::new(&data[5..]),
+                advertiser_phy: data[11],
+                periodic_advertising_interval: u16::from_le_bytes([data[12], data[13]]),
+                advertiser_clock_accuracy: data[14],
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let status_s = status_as_json(start_byte, self.status);
+        let sync_handle_s =
+            ParseBytesNode::new(start_byte + 1, 2).format("Sync_Handle", self.sync_handle, "", "");
+        let sid_s = ParseBytesNode::new(start_byte + 3, 1).format(
+            "Advertising_SID",
+            self.advertising_sid,
+            "",
+            "",
+        );
+        let address_type_s = ParseBytesNode::new(start_byte + 4, 1).format(
+            "Advertiser_Address_Type",
+            self.advertiser_address_type,
+            get_address_type_name(self.advertiser_address_type),
+            "",
+        );
+        let address_s = ParseBytesNode::new(start_byte + 5, 6).format(
+            "Advertiser_Address",
+            self.advertiser_address,
+            "",
+            "",
+        );
+        let phy_s = ParseBytesNode::new(start_byte + 11, 1).format(
+            "Advertiser_PHY",
+            self.advertiser_phy,
+            get_phy_name(self.advertiser_phy),
+            "",
+        );
+        let interval_s = ParseBytesNode::new(start_byte + 12, 2).format(
+            "Periodic_Advertising_Interval",
+            self.periodic_advertising_interval,
+            &format!("{:.2} ms", self.periodic_advertising_interval as f32 * 1.25),
+            "",
+        );
+        let clock_accuracy_s = ParseBytesNode::new(start_byte + 14, 1).format(
+            "Advertiser_Clock_Accuracy",
+            self.advertiser_clock_accuracy,
+            get_clock_accuracy_name(self.advertiser_clock_accuracy),
+            "",
+        );
+        format!(
+            "{}, {}, {}, {}, {}, {}, {}, {}",
+            status_s,
+            sync_handle_s,
+            sid_s,
+            address_type_s,
+            address_s,
+            phy_s,
+            interval_s,
+            clock_accuracy_s
+        )
+    }
+}
+
+// subevent 0x0f
+#[derive(Debug, PartialEq)]
+struct LePeriodicAdvertisingReport {
+    sync_handle: u16,
+    tx_power: i8,
+    rssi: i8,
+    cte_type: u8,
+    data_status: u8,
+    data: Vec<u8>,
+    advertising_data: AdvertisingData,
+}
+
+impl ParseNodeOpt for LePeriodicAdvertisingReport {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 7 || data.len() < 7 + data[6] as usize {
+            None
+        } else {
+            let data_len = data[6] as usize;
+            Some(LePeriodicAdvertisingReport {
+                sync_handle: u16::from_le_bytes([data[0], data[1]]),
+                tx_power: data[2] as i8,
+                rssi: data[3] as i8,
+                cte_type: data[4],
+                data_status: data[5],
+                data: data[7..7 + data_len].to_vec(),
+                advertising_data: AdvertisingData::new(&data[7..7 + data_len], args),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let data_len = self.data.len() as u16;
+        let cte_type_name_s = match self.cte_type {
+            0x00 => "AoA Constant Tone Extension",
+            0x01 => "AoD Constant Tone Extension with 1 us slots",
+            0x02 => "AoD Constant Tone Extension with 2 us slots",
+            0xff => "No Constant Tone Extension",
+            _ => "Reserved for future use",
+        };
+        let data_status_name_s = match self.data_status {
+            0x00 => "Data complete",
+            0x01 => "Data incomplete, more data to come",
+            0x02 => "Data incomplete, data truncated, no more to come",
+            _ => "Reserved for future use",
+        };
+        let sync_handle_s =
+            ParseBytesNode::new(start_byte, 2).format("Sync_Handle", self.sync_handle, "", "");
+        let tx_power_s = ParseBytesNode::new(start_byte + 2, 1).format(
+            "TX_Power",
+            self.tx_power as u8,
+            &if self.tx_power == 0x7f {
+                "Not available".to_string()
+            } else {
+                format!("{} dBm", self.tx_power)
+            },
+            "",
+        );
+        let rssi_s = ParseBytesNode::new(start_byte + 3, 1).format(
+            "RSSI",
+            self.rssi as u8,
+            &get_rssi_name(self.rssi),
+            "",
+        );
+        let cte_type_s = ParseBytesNode::new(start_byte + 4, 1).format(
+            "CTE_Type",
+            self.cte_type,
+            cte_type_name_s,
+            "",
+        );
+        let data_status_s = ParseBytesNode::new(start_byte + 5, 1).format(
+            "Data_Status",
+            self.data_status,
+            data_status_name_s,
+            "",
+        );
+        let data_len_s =
+            ParseBytesNode::new(start_byte + 6, 1).format("Data_Length", data_len as u8, "", "");
+        let data_s = format!(
+            r#""Data": {{{}}}"#,
+            self.advertising_data.as_json(start_byte + 7)
+        );
+        format!(
+            "{}, {}, {}, {}, {}, {}, {}",
+            sync_handle_s, tx_power_s, rssi_s, cte_type_s, data_status_s, data_len_s, data_s
+        )
+    }
+}
+
+// subevent 0x12
+#[derive(Debug, PartialEq)]
+struct LeAdvertisingSetTerminated {
+    status: u8,
+    advertising_handle: u8,
+    connection_handle: u16,
+    num_completed_extended_advertising_events: u8,
+}
+
+impl ParseNodeOpt for LeAdvertisingSetTerminated {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 5 {
+            None
+        } else {
+            Some(LeAdvertisingSetTerminated {
+                status: data[0],
+                advertising_handle: data[1],
+                connection_handle: u16::from_le_bytes([data[2], data[3]]),
+                num_completed_extended_advertising_events: data[4],
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let status_s = status_as_json(start_byte, self.status);
+        let advertising_handle_s = ParseBytesNode::new(start_byte + 1, 1).format(
+            "Advertising_Handle",
+            self.advertising_handle,
+            "",
+            "",
+        );
+        let handle_s = handle_as_json(start_byte + 2, self.connection_handle);
+        let num_events_s = ParseBytesNode::new(start_byte + 4, 1).format(
+            "Num_Completed_Extended_Advertising_Events",
+            self.num_completed_extended_advertising_events,
+            "",
+            "",
+        );
+        format!(
+            "{}, {}, {}, {}",
+            status_s, advertising_handle_s, handle_s, num_events_s
+        )
+    }
+}
+
+// subevent 0x19
+#[derive(Debug, PartialEq)]
+struct LeCisEstablished {
+    status: u8,
+    connection_handle: u16,
+    cig_sync_delay: u32,
+    cis_sync_delay: u32,
+    transport_latency_c_to_p: u32,
+    transport_latency_p_to_c: u32,
+    phy_c_to_p: u8,
+    phy_p_to_c: u8,
+    nse: u8,
+    bn_c_to_p: u8,
+    bn_p_to_c: u8,
+    ft_c_to_p: u8,
+    ft_p_to_c: u8,
+    max_pdu_c_to_p: u16,
+    max_pdu_p_to_c: u16,
+    iso_interval: u16,
+}
+
+impl ParseNodeOpt for LeCisEstablished {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 28 {
+            None
+        } else {
+            Some(LeCisEstablished {
+                status: data[0],
+                connection_handle: u16::from_le_bytes([data[1], data[2]]),
+                cig_sync_delay: u24_from_le_bytes(&data[3..]),
+                cis_sync_delay: u24_from_le_bytes(&data[6..]),
+                transport_latency_c_to_p: u24_from_le_bytes(&data[9..]),
+                transport_latency_p_to_c: u24_from_le_bytes(&data[12..]),
+                phy_c_to_p: data[15],
+                phy_p_to_c: data[16],
+                nse: data[17],
+                bn_c_to_p: data[18],
+                bn_p_to_c: data[19],
+                ft_c_to_p: data[20],
+                ft_p_to_c: data[21],
+                max_pdu_c_to_p: u16::from_le_bytes([data[22], data[23]]),
+                max_pdu_p_to_c: u16::from_le_bytes([data[24], data[25]]),
+                iso_interval: u16::from_le_bytes([data[26], data[27]]),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let us = |value: u32| format!("{} us", value);
+        let fields = [
+            status_as_json(start_byte, self.status),
+            handle_as_json(start_byte + 1, self.connection_handle),
+            ParseBytesNode::new(start_byte + 3, 3).format(
+                "CIG_Sync_Delay",
+                self.cig_sync_delay,
+                &us(self.cig_sync_delay),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 6, 3).format(
+                "CIS_Sync_Delay",
+                self.cis_sync_delay,
+                &us(self.cis_sync_delay),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 9, 3).format(
+                "Transport_Latency_C_To_P",
+                self.transport_latency_c_to_p,
+                &us(self.transport_latency_c_to_p),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 12, 3).format(
+                "Transport_Latency_P_To_C",
+                self.transport_latency_p_to_c,
+                &us(self.transport_latency_p_to_c),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 15, 1).format(
+                "PHY_C_To_P",
+                self.phy_c_to_p,
+                get_phy_name(self.phy_c_to_p),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 16, 1).format(
+                "PHY_P_To_C",
+                self.phy_p_to_c,
+                get_phy_name(self.phy_p_to_c),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 17, 1).format("NSE", self.nse, "", ""),
+            ParseBytesNode::new(start_byte + 18, 1).format("BN_C_To_P", self.bn_c_to_p, "", ""),
+            ParseBytesNode::new(start_byte + 19, 1).format("BN_P_To_C", self.bn_p_to_c, "", ""),
+            ParseBytesNode::new(start_byte + 20, 1).format("FT_C_To_P", self.ft_c_to_p, "", ""),
+            ParseBytesNode::new(start_byte + 21, 1).format("FT_P_To_C", self.ft_p_to_c, "", ""),
+            ParseBytesNode::new(start_byte + 22, 2).format(
+                "Max_PDU_C_To_P",
+                self.max_pdu_c_to_p,
+                "",
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 24, 2).format(
+                "Max_PDU_P_To_C",
+                self.max_pdu_p_to_c,
+                "",
+                "",
+            ),
+            iso_interval_as_json(start_byte + 26, self.iso_interval),
+        ];
+        fields.join(", ")
+    }
+}
+
+// subevent 0x1a
+#[derive(Debug, PartialEq)]
+struct LeCisRequest {
+    acl_connection_handle: u16,
+    cis_connection_handle: u16,
+    cig_id: u8,
+    cis_id: u8,
+}
+
+impl ParseNodeOpt for LeCisRequest {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 6 {
+            None
+        } else {
+            Some(LeCisRequest {
+                acl_connection_handle: u16::from_le_bytes([data[0], data[1]]),
+                cis_connection_handle: u16::from_le_bytes([data[2], data[3]]),
+                cig_id: data[4],
+                cis_id: data[5],
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let acl_handle_s = ParseBytesNode::new(start_byte, 2).format(
+            "ACL_Connection_Handle",
+            self.acl_connection_handle,
+            "",
+            "",
+        );
+        let cis_handle_s = ParseBytesNode::new(start_byte + 2, 2).format(
+            "CIS_Connection_Handle",
+            self.cis_connection_handle,
+            "",
+            "",
+        );
+        let cig_id_s = ParseBytesNode::new(start_byte + 4, 1).format("CIG_ID", self.cig_id, "", "");
+        let cis_id_s = ParseBytesNode::new(start_byte + 5, 1).format("CIS_ID", self.cis_id, "", "");
+        format!(
+            "{}, {}, {}, {}",
+            acl_handle_s, cis_handle_s, cig_id_s, cis_id_s
+        )
+    }
+}
+
+// subevent 0x1b
+#[derive(Debug, PartialEq)]
+struct LeCreateBigComplete {
+    status: u8,
+    big_handle: u8,
+    big_sync_delay: u32,
+    transport_latency_big: u32,
+    phy: u8,
+    nse: u8,
+    bn: u8,
+    pto: u8,
+    irc: u8,
+    max_pdu: u16,
+    iso_interval: u16,
+    connection_handles: Vec<u16>,
+}
+
+impl ParseNodeOpt for LeCreateBigComplete {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 18 || data.len() < 18 + data[17] as usize * 2 {
+            None
+        } else {
+            Some(LeCreateBigComplete {
+                status: data[0],
+                big_handle: data[1],
+                big_sync_delay: u24_from_le_bytes(&data[2..]),
+                transport_latency_big: u24_from_le_bytes(&data[5..]),
+                phy: data[8],
+                nse: data[9],
+                bn: data[10],
+                pto: data[11],
+                irc: data[12],
+                max_pdu: u16::from_le_bytes([data[13], data[14]]),
+                iso_interval: u16::from_le_bytes([data[15], data[16]]),
+                connection_handles: bis_handles(&data[18..], data[17]),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let fields = [
+            status_as_json(start_byte, self.status),
+            ParseBytesNode::new(start_byte + 1, 1).format("BIG_Handle", self.big_handle, "", ""),
+            ParseBytesNode::new(start_byte + 2, 3).format(
+                "BIG_Sync_Delay",
+                self.big_sync_delay,
+                &format!("{} us", self.big_sync_delay),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 5, 3).format(
+                "Transport_Latency_BIG",
+                self.transport_latency_big,
+                &format!("{} us", self.transport_latency_big),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 8, 1).format(
+                "PHY",
+                self.phy,
+                get_phy_name(self.phy),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 9, 1).format("NSE", self.nse, "", ""),
+            ParseBytesNode::new(start_byte + 10, 1).format("BN", self.bn, "", ""),
+            ParseBytesNode::new(start_byte + 11, 1).format("PTO", self.pto, "", ""),
+            ParseBytesNode::new(start_byte + 12, 1).format("IRC", self.irc, "", ""),
+            ParseBytesNode::new(start_byte + 13, 2).format("Max_PDU", self.max_pdu, "", ""),
+            iso_interval_as_json(start_byte + 15, self.iso_interval),
+            bis_handles_as_json(start_byte + 17, &self.connection_handles),
+        ];
+        fields.join(", ")
+    }
+}
+
+// subevent 0x1d
+#[derive(Debug, PartialEq)]
+struct LeBigSyncEstablished {
+    status: u8,
+    big_handle: u8,
+    transport_latency_big: u32,
+    nse: u8,
+    bn: u8,
+    pto: u8,
+    irc: u8,
+    max_pdu: u16,
+    iso_interval: u16,
+    connection_handles: Vec<u16>,
+}
+
+impl ParseNodeOpt for LeBigSyncEstablished {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 14 || data.len() < 14 + data[13] as usize * 2 {
+            None
+        } else {
+            Some(LeBigSyncEstablished {
+                status: data[0],
+                big_handle: data[1],
+                transport_latency_big: u24_from_le_bytes(&data[2..]),
+                nse: data[5],
+                bn: data[6],
+                pto: data[7],
+                irc: data[8],
+                max_pdu: u16::from_le_bytes([data[9], data[10]]),
+                iso_interval: u16::from_le_bytes([data[11], data[12]]),
+                connection_handles: bis_handles(&data[14..], data[13]),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let fields = [
+            status_as_json(start_byte, self.status),
+            ParseBytesNode::new(start_byte + 1, 1).format("BIG_Handle", self.big_handle, "", ""),
+            ParseBytesNode::new(start_byte + 2, 3).format(
+                "Transport_Latency_BIG",
+                self.transport_latency_big,
+                &format!("{} us", self.transport_latency_big),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 5, 1).format("NSE", self.nse, "", ""),
+            ParseBytesNode::new(start_byte + 6, 1).format("BN", self.bn, "", ""),
+            ParseBytesNode::new(start_byte + 7, 1).format("PTO", self.pto, "", ""),
+            ParseBytesNode::new(start_byte + 8, 1).format("IRC", self.irc, "", ""),
+            ParseBytesNode::new(start_byte + 9, 2).format("Max_PDU", self.max_pdu, "", ""),
+            iso_interval_as_json(start_byte + 11, self.iso_interval),
+            bis_handles_as_json(start_byte + 13, &self.connection_handles),
+        ];
+        fields.join(", ")
+    }
+}
+
+fn connection_head_as_json(
+    start_byte: u16,
+    status: u8,
+    connection_handle: u16,
+    role: u8,
+    peer_address_type: u8,
+    peer_address: BdAddr,
+) -> String {
+    let role_name_s = match role {
+        0x00 => "Central",
+        0x01 => "Peripheral",
+        _ => "Reserved for future use",
+    };
+    let status_s = status_as_json(start_byte, status);
+    let handle_s = handle_as_json(start_byte + 1, connection_handle);
+    let role_s = ParseBytesNode::new(start_byte + 3, 1).format("Role", role, role_name_s, "");
+    let peer_address_type_s = ParseBytesNode::new(start_byte + 4, 1).format(
+        "Peer_Address_Type",
+        peer_address_type,
+        get_address_type_name(peer_address_type),
+        "",
+    );
+    let peer_address_s =
+        ParseBytesNode::new(start_byte + 5, 6).format("Peer_Address", peer_address, "", "");
+    format!(
+        "{}, {}, {}, {}, {}",
+        status_s, handle_s, role_s, peer_address_type_s, peer_address_s
+    )
+}
+
+fn connection_tail_as_json(
+    start_byte: u16,
+    connection_interval: u16,
+    peripheral_latency: u16,
+    supervision_timeout: u16,
+    central_clock_accuracy: u8,
+) -> String {
+    let interval_s = ParseBytesNode::new(start_byte, 2).format(
+        "Connection_Interval",
+        connection_interval,
+        &format!("{:.2} ms", connection_interval as f32 * 1.25),
+        "",
+    );
+    let latency_s = ParseBytesNode::new(start_byte + 2, 2).format(
+        "Peripheral_Latency",
+        peripheral_latency,
+        "",
+        "",
+    );
+    let timeout_s = ParseBytesNode::new(start_byte + 4, 2).format(
+        "Supervision_Timeout",
+        supervision_timeout,
+        &format!("{} ms", supervision_timeout as u32 * 10),
+        "",
+    );
+    let clock_accuracy_s = ParseBytesNode::new(start_byte + 6, 1).format(
+        "Central_Clock_Accuracy",
+        central_clock_accuracy,
+        get_clock_accuracy_name(central_clock_accuracy),
+        "",
+    );
+    format!(
+        "{}, {}, {}, {}",
+        interval_s, latency_s, timeout_s, clock_accuracy_s
+    )
+}
+
+fn status_as_json(start_byte: u16, status: u8) -> String {
+    ParseBytesNode::new(start_byte, 1).format("Status", status, get_error_code_name(status), "")
+}
+
+fn handle_as_json(start_byte: u16, connection_handle: u16) -> String {
+    ParseBytesNode::new(start_byte, 2).format("Connection_Handle", connection_handle, "", "")
+}
+
+fn iso_interval_as_json(start_byte: u16, iso_interval: u16) -> String {
+    ParseBytesNode::new(start_byte, 2).format(
+        "ISO_Interval",
+        iso_interval,
+        &format!("{:.2} ms", iso_interval as f32 * 1.25),
+        "",
+    )
+}
+
+fn bis_handles(data: &[u8], num_bis: u8) -> Vec<u16> {
+    data.chunks_exact(2)
+        .take(num_bis as usize)
+        .map(|handle| u16::from_le_bytes([handle[0], handle[1]]))
+        .collect()
+}
+
+fn bis_handles_as_json(start_byte: u16, connection_handles: &[u16]) -> String {
+    let mut json = ParseBytesNode::new(start_byte, 1).format(
+        "Num_BIS",
+        connection_handles.len() as u8,
+        "",
+        "",
+    );
+    for (i, handle) in connection_handles.iter().enumerate() {
+        json.push_str(", ");
+        json.push_str(
+            &ParseBytesNode::new(start_byte + 1 + i as u16 * 2, 2).format(
+                &format!("Connection_Handle[{}]", i),
+                handle,
+                "",
+                "",
+            ),
+        );
+    }
+    json
+}
+
+fn u24_from_le_bytes(data: &[u8]) -> u32 {
+    data[0] as u32 | (data[1] as u32) << 8 | (data[2] as u32) << 16
+}
+
+fn get_subevent_name(code: u8) -> &'static str {
+    match code {
+        0x01 => "HCI_LE_Connection_Complete",
+        0x02 => "HCI_LE_Advertising_Report",
+        0x03 => "HCI_LE_Connection_Update_Complete",
+        0x04 => "HCI_LE_Read_Remote_Features_Complete",
+        0x05 => "HCI_LE_Long_Term_Key_Request",
+        0x06 => "HCI_LE_Remote_Connection_Parameter_Request",
+        0x07 => "HCI_LE_Data_Length_Change",
+        0x08 => "HCI_LE_Read_Local_P-256_Public_Key_Complete",
+        0x09 => "HCI_LE_Generate_DHKey_Complete",
+        0x0a => "HCI_LE_Enhanced_Connection_Complete [v1]",
+        0x0b => "HCI_LE_Directed_Advertising_Report",
+        0x0c => "HCI_LE_PHY_Update_Complete",
+        0x0d => "HCI_LE_Extended_Advertising_Report",
+        0x0e => "HCI_LE_Periodic_Advertising_Sync_Established",
+        0x0f => "HCI_LE_Periodic_Advertising_Report",
+        0x10 => "HCI_LE_Periodic_Advertising_Sync_Lost",
+        0x11 => "HCI_LE_Scan_Timeout",
+        0x12 => "HCI_LE_Advertising_Set_Terminated",
+        0x13 => "HCI_LE_Scan_Request_Received",
+        0x14 => "HCI_LE_Channel_Selection_Algorithm",
+        0x19 => "HCI_LE_CIS_Established",
+        0x1a => "HCI_LE_CIS_Request",
+        0x1b => "HCI_LE_Create_BIG_Complete",
+        0x1c => "HCI_LE_Terminate_BIG_Complete",
+        0x1d => "HCI_LE_BIG_Sync_Established",
+        0x1e => "HCI_LE_BIG_Sync_Lost",
+        0x29 => "HCI_LE_Enhanced_Connection_Complete [v2]",
+        _ => "Unknown",
+    }
+}
+
+fn get_address_type_name(address_type: u8) -> &'static str {
+    match address_type {
+        0x00 => "Public Device Address",
+        0x01 => "Random Device Address",
+        0x02 => "Public Identity Address",
+        0x03 => "Random (static) Identity Address",
+        0xff => "No address provided (anonymous advertisement)",
+        _ => "Reserved for future use",
+    }
+}
+
+fn get_phy_name(phy: u8) -> &'static str {
+    match phy {
+        0x01 => "LE 1M",
+        0x02 => "LE 2M",
+        0x03 => "LE Coded",
+        _ => "Reserved for future use",
+    }
+}
+
+fn get_clock_accuracy_name(clock_accuracy: u8) -> &'static str {
+    match clock_accuracy {
+        0x00 => "500 ppm",
+        0x01 => "250 ppm",
+        0x02 => "150 ppm",
+        0x03 => "100 ppm",
+        0x04 => "75 ppm",
+        0x05 => "50 ppm",
+        0x06 => "30 ppm",
+        0x07 => "20 ppm",
+        _ => "Reserved for future use",
+    }
+}
+
+fn get_rssi_name(rssi: i8) -> String {
+    if rssi == 0x7f {
+        "Not available".to_string()
+    } else {
+        format!("{} dBm", rssi)
+    }
+}
+
+fn get_le_features_name(features: u64) -> String {
+    const FEATURE_NAMES: [&str; 16] = [
+        "LE Encryption",
+        "Connection Parameters Request Procedure",
+        "Extended Reject Indication",
+        "Peripheral-initiated Features Exchange",
+        "LE Ping",
+        "LE Data Packet Length Extension",
+        "LL Privacy",
+        "Extended Scanner Filter Policies",
+        "LE 2M PHY",
+        "Stable Modulation Index - Transmitter",
+        "Stable Modulation Index - Receiver",
+        "LE Coded PHY",
+        "LE Extended Advertising",
+        "LE Periodic Advertising",
+        "Channel Selection Algorithm #2",
+        "LE Power Class 1",
+    ];
+    FEATURE_NAMES
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| features & (1 << bit) != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<&str>>()
+        .join(" | ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BdAddr;
+
+    use crate::test_utils::parse_packets;
+
+    // LE Connection Complete with its 18 parameter bytes and one byte short,
+    // Enhanced Connection Complete v1 with 30 bytes, v2 with 33 and v2 with
+    // the 30 bytes of v1, then an Advertising Report of two reports
+    const PACKETS: [&str; 6] = [
+        "04 3e 13 01 00 40 00 00 00 11 22 33 44 55 66 18 00 00 00 c8 00 00",
+        "04 3e 12 01 00 40 00 00 00 11 22 33 44 55 66 18 00 00 00 c8 00",
+        "04 3e 1f 0a 00 41 00 01 01 01 02 03 04 05 06 a1 a2 a3 a4 a5 a6 b1 b2 b3 b4 b5 b6 28 00 00 00 2c 01 05",
+        "04 3e 22 29 00 42 00 01 01 01 02 03 04 05 06 a1 a2 a3 a4 a5 a6 b1 b2 b3 b4 b5 b6 28 00 00 00 2c 01 05 01 ff ff",
+        "04 3e 1f 29 00 43 00 01 01 01 02 03 04 05 06 a1 a2 a3 a4 a5 a6 b1 b2 b3 b4 b5 b6 28 00 00 00 2c 01 05",
+        "04 3e 19 02 02 00 00 11 22 33 44 55 66 03 02 01 06 c4 04 01 a1 a2 a3 a4 a5 a6 00 b0",
+    ];
+
+    #[test]
+    fn connection_complete_needs_all_its_parameters() {
+        let (json, args) = parse_packets(&PACKETS);
+        assert!(json[0].contains(r#""Peer_Address": ["66:55:44:33:22:11", "", "B(9, 6)", ""], "Connection_Interval": [0x18, "30.00 ms", "B(15, 2)", ""], "Peripheral_Latency": [0x0, "", "B(17, 2)", ""], "Supervision_Timeout": [0xc8, "2000 ms", "B(19, 2)", ""], "Central_Clock_Accuracy": [0x0, "500 ppm", "B(21, 1)", ""]}"#));
+        assert!(json[1]
+            .ends_with(r#""Subevent_Code": [0x1, "HCI_LE_Connection_Complete", "B(3, 1)", ""]}"#));
+        assert_eq!(
+            args.hci_arg.remote_device(0x40),
+            Some(BdAddr::new(&[0x11, 0x22, 0x33, 0x44, 0x55, 0x66]))
+        );
+    }
+
+    #[test]
+    fn enhanced_connection_complete_v2_is_longer() {
+        let (json, args) = parse_packets(&PACKETS);
+        assert!(json[2].contains(r#""Peer_Resolvable_Private_Address": ["B6:B5:B4:B3:B2:B1", "", "B(21, 6)", ""], "Connection_Interval": [0x28, "50.00 ms", "B(27, 2)", ""], "Peripheral_Latency": [0x0, "", "B(29, 2)", ""], "Supervision_Timeout": [0x12c, "3000 ms", "B(31, 2)", ""], "Central_Clock_Accuracy": [0x5, "50 ppm", "B(33, 1)", ""]}"#));
+        assert!(json[3].contains(r#""Central_Clock_Accuracy": [0x5, "50 ppm", "B(33, 1)", ""], "Advertising_Handle": [0x1, "", "B(34, 1)", ""], "Sync_Handle": [0xffff, "", "B(35, 2)", ""]}"#));
+        assert!(json[4].ends_with(
+            r#""Subevent_Code": [0x29, "HCI_LE_Enhanced_Connection_Complete [v2]", "B(3, 1)", ""]}"#
+        ));
+        assert!(args.hci_arg.remote_device(0x41).is_some());
+        assert!(args.hci_arg.remote_device(0x42).is_some());
+        assert!(args.hci_arg.remote_device(0x43).is_none());
+    }
+
+    #[test]
+    fn advertising_reports_follow_each_other() {
+        let (json, _) = parse_packets(&PACKETS);
+        assert!(json[5].contains(r#""Data_Length": [0x3, "", "B(13, 1)", ""], "Data": {"AD Structure[0]": {"Length": [0x2, "", "B(14, 1)", ""]"#));
+        assert!(json[5].contains(r#""RSSI": [0xc4, "-60 dBm", "B(17, 1)", ""]}, "Report[1]": {"Event_Type": [0x4, "SCAN_RSP", "B(18, 1)", ""], "Address_Type": [0x1, "Random Device Address", "B(19, 1)", ""], "Address": ["A6:A5:A4:A3:A2:A1", "", "B(20, 6)", ""], "Data_Length": [0x0, "", "B(26, 1)", ""], "Data": {}, "RSSI": [0xb0, "-80 dBm", "B(27, 1)", ""]}"#));
+    }
+}
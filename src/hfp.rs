@@ -0,0 +1,978 @@
+use crate::bytes_to_string;
+use crate::HostStack;
+use crate::ParseNodeA;
+use crate::SummaryKeys;
+
+use crate::ParseBytesNode;
+
+#[derive(Default, Debug)]
+pub struct HFPArg {
+    connections: Vec<HfpConnection>,
+    // state of the connections that have ended, kept for the summary
+    closed: Vec<HfpConnection>,
+}
+
+impl HFPArg {
+    /// Returns the state of the AT stream on `dlci` of `acl_handle`, creating
+    /// it on first use.
+    fn connection(&mut self, acl_handle: u16, dlci: u8) -> &mut HfpConnection {
+        let index =
+            match self.connections.iter().position(|connection| {
+                connection.acl_handle == acl_handle && connection.dlci == dlci
+            }) {
+                Some(index) => index,
+                None => {
+                    self.connections.push(HfpConnection {
+                        acl_handle,
+                        dlci,
+                        ..Default::default()
+                    });
+                    self.connections.len() - 1
+                }
+            };
+        &mut self.connections[index]
+    }
+
+    /// Closes the service level connections of `acl_handle`.
+    pub(crate) fn disconnect(&mut self, acl_handle: u16) {
+        let (ended, open): (Vec<HfpConnection>, Vec<HfpConnection>) =
+            std::mem::take(&mut self.connections)
+                .into_iter()
+                .partition(|connection| connection.acl_handle == acl_handle);
+        self.connections = open;
+        self.closed.extend(ended);
+    }
+
+    pub(crate) fn summary(&self) -> String {
+        let mut keys = SummaryKeys::default();
+        self.closed
+            .iter()
+            .chain(self.connections.iter())
+            .map(|connection| connection.summary(&mut keys))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+/// What the AT stream of one RFCOMM DLC has revealed of the service level
+/// connection between a hands-free unit and its audio gateway.
+#[derive(Debug, Default)]
+struct HfpConnection {
+    acl_handle: u16,
+    dlci: u8,
+    // bytes after the last line terminator, per RFCOMM side
+    buffers: [Vec<u8>; 2],
+    hf_features: Option<u32>,
+    ag_features: Option<u32>,
+    hf_codecs: Vec<u32>,
+    codec: Option<u32>,
+    // the AG indicators in +CIND order, with their last known value
+    indicators: Vec<(String, Option<u32>)>,
+    // the HF indicators the HF supports, with whether the AG enabled them
+    hf_indicators: Vec<(u32, Option<bool>)>,
+    speaker_gain: Option<u32>,
+    microphone_gain: Option<u32>,
+    // the HF command an OK or ERROR answers
+    pending: Option<String>,
+    indicator_reporting: bool,
+    call_hold_retrieved: bool,
+    hf_indicators_retrieved: bool,
+}
+
+impl HfpConnection {
+    fn has_features(&self, hf_bit: u32, ag_bit: u32) -> bool {
+        self.hf_features.unwrap_or_default() & (1 << hf_bit) != 0
+            && self.ag_features.unwrap_or_default() & (1 << ag_bit) != 0
+    }
+
+    /// The service level connection is up once the last of the procedures
+    /// both sides' features call for has been answered with OK.
+    fn is_established(&self) -> bool {
+        self.indicator_reporting
+            && (!self.has_features(1, 0) || self.call_hold_retrieved)
+            && (!self.has_features(8, 10) || self.hf_indicators_retrieved)
+    }
+
+    fn indicator_name(&self, index: u32) -> &str {
+        index
+            .checked_sub(1)
+            .and_then(|index| self.indicators.get(index as usize))
+            .map(|(name, _)| name.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Follows one complete line, before it is decoded.
+    fn update(&mut self, line: &AtLine) {
+        let values = || line.params.iter().map(|param| param.parse::<u32>().ok());
+        match (line.name.as_str(), line.kind, line.is_command) {
+            ("+BRSF", _, true) => self.hf_features = values().next().flatten(),
+            ("+BRSF", _, false) => self.ag_features = values().next().flatten(),
+            ("+BAC", _, true) => self.hf_codecs = values().flatten().collect(),
+            ("+BCS", _, _) => self.codec = values().next().flatten(),
+            ("+CIND", _, false) if line.params.iter().any(|param| param.starts_with('(')) => {
+                self.indicators = line
+                    .params
+                    .iter()
+                    .filter_map(|param| param.split('"').nth(1))
+                    .map(|name| (name.to_string(), None))
+                    .collect();
+            }
+            ("+CIND", _, false) => {
+                for (indicator, value) in self.indicators.iter_mut().zip(values()) {
+                    indicator.1 = value;
+                }
+            }
+            ("+CIEV", _, false) => {
+                let mut values = values();
+                if let (Some(Some(index)), Some(value)) = (values.next(), values.next()) {
+                    if let Some(indicator) = index
+                        .checked_sub(1)
+                        .and_then(|index| self.indicators.get_mut(index as usize))
+                    {
+                        indicator.1 = value;
+                    }
+                }
+            }
+            ("+BIND", AtKind::Set, true) => {
+                self.hf_indicators = values().flatten().map(|id| (id, None)).collect();
+            }
+            ("+BIND", _, false) if !line.params.iter().any(|param| param.starts_with('(')) => {
+                let mut values = values();
+                if let (Some(Some(id)), Some(Some(state))) = (values.next(), values.next()) {
+                    match self
+                        .hf_indicators
+                        .iter_mut()
+                        .find(|(known, _)| *known == id)
+                    {
+                        Some(indicator) => indicator.1 = Some(state != 0),
+                        None => self.hf_indicators.push((id, Some(state != 0))),
+                    }
+                }
+            }
+            ("+VGS", _, _) => self.speaker_gain = values().next().flatten(),
+            ("+VGM", _, _) => self.microphone_gain = values().next().flatten(),
+            ("OK", _, false) => match self.pending.take().as_deref() {
+                Some("+CMER") => self.indicator_reporting = true,
+                Some("+CHLD=?") => self.call_hold_retrieved = true,
+                Some("+BIND?") => self.hf_indicators_retrieved = true,
+                _ => {}
+            },
+            ("ERROR" | "+CME ERROR", _, false) => self.pending = None,
+            (_, _, true) => {
+                self.pending = Some(match line.kind {
+                    AtKind::Test => format!("{}=?", line.name),
+                    AtKind::Read => format!("{}?", line.name),
+                    _ => line.name.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn summary(&self, keys: &mut SummaryKeys) -> String {
+        let mut fields = Vec::new();
+        if self.hf_features.is_some() || self.ag_features.is_some() {
+            fields.push(format!(
+                r#""Service Level Connection": "{}""#,
+                match self.is_established() {
+                    true => "Established",
+                    false => "Connecting",
+                }
+            ));
+        }
+        if let Some(features) = self.hf_features {
+            fields.push(format!(
+                r#""HF Supported Features": "{}""#,
+                get_features_name(features, &HF_FEATURES)
+            ));
+        }
+        if let Some(features) = self.ag_features {
+            fields.push(format!(
+                r#""AG Supported Features": "{}""#,
+                get_features_name(features, &AG_FEATURES)
+            ));
+        }
+        if !self.hf_codecs.is_empty() {
+            fields.push(format!(
+                r#""HF Codecs": "{}""#,
+                self.hf_codecs
+                    .iter()
+                    .map(|codec| get_codec_name(*codec))
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ));
+        }
+        if let Some(codec) = self.codec {
+            fields.push(format!(r#""Selected Codec": "{}""#, get_codec_name(codec)));
+        }
+        if !self.indicators.is_empty() {
+            let indicators_s = self
+                .indicators
+                .iter()
+                .map(|(name, value)| match value {
+                    Some(value) => format!(r#""{}": {}"#, bytes_to_string(name.as_bytes()), value),
+                    None => format!(r#""{}": null"#, bytes_to_string(name.as_bytes())),
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            fields.push(format!(r#""Indicators": {{{}}}"#, indicators_s));
+        }
+        if !self.hf_indicators.is_empty() {
+            let indicators_s = self
+                .hf_indicators
+                .iter()
+                .map(|(id, enabled)| {
+                    format!(
+                        r#""{}": "{}""#,
+                        get_hf_indicator_name(*id),
+                        match enabled {
+                            Some(true) => "Enabled",
+                            Some(false) => "Disabled",
+                            None => "Supported",
+                        }
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            fields.push(format!(r#""HF Indicators": {{{}}}"#, indicators_s));
+        }
+        if let Some(gain) = self.speaker_gain {
+            fields.push(format!(r#""Speaker Gain": {}"#, gain));
+        }
+        if let Some(gain) = self.microphone_gain {
+            fields.push(format!(r#""Microphone Gain": {}"#, gain));
+        }
+        let key = keys.unique(format!(
+            "Connection {:#x} DLCI {}",
+            self.acl_handle, self.dlci
+        ));
+        format!(r#""{}": {{{}}}"#, key, fields.join(", "))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AtKind {
+    // ATA, AT+CHUP, and responses
+    Action,
+    Set,
+    Read,
+    Test,
+}
+
+/// One command from the hands-free unit or one response from the audio
+/// gateway, split into its name and parameters.
+#[derive(Debug, PartialEq)]
+struct AtLine {
+    text: String,
+    is_command: bool,
+    name: String,
+    kind: AtKind,
+    params: Vec<String>,
+}
+
+impl AtLine {
+    fn new(text: String) -> Self {
+        let upper = text.to_ascii_uppercase();
+        let is_command = upper.starts_with("AT");
+        let (name, kind, params) = match is_command {
+            true => {
+                let body = &text[2..];
+                // basic commands such as ATA and ATD carry no '+'
+                let name_end = match body.starts_with('+') {
+                    true => body.find(['=', '?']).unwrap_or(body.len()),
+                    false => body.chars().next().map_or(0, char::len_utf8),
+                };
+                let rest = &body[name_end..];
+                let (kind, params) = match rest {
+                    "=?" => (AtKind::Test, ""),
+                    "?" => (AtKind::Read, ""),
+                    _ if rest.starts_with('=') => (AtKind::Set, &rest[1..]),
+                    "" => (AtKind::Action, ""),
+                    _ => (AtKind::Set, rest),
+                };
+                (body[..name_end].to_ascii_uppercase(), kind, params)
+            }
+            false => match text.split_once(':') {
+                Some((name, params)) if name.starts_with('+') => {
+                    (name.to_ascii_uppercase(), AtKind::Action, params.trim())
+                }
+                _ => (upper.clone(), AtKind::Action, ""),
+            },
+        };
+        AtLine {
+            is_command,
+            name,
+            kind,
+            params: split_params(params),
+            text,
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match (self.name.as_str(), self.is_command, self.kind) {
+            ("+BRSF", true, _) => "HF Supported Features",
+            ("+BRSF", false, _) => "AG Supported Features",
+            ("+CIND", true, AtKind::Test) => "Retrieve Indicator Mapping",
+            ("+CIND", true, _) => "Read Indicator Status",
+            ("+CIND", false, _) if self.params.iter().any(|param| param.starts_with('(')) => {
+                "Indicator Mapping"
+            }
+            ("+CIND", false, _) => "Indicator Status",
+            ("+CMER", _, _) => "Enable Indicator Status Reporting",
+            ("+CIEV", _, _) => "Indicator Event",
+            ("+CHLD", _, _) => "Call Hold and Multiparty Handling",
+            ("+BAC", _, _) => "Available Codecs",
+            ("+BCS", true, _) => "Codec Confirmation",
+            ("+BCS", false, _) => "Codec Selection",
+            ("+BCC", _, _) => "Codec Connection Request",
+            ("+BIA", _, _) => "Indicators Activation",
+            ("+CLCC", _, _) => "List Current Calls",
+            ("+CLIP", _, _) => "Calling Line Identification",
+            ("+CCWA", _, _) => "Call Waiting Notification",
+            ("A", true, _) => "Answer Call",
+            ("D", true, _) => "Dial",
+            ("+CHUP", _, _) => "Hang Up",
+            ("+BLDN", _, _) => "Redial Last Number",
+            ("+VGS", _, _) => "Speaker Gain",
+            ("+VGM", _, _) => "Microphone Gain",
+            ("+BIND", _, _) => "HF Indicators",
+            ("+BIEV", _, _) => "HF Indicator Value",
+            ("+NREC", _, _) => "Noise Reduction and Echo Canceling",
+            ("+BVRA", _, _) => "Voice Recognition Activation",
+            ("+BSIR", _, _) => "In-band Ring Tone",
+            ("+BTRH", _, _) => "Response and Hold",
+            ("+VTS", _, _) => "DTMF",
+            ("+CNUM", _, _) => "Subscriber Number Information",
+            ("+COPS", _, _) => "Network Operator",
+            ("+CMEE", _, _) => "Extended Audio Gateway Error Result Codes",
+            ("+CKPD", _, _) => "Headset Button Press",
+            ("+XAPL", _, _) => "Apple Device Information",
+            ("+IPHONEACCEV", _, _) => "Apple Accessory Event",
+            ("+APLEFM", _, _) => "Apple Siri Eyes Free Mode",
+            ("OK", false, _) => "OK",
+            ("ERROR", false, _) => "Error",
+            ("RING", false, _) => "Incoming Call Alert",
+            ("+CME ERROR", false, _) => "Extended Audio Gateway Error",
+            _ => "",
+        }
+    }
+
+    /// The parameters with their meaning.
+    fn fields(&self, connection: Option<&HfpConnection>) -> Vec<AtField> {
+        let number = |index: usize| {
+            self.params
+                .get(index)
+                .and_then(|param| param.parse::<u32>().ok())
+        };
+        let indicator_name = |index: u32| {
+            connection
+                .map(|connection| connection.indicator_name(index).to_string())
+                .unwrap_or_default()
+        };
+        let numbered = |key: &str, name: &dyn Fn(u32) -> String| {
+            self.params
+                .iter()
+                .enumerate()
+                .filter_map(|(i, param)| {
+                    let value = param.parse::<u32>().ok()?;
+                    Some((
+                        format!("{}[{}]", key, i),
+                        AtValue::Number(value),
+                        name(value),
+                    ))
+                })
+                .collect::<Vec<AtField>>()
+        };
+        let field = |key: &str, index: usize, alias: &dyn Fn(u32) -> String| {
+            number(index).map(|value| (key.to_string(), AtValue::Number(value), alias(value)))
+        };
+        let none = |_: u32| String::new();
+        match (self.name.as_str(), self.is_command) {
+            ("+BRSF", true) => field("Supported Features", 0, &|features| {
+                get_features_name(features, &HF_FEATURES)
+            })
+            .into_iter()
+            .collect(),
+            ("+BRSF", false) => field("Supported Features", 0, &|features| {
+                get_features_name(features, &AG_FEATURES)
+            })
+            .into_iter()
+            .collect(),
+            ("+CIND", false) if self.params.iter().any(|param| param.starts_with('(')) => self
+                .params
+                .iter()
+                .enumerate()
+                .map(|(i, param)| {
+                    let name = param.split('"').nth(1).unwrap_or_default();
+                    let range = param
+                        .rsplit('(')
+                        .next()
+                        .unwrap_or_default()
+                        .trim_end_matches(')');
+                    (
+                        format!("Indicator[{}]", i + 1),
+                        AtValue::Text(name.to_string()),
+                        format!("Values ({})", range),
+                    )
+                })
+                .collect(),
+            ("+CIND", false) => self
+                .params
+                .iter()
+                .enumerate()
+                .filter_map(|(i, param)| {
+                    let value = param.parse::<u32>().ok()?;
+                    let name = indicator_name(i as u32 + 1);
+                    Some((
+                        format!("Indicator[{}]", i + 1),
+                        AtValue::Number(value),
+                        get_indicator_value_name(&name, value),
+                    ))
+                })
+                .collect(),
+            ("+CIEV", false) => {
+                let name = number(0).map(indicator_name).unwrap_or_default();
+                [
+                    field("Indicator", 0, &|_| name.clone()),
+                    field("Value", 1, &|value| get_indicator_value_name(&name, value)),
+                ]
+                .into_iter()
+                .flatten()
+                .collect()
+            }
+            ("+CMER", true) => field("Indicator Reporting", 3, &|ind| {
+                match ind {
+                    0 => "Disabled",
+                    _ => "Enabled",
+                }
+                .to_string()
+            })
+            .into_iter()
+            .collect(),
+            ("+BAC", true) => numbered("Codec", &|codec| get_codec_name(codec).to_string()),
+            ("+BCS", _) => field("Codec", 0, &|codec| get_codec_name(codec).to_string())
+                .into_iter()
+                .collect(),
+            ("+BIA", true) => self
+                .params
+                .iter()
+                .enumerate()
+                .map(|(i, param)| {
+                    let state = match param.as_str() {
+                        "" => "Unchanged",
+                        "0" => "Deactivated",
+                        _ => "Activated",
+                    };
+                    (
+                        format!("Indicator[{}]", i + 1),
+                        AtValue::Text(param.clone()),
+                        match indicator_name(i as u32 + 1).as_str() {
+                            "" => state.to_string(),
+                            name => format!("{} {}", name, state),
+                        },
+                    )
+                })
+                .collect(),
+            ("+CLCC", false) => [
+                field("Call Index", 0, &none),
+                field("Direction", 1, &|direction| {
+                    match direction {
+                        0 => "Outgoing",
+                        _ => "Incoming",
+                    }
+                    .to_string()
+                }),
+                field("Status", 2, &|status| {
+                    get_call_status_name(status).to_string()
+                }),
+                field("Mode", 3, &|mode| {
+                    match mode {
+                        0 => "Voice",
+                        1 => "Data",
+                        2 => "Fax",
+                        _ => "",
+                    }
+                    .to_string()
+                }),
+                field("Multiparty", 4, &|mpty| (mpty != 0).to_string()),
+                self.params.get(5).map(|number| {
+                    (
+                        "Number".to_string(),
+                        AtValue::Text(number.trim_matches('"').to_string()),
+                        String::new(),
+                    )
+                }),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+            ("+CLIP", false) => [
+                self.params.first().map(|number| {
+                    (
+                        "Number".to_string(),
+                        AtValue::Text(number.trim_matches('"').to_string()),
+                        String::new(),
+                    )
+                }),
+                field("Type", 1, &|number_type| {
+                    match number_type {
+                        0x91 => "International",
+                        0x81 | 0x80 => "Unknown",
+                        _ => "",
+                    }
+                    .to_string()
+                }),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+            ("+VGS" | "+VGM", _) => field("Gain", 0, &|gain| format!("{} of 15", gain))
+                .into_iter()
+                .collect(),
+            ("+BIND", true) => {
+                numbered("HF Indicator", &|id| get_hf_indicator_name(id).to_string())
+            }
+            ("+BIND", false) if self.params.iter().any(|param| param.starts_with('(')) => {
+                numbered("HF Indicator", &|id| get_hf_indicator_name(id).to_string())
+            }
+            ("+BIND", false) => [
+                field("HF Indicator", 0, &|id| {
+                    get_hf_indicator_name(id).to_string()
+                }),
+                field("State", 1, &|state| {
+                    match state {
+                        0 => "Disabled",
+                        _ => "Enabled",
+                    }
+                    .to_string()
+                }),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+            ("+BIEV", true) => {
+                let id = number(0).unwrap_or_default();
+                [
+                    field("HF Indicator", 0, &|id| {
+                        get_hf_indicator_name(id).to_string()
+                    }),
+                    field("Value", 1, &|value| match id {
+                        0x0001 => match value {
+                            0 => "Disabled".to_string(),
+                            _ => "Enabled".to_string(),
+                        },
+                        0x0002 => format!("{}%", value),
+                        _ => String::new(),
+                    }),
+                ]
+                .into_iter()
+                .flatten()
+                .collect()
+            }
+            ("+APLEFM", true) => field("Eyes Free Mode", 0, &|mode| {
+                match mode {
+                    0 => "Disabled",
+                    _ => "Enabled",
+                }
+                .to_string()
+            })
+            .into_iter()
+            .collect(),
+            ("+CME ERROR", false) => field("Error Code", 0, &|code| {
+                get_cme_error_name(code).to_string()
+            })
+            .into_iter()
+            .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// A decoded parameter as key, value and alias.
+type AtField = (String, AtValue, String);
+
+#[derive(Debug, PartialEq)]
+enum AtValue {
+    Number(u32),
+    Text(String),
+}
+
+/// Splits parameters on the commas that are outside quotes and brackets.
+fn split_params(params: &str) -> Vec<String> {
+    if params.is_empty() {
+        return Vec::new();
+    }
+    let mut result = vec![String::new()];
+    let mut depth = 0;
+    let mut quoted = false;
+    for c in params.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth -= 1,
+            ',' if !quoted && depth == 0 => {
+                result.push(String::new());
+                continue;
+            }
+            _ => {}
+        }
+        result.last_mut().unwrap().push(c);
+    }
+    result
+        .iter()
+        .map(|param| param.trim().to_string())
+        .collect()
+}
+
+/// The longest partial line kept for the next frame. AT commands and
+/// results of HFP stay well within it.
+const MAX_LINE_LEN: usize = 512;
+
+/// The AT lines completed by one RFCOMM frame of a hands-free or headset
+/// DLC. Lines may start in an earlier frame; what is left after the last
+/// terminator waits for the next one.
+#[derive(Debug, PartialEq)]
+pub(crate) struct AtStream {
+    // the line, its decoded fields and where its end lies in the frame
+    lines: Vec<(AtLine, Vec<AtField>, u16, u16)>,
+    partial: Option<(String, u16, u16)>,
+    // the partial line grew past MAX_LINE_LEN and was not kept
+    dropped: bool,
+}
+
+impl ParseNodeA<(u8, usize)> for AtStream {
+    fn new(data: &[u8], args: Option<&mut HostStack>, (dlci, side): (u8, usize)) -> Self {
+        let mut connection = args.map(|args| {
+            let acl_handle = args.hci_arg.acl_handle();
+            args.hfp_arg.connection(acl_handle, dlci)
+        });
+        let mut buffer = connection
+            .as_deref_mut()
+            .map(|connection| std::mem::take(&mut connection.buffers[side]))
+            .unwrap_or_default();
+        let carried = buffer.len();
+        buffer.extend_from_slice(data);
+
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for (i, byte) in buffer.iter().enumerate() {
+            if *byte != b'\r' && *byte != b'\n' {
+                continue;
+            }
+            if i > start {
+                let line = AtLine::new(String::from_utf8_lossy(&buffer[start..i]).into_owned());
+                if let Some(connection) = connection.as_deref_mut() {
+                    connection.update(&line);
+                }
+                let fields = line.fields(connection.as_deref());
+                let frame_start = start.saturating_sub(carried);
+                lines.push((
+                    line,
+                    fields,
+                    frame_start as u16,
+                    (i - carried - frame_start) as u16,
+                ));
+            }
+            start = i + 1;
+        }
+        // a peer that never ends its line must not grow the buffer for good
+        let dropped = buffer.len() - start > MAX_LINE_LEN;
+        let partial = (start < buffer.len()).then(|| {
+            let frame_start = start.saturating_sub(carried);
+            (
+                String::from_utf8_lossy(&buffer[start..]).into_owned(),
+                frame_start as u16,
+                (buffer.len() - carried - frame_start) as u16,
+            )
+        });
+        if let Some(connection) = connection {
+            connection.buffers[side] = match dropped {
+                true => Vec::new(),
+                false => buffer[start..].to_vec(),
+            };
+        }
+        AtStream {
+            lines,
+            partial,
+            dropped,
+        }
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut fields = Vec::new();
+        for (i, (line, line_fields, offset, len)) in self.lines.iter().enumerate() {
+            let node = ParseBytesNode::new(start_byte + offset, *len);
+            let mut line_s = vec![node.format(
+                "Text",
+                bytes_to_string(line.text.as_bytes()),
+                line.description(),
+                "",
+            )];
+            line_s.extend(line_fields.iter().map(|(key, value, alias)| match value {
+                AtValue::Number(value) => node.format(key, value, alias, ""),
+                AtValue::Text(value) => {
+                    node.format(key, bytes_to_string(value.as_bytes()), alias, "")
+                }
+            }));
+            let key = match line.is_command {
+                true => "Command",
+                false => "Response",
+            };
+            fields.push(format!(r#""{}[{}]": {{{}}}"#, key, i, line_s.join(", ")));
+        }
+        if let Some((text, offset, len)) = &self.partial {
+            let (alias, error) = match self.dropped {
+                true => (
+                    "",
+                    format!("No line terminator in {} bytes, line dropped", MAX_LINE_LEN),
+                ),
+                false => ("Continues in the next frame", String::new()),
+            };
+            fields.push(ParseBytesNode::new(start_byte + offset, *len).format(
+                "Partial Line",
+                bytes_to_string(text.as_bytes()),
+                alias,
+                &error,
+            ));
+        }
+        format!(r#""AT": {{{}}}"#, fields.join(", "))
+    }
+}
+
+const HF_FEATURES: [&str; 12] = [
+    "EC and/or NR function",
+    "Three-way calling",
+    "CLI presentation capability",
+    "Voice recognition activation",
+    "Remote volume control",
+    "Enhanced call status",
+    "Enhanced call control",
+    "Codec negotiation",
+    "HF Indicators",
+    "eSCO S4 Settings Supported",
+    "Enhanced Voice Recognition Status",
+    "Voice Recognition Text",
+];
+
+const AG_FEATURES: [&str; 14] = [
+    "Three-way calling",
+    "EC and/or NR function",
+    "Voice recognition function",
+    "In-band ring tone capability",
+    "Attach a number to a voice tag",
+    "Ability to reject a call",
+    "Enhanced call status",
+    "Enhanced call control",
+    "Extended Error Result Codes",
+    "Codec negotiation",
+    "HF Indicators",
+    "eSCO S4 Settings Supported",
+    "Enhanced Voice Recognition Status",
+    "Voice Recognition Text",
+];
+
+fn get_features_name(features: u32, names: &[&str]) -> String {
+    names
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| features & (1 << bit) != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+fn get_codec_name(codec: u32) -> &'static str {
+    match codec {
+        0x01 => "CVSD",
+        0x02 => "mSBC",
+        0x03 => "LC3-SWB",
+        _ => "Reserved",
+    }
+}
+
+fn get_hf_indicator_name(id: u32) -> &'static str {
+    match id {
+        0x0001 => "Enhanced Safety",
+        0x0002 => "Battery Level",
+        _ => "Reserved",
+    }
+}
+
+/// Names the value of an AG indicator from the names +CIND gave them.
+fn get_indicator_value_name(name: &str, value: u32) -> String {
+    let meaning = match (name, value) {
+        ("service", 0) => "No Home/Roam network available",
+        ("service", 1) => "Home/Roam network available",
+        ("call", 0) => "No calls in progress",
+        ("call", 1) => "At least one call is in progress",
+        ("callsetup", 0) => "Not currently in call set up",
+        ("callsetup", 1) => "Incoming call process ongoing",
+        ("callsetup", 2) => "Outgoing call set up is ongoing",
+        ("callsetup", 3) => "Remote party being alerted in an outgoing call",
+        ("callheld", 0) => "No calls held",
+        ("callheld", 1) => "Call is placed on hold or active/held calls swapped",
+        ("callheld", 2) => "Call on hold, no active call",
+        ("roam", 0) => "Roaming is not active",
+        ("roam", 1) => "Roaming is active",
+        _ => "",
+    };
+    match meaning {
+        "" => name.to_string(),
+        meaning => format!("{}: {}", name, meaning),
+    }
+}
+
+fn get_call_status_name(status: u32) -> &'static str {
+    match status {
+        0 => "Active",
+        1 => "Held",
+        2 => "Dialing",
+        3 => "Alerting",
+        4 => "Incoming",
+        5 => "Waiting",
+        6 => "Call held by Response and Hold",
+        _ => "",
+    }
+}
+
+fn get_cme_error_name(code: u32) -> &'static str {
+    match code {
+        0 => "AG failure",
+        1 => "No connection to phone",
+        3 => "Operation not allowed",
+        4 => "Operation not supported",
+        5 => "PH-SIM PIN required",
+        10 => "SIM not inserted",
+        11 => "SIM PIN required",
+        12 => "SIM PUK required",
+        13 => "SIM failure",
+        14 => "SIM busy",
+        16 => "Incorrect password",
+        17 => "SIM PIN2 required",
+        18 => "SIM PUK2 required",
+        20 => "Memory full",
+        21 => "Invalid index",
+        23 => "Memory failure",
+        24 => "Text string too long",
+        25 => "Invalid characters in text string",
+        26 => "Dial string too long",
+        27 => "Invalid characters in dial string",
+        30 => "No network service",
+        31 => "Network Timeout",
+        32 => "Network not allowed - Emergency calls only",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AtStream;
+    use crate::test_utils::{l2cap_connect, parse_packets};
+    use crate::{str_to_array, HostStack, ParseNodeA};
+
+    // an SDP response on channel 0x0040 <-> 0x0041 giving a Handsfree
+    // record on RFCOMM 3
+    const SDP_RESPONSE: &str = "02 40 20 4d 00 49 00 40 00 07 00 01 00 44 00 41 35 3f 35 3d 09 00 01 35 06 19 11 1e 19 12 03 09 00 04 35 0c 35 03 19 01 00 35 05 19 00 03 08 03 09 00 09 35 08 35 06 19 11 1e 09 01 08 09 01 00 25 09 48 61 6e 64 73 66 72 65 65 09 03 11 09 00 10 00";
+
+    // RFCOMM on 0x0042 <-> 0x0043: the multiplexer and DLCI 6 open
+    const DLC_OPEN: [&str; 4] = [
+        "02 40 20 08 00 04 00 43 00 03 3f 01 1c",
+        "02 40 20 08 00 04 00 42 00 03 73 01 d7",
+        "02 40 20 08 00 04 00 43 00 1b 3f 01 d3",
+        "02 40 20 08 00 04 00 42 00 1b 73 01 18",
+    ];
+
+    // the service level connection: AT+BRSF split over two frames, the
+    // indicators and AT+CMER, each answered by the AG
+    const SLC: [&str; 9] = [
+        "02 40 20 0d 00 09 00 43 00 1b ef 0b 41 54 2b 42 52 8f",
+        "02 40 20 0e 00 0a 00 43 00 1b ef 0d 53 46 3d 31 36 0d 8f",
+        "02 40 20 1c 00 18 00 42 00 19 ef 29 0d 0a 2b 42 52 53 46 3a 20 35 31 32 0d 0a 0d 0a 4f 4b 0d 0a 55",
+        "02 40 20 12 00 0e 00 43 00 1b ef 15 41 54 2b 43 49 4e 44 3d 3f 0d 8f",
+        "02 40 20 39 00 35 00 42 00 19 ef 63 0d 0a 2b 43 49 4e 44 3a 20 28 22 63 61 6c 6c 22 2c 28 30 2c 31 29 29 2c 28 22 73 65 72 76 69 63 65 22 2c 28 30 2c 31 29 29 0d 0a 0d 0a 4f 4b 0d 0a 55",
+        "02 40 20 11 00 0d 00 43 00 1b ef 13 41 54 2b 43 49 4e 44 3f 0d 8f",
+        "02 40 20 1c 00 18 00 42 00 19 ef 29 0d 0a 2b 43 49 4e 44 3a 20 30 2c 31 0d 0a 0d 0a 4f 4b 0d 0a 55",
+        "02 40 20 18 00 14 00 43 00 1b ef 21 41 54 2b 43 4d 45 52 3d 33 2c 30 2c 30 2c 31 0d 8f",
+        "02 40 20 0e 00 0a 00 42 00 19 ef 0d 0d 0a 4f 4b 0d 0a 55",
+    ];
+
+    /// Parses `frames` on DLCI 6 and returns their JSON.
+    fn parse_on_dlc(frames: &[&str]) -> (Vec<String>, HostStack) {
+        let [sdp_request, sdp_response] = l2cap_connect(0x0040, 1, 0x0001, (0x0040, 0x0041));
+        let [rfcomm_request, rfcomm_response] = l2cap_connect(0x0040, 2, 0x0003, (0x0042, 0x0043));
+        let mut setup = vec![
+            sdp_request.as_str(),
+            &sdp_response,
+            SDP_RESPONSE,
+            &rfcomm_request,
+            &rfcomm_response,
+        ];
+        setup.extend_from_slice(&DLC_OPEN);
+        let (mut json, args) = parse_packets(&[&setup[..], frames].concat());
+        json.drain(..setup.len());
+        (json, args)
+    }
+
+    fn parse(packet: &str) -> String {
+        AtStream::new(&str_to_array(packet), None, (2, 0)).as_json(10)
+    }
+
+    #[test]
+    fn non_ascii_after_at() {
+        assert!(parse("41 54 c3 a9 0d")
+            .contains(r#""Command[0]": {"Text": ["ATé", "", "B(10, 4)", ""]}"#));
+    }
+
+    #[test]
+    fn command_and_response() {
+        // AT+BRSF=1023\r followed by \r\nOK\r\n
+        let json = parse("41 54 2b 42 52 53 46 3d 31 30 32 33 0d 0d 0a 4f 4b 0d 0a");
+        assert!(
+            json.contains(r#""Text": ["AT+BRSF=1023", "HF Supported Features", "B(10, 12)", ""]"#)
+        );
+        assert!(json.contains(r#""Supported Features": [0x3ff, "#));
+        assert!(json.contains(r#""Response[1]": {"Text": ["OK", "OK", "B(25, 2)", ""]}"#));
+    }
+
+    #[test]
+    fn command_split_over_frames() {
+        let (json, _) = parse_on_dlc(&SLC);
+        assert!(json[0].contains(
+            r#""AT": {"Partial Line": ["AT+BR", "Continues in the next frame", "B(12, 5)", ""]}"#
+        ));
+        assert!(json[1].contains(r#""AT": {"Command[0]": {"Text": ["AT+BRSF=16", "HF Supported Features", "B(12, 5)", ""], "Supported Features": [0x10, "Remote volume control", "B(12, 5)", ""]}}"#));
+        assert!(json[2].contains(
+            r#""Response[0]": {"Text": ["+BRSF: 512", "AG Supported Features", "B(14, 10)", ""]"#
+        ));
+    }
+
+    #[test]
+    fn slc_is_established_once_cmer_is_answered() {
+        let (_, args) = parse_on_dlc(&SLC[..SLC.len() - 1]);
+        assert!(args
+            .hfp_summary()
+            .contains(r#""Service Level Connection": "Connecting""#));
+        let (_, args) = parse_on_dlc(&SLC);
+        assert_eq!(
+            args.hfp_summary(),
+            r#"{"Connection 0x40 DLCI 6": {"Service Level Connection": "Established", "HF Supported Features": "Remote volume control", "AG Supported Features": "Codec negotiation", "Indicators": {"call": 0, "service": 1}}}"#
+        );
+    }
+
+    #[test]
+    fn unterminated_line_is_dropped() {
+        // 300 bytes of "A" twice, then AT+CIND?
+        let long_frame = format!(
+            "02 40 20 35 01 31 01 43 00 1b ef 58 02{} 8f",
+            " 41".repeat(300)
+        );
+        let (json, _) = parse_on_dlc(&[&long_frame, &long_frame, SLC[5]]);
+        assert!(json[0].contains(r#""Continues in the next frame", "B(13, 300)", """#));
+        assert!(
+            json[1].contains(r#""B(13, 300)", "No line terminator in 512 bytes, line dropped"]"#)
+        );
+        assert!(json[2].contains(
+            r#""Command[0]": {"Text": ["AT+CIND?", "Read Indicator Status", "B(12, 8)", ""]}"#
+        ));
+    }
+}
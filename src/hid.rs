@@ -0,0 +1,845 @@
+use crate::ParseBitsNode;
+use crate::ParseBytesNode;
+
+/// One short or long item of a report descriptor.
+#[derive(Debug, Clone, Copy)]
+struct Item {
+    offset: usize,
+    len: usize,
+    // 3 marks a long item
+    item_type: u8,
+    tag: u8,
+    size: usize,
+    value: u32,
+}
+
+impl Item {
+    fn truncated(&self) -> bool {
+        self.item_type != 3 && self.len - 1 < self.size
+    }
+
+    /// Minimum, maximum and exponent items are signed.
+    fn signed(&self) -> i32 {
+        match self.size {
+            1 => self.value as u8 as i8 as i32,
+            2 => self.value as u16 as i16 as i32,
+            _ => self.value as i32,
+        }
+    }
+}
+
+fn items(data: &[u8]) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let prefix = data[offset];
+        // long items carry their size in the next byte and are never defined
+        if prefix == 0xfe {
+            let size = data.get(offset + 1).copied().unwrap_or_default() as usize;
+            let end = (offset + 3 + size).min(data.len());
+            items.push(Item {
+                offset,
+                len: end - offset,
+                item_type: 3,
+                tag: 0xf,
+                size,
+                value: 0,
+            });
+            offset = end;
+            continue;
+        }
+        let size = match prefix & 0x03 {
+            3 => 4,
+            size => size as usize,
+        };
+        let end = (offset + 1 + size).min(data.len());
+        let value = data[offset + 1..end]
+            .iter()
+            .rev()
+            .fold(0u32, |value, byte| (value << 8) | *byte as u32);
+        items.push(Item {
+            offset,
+            len: end - offset,
+            item_type: (prefix >> 2) & 0x03,
+            tag: prefix >> 4,
+            size,
+            value,
+        });
+        offset = end;
+    }
+    items
+}
+
+/// Walks a HID report descriptor item by item. Each item is shown with its
+/// data, the alias naming the item and what the data means.
+pub(crate) fn report_descriptor_as_json(data: &[u8], start_byte: u16) -> String {
+    let mut fields = Vec::new();
+    let mut usage_page = 0;
+    for (i, item) in items(data).iter().enumerate() {
+        let node = ParseBytesNode::new(start_byte + item.offset as u16, item.len as u16);
+        let key = format!("Item[{}]", i);
+        if item.item_type == 3 {
+            let bytes = &data[item.offset..item.offset + item.len];
+            fields.push(node.format(&key, bytes, "Long Item", ""));
+            continue;
+        }
+        let (value, signed) = (item.value, item.signed());
+        let (name, detail) = match (item.item_type, item.tag) {
+            (0, 0x8) => ("Input", get_main_item_flags(value)),
+            (0, 0x9) => ("Output", get_main_item_flags(value)),
+            (0, 0xb) => ("Feature", get_main_item_flags(value)),
+            (0, 0xa) => ("Collection", get_collection_name(value).to_string()),
+            (0, 0xc) => ("End Collection", String::new()),
+            (1, 0x0) => {
+                usage_page = value;
+                ("Usage Page", get_usage_page_name(value).to_string())
+            }
+            (1, 0x1) => ("Logical Minimum", signed.to_string()),
+            (1, 0x2) => ("Logical Maximum", signed.to_string()),
+            (1, 0x3) => ("Physical Minimum", signed.to_string()),
+            (1, 0x4) => ("Physical Maximum", signed.to_string()),
+            (1, 0x5) => ("Unit Exponent", signed.to_string()),
+            (1, 0x6) => ("Unit", String::new()),
+            (1, 0x7) => ("Report Size", value.to_string()),
+            (1, 0x8) => ("Report ID", value.to_string()),
+            (1, 0x9) => ("Report Count", value.to_string()),
+            (1, 0xa) => ("Push", String::new()),
+            (1, 0xb) => ("Pop", String::new()),
+            (2, 0x0) => ("Usage", get_usage_name(usage_page, value).to_string()),
+            (2, 0x1) => (
+                "Usage Minimum",
+                get_usage_name(usage_page, value).to_string(),
+            ),
+            (2, 0x2) => (
+                "Usage Maximum",
+                get_usage_name(usage_page, value).to_string(),
+            ),
+            (2, 0x3) => ("Designator Index", String::new()),
+            (2, 0x4) => ("Designator Minimum", String::new()),
+            (2, 0x5) => ("Designator Maximum", String::new()),
+            (2, 0x7) => ("String Index", String::new()),
+            (2, 0x8) => ("String Minimum", String::new()),
+            (2, 0x9) => ("String Maximum", String::new()),
+            (2, 0xa) => ("Delimiter", String::new()),
+            _ => ("Reserved", String::new()),
+        };
+        let alias = match detail.is_empty() {
+            true => name.to_string(),
+            false => format!("{} ({})", name, detail),
+        };
+        let error = match item.truncated() {
+            true => "Truncated",
+            false => "",
+        };
+        fields.push(node.format(&key, value, &alias, error));
+    }
+    fields.join(", ")
+}
+
+/// The descriptor a device in Boot Protocol Mode is assumed to have: the
+/// boot keyboard as report 1 and the boot mouse as report 2.
+const BOOT_DESCRIPTOR: [u8; 117] = [
+    0x05, 0x01, 0x09, 0x06, 0xa1, 0x01, 0x85, 0x01, 0x05, 0x07, 0x19, 0xe0, 0x29, 0xe7, 0x15, 0x00,
+    0x25, 0x01, 0x75, 0x01, 0x95, 0x08, 0x81, 0x02, 0x95, 0x01, 0x75, 0x08, 0x81, 0x01, 0x95, 0x05,
+    0x75, 0x01, 0x05, 0x08, 0x19, 0x01, 0x29, 0x05, 0x91, 0x02, 0x95, 0x01, 0x75, 0x03, 0x91, 0x01,
+    0x95, 0x06, 0x75, 0x08, 0x15, 0x00, 0x25, 0x65, 0x05, 0x07, 0x19, 0x00, 0x29, 0x65, 0x81, 0x00,
+    0xc0, 0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0x85, 0x02, 0x09, 0x01, 0xa1, 0x00, 0x05, 0x09, 0x19,
+    0x01, 0x29, 0x03, 0x15, 0x00, 0x25, 0x01, 0x95, 0x03, 0x75, 0x01, 0x81, 0x02, 0x95, 0x01, 0x75,
+    0x05, 0x81, 0x01, 0x05, 0x01, 0x09, 0x30, 0x09, 0x31, 0x15, 0x81, 0x25, 0x7f, 0x75, 0x08, 0x95,
+    0x02, 0x81, 0x06, 0xc0, 0xc0,
+];
+
+/// The layout of the reports a report descriptor declares: one field per
+/// Input, Output or Feature item, with the global and local items in effect
+/// for it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct ReportDescriptor {
+    fields: Vec<ReportField>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ReportField {
+    // numbered as in HIDP: 1 input, 2 output, 3 feature
+    report_type: u8,
+    report_id: u8,
+    // the usage of the top level collection the field is in
+    application: u32,
+    // counted from the end of the report ID
+    bit_offset: usize,
+    report_size: usize,
+    report_count: usize,
+    flags: u32,
+    // extended usages, the usage page in the upper 16 bits
+    usages: Vec<u32>,
+    usage_range: Option<(u32, u32)>,
+    logical_minimum: i32,
+    logical_maximum: i32,
+}
+
+/// The global items, as saved by Push and restored by Pop.
+#[derive(Debug, Clone, Default)]
+struct GlobalItems {
+    usage_page: u32,
+    logical_minimum: i32,
+    logical_maximum: Option<Item>,
+    report_size: usize,
+    report_id: u8,
+    report_count: usize,
+}
+
+impl ReportDescriptor {
+    pub(crate) fn new(data: &[u8]) -> Self {
+        let mut fields = Vec::new();
+        let mut globals = GlobalItems::default();
+        let mut stack = Vec::new();
+        let mut usages = Vec::new();
+        let (mut usage_minimum, mut usage_maximum) = (None, None);
+        let mut collections = Vec::new();
+        let mut application = 0;
+        // bits used so far per report type and report ID
+        let mut report_lengths: Vec<((u8, u8), usize)> = Vec::new();
+        for item in items(data) {
+            let extended = |usage: u32| match item.size {
+                4 => usage,
+                _ => (globals.usage_page << 16) | usage,
+            };
+            match (item.item_type, item.tag) {
+                (0, 0x8 | 0x9 | 0xb) => {
+                    let report_type = match item.tag {
+                        0x8 => 1,
+                        0x9 => 2,
+                        _ => 3,
+                    };
+                    let key = (report_type, globals.report_id);
+                    let index = match report_lengths.iter().position(|(k, _)| *k == key) {
+                        Some(index) => index,
+                        None => {
+                            report_lengths.push((key, 0));
+                            report_lengths.len() - 1
+                        }
+                    };
+                    // the maximum is unsigned unless the minimum is negative
+                    let logical_maximum = globals
+                        .logical_maximum
+                        .map(|maximum| match globals.logical_minimum < 0 {
+                            true => maximum.signed(),
+                            false => maximum.value as i32,
+                        })
+                        .unwrap_or_default();
+                    fields.push(ReportField {
+                        report_type,
+                        report_id: globals.report_id,
+                        application,
+                        bit_offset: report_lengths[index].1,
+                        report_size: globals.report_size,
+                        report_count: globals.report_count,
+                        flags: item.value,
+                        usages: std::mem::take(&mut usages),
+                        usage_range: usage_minimum.zip(usage_maximum),
+                        logical_minimum: globals.logical_minimum,
+                        logical_maximum,
+                    });
+                    report_lengths[index].1 += globals.report_size * globals.report_count;
+                }
+                (0, 0xa) => {
+                    if collections.is_empty() {
+                        application = usages.first().copied().unwrap_or_default();
+                    }
+                    collections.push(item.value);
+                }
+                (0, 0xc) => {
+                    collections.pop();
+                }
+                (1, 0x0) => globals.usage_page = item.value,
+                (1, 0x1) => globals.logical_minimum = item.signed(),
+                (1, 0x2) => globals.logical_maximum = Some(item),
+                (1, 0x7) => globals.report_size = item.value as usize,
+                (1, 0x8) => globals.report_id = item.value as u8,
+                (1, 0x9) => globals.report_count = item.value as usize,
+                (1, 0xa) => stack.push(globals.clone()),
+                (1, 0xb) => globals = stack.pop().unwrap_or_default(),
+                (2, 0x0) => usages.push(extended(item.value)),
+                (2, 0x1) => usage_minimum = Some(extended(item.value)),
+                (2, 0x2) => usage_maximum = Some(extended(item.value)),
+                _ => {}
+            }
+            // local items only last until the next main item
+            if item.item_type == 0 {
+                usages.clear();
+                (usage_minimum, usage_maximum) = (None, None);
+            }
+        }
+        ReportDescriptor { fields }
+    }
+
+    pub(crate) fn boot() -> Self {
+        ReportDescriptor::new(&BOOT_DESCRIPTOR)
+    }
+
+    fn uses_report_ids(&self) -> bool {
+        self.fields.iter().any(|field| field.report_id != 0)
+    }
+
+    /// Decodes a report of `report_type` into one value per usage: variable
+    /// fields by the usage they carry, array fields by the usage each
+    /// element selects. The report ID leads `data` when the descriptor
+    /// declares any. Returns nothing for a report the descriptor doesn't
+    /// declare.
+    pub(crate) fn decode(&self, report_type: u8, data: &[u8]) -> Option<Report> {
+        let (report_id, body) = match self.uses_report_ids() {
+            true => {
+                let (report_id, body) = data.split_first()?;
+                (Some(*report_id), body)
+            }
+            false => (None, data),
+        };
+        let declared: Vec<&ReportField> = self
+            .fields
+            .iter()
+            .filter(|field| {
+                field.report_type == report_type && field.report_id == report_id.unwrap_or(0)
+            })
+            .collect();
+        if declared.is_empty() {
+            return None;
+        }
+        let mut values: Vec<ReportValue> = Vec::new();
+        let mut keys: Vec<String> = Vec::new();
+        let mut truncated = false;
+        for field in declared {
+            // constant fields are padding
+            if field.flags & 0x01 != 0 || field.report_size == 0 || field.report_size > 32 {
+                continue;
+            }
+            for i in 0..field.report_count {
+                let bit_offset = field.bit_offset + i * field.report_size;
+                let Some(value) = read_bits(body, bit_offset, field.report_size) else {
+                    truncated = true;
+                    break;
+                };
+                let (key, alias) = match field.flags & 0x02 != 0 {
+                    true => {
+                        let usage = field.variable_usage(i);
+                        (
+                            usage
+                                .map(usage_to_string)
+                                .unwrap_or(format!("Value[{}]", i)),
+                            field.variable_alias(usage, value),
+                        )
+                    }
+                    false => (
+                        format!("{}[{}]", field.array_key(), i),
+                        match field.array_usage(value) {
+                            Some(usage) if usage & 0xffff != 0 => usage_to_string(usage),
+                            _ => "None".to_string(),
+                        },
+                    ),
+                };
+                let repeats = keys.iter().filter(|known| **known == key).count();
+                keys.push(key.clone());
+                values.push(ReportValue {
+                    key: match repeats {
+                        0 => key,
+                        repeats => format!("{}[{}]", key, repeats),
+                    },
+                    value,
+                    alias,
+                    bit_offset,
+                    size: field.report_size,
+                });
+            }
+        }
+        Some(Report {
+            report_id,
+            values,
+            truncated,
+        })
+    }
+
+    /// Lists the reports by type and ID, with the usages each one carries.
+    pub(crate) fn summary(&self) -> String {
+        let mut reports: Vec<(u8, u8)> = Vec::new();
+        for field in &self.fields {
+            if !reports.contains(&(field.report_type, field.report_id)) {
+                reports.push((field.report_type, field.report_id));
+            }
+        }
+        reports
+            .iter()
+            .map(|(report_type, report_id)| {
+                let fields: Vec<&ReportField> = self
+                    .fields
+                    .iter()
+                    .filter(|field| {
+                        field.report_type == *report_type && field.report_id == *report_id
+                    })
+                    .collect();
+                let key = match report_id {
+                    0 => format!("{} Report", get_report_type_name(*report_type)),
+                    _ => format!(
+                        "{} Report {}",
+                        get_report_type_name(*report_type),
+                        report_id
+                    ),
+                };
+                let contents = fields
+                    .iter()
+                    .filter(|field| field.flags & 0x01 == 0)
+                    .map(|field| field.description())
+                    .collect::<Vec<String>>()
+                    .join("; ");
+                let bits: usize = fields
+                    .iter()
+                    .map(|field| field.report_size * field.report_count)
+                    .sum();
+                format!(
+                    r#""{}": "{}: {} ({} bytes)""#,
+                    key,
+                    usage_to_string(fields[0].application),
+                    contents,
+                    bits.div_ceil(8)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+/// A report decoded with its report descriptor.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Report {
+    report_id: Option<u8>,
+    values: Vec<ReportValue>,
+    // the report is shorter than the descriptor declares
+    truncated: bool,
+}
+
+#[derive(Debug, PartialEq)]
+struct ReportValue {
+    key: String,
+    value: u32,
+    alias: String,
+    // counted from the end of the report ID
+    bit_offset: usize,
+    size: usize,
+}
+
+impl Report {
+    /// One node per usage, `data` being the report this was decoded from.
+    pub(crate) fn as_json(&self, data: &[u8], start_byte: u16) -> String {
+        let mut fields = Vec::new();
+        let (body, body_start) = match self.report_id {
+            Some(report_id) => {
+                fields.push(ParseBytesNode::new(start_byte, 1).format(
+                    "Report ID",
+                    report_id,
+                    "",
+                    "",
+                ));
+                (&data[1..], start_byte + 1)
+            }
+            None => (data, start_byte),
+        };
+        for value in self.values.iter() {
+            let start = body_start + (value.bit_offset / 8) as u16;
+            fields.push(match (value.bit_offset % 8, value.size % 8) {
+                (0, 0) => ParseBytesNode::new(start, (value.size / 8) as u16).format(
+                    &value.key,
+                    value.value,
+                    &value.alias,
+                    "",
+                ),
+                (start_bit, _) => ParseBitsNode::new(
+                    start,
+                    (start_bit + value.size).div_ceil(8) as u16,
+                    start_bit as u8,
+                    value.size as u8,
+                )
+                .format(&value.key, value.value, &value.alias, ""),
+            });
+        }
+        if self.truncated {
+            fields.push(ParseBytesNode::new(body_start, body.len() as u16).format(
+                "Report Data",
+                body,
+                "",
+                "Report shorter than the descriptor declares",
+            ));
+        }
+        fields.join(", ")
+    }
+}
+
+impl ReportField {
+    /// The usage of element `i` of a variable field. The last usage repeats
+    /// when there are fewer usages than elements.
+    fn variable_usage(&self, i: usize) -> Option<u32> {
+        match self.usage_range {
+            Some((minimum, maximum)) if self.usages.is_empty() => {
+                Some((minimum + i as u32).min(maximum))
+            }
+            _ => self.usages.get(i).or(self.usages.last()).copied(),
+        }
+    }
+
+    /// The usage an array element selects, nothing for an index out of the
+    /// logical range.
+    fn array_usage(&self, value: u32) -> Option<u32> {
+        let index = value as i64 - self.logical_minimum as i64;
+        if value as i64 > self.logical_maximum as i64 || index < 0 {
+            return None;
+        }
+        match self.usage_range {
+            Some((minimum, maximum)) => {
+                Some(minimum + index as u32).filter(|usage| *usage <= maximum)
+            }
+            None => self.usages.get(index as usize).copied(),
+        }
+    }
+
+    fn array_key(&self) -> &'static str {
+        let usage = self
+            .usage_range
+            .map(|(minimum, _)| minimum)
+            .or(self.usages.first().copied())
+            .unwrap_or_default();
+        match usage >> 16 {
+            0x07 => "Key",
+            0x09 => "Button",
+            0x0c => "Consumer Control",
+            _ => "Usage",
+        }
+    }
+
+    fn variable_alias(&self, usage: Option<u32>, value: u32) -> String {
+        let page = usage.unwrap_or_default() >> 16;
+        match (page, self.report_size) {
+            (0x07 | 0x09, 1) => ["Released", "Pressed"][value as usize].to_string(),
+            (0x08, 1) => ["Off", "On"][value as usize].to_string(),
+            _ if self.logical_minimum < 0 => {
+                let shift = 32 - self.report_size as u32;
+                (((value << shift) as i32) >> shift).to_string()
+            }
+            _ => value.to_string(),
+        }
+    }
+
+    /// Reads like "X, Y (2 x 8 bits, Relative)".
+    fn description(&self) -> String {
+        let usages = match (self.usage_range, self.usages.is_empty()) {
+            (Some((minimum, maximum)), true) => format!(
+                "{} to {}",
+                usage_to_string(minimum),
+                usage_to_string(maximum)
+            ),
+            _ => {
+                let mut names: Vec<String> = Vec::new();
+                for name in self.usages.iter().map(|usage| usage_to_string(*usage)) {
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+                names.join(", ")
+            }
+        };
+        let mut attributes = vec![format!("{} x {} bits", self.report_count, self.report_size)];
+        if self.flags & 0x02 == 0 {
+            attributes.push("Array".to_string());
+        }
+        if self.flags & 0x04 != 0 {
+            attributes.push("Relative".to_string());
+        }
+        format!("{} ({})", usages, attributes.join(", "))
+    }
+}
+
+/// Reads `len` bits starting at `bit_offset`, least significant bit first
+/// as HID lays reports out.
+fn read_bits(data: &[u8], bit_offset: usize, len: usize) -> Option<u32> {
+    if bit_offset + len > data.len() * 8 {
+        return None;
+    }
+    Some((0..len).fold(0, |value, i| {
+        let bit = bit_offset + i;
+        value | ((((data[bit / 8] >> (bit % 8)) & 0x01) as u32) << i)
+    }))
+}
+
+/// Names an extended usage, falling back to its usage page and ID.
+fn usage_to_string(usage: u32) -> String {
+    let (usage_page, id) = (usage >> 16, usage & 0xffff);
+    match (usage_page, get_usage_name(usage_page, id)) {
+        (0x09, _) if id != 0 => format!("Button {}", id),
+        (_, "") => match get_usage_page_name(usage_page) {
+            "" => format!("Usage {:#06x}:{:#06x}", usage_page, id),
+            page_name => format!("{} {:#04x}", page_name, id),
+        },
+        (_, name) => name.to_string(),
+    }
+}
+
+pub(crate) fn get_report_type_name(report_type: u8) -> &'static str {
+    match report_type {
+        0x00 => "Other",
+        0x01 => "Input",
+        0x02 => "Output",
+        0x03 => "Feature",
+        _ => "Reserved",
+    }
+}
+
+fn get_main_item_flags(value: u32) -> String {
+    [
+        ["Data", "Constant"],
+        ["Array", "Variable"],
+        ["Absolute", "Relative"],
+    ]
+    .iter()
+    .enumerate()
+    .map(|(bit, names)| names[(value >> bit) as usize & 0x01])
+    .collect::<Vec<&str>>()
+    .join(", ")
+}
+
+fn get_collection_name(value: u32) -> &'static str {
+    match value {
+        0x00 => "Physical",
+        0x01 => "Application",
+        0x02 => "Logical",
+        0x03 => "Report",
+        0x04 => "Named Array",
+        0x05 => "Usage Switch",
+        0x06 => "Usage Modifier",
+        0x80..=0xff => "Vendor-defined",
+        _ => "Reserved",
+    }
+}
+
+fn get_usage_page_name(usage_page: u32) -> &'static str {
+    match usage_page {
+        0x01 => "Generic Desktop",
+        0x02 => "Simulation Controls",
+        0x03 => "VR Controls",
+        0x04 => "Sport Controls",
+        0x05 => "Game Controls",
+        0x06 => "Generic Device Controls",
+        0x07 => "Keyboard/Keypad",
+        0x08 => "LED",
+        0x09 => "Button",
+        0x0a => "Ordinal",
+        0x0b => "Telephony Device",
+        0x0c => "Consumer",
+        0x0d => "Digitizers",
+        0x0f => "Physical Input Device",
+        0x14 => "Auxiliary Display",
+        0x20 => "Sensors",
+        0x84 => "Power Device",
+        0x85 => "Battery System",
+        0xff00..=0xffff => "Vendor-defined",
+        _ => "",
+    }
+}
+
+fn get_usage_name(usage_page: u32, usage: u32) -> &'static str {
+    match (usage_page, usage) {
+        (0x01, 0x01) => "Pointer",
+        (0x01, 0x02) => "Mouse",
+        (0x01, 0x04) => "Joystick",
+        (0x01, 0x05) => "Game Pad",
+        (0x01, 0x06) => "Keyboard",
+        (0x01, 0x07) => "Keypad",
+        (0x01, 0x08) => "Multi-axis Controller",
+        (0x01, 0x30) => "X",
+        (0x01, 0x31) => "Y",
+        (0x01, 0x32) => "Z",
+        (0x01, 0x33) => "Rx",
+        (0x01, 0x34) => "Ry",
+        (0x01, 0x35) => "Rz",
+        (0x01, 0x38) => "Wheel",
+        (0x01, 0x39) => "Hat Switch",
+        (0x01, 0x80) => "System Control",
+        (0x01, 0x81) => "System Power Down",
+        (0x01, 0x82) => "System Sleep",
+        (0x01, 0x83) => "System Wake Up",
+        (0x01, 0x90) => "D-pad Up",
+        (0x01, 0x91) => "D-pad Down",
+        (0x01, 0x92) => "D-pad Right",
+        (0x01, 0x93) => "D-pad Left",
+        (0x07, _) => get_keyboard_usage_name(usage),
+        (0x08, 0x01) => "Num Lock",
+        (0x08, 0x02) => "Caps Lock",
+        (0x08, 0x03) => "Scroll Lock",
+        (0x08, 0x04) => "Compose",
+        (0x08, 0x05) => "Kana",
+        (0x0c, 0x00) => "Unassigned",
+        (0x0c, 0x01) => "Consumer Control",
+        (0x0c, 0x30) => "Power",
+        (0x0c, 0x40) => "Menu",
+        (0x0c, 0x41) => "Menu Pick",
+        (0x0c, 0x42) => "Menu Up",
+        (0x0c, 0x43) => "Menu Down",
+        (0x0c, 0x44) => "Menu Left",
+        (0x0c, 0x45) => "Menu Right",
+        (0x0c, 0x46) => "Menu Escape",
+        (0x0c, 0x9c) => "Channel Increment",
+        (0x0c, 0x9d) => "Channel Decrement",
+        (0x0c, 0xb0) => "Play",
+        (0x0c, 0xb1) => "Pause",
+        (0x0c, 0xb2) => "Record",
+        (0x0c, 0xb3) => "Fast Forward",
+        (0x0c, 0xb4) => "Rewind",
+        (0x0c, 0xb5) => "Scan Next Track",
+        (0x0c, 0xb6) => "Scan Previous Track",
+        (0x0c, 0xb7) => "Stop",
+        (0x0c, 0xb8) => "Eject",
+        (0x0c, 0xcd) => "Play/Pause",
+        (0x0c, 0xe0) => "Volume",
+        (0x0c, 0xe2) => "Mute",
+        (0x0c, 0xe9) => "Volume Increment",
+        (0x0c, 0xea) => "Volume Decrement",
+        (0x0c, 0x0183) => "AL Consumer Control Configuration",
+        (0x0c, 0x018a) => "AL Email Reader",
+        (0x0c, 0x0192) => "AL Calculator",
+        (0x0c, 0x0221) => "AC Search",
+        (0x0c, 0x0223) => "AC Home",
+        (0x0c, 0x0224) => "AC Back",
+        (0x0c, 0x0225) => "AC Forward",
+        (0x0c, 0x0226) => "AC Stop",
+        (0x0c, 0x0227) => "AC Refresh",
+        (0x0c, 0x022a) => "AC Bookmarks",
+        (0x0c, 0x0238) => "AC Pan",
+        (0x0d, 0x02) => "Pen",
+        (0x0d, 0x04) => "Touch Screen",
+        (0x0d, 0x05) => "Touch Pad",
+        _ => "",
+    }
+}
+
+/// Names the keys of the Keyboard/Keypad page as printed on a US keyboard.
+/// The backslash comes escaped, ready for the JSON output.
+fn get_keyboard_usage_name(usage: u32) -> &'static str {
+    const LETTERS_AND_DIGITS: [&str; 36] = [
+        "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q", "R",
+        "S", "T", "U", "V", "W", "X", "Y", "Z", "1", "2", "3", "4", "5", "6", "7", "8", "9", "0",
+    ];
+    const FUNCTION_KEYS: [&str; 12] = [
+        "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12",
+    ];
+    match usage {
+        0x00 => "No Event",
+        0x01 => "ErrorRollOver",
+        0x02 => "POSTFail",
+        0x03 => "ErrorUndefined",
+        0x04..=0x27 => LETTERS_AND_DIGITS[usage as usize - 0x04],
+        0x28 => "Enter",
+        0x29 => "Escape",
+        0x2a => "Backspace",
+        0x2b => "Tab",
+        0x2c => "Space",
+        0x2d => "-",
+        0x2e => "=",
+        0x2f => "[",
+        0x30 => "]",
+        0x31 => "\\\\",
+        0x32 => "Non-US #",
+        0x33 => ";",
+        0x34 => "'",
+        0x35 => "`",
+        0x36 => ",",
+        0x37 => ".",
+        0x38 => "/",
+        0x39 => "Caps Lock",
+        0x3a..=0x45 => FUNCTION_KEYS[usage as usize - 0x3a],
+        0x46 => "Print Screen",
+        0x47 => "Scroll Lock",
+        0x48 => "Pause",
+        0x49 => "Insert",
+        0x4a => "Home",
+        0x4b => "Page Up",
+        0x4c => "Delete",
+        0x4d => "End",
+        0x4e => "Page Down",
+        0x4f => "Right Arrow",
+        0x50 => "Left Arrow",
+        0x51 => "Down Arrow",
+        0x52 => "Up Arrow",
+        0x53 => "Num Lock",
+        0x54 => "Keypad /",
+        0x55 => "Keypad *",
+        0x56 => "Keypad -",
+        0x57 => "Keypad +",
+        0x58 => "Keypad Enter",
+        0x59 => "Keypad 1",
+        0x5a => "Keypad 2",
+        0x5b => "Keypad 3",
+        0x5c => "Keypad 4",
+        0x5d => "Keypad 5",
+        0x5e => "Keypad 6",
+        0x5f => "Keypad 7",
+        0x60 => "Keypad 8",
+        0x61 => "Keypad 9",
+        0x62 => "Keypad 0",
+        0x63 => "Keypad .",
+        0x64 => "Non-US \\\\",
+        0x65 => "Application",
+        0x66 => "Power",
+        0x7f => "Mute",
+        0x80 => "Volume Up",
+        0x81 => "Volume Down",
+        0xe0 => "Left Control",
+        0xe1 => "Left Shift",
+        0xe2 => "Left Alt",
+        0xe3 => "Left GUI",
+        0xe4 => "Right Control",
+        0xe5 => "Right Shift",
+        0xe6 => "Right Alt",
+        0xe7 => "Right GUI",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReportDescriptor;
+    use crate::str_to_array;
+
+    // a keyboard on report ID 1 and a consumer control on report ID 2
+    const DESCRIPTOR: &str = "05 01 09 06 a1 01 85 01 05 07 19 e0 29 e7 15 00 25 01 75 01 95 08 81 02 95 01 75 08 81 01 95 06 75 08 15 00 25 65 05 07 19 00 29 65 81 00 c0 05 0c 09 01 a1 01 85 02 15 00 26 ff 03 19 00 2a ff 03 75 10 95 01 81 00 c0";
+
+    fn decode(report: &str) -> Option<String> {
+        let data = str_to_array(report);
+        ReportDescriptor::new(&str_to_array(DESCRIPTOR))
+            .decode(1, &data)
+            .map(|report| report.as_json(&data, 10))
+    }
+
+    #[test]
+    fn reports_are_laid_out_per_report_id() {
+        assert_eq!(
+            ReportDescriptor::new(&str_to_array(DESCRIPTOR)).summary(),
+            r#""Input Report 1": "Keyboard: Left Control to Right GUI (8 x 1 bits); No Event to Application (6 x 8 bits, Array) (8 bytes)", "Input Report 2": "Consumer Control: Unassigned to Consumer 0x3ff (1 x 16 bits, Array) (2 bytes)""#
+        );
+    }
+
+    #[test]
+    fn keyboard_report_is_decoded() {
+        let json = decode("01 02 00 04 05 00 00 00 00").unwrap();
+        assert!(json.starts_with(r#""Report ID": [0x1, "", "B(10, 1)", ""], "Left Control": [0x0, "Released", "B(11, 1), b(0, 1)", ""], "Left Shift": [0x1, "Pressed", "B(11, 1), b(1, 1)", ""]"#));
+        // the reserved byte is constant and left out
+        assert!(json.contains(r#""Key[0]": [0x4, "A", "B(13, 1)", ""], "Key[1]": [0x5, "B", "B(14, 1)", ""], "Key[2]": [0x0, "None", "B(15, 1)", ""]"#));
+        assert!(!json.contains("Report shorter"));
+    }
+
+    #[test]
+    fn consumer_control_report_is_decoded() {
+        assert_eq!(
+            decode("02 e9 00").unwrap(),
+            r#""Report ID": [0x2, "", "B(10, 1)", ""], "Consumer Control[0]": [0xe9, "Volume Increment", "B(11, 2)", ""]"#
+        );
+    }
+
+    #[test]
+    fn undeclared_report_is_not_decoded() {
+        assert_eq!(decode("03 00"), None);
+    }
+}
@@ -0,0 +1,459 @@
+use crate::hid::{get_report_type_name, Report, ReportDescriptor};
+use crate::HostStack;
+use crate::ParseNodeOptA;
+
+use crate::ParseBitsNode;
+use crate::ParseBytesNode;
+
+const HANDSHAKE: u8 = 0x0;
+const HID_CONTROL: u8 = 0x1;
+const GET_REPORT: u8 = 0x4;
+const SET_REPORT: u8 = 0x5;
+const GET_PROTOCOL: u8 = 0x6;
+const SET_PROTOCOL: u8 = 0x7;
+const GET_IDLE: u8 = 0x8;
+const SET_IDLE: u8 = 0x9;
+const DATA: u8 = 0xa;
+
+const SUCCESSFUL: u8 = 0x0;
+const BOOT_PROTOCOL: u8 = 0x0;
+
+#[derive(Default, Debug)]
+pub struct HIDPArg {
+    connections: Vec<HidpConnection>,
+}
+
+/// The protocol mode of the HID device on one connection, the report
+/// descriptor it goes with and the request on its control channel still
+/// waiting for an answer.
+#[derive(Debug, Default)]
+struct HidpConnection {
+    acl_handle: u16,
+    boot_protocol: bool,
+    // parsed once, from the boot layout or the SDP record, and dropped when
+    // the protocol mode or the record changes
+    descriptor: Option<ReportDescriptor>,
+    // header of the last request, a control channel carries one at a time
+    pending: Option<u8>,
+}
+
+impl HIDPArg {
+    /// Forgets the protocol mode and pending request of `acl_handle`.
+    pub(crate) fn disconnect(&mut self, acl_handle: u16) {
+        self.connections
+            .retain(|connection| connection.acl_handle != acl_handle);
+    }
+
+    /// Drops the report descriptor of `acl_handle`, its service record may
+    /// have changed.
+    pub(crate) fn forget_descriptor(&mut self, acl_handle: u16) {
+        if let Some(connection) = self
+            .connections
+            .iter_mut()
+            .find(|connection| connection.acl_handle == acl_handle)
+        {
+            connection.descriptor = None;
+        }
+    }
+
+    /// Returns the HIDP state of `acl_handle`, creating it on first use.
+    fn connection(&mut self, acl_handle: u16) -> &mut HidpConnection {
+        let index = match self
+            .connections
+            .iter()
+            .position(|connection| connection.acl_handle == acl_handle)
+        {
+            Some(index) => index,
+            None => {
+                self.connections.push(HidpConnection {
+                    acl_handle,
+                    ..Default::default()
+                });
+                self.connections.len() - 1
+            }
+        };
+        &mut self.connections[index]
+    }
+}
+
+impl HidpConnection {
+    fn set_boot_protocol(&mut self, boot_protocol: bool) {
+        if self.boot_protocol != boot_protocol {
+            self.boot_protocol = boot_protocol;
+            self.descriptor = None;
+        }
+    }
+
+    /// Follows the transactions of the control channel. Returns the request
+    /// a DATA message on it answers.
+    fn update(&mut self, message_type: u8, parameter: u8, data: &[u8]) -> Option<u8> {
+        match message_type {
+            HANDSHAKE => {
+                if let Some(request) = self.pending.take() {
+                    if request >> 4 == SET_PROTOCOL && parameter == SUCCESSFUL {
+                        self.set_boot_protocol(request & 0x01 == BOOT_PROTOCOL);
+                    }
+                }
+                None
+            }
+            DATA => {
+                let request = self.pending.take()?;
+                if request >> 4 == GET_PROTOCOL {
+                    if let Some(protocol) = data.first() {
+                        self.set_boot_protocol(protocol & 0x01 == BOOT_PROTOCOL);
+                    }
+                }
+                Some(request >> 4)
+            }
+            HID_CONTROL => None,
+            _ => {
+                self.pending = Some((message_type << 4) | parameter);
+                None
+            }
+        }
+    }
+}
+
+/// A HIDP message on the HID control or interrupt channel. All multi-byte
+/// fields of HIDP are little-endian.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq)]
+pub(crate) struct HIDP {
+    message_type: u8,
+    parameter: u8,
+    payload: HidpPayload,
+}
+
+#[derive(Debug, PartialEq)]
+enum HidpPayload {
+    Empty,
+    GetReport {
+        report_id: Option<u8>,
+        buffer_size: Option<u16>,
+    },
+    Protocol(u8),
+    IdleRate(u8),
+    // the report decoded with the descriptor in effect, or why it can't be
+    Report(Vec<u8>, Result<Report, &'static str>),
+    Raw(Vec<u8>),
+}
+
+impl ParseNodeOptA<bool> for HIDP {
+    /// `interrupt` tells the interrupt channel from the control channel.
+    fn new(data: &[u8], args: Option<&mut HostStack>, interrupt: bool) -> Option<Self> {
+        let header = *data.first()?;
+        let (message_type, parameter) = (header >> 4, header & 0x0f);
+        let body = &data[1..];
+        let (answers, descriptor) = match args {
+            Some(args) => {
+                let acl_handle = args.hci_arg.acl_handle();
+                let connection = args.hidp_arg.connection(acl_handle);
+                let answers = match interrupt {
+                    true => None,
+                    false => connection.update(message_type, parameter, body),
+                };
+                if connection.descriptor.is_none() {
+                    connection.descriptor = match connection.boot_protocol {
+                        true => Some(ReportDescriptor::boot()),
+                        false => args
+                            .sdp_arg
+                            .hid_descriptor(&args.hci_arg)
+                            .map(ReportDescriptor::new),
+                    };
+                }
+                (answers, connection.descriptor.as_ref())
+            }
+            None => (None, None),
+        };
+        let payload = match (message_type, answers) {
+            (DATA, Some(GET_PROTOCOL)) if body.len() == 1 => HidpPayload::Protocol(body[0]),
+            (DATA, Some(GET_IDLE)) if body.len() == 1 => HidpPayload::IdleRate(body[0]),
+            (DATA | SET_REPORT, _) => HidpPayload::Report(
+                body.to_vec(),
+                match descriptor {
+                    Some(descriptor) => descriptor
+                        .decode(parameter & 0x03, body)
+                        .ok_or("Report not in the report descriptor"),
+                    None => Err("No report descriptor seen"),
+                },
+            ),
+            (GET_REPORT, _) => {
+                // the report ID is only there when the device uses them
+                let size_present = parameter & 0x08 != 0;
+                let (report_id, size) = match (size_present, body.len()) {
+                    (true, 3) => (Some(body[0]), &body[1..]),
+                    (true, _) => (None, body),
+                    (false, 1) => (Some(body[0]), &body[1..]),
+                    (false, _) => (None, body),
+                };
+                HidpPayload::GetReport {
+                    report_id,
+                    buffer_size: match (size_present, size) {
+                        (true, [low, high]) => Some(u16::from_le_bytes([*low, *high])),
+                        _ => None,
+                    },
+                }
+            }
+            (SET_PROTOCOL, _) if body.is_empty() => HidpPayload::Empty,
+            (SET_IDLE, _) if body.len() == 1 => HidpPayload::IdleRate(body[0]),
+            _ if body.is_empty() => HidpPayload::Empty,
+            _ => HidpPayload::Raw(body.to_vec()),
+        };
+        Some(HIDP {
+            message_type,
+            parameter,
+            payload,
+        })
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut fields = vec![ParseBitsNode::new(start_byte, 1, 4, 4).format(
+            "Message Type",
+            self.message_type,
+            get_message_type_name(self.message_type),
+            "",
+        )];
+        let parameter = self.parameter;
+        match self.message_type {
+            HANDSHAKE => fields.push(ParseBitsNode::new(start_byte, 1, 0, 4).format(
+                "Result Code",
+                parameter,
+                get_result_code_name(parameter),
+                "",
+            )),
+            HID_CONTROL => fields.push(ParseBitsNode::new(start_byte, 1, 0, 4).format(
+                "Control Operation",
+                parameter,
+                get_control_operation_name(parameter),
+                "",
+            )),
+            GET_REPORT => {
+                fields.push(ParseBitsNode::new(start_byte, 1, 3, 1).format(
+                    "Size",
+                    parameter >> 3,
+                    match parameter >> 3 {
+                        0 => "Buffer as large as the report",
+                        _ => "Buffer Size follows",
+                    },
+                    "",
+                ));
+                fields.push(ParseBitsNode::new(start_byte, 1, 0, 2).format(
+                    "Report Type",
+                    parameter & 0x03,
+                    get_report_type_name(parameter & 0x03),
+                    "",
+                ));
+            }
+            SET_REPORT | DATA => fields.push(ParseBitsNode::new(start_byte, 1, 0, 2).format(
+                "Report Type",
+                parameter & 0x03,
+                get_report_type_name(parameter & 0x03),
+                "",
+            )),
+            SET_PROTOCOL => fields.push(ParseBitsNode::new(start_byte, 1, 0, 1).format(
+                "Protocol",
+                parameter & 0x01,
+                get_protocol_name(parameter & 0x01),
+                "",
+            )),
+            _ => fields.push(ParseBitsNode::new(start_byte, 1, 0, 4).format(
+                "Parameter",
+                parameter,
+                "",
+                "",
+            )),
+        }
+        let offset = start_byte + 1;
+        match &self.payload {
+            HidpPayload::Empty => {}
+            HidpPayload::GetReport {
+                report_id,
+                buffer_size,
+            } => {
+                let mut offset = offset;
+                if let Some(report_id) = report_id {
+                    fields.push(ParseBytesNode::new(offset, 1).format(
+                        "Report ID",
+                        *report_id,
+                        "",
+                        "",
+                    ));
+                    offset += 1;
+                }
+                if let Some(buffer_size) = buffer_size {
+                    fields.push(ParseBytesNode::new(offset, 2).format(
+                        "Buffer Size",
+                        *buffer_size,
+                        "",
+                        "",
+                    ));
+                }
+            }
+            HidpPayload::Protocol(protocol) => fields.push(ParseBytesNode::new(offset, 1).format(
+                "Protocol",
+                *protocol,
+                get_protocol_name(*protocol),
+                "",
+            )),
+            HidpPayload::IdleRate(rate) => fields.push(ParseBytesNode::new(offset, 1).format(
+                "Idle Rate",
+                *rate,
+                &match rate {
+                    0 => "Infinite".to_string(),
+                    rate => format!("{} ms", *rate as u32 * 4),
+                },
+                "",
+            )),
+            HidpPayload::Report(data, report) => {
+                let report_s = report
+                    .as_ref()
+                    .map(|report| report.as_json(data, offset))
+                    .unwrap_or_default();
+                match report_s.is_empty() {
+                    true if !data.is_empty() => fields.push(
+                        ParseBytesNode::new(offset, data.len() as u16).format(
+                            "Report Data",
+                            &data[..],
+                            "",
+                            report
+                                .as_ref()
+                                .err()
+                                .unwrap_or(&"Report not in the report descriptor"),
+                        ),
+                    ),
+                    true => {}
+                    false => fields.push(report_s),
+                }
+            }
+            HidpPayload::Raw(data) => fields.push(
+                ParseBytesNode::new(offset, data.len() as u16).format("Data", &data[..], "", ""),
+            ),
+        }
+        format!(r#""HIDP": {{{}}}"#, fields.join(", "))
+    }
+}
+
+fn get_message_type_name(message_type: u8) -> &'static str {
+    match message_type {
+        0x0 => "HANDSHAKE",
+        0x1 => "HID_CONTROL",
+        0x4 => "GET_REPORT",
+        0x5 => "SET_REPORT",
+        0x6 => "GET_PROTOCOL",
+        0x7 => "SET_PROTOCOL",
+        0x8 => "GET_IDLE (Deprecated)",
+        0x9 => "SET_IDLE (Deprecated)",
+        0xa => "DATA",
+        0xb => "DATC (Deprecated)",
+        _ => "Reserved",
+    }
+}
+
+fn get_result_code_name(result_code: u8) -> &'static str {
+    match result_code {
+        0x0 => "SUCCESSFUL",
+        0x1 => "NOT_READY",
+        0x2 => "ERR_INVALID_REPORT_ID",
+        0x3 => "ERR_UNSUPPORTED_REQUEST",
+        0x4 => "ERR_INVALID_PARAMETER",
+        0xe => "ERR_UNKNOWN",
+        0xf => "ERR_FATAL",
+        _ => "Reserved",
+    }
+}
+
+fn get_control_operation_name(operation: u8) -> &'static str {
+    match operation {
+        0x0 => "NOP (Deprecated)",
+        0x1 => "HARD_RESET (Deprecated)",
+        0x2 => "SOFT_RESET (Deprecated)",
+        0x3 => "SUSPEND",
+        0x4 => "EXIT_SUSPEND",
+        0x5 => "VIRTUAL_CABLE_UNPLUG",
+        _ => "Reserved",
+    }
+}
+
+fn get_protocol_name(protocol: u8) -> &'static str {
+    match protocol {
+        0x0 => "Boot Protocol Mode",
+        0x1 => "Report Protocol Mode",
+        _ => "Reserved",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{l2cap_connect, parse_packets};
+
+    // HID control 0x0040 <-> 0x0041 and interrupt 0x0042 <-> 0x0043, the
+    // host switches to the boot protocol, gets mouse reports and switches
+    // back to the report protocol with no SDP record seen
+    fn parse() -> Vec<String> {
+        let control = l2cap_connect(0x0040, 1, 0x0011, (0x0040, 0x0041));
+        let interrupt = l2cap_connect(0x0040, 2, 0x0013, (0x0042, 0x0043));
+        let (mut json, _) = parse_packets(&[
+            &control[0],
+            &control[1],
+            &interrupt[0],
+            &interrupt[1],
+            "02 40 20 05 00 01 00 41 00 70",
+            "02 40 20 05 00 01 00 40 00 00",
+            "02 40 20 09 00 05 00 42 00 a1 02 05 fe 03",
+            "02 40 20 07 00 03 00 42 00 a1 02 05",
+            "02 40 20 05 00 01 00 41 00 71",
+            "02 40 20 05 00 01 00 40 00 00",
+            "02 40 20 09 00 05 00 42 00 a1 02 05 fe 03",
+        ]);
+        json.drain(..4);
+        json
+    }
+
+    #[test]
+    fn report_bits_are_extracted() {
+        let json = parse();
+        assert!(json[2].contains(r#""Report ID": [0x2, "", "B(10, 1)", ""], "Button 1": [0x1, "Pressed", "B(11, 1), b(0, 1)", ""], "Button 2": [0x0, "Released", "B(11, 1), b(1, 1)", ""], "Button 3": [0x1, "Pressed", "B(11, 1), b(2, 1)", ""], "X": [0xfe, "-2", "B(12, 1)", ""], "Y": [0x3, "3", "B(13, 1)", ""]"#));
+        assert!(json[3].contains(r#""Report Data": ["05", "", "B(11, 1)", "Report shorter than the descriptor declares"]"#));
+    }
+
+    #[test]
+    fn protocol_change_drops_the_descriptor() {
+        let json = parse();
+        assert!(json[6].contains(
+            r#""Report Data": ["02 05 fe 03", "", "B(10, 4)", "No report descriptor seen"]"#
+        ));
+    }
+
+    // the HIDDescriptorList of the device's SDP record declares a keyboard
+    // and a consumer control, the reports come after it reconnected
+    #[test]
+    fn descriptor_comes_from_the_sdp_record_of_the_device() {
+        let sdp = l2cap_connect(0x0040, 1, 0x0001, (0x0040, 0x0041));
+        let control = l2cap_connect(0x0041, 1, 0x0011, (0x0042, 0x0043));
+        let interrupt = l2cap_connect(0x0041, 2, 0x0013, (0x0044, 0x0045));
+        let (json, _) = parse_packets(&[
+            "04 03 0b 00 40 00 66 55 44 33 22 11 01 00",
+            &sdp[0],
+            &sdp[1],
+            "02 40 20 18 00 14 00 41 00 06 00 01 00 0f 35 03 19 11 24 ff ff 35 05 0a 00 00 ff ff 00",
+            "02 40 20 6b 00 67 00 40 00 07 00 01 00 62 00 5f 35 5d 35 5b 09 00 01 35 03 19 11 24 09 02 06 35 4e 35 4c 08 22 25 48 05 01 09 06 a1 01 85 01 05 07 19 e0 29 e7 15 00 25 01 75 01 95 08 81 02 95 01 75 08 81 01 95 06 75 08 15 00 25 65 05 07 19 00 29 65 81 00 c0 05 0c 09 01 a1 01 85 02 15 00 26 ff 03 19 00 2a ff 03 75 10 95 01 81 00 c0 00",
+            "04 05 04 00 40 00 13",
+            "04 03 0b 00 41 00 66 55 44 33 22 11 01 00",
+            &control[0],
+            &control[1],
+            &interrupt[0],
+            &interrupt[1],
+            "02 41 20 0e 00 0a 00 44 00 a1 01 02 00 04 05 00 00 00 00",
+            "02 41 20 08 00 04 00 44 00 a1 02 e9 00",
+            "02 41 20 06 00 02 00 44 00 a1 03",
+        ]);
+        assert!(json[11].contains(r#""Left Shift": [0x1, "Pressed", "B(11, 1), b(1, 1)", ""]"#));
+        assert!(json[11].contains(r#""Key[0]": [0x4, "A", "B(13, 1)", ""]"#));
+        assert!(json[12].contains(
+            r#""Report ID": [0x2, "", "B(10, 1)", ""], "Consumer Control[0]": [0xe9, "Volume Increment", "B(11, 2)", ""]"#
+        ));
+        assert!(json[13].contains(
+            r#""Report Data": ["03", "", "B(10, 1)", "Report not in the report descriptor"]"#
+        ));
+    }
+}
@@ -1,6 +1,14 @@
 use std::fmt::Debug;
 
+use crate::att::ATT;
+use crate::avctp::AVCTP;
+use crate::avdtp::AVDTP;
+use crate::bnep::BNEP;
+use crate::hidp::HIDP;
+use crate::obex::{ObexChannel, OBEX};
+use crate::rfcomm::RFCOMM;
 use crate::sdp::SDP;
+use crate::smp::SMP;
 
 use crate::HostStack;
 use crate::ParseNode;
@@ -8,15 +16,77 @@ use crate::ParseNodeA;
 use crate::ParseNodeOpt;
 use crate::ParseNodeOptA;
 
+use crate::ParseBitsNode;
 use crate::ParseBytesNode;
 
+mod ertm;
+use ertm::{ErtmFrame, ErtmReceiver};
+mod kframe;
+use kframe::{CreditFlow, KFrame};
+
 #[derive(Default, Debug)]
 pub struct L2CAPArg {
     channels: Vec<L2CAPChannel>,
+    payload_channel: u16,
+    payload_side: usize,
+}
+
+impl L2CAPArg {
+    /// The channel a dynamic CID belongs to on `acl_handle`, whichever end
+    /// allocated it.
+    fn channel_mut(&mut self, acl_handle: u16, cid: u16) -> Option<&mut L2CAPChannel> {
+        self.link_channels_mut(acl_handle)
+            .rev()
+            .find(|channel| channel.dest_cid == cid || channel.source_cid == cid)
+    }
+
+    /// The channels of `acl_handle`, CIDs are only unique within one link.
+    fn link_channels_mut(
+        &mut self,
+        acl_handle: u16,
+    ) -> impl DoubleEndedIterator<Item = &mut L2CAPChannel> {
+        self.channels
+            .iter_mut()
+            .filter(move |channel| channel.acl_handle == acl_handle)
+    }
+
+    /// The dynamic channel of the payload being parsed, named by the CID
+    /// its initiator allocated so that both directions agree.
+    pub(crate) fn payload_channel(&self) -> u16 {
+        self.payload_channel
+    }
+
+    /// Which end sent the payload being parsed, 0 for the initiator of its
+    /// channel and 1 for the acceptor. Data goes to the CID the receiving
+    /// end allocated, so when both ends picked the same CID the direction
+    /// can't be told and the initiator is assumed.
+    pub(crate) fn payload_side(&self) -> usize {
+        self.payload_side
+    }
+
+    /// The channels open on `acl_handle` for `psm`, in the order they were
+    /// requested and named by the CID their initiator allocated.
+    pub(crate) fn open_channels(
+        &self,
+        acl_handle: u16,
+        psm: u16,
+    ) -> impl Iterator<Item = u16> + '_ {
+        self.channels
+            .iter()
+            .filter(move |channel| channel.acl_handle == acl_handle && channel.psm == psm)
+            .map(|channel| channel.source_cid)
+    }
+
+    /// Forgets the channels of `acl_handle`, they all closed with it.
+    pub(crate) fn disconnect(&mut self, acl_handle: u16) {
+        self.channels
+            .retain(|channel| channel.acl_handle != acl_handle);
+    }
 }
 
 #[derive(Default)]
 struct L2CAPChannel {
+    acl_handle: u16,
     identifier: u8,
     source_cid: u16,
     dest_cid: u16,
@@ -26,13 +96,89 @@ struct L2CAPChannel {
     local_mtu: u16,
     remote_mtu: u16,
     flush_timeout: u16,
+
+    mode: u8,
+    local_fcs: Option<u8>,
+    remote_fcs: Option<u8>,
+    extended_control: bool,
+
+    // Enhanced Retransmission or Streaming mode, each side reassembles the
+    // I-frames it receives
+    local_rx: ErtmReceiver,
+    remote_rx: ErtmReceiver,
+
+    // LE Credit Based or Enhanced Credit Based Flow Control mode, each side
+    // announces the MPS and credits for the K-frames it receives
+    credit_based: bool,
+    local_flow: CreditFlow,
+    remote_flow: CreditFlow,
+}
+
+impl L2CAPChannel {
+    /// Records what one side asked for in its configuration options.
+    fn apply_options(&mut self, options: &ConfigOptions, local: bool) {
+        for option in options.options.iter() {
+            match &option.data {
+                Some(ConfigOptionData::MTU(option)) => match local {
+                    true => self.local_mtu = option.mtu,
+                    false => self.remote_mtu = option.mtu,
+                },
+                Some(ConfigOptionData::FlushTimeout(flush_timeout)) => {
+                    self.flush_timeout = *flush_timeout
+                }
+                Some(ConfigOptionData::RetransmissionAndFlowControl(option)) => {
+                    self.mode = option.mode
+                }
+                Some(ConfigOptionData::FCS(fcs)) => match local {
+                    true => self.local_fcs = Some(*fcs),
+                    false => self.remote_fcs = Some(*fcs),
+                },
+                Some(ConfigOptionData::ExtendedWindowSize(_)) => self.extended_control = true,
+                _ => {}
+            }
+        }
+    }
+
+    /// Records the MTU, MPS and credits one side announced in a credit based
+    /// connection or reconfigure PDU.
+    fn apply_credit_params(&mut self, local: bool, mtu: u16, mps: u16, credits: Option<u16>) {
+        match local {
+            true => self.local_mtu = mtu,
+            false => self.remote_mtu = mtu,
+        }
+        let flow = self.flow_mut(local);
+        flow.mps = mps;
+        if let Some(credits) = credits {
+            flow.credits = credits;
+        }
+    }
+
+    fn flow_mut(&mut self, local: bool) -> &mut CreditFlow {
+        match local {
+            true => &mut self.local_flow,
+            false => &mut self.remote_flow,
+        }
+    }
+
+    fn receiver_mut(&mut self, local: bool) -> &mut ErtmReceiver {
+        match local {
+            true => &mut self.local_rx,
+            false => &mut self.remote_rx,
+        }
+    }
+
+    /// The FCS is only left out when both sides asked for "No FCS".
+    fn fcs_enabled(&self) -> bool {
+        matches!(self.mode, 0x03 | 0x04)
+            && !(self.local_fcs == Some(0x00) && self.remote_fcs == Some(0x00))
+    }
 }
 
 impl Debug for L2CAPChannel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "L2CAPChannel {{ identifier:{:#x}, source_cid: {:#x}, dest_cid: {:#x}, psm: {:#x}({}), local_mtu:{:#x}, remote_mtu:{:#x}, flush_timeout:{:#x}}}",
+            "L2CAPChannel {{ identifier:{:#x}, source_cid: {:#x}, dest_cid: {:#x}, psm: {:#x}({}), local_mtu:{:#x}, remote_mtu:{:#x}, flush_timeout:{:#x}, mode:{}, fcs:{}{}}}",
             self.identifier,
             self.source_cid,
             self.dest_cid,
@@ -41,6 +187,21 @@ impl Debug for L2CAPChannel {
             self.local_mtu,
             self.remote_mtu,
             self.flush_timeout,
+            match self.credit_based {
+                true => "Credit Based Flow Control Mode",
+                false => get_mode_name(self.mode),
+            },
+            self.fcs_enabled(),
+            match self.credit_based {
+                true => format!(
+                    ", local_mps:{:#x}, remote_mps:{:#x}, local_credits:{}, remote_credits:{}",
+                    self.local_flow.mps,
+                    self.remote_flow.mps,
+                    self.local_flow.credits,
+                    self.remote_flow.credits
+                ),
+                false => String::new(),
+            },
         )
     }
 }
@@ -54,12 +215,12 @@ impl ParseNodeOpt for L2CAP {
     fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
         Some(L2CAP::L2CAPB(L2CAPB::new(data, args)))
     }
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         match self {
             L2CAP::L2CAPB(l2cap) => l2cap
-                .is_some()
-                .then(|| l2cap.as_ref().unwrap().as_json(start_byte))
-                .unwrap_or("".to_string()),
+                .as_ref()
+                .map(|l2cap| l2cap.as_json(start_byte))
+                .unwrap_or_default(),
         }
     }
 }
@@ -78,7 +239,7 @@ impl ParseNodeOpt for L2CAPB {
         } else {
             let pdu_len = u16::from_le_bytes([data[0], data[1]]);
             let cid = u16::from_le_bytes([data[2], data[3]]);
-            let payload = Channel::new(&data[4..], args, cid);
+            let payload = Channel::new(&data[4..], args, (cid, pdu_len));
             Some(L2CAPB {
                 pdu_len,
                 cid,
@@ -87,7 +248,7 @@ impl ParseNodeOpt for L2CAPB {
         }
     }
 
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         let pdu_len_s =
             ParseBytesNode::new(start_byte, 2).format("PDU Length", self.pdu_len, "", "");
         let cid_s = ParseBytesNode::new(start_byte + 2, 2).format("Channel ID", self.cid, "", "");
@@ -97,43 +258,92 @@ impl ParseNodeOpt for L2CAPB {
     }
 }
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, PartialEq)]
 enum Channel {
     Undefined,
     L2CAPSignalingChannel(Option<L2CAPSignaling>),
     ConnetionlessChannel,
-    BrEdrSecurityManager,      // 7
-    DynamicallyAllocated(PSM), // 0x40-0x7f
+    AttributeProtocol(Option<ATT>),             // 4
+    LeSignalingChannel(Option<L2CAPSignaling>), // 5
+    LeSecurityManager(Option<SMP>),             // 6
+    BrEdrSecurityManager(Option<SMP>),          // 7
+    DynamicallyAllocated(PSM),                  // 0x40-0xffff
+    // 0x40-0xffff in Enhanced Retransmission or Streaming mode
+    DynamicallyAllocatedFramed(Option<ErtmFrame>),
+    // 0x40-0xffff in LE Credit Based or Enhanced Credit Based Flow Control mode
+    DynamicallyAllocatedCredit(Option<KFrame>),
 }
 
-impl ParseNodeA<u16> for Channel {
-    fn new(data: &[u8], args: Option<&mut HostStack>, cid: u16) -> Self {
+impl ParseNodeA<(u16, u16)> for Channel {
+    /// Gets the CID and the PDU Length of the Basic L2CAP header.
+    fn new(data: &[u8], args: Option<&mut HostStack>, (cid, pdu_len): (u16, u16)) -> Self {
         match cid {
             1 => Channel::L2CAPSignalingChannel(L2CAPSignaling::new(data, args)),
             2 => Channel::ConnetionlessChannel,
-            7 => Channel::BrEdrSecurityManager,
+            4 => Channel::AttributeProtocol(ATT::new(data, args)),
+            5 => Channel::LeSignalingChannel(L2CAPSignaling::new(data, args)),
+            6 => Channel::LeSecurityManager(SMP::new(data, args, cid)),
+            7 => Channel::BrEdrSecurityManager(SMP::new(data, args, cid)),
             _ => {
-                if cid >= 0x40 && cid <= 0x7f {
-                    let mut psm = 0;
-                    let channels = &args.as_ref().unwrap().l2cap_arg.channels;
-                    for channel in channels.iter() {
-                        if channel.dest_cid == cid {
-                            psm = channel.psm;
+                if (0x0040..=0xffff).contains(&cid) {
+                    let mut args = args;
+                    let (psm, mode, credit_based) = args
+                        .as_deref_mut()
+                        .and_then(|args| {
+                            let acl_handle = args.hci_arg.acl_handle();
+                            let channel = args.l2cap_arg.channel_mut(acl_handle, cid)?;
+                            let info = (channel.psm, channel.mode, channel.credit_based);
+                            let side = match cid == channel.dest_cid {
+                                true => 0,
+                                false => 1,
+                            };
+                            args.l2cap_arg.payload_channel = channel.source_cid;
+                            args.l2cap_arg.payload_side = side;
+                            Some(info)
+                        })
+                        .unwrap_or_default();
+                    match mode {
+                        _ if credit_based => {
+                            Channel::DynamicallyAllocatedCredit(KFrame::new(data, args, cid))
                         }
+                        0x03 | 0x04 => Channel::DynamicallyAllocatedFramed(ErtmFrame::new(
+                            data,
+                            args,
+                            (cid, pdu_len),
+                        )),
+                        _ => Channel::DynamicallyAllocated(PSM::new(data, args, psm)),
                     }
-                    Channel::DynamicallyAllocated(PSM::new(data, args, psm))
                 } else {
                     Channel::Undefined
                 }
             }
         }
     }
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         match self {
-            Channel::L2CAPSignalingChannel(l2cap_signaling) => l2cap_signaling
-                .is_some()
-                .then(|| l2cap_signaling.as_ref().unwrap().as_json(start_byte))
-                .unwrap_or("".to_string()),
+            Channel::L2CAPSignalingChannel(l2cap_signaling)
+            | Channel::LeSignalingChannel(l2cap_signaling) => l2cap_signaling
+                .as_ref()
+                .map(|l2cap_signaling| l2cap_signaling.as_json(start_byte))
+                .unwrap_or_default(),
+            Channel::AttributeProtocol(att) => att
+                .as_ref()
+                .map(|att| att.as_json(start_byte))
+                .unwrap_or_default(),
+            Channel::LeSecurityManager(smp) | Channel::BrEdrSecurityManager(smp) => smp
+                .as_ref()
+                .map(|smp| smp.as_json(start_byte))
+                .unwrap_or_default(),
+            Channel::DynamicallyAllocated(psm) => psm.as_json(start_byte),
+            Channel::DynamicallyAllocatedFramed(frame) => frame
+                .as_ref()
+                .map(|frame| frame.as_json(start_byte))
+                .unwrap_or_default(),
+            Channel::DynamicallyAllocatedCredit(frame) => frame
+                .as_ref()
+                .map(|frame| frame.as_json(start_byte))
+                .unwrap_or_default(),
             _ => "".to_string(),
         }
     }
@@ -164,17 +374,22 @@ impl ParseNodeOpt for L2CAPSignaling {
             })
         }
     }
-    fn as_json(&self, start_byte: u8) -> String {
-        let code_s = ParseBytesNode::new(start_byte, 1).format("Code", self.code, "", "");
+    fn as_json(&self, start_byte: u16) -> String {
+        let code_s = ParseBytesNode::new(start_byte, 1).format(
+            "Code",
+            self.code,
+            get_signaling_code_name(self.code),
+            "",
+        );
         let identifier_s =
             ParseBytesNode::new(start_byte + 1, 1).format("Identifier", self.identifier, "", "");
         let data_length_s =
             ParseBytesNode::new(start_byte + 2, 2).format("Data Length", self.data_length, "", "");
         let data_s = self
             .data
-            .is_some()
-            .then(|| self.data.as_ref().unwrap().as_json(start_byte))
-            .unwrap_or("".to_string());
+            .as_ref()
+            .map(|data| data.as_json(start_byte))
+            .unwrap_or_default();
         format!(
             r#"{}, {}, {}, {}"#,
             code_s, identifier_s, data_length_s, data_s
@@ -185,17 +400,26 @@ impl ParseNodeOpt for L2CAPSignaling {
 #[derive(Debug, PartialEq)]
 enum L2CAPSigData {
     Undefined,
-    CommandRejectRspCode,
+    CommandRejectRspCode(Option<SignalCmdReject>),
     ConnectionReqCode(Option<SignalConnReq>),
     ConnectionRspCode(Option<SignalConnRsp>),
     ConfigurationReqCode(Option<SignalConfReq>),
     ConfigurationRspCode(Option<SignalConfRsp>),
-    DisconnectionReqCode,
-    DisconnectionRspCode,
-    EchoReqCode,
-    EchoRspCode,
+    DisconnectionReqCode(Option<SignalDisconn>),
+    DisconnectionRspCode(Option<SignalDisconn>),
+    EchoReqCode(Option<SignalEcho>),
+    EchoRspCode(Option<SignalEcho>),
     InformationReqCode(Option<SignalInfoReq>),
-    InformationRspCode,
+    InformationRspCode(Option<SignalInfoRsp>),
+    ConnectionParameterUpdateReqCode(Option<SignalConnParamUpdateReq>),
+    ConnectionParameterUpdateRspCode(Option<SignalConnParamUpdateRsp>),
+    LeCreditBasedConnectionReqCode(Option<SignalLeCreditConnReq>),
+    LeCreditBasedConnectionRspCode(Option<SignalLeCreditConnRsp>),
+    FlowControlCreditIndCode(Option<SignalFlowControlCreditInd>),
+    CreditBasedConnectionReqCode(Option<SignalCreditConnReq>),
+    CreditBasedConnectionRspCode(Option<SignalCreditConnRsp>),
+    CreditBasedReconfigureReqCode(Option<SignalCreditReconfReq>),
+    CreditBasedReconfigureRspCode(Option<SignalCreditReconfRsp>),
 }
 
 impl ParseNodeOptA<u8> for L2CAPSigData {
@@ -206,46 +430,180 @@ impl ParseNodeOptA<u8> for L2CAPSigData {
             let code = data[0];
             let data = &data[4..];
             let sig = match code {
-                0x01 => L2CAPSigData::CommandRejectRspCode,
+                0x01 => L2CAPSigData::CommandRejectRspCode(SignalCmdReject::new(data, args, id)),
                 0x02 => L2CAPSigData::ConnectionReqCode(SignalConnReq::new(data, args, id)),
                 0x03 => L2CAPSigData::ConnectionRspCode(SignalConnRsp::new(data, args, id)),
                 0x04 => L2CAPSigData::ConfigurationReqCode(SignalConfReq::new(data, args, id)),
                 0x05 => L2CAPSigData::ConfigurationRspCode(SignalConfRsp::new(data, args, id)),
-                0x06 => L2CAPSigData::DisconnectionReqCode,
-                0x07 => L2CAPSigData::DisconnectionRspCode,
-                0x08 => L2CAPSigData::EchoReqCode,
-                0x09 => L2CAPSigData::EchoRspCode,
+                0x06 => L2CAPSigData::DisconnectionReqCode(SignalDisconn::new(data, None, id)),
+                0x07 => {
+                    let sig = SignalDisconn::new(data, None, id);
+                    if let Some(sig) = &sig {
+                        sig.release_channel(args);
+                    }
+                    L2CAPSigData::DisconnectionRspCode(sig)
+                }
+                0x08 => L2CAPSigData::EchoReqCode(SignalEcho::new(data, args, id)),
+                0x09 => L2CAPSigData::EchoRspCode(SignalEcho::new(data, args, id)),
                 0x0a => L2CAPSigData::InformationReqCode(SignalInfoReq::new(data, args, id)),
-                0x0b => L2CAPSigData::InformationRspCode,
+                0x0b => L2CAPSigData::InformationRspCode(SignalInfoRsp::new(data, args, id)),
+                0x12 => L2CAPSigData::ConnectionParameterUpdateReqCode(
+                    SignalConnParamUpdateReq::new(data, args, id),
+                ),
+                0x13 => L2CAPSigData::ConnectionParameterUpdateRspCode(
+                    SignalConnParamUpdateRsp::new(data, args, id),
+                ),
+                0x14 => L2CAPSigData::LeCreditBasedConnectionReqCode(SignalLeCreditConnReq::new(
+                    data, args, id,
+                )),
+                0x15 => L2CAPSigData::LeCreditBasedConnectionRspCode(SignalLeCreditConnRsp::new(
+                    data, args, id,
+                )),
+                0x16 => L2CAPSigData::FlowControlCreditIndCode(SignalFlowControlCreditInd::new(
+                    data, args, id,
+                )),
+                0x17 => L2CAPSigData::CreditBasedConnectionReqCode(SignalCreditConnReq::new(
+                    data, args, id,
+                )),
+                0x18 => L2CAPSigData::CreditBasedConnectionRspCode(SignalCreditConnRsp::new(
+                    data, args, id,
+                )),
+                0x19 => L2CAPSigData::CreditBasedReconfigureReqCode(SignalCreditReconfReq::new(
+                    data, args, id,
+                )),
+                0x1a => L2CAPSigData::CreditBasedReconfigureRspCode(SignalCreditReconfRsp::new(
+                    data, args, id,
+                )),
                 _ => L2CAPSigData::Undefined,
             };
             Some(sig)
         }
     }
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         let start_byte = start_byte + 4;
         let str = match self {
+            L2CAPSigData::CommandRejectRspCode(sig) => {
+                sig.as_ref().map(|sig| sig.as_json(start_byte))
+            }
             L2CAPSigData::ConnectionReqCode(sig) => sig
                 .is_some()
-                .then(|| sig.as_ref().take().unwrap().as_json(start_byte)),
+                .then(|| sig.as_ref().unwrap().as_json(start_byte)),
             L2CAPSigData::ConnectionRspCode(sig) => sig
                 .is_some()
-                .then(|| sig.as_ref().take().unwrap().as_json(start_byte)),
+                .then(|| sig.as_ref().unwrap().as_json(start_byte)),
             L2CAPSigData::ConfigurationReqCode(sig) => sig
                 .is_some()
-                .then(|| sig.as_ref().take().unwrap().as_json(start_byte)),
+                .then(|| sig.as_ref().unwrap().as_json(start_byte)),
             L2CAPSigData::ConfigurationRspCode(sig) => sig
                 .is_some()
-                .then(|| sig.as_ref().take().unwrap().as_json(start_byte)),
+                .then(|| sig.as_ref().unwrap().as_json(start_byte)),
             L2CAPSigData::InformationReqCode(sig) => sig
                 .is_some()
-                .then(|| sig.as_ref().take().unwrap().as_json(start_byte)),
+                .then(|| sig.as_ref().unwrap().as_json(start_byte)),
+            L2CAPSigData::EchoReqCode(sig) | L2CAPSigData::EchoRspCode(sig) => {
+                sig.as_ref().map(|sig| sig.as_json(start_byte))
+            }
+            L2CAPSigData::InformationRspCode(sig) => {
+                sig.as_ref().map(|sig| sig.as_json(start_byte))
+            }
+            L2CAPSigData::DisconnectionReqCode(sig) | L2CAPSigData::DisconnectionRspCode(sig) => {
+                sig.as_ref().map(|sig| sig.as_json(start_byte))
+            }
+            L2CAPSigData::ConnectionParameterUpdateReqCode(sig) => {
+                sig.as_ref().map(|sig| sig.as_json(start_byte))
+            }
+            L2CAPSigData::ConnectionParameterUpdateRspCode(sig) => {
+                sig.as_ref().map(|sig| sig.as_json(start_byte))
+            }
+            L2CAPSigData::LeCreditBasedConnectionReqCode(sig) => {
+                sig.as_ref().map(|sig| sig.as_json(start_byte))
+            }
+            L2CAPSigData::LeCreditBasedConnectionRspCode(sig) => {
+                sig.as_ref().map(|sig| sig.as_json(start_byte))
+            }
+            L2CAPSigData::FlowControlCreditIndCode(sig) => {
+                sig.as_ref().map(|sig| sig.as_json(start_byte))
+            }
+            L2CAPSigData::CreditBasedConnectionReqCode(sig) => {
+                sig.as_ref().map(|sig| sig.as_json(start_byte))
+            }
+            L2CAPSigData::CreditBasedConnectionRspCode(sig) => {
+                sig.as_ref().map(|sig| sig.as_json(start_byte))
+            }
+            L2CAPSigData::CreditBasedReconfigureReqCode(sig) => {
+                sig.as_ref().map(|sig| sig.as_json(start_byte))
+            }
+            L2CAPSigData::CreditBasedReconfigureRspCode(sig) => {
+                sig.as_ref().map(|sig| sig.as_json(start_byte))
+            }
             _ => None,
         };
         str.unwrap_or("".to_string())
     }
 }
 
+#[derive(Debug, PartialEq)]
+// code 0x01
+struct SignalCmdReject {
+    reason: u16,
+    data: CmdRejectData,
+}
+
+#[derive(Debug, PartialEq)]
+enum CmdRejectData {
+    None,
+    MtuExceeded(u16),
+    InvalidCid(u16, u16),
+}
+
+impl ParseNodeOptA<u8> for SignalCmdReject {
+    fn new(data: &[u8], _args: Option<&mut HostStack>, _id: u8) -> Option<Self> {
+        if data.len() < 2 {
+            None
+        } else {
+            let reason = u16::from_le_bytes([data[0], data[1]]);
+            let data = match (reason, data.len()) {
+                (0x0001, 4..) => CmdRejectData::MtuExceeded(u16::from_le_bytes([data[2], data[3]])),
+                (0x0002, 6..) => CmdRejectData::InvalidCid(
+                    u16::from_le_bytes([data[2], data[3]]),
+                    u16::from_le_bytes([data[4], data[5]]),
+                ),
+                _ => CmdRejectData::None,
+            };
+            Some(SignalCmdReject { reason, data })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let reason_name_s = match self.reason {
+            0x0000 => "Command not understood",
+            0x0001 => "Signaling MTU exceeded",
+            0x0002 => "Invalid CID in request",
+            _ => "Reserved for future use",
+        };
+        let reason_s =
+            ParseBytesNode::new(start_byte, 2).format("Reason", self.reason, reason_name_s, "");
+        match self.data {
+            CmdRejectData::None => reason_s,
+            CmdRejectData::MtuExceeded(mtu) => format!(
+                "{}, {}",
+                reason_s,
+                ParseBytesNode::new(start_byte + 2, 2).format("Actual MTU", mtu, "", "")
+            ),
+            CmdRejectData::InvalidCid(local_cid, remote_cid) => format!(
+                "{}, {}, {}",
+                reason_s,
+                ParseBytesNode::new(start_byte + 2, 2).format("Local Endpoint", local_cid, "", ""),
+                ParseBytesNode::new(start_byte + 4, 2).format(
+                    "Remote Endpoint",
+                    remote_cid,
+                    "",
+                    ""
+                ),
+            ),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 // code 0x02
 struct SignalConnReq {
@@ -261,26 +619,26 @@ impl ParseNodeOptA<u8> for SignalConnReq {
             let psm = u16::from_le_bytes([data[0], data[1]]);
             let source_cid = u16::from_le_bytes([data[2], data[3]]);
 
-            let channels = &mut args.unwrap().l2cap_arg.channels;
-            let mut is_contain = false;
-            for channel in channels.iter() {
-                if channel.source_cid == source_cid {
-                    is_contain = true;
-                    break;
-                }
-            }
+            let args = args.unwrap();
+            let acl_handle = args.hci_arg.acl_handle();
+            let l2cap_arg = &mut args.l2cap_arg;
+            let is_contain = l2cap_arg
+                .link_channels_mut(acl_handle)
+                .any(|channel| channel.source_cid == source_cid);
             if !is_contain {
-                let mut channel = L2CAPChannel::default();
-                channel.identifier = id;
-                channel.psm = psm;
-                channel.source_cid = source_cid;
-                channels.push(channel);
+                l2cap_arg.channels.push(L2CAPChannel {
+                    acl_handle,
+                    identifier: id,
+                    psm,
+                    source_cid,
+                    ..Default::default()
+                });
             }
 
             Some(SignalConnReq { psm, source_cid })
         }
     }
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         let psm_s = ParseBytesNode::new(start_byte, 2).format(
             "PSM",
             self.psm,
@@ -310,8 +668,9 @@ impl ParseNodeOptA<u8> for SignalConnRsp {
             let dest_cid = u16::from_le_bytes([data[0], data[1]]);
             let source_cid = u16::from_le_bytes([data[2], data[3]]);
 
-            let channels = &mut args.unwrap().l2cap_arg.channels;
-            for channel in channels.iter_mut() {
+            let args = args.unwrap();
+            let acl_handle = args.hci_arg.acl_handle();
+            for channel in args.l2cap_arg.link_channels_mut(acl_handle) {
                 if channel.source_cid == source_cid {
                     channel.dest_cid = dest_cid;
                     channel.identifier = id;
@@ -327,7 +686,7 @@ impl ParseNodeOptA<u8> for SignalConnRsp {
         }
     }
 
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         let result_name_s = match self.result {
             0x0000 => "Connection Accepted",
             0x0001 => "Connection pending",
@@ -367,7 +726,7 @@ impl ParseNodeOptA<u8> for SignalConnRsp {
 struct SignalConfReq {
     dest_cid: u16,
     flags: u16,
-    option: Option<ConfigOption>,
+    options: ConfigOptions,
 }
 
 impl ParseNodeOptA<u8> for SignalConfReq {
@@ -377,45 +736,36 @@ impl ParseNodeOptA<u8> for SignalConfReq {
         } else {
             let dest_cid = u16::from_le_bytes([data[0], data[1]]);
             let flags = u16::from_le_bytes([data[2], data[3]]);
-            let option = ConfigOption::new(&data[4..], None);
+            let options = ConfigOptions::new(&data[4..], None);
 
-            let channels = &mut args.unwrap().l2cap_arg.channels;
-            for channel in channels.iter_mut() {
+            let args = args.unwrap();
+            let acl_handle = args.hci_arg.acl_handle();
+            for channel in args.l2cap_arg.link_channels_mut(acl_handle) {
                 if dest_cid == channel.dest_cid {
-                    let data = option.as_ref();
-                    if let Some(option) = data {
-                        if let Some(ConfigOptionData::MTU(option)) = &option.data {
-                            channel.identifier = id;
-                            channel.local_mtu = option.mtu;
-                        }
-                    }
+                    channel.identifier = id;
+                    channel.apply_options(&options, true);
                 } else if dest_cid == channel.source_cid {
-                    let data = option.as_ref();
-                    if let Some(option) = data {
-                        if let Some(ConfigOptionData::MTU(option)) = &option.data {
-                            channel.identifier = id;
-                            channel.remote_mtu = option.mtu;
-                        }
-                    }
+                    channel.identifier = id;
+                    channel.apply_options(&options, false);
                 }
             }
 
             Some(SignalConfReq {
                 dest_cid,
                 flags,
-                option,
+                options,
             })
         }
     }
 
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         let dest_cid_s =
             ParseBytesNode::new(start_byte, 2).format("Destination CID", self.dest_cid, "", "");
-        let flags_s = ParseBytesNode::new(start_byte + 2, 2).format("Flags", self.flags, "", "");
+        let flags_s = config_flags_as_json(self.flags, start_byte + 2);
         let mut json = format!("{}, {}", dest_cid_s, flags_s);
-        if let Some(option) = &self.option {
+        if !self.options.options.is_empty() {
             json.push_str(", ");
-            json.push_str(option.as_json(start_byte + 4).as_str());
+            json.push_str(self.options.as_json(start_byte + 4).as_str());
         }
 
         json
@@ -428,7 +778,7 @@ struct SignalConfRsp {
     source_cid: u16,
     flags: u16,
     result: u16,
-    option: Option<ConfigOption>,
+    options: ConfigOptions,
 }
 
 impl ParseNodeOptA<u8> for SignalConfRsp {
@@ -439,18 +789,14 @@ impl ParseNodeOptA<u8> for SignalConfRsp {
             let source_cid = u16::from_le_bytes([data[0], data[1]]);
             let flags = u16::from_le_bytes([data[2], data[3]]);
             let result = u16::from_le_bytes([data[4], data[5]]);
-            let option = ConfigOption::new(&data[6..], None);
+            let options = ConfigOptions::new(&data[6..], None);
 
-            let channels = &mut args.unwrap().l2cap_arg.channels;
-            for channel in channels.iter_mut() {
+            let args = args.unwrap();
+            let acl_handle = args.hci_arg.acl_handle();
+            for channel in args.l2cap_arg.link_channels_mut(acl_handle) {
                 if source_cid == channel.dest_cid {
-                    let data = option.as_ref();
-                    if let Some(option) = data {
-                        if let Some(ConfigOptionData::MTU(option)) = &option.data {
-                            channel.identifier = id;
-                            channel.remote_mtu = option.mtu;
-                        }
-                    }
+                    channel.identifier = id;
+                    channel.apply_options(&options, false);
                 }
             }
 
@@ -458,12 +804,12 @@ impl ParseNodeOptA<u8> for SignalConfRsp {
                 source_cid,
                 flags,
                 result,
-                option,
+                options,
             })
         }
     }
 
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         let result_name_s = match self.result {
             0x0000 => "Success",
             0x0001 => "Failure - unacceptable parameters",
@@ -475,19 +821,37 @@ impl ParseNodeOptA<u8> for SignalConfRsp {
         };
         let dest_cid_s =
             ParseBytesNode::new(start_byte, 2).format("Destination CID", self.source_cid, "", "");
-        let flags_s = ParseBytesNode::new(start_byte + 2, 2).format("Flags", self.flags, "", "");
+        let flags_s = config_flags_as_json(self.flags, start_byte + 2);
         let result_s =
             ParseBytesNode::new(start_byte + 4, 2).format("Result", self.result, result_name_s, "");
 
         let mut json = format!("{}, {}, {}", dest_cid_s, flags_s, result_s);
-        if let Some(option) = &self.option {
+        if !self.options.options.is_empty() {
             json.push_str(", ");
-            json.push_str(option.as_json(start_byte + 6).as_str());
+            json.push_str(self.options.as_json(start_byte + 6).as_str());
         }
         json
     }
 }
 
+fn config_flags_as_json(flags: u16, start_byte: u16) -> String {
+    let continuation_s = ParseBitsNode::new(start_byte, 2, 0, 1).format(
+        "Continuation Flag",
+        (flags & 0x0001) as u8,
+        if flags & 0x0001 != 0 {
+            "More configuration packets follow"
+        } else {
+            ""
+        },
+        "",
+    );
+    format!(
+        r#""Flags": {{{}, {}}}"#,
+        ParseBytesNode::new(start_byte, 2).format("Value", flags, "", ""),
+        continuation_s
+    )
+}
+
 #[derive(Debug, PartialEq)]
 struct SignalInfoReq {
     info_type: u16,
@@ -503,23 +867,842 @@ impl ParseNodeOptA<u8> for SignalInfoReq {
             })
         }
     }
-    fn as_json(&self, start_byte: u8) -> String {
-        let info_type_s =
-            ParseBytesNode::new(start_byte, 2).format("Info Type", self.info_type, "", "");
-        format!("{}", info_type_s)
+    fn as_json(&self, start_byte: u16) -> String {
+        ParseBytesNode::new(start_byte, 2).format(
+            "Info Type",
+            self.info_type,
+            get_info_type_name(self.info_type),
+            "",
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+// code 0x08 and 0x09
+struct SignalEcho {
+    data: Vec<u8>,
+}
+
+impl ParseNodeOptA<u8> for SignalEcho {
+    fn new(data: &[u8], _args: Option<&mut HostStack>, _id: u8) -> Option<Self> {
+        Some(SignalEcho {
+            data: data.to_vec(),
+        })
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        if self.data.is_empty() {
+            "".to_string()
+        } else {
+            ParseBytesNode::new(start_byte, self.data.len() as u16).format(
+                "Data",
+                &self.data[..],
+                "",
+                "",
+            )
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+// code 0x0b
+struct SignalInfoRsp {
+    info_type: u16,
+    result: u16,
+    data: InfoRspData,
+}
+
+#[derive(Debug, PartialEq)]
+enum InfoRspData {
+    Undefined(Vec<u8>),
+    ConnectionlessMtu(u16),
+    ExtendedFeatures(u32),
+    FixedChannels(u64),
+}
+
+impl ParseNodeOptA<u8> for SignalInfoRsp {
+    fn new(data: &[u8], _args: Option<&mut HostStack>, _id: u8) -> Option<Self> {
+        if data.len() < 4 {
+            None
+        } else {
+            let info_type = u16::from_le_bytes([data[0], data[1]]);
+            let result = u16::from_le_bytes([data[2], data[3]]);
+            let data = &data[4..];
+            let data = match (result, info_type, data.len()) {
+                (0x0000, 0x0001, 2..) => {
+                    InfoRspData::ConnectionlessMtu(u16::from_le_bytes([data[0], data[1]]))
+                }
+                (0x0000, 0x0002, 4..) => InfoRspData::ExtendedFeatures(u32::from_le_bytes(
+                    data[0..4].try_into().unwrap(),
+                )),
+                (0x0000, 0x0003, 8..) => {
+                    InfoRspData::FixedChannels(u64::from_le_bytes(data[0..8].try_into().unwrap()))
+                }
+                _ => InfoRspData::Undefined(data.to_vec()),
+            };
+            Some(SignalInfoRsp {
+                info_type,
+                result,
+                data,
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let info_type_s = ParseBytesNode::new(start_byte, 2).format(
+            "Info Type",
+            self.info_type,
+            get_info_type_name(self.info_type),
+            "",
+        );
+        let result_name_s = match self.result {
+            0x0000 => "Success",
+            0x0001 => "Not supported",
+            _ => "Reserved for future use",
+        };
+        let result_s =
+            ParseBytesNode::new(start_byte + 2, 2).format("Result", self.result, result_name_s, "");
+        let start_byte = start_byte + 4;
+        let data_s = match &self.data {
+            InfoRspData::Undefined(data) if data.is_empty() => "".to_string(),
+            InfoRspData::Undefined(data) => {
+                ParseBytesNode::new(start_byte, data.len() as u16).format("Data", &data[..], "", "")
+            }
+            InfoRspData::ConnectionlessMtu(mtu) => {
+                ParseBytesNode::new(start_byte, 2).format("Connectionless MTU", mtu, "", "")
+            }
+            InfoRspData::ExtendedFeatures(mask) => {
+                let bits = [
+                    (0, "Flow control mode"),
+                    (1, "Retransmission mode"),
+                    (2, "Bi-directional QoS"),
+                    (3, "Enhanced Retransmission Mode"),
+                    (4, "Streaming Mode"),
+                    (5, "FCS Option"),
+                    (6, "Extended Flow Specification for BR/EDR"),
+                    (7, "Fixed Channels"),
+                    (8, "Extended Window Size"),
+                    (9, "Unicast Connectionless Data Reception"),
+                    (10, "Enhanced Credit Based Flow Control Mode"),
+                ];
+                let bits_s = bits
+                    .iter()
+                    .map(|(bit, name)| {
+                        ParseBitsNode::new(start_byte, 4, *bit, 1).format(
+                            name,
+                            ((mask >> bit) & 0x01) as u8,
+                            "",
+                            "",
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                format!(
+                    r#"{}, "Extended Features": {{{}}}"#,
+                    ParseBytesNode::new(start_byte, 4).format(
+                        "Extended Feature Mask",
+                        mask,
+                        "",
+                        ""
+                    ),
+                    bits_s
+                )
+            }
+            InfoRspData::FixedChannels(channels) => {
+                let bits = [
+                    (1, "L2CAP Signaling channel"),
+                    (2, "Connectionless reception"),
+                    (3, "AMP Manager Protocol"),
+                    (7, "BR/EDR Security Manager"),
+                ];
+                let bits_s = bits
+                    .iter()
+                    .map(|(bit, name)| {
+                        ParseBitsNode::new(start_byte, 8, *bit, 1).format(
+                            name,
+                            ((channels >> bit) & 0x01) as u8,
+                            "",
+                            "",
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                format!(
+                    r#"{}, "Fixed Channels": {{{}}}"#,
+                    ParseBytesNode::new(start_byte, 8).format(
+                        "Fixed Channels Supported",
+                        channels,
+                        "",
+                        ""
+                    ),
+                    bits_s
+                )
+            }
+        };
+        if data_s.is_empty() {
+            format!("{}, {}", info_type_s, result_s)
+        } else {
+            format!("{}, {}, {}", info_type_s, result_s, data_s)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+// code 0x06 and 0x07
+struct SignalDisconn {
+    dest_cid: u16,
+    source_cid: u16,
+}
+
+impl ParseNodeOptA<u8> for SignalDisconn {
+    fn new(data: &[u8], _args: Option<&mut HostStack>, _id: u8) -> Option<Self> {
+        if data.len() < 4 {
+            None
+        } else {
+            Some(SignalDisconn {
+                dest_cid: u16::from_le_bytes([data[0], data[1]]),
+                source_cid: u16::from_le_bytes([data[2], data[3]]),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let dest_cid_s =
+            ParseBytesNode::new(start_byte, 2).format("Destination CID", self.dest_cid, "", "");
+        let source_cid_s =
+            ParseBytesNode::new(start_byte + 2, 2).format("Source CID", self.source_cid, "", "");
+        format!("{}, {}", dest_cid_s, source_cid_s)
+    }
+}
+
+impl SignalDisconn {
+    /// Once the disconnection is confirmed the CIDs are free to be reused,
+    /// and the profile on the channel stops tracking it.
+    fn release_channel(&self, args: Option<&mut HostStack>) {
+        if let Some(args) = args {
+            let acl_handle = args.hci_arg.acl_handle();
+            let (released, open): (Vec<L2CAPChannel>, Vec<L2CAPChannel>) =
+                std::mem::take(&mut args.l2cap_arg.channels)
+                    .into_iter()
+                    .partition(|channel| {
+                        channel.acl_handle == acl_handle
+                            && (channel.dest_cid == self.dest_cid
+                                && channel.source_cid == self.source_cid
+                                || channel.dest_cid == self.source_cid
+                                    && channel.source_cid == self.dest_cid)
+                    });
+            args.l2cap_arg.channels = open;
+            for channel in released.iter().filter(|channel| channel.psm == 0x0019) {
+                args.avdtp_arg
+                    .release_channel(channel.acl_handle, channel.source_cid);
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+// code 0x12
+struct SignalConnParamUpdateReq {
+    interval_min: u16,
+    interval_max: u16,
+    latency: u16,
+    timeout: u16,
+}
+
+impl ParseNodeOptA<u8> for SignalConnParamUpdateReq {
+    fn new(data: &[u8], _args: Option<&mut HostStack>, _id: u8) -> Option<Self> {
+        if data.len() < 8 {
+            None
+        } else {
+            Some(SignalConnParamUpdateReq {
+                interval_min: u16::from_le_bytes([data[0], data[1]]),
+                interval_max: u16::from_le_bytes([data[2], data[3]]),
+                latency: u16::from_le_bytes([data[4], data[5]]),
+                timeout: u16::from_le_bytes([data[6], data[7]]),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let interval_min_s = ParseBytesNode::new(start_byte, 2).format(
+            "Interval Min",
+            self.interval_min,
+            &format!("{} ms", self.interval_min as f32 * 1.25),
+            "",
+        );
+        let interval_max_s = ParseBytesNode::new(start_byte + 2, 2).format(
+            "Interval Max",
+            self.interval_max,
+            &format!("{} ms", self.interval_max as f32 * 1.25),
+            "",
+        );
+        let latency_s =
+            ParseBytesNode::new(start_byte + 4, 2).format("Latency", self.latency, "", "");
+        let timeout_s = ParseBytesNode::new(start_byte + 6, 2).format(
+            "Timeout",
+            self.timeout,
+            &format!("{} ms", self.timeout as u32 * 10),
+            "",
+        );
+        format!(
+            "{}, {}, {}, {}",
+            interval_min_s, interval_max_s, latency_s, timeout_s
+        )
     }
 }
 
+#[derive(Debug, PartialEq)]
+// code 0x13
+struct SignalConnParamUpdateRsp {
+    result: u16,
+}
+
+impl ParseNodeOptA<u8> for SignalConnParamUpdateRsp {
+    fn new(data: &[u8], _args: Option<&mut HostStack>, _id: u8) -> Option<Self> {
+        if data.len() < 2 {
+            None
+        } else {
+            Some(SignalConnParamUpdateRsp {
+                result: u16::from_le_bytes([data[0], data[1]]),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let result_name_s = match self.result {
+            0x0000 => "Connection Parameters accepted",
+            0x0001 => "Connection Parameters rejected",
+            _ => "Reserved for future use",
+        };
+        ParseBytesNode::new(start_byte, 2).format("Result", self.result, result_name_s, "")
+    }
+}
+
+#[derive(Debug, PartialEq)]
+// code 0x14
+struct SignalLeCreditConnReq {
+    spsm: u16,
+    source_cid: u16,
+    mtu: u16,
+    mps: u16,
+    initial_credits: u16,
+}
+
+impl ParseNodeOptA<u8> for SignalLeCreditConnReq {
+    fn new(data: &[u8], args: Option<&mut HostStack>, id: u8) -> Option<Self> {
+        if data.len() < 10 {
+            None
+        } else {
+            let sig = SignalLeCreditConnReq {
+                spsm: u16::from_le_bytes([data[0], data[1]]),
+                source_cid: u16::from_le_bytes([data[2], data[3]]),
+                mtu: u16::from_le_bytes([data[4], data[5]]),
+                mps: u16::from_le_bytes([data[6], data[7]]),
+                initial_credits: u16::from_le_bytes([data[8], data[9]]),
+            };
+            if let Some(args) = args {
+                open_credit_channels(
+                    args,
+                    id,
+                    sig.spsm,
+                    &[sig.source_cid],
+                    (sig.mtu, sig.mps, sig.initial_credits),
+                );
+            }
+            Some(sig)
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let spsm_s = ParseBytesNode::new(start_byte, 2).format(
+            "SPSM",
+            self.spsm,
+            get_le_psm_name(self.spsm),
+            "",
+        );
+        let source_cid_s =
+            ParseBytesNode::new(start_byte + 2, 2).format("Source CID", self.source_cid, "", "");
+        format!(
+            "{}, {}, {}",
+            spsm_s,
+            source_cid_s,
+            credit_params_as_json(self.mtu, self.mps, self.initial_credits, start_byte + 4)
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+// code 0x15
+struct SignalLeCreditConnRsp {
+    dest_cid: u16,
+    mtu: u16,
+    mps: u16,
+    initial_credits: u16,
+    result: u16,
+}
+
+impl ParseNodeOptA<u8> for SignalLeCreditConnRsp {
+    fn new(data: &[u8], args: Option<&mut HostStack>, id: u8) -> Option<Self> {
+        if data.len() < 10 {
+            None
+        } else {
+            let sig = SignalLeCreditConnRsp {
+                dest_cid: u16::from_le_bytes([data[0], data[1]]),
+                mtu: u16::from_le_bytes([data[2], data[3]]),
+                mps: u16::from_le_bytes([data[4], data[5]]),
+                initial_credits: u16::from_le_bytes([data[6], data[7]]),
+                result: u16::from_le_bytes([data[8], data[9]]),
+            };
+            if let Some(args) = args {
+                // a refused connection carries a zero Destination CID
+                let dest_cid = match sig.result {
+                    0x0000 => sig.dest_cid,
+                    _ => 0x0000,
+                };
+                accept_credit_channels(
+                    args,
+                    id,
+                    &[dest_cid],
+                    (sig.mtu, sig.mps, sig.initial_credits),
+                );
+            }
+            Some(sig)
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let dest_cid_s =
+            ParseBytesNode::new(start_byte, 2).format("Destination CID", self.dest_cid, "", "");
+        let result_s = ParseBytesNode::new(start_byte + 8, 2).format(
+            "Result",
+            self.result,
+            get_credit_conn_result_name(self.result),
+            "",
+        );
+        format!(
+            "{}, {}, {}",
+            dest_cid_s,
+            credit_params_as_json(self.mtu, self.mps, self.initial_credits, start_byte + 2),
+            result_s
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+// code 0x16
+struct SignalFlowControlCreditInd {
+    cid: u16,
+    credits: u16,
+}
+
+impl ParseNodeOptA<u8> for SignalFlowControlCreditInd {
+    fn new(data: &[u8], args: Option<&mut HostStack>, _id: u8) -> Option<Self> {
+        if data.len() < 4 {
+            None
+        } else {
+            let sig = SignalFlowControlCreditInd {
+                cid: u16::from_le_bytes([data[0], data[1]]),
+                credits: u16::from_le_bytes([data[2], data[3]]),
+            };
+            // the CID is the one the sender receives on, so the credits are
+            // for K-frames sent to it
+            if let Some(channel) = args.and_then(|args| {
+                let acl_handle = args.hci_arg.acl_handle();
+                args.l2cap_arg.channel_mut(acl_handle, sig.cid)
+            }) {
+                let local = channel.source_cid == sig.cid;
+                let flow = channel.flow_mut(local);
+                flow.credits = flow.credits.saturating_add(sig.credits);
+            }
+            Some(sig)
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let cid_s = ParseBytesNode::new(start_byte, 2).format("CID", self.cid, "", "");
+        let credits_s = ParseBytesNode::new(start_byte + 2, 2).format(
+            "Credits",
+            self.credits,
+            &self.credits.to_string(),
+            "",
+        );
+        format!("{}, {}", cid_s, credits_s)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+// code 0x17
+struct SignalCreditConnReq {
+    spsm: u16,
+    mtu: u16,
+    mps: u16,
+    initial_credits: u16,
+    source_cids: Vec<u16>,
+}
+
+impl ParseNodeOptA<u8> for SignalCreditConnReq {
+    fn new(data: &[u8], args: Option<&mut HostStack>, id: u8) -> Option<Self> {
+        if data.len() < 8 {
+            None
+        } else {
+            let sig = SignalCreditConnReq {
+                spsm: u16::from_le_bytes([data[0], data[1]]),
+                mtu: u16::from_le_bytes([data[2], data[3]]),
+                mps: u16::from_le_bytes([data[4], data[5]]),
+                initial_credits: u16::from_le_bytes([data[6], data[7]]),
+                source_cids: cid_list(&data[8..]),
+            };
+            if let Some(args) = args {
+                open_credit_channels(
+                    args,
+                    id,
+                    sig.spsm,
+                    &sig.source_cids,
+                    (sig.mtu, sig.mps, sig.initial_credits),
+                );
+            }
+            Some(sig)
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let spsm_s = ParseBytesNode::new(start_byte, 2).format(
+            "SPSM",
+            self.spsm,
+            get_le_psm_name(self.spsm),
+            "",
+        );
+        format!(
+            "{}, {}, {}",
+            spsm_s,
+            credit_params_as_json(self.mtu, self.mps, self.initial_credits, start_byte + 2),
+            cid_list_as_json("Source CID", &self.source_cids, start_byte + 8)
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+// code 0x18
+struct SignalCreditConnRsp {
+    mtu: u16,
+    mps: u16,
+    initial_credits: u16,
+    result: u16,
+    dest_cids: Vec<u16>,
+}
+
+impl ParseNodeOptA<u8> for SignalCreditConnRsp {
+    fn new(data: &[u8], args: Option<&mut HostStack>, id: u8) -> Option<Self> {
+        if data.len() < 8 {
+            None
+        } else {
+            let sig = SignalCreditConnRsp {
+                mtu: u16::from_le_bytes([data[0], data[1]]),
+                mps: u16::from_le_bytes([data[2], data[3]]),
+                initial_credits: u16::from_le_bytes([data[4], data[5]]),
+                result: u16::from_le_bytes([data[6], data[7]]),
+                dest_cids: cid_list(&data[8..]),
+            };
+            if let Some(args) = args {
+                accept_credit_channels(
+                    args,
+                    id,
+                    &sig.dest_cids,
+                    (sig.mtu, sig.mps, sig.initial_credits),
+                );
+            }
+            Some(sig)
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let result_s = ParseBytesNode::new(start_byte + 6, 2).format(
+            "Result",
+            self.result,
+            get_credit_conn_result_name(self.result),
+            "",
+        );
+        format!(
+            "{}, {}, {}",
+            credit_params_as_json(self.mtu, self.mps, self.initial_credits, start_byte),
+            result_s,
+            cid_list_as_json("Destination CID", &self.dest_cids, start_byte + 8)
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+// code 0x19
+struct SignalCreditReconfReq {
+    mtu: u16,
+    mps: u16,
+    dest_cids: Vec<u16>,
+}
+
+impl ParseNodeOptA<u8> for SignalCreditReconfReq {
+    fn new(data: &[u8], args: Option<&mut HostStack>, _id: u8) -> Option<Self> {
+        if data.len() < 4 {
+            None
+        } else {
+            let sig = SignalCreditReconfReq {
+                mtu: u16::from_le_bytes([data[0], data[1]]),
+                mps: u16::from_le_bytes([data[2], data[3]]),
+                dest_cids: cid_list(&data[4..]),
+            };
+            // the listed CIDs are the ones the sender receives on
+            if let Some(args) = args {
+                let acl_handle = args.hci_arg.acl_handle();
+                for cid in sig.dest_cids.iter() {
+                    if let Some(channel) = args.l2cap_arg.channel_mut(acl_handle, *cid) {
+                        let local = channel.source_cid == *cid;
+                        channel.apply_credit_params(local, sig.mtu, sig.mps, None);
+                    }
+                }
+            }
+            Some(sig)
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let mtu_s =
+            ParseBytesNode::new(start_byte, 2).format("MTU", self.mtu, &self.mtu.to_string(), "");
+        let mps_s = ParseBytesNode::new(start_byte + 2, 2).format(
+            "MPS",
+            self.mps,
+            &self.mps.to_string(),
+            "",
+        );
+        format!(
+            "{}, {}, {}",
+            mtu_s,
+            mps_s,
+            cid_list_as_json("Destination CID", &self.dest_cids, start_byte + 4)
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+// code 0x1a
+struct SignalCreditReconfRsp {
+    result: u16,
+}
+
+impl ParseNodeOptA<u8> for SignalCreditReconfRsp {
+    fn new(data: &[u8], _args: Option<&mut HostStack>, _id: u8) -> Option<Self> {
+        if data.len() < 2 {
+            None
+        } else {
+            Some(SignalCreditReconfRsp {
+                result: u16::from_le_bytes([data[0], data[1]]),
+            })
+        }
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let result_name_s = match self.result {
+            0x0000 => "Reconfiguration successful",
+            0x0001 => "Reconfiguration failed - reduction in size of MTU not allowed",
+            0x0002 => "Reconfiguration failed - reduction in size of MPS not allowed for more than one channel at a time",
+            0x0003 => "Reconfiguration failed - one or more Destination CIDs invalid",
+            0x0004 => "Reconfiguration failed - other unacceptable parameters",
+            _ => "Reserved for future use",
+        };
+        ParseBytesNode::new(start_byte, 2).format("Result", self.result, result_name_s, "")
+    }
+}
+
+/// Starts tracking the channels a credit based connection request asks for,
+/// with the requester's MTU, MPS and the credits it grants.
+fn open_credit_channels(
+    args: &mut HostStack,
+    id: u8,
+    spsm: u16,
+    source_cids: &[u16],
+    (mtu, mps, credits): (u16, u16, u16),
+) {
+    let acl_handle = args.hci_arg.acl_handle();
+    let l2cap_arg = &mut args.l2cap_arg;
+    for source_cid in source_cids.iter() {
+        l2cap_arg.channels.retain(|channel| {
+            channel.acl_handle != acl_handle || channel.source_cid != *source_cid
+        });
+        let mut channel = L2CAPChannel {
+            acl_handle,
+            identifier: id,
+            psm: spsm,
+            source_cid: *source_cid,
+            credit_based: true,
+            ..Default::default()
+        };
+        channel.apply_credit_params(true, mtu, mps, Some(credits));
+        l2cap_arg.channels.push(channel);
+    }
+}
+
+/// Matches the Destination CIDs of a credit based connection response, in
+/// order, with the channels of the link still pending under the same
+/// identifier. A zero CID means that channel was refused.
+fn accept_credit_channels(
+    args: &mut HostStack,
+    id: u8,
+    dest_cids: &[u16],
+    (mtu, mps, credits): (u16, u16, u16),
+) {
+    let acl_handle = args.hci_arg.acl_handle();
+    let mut dest_cids = dest_cids.iter();
+    args.l2cap_arg.channels.retain_mut(|channel| {
+        if !(channel.acl_handle == acl_handle
+            && channel.credit_based
+            && channel.identifier == id
+            && channel.dest_cid == 0)
+        {
+            return true;
+        }
+        match dest_cids.next() {
+            Some(0) | None => false,
+            Some(dest_cid) => {
+                channel.dest_cid = *dest_cid;
+                channel.apply_credit_params(false, mtu, mps, Some(credits));
+                true
+            }
+        }
+    });
+}
+
+fn cid_list(data: &[u8]) -> Vec<u16> {
+    data.chunks_exact(2)
+        .map(|cid| u16::from_le_bytes([cid[0], cid[1]]))
+        .collect()
+}
+
+fn cid_list_as_json(name: &str, cids: &[u16], start_byte: u16) -> String {
+    cids.iter()
+        .enumerate()
+        .map(|(i, cid)| {
+            ParseBytesNode::new(start_byte + 2 * i as u16, 2).format(
+                &format!("{}[{}]", name, i),
+                *cid,
+                "",
+                "",
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn credit_params_as_json(mtu: u16, mps: u16, initial_credits: u16, start_byte: u16) -> String {
+    let mtu_s = ParseBytesNode::new(start_byte, 2).format("MTU", mtu, &mtu.to_string(), "");
+    let mps_s = ParseBytesNode::new(start_byte + 2, 2).format("MPS", mps, &mps.to_string(), "");
+    let initial_credits_s = ParseBytesNode::new(start_byte + 4, 2).format(
+        "Initial Credits",
+        initial_credits,
+        &initial_credits.to_string(),
+        "",
+    );
+    format!("{}, {}, {}", mtu_s, mps_s, initial_credits_s)
+}
+
+fn get_credit_conn_result_name(result: u16) -> &'static str {
+    match result {
+        0x0000 => "Connection successful",
+        0x0002 => "Connection refused - SPSM not supported",
+        0x0004 => "Connection refused - no resources available",
+        0x0005 => "Connection refused - insufficient authentication",
+        0x0006 => "Connection refused - insufficient authorization",
+        0x0007 => "Connection refused - encryption key size too short",
+        0x0008 => "Connection refused - insufficient encryption",
+        0x0009 => "Connection refused - invalid Source CID",
+        0x000a => "Connection refused - Source CID already allocated",
+        0x000b => "Connection refused - unacceptable parameters",
+        0x000c => "Connection refused - invalid parameters",
+        _ => "Reserved for future use",
+    }
+}
+
+/// LE uses its own PSM namespace: a few fixed SIG assigned SPSMs and a dynamic
+/// range handed out by the server.
+fn get_le_psm_name(spsm: u16) -> &'static str {
+    match spsm {
+        0x0023 => "LE_PSM_IPSP",
+        0x0025 => "OTS",
+        0x0027 => "EATT",
+        0x0001..=0x007f => "SIG assigned",
+        0x0080..=0x00ff => "Dynamic",
+        _ => "Reserved for future use",
+    }
+}
+
+fn get_signaling_code_name(code: u8) -> &'static str {
+    match code {
+        0x01 => "L2CAP_COMMAND_REJECT_RSP",
+        0x02 => "L2CAP_CONNECTION_REQ",
+        0x03 => "L2CAP_CONNECTION_RSP",
+        0x04 => "L2CAP_CONFIGURATION_REQ",
+        0x05 => "L2CAP_CONFIGURATION_RSP",
+        0x06 => "L2CAP_DISCONNECTION_REQ",
+        0x07 => "L2CAP_DISCONNECTION_RSP",
+        0x08 => "L2CAP_ECHO_REQ",
+        0x09 => "L2CAP_ECHO_RSP",
+        0x0a => "L2CAP_INFORMATION_REQ",
+        0x0b => "L2CAP_INFORMATION_RSP",
+        0x12 => "L2CAP_CONNECTION_PARAMETER_UPDATE_REQ",
+        0x13 => "L2CAP_CONNECTION_PARAMETER_UPDATE_RSP",
+        0x14 => "L2CAP_LE_CREDIT_BASED_CONNECTION_REQ",
+        0x15 => "L2CAP_LE_CREDIT_BASED_CONNECTION_RSP",
+        0x16 => "L2CAP_FLOW_CONTROL_CREDIT_IND",
+        0x17 => "L2CAP_CREDIT_BASED_CONNECTION_REQ",
+        0x18 => "L2CAP_CREDIT_BASED_CONNECTION_RSP",
+        0x19 => "L2CAP_CREDIT_BASED_RECONFIGURE_REQ",
+        0x1a => "L2CAP_CREDIT_BASED_RECONFIGURE_RSP",
+        _ => "Unknown",
+    }
+}
+
+fn get_info_type_name(info_type: u16) -> &'static str {
+    match info_type {
+        0x0001 => "Connectionless MTU",
+        0x0002 => "Extended features supported",
+        0x0003 => "Fixed channels supported",
+        _ => "Reserved for future use",
+    }
+}
+
+/// The configuration options of a request or response, walked until the
+/// payload is exhausted.
+#[derive(Debug, PartialEq)]
+struct ConfigOptions {
+    options: Vec<ConfigOption>,
+}
+
+impl ParseNode for ConfigOptions {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Self {
+        let mut options = Vec::new();
+        let mut data = data;
+        while let Some(option) = ConfigOption::new(data, None) {
+            data = &data[option.len().min(data.len())..];
+            options.push(option);
+        }
+        ConfigOptions { options }
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut offset = start_byte;
+        self.options
+            .iter()
+            .enumerate()
+            .map(|(i, option)| {
+                let json = format!(
+                    r#""Configuration Option[{}]": {{{}}}"#,
+                    i,
+                    option.as_json(offset)
+                );
+                offset += option.len() as u16;
+                json
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, PartialEq)]
 enum ConfigOptionData {
     Undefined,
     MTU(ConfigOptionMTU),
-    FlushTimeout,
-    QOS,
-    RetransmissionAndFlowControl,
-    FCS,
-    ExtendedFlowSpecification,
-    ExtendedWindowSize,
+    FlushTimeout(u16),
+    QOS(ConfigOptionQos),
+    RetransmissionAndFlowControl(ConfigOptionRfc),
+    FCS(u8),
+    ExtendedFlowSpecification(ConfigOptionEfs),
+    ExtendedWindowSize(u16),
 }
 
 impl ParseNodeOpt for ConfigOptionData {
@@ -527,26 +1710,61 @@ impl ParseNodeOpt for ConfigOptionData {
         if data.len() < 2 {
             None
         } else {
-            let opt_type = data[0];
+            let opt_type = data[0] & 0x7f;
             let data = &data[2..];
-            let opt = match opt_type {
-                0x01 => ConfigOptionData::MTU(ConfigOptionMTU::new(data, None)),
-                0x02 => ConfigOptionData::FlushTimeout,
-                0x03 => ConfigOptionData::QOS,
-                0x04 => ConfigOptionData::RetransmissionAndFlowControl,
-                0x05 => ConfigOptionData::FCS,
-                0x06 => ConfigOptionData::ExtendedFlowSpecification,
-                0x07 => ConfigOptionData::ExtendedWindowSize,
+            let opt = match (opt_type, data.len()) {
+                (0x01, 2..) => ConfigOptionData::MTU(ConfigOptionMTU::new(data, None)),
+                (0x02, 2..) => {
+                    ConfigOptionData::FlushTimeout(u16::from_le_bytes([data[0], data[1]]))
+                }
+                (0x03, 22..) => ConfigOptionData::QOS(ConfigOptionQos::new(data, None)),
+                (0x04, 9..) => {
+                    ConfigOptionData::RetransmissionAndFlowControl(ConfigOptionRfc::new(data, None))
+                }
+                (0x05, 1..) => ConfigOptionData::FCS(data[0]),
+                (0x06, 16..) => {
+                    ConfigOptionData::ExtendedFlowSpecification(ConfigOptionEfs::new(data, None))
+                }
+                (0x07, 2..) => {
+                    ConfigOptionData::ExtendedWindowSize(u16::from_le_bytes([data[0], data[1]]))
+                }
                 _ => ConfigOptionData::Undefined,
             };
             Some(opt)
         }
     }
 
-    fn as_json(&self, start_byte: u8) -> String {
+    fn as_json(&self, start_byte: u16) -> String {
         match self {
             ConfigOptionData::MTU(option) => option.as_json(start_byte),
-            _ => "".to_string(),
+            ConfigOptionData::FlushTimeout(flush_timeout) => {
+                let flush_timeout_name_s = match flush_timeout {
+                    0x0001 => "No retransmissions".to_string(),
+                    0xffff => "Infinite".to_string(),
+                    _ => format!("{} ms", flush_timeout),
+                };
+                ParseBytesNode::new(start_byte, 2).format(
+                    "Flush Timeout",
+                    flush_timeout,
+                    &flush_timeout_name_s,
+                    "",
+                )
+            }
+            ConfigOptionData::QOS(option) => option.as_json(start_byte),
+            ConfigOptionData::RetransmissionAndFlowControl(option) => option.as_json(start_byte),
+            ConfigOptionData::FCS(fcs) => {
+                let fcs_name_s = match fcs {
+                    0x00 => "No FCS",
+                    0x01 => "16-bit FCS",
+                    _ => "Reserved for future use",
+                };
+                ParseBytesNode::new(start_byte, 1).format("FCS Type", fcs, fcs_name_s, "")
+            }
+            ConfigOptionData::ExtendedFlowSpecification(option) => option.as_json(start_byte),
+            ConfigOptionData::ExtendedWindowSize(window_size) => {
+                ParseBytesNode::new(start_byte, 2).format("Max Window Size", window_size, "", "")
+            }
+            ConfigOptionData::Undefined => "".to_string(),
         }
     }
 }
@@ -558,6 +1776,12 @@ struct ConfigOption {
     data: Option<ConfigOptionData>,
 }
 
+impl ConfigOption {
+    fn len(&self) -> usize {
+        self.opt_len as usize + 2
+    }
+}
+
 impl ParseNodeOpt for ConfigOption {
     fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
         if data.len() < 2 {
@@ -565,8 +1789,9 @@ impl ParseNodeOpt for ConfigOption {
         } else {
             let opt_type = data[0];
             let opt_len = data[1];
+            let end = (opt_len as usize + 2).min(data.len());
 
-            let data = ConfigOptionData::new(data, None);
+            let data = ConfigOptionData::new(&data[..end], None);
             Some(ConfigOption {
                 opt_type,
                 opt_len,
@@ -574,21 +1799,36 @@ impl ParseNodeOpt for ConfigOption {
             })
         }
     }
-    fn as_json(&self, start_byte: u8) -> String {
-        let opt_type_s =
-            ParseBytesNode::new(start_byte, 1).format("Option Type", self.opt_type, "", "");
+    fn as_json(&self, start_byte: u16) -> String {
+        let opt_type_s = ParseBitsNode::new(start_byte, 1, 0, 7).format(
+            "Option Type",
+            self.opt_type & 0x7f,
+            get_config_option_name(self.opt_type & 0x7f),
+            "",
+        );
+        let hint_s = ParseBitsNode::new(start_byte, 1, 7, 1).format(
+            "Hint",
+            self.opt_type >> 7,
+            if self.opt_type & 0x80 != 0 {
+                "Option may be skipped if not recognized"
+            } else {
+                "Option must be recognized"
+            },
+            "",
+        );
         let opt_len_s =
             ParseBytesNode::new(start_byte + 1, 1).format("Option Length", self.opt_len, "", "");
         let data_s = self
             .data
-            .is_some()
-            .then(|| self.data.as_ref().unwrap().as_json(start_byte + 2))
-            .unwrap_or("".to_string());
+            .as_ref()
+            .map(|data| data.as_json(start_byte + 2))
+            .unwrap_or_default();
 
-        format!(
-            r#""Configuration Options": {{{}, {}, {}}}"#,
-            opt_type_s, opt_len_s, data_s
-        )
+        if data_s.is_empty() {
+            format!("{}, {}, {}", opt_type_s, hint_s, opt_len_s)
+        } else {
+            format!("{}, {}, {}, {}", opt_type_s, hint_s, opt_len_s, data_s)
+        }
     }
 }
 
@@ -604,61 +1844,316 @@ impl ParseNode for ConfigOptionMTU {
         }
     }
 
-    fn as_json(&self, start_byte: u8) -> String {
-        let mtu_s = ParseBytesNode::new(start_byte, 2).format("MTU", self.mtu, "", "");
+    fn as_json(&self, start_byte: u16) -> String {
+        ParseBytesNode::new(start_byte, 2).format("MTU", self.mtu, "", "")
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct ConfigOptionQos {
+    flags: u8,
+    service_type: u8,
+    token_rate: u32,
+    token_bucket_size: u32,
+    peak_bandwidth: u32,
+    latency: u32,
+    delay_variation: u32,
+}
+
+impl ParseNode for ConfigOptionQos {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Self {
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+        ConfigOptionQos {
+            flags: data[0],
+            service_type: data[1],
+            token_rate: u32_at(2),
+            token_bucket_size: u32_at(6),
+            peak_bandwidth: u32_at(10),
+            latency: u32_at(14),
+            delay_variation: u32_at(18),
+        }
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let fields = [
+            ("Token Rate", self.token_rate),
+            ("Token Bucket Size", self.token_bucket_size),
+            ("Peak Bandwidth", self.peak_bandwidth),
+            ("Latency", self.latency),
+            ("Delay Variation", self.delay_variation),
+        ];
+        let fields_s = fields
+            .iter()
+            .enumerate()
+            .map(|(i, (name, value))| {
+                ParseBytesNode::new(start_byte + 2 + i as u16 * 4, 4).format(name, value, "", "")
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        format!(
+            "{}, {}, {}",
+            ParseBytesNode::new(start_byte, 1).format("Flags", self.flags, "", ""),
+            ParseBytesNode::new(start_byte + 1, 1).format(
+                "Service Type",
+                self.service_type,
+                get_service_type_name(self.service_type),
+                "",
+            ),
+            fields_s
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct ConfigOptionRfc {
+    mode: u8,
+    tx_window_size: u8,
+    max_transmit: u8,
+    retransmission_timeout: u16,
+    monitor_timeout: u16,
+    mps: u16,
+}
+
+impl ParseNode for ConfigOptionRfc {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Self {
+        ConfigOptionRfc {
+            mode: data[0],
+            tx_window_size: data[1],
+            max_transmit: data[2],
+            retransmission_timeout: u16::from_le_bytes([data[3], data[4]]),
+            monitor_timeout: u16::from_le_bytes([data[5], data[6]]),
+            mps: u16::from_le_bytes([data[7], data[8]]),
+        }
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        format!(
+            "{}, {}, {}, {}, {}, {}",
+            ParseBytesNode::new(start_byte, 1).format(
+                "Mode",
+                self.mode,
+                get_mode_name(self.mode),
+                ""
+            ),
+            ParseBytesNode::new(start_byte + 1, 1).format(
+                "TxWindow size",
+                self.tx_window_size,
+                "",
+                ""
+            ),
+            ParseBytesNode::new(start_byte + 2, 1).format("MaxTransmit", self.max_transmit, "", ""),
+            ParseBytesNode::new(start_byte + 3, 2).format(
+                "Retransmission time-out",
+                self.retransmission_timeout,
+                &format!("{} ms", self.retransmission_timeout),
+                ""
+            ),
+            ParseBytesNode::new(start_byte + 5, 2).format(
+                "Monitor time-out",
+                self.monitor_timeout,
+                &format!("{} ms", self.monitor_timeout),
+                ""
+            ),
+            ParseBytesNode::new(start_byte + 7, 2).format(
+                "Maximum PDU size (MPS)",
+                self.mps,
+                "",
+                ""
+            ),
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct ConfigOptionEfs {
+    identifier: u8,
+    service_type: u8,
+    max_sdu_size: u16,
+    sdu_inter_arrival_time: u32,
+    access_latency: u32,
+    flush_timeout: u32,
+}
+
+impl ParseNode for ConfigOptionEfs {
+    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Self {
+        ConfigOptionEfs {
+            identifier: data[0],
+            service_type: data[1],
+            max_sdu_size: u16::from_le_bytes([data[2], data[3]]),
+            sdu_inter_arrival_time: u32::from_le_bytes(data[4..8].try_into().unwrap()),
+            access_latency: u32::from_le_bytes(data[8..12].try_into().unwrap()),
+            flush_timeout: u32::from_le_bytes(data[12..16].try_into().unwrap()),
+        }
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        format!(
+            "{}, {}, {}, {}, {}, {}",
+            ParseBytesNode::new(start_byte, 1).format("Identifier", self.identifier, "", ""),
+            ParseBytesNode::new(start_byte + 1, 1).format(
+                "Service Type",
+                self.service_type,
+                get_service_type_name(self.service_type),
+                ""
+            ),
+            ParseBytesNode::new(start_byte + 2, 2).format(
+                "Maximum SDU Size",
+                self.max_sdu_size,
+                "",
+                ""
+            ),
+            ParseBytesNode::new(start_byte + 4, 4).format(
+                "SDU Inter-arrival Time",
+                self.sdu_inter_arrival_time,
+                &format!("{} us", self.sdu_inter_arrival_time),
+                ""
+            ),
+            ParseBytesNode::new(start_byte + 8, 4).format(
+                "Access Latency",
+                self.access_latency,
+                &format!("{} us", self.access_latency),
+                ""
+            ),
+            ParseBytesNode::new(start_byte + 12, 4).format(
+                "Flush Timeout",
+                self.flush_timeout,
+                &format!("{} us", self.flush_timeout),
+                ""
+            ),
+        )
+    }
+}
+
+fn get_config_option_name(opt_type: u8) -> &'static str {
+    match opt_type {
+        0x01 => "MTU",
+        0x02 => "Flush Timeout",
+        0x03 => "QoS",
+        0x04 => "Retransmission and Flow Control",
+        0x05 => "Frame Check Sequence (FCS)",
+        0x06 => "Extended Flow Specification",
+        0x07 => "Extended Window Size",
+        _ => "Unknown",
+    }
+}
+
+fn get_mode_name(mode: u8) -> &'static str {
+    match mode {
+        0x00 => "L2CAP Basic Mode",
+        0x01 => "Retransmission Mode",
+        0x02 => "Flow Control Mode",
+        0x03 => "Enhanced Retransmission Mode",
+        0x04 => "Streaming Mode",
+        _ => "Reserved for future use",
+    }
+}
 
-        format!("{}", mtu_s)
+fn get_service_type_name(service_type: u8) -> &'static str {
+    match service_type {
+        0x00 => "No Traffic",
+        0x01 => "Best effort",
+        0x02 => "Guaranteed",
+        _ => "Reserved for future use",
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, PartialEq)]
 enum PSM {
     Undefined,
     SDP(Option<SDP>),
-    RFCOMM,
+    RFCOMM(Option<RFCOMM>),
     TCSBin,
     TCSBinCordless,
-    BNEP,
-    HIDControl,
-    HIDInterrupt,
+    BNEP(Option<BNEP>),
+    HIDControl(Option<HIDP>),
+    HIDInterrupt(Option<HIDP>),
     UPnP,
-    AVCTP,
-    AVDTP,
-    AVCTPBrowsing,
+    AVCTP(Option<AVCTP>),
+    AVDTP(Option<AVDTP>),
+    AVCTPBrowsing(Option<AVCTP>),
     UDICPlane,
-    ATT,
+    ATT(Option<ATT>),
     ThreeDSP,
     LEPsmIpsp,
     OTS,
-    EATT,
+    EATT(Option<ATT>),
+    // dynamic PSM of a GOEP 2.0 service, as announced in its SDP record
+    OBEX(u16, OBEX),
 }
 
 impl ParseNodeA<u16> for PSM {
     fn new(data: &[u8], args: Option<&mut HostStack>, psm: u16) -> Self {
         match psm {
             0x0001 => PSM::SDP(SDP::new(data, args)),
-            0x0003 => PSM::RFCOMM,
+            0x0003 => PSM::RFCOMM(RFCOMM::new(data, args)),
             0x0005 => PSM::TCSBin,
             0x0007 => PSM::TCSBinCordless,
-            0x000F => PSM::BNEP,
-            0x0011 => PSM::HIDControl,
-            0x0013 => PSM::HIDInterrupt,
+            0x000F => PSM::BNEP(BNEP::new(data, args)),
+            0x0011 => PSM::HIDControl(HIDP::new(data, args, false)),
+            0x0013 => PSM::HIDInterrupt(HIDP::new(data, args, true)),
             0x0015 => PSM::UPnP,
-            0x0017 => PSM::AVCTP,
-            0x0019 => PSM::AVDTP,
-            0x001B => PSM::AVCTPBrowsing,
+            0x0017 => PSM::AVCTP(AVCTP::new(data, args, false)),
+            0x0019 => PSM::AVDTP(AVDTP::new(data, args)),
+            0x001B => PSM::AVCTPBrowsing(AVCTP::new(data, args, true)),
             0x001D => PSM::UDICPlane,
-            0x001F => PSM::ATT,
+            0x001F => PSM::ATT(ATT::new(data, args)),
             0x0021 => PSM::ThreeDSP,
             0x0023 => PSM::LEPsmIpsp,
             0x0025 => PSM::OTS,
-            0x0027 => PSM::EATT,
-            _ => PSM::Undefined,
+            0x0027 => PSM::EATT(ATT::new(data, args)),
+            _ => {
+                let profile = args
+                    .as_deref()
+                    .and_then(|args| args.sdp_arg.goep_profile(&args.hci_arg, psm));
+                let side = args
+                    .as_deref()
+                    .map_or(0, |args| args.l2cap_arg.payload_side());
+                match profile {
+                    Some(profile) => PSM::OBEX(
+                        psm,
+                        OBEX::new(data, args, (ObexChannel::L2cap(psm), side, Some(profile))),
+                    ),
+                    None => PSM::Undefined,
+                }
+            }
         }
     }
 
-    fn as_json(&self, _start_byte: u8) -> String {
-        "".to_string()
+    fn as_json(&self, start_byte: u16) -> String {
+        match self {
+            PSM::SDP(sdp) => sdp
+                .as_ref()
+                .map(|sdp| sdp.as_json(start_byte))
+                .unwrap_or_default(),
+            PSM::RFCOMM(rfcomm) => rfcomm
+                .as_ref()
+                .map(|rfcomm| rfcomm.as_json(start_byte))
+                .unwrap_or_default(),
+            PSM::ATT(att) | PSM::EATT(att) => att
+                .as_ref()
+                .map(|att| att.as_json(start_byte))
+                .unwrap_or_default(),
+            PSM::BNEP(bnep) => bnep
+                .as_ref()
+                .map(|bnep| bnep.as_json(start_byte))
+                .unwrap_or_default(),
+            PSM::HIDControl(hidp) | PSM::HIDInterrupt(hidp) => hidp
+                .as_ref()
+                .map(|hidp| hidp.as_json(start_byte))
+                .unwrap_or_default(),
+            PSM::AVCTP(avctp) | PSM::AVCTPBrowsing(avctp) => avctp
+                .as_ref()
+                .map(|avctp| avctp.as_json(start_byte))
+                .unwrap_or_default(),
+            PSM::AVDTP(avdtp) => avdtp
+                .as_ref()
+                .map(|avdtp| avdtp.as_json(start_byte))
+                .unwrap_or_default(),
+            PSM::OBEX(_, obex) => obex.as_json(start_byte),
+            _ => "".to_string(),
+        }
     }
 }
 
@@ -666,22 +2161,23 @@ impl PSM {
     fn get_psm_name(&self) -> String {
         let name = match self {
             PSM::SDP(_) => "SDP",
-            PSM::RFCOMM => "RFCOMM",
+            PSM::RFCOMM(_) => "RFCOMM",
             PSM::TCSBin => "TCS-BIN",
             PSM::TCSBinCordless => "TCS-BIN-CORDLESS",
-            PSM::BNEP => "BNEP",
-            PSM::HIDControl => "HID-Control",
-            PSM::HIDInterrupt => "HID-Interrupt",
+            PSM::BNEP(_) => "BNEP",
+            PSM::HIDControl(_) => "HID-Control",
+            PSM::HIDInterrupt(_) => "HID-Interrupt",
             PSM::UPnP => "UPnP",
-            PSM::AVCTP => "AVCTP",
-            PSM::AVDTP => "AVDTP",
-            PSM::AVCTPBrowsing => "AVCTP-Browsing",
+            PSM::AVCTP(_) => "AVCTP",
+            PSM::AVDTP(_) => "AVDTP",
+            PSM::AVCTPBrowsing(_) => "AVCTP-Browsing",
             PSM::UDICPlane => "UDIC-Plane",
-            PSM::ATT => "ATT",
+            PSM::ATT(_) => "ATT",
             PSM::ThreeDSP => "3DSP",
             PSM::LEPsmIpsp => "LE-PSM-IPSP",
             PSM::OTS => "OTS",
-            PSM::EATT => "EATT",
+            PSM::EATT(_) => "EATT",
+            PSM::OBEX(..) => "OBEX",
             PSM::Undefined => "Undefined",
         };
         name.to_string()
@@ -691,23 +2187,48 @@ impl PSM {
     fn get_psm_value(&self) -> u16 {
         match self {
             PSM::SDP(_) => 0x0001,
-            PSM::RFCOMM => 0x0003,
+            PSM::RFCOMM(_) => 0x0003,
             PSM::TCSBin => 0x0005,
             PSM::TCSBinCordless => 0x0007,
-            PSM::BNEP => 0x000F,
-            PSM::HIDControl => 0x0011,
-            PSM::HIDInterrupt => 0x0013,
+            PSM::BNEP(_) => 0x000F,
+            PSM::HIDControl(_) => 0x0011,
+            PSM::HIDInterrupt(_) => 0x0013,
             PSM::UPnP => 0x0015,
-            PSM::AVCTP => 0x0017,
-            PSM::AVDTP => 0x0019,
-            PSM::AVCTPBrowsing => 0x001B,
+            PSM::AVCTP(_) => 0x0017,
+            PSM::AVDTP(_) => 0x0019,
+            PSM::AVCTPBrowsing(_) => 0x001B,
             PSM::UDICPlane => 0x001D,
-            PSM::ATT => 0x001F,
+            PSM::ATT(_) => 0x001F,
             PSM::ThreeDSP => 0x0021,
             PSM::LEPsmIpsp => 0x0023,
             PSM::OTS => 0x0025,
-            PSM::EATT => 0x0027,
+            PSM::EATT(_) => 0x0027,
+            PSM::OBEX(psm, _) => *psm,
             PSM::Undefined => panic!("psm undefined!"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{l2cap_connect, parse_packets};
+
+    // SDP on ACL handle 0x0040 and RFCOMM on 0x0041, both links picking
+    // 0x0040 <-> 0x0041 for their channel
+    #[test]
+    fn channels_are_scoped_by_acl_handle() {
+        let sdp = l2cap_connect(0x0040, 1, 0x0001, (0x0040, 0x0041));
+        let rfcomm = l2cap_connect(0x0041, 1, 0x0003, (0x0040, 0x0041));
+        let (json, _) = parse_packets(&[
+            &sdp[0],
+            &sdp[1],
+            &rfcomm[0],
+            &rfcomm[1],
+            "02 41 20 08 00 04 00 41 00 03 3f 01 1c",
+            "02 40 20 18 00 14 00 41 00 06 00 01 00 0f 35 03 19 11 05 ff ff 35 05 0a 00 00 ff ff 00",
+        ]);
+        assert!(json[4].contains(r#""RFCOMM": {"EA": [0x1, "#));
+        assert!(json[4].contains(r#""Control": [0x3f, "SABM", "B(10, 1)", ""]"#));
+        assert!(json[5].contains(r#""SDP": {"PDU ID": [0x6, "SDP_ServiceSearchAttributeRequest""#));
+    }
+}
@@ -0,0 +1,410 @@
+use crate::HostStack;
+use crate::ParseNodeA;
+use crate::ParseNodeOptA;
+
+use crate::ParseBitsNode;
+use crate::ParseBytesNode;
+
+use super::PSM;
+
+/// What the receiving end of an Enhanced Retransmission or Streaming mode
+/// channel has seen in one direction: the TxSeq it expects next and the SDU
+/// being reassembled from SAR segments.
+#[derive(Default, Debug)]
+pub(super) struct ErtmReceiver {
+    expected_tx_seq: Option<u32>,
+    sdu: Vec<u8>,
+    sdu_length: u16,
+}
+
+impl ErtmReceiver {
+    /// Moves past `tx_seq`, false when it is behind the expected TxSeq, a
+    /// retransmission of an I-frame already received.
+    fn accept(&mut self, tx_seq: u32, extended: bool) -> bool {
+        let modulo = if extended { 1 << 14 } else { 1 << 6 };
+        if let Some(expected) = self.expected_tx_seq {
+            let behind = (expected + modulo - tx_seq) % modulo;
+            if behind != 0 && behind <= modulo / 2 {
+                return false;
+            }
+        }
+        self.expected_tx_seq = Some((tx_seq + 1) % modulo);
+        true
+    }
+}
+
+/// A frame on a dynamic channel configured for Enhanced Retransmission or
+/// Streaming mode: control field, optional SDU length, payload and FCS.
+#[derive(Debug, PartialEq)]
+pub(super) struct ErtmFrame {
+    control: Control,
+    sdu_length: Option<u16>,
+    payload: Vec<u8>,
+    fcs: Option<Fcs>,
+    // boxed, the reassembled SDU is a whole upper layer packet
+    sdu: Option<Box<PSM>>,
+    sdu_error: &'static str,
+}
+
+#[derive(Debug, PartialEq)]
+struct Fcs {
+    received: u16,
+    calculated: u16,
+}
+
+impl ParseNodeOptA<(u16, u16)> for ErtmFrame {
+    /// Gets the CID and the PDU Length of the Basic L2CAP header, the FCS is
+    /// found and calculated from the length the header announces.
+    fn new(data: &[u8], args: Option<&mut HostStack>, (cid, pdu_len): (u16, u16)) -> Option<Self> {
+        let mut args = args;
+        let channel = args.as_deref_mut().and_then(|args| {
+            let acl_handle = args.hci_arg.acl_handle();
+            args.l2cap_arg.channel_mut(acl_handle, cid)
+        })?;
+        // frames sent to the requester's CID are received by the requester
+        let local = channel.source_cid == cid;
+        // a capture cut short has lost the FCS and the end of the payload
+        let truncated = data.len() < pdu_len as usize;
+        let data = &data[..data.len().min(pdu_len as usize)];
+        let control_len = if channel.extended_control { 4 } else { 2 };
+        let fcs_len = if channel.fcs_enabled() && !truncated {
+            2
+        } else {
+            0
+        };
+        if data.len() < control_len + fcs_len {
+            return None;
+        }
+
+        let control = Control::new(&data[..control_len], channel.extended_control);
+        let body = &data[control_len..data.len() - fcs_len];
+        let fcs = (fcs_len != 0).then(|| {
+            let mut header = pdu_len.to_le_bytes().to_vec();
+            header.extend_from_slice(&cid.to_le_bytes());
+            header.extend_from_slice(&data[..data.len() - 2]);
+            Fcs {
+                received: u16::from_le_bytes([data[data.len() - 2], data[data.len() - 1]]),
+                calculated: crc16(&header),
+            }
+        });
+        let (sdu_length, payload) = match control.sar() {
+            Some(SAR_START) if body.len() >= 2 => {
+                (Some(u16::from_le_bytes([body[0], body[1]])), &body[2..])
+            }
+            _ => (None, body),
+        };
+
+        // a receiver drops frames with a bad FCS, so they take no part in
+        // reassembly, nor do frames missing from the capture in part or
+        // retransmitted after they were received
+        let psm = channel.psm;
+        let extended = channel.extended_control;
+        let receiver = channel.receiver_mut(local);
+        let mut sdu_error = "";
+        let sdu = match fcs.as_ref().map(|fcs| fcs.received == fcs.calculated) {
+            Some(false) => None,
+            _ if truncated => {
+                sdu_error = match channel.fcs_enabled() {
+                    true => "Frame shorter than the PDU Length, FCS not captured",
+                    false => "Frame shorter than the PDU Length",
+                };
+                None
+            }
+            _ if control
+                .field("TxSeq")
+                .is_some_and(|tx_seq| !receiver.accept(tx_seq, extended)) =>
+            {
+                sdu_error = "Retransmission of an I-frame already received";
+                None
+            }
+            _ => match control.sar() {
+                Some(SAR_UNSEGMENTED) => Some(payload.to_vec()),
+                Some(SAR_START) => {
+                    receiver.sdu = payload.to_vec();
+                    receiver.sdu_length = sdu_length.unwrap_or_default();
+                    None
+                }
+                Some(SAR_CONTINUATION) => {
+                    receiver.sdu.extend_from_slice(payload);
+                    None
+                }
+                Some(SAR_END) => {
+                    receiver.sdu.extend_from_slice(payload);
+                    let sdu = std::mem::take(&mut receiver.sdu);
+                    if sdu.len() != receiver.sdu_length as usize {
+                        sdu_error = "Reassembled SDU length does not match L2CAP SDU Length";
+                    }
+                    Some(sdu)
+                }
+                _ => None,
+            },
+        };
+        let sdu = sdu.map(|sdu| Box::new(PSM::new(&sdu, args, psm)));
+
+        Some(ErtmFrame {
+            control,
+            sdu_length,
+            payload: payload.to_vec(),
+            fcs,
+            sdu,
+            sdu_error,
+        })
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut json = self.control.as_json(start_byte);
+        let mut offset = start_byte + self.control.len();
+        if let Some(sdu_length) = self.sdu_length {
+            json.push_str(", ");
+            json.push_str(&ParseBytesNode::new(offset, 2).format(
+                "L2CAP SDU Length",
+                sdu_length,
+                "",
+                "",
+            ));
+            offset += 2;
+        }
+        let payload_start = offset;
+        if !self.payload.is_empty() {
+            json.push_str(", ");
+            json.push_str(
+                &ParseBytesNode::new(offset, self.payload.len() as u16).format(
+                    "Information Payload",
+                    &self.payload[..],
+                    "",
+                    self.sdu_error,
+                ),
+            );
+            offset += self.payload.len() as u16;
+        }
+        if let Some(fcs) = &self.fcs {
+            let error_s = match fcs.received == fcs.calculated {
+                true => String::new(),
+                false => format!("FCS mismatch, calculated {:#06x}", fcs.calculated),
+            };
+            json.push_str(", ");
+            json.push_str(&ParseBytesNode::new(offset, 2).format(
+                "FCS",
+                fcs.received,
+                "",
+                &error_s,
+            ));
+        }
+        let sdu_s = self
+            .sdu
+            .as_ref()
+            .map(|sdu| sdu.as_json(payload_start))
+            .unwrap_or_default();
+        if !sdu_s.is_empty() {
+            json.push_str(", ");
+            json.push_str(&sdu_s);
+        }
+        json
+    }
+}
+
+const SAR_UNSEGMENTED: u8 = 0b00;
+const SAR_START: u8 = 0b01;
+const SAR_END: u8 = 0b10;
+const SAR_CONTINUATION: u8 = 0b11;
+
+/// The Enhanced Control Field (16 bits) or Extended Control Field (32 bits,
+/// when an Extended Window Size was negotiated).
+#[derive(Debug, PartialEq)]
+struct Control {
+    value: u32,
+    extended: bool,
+}
+
+impl Control {
+    fn new(data: &[u8], extended: bool) -> Self {
+        let value = match extended {
+            true => u32::from_le_bytes(data[0..4].try_into().unwrap()),
+            false => u16::from_le_bytes([data[0], data[1]]) as u32,
+        };
+        Control { value, extended }
+    }
+
+    fn len(&self) -> u16 {
+        if self.extended {
+            4
+        } else {
+            2
+        }
+    }
+
+    fn is_i_frame(&self) -> bool {
+        self.value & 0x01 == 0
+    }
+
+    /// Bit position and width of each field, in the order they appear.
+    fn fields(&self) -> &'static [(&'static str, u8, u8)] {
+        match (self.extended, self.is_i_frame()) {
+            (false, true) => &[
+                ("Type", 0, 1),
+                ("TxSeq", 1, 6),
+                ("F", 7, 1),
+                ("ReqSeq", 8, 6),
+                ("SAR", 14, 2),
+            ],
+            (false, false) => &[
+                ("Type", 0, 1),
+                ("S", 2, 2),
+                ("P", 4, 1),
+                ("F", 7, 1),
+                ("ReqSeq", 8, 6),
+            ],
+            (true, true) => &[
+                ("Type", 0, 1),
+                ("F", 1, 1),
+                ("ReqSeq", 2, 14),
+                ("SAR", 16, 2),
+                ("TxSeq", 18, 14),
+            ],
+            (true, false) => &[
+                ("Type", 0, 1),
+                ("F", 1, 1),
+                ("ReqSeq", 2, 14),
+                ("S", 16, 2),
+                ("P", 18, 1),
+            ],
+        }
+    }
+
+    fn field(&self, name: &str) -> Option<u32> {
+        self.fields()
+            .iter()
+            .find(|(field, _, _)| *field == name)
+            .map(|(_, start, len)| (self.value >> start) & ((1 << len) - 1))
+    }
+
+    fn sar(&self) -> Option<u8> {
+        self.field("SAR").map(|sar| sar as u8)
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let fields_s = self
+            .fields()
+            .iter()
+            .map(|(name, start, len)| {
+                let value = (self.value >> start) & ((1 << len) - 1);
+                let alias = match *name {
+                    "Type" if value == 0 => "I-frame",
+                    "Type" => "S-frame",
+                    "SAR" => match value as u8 {
+                        SAR_UNSEGMENTED => "Unsegmented L2CAP SDU",
+                        SAR_START => "Start of L2CAP SDU",
+                        SAR_END => "End of L2CAP SDU",
+                        _ => "Continuation of L2CAP SDU",
+                    },
+                    "S" => match value {
+                        0b00 => "RR - Receiver Ready",
+                        0b01 => "REJ - Reject",
+                        0b10 => "RNR - Receiver Not Ready",
+                        _ => "SREJ - Select Reject",
+                    },
+                    _ => "",
+                };
+                ParseBitsNode::new(start_byte, self.len(), *start, *len)
+                    .format(name, value, alias, "")
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        let name = match self.extended {
+            true => "Extended Control Field",
+            false => "Enhanced Control Field",
+        };
+        format!(r#""{}": {{{}}}"#, name, fields_s)
+    }
+}
+
+/// The CRC-16 used for the L2CAP FCS, generator D^16 + D^15 + D^2 + 1 with
+/// an initial value of zero, computed least significant bit first.
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u16, |crc, _| match crc & 0x01 {
+            0 => crc >> 1,
+            _ => (crc >> 1) ^ 0xa001,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{hci, str_to_array, HostStack};
+
+    // SDP channel 0x0040 <-> 0x0080, both sides configured for ERTM with FCS
+    const SETUP: [&str; 6] = [
+        "02 40 20 0c 00 08 00 01 00 02 01 04 00 01 00 40 00",
+        "02 40 20 10 00 0c 00 01 00 03 01 08 00 80 00 40 00 00 00 00 00",
+        "02 40 20 17 00 13 00 01 00 04 02 0f 00 80 00 00 00 04 09 03 08 03 d0 07 e0 2e a0 02",
+        "02 40 20 19 00 15 00 01 00 05 02 11 00 40 00 00 00 00 00 04 09 03 08 03 d0 07 e0 2e a0 02",
+        "02 40 20 17 00 13 00 01 00 04 03 0f 00 40 00 00 00 04 09 03 08 03 d0 07 e0 2e a0 02",
+        "02 40 20 19 00 15 00 01 00 05 03 11 00 80 00 00 00 00 00 04 09 03 08 03 d0 07 e0 2e a0 02",
+    ];
+
+    fn parse(frames: &[&str]) -> Vec<String> {
+        let mut args = HostStack::new();
+        SETUP
+            .iter()
+            .chain(frames)
+            .map(|packet| hci::parse(&str_to_array(packet), &mut args))
+            .skip(SETUP.len())
+            .collect()
+    }
+
+    #[test]
+    fn sar_segments_are_reassembled() {
+        let json = parse(&[
+            "02 40 20 10 00 0c 00 80 00 00 40 0d 00 02 00 01 00 08 35 39 e1",
+            "02 40 20 0f 00 0b 00 80 00 02 80 03 19 11 0a 00 10 00 61 5b",
+        ]);
+        assert!(json[0].contains(r#""L2CAP SDU Length": [0xd, "", "B(11, 2)", ""]"#));
+        assert!(json[0].contains(r#""FCS": [0xe139, "", "B(19, 2)", ""]"#));
+        assert!(!json[0].contains(r#""SDP""#));
+        assert!(json[1].contains(r#""FCS": [0x5b61, "", "B(18, 2)", ""]"#));
+        assert!(json[1].contains(r#""PDU ID": [0x2, "SDP_ServiceSearchRequest""#));
+        assert!(json[1].contains(r#""Element[0]": [0x110a, "UUID (Audio Source)""#));
+    }
+
+    #[test]
+    fn each_direction_is_reassembled_apart() {
+        // a request and its response interleaved, the request's first
+        // segment retransmitted before its end
+        let json = parse(&[
+            "02 40 20 10 00 0c 00 80 00 00 40 0d 00 02 00 01 00 08 35 39 e1",
+            "02 40 20 10 00 0c 00 40 00 00 40 0e 00 03 00 01 00 09 00 ba e3",
+            "02 40 20 10 00 0c 00 80 00 00 40 0d 00 02 00 01 00 08 35 39 e1",
+            "02 40 20 0f 00 0b 00 80 00 02 80 03 19 11 0a 00 10 00 61 5b",
+            "02 40 20 10 00 0c 00 40 00 02 80 01 00 01 00 01 00 00 00 0c de",
+        ]);
+        assert!(json[2].contains(
+            r#""Information Payload": ["02 00 01 00 08 35", "", "B(13, 6)", "Retransmission of an I-frame already received"]"#
+        ));
+        assert!(json[3].contains(r#""PDU ID": [0x2, "SDP_ServiceSearchRequest""#));
+        assert!(json[3].contains(r#""Element[0]": [0x110a, "UUID (Audio Source)""#));
+        assert!(!json[3].contains("does not match"));
+        assert!(json[4].contains(r#""PDU ID": [0x3, "SDP_ServiceSearchResponse""#));
+        assert!(!json[4].contains("does not match"));
+    }
+
+    #[test]
+    fn bad_fcs_is_flagged_and_not_reassembled() {
+        let json = parse(&[
+            "02 40 20 15 00 11 00 80 00 00 00 02 00 01 00 08 35 03 19 11 0a 00 10 00 fd 90",
+        ]);
+        assert!(json[0]
+            .contains(r#""FCS": [0x90fd, "", "B(24, 2)", "FCS mismatch, calculated 0x90fc"]"#));
+        assert!(!json[0].contains(r#""SDP""#));
+    }
+
+    #[test]
+    fn truncated_frame_is_flagged() {
+        // the PDU Length announces 17 bytes, the capture holds 14
+        let json = parse(&["02 40 20 12 00 11 00 80 00 00 00 02 00 01 00 08 35 03 19 11 0a 00 10"]);
+        assert!(json[0].contains(
+            r#""Information Payload": ["02 00 01 00 08 35 03 19 11 0a 00 10", "", "B(11, 12)", "Frame shorter than the PDU Length, FCS not captured"]"#
+        ));
+        assert!(!json[0].contains(r#""FCS""#));
+    }
+}
@@ -0,0 +1,175 @@
+use crate::HostStack;
+use crate::ParseNodeA;
+use crate::ParseNodeOptA;
+
+use crate::ParseBytesNode;
+
+use super::PSM;
+
+/// What the receiving end of a credit based channel has announced for one
+/// direction: the largest K-frame it accepts, the credits it has handed out
+/// and not yet seen used, and the SDU currently being reassembled.
+#[derive(Default, Debug)]
+pub(super) struct CreditFlow {
+    pub(super) mps: u16,
+    pub(super) credits: u16,
+    sdu: Vec<u8>,
+    sdu_length: Option<u16>,
+}
+
+/// A K-frame on a channel opened in LE Credit Based or Enhanced Credit Based
+/// Flow Control mode.
+#[derive(Debug, PartialEq)]
+pub(super) struct KFrame {
+    sdu_length: Option<u16>,
+    sdu_length_error: &'static str,
+    payload: Vec<u8>,
+    payload_error: String,
+    sdu: Option<Box<PSM>>,
+}
+
+impl ParseNodeOptA<u16> for KFrame {
+    fn new(data: &[u8], args: Option<&mut HostStack>, cid: u16) -> Option<Self> {
+        let mut args = args;
+        let channel = args.as_deref_mut().and_then(|args| {
+            let acl_handle = args.hci_arg.acl_handle();
+            args.l2cap_arg.channel_mut(acl_handle, cid)
+        })?;
+        // frames sent to the requester's CID are received by the requester
+        let local = channel.source_cid == cid;
+        let mtu = match local {
+            true => channel.local_mtu,
+            false => channel.remote_mtu,
+        };
+        let psm = channel.psm;
+        let flow = channel.flow_mut(local);
+
+        let mut errors = Vec::new();
+        if data.len() > flow.mps as usize {
+            errors.push(format!("K-frame exceeds MPS of {}", flow.mps));
+        }
+        match flow.credits.checked_sub(1) {
+            Some(credits) => flow.credits = credits,
+            None => errors.push("Credit underflow, sender had no credits left".to_string()),
+        }
+
+        let mut sdu_length_error = "";
+        let (sdu_length, payload) = match flow.sdu_length {
+            None if data.len() >= 2 => {
+                let sdu_length = u16::from_le_bytes([data[0], data[1]]);
+                if sdu_length > mtu {
+                    sdu_length_error = "L2CAP SDU Length exceeds MTU";
+                }
+                flow.sdu_length = Some(sdu_length);
+                flow.sdu.clear();
+                (Some(sdu_length), &data[2..])
+            }
+            _ => (None, data),
+        };
+
+        let mut sdu = None;
+        if let Some(expected) = flow.sdu_length {
+            flow.sdu.extend_from_slice(payload);
+            if flow.sdu.len() >= expected as usize {
+                if flow.sdu.len() > expected as usize {
+                    errors.push("Reassembled SDU longer than L2CAP SDU Length".to_string());
+                }
+                flow.sdu_length = None;
+                sdu = Some(std::mem::take(&mut flow.sdu));
+            }
+        }
+        let sdu = sdu.map(|sdu| Box::new(PSM::new(&sdu, args, psm)));
+
+        Some(KFrame {
+            sdu_length,
+            sdu_length_error,
+            payload: payload.to_vec(),
+            payload_error: errors.join("; "),
+            sdu,
+        })
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut fields = Vec::new();
+        let mut offset = start_byte;
+        if let Some(sdu_length) = self.sdu_length {
+            fields.push(ParseBytesNode::new(offset, 2).format(
+                "L2CAP SDU Length",
+                sdu_length,
+                "",
+                self.sdu_length_error,
+            ));
+            offset += 2;
+        }
+        fields.push(
+            ParseBytesNode::new(offset, self.payload.len() as u16).format(
+                "Information Payload",
+                &self.payload[..],
+                "",
+                &self.payload_error,
+            ),
+        );
+        let sdu_s = self
+            .sdu
+            .as_ref()
+            .map(|sdu| sdu.as_json(offset))
+            .unwrap_or_default();
+        if !sdu_s.is_empty() {
+            fields.push(sdu_s);
+        }
+        fields.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{hci, str_to_array, HostStack};
+
+    // EATT channel 0x0040 <-> 0x0081, MPS 10 both ways, the requester grants
+    // 2 credits and the responder 1
+    const SETUP: [&str; 2] = [
+        "02 40 20 12 00 0e 00 05 00 14 01 0a 00 27 00 40 00 64 00 0a 00 02 00",
+        "02 40 20 12 00 0e 00 05 00 15 01 0a 00 81 00 64 00 0a 00 01 00 00 00",
+    ];
+
+    fn parse(frames: &[&str]) -> Vec<String> {
+        let mut args = HostStack::new();
+        SETUP
+            .iter()
+            .chain(frames)
+            .map(|packet| hci::parse(&str_to_array(packet), &mut args))
+            .skip(SETUP.len())
+            .collect()
+    }
+
+    #[test]
+    fn sdu_is_reassembled_across_k_frames() {
+        let json = parse(&[
+            "02 40 20 0e 00 0a 00 81 00 0c 00 12 03 00 01 02 03 04 05",
+            "02 40 20 08 00 04 00 81 00 06 07 08 09",
+        ]);
+        assert!(json[0].contains(r#""L2CAP SDU Length": [0xc, "", "B(9, 2)", ""]"#));
+        assert!(!json[0].contains(r#""ATT""#));
+        assert!(json[1].contains(r#""Opcode": [0x12, "ATT_WRITE_REQ""#));
+        assert!(json[1]
+            .contains(r#""Attribute Value": ["01 02 03 04 05 06 07 08 09", "", "B(12, 9)", ""]"#));
+    }
+
+    #[test]
+    fn credits_are_counted_per_direction() {
+        let json = parse(&[
+            "02 40 20 0e 00 0a 00 81 00 0c 00 12 03 00 01 02 03 04 05",
+            "02 40 20 08 00 04 00 81 00 06 07 08 09",
+            "02 40 20 09 00 05 00 40 00 03 00 13 00 00",
+        ]);
+        assert!(!json[0].contains("Credit underflow"));
+        assert!(json[1].contains("Credit underflow, sender had no credits left"));
+        assert!(!json[2].contains("Credit underflow"));
+    }
+
+    #[test]
+    fn k_frame_larger_than_mps_is_flagged() {
+        let json = parse(&["02 40 20 0f 00 0b 00 40 00 09 00 00 01 02 03 04 05 06 07 08"]);
+        assert!(json[0].contains("K-frame exceeds MPS of 10"));
+    }
+}
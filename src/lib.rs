@@ -1,46 +1,178 @@
 pub mod hci;
 pub use hci::HciPacket;
 
+mod ad;
+mod assigned_numbers;
+mod att;
+mod avctp;
+mod avdtp;
+mod avrcp;
+mod bnep;
+mod hfp;
+mod hid;
+mod hidp;
+pub use ad::{AdDecoderKey, AdPayloadDecoder};
 mod l2cap;
+mod obex;
+mod rfcomm;
 mod sdp;
+mod smp;
 
+use ad::AdArg;
+use att::ATTArg;
+use avctp::AVCTPArg;
+use avdtp::AVDTPArg;
+use avrcp::AVRCPArg;
+use hci::HciArg;
+use hfp::HFPArg;
+use hidp::HIDPArg;
 use l2cap::L2CAPArg;
+use obex::OBEXArg;
+use rfcomm::RFCOMMArg;
 use sdp::SDPArg;
+use smp::SMPArg;
 
 #[allow(unused)]
 #[derive(Debug)]
 pub struct HostStack {
+    hci_arg: HciArg,
+    ad_arg: AdArg,
     l2cap_arg: L2CAPArg,
     sdp_arg: SDPArg,
+    att_arg: ATTArg,
+    smp_arg: SMPArg,
+    rfcomm_arg: RFCOMMArg,
+    hfp_arg: HFPArg,
+    obex_arg: OBEXArg,
+    avdtp_arg: AVDTPArg,
+    avctp_arg: AVCTPArg,
+    avrcp_arg: AVRCPArg,
+    hidp_arg: HIDPArg,
 }
 
 impl HostStack {
     pub fn new() -> Self {
         HostStack {
+            hci_arg: HciArg::default(),
+            ad_arg: AdArg::default(),
             l2cap_arg: L2CAPArg::default(),
             sdp_arg: SDPArg::default(),
+            att_arg: ATTArg::default(),
+            smp_arg: SMPArg::default(),
+            rfcomm_arg: RFCOMMArg::default(),
+            hfp_arg: HFPArg::default(),
+            obex_arg: OBEXArg::default(),
+            avdtp_arg: AVDTPArg::default(),
+            avctp_arg: AVCTPArg::default(),
+            avrcp_arg: AVRCPArg::default(),
+            hidp_arg: HIDPArg::default(),
         }
     }
+
+    /// Ends everything known of the connection `acl_handle`, on HCI
+    /// Disconnection Complete. What the summaries show of it is kept.
+    fn disconnect(&mut self, acl_handle: u16) {
+        self.hci_arg.disconnect(acl_handle);
+        self.l2cap_arg.disconnect(acl_handle);
+        self.att_arg.disconnect(acl_handle);
+        self.smp_arg.disconnect(acl_handle);
+        self.sdp_arg.disconnect(acl_handle);
+        self.rfcomm_arg.disconnect(acl_handle);
+        self.hfp_arg.disconnect(acl_handle);
+        self.obex_arg.disconnect(acl_handle);
+        self.avdtp_arg.disconnect(acl_handle);
+        self.avctp_arg.disconnect(acl_handle);
+        self.avrcp_arg.disconnect(acl_handle);
+        self.hidp_arg.disconnect(acl_handle);
+    }
+
+    /// Summary of the pairings seen so far: Secure Simple Pairing per
+    /// BD_ADDR and Security Manager pairing per connection.
+    pub fn pairing_summary(&self) -> String {
+        let summaries = [
+            self.hci_arg.pairing_summary(),
+            self.smp_arg.pairing_summary(),
+        ];
+        format!(
+            "{{{}}}",
+            summaries
+                .into_iter()
+                .filter(|summary| !summary.is_empty())
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+
+    /// Per connection view of the remote GATT databases rebuilt from the
+    /// discovery procedures seen so far.
+    pub fn gatt_summary(&self) -> String {
+        format!("{{{}}}", self.att_arg.gatt_summary())
+    }
+
+    /// Per remote device list of the SDP service records seen so far, with
+    /// the profile versions and the channels they are reached on. Devices
+    /// are named by BD_ADDR once their Connection Complete was seen.
+    pub fn sdp_summary(&self) -> String {
+        format!("{{{}}}", self.sdp_arg.summary())
+    }
+
+    /// Per connection state of the RFCOMM DLCs seen so far, with the profile
+    /// each server channel was found to serve in the SDP records.
+    pub fn rfcomm_summary(&self) -> String {
+        format!("{{{}}}", self.rfcomm_arg.summary())
+    }
+
+    /// Per DLC state of the hands-free service level connections seen so
+    /// far: features, codecs, indicators and volume.
+    pub fn hfp_summary(&self) -> String {
+        format!("{{{}}}", self.hfp_arg.summary())
+    }
+
+    /// Per channel state of the OBEX sessions seen so far: profile, current
+    /// folder, the last PBAP vCard listing and the requests that failed.
+    pub fn obex_summary(&self) -> String {
+        format!("{{{}}}", self.obex_arg.summary())
+    }
+
+    /// Per connection table of the AVDTP stream end points seen so far, with
+    /// their capabilities, configuration and state, and per media channel
+    /// statistics: sequence gaps, bit rate over time and bitpool changes.
+    pub fn avdtp_summary(&self) -> String {
+        format!("{{{}}}", self.avdtp_arg.summary())
+    }
+
+    /// Registers a decoder for manufacturer specific data or service data. It
+    /// takes precedence over the built-in decoders and earlier registrations
+    /// for the same key.
+    pub fn register_ad_decoder(&mut self, key: AdDecoderKey, decoder: AdPayloadDecoder) {
+        self.ad_arg.register(key, decoder);
+    }
+}
+
+impl Default for HostStack {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 pub trait ParseNode {
     fn new(data: &[u8], args: Option<&mut HostStack>) -> Self;
-    fn as_json(&self, start_byte: u8) -> String;
+    fn as_json(&self, start_byte: u16) -> String;
 }
 
 pub trait ParseNodeA<T> {
     fn new(data: &[u8], args: Option<&mut HostStack>, param: T) -> Self;
-    fn as_json(&self, start_byte: u8) -> String;
+    fn as_json(&self, start_byte: u16) -> String;
 }
 
 pub trait ParseNodeOpt: Sized {
     fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self>;
-    fn as_json(&self, start_byte: u8) -> String;
+    fn as_json(&self, start_byte: u16) -> String;
 }
 
 pub trait ParseNodeOptA<T>: Sized {
     fn new(data: &[u8], args: Option<&mut HostStack>, param: T) -> Option<Self>;
-    fn as_json(&self, start_byte: u8) -> String;
+    fn as_json(&self, start_byte: u16) -> String;
 }
 
 use duplicate::duplicate_item;
@@ -54,6 +186,7 @@ pub trait ParseNodeFormat {
     [ u8 ]; [ &u8 ];
     [ u16 ]; [ &u16 ];
     [ u32 ]; [ &u32 ];
+    [ u64 ]; [ &u64 ];
 )]
 impl ParseNodeFormat for int_type {
     fn node_format(&self) -> String {
@@ -61,6 +194,13 @@ impl ParseNodeFormat for int_type {
     }
 }
 
+impl ParseNodeFormat for &[u8] {
+    fn node_format(&self) -> String {
+        let bytes: Vec<String> = self.iter().map(|b| format!("{:02x}", b)).collect();
+        format!(r#""{}""#, bytes.join(" "))
+    }
+}
+
 #[duplicate_item(
     str_type;
     [ &str ];
@@ -73,12 +213,12 @@ impl ParseNodeFormat for str_type {
 }
 
 pub struct ParseBytesNode {
-    start_byte: u8,
-    len_in_bytes: u8,
+    start_byte: u16,
+    len_in_bytes: u16,
 }
 
 impl ParseBytesNode {
-    fn new(start_byte: u8, len_in_bytes: u8) -> Self {
+    fn new(start_byte: u16, len_in_bytes: u16) -> Self {
         ParseBytesNode {
             start_byte,
             len_in_bytes,
@@ -99,14 +239,14 @@ impl ParseBytesNode {
 }
 
 pub struct ParseBitsNode {
-    start_byte: u8,
-    len_in_bytes: u8,
+    start_byte: u16,
+    len_in_bytes: u16,
     start_bit: u8,
     len_in_bits: u8,
 }
 
 impl ParseBitsNode {
-    fn new(start_byte: u8, len_in_bytes: u8, start_bit: u8, len_in_bits: u8) -> Self {
+    fn new(start_byte: u16, len_in_bytes: u16, start_bit: u8, len_in_bits: u8) -> Self {
         ParseBitsNode {
             start_byte,
             len_in_bytes,
@@ -135,3 +275,101 @@ pub fn str_to_array(s: &str) -> Vec<u8> {
         .map(|x| u8::from_str_radix(x, 16).unwrap())
         .collect()
 }
+
+/// Turns text carried in a packet into something safe to embed in the JSON
+/// output: invalid UTF-8 and control characters are dropped, quotes escaped.
+pub(crate) fn bytes_to_string(data: &[u8]) -> String {
+    String::from_utf8_lossy(data)
+        .chars()
+        .filter(|c| !c.is_control())
+        .flat_map(|c| match c {
+            '"' | '\\' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+/// Keys of the summary entries handed out so far. A handle is given to a
+/// new connection once the last one on it ended, the entries of the later
+/// connections are numbered from the second: "Connection 0x40 #2".
+#[derive(Default)]
+pub(crate) struct SummaryKeys(Vec<String>);
+
+impl SummaryKeys {
+    pub(crate) fn unique(&mut self, key: String) -> String {
+        let count = self.0.iter().filter(|seen| **seen == key).count();
+        self.0.push(key.clone());
+        match count {
+            0 => key,
+            count => format!("{} #{}", key, count + 1),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use crate::{hci, str_to_array, HostStack};
+
+    /// Parses `packets` in order on one host stack, as they come in a
+    /// capture. Returns the JSON of each and the stack after the last.
+    pub(crate) fn parse_packets(packets: &[&str]) -> (Vec<String>, HostStack) {
+        let mut args = HostStack::new();
+        let json = packets
+            .iter()
+            .map(|packet| hci::parse(&str_to_array(packet), &mut args))
+            .collect();
+        (json, args)
+    }
+
+    /// The L2CAP Connection Request and successful Response opening `psm`
+    /// on `acl_handle`, the initiator allocating `source_cid` and the
+    /// acceptor `dest_cid`.
+    pub(crate) fn l2cap_connect(
+        acl_handle: u16,
+        identifier: u8,
+        psm: u16,
+        (source_cid, dest_cid): (u16, u16),
+    ) -> [String; 2] {
+        let signal = |code: u8, data: &[u16]| {
+            let mut pdu = vec![code, identifier, 2 * data.len() as u8, 0];
+            pdu.extend(data.iter().flat_map(|value| value.to_le_bytes()));
+            let mut packet = vec![0x02];
+            packet.extend((acl_handle | 0x2000).to_le_bytes());
+            packet.extend((pdu.len() as u16 + 4).to_le_bytes());
+            packet.extend((pdu.len() as u16).to_le_bytes());
+            packet.extend(1u16.to_le_bytes());
+            packet.extend(pdu);
+            packet
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+        [
+            signal(0x02, &[psm, source_cid]),
+            signal(0x03, &[dest_cid, source_cid, 0, 0]),
+        ]
+    }
+
+    /// Opens `psm` as 0x0040 <-> 0x0041 on ACL handle 0x0040, then parses
+    /// `packets` like [`parse_packets`]. Returns the JSON of `packets` only.
+    pub(crate) fn parse_on_channel(psm: u16, packets: &[&str]) -> (Vec<String>, HostStack) {
+        let [request, response] = l2cap_connect(0x0040, 1, psm, (0x0040, 0x0041));
+        let mut all = vec![request.as_str(), response.as_str()];
+        all.extend_from_slice(packets);
+        let (mut json, args) = parse_packets(&all);
+        json.drain(..2);
+        (json, args)
+    }
+
+    #[test]
+    fn l2cap_connect_builds_signaling_packets() {
+        assert_eq!(
+            l2cap_connect(0x0040, 1, 0x0001, (0x0040, 0x0041)),
+            [
+                "02 40 20 0c 00 08 00 01 00 02 01 04 00 01 00 40 00",
+                "02 40 20 10 00 0c 00 01 00 03 01 08 00 41 00 40 00 00 00 00 00",
+            ]
+        );
+    }
+}
@@ -0,0 +1,695 @@
+use crate::assigned_numbers::get_uuid16_name;
+use crate::bytes_to_string;
+use crate::HostStack;
+use crate::ParseNodeA;
+use crate::SummaryKeys;
+
+use crate::ParseBitsNode;
+use crate::ParseBytesNode;
+
+mod headers;
+use headers::{HeaderValue, ObexHeader};
+
+const CONNECT: u8 = 0x00;
+const DISCONNECT: u8 = 0x01;
+const GET: u8 = 0x03;
+const SET_PATH: u8 = 0x05;
+const SUCCESS: u8 = 0xa0;
+
+/// Where an OBEX session runs: an RFCOMM DLC or, for GOEP 2.0, an L2CAP
+/// channel on the PSM the SDP record announced.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ObexChannel {
+    Rfcomm(u8),
+    L2cap(u16),
+}
+
+#[derive(Default, Debug)]
+pub struct OBEXArg {
+    sessions: Vec<ObexSession>,
+    // state of the connections that have ended, kept for the summary
+    closed: Vec<ObexSession>,
+}
+
+impl OBEXArg {
+    /// Returns the session on `channel` of `acl_handle`, creating it on first
+    /// use.
+    fn session(&mut self, acl_handle: u16, channel: ObexChannel) -> &mut ObexSession {
+        let index = match self
+            .sessions
+            .iter()
+            .position(|session| session.acl_handle == acl_handle && session.channel == channel)
+        {
+            Some(index) => index,
+            None => {
+                self.sessions.push(ObexSession {
+                    acl_handle,
+                    channel,
+                    ..Default::default()
+                });
+                self.sessions.len() - 1
+            }
+        };
+        &mut self.sessions[index]
+    }
+
+    /// Closes the OBEX sessions of `acl_handle`.
+    pub(crate) fn disconnect(&mut self, acl_handle: u16) {
+        let (ended, open): (Vec<ObexSession>, Vec<ObexSession>) =
+            std::mem::take(&mut self.sessions)
+                .into_iter()
+                .partition(|session| session.acl_handle == acl_handle);
+        self.sessions = open;
+        self.closed.extend(ended);
+    }
+
+    pub(crate) fn summary(&self) -> String {
+        let mut keys = SummaryKeys::default();
+        self.closed
+            .iter()
+            .chain(self.sessions.iter())
+            .map(|session| session.summary(&mut keys))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+#[derive(Debug)]
+struct ObexSession {
+    acl_handle: u16,
+    channel: ObexChannel,
+    // bytes of a packet not complete yet, per side of the channel
+    buffers: [Vec<u8>; 2],
+    profile: Option<u16>,
+    connected: bool,
+    connection_id: Option<u32>,
+    // announced by the client and the server in Connect
+    max_packet_length: [Option<u16>; 2],
+    single_response_mode: Option<u8>,
+    // opcode of the last request, which the following responses answer
+    pending: Option<u8>,
+    // flags and name of a SetPath waiting for its response
+    set_path: Option<(u8, Option<String>)>,
+    folder: Vec<String>,
+    // Type and Name of the object being transferred
+    object_type: Option<String>,
+    object_name: Option<String>,
+    // Body headers of the GET responses so far
+    body: Vec<u8>,
+    vcard_listing: Vec<(String, String)>,
+    failures: Vec<String>,
+}
+
+impl Default for ObexSession {
+    fn default() -> Self {
+        ObexSession {
+            acl_handle: 0,
+            channel: ObexChannel::Rfcomm(0),
+            buffers: Default::default(),
+            profile: None,
+            connected: false,
+            connection_id: None,
+            max_packet_length: [None; 2],
+            single_response_mode: None,
+            pending: None,
+            set_path: None,
+            folder: Vec::new(),
+            object_type: None,
+            object_name: None,
+            body: Vec::new(),
+            vcard_listing: Vec::new(),
+            failures: Vec::new(),
+        }
+    }
+}
+
+impl ObexSession {
+    /// Follows one complete packet. A GET that completes a vCard listing
+    /// hands the listing back to the packet.
+    fn update(&mut self, packet: &mut ObexPacket) {
+        let header = |id: u8| packet.headers.iter().find(|header| header.id == id);
+        if let Some(mode) = header(headers::SRM).and_then(|header| header.byte()) {
+            self.single_response_mode = Some(mode);
+        }
+        if !is_response(packet.code) {
+            let opcode = packet.code & 0x7f;
+            self.pending = Some(opcode);
+            match opcode {
+                CONNECT => {
+                    self.max_packet_length[0] = packet.connect.map(|connect| connect.2);
+                    if let Some(profile) = header(headers::TARGET)
+                        .and_then(|header| header.bytes())
+                        .and_then(headers::get_target_profile)
+                    {
+                        self.profile = Some(profile);
+                    }
+                }
+                SET_PATH => {
+                    let name = header(headers::NAME)
+                        .and_then(|header| header.text())
+                        .map(|name| name.to_string());
+                    self.set_path = packet.set_path.map(|(flags, _)| (flags, name));
+                }
+                // the first packet of a PUT or GET names the object
+                _ if header(headers::TYPE).is_some() || header(headers::NAME).is_some() => {
+                    self.object_type = header(headers::TYPE)
+                        .and_then(|header| header.text())
+                        .map(|name| name.to_string());
+                    self.object_name = header(headers::NAME)
+                        .and_then(|header| header.text())
+                        .map(|name| name.to_string());
+                    self.body.clear();
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        let success = packet.code == SUCCESS;
+        match packet.request {
+            Some(CONNECT) if success => {
+                self.connected = true;
+                self.max_packet_length[1] = packet.connect.map(|connect| connect.2);
+                self.connection_id =
+                    header(headers::CONNECTION_ID).and_then(|header| header.quad());
+            }
+            Some(DISCONNECT) if success => self.connected = false,
+            Some(SET_PATH) if success => {
+                if let Some((flags, name)) = self.set_path.take() {
+                    if flags & 0x01 != 0 {
+                        self.folder.pop();
+                    }
+                    match name.as_deref() {
+                        Some("") | None if flags & 0x01 == 0 => self.folder.clear(),
+                        Some("") | None => {}
+                        Some(name) => self.folder.push(name.to_string()),
+                    }
+                }
+            }
+            Some(GET) => {
+                for header in packet.headers.iter() {
+                    if let (headers::BODY | headers::END_OF_BODY, HeaderValue::Bytes(bytes)) =
+                        (header.id, &header.value)
+                    {
+                        self.body.extend_from_slice(bytes);
+                    }
+                }
+                if success && self.object_type.as_deref() == Some("x-bt/vcard-listing") {
+                    self.vcard_listing = parse_vcard_listing(&self.body);
+                    packet.vcard_listing = self.vcard_listing.clone();
+                }
+            }
+            _ => {}
+        }
+        if packet.code & 0x7f >= 0x40 {
+            let mut failure = get_opcode_name(packet.request.unwrap_or(0x7f)).to_string();
+            if packet.request == Some(GET) || packet.request == Some(0x02) {
+                for name in [&self.object_type, &self.object_name].into_iter().flatten() {
+                    failure.push(' ');
+                    failure.push_str(name);
+                }
+            }
+            self.failures.push(format!(
+                "{}: {}",
+                failure,
+                get_response_code_name(packet.code)
+            ));
+        }
+    }
+
+    fn summary(&self, keys: &mut SummaryKeys) -> String {
+        let mut fields = Vec::new();
+        if let Some(profile) = self.profile {
+            fields.push(format!(r#""Profile": "{}""#, get_uuid16_name(profile)));
+        }
+        fields.push(format!(r#""Connected": {}"#, self.connected));
+        if let Some(connection_id) = self.connection_id {
+            fields.push(format!(r#""Connection ID": "{:#x}""#, connection_id));
+        }
+        for (side, length) in ["Client", "Server"].iter().zip(self.max_packet_length) {
+            if let Some(length) = length {
+                fields.push(format!(r#""{} Maximum Packet Length": {}"#, side, length));
+            }
+        }
+        if let Some(mode) = self.single_response_mode {
+            fields.push(format!(
+                r#""Single Response Mode": "{}""#,
+                match mode {
+                    0x00 => "Disabled",
+                    0x01 => "Enabled",
+                    _ => "Supported",
+                }
+            ));
+        }
+        fields.push(format!(
+            r#""Current Folder": "/{}""#,
+            bytes_to_string(self.folder.join("/").as_bytes())
+        ));
+        if !self.vcard_listing.is_empty() {
+            let listing_s = self
+                .vcard_listing
+                .iter()
+                .map(|(handle, name)| {
+                    format!(
+                        r#""{}": "{}""#,
+                        bytes_to_string(handle.as_bytes()),
+                        bytes_to_string(name.as_bytes())
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            fields.push(format!(r#""vCard Listing": {{{}}}"#, listing_s));
+        }
+        if !self.failures.is_empty() {
+            let failures_s = self
+                .failures
+                .iter()
+                .map(|failure| format!(r#""{}""#, bytes_to_string(failure.as_bytes())))
+                .collect::<Vec<String>>()
+                .join(", ");
+            fields.push(format!(r#""Failures": [{}]"#, failures_s));
+        }
+        let channel = match self.channel {
+            ObexChannel::Rfcomm(dlci) => format!("DLCI {}", dlci),
+            ObexChannel::L2cap(psm) => format!("PSM {:#06x}", psm),
+        };
+        let key = keys.unique(format!("Connection {:#x} {}", self.acl_handle, channel));
+        format!(r#""{}": {{{}}}"#, key, fields.join(", "))
+    }
+}
+
+/// Responses carry the final bit and a code of 0x10 or above, Abort is the
+/// only request with all bits set.
+fn is_response(code: u8) -> bool {
+    code & 0x7f >= 0x10 && code != 0xff
+}
+
+/// Takes the handle and name of each card out of a PBAP vCard listing.
+fn parse_vcard_listing(body: &[u8]) -> Vec<(String, String)> {
+    let body = String::from_utf8_lossy(body);
+    let attribute = |card: &str, name: &str| {
+        let start = card.find(&format!("{}=\"", name))? + name.len() + 2;
+        let end = card[start..].find('"')?;
+        Some(card[start..start + end].to_string())
+    };
+    body.split("<card")
+        .skip(1)
+        .filter_map(|card| {
+            let card = &card[..card.find('>').unwrap_or(card.len())];
+            Some((
+                attribute(card, "handle")?,
+                attribute(card, "name").unwrap_or_default(),
+            ))
+        })
+        .collect()
+}
+
+/// One OBEX request or response.
+#[derive(Debug, PartialEq)]
+struct ObexPacket {
+    code: u8,
+    length: u16,
+    // opcode of the request a response answers
+    request: Option<u8>,
+    // version, flags and maximum packet length of Connect and its response
+    connect: Option<(u8, u8, u16)>,
+    // flags and constants of SetPath
+    set_path: Option<(u8, u8)>,
+    headers: Vec<ObexHeader>,
+    profile: Option<u16>,
+    vcard_listing: Vec<(String, String)>,
+}
+
+impl ObexPacket {
+    fn new(data: &[u8], request: Option<u8>, profile: Option<u16>) -> Self {
+        let code = data[0];
+        let length = u16::from_be_bytes([data[1], data[2]]);
+        let opcode = match is_response(code) {
+            true => request,
+            false => Some(code & 0x7f),
+        };
+        let connect = (opcode == Some(CONNECT) && data.len() >= 7)
+            .then(|| (data[3], data[4], u16::from_be_bytes([data[5], data[6]])));
+        let set_path = (!is_response(code) && opcode == Some(SET_PATH) && data.len() >= 5)
+            .then(|| (data[3], data[4]));
+        let headers_start = 3 + connect.map_or(0, |_| 4) + set_path.map_or(0, |_| 2);
+        ObexPacket {
+            code,
+            length,
+            request,
+            connect,
+            set_path,
+            headers: ObexHeader::parse_all(&data[headers_start.min(data.len())..]),
+            profile,
+            vcard_listing: Vec::new(),
+        }
+    }
+
+    fn as_json(&self, start_byte: u16, packet_len: u16) -> String {
+        let mut fields = match is_response(self.code) {
+            true => vec![ParseBytesNode::new(start_byte, 1).format(
+                "Response Code",
+                self.code,
+                &match self.request {
+                    Some(request) => format!(
+                        "{} ({})",
+                        get_response_code_name(self.code),
+                        get_opcode_name(request)
+                    ),
+                    None => get_response_code_name(self.code).to_string(),
+                },
+                "",
+            )],
+            false => vec![
+                ParseBytesNode::new(start_byte, 1).format(
+                    "Opcode",
+                    self.code,
+                    get_opcode_name(self.code & 0x7f),
+                    "",
+                ),
+                ParseBitsNode::new(start_byte, 1, 7, 1).format("Final Bit", self.code >> 7, "", ""),
+            ],
+        };
+        fields.push(ParseBytesNode::new(start_byte + 1, 2).format(
+            "Packet Length",
+            self.length,
+            "",
+            match self.length == packet_len {
+                true => "",
+                false => "Packet Length does not match the bytes received",
+            },
+        ));
+        let mut offset = start_byte + 3;
+        if let Some((version, flags, max_packet_length)) = self.connect {
+            fields.push(ParseBytesNode::new(offset, 1).format(
+                "OBEX Version",
+                version,
+                &format!("{}.{}", version >> 4, version & 0x0f),
+                "",
+            ));
+            fields.push(ParseBytesNode::new(offset + 1, 1).format("Flags", flags, "", ""));
+            fields.push(ParseBytesNode::new(offset + 2, 2).format(
+                "Maximum Packet Length",
+                max_packet_length,
+                "",
+                "",
+            ));
+            offset += 4;
+        }
+        if let Some((flags, constants)) = self.set_path {
+            let flags_name = [(0x01, "Back up a level"), (0x02, "Don't create")]
+                .iter()
+                .filter(|(bit, _)| flags & bit != 0)
+                .map(|(_, name)| *name)
+                .collect::<Vec<&str>>()
+                .join(", ");
+            fields.push(ParseBytesNode::new(offset, 1).format("Flags", flags, &flags_name, ""));
+            fields.push(ParseBytesNode::new(offset + 1, 1).format("Constants", constants, "", ""));
+            offset += 2;
+        }
+        for (i, header) in self.headers.iter().enumerate() {
+            fields.push(format!(
+                r#""Header[{}]": {{{}}}"#,
+                i,
+                header.as_json(offset, self.profile)
+            ));
+            offset += header.len();
+        }
+        if !self.vcard_listing.is_empty() {
+            let node = ParseBytesNode::new(start_byte, packet_len);
+            let cards_s = self
+                .vcard_listing
+                .iter()
+                .enumerate()
+                .map(|(i, (handle, name))| {
+                    node.format(
+                        &format!("Card[{}]", i),
+                        bytes_to_string(handle.as_bytes()),
+                        &bytes_to_string(name.as_bytes()),
+                        "",
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            fields.push(format!(r#""vCard Listing": {{{}}}"#, cards_s));
+        }
+        fields.join(", ")
+    }
+}
+
+/// The OBEX packets completed by one RFCOMM frame or L2CAP SDU. A packet
+/// may start in an earlier frame; what is left of an incomplete one waits
+/// for the next.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq)]
+pub(crate) struct OBEX {
+    // the packet, where it starts in the frame and whether earlier frames
+    // held part of it
+    packets: Vec<(ObexPacket, u16, bool)>,
+    // offset and length of the bytes kept for the next frame, with how
+    // many of the packet length have arrived
+    partial: Option<(u16, u16, usize, u16)>,
+}
+
+impl ParseNodeA<(ObexChannel, usize, Option<u16>)> for OBEX {
+    fn new(
+        data: &[u8],
+        args: Option<&mut HostStack>,
+        (channel, side, profile): (ObexChannel, usize, Option<u16>),
+    ) -> Self {
+        let mut session = args.map(|args| {
+            let acl_handle = args.hci_arg.acl_handle();
+            args.obex_arg.session(acl_handle, channel)
+        });
+        let mut buffer = Vec::new();
+        if let Some(session) = session.as_deref_mut() {
+            session.profile = session.profile.or(profile);
+            buffer = std::mem::take(&mut session.buffers[side]);
+        }
+        let carried = buffer.len();
+        buffer.extend_from_slice(data);
+
+        let mut packets = Vec::new();
+        let mut start = 0;
+        while buffer.len() - start >= 3 {
+            // a length below the header size can not be followed, take the rest
+            let length = match u16::from_be_bytes([buffer[start + 1], buffer[start + 2]]) {
+                length if length < 3 => buffer.len() - start,
+                length => length as usize,
+            };
+            if buffer.len() - start < length {
+                break;
+            }
+            let packet_data = &buffer[start..start + length];
+            let request = session.as_deref().and_then(|session| session.pending);
+            let profile = session
+                .as_deref()
+                .map_or(profile, |session| session.profile);
+            let mut packet = ObexPacket::new(packet_data, request, profile);
+            if let Some(session) = session.as_deref_mut() {
+                session.update(&mut packet);
+            }
+            packets.push((
+                packet,
+                start.saturating_sub(carried) as u16,
+                start < carried,
+            ));
+            start += length;
+        }
+        let partial = (start < buffer.len()).then(|| {
+            let frame_start = start.saturating_sub(carried);
+            let total = match buffer.len() - start {
+                1 | 2 => 0,
+                _ => u16::from_be_bytes([buffer[start + 1], buffer[start + 2]]),
+            };
+            (
+                frame_start as u16,
+                (buffer.len() - carried - frame_start) as u16,
+                buffer.len() - start,
+                total,
+            )
+        });
+        if let Some(session) = session {
+            session.buffers[side] = buffer[start..].to_vec();
+        }
+        OBEX { packets, partial }
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut fields = Vec::new();
+        for (i, (packet, offset, reassembled)) in self.packets.iter().enumerate() {
+            let key = match reassembled {
+                true => "Reassembled Packet",
+                false => "Packet",
+            };
+            fields.push(format!(
+                r#""{}[{}]": {{{}}}"#,
+                key,
+                i,
+                packet.as_json(start_byte + offset, packet.length.max(3))
+            ));
+        }
+        if let Some((offset, len, received, total)) = self.partial {
+            fields.push(ParseBytesNode::new(start_byte + offset, len).format(
+                "Partial Packet",
+                len,
+                &match total {
+                    0 => "Continues in the next frame".to_string(),
+                    total => format!("{} of {} bytes received", received, total),
+                },
+                "",
+            ));
+        }
+        format!(r#""OBEX": {{{}}}"#, fields.join(", "))
+    }
+}
+
+fn get_opcode_name(opcode: u8) -> &'static str {
+    match opcode {
+        0x00 => "Connect",
+        0x01 => "Disconnect",
+        0x02 => "Put",
+        0x03 => "Get",
+        0x05 => "SetPath",
+        0x06 => "Action",
+        0x07 => "Session",
+        0x7f => "Abort",
+        _ => "Reserved",
+    }
+}
+
+fn get_response_code_name(code: u8) -> &'static str {
+    match code {
+        0x90 => "Continue",
+        0xa0 => "Success",
+        0xa1 => "Created",
+        0xa2 => "Accepted",
+        0xa3 => "Non-Authoritative Information",
+        0xa4 => "No Content",
+        0xa5 => "Reset Content",
+        0xa6 => "Partial Content",
+        0xb0 => "Multiple Choices",
+        0xb1 => "Moved Permanently",
+        0xb2 => "Moved Temporarily",
+        0xb3 => "See Other",
+        0xb4 => "Not Modified",
+        0xb5 => "Use Proxy",
+        0xc0 => "Bad Request",
+        0xc1 => "Unauthorized",
+        0xc2 => "Payment Required",
+        0xc3 => "Forbidden",
+        0xc4 => "Not Found",
+        0xc5 => "Method Not Allowed",
+        0xc6 => "Not Acceptable",
+        0xc7 => "Proxy Authentication Required",
+        0xc8 => "Request Time Out",
+        0xc9 => "Conflict",
+        0xca => "Gone",
+        0xcb => "Length Required",
+        0xcc => "Precondition Failed",
+        0xcd => "Requested Entity Too Large",
+        0xce => "Request URL Too Large",
+        0xcf => "Unsupported Media Type",
+        0xd0 => "Internal Server Error",
+        0xd1 => "Not Implemented",
+        0xd2 => "Bad Gateway",
+        0xd3 => "Service Unavailable",
+        0xd4 => "Gateway Timeout",
+        0xd5 => "HTTP Version Not Supported",
+        0xe0 => "Database Full",
+        0xe1 => "Database Locked",
+        _ => "Reserved",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_vcard_listing;
+    use crate::test_utils::{l2cap_connect, parse_packets};
+    use crate::HostStack;
+
+    // an SDP record puts Object Push on GOEP L2CAP PSM 0x1001, the client
+    // opens it as 0x0042 <-> 0x0043 and its Connect is split around a
+    // packet of the server
+    const PACKETS: [&str; 5] = [
+        "02 40 20 18 00 14 00 41 00 06 00 01 00 0f 35 03 19 11 05 ff ff 35 05 0a 00 00 ff ff 00",
+        "02 40 20 1e 00 1a 00 40 00 07 00 01 00 15 00 12 35 10 35 0e 09 00 01 35 03 19 11 05 09 02 00 09 10 01 00",
+        "02 40 20 07 00 03 00 43 00 80 00 07",
+        "02 40 20 07 00 03 00 42 00 a0 00 03",
+        "02 40 20 08 00 04 00 43 00 10 00 ff ff",
+    ];
+
+    #[test]
+    fn goep_over_l2cap_reassembles_per_direction() {
+        let sdp = l2cap_connect(0x0040, 1, 0x0001, (0x0040, 0x0041));
+        let goep = l2cap_connect(0x0040, 2, 0x1001, (0x0042, 0x0043));
+        let (json, _) = parse_packets(&[
+            &sdp[0], &sdp[1], PACKETS[0], PACKETS[1], &goep[0], &goep[1], PACKETS[2], PACKETS[3],
+            PACKETS[4],
+        ]);
+        assert!(
+            json[6].contains(r#""Partial Packet": [0x3, "3 of 7 bytes received", "B(9, 3)", ""]"#)
+        );
+        assert!(json[7].contains(r#""Response Code": [0xa0, "Success", "B(9, 1)", ""]"#));
+        assert!(json[8]
+            .contains(r#""Reassembled Packet[0]": {"Opcode": [0x80, "Connect", "B(9, 1)", ""]"#));
+        assert!(json[8].contains(r#""Maximum Packet Length": [0xffff, "", "B(14, 2)", ""]"#));
+    }
+
+    // an SDP record puts the PBAP server on GOEP L2CAP PSM 0x1003, then on
+    // 0x0042 <-> 0x0043 the client connects and pulls the vCard listing of
+    // telecom/pb, which comes in a Body and an End of Body
+    const PBAP: [&str; 7] = [
+        "02 40 20 1e 00 1a 00 40 00 07 00 01 00 15 00 12 35 10 35 0e 09 00 01 35 03 19 11 2f 09 02 00 09 10 03 00",
+        "02 40 20 27 00 23 00 43 00 80 00 23 10 00 ff ff 46 00 13 79 61 35 f0 f0 c5 11 d8 09 66 08 00 20 0c 9a 66 4c 00 09 10 04 00 00 02 03",
+        "02 40 20 23 00 1f 00 42 00 a0 00 1f 10 00 04 00 cb 00 00 00 01 4a 00 13 79 61 35 f0 f0 c5 11 d8 09 66 08 00 20 0c 9a 66",
+        "02 40 20 49 00 45 00 43 00 83 00 45 cb 00 00 00 01 01 00 19 00 74 00 65 00 6c 00 65 00 63 00 6f 00 6d 00 2f 00 70 00 62 00 00 42 00 16 78 2d 62 74 2f 76 63 61 72 64 2d 6c 69 73 74 69 6e 67 00 4c 00 0e 04 02 00 0a 05 02 00 00 01 01 00",
+        "02 40 20 4d 00 49 00 42 00 90 00 49 4c 00 07 08 02 00 02 48 00 3f 3c 3f 78 6d 6c 20 76 65 72 73 69 6f 6e 3d 22 31 2e 30 22 3f 3e 3c 76 43 61 72 64 2d 6c 69 73 74 69 6e 67 20 76 65 72 73 69 6f 6e 3d 22 31 2e 30 22 3e 3c 63 61 72 64 20 68 61 6e 64",
+        "02 40 20 0c 00 08 00 43 00 83 00 08 cb 00 00 00 01",
+        "02 40 20 54 00 50 00 42 00 a0 00 50 49 00 4d 6c 65 3d 22 30 2e 76 63 66 22 20 6e 61 6d 65 3d 22 41 6c 69 63 65 22 2f 3e 3c 63 61 72 64 20 68 61 6e 64 6c 65 3d 22 31 2e 76 63 66 22 20 6e 61 6d 65 3d 22 42 6f 62 22 2f 3e 3c 2f 76 43 61 72 64 2d 6c 69 73 74 69 6e 67 3e",
+    ];
+
+    /// Parses the PBAP session and returns the JSON of its OBEX packets.
+    fn parse_pbap() -> (Vec<String>, HostStack) {
+        let sdp = l2cap_connect(0x0040, 1, 0x0001, (0x0040, 0x0041));
+        let goep = l2cap_connect(0x0040, 2, 0x1003, (0x0042, 0x0043));
+        let mut packets = vec![sdp[0].as_str(), &sdp[1], PBAP[0], &goep[0], &goep[1]];
+        packets.extend_from_slice(&PBAP[1..]);
+        let (mut json, args) = parse_packets(&packets);
+        json.drain(..5);
+        (json, args)
+    }
+
+    #[test]
+    fn app_params_are_named_by_profile() {
+        let (json, _) = parse_pbap();
+        assert!(json[0].contains(r#""Application Parameter[0]": {"Tag ID": [0x10, "PbapSupportedFeatures", "B(38, 1)", ""], "Length": [0x4, "", "B(39, 1)", ""], "Value": [0x203, "Download, Browsing, Default Contact Image Format", "B(40, 4)", ""]}"#));
+        assert!(json[2].contains(r#""Application Parameters": {"Application Parameter[0]": {"Tag ID": [0x4, "MaxListCount", "B(67, 1)", ""], "Length": [0x2, "", "B(68, 1)", ""], "Value": [0xa, "", "B(69, 2)", ""]}, "Application Parameter[1]": {"Tag ID": [0x5, "ListStartOffset", "B(71, 1)", ""], "Length": [0x2, "", "B(72, 1)", ""], "Value": [0x0, "", "B(73, 2)", ""]}, "Application Parameter[2]": {"Tag ID": [0x1, "Order", "B(75, 1)", ""], "Length": [0x1, "", "B(76, 1)", ""], "Value": [0x0, "Indexed", "B(77, 1)", ""]}}"#));
+        assert!(json[3].contains(
+            r#""Application Parameter[0]": {"Tag ID": [0x8, "PhonebookSize", "B(15, 1)", ""]"#
+        ));
+    }
+
+    #[test]
+    fn vcard_listing_is_taken_from_the_whole_body() {
+        let (json, args) = parse_pbap();
+        assert!(!json[3].contains("vCard Listing"));
+        assert!(json[5].contains(r#""vCard Listing": {"Card[0]": ["0.vcf", "Alice", "B(9, 80)", ""], "Card[1]": ["1.vcf", "Bob", "B(9, 80)", ""]}"#));
+        assert_eq!(
+            args.obex_summary(),
+            r#"{"Connection 0x40 PSM 0x1003": {"Profile": "Phonebook Access - PSE", "Connected": true, "Connection ID": "0x1", "Client Maximum Packet Length": 65535, "Server Maximum Packet Length": 1024, "Current Folder": "/", "vCard Listing": {"0.vcf": "Alice", "1.vcf": "Bob"}}}"#
+        );
+    }
+
+    #[test]
+    fn vcard_listing_cards_need_a_handle() {
+        let listing = br#"<vCard-listing version="1.0"><card name="No Handle"/><card name="Carol" handle="2.vcf"/><card handle="3.vcf"/></vCard-listing>"#;
+        assert_eq!(
+            parse_vcard_listing(listing),
+            [
+                ("2.vcf".to_string(), "Carol".to_string()),
+                ("3.vcf".to_string(), String::new()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,536 @@
+use crate::ad::uuid128_to_string;
+use crate::bytes_to_string;
+
+use crate::ParseBytesNode;
+
+/// One OBEX header. The two high bits of the header identifier tell its
+/// encoding: null terminated UTF-16 text or a byte sequence, both with a two
+/// byte length, a single byte or a four byte quantity.
+#[derive(Debug, PartialEq)]
+pub(super) struct ObexHeader {
+    pub(super) id: u8,
+    length: u16,
+    pub(super) value: HeaderValue,
+    error: &'static str,
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) enum HeaderValue {
+    Text(String),
+    Bytes(Vec<u8>),
+    Byte(u8),
+    Quad(u32),
+    AppParams(Vec<AppParam>),
+}
+
+/// A tag-length-value triplet of the Application Parameters header.
+#[derive(Debug, PartialEq)]
+pub(super) struct AppParam {
+    tag: u8,
+    value: Vec<u8>,
+}
+
+pub(super) const NAME: u8 = 0x01;
+pub(super) const TYPE: u8 = 0x42;
+pub(super) const TARGET: u8 = 0x46;
+pub(super) const BODY: u8 = 0x48;
+pub(super) const END_OF_BODY: u8 = 0x49;
+pub(super) const WHO: u8 = 0x4a;
+pub(super) const APP_PARAMS: u8 = 0x4c;
+pub(super) const SRM: u8 = 0x97;
+pub(super) const CONNECTION_ID: u8 = 0xcb;
+
+impl ObexHeader {
+    /// Reads the headers that follow the opcode specific fields, up to the
+    /// end of the packet or the first header that does not fit.
+    pub(super) fn parse_all(data: &[u8]) -> Vec<ObexHeader> {
+        let mut headers = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            let header = ObexHeader::new(&data[offset..]);
+            offset += header.length as usize;
+            let truncated = !header.error.is_empty();
+            headers.push(header);
+            if truncated {
+                break;
+            }
+        }
+        headers
+    }
+
+    fn new(data: &[u8]) -> Self {
+        let id = data[0];
+        let length = match id >> 6 {
+            0 | 1 if data.len() >= 3 => u16::from_be_bytes([data[1], data[2]]),
+            0 | 1 => 3,
+            2 => 2,
+            _ => 5,
+        };
+        if data.len() < length as usize || (id >> 6 < 2 && length < 3) {
+            return ObexHeader {
+                id,
+                length: data.len() as u16,
+                value: HeaderValue::Bytes(data[1..].to_vec()),
+                error: "Header exceeds the packet",
+            };
+        }
+        let body = &data[..length as usize];
+        let value = match id >> 6 {
+            0 => {
+                let units: Vec<u16> = body[3..]
+                    .chunks_exact(2)
+                    .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                    .take_while(|unit| *unit != 0)
+                    .collect();
+                HeaderValue::Text(String::from_utf16_lossy(&units))
+            }
+            1 if id == APP_PARAMS => HeaderValue::AppParams(AppParam::parse_all(&body[3..])),
+            // Type and Time are null terminated ASCII
+            1 if id == TYPE || id == 0x44 => HeaderValue::Text(
+                String::from_utf8_lossy(&body[3..])
+                    .trim_end_matches('\0')
+                    .to_string(),
+            ),
+            1 => HeaderValue::Bytes(body[3..].to_vec()),
+            2 => HeaderValue::Byte(body[1]),
+            _ => HeaderValue::Quad(u32::from_be_bytes([body[1], body[2], body[3], body[4]])),
+        };
+        ObexHeader {
+            id,
+            length,
+            value,
+            error: "",
+        }
+    }
+
+    pub(super) fn text(&self) -> Option<&str> {
+        match &self.value {
+            HeaderValue::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub(super) fn bytes(&self) -> Option<&[u8]> {
+        match &self.value {
+            HeaderValue::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub(super) fn quad(&self) -> Option<u32> {
+        match self.value {
+            HeaderValue::Quad(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(super) fn byte(&self) -> Option<u8> {
+        match self.value {
+            HeaderValue::Byte(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(super) fn len(&self) -> u16 {
+        self.length
+    }
+
+    /// `profile` names the tags of the Application Parameters.
+    pub(super) fn as_json(&self, start_byte: u16, profile: Option<u16>) -> String {
+        let mut fields = vec![ParseBytesNode::new(start_byte, 1).format(
+            "Header ID",
+            self.id,
+            get_header_id_name(self.id),
+            self.error,
+        )];
+        let value_start = match self.id >> 6 {
+            0 | 1 if self.error.is_empty() => {
+                fields.push(ParseBytesNode::new(start_byte + 1, 2).format(
+                    "Header Length",
+                    self.length,
+                    "",
+                    "",
+                ));
+                start_byte + 3
+            }
+            _ => start_byte + 1,
+        };
+        let node = ParseBytesNode::new(value_start, self.length - (value_start - start_byte));
+        let key = match get_header_id_name(self.id) {
+            "" => "Value",
+            name => name,
+        };
+        fields.push(match &self.value {
+            HeaderValue::Text(text) => node.format(key, bytes_to_string(text.as_bytes()), "", ""),
+            HeaderValue::Bytes(bytes) => node.format(
+                key,
+                &bytes[..],
+                &match self.id {
+                    TARGET | WHO | 0x50 => get_target_name(bytes),
+                    _ => String::new(),
+                },
+                "",
+            ),
+            HeaderValue::Byte(value) => {
+                node.format(key, value, get_byte_value_name(self.id, *value), "")
+            }
+            HeaderValue::Quad(value) => node.format(key, value, "", ""),
+            HeaderValue::AppParams(params) => {
+                let mut offset = value_start;
+                let params_s = params
+                    .iter()
+                    .enumerate()
+                    .map(|(i, param)| {
+                        let param_s = param.as_json(offset, profile);
+                        offset += 2 + param.value.len() as u16;
+                        format!(r#""Application Parameter[{}]": {{{}}}"#, i, param_s)
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                format!(r#""{}": {{{}}}"#, key, params_s)
+            }
+        });
+        fields.join(", ")
+    }
+}
+
+impl AppParam {
+    fn parse_all(data: &[u8]) -> Vec<AppParam> {
+        let mut params = Vec::new();
+        let mut offset = 0;
+        while offset + 2 <= data.len() {
+            let len = data[offset + 1] as usize;
+            let end = (offset + 2 + len).min(data.len());
+            params.push(AppParam {
+                tag: data[offset],
+                value: data[offset + 2..end].to_vec(),
+            });
+            offset = end;
+        }
+        params
+    }
+
+    fn as_json(&self, start_byte: u16, profile: Option<u16>) -> String {
+        let family = profile.map(get_profile_family).unwrap_or_default();
+        let mut fields = vec![
+            ParseBytesNode::new(start_byte, 1).format(
+                "Tag ID",
+                self.tag,
+                get_app_param_tag_name(family, self.tag),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 1, 1).format("Length", self.value.len() as u8, "", ""),
+        ];
+        let node = ParseBytesNode::new(start_byte + 2, self.value.len() as u16);
+        let value = &self.value[..];
+        let number = match value.len() {
+            1 | 2 | 4 | 8 => Some(
+                value
+                    .iter()
+                    .fold(0u64, |number, byte| number << 8 | *byte as u64),
+            ),
+            _ => None,
+        };
+        fields.push(match number {
+            Some(number) => {
+                let alias = get_app_param_value_name(family, self.tag, number);
+                match value.len() {
+                    1 => node.format("Value", number as u8, &alias, ""),
+                    2 => node.format("Value", number as u16, &alias, ""),
+                    4 => node.format("Value", number as u32, &alias, ""),
+                    _ => node.format("Value", number, &alias, ""),
+                }
+            }
+            None if !value.is_empty()
+                && value.iter().all(|c| c.is_ascii_graphic() || *c == b' ') =>
+            {
+                node.format("Value", bytes_to_string(value), "", "")
+            }
+            None => node.format("Value", value, "", ""),
+        });
+        fields.join(", ")
+    }
+}
+
+/// The application parameters of PBAP and MAP use different tags.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum ProfileFamily {
+    #[default]
+    Unknown,
+    Pbap,
+    Map,
+}
+
+fn get_profile_family(profile: u16) -> ProfileFamily {
+    match profile {
+        0x112e..=0x1130 => ProfileFamily::Pbap,
+        0x1132..=0x1134 => ProfileFamily::Map,
+        _ => ProfileFamily::Unknown,
+    }
+}
+
+/// The profile an OBEX Target or Who header addresses.
+pub(super) fn get_target_profile(uuid: &[u8]) -> Option<u16> {
+    let uuid = u128::from_be_bytes(uuid.try_into().ok()?);
+    match uuid {
+        0x796135f0_f0c5_11d8_0966_0800200c9a66 => Some(0x112f),
+        0xbb582b40_420c_11db_b0de_0800200c9a66 => Some(0x1132),
+        0xbb582b41_420c_11db_b0de_0800200c9a66 => Some(0x1133),
+        0xf9ec7bc4_953c_11d2_984e_525400dc9e09 => Some(0x1106),
+        _ => None,
+    }
+}
+
+fn get_target_name(uuid: &[u8]) -> String {
+    match get_target_profile(uuid) {
+        Some(0x112f) => "Phonebook Access - PSE".to_string(),
+        Some(0x1132) => "Message Access Server".to_string(),
+        Some(0x1133) => "Message Notification Server".to_string(),
+        Some(0x1106) => "OBEX File Transfer".to_string(),
+        _ => match <[u8; 16]>::try_from(uuid) {
+            Ok(uuid) => uuid128_to_string(u128::from_be_bytes(uuid)),
+            Err(_) => String::new(),
+        },
+    }
+}
+
+fn get_header_id_name(id: u8) -> &'static str {
+    match id {
+        0x01 => "Name",
+        0x05 => "Description",
+        0x15 => "DestName",
+        0x42 => "Type",
+        0x44 => "Time",
+        0x46 => "Target",
+        0x47 => "HTTP",
+        0x48 => "Body",
+        0x49 => "End of Body",
+        0x4a => "Who",
+        0x4c => "Application Parameters",
+        0x4d => "Authenticate Challenge",
+        0x4e => "Authenticate Response",
+        0x4f => "Object Class",
+        0x50 => "WAN UUID",
+        0x51 => "Session Parameters",
+        0x93 => "Session Sequence Number",
+        0x94 => "Action Id",
+        0x97 => "Single Response Mode",
+        0x98 => "Single Response Mode Parameters",
+        0xc0 => "Count",
+        0xc3 => "Length",
+        0xc4 => "Time",
+        0xcb => "Connection ID",
+        0xcf => "Creator ID",
+        0xd6 => "Permissions",
+        _ => "",
+    }
+}
+
+fn get_byte_value_name(id: u8, value: u8) -> &'static str {
+    match (id, value) {
+        (0x94, 0x00) => "Copy Object",
+        (0x94, 0x01) => "Move/Rename Object",
+        (0x94, 0x02) => "Set Object Permissions",
+        (0x97, 0x00) => "Disable",
+        (0x97, 0x01) => "Enable",
+        (0x97, 0x02) => "Indicate",
+        (0x98, 0x01) => "Wait",
+        _ => "",
+    }
+}
+
+fn get_app_param_tag_name(family: ProfileFamily, tag: u8) -> &'static str {
+    match family {
+        ProfileFamily::Pbap => match tag {
+            0x01 => "Order",
+            0x02 => "SearchValue",
+            0x03 => "SearchProperty",
+            0x04 => "MaxListCount",
+            0x05 => "ListStartOffset",
+            0x06 => "PropertySelector",
+            0x07 => "Format",
+            0x08 => "PhonebookSize",
+            0x09 => "NewMissedCalls",
+            0x0a => "PrimaryFolderVersion",
+            0x0b => "SecondaryFolderVersion",
+            0x0c => "vCardSelector",
+            0x0d => "DatabaseIdentifier",
+            0x0e => "vCardSelectorOperator",
+            0x0f => "ResetNewMissedCalls",
+            0x10 => "PbapSupportedFeatures",
+            _ => "",
+        },
+        ProfileFamily::Map => match tag {
+            0x01 => "MaxListCount",
+            0x02 => "ListStartOffset",
+            0x03 => "FilterMessageType",
+            0x04 => "FilterPeriodBegin",
+            0x05 => "FilterPeriodEnd",
+            0x06 => "FilterReadStatus",
+            0x07 => "FilterRecipient",
+            0x08 => "FilterOriginator",
+            0x09 => "FilterPriority",
+            0x0a => "Attachment",
+            0x0b => "Transparent",
+            0x0c => "Retry",
+            0x0d => "NewMessage",
+            0x0e => "NotificationStatus",
+            0x0f => "MASInstanceID",
+            0x10 => "ParameterMask",
+            0x11 => "FolderListingSize",
+            0x12 => "ListingSize",
+            0x13 => "SubjectLength",
+            0x14 => "Charset",
+            0x15 => "FractionRequest",
+            0x16 => "FractionDeliver",
+            0x17 => "StatusIndicator",
+            0x18 => "StatusValue",
+            0x19 => "MSETime",
+            0x1a => "DatabaseIdentifier",
+            0x1b => "ConversationListingVersionCounter",
+            0x1c => "PresenceAvailability",
+            0x1d => "PresenceText",
+            0x1e => "LastActivity",
+            0x1f => "FilterLastActivityBegin",
+            0x20 => "FilterLastActivityEnd",
+            0x21 => "ChatState",
+            0x22 => "ConversationID",
+            0x23 => "FolderVersionCounter",
+            0x24 => "FilterMessageHandle",
+            0x25 => "NotificationFilterMask",
+            0x26 => "ConvParameterMask",
+            0x27 => "OwnerUCI",
+            0x28 => "ExtendedData",
+            0x29 => "MapSupportedFeatures",
+            0x2a => "MessageHandle",
+            0x2b => "ModifyText",
+            _ => "",
+        },
+        ProfileFamily::Unknown => "",
+    }
+}
+
+const VCARD_PROPERTIES: [&str; 32] = [
+    "VERSION",
+    "FN",
+    "N",
+    "PHOTO",
+    "BDAY",
+    "ADR",
+    "LABEL",
+    "TEL",
+    "EMAIL",
+    "MAILER",
+    "TZ",
+    "GEO",
+    "TITLE",
+    "ROLE",
+    "LOGO",
+    "AGENT",
+    "ORG",
+    "NOTE",
+    "REV",
+    "SOUND",
+    "URL",
+    "UID",
+    "KEY",
+    "NICKNAME",
+    "CATEGORIES",
+    "PROID",
+    "CLASS",
+    "SORT-STRING",
+    "X-IRMC-CALL-DATETIME",
+    "X-BT-SPEEDDIALKEY",
+    "X-BT-UCI",
+    "X-BT-UID",
+];
+
+const PBAP_FEATURES: [&str; 10] = [
+    "Download",
+    "Browsing",
+    "Database Identifier",
+    "Folder Version Counters",
+    "vCard Selecting",
+    "Enhanced Missed Calls",
+    "X-BT-UCI vCard Property",
+    "X-BT-UID vCard Property",
+    "Contact Referencing",
+    "Default Contact Image Format",
+];
+
+const MAP_FEATURES: [&str; 23] = [
+    "Notification Registration",
+    "Notification",
+    "Browsing",
+    "Uploading",
+    "Delete",
+    "Instance Information",
+    "Extended Event Report 1.1",
+    "Event Report Version 1.2",
+    "Message Format Version 1.1",
+    "Messages-Listing Format Version 1.1",
+    "Persistent Message Handles",
+    "Database Identifier",
+    "Folder Version Counter",
+    "Conversation Version Counters",
+    "Participant Presence Change Notification",
+    "Participant Chat State Change Notification",
+    "PBAP Contact Cross Reference",
+    "Notification Filtering",
+    "UTC Offset Timestamp Format",
+    "MapSupportedFeatures in Connect Request",
+    "Conversation listing",
+    "Owner Status",
+    "Message Forwarding",
+];
+
+const MAP_MESSAGE_TYPES: [&str; 5] = ["SMS_GSM", "SMS_CDMA", "EMAIL", "MMS", "IM"];
+
+fn get_bits_name(value: u64, names: &[&str]) -> String {
+    names
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| value & (1 << bit) != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+fn get_app_param_value_name(family: ProfileFamily, tag: u8, value: u64) -> String {
+    let name = match (family, tag, value) {
+        (ProfileFamily::Pbap, 0x01, 0x00) => "Indexed",
+        (ProfileFamily::Pbap, 0x01, 0x01) => "Alphanumeric",
+        (ProfileFamily::Pbap, 0x01, 0x02) => "Phonetic",
+        (ProfileFamily::Pbap, 0x03, 0x00) => "Name",
+        (ProfileFamily::Pbap, 0x03, 0x01) => "Number",
+        (ProfileFamily::Pbap, 0x03, 0x02) => "Sound",
+        (ProfileFamily::Pbap, 0x06 | 0x0c, _) => return get_bits_name(value, &VCARD_PROPERTIES),
+        (ProfileFamily::Pbap, 0x07, 0x00) => "vCard 2.1",
+        (ProfileFamily::Pbap, 0x07, 0x01) => "vCard 3.0",
+        (ProfileFamily::Pbap, 0x0e, 0x00) => "OR",
+        (ProfileFamily::Pbap, 0x0e, 0x01) => "AND",
+        (ProfileFamily::Pbap, 0x10, _) => return get_bits_name(value, &PBAP_FEATURES),
+        (ProfileFamily::Map, 0x03, _) => {
+            return match get_bits_name(value, &MAP_MESSAGE_TYPES).as_str() {
+                "" => String::new(),
+                types => format!("Filtered out: {}", types),
+            }
+        }
+        (ProfileFamily::Map, 0x06, 0x01) => "Unread messages only",
+        (ProfileFamily::Map, 0x06, 0x02) => "Read messages only",
+        (ProfileFamily::Map, 0x0a..=0x0e, 0x00) => "Off",
+        (ProfileFamily::Map, 0x0a..=0x0e, 0x01) => "On",
+        (ProfileFamily::Map, 0x14, 0x00) => "Native",
+        (ProfileFamily::Map, 0x14, 0x01) => "UTF-8",
+        (ProfileFamily::Map, 0x15, 0x00) => "First",
+        (ProfileFamily::Map, 0x15, 0x01) => "Next",
+        (ProfileFamily::Map, 0x16, 0x00) => "More",
+        (ProfileFamily::Map, 0x16, 0x01) => "Last",
+        (ProfileFamily::Map, 0x17, 0x00) => "readStatus",
+        (ProfileFamily::Map, 0x17, 0x01) => "deletedStatus",
+        (ProfileFamily::Map, 0x17, 0x02) => "setExtendedData",
+        (ProfileFamily::Map, 0x29, _) => return get_bits_name(value, &MAP_FEATURES),
+        _ => "",
+    };
+    name.to_string()
+}
@@ -0,0 +1,971 @@
+use crate::assigned_numbers::get_uuid16_name;
+use crate::hfp::AtStream;
+use crate::obex::{ObexChannel, OBEX};
+use crate::HostStack;
+use crate::ParseNodeA;
+use crate::ParseNodeOpt;
+use crate::SummaryKeys;
+
+use crate::ParseBitsNode;
+use crate::ParseBytesNode;
+
+const SABM: u8 = 0x2f;
+const UA: u8 = 0x63;
+const DM: u8 = 0x0f;
+const DISC: u8 = 0x43;
+const UIH: u8 = 0xef;
+const PF: u8 = 0x10;
+
+#[derive(Default, Debug)]
+pub struct RFCOMMArg {
+    dlcs: Vec<Dlc>,
+    // DLCs of each connection that has ended, kept for the summary
+    closed: Vec<Vec<Dlc>>,
+}
+
+impl RFCOMMArg {
+    fn find(&self, acl_handle: u16, dlci: u8) -> Option<&Dlc> {
+        self.dlcs
+            .iter()
+            .find(|dlc| dlc.acl_handle == acl_handle && dlc.dlci == dlci)
+    }
+
+    /// Returns the DLC `dlci` of `acl_handle`, creating it on first use.
+    fn dlc(&mut self, acl_handle: u16, dlci: u8) -> &mut Dlc {
+        let index = match self
+            .dlcs
+            .iter()
+            .position(|dlc| dlc.acl_handle == acl_handle && dlc.dlci == dlci)
+        {
+            Some(index) => index,
+            None => {
+                self.dlcs.push(Dlc::new(acl_handle, dlci));
+                self.dlcs.len() - 1
+            }
+        };
+        &mut self.dlcs[index]
+    }
+
+    /// Closes the multiplexer session of `acl_handle` with all its DLCs.
+    pub(crate) fn disconnect(&mut self, acl_handle: u16) {
+        let (ended, open): (Vec<Dlc>, Vec<Dlc>) = std::mem::take(&mut self.dlcs)
+            .into_iter()
+            .partition(|dlc| dlc.acl_handle == acl_handle);
+        self.dlcs = open;
+        if !ended.is_empty() {
+            self.closed.push(ended);
+        }
+    }
+
+    pub(crate) fn summary(&self) -> String {
+        let mut keys = SummaryKeys::default();
+        self.closed
+            .iter()
+            .chain(std::iter::once(&self.dlcs))
+            .map(|dlcs| dlcs_summary(dlcs, &mut keys))
+            .filter(|summary| !summary.is_empty())
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+/// Summary of `dlcs`, grouped by connection.
+fn dlcs_summary(dlcs: &[Dlc], keys: &mut SummaryKeys) -> String {
+    let mut acl_handles: Vec<u16> = Vec::new();
+    for dlc in dlcs.iter() {
+        if !acl_handles.contains(&dlc.acl_handle) {
+            acl_handles.push(dlc.acl_handle);
+        }
+    }
+    acl_handles
+        .iter()
+        .map(|acl_handle| {
+            let dlcs_s = dlcs
+                .iter()
+                .filter(|dlc| dlc.acl_handle == *acl_handle && dlc.dlci != 0)
+                .map(|dlc| dlc.summary())
+                .collect::<Vec<String>>()
+                .join(", ");
+            let key = keys.unique(format!("Connection {:#x}", acl_handle));
+            format!(r#""{}": {{{}}}"#, key, dlcs_s)
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DlcState {
+    Closed,
+    Opening,
+    Open,
+    Closing,
+}
+
+/// One data link connection. RFCOMM runs a single multiplexer session
+/// between two devices, so the ACL link tells which L2CAP channel it is on.
+/// Per side values are indexed by who sent them, the session initiator
+/// first: UIH frames carry C/R set when they come from the initiator.
+#[derive(Debug)]
+struct Dlc {
+    acl_handle: u16,
+    dlci: u8,
+    state: DlcState,
+    frame_size: Option<u16>,
+    credit_based: bool,
+    // credits each side has been granted and not used yet
+    credits: [u16; 2],
+    // the last modem signals each side sent with MSC
+    modem_signals: [Option<u8>; 2],
+    profile: Option<u16>,
+}
+
+impl Dlc {
+    fn new(acl_handle: u16, dlci: u8) -> Self {
+        Dlc {
+            acl_handle,
+            dlci,
+            state: DlcState::Closed,
+            frame_size: None,
+            credit_based: false,
+            credits: [0; 2],
+            modem_signals: [None; 2],
+            profile: None,
+        }
+    }
+
+    fn summary(&self) -> String {
+        let mut fields = vec![
+            format!(r#""Server Channel": {}"#, self.dlci >> 1),
+            format!(
+                r#""Profile": "{}""#,
+                self.profile.map(get_uuid16_name).unwrap_or("Unknown")
+            ),
+            format!(r#""State": "{:?}""#, self.state),
+        ];
+        if let Some(frame_size) = self.frame_size {
+            fields.push(format!(r#""Maximum Frame Size": {}"#, frame_size));
+        }
+        fields.push(format!(
+            r#""Credit Based Flow Control": {}"#,
+            self.credit_based
+        ));
+        if self.credit_based {
+            fields.push(format!(
+                r#""Credits": {{"Initiator": {}, "Responder": {}}}"#,
+                self.credits[0], self.credits[1]
+            ));
+        }
+        for (side, signals) in ["Initiator", "Responder"].iter().zip(self.modem_signals) {
+            if let Some(signals) = signals {
+                fields.push(format!(r#""{} Modem Signals": "{:#04x}""#, side, signals));
+            }
+        }
+        format!(r#""DLCI {}": {{{}}}"#, self.dlci, fields.join(", "))
+    }
+}
+
+/// An RFCOMM frame: address, control, length, the credit field of credit
+/// based flow control, information and FCS.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq)]
+pub struct RFCOMM {
+    address: u8,
+    control: u8,
+    length: u16,
+    length_len: u16,
+    credits: Option<u8>,
+    information: Information,
+    information_len: u16,
+    information_error: String,
+    fcs: u8,
+    calculated_fcs: u8,
+    profile: Option<u16>,
+    payload: Option<ProfilePayload>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Information {
+    Empty,
+    Data(Vec<u8>),
+    // UIH frames on DLCI 0
+    MultiplexerControl(Vec<MultiplexerCommand>),
+}
+
+/// The information of a DLC decoded by the profile its server channel serves.
+#[derive(Debug, PartialEq)]
+enum ProfilePayload {
+    // Hands-Free and Headset profiles
+    At(AtStream),
+    // Object Push, File Transfer, Phonebook Access and Message Access
+    Obex(OBEX),
+}
+
+impl ParseNodeOpt for RFCOMM {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
+        if data.len() < 4 {
+            return None;
+        }
+        let address = data[0];
+        let control = data[1];
+        let (length, length_len) = match data[2] & 0x01 {
+            1 => ((data[2] >> 1) as u16, 1),
+            _ => ((data[2] >> 1) as u16 | (*data.get(3)? as u16) << 7, 2),
+        };
+        let header_len = 2 + length_len as usize;
+        if data.len() < header_len + 1 {
+            return None;
+        }
+        let dlci = address >> 2;
+        let frame_type = control & !PF;
+        let acl_handle = args
+            .as_deref()
+            .map(|args| args.hci_arg.acl_handle())
+            .unwrap_or_default();
+        // with credit based flow control a UIH frame with P/F set carries
+        // credits ahead of the information. It is only used once a PN
+        // exchange agreed on it, a DLC whose PN was not seen has none.
+        let credit_based = args
+            .as_deref()
+            .and_then(|args| args.rfcomm_arg.find(acl_handle, dlci))
+            .is_some_and(|dlc| dlc.credit_based);
+        let credits = match frame_type == UIH && control & PF != 0 && dlci != 0 && credit_based {
+            true if data.len() > header_len + 1 => Some(data[header_len]),
+            _ => None,
+        };
+        let info_start = header_len + credits.is_some() as usize;
+        let body = &data[info_start..data.len() - 1];
+        let information = match (frame_type, dlci) {
+            _ if body.is_empty() => Information::Empty,
+            (UIH, 0) => Information::MultiplexerControl(multiplexer_commands(body)),
+            _ => Information::Data(body.to_vec()),
+        };
+        // the FCS of UIH frames covers the address and control fields only
+        let fcs_len = match frame_type {
+            UIH => 2,
+            _ => header_len,
+        };
+        let mut errors = Vec::new();
+        let mut profile = None;
+        let mut payload = None;
+        if let Some(args) = args {
+            let side = match address & 0x02 {
+                0 => 1,
+                _ => 0,
+            };
+            if let Information::MultiplexerControl(commands) = &information {
+                for command in commands.iter() {
+                    command.update(&mut args.rfcomm_arg, acl_handle, side);
+                }
+            }
+            if dlci == 0 && matches!(frame_type, UA | DM) {
+                // closing the multiplexer closes every DLC on it
+                if args.rfcomm_arg.dlc(acl_handle, 0).state == DlcState::Closing {
+                    for dlc in args
+                        .rfcomm_arg
+                        .dlcs
+                        .iter_mut()
+                        .filter(|dlc| dlc.acl_handle == acl_handle)
+                    {
+                        dlc.state = DlcState::Closed;
+                    }
+                }
+            }
+            let dlc = args.rfcomm_arg.dlc(acl_handle, dlci);
+            dlc.state = match (frame_type, dlc.state) {
+                (SABM, _) => DlcState::Opening,
+                (DISC, _) => DlcState::Closing,
+                (UA, DlcState::Opening) => DlcState::Open,
+                (UA | DM, _) => DlcState::Closed,
+                (_, state) => state,
+            };
+            if dlci != 0 {
+                dlc.profile = dlc
+                    .profile
+                    .or_else(|| args.sdp_arg.rfcomm_profile(&args.hci_arg, dlci >> 1));
+                profile = dlc.profile;
+            }
+            if frame_type == UIH && dlci != 0 {
+                if let Some(credits) = credits {
+                    dlc.credits[1 - side] = dlc.credits[1 - side].saturating_add(credits as u16);
+                }
+                if !body.is_empty() && dlc.credit_based {
+                    match dlc.credits[side].checked_sub(1) {
+                        Some(credits) => dlc.credits[side] = credits,
+                        None => {
+                            errors.push("Credit underflow, sender had no credits left".to_string())
+                        }
+                    }
+                }
+                if let Some(frame_size) = dlc.frame_size {
+                    if body.len() > frame_size as usize {
+                        errors.push(format!(
+                            "Information exceeds the negotiated frame size of {}",
+                            frame_size
+                        ));
+                    }
+                }
+            }
+            if frame_type == UIH && dlci != 0 && !body.is_empty() {
+                payload = match profile {
+                    Some(0x1108 | 0x1112 | 0x111e | 0x111f | 0x1131) => Some(ProfilePayload::At(
+                        AtStream::new(body, Some(args), (dlci, side)),
+                    )),
+                    Some(0x1105 | 0x1106 | 0x112e | 0x112f | 0x1130 | 0x1132 | 0x1133 | 0x1134) => {
+                        Some(ProfilePayload::Obex(OBEX::new(
+                            body,
+                            Some(args),
+                            (ObexChannel::Rfcomm(dlci), side, profile),
+                        )))
+                    }
+                    _ => None,
+                };
+            }
+        }
+        Some(RFCOMM {
+            address,
+            control,
+            length,
+            length_len,
+            credits,
+            information,
+            information_len: body.len() as u16,
+            information_error: errors.join("; "),
+            fcs: data[data.len() - 1],
+            calculated_fcs: fcs(&data[..fcs_len]),
+            profile,
+            payload,
+        })
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let dlci = self.address >> 2;
+        let mut fields = vec![
+            ParseBitsNode::new(start_byte, 1, 0, 1).format("EA", self.address & 0x01, "", ""),
+            ParseBitsNode::new(start_byte, 1, 1, 1).format(
+                "C/R",
+                (self.address >> 1) & 0x01,
+                "",
+                "",
+            ),
+            ParseBitsNode::new(start_byte, 1, 2, 6).format(
+                "DLCI",
+                dlci,
+                &match self.profile {
+                    Some(profile) => {
+                        format!("{} ({})", get_dlci_name(dlci), get_uuid16_name(profile))
+                    }
+                    None => get_dlci_name(dlci),
+                },
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 1, 1).format(
+                "Control",
+                self.control,
+                get_frame_type_name(self.control & !PF),
+                "",
+            ),
+            ParseBitsNode::new(start_byte + 1, 1, 4, 1).format(
+                "P/F",
+                (self.control >> 4) & 0x01,
+                "",
+                "",
+            ),
+            ParseBitsNode::new(start_byte + 2, 1, 0, 1).format(
+                "Length EA",
+                (self.length_len == 1) as u8,
+                "",
+                "",
+            ),
+            ParseBitsNode::new(
+                start_byte + 2,
+                self.length_len,
+                1,
+                self.length_len as u8 * 8 - 1,
+            )
+            .format(
+                "Length",
+                self.length,
+                "",
+                match self.length == self.information_len {
+                    true => "",
+                    false => "Length does not match the information carried",
+                },
+            ),
+        ];
+        let mut offset = start_byte + 2 + self.length_len;
+        if let Some(credits) = self.credits {
+            fields.push(ParseBytesNode::new(offset, 1).format("Credits", credits, "", ""));
+            offset += 1;
+        }
+        match &self.information {
+            Information::Empty => {}
+            Information::Data(data) => {
+                fields.push(ParseBytesNode::new(offset, data.len() as u16).format(
+                    "Information",
+                    &data[..],
+                    "",
+                    &self.information_error,
+                ));
+                match &self.payload {
+                    Some(ProfilePayload::At(at)) => fields.push(at.as_json(offset)),
+                    Some(ProfilePayload::Obex(obex)) => fields.push(obex.as_json(offset)),
+                    None => {}
+                }
+            }
+            Information::MultiplexerControl(commands) => {
+                let mut command_offset = offset;
+                fields.extend(commands.iter().enumerate().map(|(i, command)| {
+                    let command_s = command.as_json(command_offset);
+                    command_offset += command.len();
+                    format!(r#""Multiplexer Command[{}]": {{{}}}"#, i, command_s)
+                }));
+            }
+        }
+        offset += self.information_len;
+        let fcs_error = match self.fcs == self.calculated_fcs {
+            true => String::new(),
+            false => format!("FCS mismatch, calculated {:#04x}", self.calculated_fcs),
+        };
+        fields.push(ParseBytesNode::new(offset, 1).format("FCS", self.fcs, "", &fcs_error));
+        format!(r#""RFCOMM": {{{}}}"#, fields.join(", "))
+    }
+}
+
+/// One multiplexer control message: type, length and value.
+#[derive(Debug, PartialEq)]
+struct MultiplexerCommand {
+    command_type: u8,
+    length: u16,
+    length_len: u16,
+    params: MultiplexerParams,
+}
+
+#[derive(Debug, PartialEq)]
+enum MultiplexerParams {
+    Empty,
+    ParameterNegotiation {
+        dlci: u8,
+        frame_type: u8,
+        convergence_layer: u8,
+        priority: u8,
+        ack_timer: u8,
+        max_frame_size: u16,
+        max_retransmissions: u8,
+        window_size: u8,
+    },
+    ModemStatus {
+        dlci: u8,
+        signals: u8,
+        break_signal: Option<u8>,
+    },
+    RemotePortNegotiation {
+        dlci: u8,
+        settings: Option<[u8; 7]>,
+    },
+    RemoteLineStatus {
+        dlci: u8,
+        line_status: u8,
+    },
+    NonSupportedCommand(u8),
+    // the test pattern and anything undecoded
+    Value(Vec<u8>),
+}
+
+fn multiplexer_commands(data: &[u8]) -> Vec<MultiplexerCommand> {
+    let mut commands = Vec::new();
+    let mut offset = 0;
+    while offset + 2 <= data.len() {
+        let command_type = data[offset];
+        let (length, length_len) = match data[offset + 1] & 0x01 {
+            1 => ((data[offset + 1] >> 1) as u16, 1),
+            _ => match data.get(offset + 2) {
+                Some(high) => ((data[offset + 1] >> 1) as u16 | (*high as u16) << 7, 2),
+                None => break,
+            },
+        };
+        let start = offset + 1 + length_len as usize;
+        let end = (start + length as usize).min(data.len());
+        commands.push(MultiplexerCommand {
+            command_type,
+            length,
+            length_len,
+            params: MultiplexerParams::new(&data[start..end], command_type >> 2),
+        });
+        offset = end;
+    }
+    commands
+}
+
+impl MultiplexerParams {
+    fn new(data: &[u8], command_type: u8) -> Self {
+        let dlci = data.first().map(|dlci| dlci >> 2).unwrap_or_default();
+        match command_type {
+            0x20 if data.len() >= 8 => MultiplexerParams::ParameterNegotiation {
+                dlci: data[0] & 0x3f,
+                frame_type: data[1] & 0x0f,
+                convergence_layer: data[1] >> 4,
+                priority: data[2] & 0x3f,
+                ack_timer: data[3],
+                max_frame_size: u16::from_le_bytes([data[4], data[5]]),
+                max_retransmissions: data[6],
+                window_size: data[7] & 0x07,
+            },
+            0x38 if data.len() >= 2 => MultiplexerParams::ModemStatus {
+                dlci,
+                signals: data[1],
+                break_signal: data.get(2).copied(),
+            },
+            0x24 if !data.is_empty() => MultiplexerParams::RemotePortNegotiation {
+                dlci,
+                settings: data.get(1..8).map(|settings| settings.try_into().unwrap()),
+            },
+            0x14 if data.len() >= 2 => MultiplexerParams::RemoteLineStatus {
+                dlci,
+                line_status: data[1],
+            },
+            0x04 if !data.is_empty() => MultiplexerParams::NonSupportedCommand(data[0]),
+            _ if data.is_empty() => MultiplexerParams::Empty,
+            _ => MultiplexerParams::Value(data.to_vec()),
+        }
+    }
+}
+
+impl MultiplexerCommand {
+    /// Applies a PN or MSC command sent by `side` to the DLC it names.
+    fn update(&self, rfcomm_arg: &mut RFCOMMArg, acl_handle: u16, side: usize) {
+        let is_command = self.command_type & 0x02 != 0;
+        match self.params {
+            MultiplexerParams::ParameterNegotiation {
+                dlci,
+                convergence_layer,
+                max_frame_size,
+                window_size,
+                ..
+            } => {
+                let dlc = rfcomm_arg.dlc(acl_handle, dlci);
+                dlc.frame_size = Some(max_frame_size);
+                dlc.credit_based = match is_command {
+                    true => convergence_layer == 0x0f,
+                    false => convergence_layer == 0x0e,
+                };
+                // the initial credits are granted to the other side
+                dlc.credits[1 - side] = match dlc.credit_based {
+                    true => window_size as u16,
+                    false => 0,
+                };
+            }
+            MultiplexerParams::ModemStatus { dlci, signals, .. } if is_command => {
+                rfcomm_arg.dlc(acl_handle, dlci).modem_signals[side] = Some(signals);
+            }
+            _ => {}
+        }
+    }
+
+    fn len(&self) -> u16 {
+        1 + self.length_len + self.length
+    }
+
+    fn as_json(&self, start_byte: u16) -> String {
+        let command_type = self.command_type >> 2;
+        let mut fields = vec![
+            ParseBitsNode::new(start_byte, 1, 0, 1).format("EA", self.command_type & 0x01, "", ""),
+            ParseBitsNode::new(start_byte, 1, 1, 1).format(
+                "C/R",
+                (self.command_type >> 1) & 0x01,
+                match self.command_type & 0x02 {
+                    0 => "Response",
+                    _ => "Command",
+                },
+                "",
+            ),
+            ParseBitsNode::new(start_byte, 1, 2, 6).format(
+                "Type",
+                command_type,
+                get_multiplexer_command_name(command_type),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 1, self.length_len).format(
+                "Length",
+                self.length,
+                "",
+                "",
+            ),
+        ];
+        let params_s = self
+            .params
+            .as_json(start_byte + 1 + self.length_len, self.length);
+        if !params_s.is_empty() {
+            fields.push(params_s);
+        }
+        fields.join(", ")
+    }
+}
+
+impl MultiplexerParams {
+    fn as_json(&self, start_byte: u16, length: u16) -> String {
+        let dlci_s = |dlci: u8| {
+            ParseBitsNode::new(start_byte, 1, 2, 6).format("DLCI", dlci, &get_dlci_name(dlci), "")
+        };
+        match self {
+            MultiplexerParams::Empty => String::new(),
+            MultiplexerParams::ParameterNegotiation {
+                dlci,
+                frame_type,
+                convergence_layer,
+                priority,
+                ack_timer,
+                max_frame_size,
+                max_retransmissions,
+                window_size,
+            } => [
+                ParseBitsNode::new(start_byte, 1, 0, 6).format(
+                    "DLCI",
+                    dlci,
+                    &get_dlci_name(*dlci),
+                    "",
+                ),
+                ParseBitsNode::new(start_byte + 1, 1, 0, 4).format(
+                    "Frame Type",
+                    frame_type,
+                    match frame_type {
+                        0x00 => "UIH",
+                        _ => "Not used by RFCOMM",
+                    },
+                    "",
+                ),
+                ParseBitsNode::new(start_byte + 1, 1, 4, 4).format(
+                    "Convergence Layer",
+                    convergence_layer,
+                    match convergence_layer {
+                        0x00 => "No credit based flow control",
+                        0x0f => "Credit based flow control request",
+                        0x0e => "Credit based flow control accepted",
+                        _ => "Not used by RFCOMM",
+                    },
+                    "",
+                ),
+                ParseBitsNode::new(start_byte + 2, 1, 0, 6).format("Priority", priority, "", ""),
+                ParseBytesNode::new(start_byte + 3, 1).format(
+                    "Acknowledgement Timer",
+                    ack_timer,
+                    "",
+                    "",
+                ),
+                ParseBytesNode::new(start_byte + 4, 2).format(
+                    "Maximum Frame Size",
+                    max_frame_size,
+                    &max_frame_size.to_string(),
+                    "",
+                ),
+                ParseBytesNode::new(start_byte + 6, 1).format(
+                    "Maximum Number of Retransmissions",
+                    max_retransmissions,
+                    "",
+                    "",
+                ),
+                ParseBitsNode::new(start_byte + 7, 1, 0, 3).format(
+                    "Initial Credits",
+                    window_size,
+                    "",
+                    "",
+                ),
+            ]
+            .join(", "),
+            MultiplexerParams::ModemStatus {
+                dlci,
+                signals,
+                break_signal,
+            } => {
+                let names = [
+                    (1, "FC", "Flow Control"),
+                    (2, "RTC", "Ready To Communicate"),
+                    (3, "RTR", "Ready To Receive"),
+                    (6, "IC", "Incoming Call"),
+                    (7, "DV", "Data Valid"),
+                ];
+                let mut fields = vec![dlci_s(*dlci)];
+                fields.extend(names.iter().map(|(bit, name, alias)| {
+                    ParseBitsNode::new(start_byte + 1, 1, *bit, 1).format(
+                        name,
+                        (signals >> bit) & 0x01,
+                        alias,
+                        "",
+                    )
+                }));
+                if let Some(break_signal) = break_signal {
+                    fields.push(ParseBitsNode::new(start_byte + 2, 1, 1, 1).format(
+                        "Break",
+                        (break_signal >> 1) & 0x01,
+                        "",
+                        "",
+                    ));
+                    fields.push(ParseBitsNode::new(start_byte + 2, 1, 4, 4).format(
+                        "Break Length",
+                        break_signal >> 4,
+                        "Units of 200 ms",
+                        "",
+                    ));
+                }
+                fields.join(", ")
+            }
+            MultiplexerParams::RemotePortNegotiation { dlci, settings } => {
+                let mut fields = vec![dlci_s(*dlci)];
+                if let Some(settings) = settings {
+                    let line_settings = settings[1];
+                    let parameter_mask = u16::from_le_bytes([settings[5], settings[6]]);
+                    fields.extend([
+                        ParseBytesNode::new(start_byte + 1, 1).format(
+                            "Baud Rate",
+                            settings[0],
+                            get_baud_rate_name(settings[0]),
+                            "",
+                        ),
+                        ParseBitsNode::new(start_byte + 2, 1, 0, 2).format(
+                            "Data Bits",
+                            line_settings & 0x03,
+                            match line_settings & 0x03 {
+                                0b00 => "5 bits",
+                                0b01 => "6 bits",
+                                0b10 => "7 bits",
+                                _ => "8 bits",
+                            },
+                            "",
+                        ),
+                        ParseBitsNode::new(start_byte + 2, 1, 2, 1).format(
+                            "Stop Bits",
+                            (line_settings >> 2) & 0x01,
+                            match line_settings & 0x04 {
+                                0 => "1 stop bit",
+                                _ => "1.5 stop bits",
+                            },
+                            "",
+                        ),
+                        ParseBitsNode::new(start_byte + 2, 1, 3, 1).format(
+                            "Parity",
+                            (line_settings >> 3) & 0x01,
+                            match line_settings & 0x08 {
+                                0 => "No parity",
+                                _ => "Parity",
+                            },
+                            "",
+                        ),
+                        ParseBitsNode::new(start_byte + 2, 1, 4, 2).format(
+                            "Parity Type",
+                            (line_settings >> 4) & 0x03,
+                            match (line_settings >> 4) & 0x03 {
+                                0b00 => "Odd parity",
+                                0b01 => "Even parity",
+                                0b10 => "Mark parity",
+                                _ => "Space parity",
+                            },
+                            "",
+                        ),
+                        ParseBytesNode::new(start_byte + 3, 1).format(
+                            "Flow Control",
+                            settings[2],
+                            "",
+                            "",
+                        ),
+                        ParseBytesNode::new(start_byte + 4, 1).format("XON", settings[3], "", ""),
+                        ParseBytesNode::new(start_byte + 5, 1).format("XOFF", settings[4], "", ""),
+                        ParseBytesNode::new(start_byte + 6, 2).format(
+                            "Parameter Mask",
+                            parameter_mask,
+                            "",
+                            "",
+                        ),
+                    ]);
+                }
+                fields.join(", ")
+            }
+            MultiplexerParams::RemoteLineStatus { dlci, line_status } => format!(
+                "{}, {}, {}",
+                dlci_s(*dlci),
+                ParseBitsNode::new(start_byte + 1, 1, 0, 1).format(
+                    "Error",
+                    line_status & 0x01,
+                    "",
+                    ""
+                ),
+                ParseBitsNode::new(start_byte + 1, 1, 1, 3).format(
+                    "Error Type",
+                    (line_status >> 1) & 0x07,
+                    match (line_status >> 1) & 0x07 {
+                        0b001 => "Overrun Error",
+                        0b010 => "Parity Error",
+                        0b100 => "Framing Error",
+                        _ => "",
+                    },
+                    ""
+                )
+            ),
+            MultiplexerParams::NonSupportedCommand(command_type) => {
+                ParseBytesNode::new(start_byte, 1).format(
+                    "Command Type",
+                    command_type,
+                    get_multiplexer_command_name(command_type >> 2),
+                    "",
+                )
+            }
+            MultiplexerParams::Value(value) => ParseBytesNode::new(start_byte, value.len() as u16)
+                .format(
+                    "Value",
+                    &value[..],
+                    "",
+                    match value.len() < length as usize {
+                        true => "Truncated",
+                        false => "",
+                    },
+                ),
+        }
+    }
+}
+
+/// The FCS of TS 07.10: CRC-8 with generator x^8 + x^2 + x + 1, an initial
+/// value of all ones and the result complemented.
+fn fcs(data: &[u8]) -> u8 {
+    let crc = data.iter().fold(0xff, |crc, byte| {
+        (0..8).fold(crc ^ *byte, |crc, _| match crc & 0x01 {
+            0 => crc >> 1,
+            _ => (crc >> 1) ^ 0xe0,
+        })
+    });
+    0xff - crc
+}
+
+fn get_dlci_name(dlci: u8) -> String {
+    match dlci {
+        0 => "Multiplexer Control Channel".to_string(),
+        _ => format!("Server Channel {}", dlci >> 1),
+    }
+}
+
+fn get_frame_type_name(frame_type: u8) -> &'static str {
+    match frame_type {
+        SABM => "SABM",
+        UA => "UA",
+        DM => "DM",
+        DISC => "DISC",
+        UIH => "UIH",
+        _ => "Unknown",
+    }
+}
+
+fn get_multiplexer_command_name(command_type: u8) -> &'static str {
+    match command_type {
+        0x20 => "PN - DLC Parameter Negotiation",
+        0x08 => "Test Command",
+        0x28 => "FCon - Flow Control On",
+        0x18 => "FCoff - Flow Control Off",
+        0x38 => "MSC - Modem Status Command",
+        0x04 => "NSC - Non Supported Command Response",
+        0x24 => "RPN - Remote Port Negotiation",
+        0x14 => "RLS - Remote Line Status",
+        0x10 => "PSC - Power Saving Control",
+        0x30 => "CLD - Multiplexer Close Down",
+        _ => "Unknown",
+    }
+}
+
+fn get_baud_rate_name(baud_rate: u8) -> &'static str {
+    match baud_rate {
+        0 => "2400 bit/s",
+        1 => "4800 bit/s",
+        2 => "7200 bit/s",
+        3 => "9600 bit/s",
+        4 => "19200 bit/s",
+        5 => "38400 bit/s",
+        6 => "57600 bit/s",
+        7 => "115200 bit/s",
+        8 => "230400 bit/s",
+        _ => "Reserved",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{hci, str_to_array, HostStack};
+
+    // RFCOMM on L2CAP 0x0040 <-> 0x0041: the multiplexer comes up, PN
+    // agrees on credit based flow control for server channel 1, the
+    // initiator granting 1 credit and the responder 2, and DLCI 2 opens
+    const SETUP: [&str; 8] = [
+        "02 40 20 0c 00 08 00 01 00 02 01 04 00 03 00 40 00",
+        "02 40 20 10 00 0c 00 01 00 03 01 08 00 41 00 40 00 00 00 00 00",
+        "02 40 20 08 00 04 00 41 00 03 3f 01 1c",
+        "02 40 20 08 00 04 00 40 00 03 73 01 d7",
+        "02 40 20 12 00 0e 00 41 00 03 ef 15 83 11 02 f0 00 00 7f 00 00 01 70",
+        "02 40 20 12 00 0e 00 40 00 01 ef 15 81 11 02 e0 00 00 7f 00 00 02 aa",
+        "02 40 20 08 00 04 00 41 00 0b 3f 01 59",
+        "02 40 20 08 00 04 00 40 00 0b 73 01 92",
+    ];
+
+    fn parse(frames: &[&str]) -> Vec<String> {
+        let mut args = HostStack::new();
+        SETUP
+            .iter()
+            .chain(frames)
+            .map(|packet| hci::parse(&str_to_array(packet), &mut args))
+            .skip(SETUP.len())
+            .collect()
+    }
+
+    #[test]
+    fn fcs_is_checked() {
+        let json = parse(&[
+            "02 40 20 0a 00 06 00 41 00 0b ef 05 63 64 9a",
+            "02 40 20 0a 00 06 00 41 00 0b ef 05 67 68 9b",
+        ]);
+        assert!(json[0].contains(r#""FCS": [0x9a, "", "B(14, 1)", ""]"#));
+        assert!(
+            json[1].contains(r#""FCS": [0x9b, "", "B(14, 1)", "FCS mismatch, calculated 0x9a"]"#)
+        );
+    }
+
+    #[test]
+    fn credits_are_granted_and_used() {
+        let json = parse(&[
+            // P/F set: 5 credits for the responder ahead of the information
+            "02 40 20 0b 00 07 00 41 00 0b ff 05 05 61 62 86",
+            "02 40 20 0a 00 06 00 41 00 0b ef 05 63 64 9a",
+            "02 40 20 0a 00 06 00 41 00 0b ef 05 65 66 9a",
+        ]);
+        assert!(json[0].contains(
+            r#""Credits": [0x5, "", "B(12, 1)", ""], "Information": ["61 62", "", "B(13, 2)", ""]"#
+        ));
+        assert!(json[1].contains(r#""Information": ["63 64", "", "B(12, 2)", ""]"#));
+        assert!(json[2].contains(
+            r#""Information": ["65 66", "", "B(12, 2)", "Credit underflow, sender had no credits left"]"#
+        ));
+    }
+
+    #[test]
+    fn dlc_without_pn_has_no_credit_field() {
+        let mut args = HostStack::new();
+        let json: Vec<String> = [
+            SETUP[0],
+            SETUP[1],
+            "02 40 20 0b 00 07 00 41 00 0b ff 05 05 61 62 86",
+        ]
+        .iter()
+        .map(|packet| hci::parse(&str_to_array(packet), &mut args))
+        .collect();
+        assert!(!json[2].contains(r#""Credits""#));
+        assert!(json[2].contains(r#""Information": ["05 61 62", "", "B(12, 3)", ""]"#));
+    }
+
+    #[test]
+    fn reused_handle_gets_its_own_summary_entry() {
+        let mut args = HostStack::new();
+        for packet in SETUP
+            .iter()
+            .chain(&["04 05 04 00 40 00 13"])
+            .chain(SETUP.iter())
+        {
+            hci::parse(&str_to_array(packet), &mut args);
+        }
+        let summary = args.rfcomm_summary();
+        assert!(summary.starts_with(r#"{"Connection 0x40": {"DLCI 2": {"Server Channel": 1, "#));
+        assert!(summary.contains(r#"}}, "Connection 0x40 #2": {"DLCI 2": {"Server Channel": 1, "#));
+    }
+}
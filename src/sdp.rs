@@ -1,42 +1,709 @@
+use crate::hci::{BdAddr, HciArg};
 use crate::HostStack;
 use crate::ParseNodeOpt;
+use crate::SummaryKeys;
 
 use crate::ParseBytesNode;
 
+mod data_element;
+use data_element::DataElement;
+mod records;
+use records::ServiceRecord;
+
 #[derive(Default, Debug, Clone)]
-pub struct SDPArg {}
+pub struct SDPArg {
+    transactions: Vec<SdpTransaction>,
+    devices: Vec<SdpDevice>,
+    // state of the connections that have ended, kept for the summary
+    closed: Vec<SdpDevice>,
+}
+
+/// A ServiceAttribute or ServiceSearchAttribute transaction whose attribute
+/// bytes are still coming in. A continuation request carries a new
+/// Transaction ID, so it is matched by the continuation state it echoes.
+#[derive(Debug, Clone)]
+struct SdpTransaction {
+    acl_handle: u16,
+    trans_id: u16,
+    lists: Vec<u8>,
+    continuation_state: Vec<u8>,
+}
+
+/// The service records seen for one remote device. They outlive the
+/// connection, unless the device's BD_ADDR never showed in a Connection
+/// Complete and only the connection handle names it.
+#[derive(Debug, Clone)]
+struct SdpDevice {
+    remote: SdpRemote,
+    records: Vec<ServiceRecord>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SdpRemote {
+    Device(BdAddr),
+    Connection(u16),
+}
+
+impl SdpRemote {
+    /// The remote device of the ACL data being parsed.
+    fn new(hci_arg: &HciArg) -> Self {
+        let acl_handle = hci_arg.acl_handle();
+        match hci_arg.remote_device(acl_handle) {
+            Some(bd_addr) => SdpRemote::Device(bd_addr),
+            None => SdpRemote::Connection(acl_handle),
+        }
+    }
+}
+
+impl SDPArg {
+    /// Follows requests and responses, concatenating attribute bytes split
+    /// over continuations. Returns the attribute lists reassembled from more
+    /// than one response once the last of them is in.
+    fn update(
+        &mut self,
+        hci_arg: &HciArg,
+        trans_id: u16,
+        pdu_id: u8,
+        params: &SdpParams,
+        continuation_state: &[u8],
+    ) -> Option<DataElement> {
+        let acl_handle = hci_arg.acl_handle();
+        let same_transaction = |transaction: &SdpTransaction| {
+            transaction.acl_handle == acl_handle && transaction.trans_id == trans_id
+        };
+        match (pdu_id, params) {
+            (0x04 | 0x06, _) => {
+                let continued = self.transactions.iter().position(|transaction| {
+                    transaction.acl_handle == acl_handle
+                        && !continuation_state.is_empty()
+                        && transaction.continuation_state == continuation_state
+                });
+                let lists = continued
+                    .map(|index| self.transactions.remove(index).lists)
+                    .unwrap_or_default();
+                self.transactions
+                    .retain(|transaction| !same_transaction(transaction));
+                self.transactions.push(SdpTransaction {
+                    acl_handle,
+                    trans_id,
+                    lists,
+                    continuation_state: Vec::new(),
+                });
+                None
+            }
+            (0x05 | 0x07, SdpParams::AttributeListsRsp { lists, .. }) => {
+                let index = self.transactions.iter().position(same_transaction);
+                let mut buffer = index
+                    .map(|index| self.transactions.remove(index).lists)
+                    .unwrap_or_default();
+                let continued = !buffer.is_empty();
+                buffer.extend_from_slice(lists);
+                if !continuation_state.is_empty() {
+                    self.transactions.push(SdpTransaction {
+                        acl_handle,
+                        trans_id,
+                        lists: buffer,
+                        continuation_state: continuation_state.to_vec(),
+                    });
+                    return None;
+                }
+                let element = DataElement::new(&buffer).filter(|element| {
+                    element.is_complete() && element.len as usize == buffer.len()
+                })?;
+                let records = match pdu_id {
+                    0x05 => vec![ServiceRecord::new(&element)],
+                    _ => element.elements().iter().map(ServiceRecord::new).collect(),
+                };
+                self.device(SdpRemote::new(hci_arg)).add_records(records);
+                continued.then_some(element)
+            }
+            (0x01, _) => {
+                self.transactions
+                    .retain(|transaction| !same_transaction(transaction));
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the records of `remote`, creating them on first use.
+    fn device(&mut self, remote: SdpRemote) -> &mut SdpDevice {
+        let index = match self
+            .devices
+            .iter()
+            .position(|device| device.remote == remote)
+        {
+            Some(index) => index,
+            None => {
+                self.devices.push(SdpDevice {
+                    remote,
+                    records: Vec::new(),
+                });
+                self.devices.len() - 1
+            }
+        };
+        &mut self.devices[index]
+    }
 
-#[derive(Debug, Default, PartialEq)]
+    /// The records of the remote device the ACL data being parsed comes
+    /// from or goes to.
+    fn records(&self, hci_arg: &HciArg) -> impl Iterator<Item = &ServiceRecord> {
+        let remote = SdpRemote::new(hci_arg);
+        self.devices
+            .iter()
+            .filter(move |device| device.remote == remote)
+            .flat_map(|device| device.records.iter())
+    }
+
+    /// The profile served on RFCOMM `channel` of the remote device, as far
+    /// as the service records seen so far tell.
+    pub(crate) fn rfcomm_profile(&self, hci_arg: &HciArg, channel: u8) -> Option<u16> {
+        self.records(hci_arg)
+            .find_map(|record| record.rfcomm_profile(channel))
+    }
+
+    /// The OBEX profile served on L2CAP `psm` of the remote device, as far
+    /// as the service records seen so far tell.
+    pub(crate) fn goep_profile(&self, hci_arg: &HciArg, psm: u16) -> Option<u16> {
+        self.records(hci_arg)
+            .find_map(|record| record.goep_profile(psm))
+    }
+
+    /// The report descriptor of the HID service of the remote device, once
+    /// its service record was seen.
+    pub(crate) fn hid_descriptor(&self, hci_arg: &HciArg) -> Option<&[u8]> {
+        self.records(hci_arg)
+            .find_map(|record| record.hid_descriptor())
+    }
+
+    /// Drops the transactions of `acl_handle`. The records of a device known
+    /// by its BD_ADDR are kept for when it reconnects, those known by the
+    /// handle alone are closed, they say nothing of the device the handle is
+    /// given to next.
+    pub(crate) fn disconnect(&mut self, acl_handle: u16) {
+        self.transactions
+            .retain(|transaction| transaction.acl_handle != acl_handle);
+        let (ended, open): (Vec<SdpDevice>, Vec<SdpDevice>) = std::mem::take(&mut self.devices)
+            .into_iter()
+            .partition(|device| device.remote == SdpRemote::Connection(acl_handle));
+        self.devices = open;
+        self.closed.extend(ended);
+    }
+
+    pub(crate) fn summary(&self) -> String {
+        let mut keys = SummaryKeys::default();
+        self.closed
+            .iter()
+            .chain(self.devices.iter())
+            .map(|device| device.summary(&mut keys))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+impl SdpDevice {
+    fn add_records(&mut self, records: Vec<ServiceRecord>) {
+        for record in records {
+            if record.handle.is_some() {
+                self.records.retain(|known| known.handle != record.handle);
+            }
+            self.records.push(record);
+        }
+    }
+
+    fn summary(&self, keys: &mut SummaryKeys) -> String {
+        let records_s = self
+            .records
+            .iter()
+            .enumerate()
+            .map(|(i, record)| format!(r#""Service Record[{}]": {{{}}}"#, i, record.summary()))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let key = match self.remote {
+            SdpRemote::Device(bd_addr) => bd_addr.to_string(),
+            SdpRemote::Connection(acl_handle) => {
+                keys.unique(format!("Connection {:#x}", acl_handle))
+            }
+        };
+        format!(r#""{}": {{{}}}"#, key, records_s)
+    }
+}
+
+/// An SDP PDU. All multi-byte fields of SDP are big-endian.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq)]
 pub struct SDP {
     pdu_id: u8,
     trans_id: u16,
     param_len: u16,
+    params: SdpParams,
+    continuation_state: Option<Vec<u8>>,
+    // attribute lists split over several responses, once the last is in
+    reassembled: Option<DataElement>,
 }
 
 impl ParseNodeOpt for SDP {
-    fn new(data: &[u8], _args: Option<&mut HostStack>) -> Option<Self> {
+    fn new(data: &[u8], args: Option<&mut HostStack>) -> Option<Self> {
         if data.len() < 5 {
             return None;
         }
+        let pdu_id = data[0];
+        let param_len = u16::from_be_bytes([data[3], data[4]]);
+        let param_data = &data[5..data.len().min(5 + param_len as usize)];
+        let (mut params, params_len) = SdpParams::new(param_data, pdu_id);
+        // every PDU but the error response ends with the continuation state
+        let continuation_state = match pdu_id {
+            0x02..=0x07 => param_data.get(params_len..).and_then(|rest| {
+                let info_len = (*rest.first()? as usize).min(rest.len() - 1);
+                Some(rest[1..1 + info_len].to_vec())
+            }),
+            _ => None,
+        };
+        // a piece of attribute lists split over several responses
+        let is_fragment = continuation_state
+            .as_ref()
+            .is_some_and(|continuation_state| !continuation_state.is_empty());
+        let trans_id = u16::from_be_bytes([data[1], data[2]]);
+        let reassembled = args.and_then(|args| {
+            let acl_handle = args.hci_arg.acl_handle();
+            // a complete attribute response may bring a new HID record
+            if matches!(pdu_id, 0x05 | 0x07) && !is_fragment {
+                args.hidp_arg.forget_descriptor(acl_handle);
+            }
+            args.sdp_arg.update(
+                &args.hci_arg,
+                trans_id,
+                pdu_id,
+                &params,
+                continuation_state.as_deref().unwrap_or_default(),
+            )
+        });
+        if let SdpParams::AttributeListsRsp { decoded, .. } = &mut params {
+            if is_fragment || reassembled.is_some() {
+                *decoded = None;
+            }
+        }
         Some(SDP {
-            pdu_id: data[0],
-            trans_id: u16::from_le_bytes([data[1], data[2]]),
-            param_len: u16::from_le_bytes([data[3], data[4]]),
+            pdu_id,
+            trans_id,
+            param_len,
+            params,
+            continuation_state,
+            reassembled,
         })
     }
-    fn as_json(&self, start_byte: u8) -> String {
-        let pdu_id_s = ParseBytesNode::new(start_byte, 1).format("PDU ID", self.pdu_id, "", "");
-        let trans_id_s =
-            ParseBytesNode::new(start_byte + 1, 2).format("Transaction ID", self.trans_id, "", "");
-        let param_len_s = ParseBytesNode::new(start_byte + 3, 2).format(
-            "Parameter Length",
-            self.param_len,
-            "",
-            "",
+    fn as_json(&self, start_byte: u16) -> String {
+        let mut fields = vec![
+            ParseBytesNode::new(start_byte, 1).format(
+                "PDU ID",
+                self.pdu_id,
+                get_pdu_id_name(self.pdu_id),
+                "",
+            ),
+            ParseBytesNode::new(start_byte + 1, 2).format("Transaction ID", self.trans_id, "", ""),
+            ParseBytesNode::new(start_byte + 3, 2).format(
+                "Parameter Length",
+                self.param_len,
+                "",
+                "",
+            ),
+        ];
+        let (params_s, params_len) = self.params.as_json(self.pdu_id, start_byte + 5);
+        if !params_s.is_empty() {
+            fields.push(params_s);
+        }
+        if let Some(continuation_state) = &self.continuation_state {
+            let offset = start_byte + 5 + params_len;
+            fields.push(ParseBytesNode::new(offset, 1).format(
+                "InfoLength",
+                continuation_state.len() as u8,
+                "",
+                "",
+            ));
+            if !continuation_state.is_empty() {
+                fields.push(
+                    ParseBytesNode::new(offset + 1, continuation_state.len() as u16).format(
+                        "Continuation Information",
+                        &continuation_state[..],
+                        "",
+                        "",
+                    ),
+                );
+            }
+        }
+        if let Some(reassembled) = &self.reassembled {
+            fields.push(match self.pdu_id {
+                0x05 => {
+                    reassembled.attribute_list_as_json("Reassembled AttributeList", start_byte + 7)
+                }
+                _ => reassembled
+                    .attribute_lists_as_json("Reassembled AttributeLists", start_byte + 7),
+            });
+        }
+        format!(r#""SDP": {{{}}}"#, fields.join(", "))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum SdpParams {
+    Empty,
+    ErrorRsp {
+        error_code: u16,
+    },
+    ServiceSearchReq {
+        pattern: DataElement,
+        max_count: u16,
+    },
+    ServiceSearchRsp {
+        total_count: u16,
+        current_count: u16,
+        handles: Vec<u32>,
+    },
+    ServiceAttributeReq {
+        handle: u32,
+        max_byte_count: u16,
+        id_list: DataElement,
+    },
+    ServiceSearchAttributeReq {
+        pattern: DataElement,
+        max_byte_count: u16,
+        id_list: DataElement,
+    },
+    // the AttributeList or AttributeLists of either attribute response,
+    // decoded when the bytes carried form complete data elements
+    AttributeListsRsp {
+        byte_count: u16,
+        lists: Vec<u8>,
+        decoded: Option<DataElement>,
+    },
+}
+
+fn be_u16(data: &[u8], index: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        data.get(index..index + 2)?.try_into().unwrap(),
+    ))
+}
+
+fn be_u32(data: &[u8], index: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(index..index + 4)?.try_into().unwrap(),
+    ))
+}
+
+impl SdpParams {
+    /// Parses the parameters ahead of the continuation state, returning them
+    /// with the number of bytes they take.
+    fn new(data: &[u8], pdu_id: u8) -> (Self, usize) {
+        let params = || -> Option<(Self, usize)> {
+            match pdu_id {
+                0x01 => Some((
+                    SdpParams::ErrorRsp {
+                        error_code: be_u16(data, 0)?,
+                    },
+                    2,
+                )),
+                0x02 => {
+                    let pattern = DataElement::new(data)?;
+                    let offset = pattern.len as usize;
+                    Some((
+                        SdpParams::ServiceSearchReq {
+                            max_count: be_u16(data, offset)?,
+                            pattern,
+                        },
+                        offset + 2,
+                    ))
+                }
+                0x03 => {
+                    let current_count = be_u16(data, 2)?;
+                    let handles: Vec<u32> = data[4..]
+                        .chunks_exact(4)
+                        .take(current_count as usize)
+                        .map(|handle| u32::from_be_bytes(handle.try_into().unwrap()))
+                        .collect();
+                    let len = 4 + handles.len() * 4;
+                    Some((
+                        SdpParams::ServiceSearchRsp {
+                            total_count: be_u16(data, 0)?,
+                            current_count,
+                            handles,
+                        },
+                        len,
+                    ))
+                }
+                0x04 => {
+                    let id_list = DataElement::new(data.get(6..)?)?;
+                    let len = 6 + id_list.len as usize;
+                    Some((
+                        SdpParams::ServiceAttributeReq {
+                            handle: be_u32(data, 0)?,
+                            max_byte_count: be_u16(data, 4)?,
+                            id_list,
+                        },
+                        len,
+                    ))
+                }
+                0x06 => {
+                    let pattern = DataElement::new(data)?;
+                    let offset = pattern.len as usize;
+                    let max_byte_count = be_u16(data, offset)?;
+                    let id_list = DataElement::new(data.get(offset + 2..)?)?;
+                    let len = offset + 2 + id_list.len as usize;
+                    Some((
+                        SdpParams::ServiceSearchAttributeReq {
+                            pattern,
+                            max_byte_count,
+                            id_list,
+                        },
+                        len,
+                    ))
+                }
+                0x05 | 0x07 => {
+                    let byte_count = be_u16(data, 0)?;
+                    let lists = &data[2..data.len().min(2 + byte_count as usize)];
+                    let decoded = DataElement::new(lists)
+                        .filter(|list| list.is_complete() && list.len as usize == lists.len());
+                    Some((
+                        SdpParams::AttributeListsRsp {
+                            byte_count,
+                            lists: lists.to_vec(),
+                            decoded,
+                        },
+                        2 + lists.len(),
+                    ))
+                }
+                _ => None,
+            }
+        };
+        params().unwrap_or((SdpParams::Empty, data.len()))
+    }
+
+    /// Renders the parameters, returning them with the number of bytes they
+    /// take.
+    fn as_json(&self, pdu_id: u8, start_byte: u16) -> (String, u16) {
+        match self {
+            SdpParams::Empty => (String::new(), 0),
+            SdpParams::ErrorRsp { error_code } => (
+                ParseBytesNode::new(start_byte, 2).format(
+                    "ErrorCode",
+                    error_code,
+                    get_error_code_name(*error_code),
+                    "",
+                ),
+                2,
+            ),
+            SdpParams::ServiceSearchReq { pattern, max_count } => (
+                format!(
+                    "{}, {}",
+                    pattern.as_json("ServiceSearchPattern", start_byte),
+                    ParseBytesNode::new(start_byte + pattern.len, 2).format(
+                        "MaximumServiceRecordCount",
+                        max_count,
+                        "",
+                        ""
+                    )
+                ),
+                pattern.len + 2,
+            ),
+            SdpParams::ServiceSearchRsp {
+                total_count,
+                current_count,
+                handles,
+            } => {
+                let mut fields = vec![
+                    ParseBytesNode::new(start_byte, 2).format(
+                        "TotalServiceRecordCount",
+                        total_count,
+                        "",
+                        "",
+                    ),
+                    ParseBytesNode::new(start_byte + 2, 2).format(
+                        "CurrentServiceRecordCount",
+                        current_count,
+                        "",
+                        match handles.len() < *current_count as usize {
+                            true => "Fewer service record handles than counted",
+                            false => "",
+                        },
+                    ),
+                ];
+                fields.extend(handles.iter().enumerate().map(|(i, handle)| {
+                    ParseBytesNode::new(start_byte + 4 + i as u16 * 4, 4).format(
+                        &format!("ServiceRecordHandle[{}]", i),
+                        handle,
+                        "",
+                        "",
+                    )
+                }));
+                (fields.join(", "), 4 + handles.len() as u16 * 4)
+            }
+            SdpParams::ServiceAttributeReq {
+                handle,
+                max_byte_count,
+                id_list,
+            } => (
+                format!(
+                    "{}, {}, {}",
+                    ParseBytesNode::new(start_byte, 4).format(
+                        "ServiceRecordHandle",
+                        handle,
+                        "",
+                        ""
+                    ),
+                    ParseBytesNode::new(start_byte + 4, 2).format(
+                        "MaximumAttributeByteCount",
+                        max_byte_count,
+                        "",
+                        ""
+                    ),
+                    id_list.attribute_id_list_as_json("AttributeIDList", start_byte + 6)
+                ),
+                6 + id_list.len,
+            ),
+            SdpParams::ServiceSearchAttributeReq {
+                pattern,
+                max_byte_count,
+                id_list,
+            } => (
+                format!(
+                    "{}, {}, {}",
+                    pattern.as_json("ServiceSearchPattern", start_byte),
+                    ParseBytesNode::new(start_byte + pattern.len, 2).format(
+                        "MaximumAttributeByteCount",
+                        max_byte_count,
+                        "",
+                        ""
+                    ),
+                    id_list
+                        .attribute_id_list_as_json("AttributeIDList", start_byte + pattern.len + 2)
+                ),
+                pattern.len + 2 + id_list.len,
+            ),
+            SdpParams::AttributeListsRsp {
+                byte_count,
+                lists,
+                decoded,
+            } => {
+                // a ServiceAttribute response carries the one record asked for
+                let (byte_count_key, lists_key) = match pdu_id {
+                    0x05 => ("AttributeListByteCount", "AttributeList"),
+                    _ => ("AttributeListsByteCount", "AttributeLists"),
+                };
+                let byte_count_s = ParseBytesNode::new(start_byte, 2).format(
+                    byte_count_key,
+                    byte_count,
+                    "",
+                    match lists.len() < *byte_count as usize {
+                        true => "Fewer attribute bytes than counted",
+                        false => "",
+                    },
+                );
+                let lists_s = match decoded {
+                    Some(decoded) if pdu_id == 0x05 => {
+                        decoded.attribute_list_as_json(lists_key, start_byte + 2)
+                    }
+                    Some(decoded) => decoded.attribute_lists_as_json(lists_key, start_byte + 2),
+                    None => ParseBytesNode::new(start_byte + 2, lists.len() as u16).format(
+                        lists_key,
+                        &lists[..],
+                        "",
+                        "",
+                    ),
+                };
+                (
+                    format!("{}, {}", byte_count_s, lists_s),
+                    2 + lists.len() as u16,
+                )
+            }
+        }
+    }
+}
+
+fn get_pdu_id_name(pdu_id: u8) -> &'static str {
+    match pdu_id {
+        0x01 => "SDP_ErrorResponse",
+        0x02 => "SDP_ServiceSearchRequest",
+        0x03 => "SDP_ServiceSearchResponse",
+        0x04 => "SDP_ServiceAttributeRequest",
+        0x05 => "SDP_ServiceAttributeResponse",
+        0x06 => "SDP_ServiceSearchAttributeRequest",
+        0x07 => "SDP_ServiceSearchAttributeResponse",
+        _ => "Reserved for future use",
+    }
+}
+
+fn get_error_code_name(error_code: u16) -> &'static str {
+    match error_code {
+        0x0001 => "Invalid/unsupported SDP version",
+        0x0002 => "Invalid Service Record Handle",
+        0x0003 => "Invalid request syntax",
+        0x0004 => "Invalid PDU Size",
+        0x0005 => "Invalid Continuation State",
+        0x0006 => "Insufficient Resources to satisfy Request",
+        _ => "Reserved for future use",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{l2cap_connect, parse_on_channel, parse_packets};
+
+    // SDP channel 0x0040 <-> 0x0041, then a ServiceSearchAttribute
+    // transaction whose attribute lists are split over two responses
+    const PACKETS: [&str; 4] = [
+        "02 40 20 18 00 14 00 41 00 06 00 01 00 0f 35 03 19 11 1f ff ff 35 05 0a 00 00 ff ff 00",
+        "02 40 20 32 00 2e 00 40 00 07 00 01 00 29 00 24 35 47 35 45 09 00 01 35 06 19 11 1f 19 12 03 09 00 04 35 0c 35 03 19 01 00 35 05 19 00 03 08 03 09 00 09 35 02 ab cd",
+        "02 40 20 1a 00 16 00 41 00 06 00 02 00 11 35 03 19 11 1f ff ff 35 05 0a 00 00 ff ff 02 ab cd",
+        "02 40 20 31 00 2d 00 40 00 07 00 02 00 28 00 25 08 35 06 19 11 1e 09 01 08 09 01 00 25 11 48 61 6e 64 73 66 72 65 65 20 47 61 74 65 77 61 79 09 03 11 09 00 2f 00",
+    ];
+
+    #[test]
+    fn continuation_is_reassembled() {
+        let (json, _) = parse_on_channel(0x0001, &PACKETS);
+        assert!(json[1].contains(r#""Continuation Information": ["ab cd", "", "B(53, 2)", ""]"#));
+        assert!(!json[1].contains("Reassembled AttributeLists"));
+        assert!(json[3].contains(r#""Reassembled AttributeLists": {"#));
+        assert!(json[3].contains(r#""Element[0]": [0x111f, "UUID (Handsfree Audio Gateway)""#));
+        assert!(json[3].contains(r#""Attribute Value": ["Handsfree Gateway""#));
+    }
+
+    #[test]
+    fn reassembled_record_reaches_the_summary() {
+        let (_, args) = parse_on_channel(0x0001, &PACKETS);
+        assert_eq!(
+            args.sdp_summary(),
+            r#"{"Connection 0x40": {"Service Record[0]": {"Description": "Handsfree v1.8 on RFCOMM 3", "Service Name": "Handsfree Gateway", "Service Classes": "Handsfree Audio Gateway, Generic Audio", "RFCOMM Channel": 3, "Supported Features": "0x002f"}}}"#
+        );
+    }
+
+    #[test]
+    fn records_outlive_the_connection_of_their_device() {
+        let sdp = l2cap_connect(0x0040, 1, 0x0001, (0x0040, 0x0041));
+        let rfcomm = l2cap_connect(0x0041, 1, 0x0003, (0x0040, 0x0041));
+        let (_, args) = parse_packets(
+            &[
+                &[
+                    "04 03 0b 00 40 00 66 55 44 33 22 11 01 00",
+                    &sdp[0],
+                    &sdp[1],
+                ],
+                &PACKETS[..],
+                &[
+                    "04 05 04 00 40 00 13",
+                    // the device comes back on another handle and opens the
+                    // server channel its record gave
+                    "04 03 0b 00 41 00 66 55 44 33 22 11 01 00",
+                    &rfcomm[0],
+                    &rfcomm[1],
+                    "02 41 20 08 00 04 00 41 00 1b 3f 01 d3",
+                ],
+            ]
+            .concat(),
         );
-        format!(
-            r#""SDP": {{{}, {}, {}}}"#,
-            pdu_id_s, trans_id_s, param_len_s
-        )
+        assert!(args
+            .sdp_summary()
+            .starts_with(r#"{"11:22:33:44:55:66": {"Service Record[0]": {"Description": "Handsfree v1.8 on RFCOMM 3""#));
+        assert!(args.rfcomm_summary().contains(
+            r#""Connection 0x41": {"DLCI 6": {"Server Channel": 3, "Profile": "Handsfree""#
+        ));
     }
 }
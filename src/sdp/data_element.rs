@@ -0,0 +1,459 @@
+use crate::ad::uuid128_to_string;
+use crate::assigned_numbers::{get_uuid128_name, get_uuid16_name, get_uuid32_name};
+use crate::bytes_to_string;
+use crate::hid::report_descriptor_as_json;
+
+use crate::ParseBytesNode;
+
+/// One SDP data element, header included. Sequences and alternatives hold
+/// their elements parsed in turn.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct DataElement {
+    descriptor: u8,
+    header_len: u16,
+    pub(super) len: u16,
+    pub(super) value: DataElementValue,
+    error: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum DataElementValue {
+    Nil,
+    UnsignedInt(u64),
+    // kept as sent, the size tells where the sign bit is
+    SignedInt(u64),
+    // 128-bit integers and elements whose size doesn't fit their type
+    Bytes(Vec<u8>),
+    Uuid16(u16),
+    Uuid32(u32),
+    Uuid128(u128),
+    Text(Vec<u8>),
+    Boolean(u8),
+    Sequence(Vec<DataElement>),
+    Alternative(Vec<DataElement>),
+    Url(Vec<u8>),
+}
+
+fn be_uint(data: &[u8]) -> u64 {
+    data.iter()
+        .fold(0, |value, byte| (value << 8) | *byte as u64)
+}
+
+impl DataElement {
+    /// Parses the data element at the start of `data`. An element claiming
+    /// more bytes than are left is cut short and flagged.
+    pub(super) fn new(data: &[u8]) -> Option<Self> {
+        let descriptor = *data.first()?;
+        let element_type = descriptor >> 3;
+        let size_index = descriptor & 0x07;
+        let (header_len, size) = match size_index {
+            0 if element_type == 0 => (1, 0),
+            0..=4 => (1, 1 << size_index),
+            5 => (2, *data.get(1)? as usize),
+            6 => (3, be_uint(data.get(1..3)?) as usize),
+            _ => (5, be_uint(data.get(1..5)?) as usize),
+        };
+        let end = (header_len + size).min(data.len());
+        let body = &data[header_len..end];
+        let mut error = match body.len() < size {
+            true => "Data element truncated",
+            false => "",
+        };
+        let complete = error.is_empty();
+        let value = match (element_type, size_index) {
+            (0, 0) => DataElementValue::Nil,
+            (1, 0..=3) if complete => DataElementValue::UnsignedInt(be_uint(body)),
+            (2, 0..=3) if complete => DataElementValue::SignedInt(be_uint(body)),
+            (1 | 2, 4) => DataElementValue::Bytes(body.to_vec()),
+            (3, 1) if complete => DataElementValue::Uuid16(be_uint(body) as u16),
+            (3, 2) if complete => DataElementValue::Uuid32(be_uint(body) as u32),
+            (3, 4) if complete => {
+                DataElementValue::Uuid128(u128::from_be_bytes(body.try_into().unwrap()))
+            }
+            (4, 5..) => DataElementValue::Text(body.to_vec()),
+            (5, 0) if complete => DataElementValue::Boolean(body[0]),
+            (6, 5..) => DataElementValue::Sequence(data_element_list(body)),
+            (7, 5..) => DataElementValue::Alternative(data_element_list(body)),
+            (8, 5..) => DataElementValue::Url(body.to_vec()),
+            _ => {
+                if complete {
+                    error = "Invalid size for data element type";
+                }
+                DataElementValue::Bytes(body.to_vec())
+            }
+        };
+        Some(DataElement {
+            descriptor,
+            header_len: header_len as u16,
+            len: end as u16,
+            value,
+            error,
+        })
+    }
+
+    /// Whether the element and everything nested in it was parsed whole.
+    pub(super) fn is_complete(&self) -> bool {
+        self.error.is_empty()
+            && match &self.value {
+                DataElementValue::Sequence(elements) | DataElementValue::Alternative(elements) => {
+                    elements.iter().all(|element| element.is_complete())
+                }
+                _ => true,
+            }
+    }
+
+    pub(super) fn elements(&self) -> &[DataElement] {
+        match &self.value {
+            DataElementValue::Sequence(elements) | DataElementValue::Alternative(elements) => {
+                elements
+            }
+            _ => &[],
+        }
+    }
+
+    pub(super) fn as_uint(&self) -> Option<u64> {
+        match self.value {
+            DataElementValue::UnsignedInt(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_text(&self) -> Option<String> {
+        match &self.value {
+            DataElementValue::Text(text) => Some(bytes_to_string(text)),
+            _ => None,
+        }
+    }
+
+    /// The bytes of a text string, which carries binary data at times.
+    pub(super) fn as_bytes(&self) -> Option<&[u8]> {
+        match &self.value {
+            DataElementValue::Text(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// The UUID as a 16-bit one, when it is or is built on the Base UUID.
+    pub(super) fn as_uuid16(&self) -> Option<u16> {
+        const BASE_UUID: u128 = 0x0000_0000_0000_1000_8000_0080_5f9b_34fb;
+        match self.value {
+            DataElementValue::Uuid16(uuid) => Some(uuid),
+            DataElementValue::Uuid32(uuid) => u16::try_from(uuid).ok(),
+            DataElementValue::Uuid128(uuid) if uuid & ((1 << 96) - 1) == BASE_UUID => {
+                u16::try_from(uuid >> 96).ok()
+            }
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_json(&self, key: &str, start_byte: u16) -> String {
+        let node = ParseBytesNode::new(start_byte, self.len);
+        let type_name = get_type_name(self.descriptor >> 3);
+        match &self.value {
+            DataElementValue::Sequence(elements) | DataElementValue::Alternative(elements) => {
+                let mut fields = vec![self.header_as_json(start_byte)];
+                let mut offset = start_byte + self.header_len;
+                for (i, element) in elements.iter().enumerate() {
+                    fields.push(element.as_json(&format!("Element[{}]", i), offset));
+                    offset += element.len;
+                }
+                format!(r#""{}": {{{}}}"#, key, fields.join(", "))
+            }
+            DataElementValue::Nil => node.format(key, "", type_name, self.error),
+            DataElementValue::UnsignedInt(value) => node.format(key, value, type_name, self.error),
+            DataElementValue::SignedInt(value) => {
+                let shift = 64 - 8 * (self.len - self.header_len) as u32;
+                let signed = ((value << shift) as i64) >> shift;
+                node.format(
+                    key,
+                    value,
+                    &format!("{} ({})", type_name, signed),
+                    self.error,
+                )
+            }
+            DataElementValue::Bytes(bytes) => node.format(key, &bytes[..], type_name, self.error),
+            DataElementValue::Uuid16(uuid) => node.format(
+                key,
+                uuid,
+                &with_detail(type_name, get_uuid16_name(*uuid)),
+                self.error,
+            ),
+            DataElementValue::Uuid32(uuid) => node.format(
+                key,
+                uuid,
+                &with_detail(type_name, get_uuid32_name(*uuid)),
+                self.error,
+            ),
+            DataElementValue::Uuid128(uuid) => node.format(
+                key,
+                uuid128_to_string(*uuid),
+                &with_detail(type_name, get_uuid128_name(*uuid)),
+                self.error,
+            ),
+            DataElementValue::Text(text) | DataElementValue::Url(text) => {
+                node.format(key, bytes_to_string(text), type_name, self.error)
+            }
+            DataElementValue::Boolean(value) => node.format(
+                key,
+                value,
+                &with_detail(type_name, &(*value != 0).to_string()),
+                self.error,
+            ),
+        }
+    }
+
+    fn header_as_json(&self, start_byte: u16) -> String {
+        let descriptor_s = ParseBytesNode::new(start_byte, 1).format(
+            "Data Element Descriptor",
+            self.descriptor,
+            get_type_name(self.descriptor >> 3),
+            self.error,
+        );
+        let size = (self.len - self.header_len) as u32;
+        match self.header_len {
+            1 => descriptor_s,
+            header_len => format!(
+                "{}, {}",
+                descriptor_s,
+                ParseBytesNode::new(start_byte + 1, header_len - 1).format(
+                    "Data Element Size",
+                    size,
+                    "",
+                    ""
+                )
+            ),
+        }
+    }
+
+    /// Renders an AttributeIDList, a sequence of attribute IDs and ranges of
+    /// them.
+    pub(super) fn attribute_id_list_as_json(&self, key: &str, start_byte: u16) -> String {
+        if !matches!(self.value, DataElementValue::Sequence(_)) {
+            return self.as_json(key, start_byte);
+        }
+        let mut fields = vec![self.header_as_json(start_byte)];
+        let mut offset = start_byte + self.header_len;
+        for (i, element) in self.elements().iter().enumerate() {
+            let node = ParseBytesNode::new(offset, element.len);
+            fields.push(match (element.descriptor, element.as_uint()) {
+                (0x09, Some(id)) => node.format(
+                    &format!("Attribute ID[{}]", i),
+                    id as u16,
+                    get_attribute_id_name(id as u16, None),
+                    "",
+                ),
+                (0x0a, Some(range)) => node.format(
+                    &format!("Attribute ID Range[{}]", i),
+                    range as u32,
+                    &format!("{:#06x}-{:#06x}", range >> 16, range & 0xffff),
+                    "",
+                ),
+                _ => element.as_json(&format!("Element[{}]", i), offset),
+            });
+            offset += element.len;
+        }
+        format!(r#""{}": {{{}}}"#, key, fields.join(", "))
+    }
+
+    /// Renders an AttributeList, a sequence of attribute ID and value pairs.
+    /// IDs are named for the service class the record declares.
+    pub(super) fn attribute_list_as_json(&self, key: &str, start_byte: u16) -> String {
+        if !matches!(self.value, DataElementValue::Sequence(_)) {
+            return self.as_json(key, start_byte);
+        }
+        let service_class = self
+            .attribute(0x0001)
+            .and_then(|classes| classes.elements().first())
+            .and_then(|class| class.as_uuid16());
+        let mut fields = vec![self.header_as_json(start_byte)];
+        let mut offset = start_byte + self.header_len;
+        for (i, pair) in self.elements().chunks(2).enumerate() {
+            let id_s = match pair[0].as_uint() {
+                Some(id) => ParseBytesNode::new(offset, pair[0].len).format(
+                    "Attribute ID",
+                    id as u16,
+                    get_attribute_id_name(id as u16, service_class),
+                    pair[0].error,
+                ),
+                None => pair[0].as_json("Attribute ID", offset),
+            };
+            offset += pair[0].len;
+            let value_s = pair
+                .get(1)
+                .map(|value| {
+                    let value_s = match (pair[0].as_uint(), service_class) {
+                        (Some(0x0206), Some(0x1124)) => {
+                            value.hid_descriptor_list_as_json("Attribute Value", offset)
+                        }
+                        _ => value.as_json("Attribute Value", offset),
+                    };
+                    offset += value.len;
+                    format!(", {}", value_s)
+                })
+                .unwrap_or_default();
+            fields.push(format!(r#""Attribute[{}]": {{{}{}}}"#, i, id_s, value_s));
+        }
+        format!(r#""{}": {{{}}}"#, key, fields.join(", "))
+    }
+
+    /// Renders a HIDDescriptorList, walking the report descriptors it holds
+    /// item by item.
+    fn hid_descriptor_list_as_json(&self, key: &str, start_byte: u16) -> String {
+        if !matches!(self.value, DataElementValue::Sequence(_)) {
+            return self.as_json(key, start_byte);
+        }
+        let mut fields = vec![self.header_as_json(start_byte)];
+        let mut offset = start_byte + self.header_len;
+        for (i, descriptor) in self.elements().iter().enumerate() {
+            let element_key = format!("Element[{}]", i);
+            fields.push(match descriptor.elements() {
+                [class, report_descriptor @ DataElement {
+                    value: DataElementValue::Text(bytes),
+                    ..
+                }] if class.as_uint() == Some(0x22) => {
+                    let class_offset = offset + descriptor.header_len;
+                    let descriptor_offset = class_offset + class.len;
+                    format!(
+                        r#""{}": {{{}, {}, "Report Descriptor": {{{}, {}}}}}"#,
+                        element_key,
+                        descriptor.header_as_json(offset),
+                        ParseBytesNode::new(class_offset, class.len).format(
+                            "Class Descriptor Type",
+                            0x22u8,
+                            "Report",
+                            ""
+                        ),
+                        report_descriptor.header_as_json(descriptor_offset),
+                        report_descriptor_as_json(
+                            bytes,
+                            descriptor_offset + report_descriptor.header_len
+                        )
+                    )
+                }
+                _ => descriptor.as_json(&element_key, offset),
+            });
+            offset += descriptor.len;
+        }
+        format!(r#""{}": {{{}}}"#, key, fields.join(", "))
+    }
+
+    /// Renders the AttributeLists of a ServiceSearchAttribute response, one
+    /// AttributeList per matching service record.
+    pub(super) fn attribute_lists_as_json(&self, key: &str, start_byte: u16) -> String {
+        if !matches!(self.value, DataElementValue::Sequence(_)) {
+            return self.as_json(key, start_byte);
+        }
+        let mut fields = vec![self.header_as_json(start_byte)];
+        let mut offset = start_byte + self.header_len;
+        for (i, list) in self.elements().iter().enumerate() {
+            fields.push(list.attribute_list_as_json(&format!("AttributeList[{}]", i), offset));
+            offset += list.len;
+        }
+        format!(r#""{}": {{{}}}"#, key, fields.join(", "))
+    }
+
+    /// The value of attribute `id` in an AttributeList.
+    pub(super) fn attribute(&self, id: u16) -> Option<&DataElement> {
+        self.elements()
+            .chunks(2)
+            .find(|pair| pair[0].as_uint() == Some(id as u64))
+            .and_then(|pair| pair.get(1))
+    }
+}
+
+fn data_element_list(data: &[u8]) -> Vec<DataElement> {
+    let mut elements = Vec::new();
+    let mut offset = 0;
+    while let Some(element) = DataElement::new(&data[offset..]) {
+        offset += element.len as usize;
+        elements.push(element);
+    }
+    elements
+}
+
+fn with_detail(name: &str, detail: &str) -> String {
+    match detail.is_empty() {
+        true => name.to_string(),
+        false => format!("{} ({})", name, detail),
+    }
+}
+
+fn get_type_name(element_type: u8) -> &'static str {
+    match element_type {
+        0 => "Nil",
+        1 => "Unsigned Integer",
+        2 => "Signed Integer",
+        3 => "UUID",
+        4 => "Text String",
+        5 => "Boolean",
+        6 => "Data Element Sequence",
+        7 => "Data Element Alternative",
+        8 => "URL",
+        _ => "Reserved",
+    }
+}
+
+/// Names an attribute ID. IDs from 0x0200 up mean different things in the
+/// records of different service classes, and the language based ones
+/// assume the primary language base of 0x0100.
+pub(super) fn get_attribute_id_name(id: u16, service_class: Option<u16>) -> &'static str {
+    match (id, service_class.unwrap_or_default()) {
+        (0x0000, _) => "ServiceRecordHandle",
+        (0x0001, _) => "ServiceClassIDList",
+        (0x0002, _) => "ServiceRecordState",
+        (0x0003, _) => "ServiceID",
+        (0x0004, _) => "ProtocolDescriptorList",
+        (0x0005, _) => "BrowseGroupList",
+        (0x0006, _) => "LanguageBaseAttributeIDList",
+        (0x0007, _) => "ServiceInfoTimeToLive",
+        (0x0008, _) => "ServiceAvailability",
+        (0x0009, _) => "BluetoothProfileDescriptorList",
+        (0x000a, _) => "DocumentationURL",
+        (0x000b, _) => "ClientExecutableURL",
+        (0x000c, _) => "IconURL",
+        (0x000d, _) => "AdditionalProtocolDescriptorLists",
+        (0x0100, _) => "ServiceName",
+        (0x0101, _) => "ServiceDescription",
+        (0x0102, _) => "ProviderName",
+        (0x0200, 0x1000) => "VersionNumberList",
+        (0x0201, 0x1000) => "ServiceDatabaseState",
+        (0x0200, 0x1001) => "GroupID",
+        (0x0200, 0x1200) => "SpecificationID",
+        (0x0201, 0x1200) => "VendorID",
+        (0x0202, 0x1200) => "ProductID",
+        (0x0203, 0x1200) => "Version",
+        (0x0204, 0x1200) => "PrimaryRecord",
+        (0x0205, 0x1200) => "VendorIDSource",
+        (0x0200, 0x1124) => "HIDDeviceReleaseNumber",
+        (0x0201, 0x1124) => "HIDParserVersion",
+        (0x0202, 0x1124) => "HIDDeviceSubclass",
+        (0x0203, 0x1124) => "HIDCountryCode",
+        (0x0204, 0x1124) => "HIDVirtualCable",
+        (0x0205, 0x1124) => "HIDReconnectInitiate",
+        (0x0206, 0x1124) => "HIDDescriptorList",
+        (0x0207, 0x1124) => "HIDLANGIDBaseList",
+        (0x0208, 0x1124) => "HIDSDPDisable",
+        (0x0209, 0x1124) => "HIDBatteryPower",
+        (0x020a, 0x1124) => "HIDRemoteWake",
+        (0x020b, 0x1124) => "HIDProfileVersion",
+        (0x020c, 0x1124) => "HIDSupervisionTimeout",
+        (0x020d, 0x1124) => "HIDNormallyConnectable",
+        (0x020e, 0x1124) => "HIDBootDevice",
+        (0x020f, 0x1124) => "HIDSSRHostMaxLatency",
+        (0x0210, 0x1124) => "HIDSSRHostMinTimeout",
+        (0x0200, 0x1105 | 0x1106 | 0x112f | 0x1132 | 0x1133) => "GoepL2capPsm",
+        (0x0301, 0x111f) => "Network",
+        (0x0302, 0x1108 | 0x1131) => "RemoteAudioVolumeControl",
+        (0x0303, 0x1105) => "SupportedFormatsList",
+        (0x030a, 0x1115..=0x1117) => "SecurityDescription",
+        (0x030b, 0x1116 | 0x1117) => "NetAccessType",
+        (0x030c, 0x1116 | 0x1117) => "MaxNetAccessrate",
+        (0x0311, _) => "SupportedFeatures",
+        (0x0314, 0x112f) => "SupportedRepositories",
+        (0x0315, 0x1132) => "MASInstanceID",
+        (0x0316, 0x1132) => "SupportedMessageTypes",
+        (0x0317, 0x112f) => "PbapSupportedFeatures",
+        (0x0317, 0x1132 | 0x1133) => "MapSupportedFeatures",
+        _ => "",
+    }
+}
@@ -0,0 +1,210 @@
+use super::data_element::{DataElement, DataElementValue};
+
+use crate::assigned_numbers::get_uuid16_name;
+use crate::hid::ReportDescriptor;
+
+/// What a service record tells about a service: what it is and where to
+/// reach it.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ServiceRecord {
+    pub(super) handle: Option<u32>,
+    classes: Vec<u16>,
+    name: Option<String>,
+    rfcomm_channel: Option<u8>,
+    psm: Option<u16>,
+    goep_psm: Option<u16>,
+    profiles: Vec<(u16, u16)>,
+    supported_features: Option<u64>,
+    hid_descriptor: Option<Vec<u8>>,
+}
+
+impl ServiceRecord {
+    /// Picks the service out of a complete AttributeList.
+    pub(super) fn new(list: &DataElement) -> Self {
+        let mut record = ServiceRecord {
+            handle: list
+                .attribute(0x0000)
+                .and_then(|handle| handle.as_uint())
+                .map(|handle| handle as u32),
+            classes: list
+                .attribute(0x0001)
+                .map(|classes| {
+                    classes
+                        .elements()
+                        .iter()
+                        .filter_map(|class| class.as_uuid16())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            name: list.attribute(0x0100).and_then(|name| name.as_text()),
+            supported_features: list
+                .attribute(0x0311)
+                .and_then(|features| features.as_uint()),
+            ..Default::default()
+        };
+        // the first of the alternatives is the one the rest build on
+        let descriptors = match list.attribute(0x0004) {
+            Some(
+                alternatives @ DataElement {
+                    value: DataElementValue::Alternative(_),
+                    ..
+                },
+            ) => alternatives
+                .elements()
+                .first()
+                .map(|descriptors| descriptors.elements())
+                .unwrap_or_default(),
+            Some(descriptors) => descriptors.elements(),
+            None => &[],
+        };
+        for descriptor in descriptors {
+            let parts = descriptor.elements();
+            let parameter = parts.get(1).and_then(|parameter| parameter.as_uint());
+            match (parts.first().and_then(|uuid| uuid.as_uuid16()), parameter) {
+                (Some(0x0100), Some(psm)) => record.psm = Some(psm as u16),
+                (Some(0x0003), Some(channel)) => record.rfcomm_channel = Some(channel as u8),
+                _ => {}
+            }
+        }
+        if let Some(profiles) = list.attribute(0x0009) {
+            record.profiles = profiles
+                .elements()
+                .iter()
+                .filter_map(|profile| {
+                    let parts = profile.elements();
+                    Some((parts.first()?.as_uuid16()?, parts.get(1)?.as_uint()? as u16))
+                })
+                .collect();
+        }
+        // OBEX based profiles can run straight over L2CAP
+        if record
+            .classes
+            .iter()
+            .any(|class| matches!(class, 0x1105 | 0x1106 | 0x112f | 0x1132 | 0x1133))
+        {
+            record.goep_psm = list
+                .attribute(0x0200)
+                .and_then(|psm| psm.as_uint())
+                .map(|psm| psm as u16);
+        }
+        // the HIDDescriptorList holds (class, descriptor) pairs, 0x22 is
+        // the report descriptor
+        if record.classes.contains(&0x1124) {
+            record.hid_descriptor = list.attribute(0x0206).and_then(|descriptors| {
+                descriptors
+                    .elements()
+                    .iter()
+                    .find_map(|descriptor| match descriptor.elements() {
+                        [class, report_descriptor] if class.as_uint() == Some(0x22) => {
+                            report_descriptor.as_bytes().map(|bytes| bytes.to_vec())
+                        }
+                        _ => None,
+                    })
+            });
+        }
+        record
+    }
+
+    /// The report descriptor of a HID service.
+    pub(super) fn hid_descriptor(&self) -> Option<&[u8]> {
+        self.hid_descriptor.as_deref()
+    }
+
+    /// The profile of the service, when it is reached on RFCOMM `channel`.
+    pub(super) fn rfcomm_profile(&self, channel: u8) -> Option<u16> {
+        match self.rfcomm_channel == Some(channel) {
+            true => self.profile(),
+            false => None,
+        }
+    }
+
+    /// The profile of the service, when it is reached over L2CAP on `psm`
+    /// with GOEP 2.0.
+    pub(super) fn goep_profile(&self, psm: u16) -> Option<u16> {
+        match self.goep_psm == Some(psm) {
+            true => self.profile(),
+            false => None,
+        }
+    }
+
+    fn profile(&self) -> Option<u16> {
+        self.profiles
+            .first()
+            .map(|(profile, _)| *profile)
+            .or(self.classes.first().copied())
+    }
+
+    /// Reads like "Handsfree v1.8 on RFCOMM 3".
+    fn description(&self) -> String {
+        let mut description = match self.profiles.is_empty() {
+            true => self
+                .classes
+                .first()
+                .map(|class| uuid16_to_string(*class))
+                .unwrap_or("Unknown service".to_string()),
+            false => self
+                .profiles
+                .iter()
+                .map(|(profile, version)| {
+                    format!(
+                        "{} v{}.{}",
+                        uuid16_to_string(*profile),
+                        version >> 8,
+                        version & 0xff
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(", "),
+        };
+        if let Some(channel) = self.rfcomm_channel {
+            description.push_str(&format!(" on RFCOMM {}", channel));
+        } else if let Some(psm) = self.goep_psm.or(self.psm) {
+            description.push_str(&format!(" on PSM {:#06x}", psm));
+        }
+        description
+    }
+
+    pub(super) fn summary(&self) -> String {
+        let mut fields = vec![format!(r#""Description": "{}""#, self.description())];
+        if let Some(handle) = self.handle {
+            fields.push(format!(r#""Service Record Handle": "{:#010x}""#, handle));
+        }
+        if let Some(name) = &self.name {
+            fields.push(format!(r#""Service Name": "{}""#, name));
+        }
+        fields.push(format!(
+            r#""Service Classes": "{}""#,
+            self.classes
+                .iter()
+                .map(|class| uuid16_to_string(*class))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ));
+        if let Some(channel) = self.rfcomm_channel {
+            fields.push(format!(r#""RFCOMM Channel": {}"#, channel));
+        }
+        if let Some(psm) = self.psm {
+            fields.push(format!(r#""L2CAP PSM": "{:#06x}""#, psm));
+        }
+        if let Some(psm) = self.goep_psm {
+            fields.push(format!(r#""GOEP L2CAP PSM": "{:#06x}""#, psm));
+        }
+        if let Some(features) = self.supported_features {
+            fields.push(format!(r#""Supported Features": "{:#06x}""#, features));
+        }
+        if let Some(descriptor) = &self.hid_descriptor {
+            fields.push(format!(
+                r#""HID Reports": {{{}}}"#,
+                ReportDescriptor::new(descriptor).summary()
+            ));
+        }
+        fields.join(", ")
+    }
+}
+
+fn uuid16_to_string(uuid: u16) -> String {
+    match get_uuid16_name(uuid) {
+        "" => format!("{:#06x}", uuid),
+        name => name.to_string(),
+    }
+}
@@ -0,0 +1,547 @@
+use crate::hci::{AssociationModel, BdAddr};
+use crate::HostStack;
+use crate::ParseNodeOptA;
+use crate::SummaryKeys;
+
+use crate::ParseBitsNode;
+use crate::ParseBytesNode;
+
+#[derive(Default, Debug)]
+pub struct SMPArg {
+    pairings: Vec<SmpPairing>,
+    // state of the connections that have ended, kept for the summary
+    closed: Vec<SmpPairing>,
+}
+
+impl SMPArg {
+    /// Returns the pairing state of `acl_handle`, creating it on first use.
+    fn pairing(&mut self, acl_handle: u16) -> &mut SmpPairing {
+        let index = match self
+            .pairings
+            .iter()
+            .position(|pairing| pairing.acl_handle == acl_handle)
+        {
+            Some(index) => index,
+            None => {
+                self.pairings.push(SmpPairing::new(acl_handle));
+                self.pairings.len() - 1
+            }
+        };
+        &mut self.pairings[index]
+    }
+
+    /// Ends the pairing of `acl_handle`, a new connection on the handle
+    /// pairs afresh.
+    pub(crate) fn disconnect(&mut self, acl_handle: u16) {
+        let (ended, open): (Vec<SmpPairing>, Vec<SmpPairing>) = std::mem::take(&mut self.pairings)
+            .into_iter()
+            .partition(|pairing| pairing.acl_handle == acl_handle);
+        self.pairings = open;
+        self.closed.extend(ended);
+    }
+
+    pub(crate) fn pairing_summary(&self) -> String {
+        let mut keys = SummaryKeys::default();
+        self.closed
+            .iter()
+            .chain(self.pairings.iter())
+            .map(|pairing| pairing.summary(&mut keys))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+/// The features one side announced in its Pairing Request or Response.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PairingFeatures {
+    io_capability: u8,
+    oob_data_flag: u8,
+    auth_req: u8,
+    max_key_size: u8,
+    initiator_key_distribution: u8,
+    responder_key_distribution: u8,
+}
+
+/// What has been seen of the SMP pairing on one connection. A new Pairing
+/// Request starts the pairing over.
+#[derive(Debug)]
+struct SmpPairing {
+    acl_handle: u16,
+    br_edr: bool,
+    request: Option<PairingFeatures>,
+    response: Option<PairingFeatures>,
+    keys: Vec<&'static str>,
+    identity_address: Option<(u8, BdAddr)>,
+    failed: Option<u8>,
+}
+
+impl SmpPairing {
+    fn new(acl_handle: u16) -> Self {
+        SmpPairing {
+            acl_handle,
+            br_edr: false,
+            request: None,
+            response: None,
+            keys: Vec::new(),
+            identity_address: None,
+            failed: None,
+        }
+    }
+
+    fn update(&mut self, code: u8, pdu: &SmpPdu) {
+        match pdu {
+            SmpPdu::PairingFeatures(features) if code == 0x01 => {
+                *self = SmpPairing {
+                    br_edr: self.br_edr,
+                    request: Some(*features),
+                    ..SmpPairing::new(self.acl_handle)
+                };
+            }
+            SmpPdu::PairingFeatures(features) => self.response = Some(*features),
+            SmpPdu::Reason(reason) if code == 0x05 => self.failed = Some(*reason),
+            SmpPdu::IdentityAddress { addr_type, bd_addr } => {
+                self.identity_address = Some((*addr_type, *bd_addr))
+            }
+            _ => {}
+        }
+        let key = match code {
+            0x06 => "LTK",
+            0x07 => "EDIV/Rand",
+            0x08 => "IRK",
+            0x09 => "Identity Address",
+            0x0a => "CSRK",
+            _ => return,
+        };
+        if !self.keys.contains(&key) {
+            self.keys.push(key);
+        }
+    }
+
+    fn is_secure_connections(&self) -> Option<bool> {
+        let (request, response) = (self.request?, self.response?);
+        Some(request.auth_req & response.auth_req & 0x08 != 0)
+    }
+
+    /// Association model picked from the IO capability mapping table of the
+    /// Core spec (Vol 3, Part H, 2.3.5.1).
+    fn association_model(&self) -> Option<AssociationModel> {
+        let (request, response) = (self.request?, self.response?);
+        let secure_connections = self.is_secure_connections()?;
+        let oob = match secure_connections {
+            true => request.oob_data_flag != 0 || response.oob_data_flag != 0,
+            false => request.oob_data_flag != 0 && response.oob_data_flag != 0,
+        };
+        if oob {
+            return Some(AssociationModel::OutOfBand);
+        }
+        if (request.auth_req | response.auth_req) & 0x04 == 0 {
+            return Some(AssociationModel::JustWorks);
+        }
+        let model = match (request.io_capability, response.io_capability) {
+            (0x03, _) | (_, 0x03) => AssociationModel::JustWorks,
+            (0x05.., _) | (_, 0x05..) => AssociationModel::JustWorks,
+            (0x01, 0x01) if secure_connections => AssociationModel::NumericComparison,
+            (0x00 | 0x01, 0x00 | 0x01) => AssociationModel::JustWorks,
+            (0x01 | 0x04, 0x01 | 0x04) if secure_connections => AssociationModel::NumericComparison,
+            _ => AssociationModel::PasskeyEntry,
+        };
+        Some(model)
+    }
+
+    fn summary(&self, keys: &mut SummaryKeys) -> String {
+        let mut fields = vec![format!(
+            r#""Transport": "{}""#,
+            match self.br_edr {
+                true => "BR/EDR",
+                false => "LE",
+            }
+        )];
+        if let Some(secure_connections) = self.is_secure_connections() {
+            fields.push(format!(r#""Secure Connections": {}"#, secure_connections));
+        }
+        if !self.br_edr {
+            let model_s = self
+                .association_model()
+                .map(|model| model.get_name())
+                .unwrap_or("Unknown");
+            fields.push(format!(r#""Association Model": "{}""#, model_s));
+        }
+        if let (Some(request), Some(response)) = (self.request, self.response) {
+            fields.push(format!(
+                r#""Bonding": {}"#,
+                request.auth_req & response.auth_req & 0x03 == 0x01
+            ));
+            fields.push(format!(
+                r#""Encryption Key Size": {}"#,
+                request.max_key_size.min(response.max_key_size)
+            ));
+        }
+        fields.push(format!(r#""Keys Distributed": "{}""#, self.keys.join(", ")));
+        if let Some((addr_type, bd_addr)) = self.identity_address {
+            fields.push(format!(
+                r#""Identity Address": "{} ({})""#,
+                bd_addr,
+                get_addr_type_name(addr_type)
+            ));
+        }
+        let status_s = match self.failed {
+            Some(reason) => get_pairing_failed_reason_name(reason),
+            None if !self.keys.is_empty() => "Keys Distributed",
+            None => "Pending",
+        };
+        fields.push(format!(r#""Status": "{}""#, status_s));
+        let key = keys.unique(format!("Connection {:#x}", self.acl_handle));
+        format!(r#""{}": {{{}}}"#, key, fields.join(", "))
+    }
+}
+
+/// A Security Manager Protocol command, carried on the LE fixed channel
+/// 0x0006 or the BR/EDR fixed channel 0x0007.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq)]
+pub struct SMP {
+    code: u8,
+    pdu: SmpPdu,
+}
+
+impl ParseNodeOptA<u16> for SMP {
+    fn new(data: &[u8], args: Option<&mut HostStack>, cid: u16) -> Option<Self> {
+        if data.is_empty() {
+            return None;
+        }
+        let code = data[0];
+        let pdu = SmpPdu::new(&data[1..], code);
+        if let Some(args) = args {
+            let pairing = args.smp_arg.pairing(args.hci_arg.acl_handle());
+            pairing.br_edr = cid == 0x0007;
+            pairing.update(code, &pdu);
+        }
+        Some(SMP { code, pdu })
+    }
+    fn as_json(&self, start_byte: u16) -> String {
+        let code_s = ParseBytesNode::new(start_byte, 1).format(
+            "Code",
+            self.code,
+            get_smp_code_name(self.code),
+            "",
+        );
+        let pdu_s = self.pdu.as_json(self.code, start_byte + 1);
+        match pdu_s.is_empty() {
+            true => format!(r#""SMP": {{{}}}"#, code_s),
+            false => format!(r#""SMP": {{{}, {}}}"#, code_s, pdu_s),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum SmpPdu {
+    Empty,
+    PairingFeatures(PairingFeatures),
+    // confirm, random, DHKey check values and the distributed keys
+    Value(Vec<u8>),
+    Reason(u8),
+    AuthReq(u8),
+    CentralIdentification { ediv: u16, rand: u64 },
+    IdentityAddress { addr_type: u8, bd_addr: BdAddr },
+    PublicKey { x: Vec<u8>, y: Vec<u8> },
+}
+
+impl SmpPdu {
+    fn new(data: &[u8], code: u8) -> Self {
+        let pdu = match code {
+            0x01 | 0x02 if data.len() >= 6 => Some(SmpPdu::PairingFeatures(PairingFeatures {
+                io_capability: data[0],
+                oob_data_flag: data[1],
+                auth_req: data[2],
+                max_key_size: data[3],
+                initiator_key_distribution: data[4],
+                responder_key_distribution: data[5],
+            })),
+            0x03 | 0x04 | 0x06 | 0x08 | 0x0a | 0x0d if data.len() >= 16 => {
+                Some(SmpPdu::Value(data[..16].to_vec()))
+            }
+            0x05 | 0x0e if !data.is_empty() => Some(SmpPdu::Reason(data[0])),
+            0x0b if !data.is_empty() => Some(SmpPdu::AuthReq(data[0])),
+            0x07 if data.len() >= 10 => Some(SmpPdu::CentralIdentification {
+                ediv: u16::from_le_bytes([data[0], data[1]]),
+                rand: u64::from_le_bytes(data[2..10].try_into().unwrap()),
+            }),
+            0x09 if data.len() >= 7 => Some(SmpPdu::IdentityAddress {
+                addr_type: data[0],
+                bd_addr: BdAddr::new(&data[1..]),
+            }),
+            0x0c if data.len() >= 64 => Some(SmpPdu::PublicKey {
+                x: data[..32].to_vec(),
+                y: data[32..64].to_vec(),
+            }),
+            _ => None,
+        };
+        pdu.unwrap_or(SmpPdu::Empty)
+    }
+
+    fn as_json(&self, code: u8, start_byte: u16) -> String {
+        match self {
+            SmpPdu::Empty => String::new(),
+            SmpPdu::PairingFeatures(features) => {
+                let io_capability_s = ParseBytesNode::new(start_byte, 1).format(
+                    "IO Capability",
+                    features.io_capability,
+                    get_io_capability_name(features.io_capability),
+                    "",
+                );
+                let oob_data_flag_s = ParseBytesNode::new(start_byte + 1, 1).format(
+                    "OOB Data Flag",
+                    features.oob_data_flag,
+                    get_oob_data_flag_name(features.oob_data_flag),
+                    "",
+                );
+                let auth_req_s = auth_req_as_json(features.auth_req, start_byte + 2);
+                let max_key_size_s = ParseBytesNode::new(start_byte + 3, 1).format(
+                    "Maximum Encryption Key Size",
+                    features.max_key_size,
+                    "",
+                    match features.max_key_size {
+                        7..=16 => "",
+                        _ => "Key size out of range 7 to 16",
+                    },
+                );
+                let initiator_s = key_distribution_as_json(
+                    "Initiator Key Distribution",
+                    features.initiator_key_distribution,
+                    start_byte + 4,
+                );
+                let responder_s = key_distribution_as_json(
+                    "Responder Key Distribution",
+                    features.responder_key_distribution,
+                    start_byte + 5,
+                );
+                format!(
+                    "{}, {}, {}, {}, {}, {}",
+                    io_capability_s,
+                    oob_data_flag_s,
+                    auth_req_s,
+                    max_key_size_s,
+                    initiator_s,
+                    responder_s
+                )
+            }
+            SmpPdu::Value(value) => {
+                let key = match code {
+                    0x03 => "Confirm Value",
+                    0x04 => "Random Value",
+                    0x06 => "Long Term Key",
+                    0x08 => "Identity Resolving Key",
+                    0x0a => "Signature Key",
+                    _ => "DHKey Check",
+                };
+                ParseBytesNode::new(start_byte, 16).format(key, &value[..], "", "")
+            }
+            SmpPdu::Reason(reason) => match code {
+                0x05 => ParseBytesNode::new(start_byte, 1).format(
+                    "Reason",
+                    reason,
+                    get_pairing_failed_reason_name(*reason),
+                    "",
+                ),
+                _ => ParseBytesNode::new(start_byte, 1).format(
+                    "Notification Type",
+                    reason,
+                    get_keypress_notification_name(*reason),
+                    "",
+                ),
+            },
+            SmpPdu::AuthReq(auth_req) => auth_req_as_json(*auth_req, start_byte),
+            SmpPdu::CentralIdentification { ediv, rand } => format!(
+                "{}, {}",
+                ParseBytesNode::new(start_byte, 2).format("EDIV", ediv, "", ""),
+                ParseBytesNode::new(start_byte + 2, 8).format("Rand", rand, "", "")
+            ),
+            SmpPdu::IdentityAddress { addr_type, bd_addr } => format!(
+                "{}, {}",
+                ParseBytesNode::new(start_byte, 1).format(
+                    "AddrType",
+                    addr_type,
+                    get_addr_type_name(*addr_type),
+                    ""
+                ),
+                ParseBytesNode::new(start_byte + 1, 6).format("BD_ADDR", *bd_addr, "", "")
+            ),
+            SmpPdu::PublicKey { x, y } => format!(
+                "{}, {}",
+                ParseBytesNode::new(start_byte, 32).format("Public Key X", &x[..], "", ""),
+                ParseBytesNode::new(start_byte + 32, 32).format("Public Key Y", &y[..], "", "")
+            ),
+        }
+    }
+}
+
+fn auth_req_as_json(auth_req: u8, start_byte: u16) -> String {
+    let bonding_flags = auth_req & 0x03;
+    let mut bits = vec![ParseBitsNode::new(start_byte, 1, 0, 2).format(
+        "Bonding_Flags",
+        bonding_flags,
+        match bonding_flags {
+            0x00 => "No Bonding",
+            0x01 => "Bonding",
+            _ => "Reserved for future use",
+        },
+        "",
+    )];
+    let names = [(2, "MITM"), (3, "SC"), (4, "Keypress"), (5, "CT2")];
+    bits.extend(names.iter().map(|(bit, name)| {
+        ParseBitsNode::new(start_byte, 1, *bit, 1).format(name, (auth_req >> bit) & 0x01, "", "")
+    }));
+    format!(
+        r#"{}, "AuthReq Bits": {{{}}}"#,
+        ParseBytesNode::new(start_byte, 1).format("AuthReq", auth_req, "", ""),
+        bits.join(", ")
+    )
+}
+
+fn key_distribution_as_json(key: &str, keys: u8, start_byte: u16) -> String {
+    let names = ["EncKey", "IdKey", "SignKey", "LinkKey"];
+    let bits_s = names
+        .iter()
+        .enumerate()
+        .map(|(bit, name)| {
+            ParseBitsNode::new(start_byte, 1, bit as u8, 1).format(
+                name,
+                (keys >> bit) & 0x01,
+                "",
+                "",
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+    format!(
+        r#"{}, "{} Bits": {{{}}}"#,
+        ParseBytesNode::new(start_byte, 1).format(key, keys, "", ""),
+        key,
+        bits_s
+    )
+}
+
+fn get_smp_code_name(code: u8) -> &'static str {
+    match code {
+        0x01 => "Pairing Request",
+        0x02 => "Pairing Response",
+        0x03 => "Pairing Confirm",
+        0x04 => "Pairing Random",
+        0x05 => "Pairing Failed",
+        0x06 => "Encryption Information",
+        0x07 => "Central Identification",
+        0x08 => "Identity Information",
+        0x09 => "Identity Address Information",
+        0x0a => "Signing Information",
+        0x0b => "Security Request",
+        0x0c => "Pairing Public Key",
+        0x0d => "Pairing DHKey Check",
+        0x0e => "Pairing Keypress Notification",
+        _ => "Reserved for future use",
+    }
+}
+
+fn get_io_capability_name(io_capability: u8) -> &'static str {
+    match io_capability {
+        0x00 => "DisplayOnly",
+        0x01 => "DisplayYesNo",
+        0x02 => "KeyboardOnly",
+        0x03 => "NoInputNoOutput",
+        0x04 => "KeyboardDisplay",
+        _ => "Reserved for future use",
+    }
+}
+
+fn get_oob_data_flag_name(oob_data_flag: u8) -> &'static str {
+    match oob_data_flag {
+        0x00 => "OOB Authentication data not present",
+        0x01 => "OOB Authentication data from remote device present",
+        _ => "Reserved for future use",
+    }
+}
+
+fn get_pairing_failed_reason_name(reason: u8) -> &'static str {
+    match reason {
+        0x01 => "Passkey Entry Failed",
+        0x02 => "OOB Not Available",
+        0x03 => "Authentication Requirements",
+        0x04 => "Confirm Value Failed",
+        0x05 => "Pairing Not Supported",
+        0x06 => "Encryption Key Size",
+        0x07 => "Command Not Supported",
+        0x08 => "Unspecified Reason",
+        0x09 => "Repeated Attempts",
+        0x0a => "Invalid Parameters",
+        0x0b => "DHKey Check Failed",
+        0x0c => "Numeric Comparison Failed",
+        0x0d => "BR/EDR pairing in progress",
+        0x0e => "Cross-transport Key Derivation/Generation not allowed",
+        0x0f => "Key Rejected",
+        0x10 => "Busy",
+        _ => "Reserved for future use",
+    }
+}
+
+fn get_keypress_notification_name(notification_type: u8) -> &'static str {
+    match notification_type {
+        0x00 => "Passkey entry started",
+        0x01 => "Passkey digit entered",
+        0x02 => "Passkey digit erased",
+        0x03 => "Passkey cleared",
+        0x04 => "Passkey entry completed",
+        _ => "Reserved for future use",
+    }
+}
+
+fn get_addr_type_name(addr_type: u8) -> &'static str {
+    match addr_type {
+        0x00 => "Public Device Address",
+        0x01 => "Static Random Device Address",
+        _ => "Reserved for future use",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::parse_packets;
+
+    // LE Secure Connections pairing on handle 0x40 that distributes the
+    // identity keys, then a legacy pairing on handle 0x41 that fails, and a
+    // Pairing Request too short for its features
+    const PACKETS: [&str; 10] = [
+        "02 40 20 0b 00 07 00 06 00 01 04 00 2d 10 0d 0f",
+        "02 40 20 0b 00 07 00 06 00 02 01 00 0d 10 0c 0e",
+        "02 40 20 45 00 41 00 06 00 0c 00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f 10 11 12 13 14 15 16 17 18 19 1a 1b 1c 1d 1e 1f 20 21 22 23 24 25 26 27 28 29 2a 2b 2c 2d 2e 2f 30 31 32 33 34 35 36 37 38 39 3a 3b 3c 3d 3e 3f",
+        "02 40 20 15 00 11 00 06 00 08 10 11 12 13 14 15 16 17 18 19 1a 1b 1c 1d 1e 1f",
+        "02 40 20 0c 00 08 00 06 00 09 01 c1 c2 c3 c4 c5 c6",
+        "02 41 20 0b 00 07 00 06 00 01 00 00 05 10 01 01",
+        "02 41 20 0b 00 07 00 06 00 02 02 00 05 07 01 01",
+        "02 41 20 0f 00 0b 00 06 00 07 34 12 01 02 03 04 05 06 07 08",
+        "02 41 20 06 00 02 00 06 00 05 04",
+        "02 41 20 07 00 03 00 06 00 01 03 00",
+    ];
+
+    #[test]
+    fn pdus_are_decoded() {
+        let (json, _) = parse_packets(&PACKETS);
+        assert!(json[0].contains(r#""SMP": {"Code": [0x1, "Pairing Request", "B(9, 1)", ""], "IO Capability": [0x4, "KeyboardDisplay", "B(10, 1)", ""], "OOB Data Flag": [0x0, "OOB Authentication data not present", "B(11, 1)", ""], "AuthReq": [0x2d, "", "B(12, 1)", ""], "AuthReq Bits": {"Bonding_Flags": [0x1, "Bonding", "B(12, 1), b(0, 2)", ""], "MITM": [0x1, "", "B(12, 1), b(2, 1)", ""], "SC": [0x1, "", "B(12, 1), b(3, 1)", ""]"#));
+        assert!(json[0].contains(r#""Maximum Encryption Key Size": [0x10, "", "B(13, 1)", ""], "Initiator Key Distribution": [0xd, "", "B(14, 1)", ""]"#));
+        assert!(json[2].contains(r#""Public Key Y": ["20 21 22 23 24 25 26 27 28 29 2a 2b 2c 2d 2e 2f 30 31 32 33 34 35 36 37 38 39 3a 3b 3c 3d 3e 3f", "", "B(42, 32)", ""]"#));
+        assert!(json[4].contains(r#""AddrType": [0x1, "Static Random Device Address", "B(10, 1)", ""], "BD_ADDR": ["C6:C5:C4:C3:C2:C1", "", "B(11, 6)", ""]"#));
+        assert!(json[7].contains(r#""EDIV": [0x1234, "", "B(10, 2)", ""], "Rand": [0x807060504030201, "", "B(12, 8)", ""]"#));
+        assert!(json[8].contains(r#""Reason": [0x4, "Confirm Value Failed", "B(10, 1)", ""]"#));
+        assert!(json[9].ends_with(r#""SMP": {"Code": [0x1, "Pairing Request", "B(9, 1)", ""]}}"#));
+    }
+
+    #[test]
+    fn summary_tells_the_association_model() {
+        let (_, args) = parse_packets(&PACKETS);
+        assert_eq!(
+            args.pairing_summary(),
+            concat!(
+                r#"{"Connection 0x40": {"Transport": "LE", "Secure Connections": true, "Association Model": "Numeric Comparison", "Bonding": true, "Encryption Key Size": 16, "Keys Distributed": "IRK, Identity Address", "Identity Address": "C6:C5:C4:C3:C2:C1 (Static Random Device Address)", "Status": "Keys Distributed"}, "#,
+                r#""Connection 0x41": {"Transport": "LE", "Secure Connections": false, "Association Model": "Passkey Entry", "Bonding": true, "Encryption Key Size": 7, "Keys Distributed": "EDIV/Rand", "Status": "Confirm Value Failed"}}"#
+            )
+        );
+    }
+}